@@ -0,0 +1,157 @@
+//! Log of search queries which returned no results.
+//!
+//! Such queries show which products and organisations people look for but the dataset lacks.
+//! Together with the explicit requests for missing products (see `product_requests`) they help to
+//! decide which data to add next.
+//! The queries are counted per day in the feedback database and kept only for a limited number of
+//! days: each entry carries the time it expires at, after which it's removed by a TTL index
+//! (created by the lab's `import` command) and until then ignored by the listing. Queries are
+//! anonymised before being stored: they are folded to keywords, and queries which might contain
+//! personal data (e-mail addresses, phone numbers) are not stored at all. The log can be exported
+//! in full with the lab's `requests` command.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use sustainity_models::{gtin, keywords};
+
+use crate::{db::Db, errors::BackendError};
+
+/// Maximal length of a stored query in characters.
+const MAX_QUERY_LENGTH: usize = 100;
+
+/// Minimal number of digits in a word (or in consecutive numeric words) for it to be considered
+/// a phone number (if not a GTIN).
+const MIN_PHONE_DIGITS: usize = 6;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Query which returned no results, with the number of times it was made.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ZeroResultQuery {
+    /// Anonymised query.
+    #[serde(rename = "query")]
+    pub query: String,
+
+    /// Number of times the query was made in the retained days.
+    #[serde(rename = "count")]
+    pub count: usize,
+}
+
+/// Records the query, made now and with no results, if the logging is enabled.
+///
+/// The log is not essential for serving the search, so failures to store the query are only
+/// reported.
+pub async fn record(query: &str, db: &Db) {
+    let config = db.search_config();
+    if config.zero_result_days == 0 {
+        return;
+    }
+    let Some(query) = anonymise(query) else {
+        return;
+    };
+
+    let day = today();
+    let expires = expiry(day, config.zero_result_days);
+    let max_queries = config.zero_result_max_queries;
+    if let Err(err) = db.record_zero_result_query(&query, day, expires, max_queries).await {
+        tracing::warn!("Failed to log a query with no results: {err}");
+    }
+}
+
+/// Returns the queries logged in the retained days, the most frequent first.
+///
+/// Returns `None` if the logging is disabled.
+pub async fn queries(
+    offset: usize,
+    limit: usize,
+    db: &Db,
+) -> Result<Option<Vec<ZeroResultQuery>>, BackendError> {
+    let retention_days = db.search_config().zero_result_days;
+    if retention_days == 0 {
+        return Ok(None);
+    }
+    let oldest = oldest_retained(today(), retention_days);
+    Ok(Some(db.get_zero_result_queries(oldest, offset, limit).await?))
+}
+
+/// Returns the oldest day retained on the given day.
+fn oldest_retained(day: u64, retention_days: usize) -> u64 {
+    day.saturating_sub((retention_days as u64).saturating_sub(1))
+}
+
+/// Returns the time (in seconds since the Unix epoch) after which the counts of the given day
+/// are removed from the database.
+fn expiry(day: u64, retention_days: usize) -> u64 {
+    day.saturating_add(retention_days as u64).saturating_mul(SECONDS_PER_DAY)
+}
+
+/// Returns the number of days since the Unix epoch.
+fn today() -> u64 {
+    let since_epoch = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    since_epoch.as_secs() / SECONDS_PER_DAY
+}
+
+/// Checks if the digits might be a phone number.
+fn is_phone_number(word: &str) -> bool {
+    let num_digits = word.chars().filter(char::is_ascii_digit).count();
+    num_digits >= MIN_PHONE_DIGITS && gtin::normalize(word).is_err()
+}
+
+/// Checks if the words might contain personal data.
+///
+/// Phone numbers are often written in groups of digits, so consecutive numeric words are checked
+/// also together.
+fn is_personal(words: &[String]) -> bool {
+    let mut number = String::new();
+    for word in words {
+        if word.contains('@') {
+            return true;
+        }
+        if word.chars().all(|c| c.is_ascii_digit()) {
+            number.push_str(word);
+        } else if is_phone_number(word) || is_phone_number(&std::mem::take(&mut number)) {
+            return true;
+        }
+    }
+    is_phone_number(&number)
+}
+
+/// Folds the query to its keywords.
+///
+/// Returns `None` if the query is empty or might contain personal data.
+fn anonymise(query: &str) -> Option<String> {
+    let words = keywords::tokenize(query);
+    if words.is_empty() || is_personal(&words) {
+        return None;
+    }
+    Some(words.join(" ").chars().take(MAX_QUERY_LENGTH).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn anonymisation() {
+        assert_eq!(anonymise("  Müller  Brot! "), Some("muller brot".to_owned()));
+        assert_eq!(anonymise("08712345000011"), Some("08712345000011".to_owned()));
+        assert_eq!(anonymise("xps 13"), Some("xps 13".to_owned()));
+        assert_eq!(anonymise("john@example.com"), None);
+        assert_eq!(anonymise("call +31 612345678"), None);
+        assert_eq!(anonymise("call +31 6 12 34 56 78 please"), None);
+        assert_eq!(anonymise("+31 6 12 34 56 78"), None);
+        assert_eq!(anonymise("8 712345 000011"), Some("8 712345 000011".to_owned()));
+        assert_eq!(anonymise("iphone 15 128 gb"), Some("iphone 15 128 gb".to_owned()));
+        assert_eq!(anonymise(" . "), None);
+        assert_eq!(anonymise(&"a".repeat(200)).map(|q| q.len()), Some(MAX_QUERY_LENGTH));
+    }
+
+    #[test]
+    fn retention() {
+        assert_eq!(oldest_retained(12, 2), 11);
+        assert_eq!(oldest_retained(12, 1), 12);
+        assert_eq!(oldest_retained(1, 30), 0);
+        assert_eq!(expiry(12, 2), 14 * SECONDS_PER_DAY);
+    }
+}
@@ -0,0 +1,92 @@
+//! Authentication with API keys.
+//!
+//! Clients pass the key in the `X-Api-Key` header. Requests without a key are anonymous, requests
+//! with an unknown key are rejected.
+
+use std::collections::HashMap;
+
+use hyper::{header::HeaderValue, Request};
+
+use crate::models::{ApiKey, Scope};
+
+/// Header carrying the API key.
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Result of authenticating a request.
+#[derive(Debug, Clone, Copy)]
+pub enum Authentication<'a> {
+    /// No key was given.
+    Anonymous,
+
+    /// A known key was given.
+    Key(&'a ApiKey),
+
+    /// An unknown or malformed key was given.
+    Invalid,
+}
+
+impl<'a> Authentication<'a> {
+    /// Returns the API key if the request was authenticated.
+    pub fn key(&self) -> Option<&'a ApiKey> {
+        match self {
+            Self::Key(key) => Some(key),
+            Self::Anonymous | Self::Invalid => None,
+        }
+    }
+
+    pub fn has_scope(&self, scope: Scope) -> bool {
+        self.key().is_some_and(|key| key.has_scope(scope))
+    }
+}
+
+/// Registry of valid API keys.
+#[derive(Debug, Default)]
+pub struct ApiKeys {
+    keys: HashMap<String, ApiKey>,
+}
+
+impl ApiKeys {
+    pub fn new(keys: Vec<ApiKey>) -> Self {
+        Self { keys: keys.into_iter().map(|key| (key.key.clone(), key)).collect() }
+    }
+
+    /// Checks the API key passed with the request.
+    pub fn authenticate<B>(&self, request: &Request<B>) -> Authentication<'_> {
+        match request.headers().get(API_KEY_HEADER).map(HeaderValue::to_str) {
+            None => Authentication::Anonymous,
+            Some(Ok(key)) => {
+                self.keys.get(key).map_or(Authentication::Invalid, Authentication::Key)
+            }
+            Some(Err(_)) => Authentication::Invalid,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn authentication() {
+        let keys = ApiKeys::new(vec![ApiKey {
+            name: "partner".to_owned(),
+            key: "secret".to_owned(),
+            scopes: vec![Scope::Partner],
+        }]);
+        let request = |key: Option<&str>| {
+            let mut builder = Request::builder();
+            if let Some(key) = key {
+                builder = builder.header(API_KEY_HEADER, key);
+            }
+            builder.body(()).unwrap()
+        };
+
+        assert!(matches!(keys.authenticate(&request(None)), Authentication::Anonymous));
+        assert!(matches!(keys.authenticate(&request(Some("wrong"))), Authentication::Invalid));
+
+        let auth = keys.authenticate(&request(Some("secret")));
+        assert_eq!(auth.key().map(|key| key.name.as_str()), Some("partner"));
+        assert!(auth.has_scope(Scope::Partner));
+        assert!(!auth.has_scope(Scope::Admin));
+    }
+}
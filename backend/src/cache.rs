@@ -0,0 +1,204 @@
+//! In-process cache for responses of the hot endpoints.
+//!
+//! The data changes only when a new dataset is imported, so the cached entries can be served until
+//! they expire or the cache is cleared on dataset reload.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use sustainity_api::models as api;
+use sustainity_models::store::RelevanceMatrix;
+
+use crate::{config::CacheConfig, models::DetailedSearchResult};
+
+struct Entry<V> {
+    /// Position in the LRU order.
+    tick: u64,
+
+    /// Time of insertion.
+    inserted: Instant,
+
+    /// The cached value.
+    value: V,
+}
+
+struct Inner<V> {
+    /// Cached entries.
+    entries: HashMap<String, Entry<V>>,
+
+    /// Keys of the entries ordered from the least to the most recently used.
+    order: BTreeMap<u64, String>,
+
+    /// Counter used to order the entries.
+    tick: u64,
+}
+
+impl<V> Inner<V> {
+    fn new() -> Self {
+        Self { entries: HashMap::new(), order: BTreeMap::new(), tick: 0 }
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    fn remove(&mut self, key: &str) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.order.remove(&entry.tick);
+        }
+    }
+
+    fn evict_oldest(&mut self) {
+        if let Some((_, key)) = self.order.pop_first() {
+            self.entries.remove(&key);
+        }
+    }
+}
+
+/// LRU cache with a limited time-to-live of the entries.
+pub struct Cache<V> {
+    inner: Mutex<Inner<V>>,
+    capacity: usize,
+    ttl: Duration,
+}
+
+impl<V: Clone> Cache<V> {
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self { inner: Mutex::new(Inner::new()), capacity, ttl }
+    }
+
+    /// Returns the value if it's present and did not expire yet.
+    pub fn get(&self, key: &str) -> Option<V> {
+        let mut inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        let tick = inner.next_tick();
+        let entry = inner.entries.get_mut(key)?;
+        if entry.inserted.elapsed() >= self.ttl {
+            inner.remove(key);
+            return None;
+        }
+
+        let old_tick = std::mem::replace(&mut entry.tick, tick);
+        let value = entry.value.clone();
+        inner.order.remove(&old_tick);
+        inner.order.insert(tick, key.to_owned());
+        Some(value)
+    }
+
+    /// Inserts the value evicting the least recently used entry if the cache is full.
+    pub fn insert(&self, key: String, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        inner.remove(&key);
+        while inner.entries.len() >= self.capacity {
+            inner.evict_oldest();
+        }
+
+        let tick = inner.next_tick();
+        inner.order.insert(tick, key.clone());
+        inner.entries.insert(key, Entry { tick, inserted: Instant::now(), value });
+    }
+
+    /// Removes all the entries.
+    pub fn clear(&self) {
+        let mut inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        inner.entries.clear();
+        inner.order.clear();
+    }
+}
+
+/// Caches for all the cached endpoints.
+pub struct Caches {
+    /// Library contents by the preferred languages.
+    pub library: Cache<Vec<api::LibraryItemShort>>,
+
+    /// Full product data (with alternatives) by ID variant, ID and region.
+    pub products: Cache<Option<api::ProductFull>>,
+
+    /// Text search results by query.
+    pub search: Cache<Vec<DetailedSearchResult>>,
+
+    /// Relevance of certifications for product categories (a single entry).
+    pub relevance: Cache<RelevanceMatrix>,
+}
+
+impl Caches {
+    pub fn new(config: &CacheConfig) -> Self {
+        Self {
+            library: Cache::new(config.capacity, config.ttl),
+            products: Cache::new(config.capacity, config.ttl),
+            search: Cache::new(config.capacity, config.ttl),
+            relevance: Cache::new(1, config.ttl),
+        }
+    }
+
+    /// Removes all cached entries, e.g. after the dataset was reloaded.
+    pub fn clear(&self) {
+        self.library.clear();
+        self.products.clear();
+        self.search.clear();
+        self.relevance.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: Duration = Duration::from_secs(3600);
+
+    impl<V> Cache<V> {
+        fn len(&self) -> usize {
+            self.inner.lock().unwrap().entries.len()
+        }
+    }
+
+    #[test]
+    fn least_recently_used_is_evicted() {
+        let cache = Cache::<u32>::new(2, HOUR);
+        cache.insert("a".to_owned(), 1);
+        cache.insert("b".to_owned(), 2);
+        assert_eq!(cache.get("a"), Some(1));
+
+        cache.insert("c".to_owned(), 3);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get("a"), Some(1));
+        assert_eq!(cache.get("b"), None);
+        assert_eq!(cache.get("c"), Some(3));
+    }
+
+    #[test]
+    fn reinsertion_replaces_value() {
+        let cache = Cache::<u32>::new(2, HOUR);
+        cache.insert("a".to_owned(), 1);
+        cache.insert("a".to_owned(), 2);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.get("a"), Some(2));
+    }
+
+    #[test]
+    fn expired_entries_are_not_returned() {
+        let cache = Cache::<u32>::new(2, Duration::ZERO);
+        cache.insert("a".to_owned(), 1);
+        assert_eq!(cache.get("a"), None);
+        assert_eq!(cache.len(), 0);
+    }
+
+    #[test]
+    fn clearing() {
+        let cache = Cache::<u32>::new(2, HOUR);
+        cache.insert("a".to_owned(), 1);
+        cache.clear();
+        assert_eq!(cache.get("a"), None);
+
+        let cache = Cache::<u32>::new(0, HOUR);
+        cache.insert("a".to_owned(), 1);
+        assert_eq!(cache.len(), 0);
+    }
+}
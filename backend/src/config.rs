@@ -1,5 +1,10 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 use snafu::prelude::*;
+use sustainity_models::keywords::StopWords;
+
+use crate::models::{ApiKey, Webhook};
 
 #[derive(Debug, Snafu)]
 #[snafu(visibility(pub(crate)))]
@@ -52,21 +57,452 @@ impl SecretConfig {
     pub fn load_or_default() -> Self {
         match Self::load_from_file() {
             Ok(ok) => {
-                log::info!("Loaded config from a file");
+                tracing::info!("Loaded config from a file");
                 return ok;
             }
-            Err(err) => log::warn!("{err}"),
+            Err(err) => tracing::warn!("{err}"),
         }
 
         match Self::load_from_env() {
             Ok(ok) => {
-                log::info!("Loaded config from an env variable");
+                tracing::info!("Loaded config from an env variable");
                 return ok;
             }
-            Err(err) => log::warn!("{err}"),
+            Err(err) => tracing::warn!("{err}"),
         }
 
-        log::info!("Using default config");
+        tracing::info!("Using default config");
         SecretConfig::new_debug()
     }
 }
+
+/// Loads the API keys.
+///
+/// The keys are read from a JSON file or, if it does not exist, from the `SUSTAINITY_API_KEYS`
+/// environment variable. If neither is available, no keys are configured.
+pub fn load_api_keys() -> Vec<ApiKey> {
+    fn load_from_file() -> Result<Vec<ApiKey>, ConfigError> {
+        const PATH: &str = "/etc/sustainity/secrets/api_keys.json";
+        let data = std::fs::read_to_string(PATH)?;
+        Ok(serde_json::from_str(&data)?)
+    }
+
+    fn load_from_env() -> Result<Vec<ApiKey>, ConfigError> {
+        const ENV: &str = "SUSTAINITY_API_KEYS";
+        let data = std::env::var(ENV)?;
+        Ok(serde_json::from_str(&data)?)
+    }
+
+    for load in [load_from_file, load_from_env] {
+        match load() {
+            Ok(keys) => {
+                tracing::info!("Loaded {} API keys", keys.len());
+                return keys;
+            }
+            Err(err) => tracing::warn!("API keys: {err}"),
+        }
+    }
+
+    tracing::info!("No API keys configured");
+    Vec::new()
+}
+
+/// Loads the webhooks notified about dataset updates.
+///
+/// The webhooks are read from a JSON file or, if it does not exist, from the `SUSTAINITY_WEBHOOKS`
+/// environment variable. If neither is available, no webhooks are configured.
+pub fn load_webhooks() -> Vec<Webhook> {
+    fn load_from_file() -> Result<Vec<Webhook>, ConfigError> {
+        const PATH: &str = "/etc/sustainity/secrets/webhooks.json";
+        let data = std::fs::read_to_string(PATH)?;
+        Ok(serde_json::from_str(&data)?)
+    }
+
+    fn load_from_env() -> Result<Vec<Webhook>, ConfigError> {
+        const ENV: &str = "SUSTAINITY_WEBHOOKS";
+        let data = std::env::var(ENV)?;
+        Ok(serde_json::from_str(&data)?)
+    }
+
+    for load in [load_from_file, load_from_env] {
+        match load() {
+            Ok(hooks) => {
+                tracing::info!("Loaded {} webhooks", hooks.len());
+                return hooks;
+            }
+            Err(err) => tracing::warn!("Webhooks: {err}"),
+        }
+    }
+
+    tracing::info!("No webhooks configured");
+    Vec::new()
+}
+
+/// Loads the dataset snapshots served next to the main dataset.
+///
+/// The snapshots are read from a JSON file mapping snapshot names to dataset configs or, if it does
+/// not exist, from the `SUSTAINITY_SNAPSHOTS` environment variable. If neither is available, only
+/// the main dataset is served.
+pub fn load_snapshots() -> BTreeMap<String, DatasetConfig> {
+    fn load_from_file() -> Result<BTreeMap<String, DatasetConfig>, ConfigError> {
+        const PATH: &str = "/etc/sustainity/snapshots.json";
+        let data = std::fs::read_to_string(PATH)?;
+        Ok(serde_json::from_str(&data)?)
+    }
+
+    fn load_from_env() -> Result<BTreeMap<String, DatasetConfig>, ConfigError> {
+        const ENV: &str = "SUSTAINITY_SNAPSHOTS";
+        let data = std::env::var(ENV)?;
+        Ok(serde_json::from_str(&data)?)
+    }
+
+    for load in [load_from_file, load_from_env] {
+        match load() {
+            Ok(snapshots) => {
+                tracing::info!("Loaded {} dataset snapshots", snapshots.len());
+                return snapshots;
+            }
+            Err(err) => tracing::warn!("Snapshots: {err}"),
+        }
+    }
+
+    tracing::info!("No dataset snapshots configured");
+    BTreeMap::new()
+}
+
+/// Reads and parses an optional environment variable, logging a warning if it cannot be parsed.
+fn load_var<T: std::str::FromStr>(name: &str) -> Option<T> {
+    match std::env::var(name) {
+        Ok(value) => match value.parse() {
+            Ok(value) => Some(value),
+            Err(_) => {
+                tracing::warn!("Failed to parse `{name}` environment variable: '{value}'");
+                None
+            }
+        },
+        Err(_) => None,
+    }
+}
+
+/// Configuration of the response cache.
+#[derive(Clone, Debug)]
+pub struct CacheConfig {
+    /// Maximal number of entries per cached endpoint.
+    pub capacity: usize,
+
+    /// Time after which the entries expire.
+    pub ttl: std::time::Duration,
+}
+
+impl CacheConfig {
+    const DEFAULT_CAPACITY: usize = 10_000;
+    const DEFAULT_TTL_SECS: u64 = 3600;
+
+    /// Loads the config from `SUSTAINITY_CACHE_CAPACITY` and `SUSTAINITY_CACHE_TTL` (in seconds)
+    /// environment variables, using defaults for the missing ones.
+    pub fn load_or_default() -> Self {
+        let capacity = load_var("SUSTAINITY_CACHE_CAPACITY").unwrap_or(Self::DEFAULT_CAPACITY);
+        let ttl = load_var("SUSTAINITY_CACHE_TTL").unwrap_or(Self::DEFAULT_TTL_SECS);
+        tracing::info!("Cache capacity: {capacity}, TTL: {ttl}s");
+        Self { capacity, ttl: std::time::Duration::from_secs(ttl) }
+    }
+}
+
+/// Configuration of the DB queries.
+#[derive(Clone, Debug)]
+pub struct QueryConfig {
+    /// Queries taking longer than this are logged.
+    pub slow_threshold: std::time::Duration,
+}
+
+impl QueryConfig {
+    const DEFAULT_SLOW_THRESHOLD_MS: u64 = 1000;
+
+    /// Loads the config from the `SUSTAINITY_SLOW_QUERY_MS` environment variable (in
+    /// milliseconds), using the default if it is missing.
+    pub fn load_or_default() -> Self {
+        let slow_threshold =
+            load_var("SUSTAINITY_SLOW_QUERY_MS").unwrap_or(Self::DEFAULT_SLOW_THRESHOLD_MS);
+        tracing::info!("Slow query threshold: {slow_threshold}ms");
+        Self { slow_threshold: std::time::Duration::from_millis(slow_threshold) }
+    }
+}
+
+/// Configuration of the database handles and the limit of concurrent queries.
+#[derive(Clone, Debug)]
+pub struct PoolConfig {
+    /// Maximal number of queries running at once.
+    pub max_size: usize,
+
+    /// Time a query waits for a free slot in the pool before failing.
+    pub acquire_timeout: std::time::Duration,
+
+    /// Time after which a running query is abandoned.
+    pub query_timeout: std::time::Duration,
+
+    /// Maximal number of attempts to run a query failing with a transient error.
+    pub max_attempts: u32,
+
+    /// Delay before the first retry (doubled with each next retry).
+    pub initial_backoff: std::time::Duration,
+}
+
+impl PoolConfig {
+    const DEFAULT_MAX_SIZE: usize = 32;
+    const DEFAULT_ACQUIRE_TIMEOUT_MS: u64 = 2000;
+    const DEFAULT_QUERY_TIMEOUT_MS: u64 = 30_000;
+    const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+    const DEFAULT_INITIAL_BACKOFF_MS: u64 = 100;
+
+    /// Loads the config from `SUSTAINITY_DB_POOL_SIZE`, `SUSTAINITY_DB_ACQUIRE_TIMEOUT_MS`,
+    /// `SUSTAINITY_DB_QUERY_TIMEOUT_MS`, `SUSTAINITY_DB_MAX_ATTEMPTS` and
+    /// `SUSTAINITY_DB_BACKOFF_MS` environment variables, using defaults for the missing ones.
+    pub fn load_or_default() -> Self {
+        let default = Self::default();
+        let millis = |name, default: std::time::Duration| {
+            load_var(name).map_or(default, std::time::Duration::from_millis)
+        };
+        let config = Self {
+            max_size: load_var("SUSTAINITY_DB_POOL_SIZE").unwrap_or(default.max_size).max(1),
+            acquire_timeout: millis("SUSTAINITY_DB_ACQUIRE_TIMEOUT_MS", default.acquire_timeout),
+            query_timeout: millis("SUSTAINITY_DB_QUERY_TIMEOUT_MS", default.query_timeout),
+            max_attempts: load_var("SUSTAINITY_DB_MAX_ATTEMPTS")
+                .unwrap_or(default.max_attempts)
+                .max(1),
+            initial_backoff: millis("SUSTAINITY_DB_BACKOFF_MS", default.initial_backoff),
+        };
+        tracing::info!("DB pool: {config:?}");
+        config
+    }
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            max_size: Self::DEFAULT_MAX_SIZE,
+            acquire_timeout: std::time::Duration::from_millis(Self::DEFAULT_ACQUIRE_TIMEOUT_MS),
+            query_timeout: std::time::Duration::from_millis(Self::DEFAULT_QUERY_TIMEOUT_MS),
+            max_attempts: Self::DEFAULT_MAX_ATTEMPTS,
+            initial_backoff: std::time::Duration::from_millis(Self::DEFAULT_INITIAL_BACKOFF_MS),
+        }
+    }
+}
+
+/// Configuration of the rate limiting.
+#[derive(Clone, Debug)]
+pub struct RateLimitConfig {
+    /// Number of requests allowed per minute from a single IP address (zero means no limit).
+    pub per_ip_per_minute: u32,
+
+    /// Number of requests allowed per minute with a single partner API key (zero means no limit).
+    pub per_key_per_minute: u32,
+
+    /// Number of readiness checks allowed per minute from a single IP address (zero means no
+    /// limit).
+    ///
+    /// Readiness checks query the database, so they are limited separately from the API.
+    pub readiness_per_minute: u32,
+
+    /// Identify clients by the `X-Forwarded-For` header set by a reverse proxy.
+    pub trust_proxy: bool,
+}
+
+impl RateLimitConfig {
+    const DEFAULT_PER_IP_PER_MINUTE: u32 = 120;
+    const DEFAULT_PER_KEY_PER_MINUTE: u32 = 600;
+    const DEFAULT_READINESS_PER_MINUTE: u32 = 300;
+
+    /// Loads the config from `SUSTAINITY_RATE_LIMIT_IP`, `SUSTAINITY_RATE_LIMIT_KEY`,
+    /// `SUSTAINITY_RATE_LIMIT_READINESS` (all in requests per minute) and `SUSTAINITY_TRUST_PROXY`
+    /// environment variables, using defaults for the missing ones.
+    pub fn load_or_default() -> Self {
+        let per_ip_per_minute =
+            load_var("SUSTAINITY_RATE_LIMIT_IP").unwrap_or(Self::DEFAULT_PER_IP_PER_MINUTE);
+        let per_key_per_minute =
+            load_var("SUSTAINITY_RATE_LIMIT_KEY").unwrap_or(Self::DEFAULT_PER_KEY_PER_MINUTE);
+        let readiness_per_minute = load_var("SUSTAINITY_RATE_LIMIT_READINESS")
+            .unwrap_or(Self::DEFAULT_READINESS_PER_MINUTE);
+        let trust_proxy = load_var("SUSTAINITY_TRUST_PROXY").unwrap_or(false);
+        tracing::info!(
+            "Rate limits: {per_ip_per_minute}/min per IP, {per_key_per_minute}/min per API key, \
+             {readiness_per_minute}/min readiness checks per IP, trust proxy: {trust_proxy}"
+        );
+        Self { per_ip_per_minute, per_key_per_minute, readiness_per_minute, trust_proxy }
+    }
+}
+
+/// Weights of the quality signals boosting the search results.
+///
+/// The boosts are small compared to the scores of the matches, so that they mostly decide the order
+/// of equally matching results.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchConfig {
+    /// Boost per certification of the entity.
+    pub certification_weight: f64,
+
+    /// Boost multiplied by the Sustainity score (products only).
+    pub score_weight: f64,
+
+    /// Boost for entities with a description.
+    pub description_weight: f64,
+
+    /// Boost for entities with an image.
+    pub image_weight: f64,
+
+    /// Words left out of the queries. Must match the configuration of the lab.
+    pub stop_words: StopWords,
+
+    /// Number of days for which queries with no results are logged (zero disables the logging).
+    pub zero_result_days: usize,
+
+    /// Maximal number of distinct queries with no results logged per day.
+    pub zero_result_max_queries: usize,
+}
+
+impl SearchConfig {
+    const DEFAULT_CERTIFICATION_WEIGHT: f64 = 0.1;
+    const DEFAULT_SCORE_WEIGHT: f64 = 0.2;
+    const DEFAULT_DESCRIPTION_WEIGHT: f64 = 0.05;
+    const DEFAULT_IMAGE_WEIGHT: f64 = 0.05;
+    const DEFAULT_ZERO_RESULT_DAYS: usize = 0;
+    const DEFAULT_ZERO_RESULT_MAX_QUERIES: usize = 10_000;
+
+    /// Loads the config from `SUSTAINITY_SEARCH_CERTIFICATION_WEIGHT`,
+    /// `SUSTAINITY_SEARCH_SCORE_WEIGHT`, `SUSTAINITY_SEARCH_DESCRIPTION_WEIGHT`,
+    /// `SUSTAINITY_SEARCH_IMAGE_WEIGHT`, `SUSTAINITY_SEARCH_STOP_WORDS` (comma-separated
+    /// languages), `SUSTAINITY_SEARCH_ZERO_RESULT_DAYS` and
+    /// `SUSTAINITY_SEARCH_ZERO_RESULT_MAX_QUERIES` environment variables, using defaults for the
+    /// missing ones.
+    pub fn load_or_default() -> Self {
+        let default = Self::default();
+        let config = Self {
+            certification_weight: load_var("SUSTAINITY_SEARCH_CERTIFICATION_WEIGHT")
+                .unwrap_or(default.certification_weight),
+            score_weight: load_var("SUSTAINITY_SEARCH_SCORE_WEIGHT")
+                .unwrap_or(default.score_weight),
+            description_weight: load_var("SUSTAINITY_SEARCH_DESCRIPTION_WEIGHT")
+                .unwrap_or(default.description_weight),
+            image_weight: load_var("SUSTAINITY_SEARCH_IMAGE_WEIGHT")
+                .unwrap_or(default.image_weight),
+            stop_words: load_var("SUSTAINITY_SEARCH_STOP_WORDS").unwrap_or(default.stop_words),
+            zero_result_days: load_var("SUSTAINITY_SEARCH_ZERO_RESULT_DAYS")
+                .unwrap_or(default.zero_result_days),
+            zero_result_max_queries: load_var("SUSTAINITY_SEARCH_ZERO_RESULT_MAX_QUERIES")
+                .unwrap_or(default.zero_result_max_queries),
+        };
+        tracing::info!("Search config: {config:?}");
+        config
+    }
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            certification_weight: Self::DEFAULT_CERTIFICATION_WEIGHT,
+            score_weight: Self::DEFAULT_SCORE_WEIGHT,
+            description_weight: Self::DEFAULT_DESCRIPTION_WEIGHT,
+            image_weight: Self::DEFAULT_IMAGE_WEIGHT,
+            stop_words: StopWords::default(),
+            zero_result_days: Self::DEFAULT_ZERO_RESULT_DAYS,
+            zero_result_max_queries: Self::DEFAULT_ZERO_RESULT_MAX_QUERIES,
+        }
+    }
+}
+
+/// Configuration of the public website.
+#[derive(Clone, Debug)]
+pub struct SiteConfig {
+    /// URL of the frontend, without the trailing slash.
+    pub base_url: String,
+}
+
+impl SiteConfig {
+    const DEFAULT_BASE_URL: &'static str = "https://sustainity.dev";
+
+    /// Loads the config from the `SUSTAINITY_SITE_URL` environment variable.
+    pub fn load_or_default() -> Self {
+        let base_url = std::env::var("SUSTAINITY_SITE_URL")
+            .unwrap_or_else(|_| Self::DEFAULT_BASE_URL.into())
+            .trim_end_matches('/')
+            .to_owned();
+        tracing::info!("Site URL: {base_url}");
+        Self { base_url }
+    }
+
+    /// Returns the absolute URL of the given path.
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{path}", self.base_url)
+    }
+}
+
+/// Configuration of the bulk downloads.
+#[derive(Clone, Debug)]
+pub struct ExportConfig {
+    /// Directory where the generated exports are stored.
+    pub dir: std::path::PathBuf,
+}
+
+impl ExportConfig {
+    const DEFAULT_DIR: &'static str = "/var/cache/sustainity/exports";
+
+    /// Loads the config from the `SUSTAINITY_EXPORT_DIR` environment variable.
+    pub fn load_or_default() -> Self {
+        let dir =
+            std::env::var("SUSTAINITY_EXPORT_DIR").unwrap_or_else(|_| Self::DEFAULT_DIR.into());
+        tracing::info!("Export directory: {dir}");
+        Self { dir: dir.into() }
+    }
+}
+
+/// Identifies the served dataset.
+///
+/// Each dataset is imported into its own database, so that a new one can be prepared while the old
+/// one is still being served.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DatasetConfig {
+    /// Name of the database holding the dataset.
+    #[serde(rename = "database")]
+    pub database: String,
+
+    /// Version of the dataset.
+    #[serde(rename = "version")]
+    pub version: String,
+}
+
+impl DatasetConfig {
+    const DEFAULT_DATABASE: &'static str = "sustainity";
+
+    pub fn load_from_file() -> Result<Self, ConfigError> {
+        const PATH: &str = "/etc/sustainity/dataset.json";
+        let data = std::fs::read_to_string(PATH)?;
+        let config = serde_json::from_str(&data)?;
+        Ok(config)
+    }
+
+    /// Loads the config from a file or, if it does not exist, from `SUSTAINITY_DATABASE` and
+    /// `SUSTAINITY_DATASET_VERSION` environment variables.
+    ///
+    /// If the version is not set, the current time is used, so that everything derived from the
+    /// version is invalidated at least on restart.
+    pub fn load_or_default() -> Self {
+        match Self::load_from_file() {
+            Ok(ok) => {
+                tracing::info!("Loaded dataset config from a file");
+                return ok;
+            }
+            Err(err) => tracing::warn!("{err}"),
+        }
+
+        let database =
+            std::env::var("SUSTAINITY_DATABASE").unwrap_or_else(|_| Self::DEFAULT_DATABASE.into());
+        let version = match std::env::var("SUSTAINITY_DATASET_VERSION") {
+            Ok(version) => version,
+            Err(_) => {
+                let version = humantime::format_rfc3339_seconds(std::time::SystemTime::now());
+                tracing::warn!("Dataset version not set, using `{version}`");
+                version.to_string()
+            }
+        };
+        Self { database, version }
+    }
+}
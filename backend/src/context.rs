@@ -7,37 +7,39 @@ use futures::future::BoxFuture;
 use hyper::{service::Service, Request};
 use swagger::{Push, XSpanIdString};
 
-use crate::{config::SecretConfig, db::Db};
+use crate::{db::Db, languages::Languages};
 
-swagger::new_context_type!(SustainityContext, EmptyContext, swagger::XSpanIdString, Db);
+swagger::new_context_type!(SustainityContext, EmptyContext, swagger::XSpanIdString, Db, Languages);
 
 pub struct MakeAddContext<T, A> {
     inner: T,
-    config: SecretConfig,
+    db: Db,
     marker: PhantomData<A>,
 }
 
-impl<T, A, B, Z> MakeAddContext<T, A>
+impl<T, A, B, D, Z> MakeAddContext<T, A>
 where
     A: Default + Push<XSpanIdString, Result = B>,
-    B: Push<Db, Result = Z>,
+    B: Push<Db, Result = D>,
+    D: Push<Languages, Result = Z>,
 {
-    pub fn new(inner: T, config: SecretConfig) -> MakeAddContext<T, A> {
-        MakeAddContext { inner, config, marker: PhantomData }
+    pub fn new(inner: T, db: Db) -> MakeAddContext<T, A> {
+        MakeAddContext { inner, db, marker: PhantomData }
     }
 }
 
-impl<Target, T, A, B, Z> Service<Target> for MakeAddContext<T, A>
+impl<Target, T, A, B, D, Z> Service<Target> for MakeAddContext<T, A>
 where
     Target: Send,
     A: Default + Push<XSpanIdString, Result = B> + Send,
-    B: Push<Db, Result = Z>,
+    B: Push<Db, Result = D>,
+    D: Push<Languages, Result = Z>,
     Z: Send + 'static,
     T: Service<Target> + Send,
     T::Future: Send + 'static,
 {
     type Error = T::Error;
-    type Response = AddContext<T::Response, A, B, Z>;
+    type Response = AddContext<T::Response, A, B, D, Z>;
     type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
 
     fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
@@ -46,35 +48,38 @@ where
 
     fn call(&mut self, target: Target) -> Self::Future {
         let service = self.inner.call(target);
-        let config = self.config.clone();
-        Box::pin(async move { Ok(AddContext::new(service.await?, config)) })
+        let db = self.db.clone();
+        Box::pin(async move { Ok(AddContext::new(service.await?, db)) })
     }
 }
 
-pub struct AddContext<T, A, B, Z>
+pub struct AddContext<T, A, B, D, Z>
 where
     A: Default + Push<XSpanIdString, Result = B>,
-    B: Push<Db, Result = Z>,
+    B: Push<Db, Result = D>,
+    D: Push<Languages, Result = Z>,
 {
     inner: T,
-    config: SecretConfig,
+    db: Db,
     marker: PhantomData<A>,
 }
 
-impl<T, A, B, Z> AddContext<T, A, B, Z>
+impl<T, A, B, D, Z> AddContext<T, A, B, D, Z>
 where
     A: Default + Push<XSpanIdString, Result = B>,
-    B: Push<Db, Result = Z>,
+    B: Push<Db, Result = D>,
+    D: Push<Languages, Result = Z>,
 {
-    pub fn new(inner: T, config: SecretConfig) -> Self {
-        AddContext { inner, config, marker: PhantomData }
+    pub fn new(inner: T, db: Db) -> Self {
+        AddContext { inner, db, marker: PhantomData }
     }
 }
 
-impl<T, A, B, Z, ReqBody> Service<Request<ReqBody>> for AddContext<T, A, B, Z>
+impl<T, A, B, D, Z, ReqBody> Service<Request<ReqBody>> for AddContext<T, A, B, D, Z>
 where
     A: Default + Push<XSpanIdString, Result = B>,
-    B: Push<Db, Result = Z>,
+    B: Push<Db, Result = D>,
+    D: Push<Languages, Result = Z>,
     Z: Send + 'static,
     T: Service<(Request<ReqBody>, Z)>,
 {
@@ -87,9 +92,11 @@ where
     }
 
     fn call(&mut self, request: Request<ReqBody>) -> Self::Future {
-        log::info!("Request: {} {}", request.method(), request.uri());
+        // The router puts the `Db` of the selected dataset snapshot into the extensions.
+        let db = request.extensions().get::<Db>().cloned().unwrap_or_else(|| self.db.clone());
+        let languages = Languages::from_request(&request);
         let context = A::default().push(XSpanIdString::get_or_generate(&request));
-        let context = context.push(Db::new(self.config.clone()));
+        let context = context.push(db).push(languages);
         self.inner.call((request, context))
     }
 }
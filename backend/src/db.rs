@@ -1,42 +1,126 @@
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, PoisonError, RwLock,
+    },
+    time::{Duration, Instant},
+};
 
-use arangors::Connection;
 use serde::Deserialize;
 use serde_json::value::Value;
 use snafu::prelude::*;
+use tracing::Instrument;
 
 use sustainity_api::models as api;
-use sustainity_models::store::{LibraryItem, Organisation, Presentation, Product};
+use sustainity_models::{
+    changes::Change,
+    collections,
+    feedback::{Feedback, FeedbackStatus},
+    product_requests::ProductRequest,
+    store::{
+        Category, CategoryStats, CertificationInfo, ExternalIdKind, IdentifierKind, LibraryItem,
+        Organisation, OrganisationIds, OrganisationLabel, Presentation, Product, ProductIds,
+    },
+};
 
 use crate::{
-    config::SecretConfig,
+    analytics::ZeroResultQuery,
+    cache::Caches,
+    config::{CacheConfig, DatasetConfig, PoolConfig, QueryConfig, SearchConfig, SecretConfig},
     errors,
-    models::{OrganisationSearchResult, ProductSearchResult},
+    export::ExportCollection,
+    metrics::Metrics,
+    models::{
+        AlternativesOptions, OrganisationSearchResult, OwnershipEntry, PrecomputedAlternatives,
+        ProductSearchResult, ProductSort,
+    },
+    pool::{self, Pool},
 };
 
-const DB_NAME_SUSTAINITY: &str = "sustainity";
-
-macro_rules! db {
-    ($db:expr, $config:ident) => {{
-        let name = $db.to_string();
-        let conn =
-            Connection::establish_basic_auth(&$config.host, &$config.user, &$config.password)
-                .await
-                .context(errors::ConnectSnafu)?;
-        conn.db(&name).await.context(errors::DatabaseSnafu { name })?
-    }};
+/// Search result returned by the organisation search queries.
+macro_rules! organisation_search_result {
+    () => {
+        "{
+            id: o._key,
+            ids: o.ids,
+            name: o.names[0],
+            quality: {
+                num_certifications: COUNT(VALUES(o.certifications)[* FILTER CURRENT != null]),
+                has_description: LENGTH(o.descriptions) > 0,
+                has_image: LENGTH(o.images) > 0,
+            },
+        }"
+    };
 }
 
+/// Search result returned by the product search queries.
+macro_rules! product_search_result {
+    () => {
+        "{
+            id: p._key,
+            ids: p.ids,
+            name: p.names[0],
+            quality: {
+                num_certifications: COUNT(VALUES(p.certifications)[* FILTER CURRENT != null]),
+                score: p.sustainity_score.total,
+                has_description: LENGTH(p.descriptions) > 0,
+                has_image: LENGTH(p.images) > 0,
+            },
+            manufacturer_ids: p.manufacturer_ids,
+        }"
+    };
+}
+
+/// Database holding data which must survive dataset reloads.
+const DB_NAME_FEEDBACK: &str = "sustainity_feedback";
+
+/// Database holding changes between all the imported dataset versions.
+const DB_NAME_CHANGES: &str = "sustainity_changes";
+
 #[derive(Default)]
 pub struct Query {
     db_name: String,
+    name: &'static str,
+    slow_threshold: Duration,
     query: String,
     vars: HashMap<&'static str, Value>,
+
+    /// Whether the query may be retried after a transient error.
+    retry: bool,
 }
 
 impl Query {
-    pub fn builder(db_name: &str) -> Self {
-        Self { db_name: db_name.to_string(), query: String::new(), vars: HashMap::new() }
+    /// Starts a query of the given type (used to aggregate the metrics, e.g. the `Db` method name).
+    ///
+    /// Queries taking at least `slow_threshold` are logged.
+    pub fn builder(db_name: &str, name: &'static str, slow_threshold: Duration) -> Self {
+        Self {
+            db_name: db_name.to_string(),
+            name,
+            slow_threshold,
+            query: String::new(),
+            vars: HashMap::new(),
+            retry: true,
+        }
+    }
+
+    /// Disables retrying of the query.
+    ///
+    /// A transient error doesn't tell whether the server executed the query, so queries which are
+    /// not idempotent (e.g. inserting a document or incrementing a counter) must not be repeated.
+    pub fn no_retry(mut self) -> Self {
+        self.retry = false;
+        self
+    }
+
+    /// Returns the maximal number of attempts to run the query.
+    fn max_attempts(&self, config: &PoolConfig) -> u32 {
+        if self.retry {
+            config.max_attempts
+        } else {
+            1
+        }
     }
 
     pub fn get_query(&self) -> String {
@@ -74,60 +158,255 @@ impl Query {
         arangors::AqlQuery::builder().query(&self.query).bind_vars(self.vars.clone()).build()
     }
 
-    pub async fn all<R>(self, config: &SecretConfig) -> Result<Vec<R>, errors::DbError>
+    async fn try_run<R>(&self, pool: &Pool) -> Result<Vec<R>, errors::DbError>
     where
         for<'de> R: Clone + std::fmt::Debug + Deserialize<'de>,
     {
-        let db = db!(self.db_name, config);
-        let results: Vec<R> = db
-            .aql_query(self.build())
+        let lease = pool.acquire(&self.db_name).await?;
+        let timeout = pool.config().query_timeout;
+        let results: Vec<R> = tokio::time::timeout(timeout, lease.db.aql_query(self.build()))
             .await
+            .map_err(|_| errors::DbError::Timeout { timeout, query: self.get_query() })?
             .context(errors::QuerySnafu { query: self.get_query() })?;
 
         Ok(results)
     }
 
-    pub async fn one<R>(self, config: &SecretConfig) -> Result<Option<R>, errors::DbError>
+    /// Runs the query, retrying it with a backoff if it fails with a transient error (unless
+    /// retrying is disabled).
+    async fn run<R>(&self, pool: &Pool) -> Result<Vec<R>, errors::DbError>
+    where
+        for<'de> R: Clone + std::fmt::Debug + Deserialize<'de>,
+    {
+        let max_attempts = self.max_attempts(pool.config());
+        let mut attempt = 1;
+        loop {
+            match self.try_run(pool).await {
+                Err(err) if err.is_transient() && attempt < max_attempts => {
+                    tracing::warn!("Query failed (attempt {attempt}): {err}");
+                    pool.invalidate(&self.db_name);
+                    tokio::time::sleep(pool::backoff(pool.config().initial_backoff, attempt)).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    pub async fn all<R>(self, pool: &Pool, metrics: &Metrics) -> Result<Vec<R>, errors::DbError>
+    where
+        for<'de> R: Clone + std::fmt::Debug + Deserialize<'de>,
+    {
+        let span = tracing::info_span!(
+            "db_query",
+            db = %self.db_name,
+            name = self.name,
+            query = %self.query
+        );
+        let start = Instant::now();
+        let results = self.run(pool).instrument(span.clone()).await;
+        let elapsed = start.elapsed();
+        metrics.record_query(self.name, elapsed, results.is_ok());
+        if elapsed >= self.slow_threshold {
+            let elapsed_ms = elapsed.as_millis() as u64;
+            span.in_scope(|| tracing::warn!(elapsed_ms, "Slow query"));
+        }
+        results
+    }
+
+    pub async fn one<R>(self, pool: &Pool, metrics: &Metrics) -> Result<Option<R>, errors::DbError>
     where
         for<'de> R: Deserialize<'de> + Clone,
         R: std::fmt::Debug,
     {
-        let db = db!(self.db_name, config);
-        let results: Vec<R> = db
-            .aql_query(self.build())
-            .await
-            .context(errors::QuerySnafu { query: self.get_query() })?;
+        let results: Vec<R> = self.all(pool, metrics).await?;
         Ok(results.first().cloned())
     }
 }
 
+/// A dataset served only when explicitly selected by the client.
+#[derive(Clone)]
+struct Snapshot {
+    dataset: Arc<DatasetConfig>,
+    cache: Arc<Caches>,
+}
+
 #[derive(Clone)]
 pub struct Db {
-    config: SecretConfig,
+    pool: Arc<Pool>,
+    cache_config: CacheConfig,
+    query_config: QueryConfig,
+    cache: Arc<Caches>,
+    search: Arc<SearchConfig>,
+    metrics: Arc<Metrics>,
+    dataset: Arc<RwLock<Arc<DatasetConfig>>>,
+    snapshots: Arc<RwLock<HashMap<String, Snapshot>>>,
+    ready: Arc<AtomicBool>,
 }
 
 impl Db {
-    pub fn new(config: SecretConfig) -> Self {
-        Self { config }
+    /// Creates a new `Db` serving the given dataset.
+    ///
+    /// The `Db` is not ready until the dataset is checked and swapped in with `swap_dataset`.
+    pub fn new(
+        config: SecretConfig,
+        pool_config: PoolConfig,
+        cache_config: &CacheConfig,
+        query_config: QueryConfig,
+        search_config: SearchConfig,
+        dataset: DatasetConfig,
+    ) -> Self {
+        Self {
+            pool: Arc::new(Pool::new(config, pool_config)),
+            cache_config: cache_config.clone(),
+            query_config,
+            cache: Arc::new(Caches::new(cache_config)),
+            search: Arc::new(search_config),
+            metrics: Arc::new(Metrics::new()),
+            dataset: Arc::new(RwLock::new(Arc::new(dataset))),
+            snapshots: Arc::new(RwLock::new(HashMap::new())),
+            ready: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Returns a `Db` serving the named snapshot instead of the main dataset.
+    ///
+    /// The returned `Db` shares the metrics and the snapshots with this one, but has its own cache.
+    pub fn snapshot(&self, name: &str) -> Option<Db> {
+        let snapshot =
+            self.snapshots.read().unwrap_or_else(PoisonError::into_inner).get(name)?.clone();
+        Some(Self {
+            cache: snapshot.cache,
+            dataset: Arc::new(RwLock::new(snapshot.dataset)),
+            ready: Arc::new(AtomicBool::new(true)),
+            ..self.clone()
+        })
+    }
+
+    /// Starts serving the dataset as a named snapshot, replacing a previous one of the same name.
+    pub fn mount_snapshot(&self, name: String, dataset: DatasetConfig) {
+        let snapshot = Snapshot {
+            dataset: Arc::new(dataset),
+            cache: Arc::new(Caches::new(&self.cache_config)),
+        };
+        self.snapshots.write().unwrap_or_else(PoisonError::into_inner).insert(name, snapshot);
+    }
+
+    /// Stops serving the named snapshot. Returns `false` if it was not served.
+    pub fn unmount_snapshot(&self, name: &str) -> bool {
+        self.snapshots.write().unwrap_or_else(PoisonError::into_inner).remove(name).is_some()
+    }
+
+    /// Lists the served snapshots.
+    pub fn snapshots(&self) -> BTreeMap<String, DatasetConfig> {
+        let snapshots = self.snapshots.read().unwrap_or_else(PoisonError::into_inner);
+        snapshots.iter().map(|(name, s)| (name.clone(), s.dataset.as_ref().clone())).collect()
+    }
+
+    /// Returns versions of the main dataset and all the snapshots.
+    pub fn served_versions(&self) -> Vec<String> {
+        let mut versions = vec![self.dataset().version.clone()];
+        let snapshots = self.snapshots.read().unwrap_or_else(PoisonError::into_inner);
+        versions.extend(snapshots.values().map(|s| s.dataset.version.clone()));
+        versions
+    }
+
+    /// Returns the served dataset.
+    pub fn dataset(&self) -> Arc<DatasetConfig> {
+        self.dataset.read().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    /// Checks if a complete dataset is being served.
+    pub fn is_ready(&self) -> bool {
+        self.ready.load(Ordering::Acquire)
+    }
+
+    /// Starts serving the given dataset.
+    ///
+    /// Queries started before the swap finish on the old dataset.
+    pub fn swap_dataset(&self, dataset: DatasetConfig) {
+        *self.dataset.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(dataset);
+        self.cache.clear();
+        self.ready.store(true, Ordering::Release);
+    }
+
+    /// Returns the metrics shared by all the clones of this `Db`.
+    pub fn metrics(&self) -> &Arc<Metrics> {
+        &self.metrics
+    }
+
+    /// Checks if the database of the served dataset is reachable.
+    pub async fn ping(&self) -> Result<(), errors::DbError> {
+        self.query("ping").line("RETURN 1").one::<u32>(&self.pool, &self.metrics).await.map(|_| ())
+    }
+
+    /// Starts a query of the given type in the database of the served dataset.
+    fn query(&self, name: &'static str) -> Query {
+        self.query_in(&self.dataset().database, name)
+    }
+
+    /// Starts a query of the given type in the given database.
+    fn query_in(&self, database: &str, name: &'static str) -> Query {
+        Query::builder(database, name, self.query_config.slow_threshold)
+    }
+
+    /// Counts entries in the main collections of the dataset in the given database.
+    pub async fn count_dataset_entries(
+        &self,
+        database: &str,
+    ) -> Result<Option<HashMap<String, usize>>, errors::DbError> {
+        self.query_in(database, "count_dataset_entries")
+            .line("RETURN {")
+            .line("    organisations: LENGTH(organisations),")
+            .line("    products: LENGTH(products),")
+            .line("    library: LENGTH(library),")
+            .line("}")
+            .one(&self.pool, &self.metrics)
+            .await
+    }
+
+    /// Returns the response cache shared by all the clones of this `Db`.
+    pub fn cache(&self) -> &Caches {
+        &self.cache
+    }
+
+    pub fn search_config(&self) -> &SearchConfig {
+        &self.search
     }
 
     pub async fn get_library_contents(&self) -> Result<Vec<LibraryItem>, errors::DbError> {
-        Query::builder(DB_NAME_SUSTAINITY)
+        self.query("get_library_contents")
             .line("WITH library")
             .line("FOR i IN library")
             .line("    RETURN i")
-            .all(&self.config)
+            .all(&self.pool, &self.metrics)
             .await
     }
 
     pub async fn get_library_item(&self, id: &str) -> Result<Option<LibraryItem>, errors::DbError> {
-        Query::builder(DB_NAME_SUSTAINITY)
+        self.query("get_library_item")
             .line("WITH library")
             .line("FOR i IN library")
             .line("    FILTER i.id == @id")
             .line("    RETURN i")
             .bind("id", id)
-            .one(&self.config)
+            .one(&self.pool, &self.metrics)
+            .await
+    }
+
+    /// Finds the library item with the given current or former slug.
+    pub async fn get_library_item_by_slug(
+        &self,
+        slug: &str,
+    ) -> Result<Option<LibraryItem>, errors::DbError> {
+        self.query("get_library_item_by_slug")
+            .line("WITH library")
+            .line("FOR i IN library")
+            .line("    FILTER i.slug == @slug OR @slug IN i.former_slugs")
+            .line("    LIMIT 1")
+            .line("    RETURN i")
+            .bind("slug", slug)
+            .one(&self.pool, &self.metrics)
             .await
     }
 
@@ -135,13 +414,13 @@ impl Db {
         &self,
         id: &str,
     ) -> Result<Option<Presentation>, errors::DbError> {
-        Query::builder(DB_NAME_SUSTAINITY)
+        self.query("get_presentation")
             .line("WITH presentations")
             .line("FOR p IN presentations")
             .line("    FILTER p.id == @id")
             .line("    RETURN p")
             .bind("id", id)
-            .one(&self.config)
+            .one(&self.pool, &self.metrics)
             .await
     }
 
@@ -150,7 +429,7 @@ impl Db {
         id_variant: api::OrganisationIdVariant,
         id: &str,
     ) -> Result<Option<Organisation>, errors::DbError> {
-        let builder = Query::builder(DB_NAME_SUSTAINITY);
+        let builder = self.query("get_organisation");
         match id_variant {
             api::OrganisationIdVariant::Wiki => builder
                 .line("WITH organisations, organisation_wiki_ids, organisation_wiki_id_edges")
@@ -162,33 +441,174 @@ impl Db {
                 .line("WITH organisations, organisation_vat_ids, organisation_vat_id_edges")
                 .line("FOR v IN organisation_vat_ids")
                 .line("    FILTER v._key == @id")
-                .line("    FOR o IN OUTBOUND v organisation_vat_ids_edges")
+                .line("    FOR o IN OUTBOUND v organisation_vat_id_edges")
                 .line("        RETURN o"),
             api::OrganisationIdVariant::Www => builder
                 .line("WITH organisations, organisation_wwws, organisation_www_edges")
                 .line("FOR w IN organisation_wwws")
                 .line("    FILTER w._key == @id")
-                .line("    FOR o IN OUTBOUND w organisation_wwws_edges")
+                .line("    FOR o IN OUTBOUND w organisation_www_edges")
                 .line("        RETURN o"),
         }
         .bind("id", id)
-        .one(&self.config)
+        .one(&self.pool, &self.metrics)
         .await
     }
 
+    /// Returns the organisation with the given DB key.
+    pub async fn get_organisation_by_key(
+        &self,
+        key: &str,
+    ) -> Result<Option<Organisation>, errors::DbError> {
+        self.query("get_organisation_by_key")
+            .line("WITH organisations")
+            .line("FOR o IN organisations")
+            .line("    FILTER o._key == @key")
+            .line("    RETURN o")
+            .bind("key", key)
+            .one(&self.pool, &self.metrics)
+            .await
+    }
+
+    /// Returns the entries of the lookup of organisation names for the given DB keys.
+    ///
+    /// Keys of unknown organisations are skipped.
+    pub async fn get_organisation_labels(
+        &self,
+        keys: &[String],
+    ) -> Result<Vec<OrganisationLabel>, errors::DbError> {
+        self.query("get_organisation_labels")
+            .line("WITH organisation_labels")
+            .line("FOR l IN organisation_labels")
+            .line("    FILTER l._key IN @keys")
+            .line("    RETURN l")
+            .bind("keys", keys.to_vec())
+            .all(&self.pool, &self.metrics)
+            .await
+    }
+
     pub async fn find_organisation_products(
         &self,
         id: &str,
     ) -> Result<Vec<Product>, errors::DbError> {
-        Query::builder(DB_NAME_SUSTAINITY)
+        self.query("find_organisation_products")
+            .line("WITH organisations, products, manufacturing_edges")
+            .line("FOR o IN organisations")
+            .line("    FILTER o._key == @id")
+            .line("    FOR p IN 1..1 OUTBOUND o manufacturing_edges")
+            .line("        RETURN p")
+            .bind("id", id)
+            .all(&self.pool, &self.metrics)
+            .await
+    }
+
+    /// Returns the product of the organisation best matching the given keywords.
+    ///
+    /// At least half of the keywords must be among the keywords of the product.
+    pub async fn find_organisation_product_by_keywords(
+        &self,
+        id: &str,
+        keywords: &[String],
+    ) -> Result<Option<Product>, errors::DbError> {
+        self.query("find_organisation_product_by_keywords")
             .line("WITH organisations, products, manufacturing_edges")
             .line("FOR o IN organisations")
             .line("    FILTER o._key == @id")
             .line("    FOR p IN 1..1 OUTBOUND o manufacturing_edges")
+            .line("        LET matched = LENGTH(INTERSECTION(p.keywords, @keywords))")
+            .line("        FILTER matched > 0 AND matched * 2 >= LENGTH(@keywords)")
+            .line("        SORT matched DESC, p.sustainity_score.total DESC, p._key")
+            .line("        LIMIT 1")
             .line("        RETURN p")
             .bind("id", id)
-            .all(&self.config)
+            .bind("keywords", keywords.to_vec())
+            .one(&self.pool, &self.metrics)
+            .await
+    }
+
+    pub async fn count_organisation_products(&self, id: &str) -> Result<usize, errors::DbError> {
+        self.query("count_organisation_products")
+            .line("WITH organisations, products, manufacturing_edges")
+            .line("FOR o IN organisations")
+            .line("    FILTER o._key == @id")
+            .line("    RETURN LENGTH(FOR p IN 1..1 OUTBOUND o manufacturing_edges RETURN 1)")
+            .bind("id", id)
+            .one(&self.pool, &self.metrics)
             .await
+            .map(Option::unwrap_or_default)
+    }
+
+    /// Returns a page of products of the organisation in the given order.
+    pub async fn find_organisation_products_page(
+        &self,
+        id: &str,
+        sort: ProductSort,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<Product>, errors::DbError> {
+        self.query("find_organisation_products_page")
+            .line("WITH organisations, products, manufacturing_edges")
+            .line("FOR o IN organisations")
+            .line("    FILTER o._key == @id")
+            .line("    FOR p IN 1..1 OUTBOUND o manufacturing_edges")
+            .cond("        SORT p.sustainity_score.total DESC, p._key", sort == ProductSort::Score)
+            .cond("        SORT p.names[0].text, p._key", sort == ProductSort::Name)
+            .cond(
+                "        SORT p.categories[0], p.sustainity_score.total DESC, p._key",
+                sort == ProductSort::Category,
+            )
+            .line("        LIMIT @offset, @limit")
+            .line("        RETURN p")
+            .bind("id", id)
+            .bind("offset", offset)
+            .bind("limit", limit)
+            .all(&self.pool, &self.metrics)
+            .await
+    }
+
+    /// Returns IDs of a page of organisations ordered by their DB keys.
+    pub async fn get_organisation_ids_page(
+        &self,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<OrganisationIds>, errors::DbError> {
+        self.query("get_organisation_ids_page")
+            .line("WITH organisations")
+            .line("FOR o IN organisations")
+            .line("    SORT o._key")
+            .line("    LIMIT @offset, @limit")
+            .line("    RETURN o.ids")
+            .bind("offset", offset)
+            .bind("limit", limit)
+            .all(&self.pool, &self.metrics)
+            .await
+    }
+
+    /// Returns a page of whole entries of the collection, ordered by their DB keys.
+    ///
+    /// The page starts after the entry with the given key, so that the collection can be read
+    /// without the cost of large offsets.
+    pub async fn get_export_page(
+        &self,
+        collection: ExportCollection,
+        after: &str,
+        limit: usize,
+    ) -> Result<Vec<Value>, errors::DbError> {
+        let builder = self.query("get_export_page");
+        match collection {
+            ExportCollection::Organisations => {
+                builder.line("WITH organisations").line("FOR e IN organisations")
+            }
+            ExportCollection::Products => builder.line("WITH products").line("FOR e IN products"),
+        }
+        .line("    FILTER e._key > @after")
+        .line("    SORT e._key")
+        .line("    LIMIT @limit")
+        .line("    RETURN UNSET(e, \"_id\", \"_rev\")")
+        .bind("after", after)
+        .bind("limit", limit)
+        .all(&self.pool, &self.metrics)
+        .await
     }
 
     pub async fn get_product(
@@ -196,13 +616,13 @@ impl Db {
         id_variant: api::ProductIdVariant,
         id: &str,
     ) -> Result<Option<Product>, errors::DbError> {
-        let builder = Query::builder(DB_NAME_SUSTAINITY);
+        let builder = self.query("get_product");
         match id_variant {
             api::ProductIdVariant::Ean => builder
                 .line("WITH product_eans, product_ean_edges, products")
                 .line("FOR e IN product_eans")
                 .line("    FILTER e._key == @id")
-                .line("    FOR p IN OUTBOUND e product_eans_edges")
+                .line("    FOR p IN OUTBOUND e product_ean_edges")
                 .line("        RETURN p"),
             api::ProductIdVariant::Gtin => builder
                 .line("WITH product_gtins, product_gtin_edges, products")
@@ -218,34 +638,221 @@ impl Db {
                 .line("        RETURN p"),
         }
         .bind("id", id)
-        .one(&self.config)
+        .one(&self.pool, &self.metrics)
         .await
     }
 
+    /// Returns IDs of a page of products ordered by their DB keys.
+    pub async fn get_product_ids_page(
+        &self,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<ProductIds>, errors::DbError> {
+        self.query("get_product_ids_page")
+            .line("WITH products")
+            .line("FOR p IN products")
+            .line("    SORT p._key")
+            .line("    LIMIT @offset, @limit")
+            .line("    RETURN p.ids")
+            .bind("offset", offset)
+            .bind("limit", limit)
+            .all(&self.pool, &self.metrics)
+            .await
+    }
+
     pub async fn find_product_manufacturers(
         &self,
         id: &str,
     ) -> Result<Vec<Organisation>, errors::DbError> {
-        Query::builder(DB_NAME_SUSTAINITY)
+        self.query("find_product_manufacturers")
             .line("WITH organisations, products, manufacturing_edges")
             .line("FOR p IN products")
             .line("    FILTER p._key == @id")
             .line("    FOR o IN INBOUND p manufacturing_edges")
             .line("        RETURN o")
             .bind("id", id)
-            .all(&self.config)
+            .all(&self.pool, &self.metrics)
+            .await
+    }
+
+    /// Walks from the product to its manufacturers and then to their parent organisations.
+    ///
+    /// Each organisation is returned once, at the lowest depth it was found at.
+    pub async fn find_product_ownership(
+        &self,
+        id: &str,
+        max_depth: usize,
+    ) -> Result<Vec<OwnershipEntry>, errors::DbError> {
+        self.query("find_product_ownership")
+            .line("WITH organisations, products, graph_edges")
+            .line("FOR p IN products")
+            .line("    FILTER p._key == @id")
+            .line("    FOR o, e, path IN 1..@max_depth OUTBOUND p graph_edges")
+            .line(
+                "        PRUNE e != null AND e.relation NOT IN [\"manufactured_by\", \"owned_by\"]",
+            )
+            .line("        OPTIONS { order: \"bfs\", uniqueVertices: \"global\" }")
+            .line("        FILTER path.edges[0].relation == \"manufactured_by\"")
+            .line("        FILTER SLICE(path.edges, 1)[*].relation ALL == \"owned_by\"")
+            .line("        SORT LENGTH(path.edges)")
+            .line("        RETURN {")
+            .line("            depth: LENGTH(path.edges),")
+            .line("            owns: LENGTH(path.edges) > 1 ? path.vertices[-2]._key : null,")
+            .line("            organisation: o")
+            .line("        }")
+            .bind("id", id)
+            .bind("max_depth", max_depth)
+            .all(&self.pool, &self.metrics)
             .await
     }
 
     pub async fn find_product_categories(&self, id: &str) -> Result<Vec<String>, errors::DbError> {
-        Query::builder(DB_NAME_SUSTAINITY)
+        self.query("find_product_categories")
             .line("WITH categories, products, category_edges")
             .line("FOR p IN products")
             .line("    FILTER p._key == @id")
             .line("    FOR c IN 1..1 INBOUND p category_edges")
             .line("        RETURN c._key")
             .bind("id", id)
-            .all(&self.config)
+            .all(&self.pool, &self.metrics)
+            .await
+    }
+
+    pub async fn get_categories(&self) -> Result<Vec<Category>, errors::DbError> {
+        self.query("get_categories")
+            .line("WITH categories")
+            .line("FOR c IN categories")
+            .line("    SORT c._key")
+            .line("    RETURN c")
+            .all(&self.pool, &self.metrics)
+            .await
+    }
+
+    pub async fn get_certifications(&self) -> Result<Vec<CertificationInfo>, errors::DbError> {
+        self.query("get_certifications")
+            .line("WITH certifications")
+            .line("FOR c IN certifications")
+            .line("    SORT c._key")
+            .line("    RETURN c")
+            .all(&self.pool, &self.metrics)
+            .await
+    }
+
+    pub async fn get_category(&self, category: &str) -> Result<Option<Category>, errors::DbError> {
+        self.query("get_category")
+            .line("WITH categories")
+            .line("FOR c IN categories")
+            .line("    FILTER c._key == @category")
+            .line("    RETURN c")
+            .bind("category", category)
+            .one(&self.pool, &self.metrics)
+            .await
+    }
+
+    pub async fn get_category_stats(
+        &self,
+        category: &str,
+    ) -> Result<Option<CategoryStats>, errors::DbError> {
+        self.query("get_category_stats")
+            .line("WITH category_stats")
+            .line("FOR s IN category_stats")
+            .line("    FILTER s._key == @category")
+            .line("    RETURN s")
+            .bind("category", category)
+            .one(&self.pool, &self.metrics)
+            .await
+    }
+
+    /// Returns the products with the given keys in the same order, skipping the missing ones.
+    pub async fn get_products_by_keys(
+        &self,
+        keys: &[String],
+    ) -> Result<Vec<Product>, errors::DbError> {
+        self.query("get_products_by_keys")
+            .line("WITH products")
+            .line("FOR key IN @keys")
+            .line("    LET p = DOCUMENT(\"products\", key)")
+            .line("    FILTER p != null")
+            .line("    RETURN p")
+            .bind("keys", keys.to_vec())
+            .all(&self.pool, &self.metrics)
+            .await
+    }
+
+    pub async fn find_category_products(
+        &self,
+        category: &str,
+        region_code: Option<&str>,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<Product>, errors::DbError> {
+        let r = region_code.is_some();
+        self.query("find_category_products")
+            .line("WITH categories, products, category_edges")
+            .line("FOR c IN categories")
+            .line("    FILTER c._key == @category")
+            .line("    FOR p IN 1..1 OUTBOUND c category_edges")
+            .cond("        FILTER p.regions.variant == \"all\"", r)
+            .cond("            OR @region_code IN p.regions.content", r)
+            .line("        SORT p.sustainity_score.total DESC, p._key")
+            .line("        LIMIT @offset, @limit")
+            .line("        RETURN p")
+            .bind("category", category)
+            .bind("offset", offset)
+            .bind("limit", limit)
+            .bond("region_code", region_code)
+            .all(&self.pool, &self.metrics)
+            .await
+    }
+
+    /// Picks well-scored products in a pseudo-random order determined by the seed.
+    pub async fn find_featured_products(
+        &self,
+        seed: &str,
+        pool: usize,
+        limit: usize,
+    ) -> Result<Vec<Product>, errors::DbError> {
+        self.query("find_featured_products")
+            .line("WITH products")
+            .line("FOR p IN products")
+            .line("    FILTER p.sustainity_score.total > 0")
+            .line("    SORT p.sustainity_score.total DESC, p._key")
+            .line("    LIMIT @pool")
+            .line("    SORT MD5(CONCAT(@seed, p._key))")
+            .line("    LIMIT @limit")
+            .line("    RETURN p")
+            .bind("seed", seed)
+            .bind("pool", pool)
+            .bind("limit", limit)
+            .all(&self.pool, &self.metrics)
+            .await
+    }
+
+    /// Picks well-certified organisations in a pseudo-random order determined by the seed.
+    pub async fn find_featured_organisations(
+        &self,
+        seed: &str,
+        pool: usize,
+        limit: usize,
+    ) -> Result<Vec<Organisation>, errors::DbError> {
+        self.query("find_featured_organisations")
+            .line("WITH organisations")
+            .line("FOR o IN organisations")
+            .line("    LET score")
+            .line("      = 0.90 * (o.certifications.bcorp != null)")
+            .line("      + 0.90 * (o.certifications.eu_ecolabel != null)")
+            .line("      + 0.60 * 0.01 * o.certifications.fti.score")
+            .line("      + 0.30 * (o.certifications.tco != null)")
+            .line("    FILTER score > 0")
+            .line("    SORT score DESC, o._key")
+            .line("    LIMIT @pool")
+            .line("    SORT MD5(CONCAT(@seed, o._key))")
+            .line("    LIMIT @limit")
+            .line("    RETURN o")
+            .bind("seed", seed)
+            .bind("pool", pool)
+            .bind("limit", limit)
+            .all(&self.pool, &self.metrics)
             .await
     }
 
@@ -254,16 +861,33 @@ impl Db {
         id: &str,
         category: &str,
         region_code: Option<&str>,
+        options: &AlternativesOptions,
     ) -> Result<Vec<Product>, errors::DbError> {
         let r = region_code.is_some();
-        Query::builder(DB_NAME_SUSTAINITY)
+        let m = options.exclude_same_manufacturer;
+        let c = options.require_certification;
+        self.query("find_product_alternatives")
             .line("WITH categories, products, category_edges")
+            .cond("LET manufacturer_ids = FIRST(", m)
+            .cond("    FOR o IN products FILTER o._key == @id RETURN o.manufacturer_ids", m)
+            .cond(")", m)
+            .line("LET parent_id = FIRST(")
+            .line("    FOR o IN products FILTER o._key == @id RETURN o.variant_of || o._key")
+            .line(")")
             .line("FOR c IN categories")
             .line("    FILTER c._key == @category")
             .line("    FOR p IN 1..1 OUTBOUND c category_edges")
-            .line("        FILTER p._key != @id")
+            .line("        FILTER p._key != @id AND p._key != parent_id")
+            .line("        FILTER p.variant_of == null")
             .cond("        FILTER p.regions.variant == \"all\"", r)
             .cond("            OR @region_code IN p.regions.content", r)
+            .cond(
+                "        FILTER LENGTH(INTERSECTION(p.manufacturer_ids, manufacturer_ids)) == 0",
+                m,
+            )
+            .cond("        FILTER p.certifications.bcorp != null", c)
+            .cond("            OR p.certifications.eu_ecolabel != null", c)
+            .cond("            OR p.certifications.tco != null", c)
             .line("        LET score")
             .line("          = (@id IN p.follows)")
             .line("          + 0.90 * (p.certifications.bcorp != null)")
@@ -272,12 +896,63 @@ impl Db {
             .line("          + 0.30 * (p.certifications.tco != null)")
             .line("        LET randomized_score = score + 0.01 * RAND()")
             .line("        SORT randomized_score DESC")
-            .line("        LIMIT 10")
+            .line("        LIMIT @limit")
             .line("        RETURN p")
             .bind("id", id)
             .bind("category", category)
+            .bind("limit", options.max_per_category)
             .bond("region_code", region_code)
-            .all(&self.config)
+            .all(&self.pool, &self.metrics)
+            .await
+    }
+
+    /// Returns the precomputed alternatives of the product in the category and region.
+    ///
+    /// Returns `None` if they were not precomputed.
+    pub async fn find_precomputed_product_alternatives(
+        &self,
+        id: &str,
+        category: &str,
+        region_code: Option<&str>,
+    ) -> Result<Option<PrecomputedAlternatives<Product>>, errors::DbError> {
+        let region_code = region_code.map(str::to_ascii_uppercase);
+        let key = collections::product_alternatives(id, category, region_code.as_deref()).key;
+        self.query("find_precomputed_product_alternatives")
+            .line("WITH product_alternatives, products")
+            .line("FOR a IN product_alternatives")
+            .line("    FILTER a._key == @key")
+            .line("    RETURN {")
+            .line("        alternatives: DOCUMENT(\"products\", a.alternatives),")
+            .line("        exhaustive: a.exhaustive == true,")
+            .line("    }")
+            .bind("key", key)
+            .one(&self.pool, &self.metrics)
+            .await
+    }
+
+    /// Returns the keys of those of the given products which are made by an organisation having
+    /// all the given keywords.
+    pub async fn filter_products_by_manufacturer_keywords(
+        &self,
+        ids: &[String],
+        keywords: &[String],
+    ) -> Result<Vec<String>, errors::DbError> {
+        self.query("filter_products_by_manufacturer_keywords")
+            .line("WITH products, organisations, manufacturing_edges")
+            .line("FOR p IN products")
+            .line("    FILTER p._key IN @ids")
+            .line("    LET makers = (")
+            .line("        FOR o IN 1..1 INBOUND p manufacturing_edges")
+            .line("            FILTER LENGTH(INTERSECTION(o.keywords, @keywords)) == @num_keywords")
+            .line("            LIMIT 1")
+            .line("            RETURN 1")
+            .line("    )")
+            .line("    FILTER LENGTH(makers) > 0")
+            .line("    RETURN p._key")
+            .bind("ids", ids.to_vec())
+            .bind("keywords", keywords.to_vec())
+            .bind("num_keywords", keywords.len())
+            .all(&self.pool, &self.metrics)
             .await
     }
 
@@ -285,45 +960,76 @@ impl Db {
         &self,
         matching: &str,
     ) -> Result<Vec<OrganisationSearchResult>, errors::DbError> {
-        Query::builder(DB_NAME_SUSTAINITY)
+        self.query("search_organisations_exact_by_keyword")
             .line("FOR k IN organisation_keywords")
             .line("    FILTER k.keyword == @match")
             .line("    FOR o IN 1..1 OUTBOUND k organisation_keyword_edges")
-            .line("        RETURN { id: o._key, ids: o.ids, name: o.names[0] }")
+            .line(concat!("        RETURN ", organisation_search_result!()))
             .bind("match", matching)
-            .all(&self.config)
+            .all(&self.pool, &self.metrics)
             .await
     }
 
-    pub async fn search_organisations_substring_by_website(
+    /// Finds organisations having an identifier of the given kind equal to one of the values.
+    pub async fn search_organisations_exact_by_identifier(
         &self,
-        matching: &str,
+        kind: IdentifierKind,
+        values: &[String],
     ) -> Result<Vec<OrganisationSearchResult>, errors::DbError> {
-        Query::builder(DB_NAME_SUSTAINITY)
+        self.query("search_organisations_exact_by_identifier")
             .line("WITH organisations")
-            .line("FOR o IN organisations")
-            .line("    FILTER o.websites[? 1")
-            .line("        FILTER CONTAINS(CURRENT, @match)")
-            .line("      ]")
-            .line("    RETURN { id: o._key, ids: o.ids, name: o.names[0] }")
-            .bind("match", matching)
-            .all(&self.config)
+            .line("FOR i IN organisation_identifiers")
+            .line("    FILTER i.kind == @kind AND i.value IN @values")
+            .line("    COLLECT key = i.organisation")
+            .line("    LET o = DOCUMENT(\"organisations\", key)")
+            .line("    FILTER o != null")
+            .line(concat!("    RETURN ", organisation_search_result!()))
+            .bind("kind", kind.as_str())
+            .bind("values", values.to_vec())
+            .all(&self.pool, &self.metrics)
             .await
     }
 
-    pub async fn search_organisations_substring_by_vat_number(
+    /// Returns the products with the given (canonical) external identifier.
+    pub async fn find_products_by_external_id(
         &self,
-        matching: &str,
-    ) -> Result<Vec<OrganisationSearchResult>, errors::DbError> {
-        Query::builder(DB_NAME_SUSTAINITY)
+        kind: ExternalIdKind,
+        id: &str,
+    ) -> Result<Vec<Product>, errors::DbError> {
+        let builder = self
+            .query("find_products_by_external_id")
+            .line("WITH products")
+            .line("FOR p IN products");
+        match kind {
+            ExternalIdKind::Asin => builder.line("    FILTER @id IN p.external_ids.asin[*]"),
+        }
+        .line("    SORT p._key")
+        .line("    RETURN p")
+        .bind("id", id)
+        .all(&self.pool, &self.metrics)
+        .await
+    }
+
+    pub async fn find_organisations_by_identifier(
+        &self,
+        id: &str,
+        vat: &str,
+    ) -> Result<Vec<Organisation>, errors::DbError> {
+        self.query("find_organisations_by_identifier")
             .line("WITH organisations")
-            .line("FOR o IN organisations")
-            .line("    FILTER o.vat_numbers[? 1")
-            .line("        FILTER CONTAINS(CURRENT, @match)")
-            .line("      ]")
-            .line("    RETURN { id: o._key, ids: o.ids, name: o.names[0] }")
-            .bind("match", matching)
-            .all(&self.config)
+            .line("FOR i IN organisation_identifiers")
+            .line("    FILTER (i.kind IN [@lei, @registration_number] AND i.value == @id)")
+            .line("        OR (i.kind == @vat_kind AND i.value == @vat)")
+            .line("    COLLECT key = i.organisation")
+            .line("    LET o = DOCUMENT(\"organisations\", key)")
+            .line("    FILTER o != null")
+            .line("    RETURN o")
+            .bind("lei", IdentifierKind::Lei.as_str())
+            .bind("registration_number", IdentifierKind::RegistrationNumber.as_str())
+            .bind("vat_kind", IdentifierKind::Vat.as_str())
+            .bind("id", id)
+            .bind("vat", vat)
+            .all(&self.pool, &self.metrics)
             .await
     }
 
@@ -331,14 +1037,14 @@ impl Db {
         &self,
         matching: &str,
     ) -> Result<Vec<ProductSearchResult>, errors::DbError> {
-        Query::builder(DB_NAME_SUSTAINITY)
+        self.query("search_products_exact_by_keyword")
             .line("WITH products, product_keywords, product_keyword_edges")
             .line("FOR k IN product_keywords")
             .line("    FILTER k.keyword == @match")
             .line("    FOR p IN 1..1 OUTBOUND k product_keyword_edges")
-            .line("        RETURN { id: p._key, ids: p.ids, name: p.names[0] }")
+            .line(concat!("        RETURN ", product_search_result!()))
             .bind("match", matching)
-            .all(&self.config)
+            .all(&self.pool, &self.metrics)
             .await
     }
 
@@ -346,14 +1052,237 @@ impl Db {
         &self,
         matching: &str,
     ) -> Result<Vec<ProductSearchResult>, errors::DbError> {
-        Query::builder(DB_NAME_SUSTAINITY)
+        self.query("search_products_exact_by_gtin")
             .line("WITH products, product_gtins, product_gtin_edges")
             .line("FOR g IN product_gtins")
             .line("    FILTER g._key == @match")
             .line("    FOR p IN OUTBOUND g product_gtin_edges")
-            .line("        RETURN { id: p._key, ids: p.ids, name: p.names[0] }")
+            .line(concat!("        RETURN ", product_search_result!()))
             .bind("match", matching)
-            .all(&self.config)
+            .all(&self.pool, &self.metrics)
             .await
     }
+
+    /// Returns the time of the first comparison made against the given dataset version.
+    pub async fn find_changes_start(&self, since: &str) -> Result<Option<String>, errors::DbError> {
+        self.query_in(DB_NAME_CHANGES, "find_changes_start")
+            .line("FOR c IN changes")
+            .line("    FILTER c.previous_version == @since")
+            .line("    SORT c.created")
+            .line("    LIMIT 1")
+            .line("    RETURN c.created")
+            .bind("since", since)
+            .one(&self.pool, &self.metrics)
+            .await
+    }
+
+    /// Returns changes made at or after the given time, from the oldest.
+    pub async fn get_changes(
+        &self,
+        start: &str,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<Change>, errors::DbError> {
+        self.query_in(DB_NAME_CHANGES, "get_changes")
+            .line("FOR c IN changes")
+            .line("    FILTER c.created >= @start")
+            .line("    SORT c.created, c._key")
+            .line("    LIMIT @offset, @limit")
+            .line("    RETURN c")
+            .bind("start", start)
+            .bind("offset", offset)
+            .bind("limit", limit)
+            .all(&self.pool, &self.metrics)
+            .await
+    }
+
+    pub async fn insert_feedback(
+        &self,
+        feedback: Value,
+    ) -> Result<Option<String>, errors::DbError> {
+        self.query_in(DB_NAME_FEEDBACK, "insert_feedback")
+            .line("INSERT @feedback INTO feedback")
+            .line("    RETURN NEW._key")
+            .bind("feedback", feedback)
+            .no_retry()
+            .one(&self.pool, &self.metrics)
+            .await
+    }
+
+    pub async fn get_feedback(
+        &self,
+        status: Option<FeedbackStatus>,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<Feedback>, errors::DbError> {
+        self.query_in(DB_NAME_FEEDBACK, "get_feedback")
+            .line("FOR f IN feedback")
+            .cond("    FILTER f.status == @status", status.is_some())
+            .line("    SORT f.created")
+            .line("    LIMIT @offset, @limit")
+            .line("    RETURN f")
+            .bond("status", status.map(FeedbackStatus::as_str))
+            .bind("offset", offset)
+            .bind("limit", limit)
+            .all(&self.pool, &self.metrics)
+            .await
+    }
+
+    pub async fn get_feedback_item(&self, key: &str) -> Result<Option<Feedback>, errors::DbError> {
+        self.query_in(DB_NAME_FEEDBACK, "get_feedback_item")
+            .line("FOR f IN feedback")
+            .line("    FILTER f._key == @key")
+            .line("    RETURN f")
+            .bind("key", key)
+            .one(&self.pool, &self.metrics)
+            .await
+    }
+
+    pub async fn update_feedback_status(
+        &self,
+        key: &str,
+        status: FeedbackStatus,
+        note: Option<&str>,
+        updated: &str,
+    ) -> Result<Option<Feedback>, errors::DbError> {
+        self.query_in(DB_NAME_FEEDBACK, "update_feedback_status")
+            .line("FOR f IN feedback")
+            .line("    FILTER f._key == @key")
+            .line(
+                "    UPDATE f WITH { status: @status, note: @note, updated: @updated } IN feedback",
+            )
+            .line("    RETURN NEW")
+            .bind("key", key)
+            .bind("status", status.as_str())
+            .bind("note", note)
+            .bind("updated", updated)
+            .no_retry()
+            .one(&self.pool, &self.metrics)
+            .await
+    }
+
+    pub async fn insert_product_request(
+        &self,
+        request: Value,
+    ) -> Result<Option<String>, errors::DbError> {
+        self.query_in(DB_NAME_FEEDBACK, "insert_product_request")
+            .line("INSERT @request INTO product_requests")
+            .line("    RETURN NEW._key")
+            .bind("request", request)
+            .no_retry()
+            .one(&self.pool, &self.metrics)
+            .await
+    }
+
+    /// Inserts the request or, if a request with the same key exists, counts it and fills in the
+    /// details missing in the existing one.
+    pub async fn upsert_product_request(
+        &self,
+        request: Value,
+    ) -> Result<Option<String>, errors::DbError> {
+        self.query_in(DB_NAME_FEEDBACK, "upsert_product_request")
+            .line("UPSERT { _key: @request._key }")
+            .line("    INSERT @request")
+            .line("    UPDATE {")
+            .line("        count: OLD.count + 1,")
+            .line("        name: NOT_NULL(OLD.name, @request.name),")
+            .line("        brand: NOT_NULL(OLD.brand, @request.brand),")
+            .line("        photo_url: NOT_NULL(OLD.photo_url, @request.photo_url),")
+            .line("        updated: @request.updated,")
+            .line("    }")
+            .line("    IN product_requests")
+            .line("    RETURN NEW._key")
+            .bind("request", request)
+            .no_retry()
+            .one(&self.pool, &self.metrics)
+            .await
+    }
+
+    pub async fn get_product_requests(
+        &self,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<ProductRequest>, errors::DbError> {
+        self.query_in(DB_NAME_FEEDBACK, "get_product_requests")
+            .line("FOR r IN product_requests")
+            .line("    SORT r.count DESC, r.created")
+            .line("    LIMIT @offset, @limit")
+            .line("    RETURN r")
+            .bind("offset", offset)
+            .bind("limit", limit)
+            .all(&self.pool, &self.metrics)
+            .await
+    }
+
+    /// Counts the query with no results made on the given day.
+    ///
+    /// New queries are not stored once there are `max_queries` of them for the day.
+    pub async fn record_zero_result_query(
+        &self,
+        query: &str,
+        day: u64,
+        expires: u64,
+        max_queries: usize,
+    ) -> Result<Option<String>, errors::DbError> {
+        self.query_in(DB_NAME_FEEDBACK, "record_zero_result_query")
+            .line("LET stored = LENGTH(")
+            .line("    FOR q IN zero_result_queries")
+            .line("        FILTER q.day == @day")
+            .line("        LIMIT @max_queries")
+            .line("        RETURN 1")
+            .line(")")
+            .line("LET known = LENGTH(")
+            .line("    FOR q IN zero_result_queries")
+            .line("        FILTER q.day == @day AND q.query == @query")
+            .line("        LIMIT 1")
+            .line("        RETURN 1")
+            .line(")")
+            .line("FILTER known > 0 OR stored < @max_queries")
+            .line("UPSERT { day: @day, query: @query }")
+            .line("    INSERT { day: @day, query: @query, count: 1, expires: @expires }")
+            .line("    UPDATE { count: OLD.count + 1 }")
+            .line("    IN zero_result_queries")
+            .line("    RETURN NEW._key")
+            .bind("query", query)
+            .bind("day", day)
+            .bind("expires", expires)
+            .bind("max_queries", max_queries)
+            .no_retry()
+            .one(&self.pool, &self.metrics)
+            .await
+    }
+
+    /// Sums the counts of the queries with no results made since the `oldest` day.
+    pub async fn get_zero_result_queries(
+        &self,
+        oldest: u64,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<ZeroResultQuery>, errors::DbError> {
+        self.query_in(DB_NAME_FEEDBACK, "get_zero_result_queries")
+            .line("FOR q IN zero_result_queries")
+            .line("    FILTER q.day >= @oldest")
+            .line("    COLLECT query = q.query AGGREGATE count = SUM(q.count)")
+            .line("    SORT count DESC, query")
+            .line("    LIMIT @offset, @limit")
+            .line("    RETURN { query, count }")
+            .bind("oldest", oldest)
+            .bind("offset", offset)
+            .bind("limit", limit)
+            .all(&self.pool, &self.metrics)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retrying() {
+        let config = PoolConfig { max_attempts: 3, ..PoolConfig::default() };
+        let query = Query::builder("sustainity", "test", Duration::ZERO).line("RETURN 1");
+        assert_eq!(query.max_attempts(&config), 3);
+        assert_eq!(query.no_retry().max_attempts(&config), 1);
+    }
 }
@@ -11,6 +11,54 @@ pub enum DbError {
 
     #[snafu(display("Query: {source}\n{query}"))]
     Query { source: arangors::ClientError, query: String },
+
+    #[snafu(display("Query timed out after {timeout:?}\n{query}"))]
+    Timeout { timeout: std::time::Duration, query: String },
+
+    #[snafu(display("No database connection available within {timeout:?}"))]
+    PoolExhausted { timeout: std::time::Duration },
+}
+
+impl DbError {
+    /// Checks if the error might go away when the query is repeated.
+    ///
+    /// These are failures to reach the server and errors of a server which is (re)starting or
+    /// overloaded. Timeouts are not retried, a repeated slow query would only add to the load.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::Connect { source }
+            | Self::Database { source, .. }
+            | Self::Query { source, .. } => match source {
+                arangors::ClientError::HttpClient(_) => true,
+                arangors::ClientError::Arango(error) => error.code() == 503,
+                _ => false,
+            },
+            Self::Timeout { .. } | Self::PoolExhausted { .. } => false,
+        }
+    }
+}
+
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
+pub enum ReloadError {
+    #[snafu(context(false), display("Database: {source}"))]
+    Db { source: DbError },
+
+    #[snafu(display("Dataset in `{database}` is incomplete: `{collection}` is empty"))]
+    Incomplete { database: String, collection: String },
+}
+
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
+pub enum ExportError {
+    #[snafu(context(false), display("Database: {source}"))]
+    Db { source: DbError },
+
+    #[snafu(context(false), display("IO: {source}"))]
+    Io { source: std::io::Error },
+
+    #[snafu(display("Export task: {source}"))]
+    Join { source: tokio::task::JoinError },
 }
 
 #[derive(Debug, Snafu)]
@@ -21,12 +69,22 @@ pub enum BackendError {
 
     #[snafu(context(false), display("Model conversion: {source}"))]
     Convert { source: sustainity_models::models::IntoApiError },
+
+    #[snafu(context(false), display("Serialization: {source}"))]
+    Serialization { source: serde_json::Error },
+}
+
+impl BackendError {
+    /// Checks if the error is caused by the backend being temporarily overloaded.
+    pub fn is_unavailable(&self) -> bool {
+        matches!(self, Self::Db { source: DbError::PoolExhausted { .. } })
+    }
 }
 
 impl From<BackendError> for swagger::ApiError {
     fn from(error: BackendError) -> Self {
         let message = error.to_string();
-        log::error!("{}", message);
+        tracing::error!("{}", message);
         Self(message)
     }
 }
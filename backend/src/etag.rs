@@ -0,0 +1,127 @@
+//! Support for entity tags.
+//!
+//! The data served by the backend changes only when a new dataset is imported, so a strong entity
+//! tag can be derived from the dataset version and the requested resource without looking at the
+//! response body.
+//!
+//! Responses of the library endpoints depend also on the `Accept-Language` header, so it's a part
+//! of their tags.
+
+use hyper::{
+    header::{self, HeaderValue},
+    Request,
+};
+
+/// Path prefixes of endpoints whose responses are tagged.
+const TAGGED_PREFIXES: &[&str] =
+    &["/product", "/organisation", "/library", "/categories", "/sitemap", "/jsonld", "/changes"];
+
+/// Paths whose responses change even if the dataset stays the same, either during the day or with
+/// the feedback submitted by users.
+const UNTAGGED_PATHS: &[&str] =
+    &["/products/featured", "/organisations/featured", "/products/requests"];
+
+/// Path prefixes of endpoints whose responses depend on the preferred languages.
+const LOCALIZED_PREFIXES: &[&str] = &["/library"];
+
+/// Checks if responses for the given path should be tagged.
+pub fn is_tagged(path: &str) -> bool {
+    TAGGED_PREFIXES.iter().any(|prefix| path.starts_with(prefix)) && !UNTAGGED_PATHS.contains(&path)
+}
+
+/// Checks if responses for the given path depend on the `Accept-Language` header.
+pub fn is_localized(path: &str) -> bool {
+    LOCALIZED_PREFIXES.iter().any(|prefix| path.starts_with(prefix))
+}
+
+/// Computes a strong entity tag for the requested resource in the given dataset version.
+pub fn compute<B>(dataset_version: &str, request: &Request<B>) -> String {
+    let uri = request.uri();
+    let resource = uri.path_and_query().map_or_else(|| uri.path(), |p| p.as_str());
+    let languages = if is_localized(uri.path()) {
+        request.headers().get(header::ACCEPT_LANGUAGE).and_then(|v| v.to_str().ok())
+    } else {
+        None
+    };
+    let digest = md5::compute(
+        format!("{dataset_version}\n{resource}\n{}", languages.unwrap_or_default()).as_bytes(),
+    );
+    format!("\"{digest:x}\"")
+}
+
+/// Checks if the `If-None-Match` header lists the entity tag.
+///
+/// The header may contain a list of tags. As specified in RFC 9110, weak comparison is used, so
+/// the `W/` prefix is ignored. The `*` wildcard is not handled here, see `is_wildcard`.
+pub fn matches(if_none_match: &HeaderValue, etag: &str) -> bool {
+    let Ok(value) = if_none_match.to_str() else {
+        return false;
+    };
+    value.split(',').map(str::trim).any(|tag| tag.trim_start_matches("W/") == etag)
+}
+
+/// Checks if the `If-None-Match` header is the `*` wildcard.
+///
+/// The wildcard matches any current representation, so it's known to match only after the resource
+/// was found to exist.
+pub fn is_wildcard(if_none_match: &HeaderValue) -> bool {
+    if_none_match.to_str().is_ok_and(|value| value.trim() == "*")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(uri: &str, language: Option<&'static str>) -> Request<()> {
+        let mut builder = Request::get(uri);
+        if let Some(language) = language {
+            builder = builder.header(header::ACCEPT_LANGUAGE, language);
+        }
+        builder.body(()).unwrap()
+    }
+
+    #[test]
+    fn tags_depend_on_version_and_resource() {
+        let request1 = request("/product/gtin/123?region=POL", None);
+        let request2 = request("/product/gtin/123", None);
+
+        let tag = compute("1", &request1);
+        assert!(tag.starts_with('"') && tag.ends_with('"'));
+        assert_eq!(tag, compute("1", &request1));
+        assert_ne!(tag, compute("2", &request1));
+        assert_ne!(tag, compute("1", &request2));
+    }
+
+    #[test]
+    fn library_tags_depend_on_languages() {
+        let tag = compute("1", &request("/library/cert:bcorp", None));
+        assert_ne!(tag, compute("1", &request("/library/cert:bcorp", Some("nl"))));
+        assert_eq!(
+            compute("1", &request("/product/gtin/123", None)),
+            compute("1", &request("/product/gtin/123", Some("nl")))
+        );
+    }
+
+    #[test]
+    fn matching() {
+        let tag = "\"abc\"";
+        assert!(matches(&HeaderValue::from_static("\"abc\""), tag));
+        assert!(matches(&HeaderValue::from_static("W/\"abc\""), tag));
+        assert!(matches(&HeaderValue::from_static("\"x\", \"abc\""), tag));
+        assert!(!matches(&HeaderValue::from_static("*"), tag));
+        assert!(is_wildcard(&HeaderValue::from_static(" * ")));
+        assert!(!is_wildcard(&HeaderValue::from_static("\"abc\"")));
+        assert!(!matches(&HeaderValue::from_static("\"abcd\""), tag));
+        assert!(!matches(&HeaderValue::from_static("abc"), tag));
+    }
+
+    #[test]
+    fn tagged_paths() {
+        assert!(is_tagged("/product/gtin/123"));
+        assert!(is_tagged("/products/compare"));
+        assert!(!is_tagged("/products/featured"));
+        assert!(!is_tagged("/products/requests"));
+        assert!(is_tagged("/library"));
+        assert!(!is_tagged("/search/text"));
+    }
+}
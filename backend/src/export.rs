@@ -0,0 +1,468 @@
+//! Bulk download of the whole dataset.
+//!
+//! The collections are exported as compressed JSON Lines files. An export is generated in the
+//! background on the first request for a given dataset version and stored on disk, so that the
+//! following downloads (including resumed ones using the `Range` header) are served directly from
+//! the file. Until the file is ready, the requests are asked to retry later.
+
+use std::{
+    collections::HashSet,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use hyper::body::{Body, Bytes};
+use snafu::prelude::*;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+use crate::{
+    config::ExportConfig,
+    db::Db,
+    errors::{self, ExportError},
+};
+
+/// Number of entries read from the database at once.
+const PAGE_SIZE: usize = 1000;
+
+/// Size of chunks the files are sent in.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Compression level used for both formats.
+const COMPRESSION_LEVEL: u32 = 6;
+
+/// Time after which clients should retry a download of an export being generated.
+pub const RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// Content type of the uncompressed exports.
+const JSON_LINES_CONTENT_TYPE: &str = "application/jsonl";
+
+/// Collections available for download.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportCollection {
+    Organisations,
+    Products,
+}
+
+impl ExportCollection {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Organisations => "organisations",
+            Self::Products => "products",
+        }
+    }
+}
+
+/// Supported compression formats.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Gzip => "gz",
+            Self::Zstd => "zst",
+        }
+    }
+
+    /// Content type and encoding the file is served with.
+    ///
+    /// Gzip is served as the content encoding of JSON Lines, which all HTTP clients understand. The
+    /// `zstd` content coding is not as widely supported, so such files are served as they are.
+    pub fn content_type(self) -> (&'static str, Option<&'static str>) {
+        match self {
+            Self::Gzip => (JSON_LINES_CONTENT_TYPE, Some("gzip")),
+            Self::Zstd => ("application/zstd", None),
+        }
+    }
+}
+
+/// Parses file names like `products.jsonl.gz`.
+pub fn parse_file_name(name: &str) -> Option<(ExportCollection, Compression)> {
+    let (collection, extension) = name.split_once(".jsonl.")?;
+    let collection = match collection {
+        "organisations" => ExportCollection::Organisations,
+        "products" => ExportCollection::Products,
+        _ => return None,
+    };
+    let compression = match extension {
+        "gz" => Compression::Gzip,
+        "zst" => Compression::Zstd,
+        _ => return None,
+    };
+    Some((collection, compression))
+}
+
+/// Compressing writer of the export files.
+enum Encoder {
+    Gzip(flate2::write::GzEncoder<std::io::BufWriter<std::fs::File>>),
+    Zstd(zstd::Encoder<'static, std::io::BufWriter<std::fs::File>>),
+}
+
+impl Encoder {
+    fn new(path: &Path, compression: Compression) -> std::io::Result<Self> {
+        let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        Ok(match compression {
+            Compression::Gzip => Self::Gzip(flate2::write::GzEncoder::new(
+                file,
+                flate2::Compression::new(COMPRESSION_LEVEL),
+            )),
+            Compression::Zstd => Self::Zstd(zstd::Encoder::new(file, COMPRESSION_LEVEL as i32)?),
+        })
+    }
+
+    fn write_lines(&mut self, entries: &[serde_json::Value]) -> std::io::Result<()> {
+        for entry in entries {
+            let writer: &mut dyn Write = match self {
+                Self::Gzip(encoder) => encoder,
+                Self::Zstd(encoder) => encoder,
+            };
+            serde_json::to_writer(&mut *writer, entry)?;
+            writer.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> std::io::Result<()> {
+        let mut file = match self {
+            Self::Gzip(encoder) => encoder.finish()?,
+            Self::Zstd(encoder) => encoder.finish()?,
+        };
+        file.flush()
+    }
+}
+
+/// An export file ready to be served.
+pub struct ExportFile {
+    pub path: PathBuf,
+    pub len: u64,
+    pub file_name: String,
+    pub content_type: &'static str,
+    pub content_encoding: Option<&'static str>,
+
+    /// Strong entity tag identifying this particular generated file.
+    pub etag: String,
+
+    /// Modification time of the file formatted as an HTTP date.
+    pub last_modified: String,
+}
+
+impl ExportFile {
+    /// Checks if the `If-Range` header value still identifies this file.
+    ///
+    /// Only exact matches of the strong entity tag or the modification date count, so a range
+    /// request for a regenerated file gets the whole file instead of a part of a different one.
+    pub fn matches_if_range(&self, if_range: &str) -> bool {
+        let if_range = if_range.trim();
+        if_range == self.etag || if_range == self.last_modified
+    }
+}
+
+/// State of an export requested for download.
+pub enum Prepared {
+    Ready(ExportFile),
+    Generating,
+}
+
+/// Generates and keeps the export files.
+pub struct Exporter {
+    dir: PathBuf,
+
+    /// Paths of files being generated, so that each is generated only once.
+    pending: Arc<Mutex<HashSet<PathBuf>>>,
+}
+
+impl Exporter {
+    pub fn new(config: &ExportConfig) -> Self {
+        Self { dir: config.dir.clone(), pending: Arc::new(Mutex::new(HashSet::new())) }
+    }
+
+    /// Returns the export file for the current dataset.
+    ///
+    /// If the file doesn't exist yet, its generation is started in the background.
+    pub async fn prepare(
+        &self,
+        collection: ExportCollection,
+        compression: Compression,
+        db: &Db,
+    ) -> Result<Prepared, ExportError> {
+        let version = db.dataset().version.clone();
+        let version_dir = version_dir(&self.dir, &version);
+        let file_name = format!("{}.jsonl.{}", collection.name(), compression.extension());
+        let path = version_dir.join(&file_name);
+
+        if let Ok(metadata) = tokio::fs::metadata(&path).await {
+            let modified = metadata.modified()?;
+            let len = metadata.len();
+            let etag = format!(
+                "\"{:x}\"",
+                md5::compute(format!("{version}\n{file_name}\n{len}\n{modified:?}").as_bytes())
+            );
+            let (content_type, content_encoding) = compression.content_type();
+            return Ok(Prepared::Ready(ExportFile {
+                path,
+                len,
+                file_name,
+                content_type,
+                content_encoding,
+                etag,
+                last_modified: http_date(modified),
+            }));
+        }
+
+        let is_new =
+            self.pending.lock().unwrap_or_else(PoisonError::into_inner).insert(path.clone());
+        if is_new {
+            let dir = self.dir.clone();
+            let pending = self.pending.clone();
+            let db = db.clone();
+            tokio::spawn(async move {
+                tracing::info!("Generating export `{file_name}` of dataset `{version}`");
+                remove_old_versions(&dir, &db.served_versions()).await;
+                let result = match tokio::fs::create_dir_all(&version_dir).await {
+                    Ok(()) => generate(&path, collection, compression, &db).await,
+                    Err(err) => Err(err.into()),
+                };
+                if let Err(err) = result {
+                    tracing::error!("Export `{file_name}` of dataset `{version}` failed: {err}");
+                }
+                pending.lock().unwrap_or_else(PoisonError::into_inner).remove(&path);
+            });
+        }
+        Ok(Prepared::Generating)
+    }
+}
+
+fn version_dir(dir: &Path, version: &str) -> PathBuf {
+    dir.join(format!("{:x}", md5::compute(version.as_bytes())))
+}
+
+/// Removes exports of the datasets (including snapshots) which are not served anymore.
+async fn remove_old_versions(dir: &Path, served: &[String]) {
+    let Ok(mut entries) = tokio::fs::read_dir(dir).await else {
+        return;
+    };
+    let served: Vec<PathBuf> = served.iter().map(|version| version_dir(dir, version)).collect();
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let path = entry.path();
+        if !served.contains(&path) {
+            if let Err(err) = tokio::fs::remove_dir_all(&path).await {
+                tracing::warn!("Failed to remove old export `{}`: {err}", path.display());
+            }
+        }
+    }
+}
+
+/// Formats the time as an HTTP date (RFC 9110, section 5.6.7).
+fn http_date(time: SystemTime) -> String {
+    const WEEKDAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+    const MONTHS: [&str; 12] =
+        ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+    let secs = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let days = secs / 86400;
+    let secs_of_day = secs % 86400;
+
+    // Civil date from days since the epoch, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{}, {day:02} {} {year} {:02}:{:02}:{:02} GMT",
+        WEEKDAYS[(days % 7) as usize],
+        MONTHS[(month - 1) as usize],
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60,
+    )
+}
+
+/// Writes the whole collection into a compressed file.
+///
+/// The file is written under a temporary name and renamed when complete, so that a failed export is
+/// never served.
+async fn generate(
+    path: &Path,
+    collection: ExportCollection,
+    compression: Compression,
+    db: &Db,
+) -> Result<(), ExportError> {
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_path);
+    let mut encoder = {
+        let tmp_path = tmp_path.clone();
+        tokio::task::spawn_blocking(move || Encoder::new(&tmp_path, compression))
+            .await
+            .context(errors::JoinSnafu)??
+    };
+
+    let mut after = String::new();
+    loop {
+        let page = db.get_export_page(collection, &after, PAGE_SIZE).await?;
+        let Some(last) = page.last() else {
+            break;
+        };
+        after = last.get("_key").and_then(|key| key.as_str()).unwrap_or_default().to_owned();
+        let is_last = page.len() < PAGE_SIZE || after.is_empty();
+
+        encoder = tokio::task::spawn_blocking(move || {
+            encoder.write_lines(&page)?;
+            Ok::<_, std::io::Error>(encoder)
+        })
+        .await
+        .context(errors::JoinSnafu)??;
+
+        if is_last {
+            break;
+        }
+    }
+
+    tokio::task::spawn_blocking(move || encoder.finish()).await.context(errors::JoinSnafu)??;
+    tokio::fs::rename(&tmp_path, path).await?;
+    Ok(())
+}
+
+/// Parses the `Range` header.
+///
+/// Only a single range is supported; if more are requested, the whole file is served, as allowed by
+/// RFC 9110. Returns `Some(Err(()))` if the range cannot be satisfied.
+pub fn parse_range(header: &str, len: u64) -> Option<Result<(u64, u64), ()>> {
+    let spec = header.trim().strip_prefix("bytes=")?;
+    if spec.contains(',') {
+        return None;
+    }
+
+    let (start, end) = spec.split_once('-')?;
+    let (start, end) = match (start.trim(), end.trim()) {
+        ("", suffix) => {
+            let suffix: u64 = suffix.parse().ok()?;
+            if suffix == 0 {
+                return Some(Err(()));
+            }
+            (len.saturating_sub(suffix), len.saturating_sub(1))
+        }
+        (start, "") => (start.parse().ok()?, len.saturating_sub(1)),
+        (start, end) => (start.parse().ok()?, end.parse::<u64>().ok()?.min(len.saturating_sub(1))),
+    };
+
+    if start >= len || start > end {
+        return Some(Err(()));
+    }
+    Some(Ok((start, end)))
+}
+
+/// Streams `count` bytes of the file starting at `start`.
+pub fn stream(path: PathBuf, start: u64, count: u64) -> Body {
+    let (mut sender, body) = Body::channel();
+    tokio::spawn(async move {
+        let mut file = match tokio::fs::File::open(&path).await {
+            Ok(file) => file,
+            Err(err) => {
+                tracing::error!("Failed to open export `{}`: {err}", path.display());
+                sender.abort();
+                return;
+            }
+        };
+        if let Err(err) = file.seek(std::io::SeekFrom::Start(start)).await {
+            tracing::error!("Failed to seek in export `{}`: {err}", path.display());
+            sender.abort();
+            return;
+        }
+
+        let mut remaining = count;
+        let mut buffer = vec![0; CHUNK_SIZE];
+        while remaining > 0 {
+            let size = usize::try_from(remaining).map_or(CHUNK_SIZE, |r| r.min(CHUNK_SIZE));
+            match file.read(&mut buffer[..size]).await {
+                Ok(0) => break,
+                Ok(read) => {
+                    remaining -= read as u64;
+                    if sender.send_data(Bytes::copy_from_slice(&buffer[..read])).await.is_err() {
+                        // The client disconnected.
+                        return;
+                    }
+                }
+                Err(err) => {
+                    tracing::error!("Failed to read export `{}`: {err}", path.display());
+                    sender.abort();
+                    return;
+                }
+            }
+        }
+    });
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_names() {
+        assert_eq!(
+            parse_file_name("products.jsonl.gz"),
+            Some((ExportCollection::Products, Compression::Gzip))
+        );
+        assert_eq!(
+            parse_file_name("organisations.jsonl.zst"),
+            Some((ExportCollection::Organisations, Compression::Zstd))
+        );
+        assert_eq!(parse_file_name("products.jsonl"), None);
+        assert_eq!(parse_file_name("categories.jsonl.gz"), None);
+    }
+
+    #[test]
+    fn ranges() {
+        assert_eq!(parse_range("bytes=0-99", 1000), Some(Ok((0, 99))));
+        assert_eq!(parse_range("bytes=500-", 1000), Some(Ok((500, 999))));
+        assert_eq!(parse_range("bytes=-100", 1000), Some(Ok((900, 999))));
+        assert_eq!(parse_range("bytes=900-2000", 1000), Some(Ok((900, 999))));
+        assert_eq!(parse_range("bytes=1000-", 1000), Some(Err(())));
+        assert_eq!(parse_range("bytes=5-1", 1000), Some(Err(())));
+        assert_eq!(parse_range("bytes=0-1,5-6", 1000), None);
+        assert_eq!(parse_range("items=0-1", 1000), None);
+    }
+
+    #[test]
+    fn http_dates() {
+        assert_eq!(http_date(UNIX_EPOCH), "Thu, 01 Jan 1970 00:00:00 GMT");
+        assert_eq!(
+            http_date(UNIX_EPOCH + Duration::from_secs(784_111_777)),
+            "Sun, 06 Nov 1994 08:49:37 GMT"
+        );
+        assert_eq!(
+            http_date(UNIX_EPOCH + Duration::from_secs(951_782_400)),
+            "Tue, 29 Feb 2000 00:00:00 GMT"
+        );
+    }
+
+    #[test]
+    fn if_range() {
+        let file = ExportFile {
+            path: PathBuf::from("products.jsonl.gz"),
+            len: 100,
+            file_name: "products.jsonl.gz".to_owned(),
+            content_type: JSON_LINES_CONTENT_TYPE,
+            content_encoding: Some("gzip"),
+            etag: "\"abc\"".to_owned(),
+            last_modified: http_date(UNIX_EPOCH),
+        };
+        assert!(file.matches_if_range("\"abc\""));
+        assert!(file.matches_if_range("Thu, 01 Jan 1970 00:00:00 GMT"));
+        assert!(!file.matches_if_range("W/\"abc\""));
+        assert!(!file.matches_if_range("\"def\""));
+        assert!(!file.matches_if_range("Fri, 02 Jan 1970 00:00:00 GMT"));
+    }
+}
@@ -0,0 +1,86 @@
+//! Moderation queue for feedback submitted by the users.
+
+use sustainity_models::feedback::{Feedback, FeedbackStatus, FeedbackSubmission};
+
+use crate::{db::Db, errors::BackendError, models::FeedbackStatusChange, timestamps};
+
+/// Maximal length of the subject and the correction.
+const MAX_SUBJECT_LENGTH: usize = 200;
+
+/// Maximal length of the message.
+const MAX_MESSAGE_LENGTH: usize = 2000;
+
+/// Result of changing the moderation status.
+#[derive(Debug)]
+pub enum Moderation {
+    /// The status was changed.
+    Changed(Feedback),
+
+    /// No feedback with the given ID exists.
+    NotFound,
+
+    /// The feedback cannot be moved from its current status to the requested one.
+    InvalidTransition { from: FeedbackStatus, to: FeedbackStatus },
+}
+
+/// Checks the submission, returning a description of the problem if it's invalid.
+pub fn validate(submission: &FeedbackSubmission) -> Result<(), &'static str> {
+    if submission.subject.trim().is_empty() {
+        return Err("The subject must not be empty");
+    }
+    if submission.subject.chars().count() > MAX_SUBJECT_LENGTH {
+        return Err("The subject is too long");
+    }
+    if submission.correction.as_ref().is_some_and(|c| c.chars().count() > MAX_SUBJECT_LENGTH) {
+        return Err("The correction is too long");
+    }
+    if submission.message.chars().count() > MAX_MESSAGE_LENGTH {
+        return Err("The message is too long");
+    }
+    Ok(())
+}
+
+/// Stores the submission in the moderation queue and returns its ID.
+pub async fn submit(submission: FeedbackSubmission, db: &Db) -> Result<String, BackendError> {
+    let created = timestamps::now();
+    let feedback = Feedback {
+        db_key: None,
+        submission,
+        status: FeedbackStatus::Pending,
+        note: None,
+        updated: created.clone(),
+        created,
+    };
+    let key = db.insert_feedback(serde_json::to_value(&feedback)?).await?;
+    Ok(key.unwrap_or_default())
+}
+
+/// Lists feedback, optionally only with the given status, from the oldest.
+pub async fn list(
+    status: Option<FeedbackStatus>,
+    offset: usize,
+    limit: usize,
+    db: &Db,
+) -> Result<Vec<Feedback>, BackendError> {
+    Ok(db.get_feedback(status, offset, limit).await?)
+}
+
+/// Changes the moderation status of the feedback.
+pub async fn moderate(
+    key: &str,
+    change: FeedbackStatusChange,
+    db: &Db,
+) -> Result<Moderation, BackendError> {
+    let Some(feedback) = db.get_feedback_item(key).await? else {
+        return Ok(Moderation::NotFound);
+    };
+    if !feedback.status.can_change_to(change.status) {
+        return Ok(Moderation::InvalidTransition { from: feedback.status, to: change.status });
+    }
+
+    let note = change.note.as_deref();
+    match db.update_feedback_status(key, change.status, note, &timestamps::now()).await? {
+        Some(feedback) => Ok(Moderation::Changed(feedback)),
+        None => Ok(Moderation::NotFound),
+    }
+}
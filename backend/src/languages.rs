@@ -0,0 +1,71 @@
+//! Negotiation of the language of the responses.
+
+use hyper::{header, Request};
+
+/// Languages preferred by the client, in order of preference.
+///
+/// Parsed from the `Accept-Language` header, e.g. `nl-BE, nl;q=0.9, en;q=0.8`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Languages(Vec<String>);
+
+impl Languages {
+    /// Parses the value of the `Accept-Language` header.
+    ///
+    /// Invalid entries, the wildcard and entries with zero quality are skipped.
+    pub fn parse(header: &str) -> Self {
+        let mut entries: Vec<(u16, String)> = header
+            .split(',')
+            .filter_map(|entry| {
+                let mut parts = entry.split(';').map(str::trim);
+                let tag = parts.next()?.to_lowercase();
+                if tag.is_empty() || tag == "*" {
+                    return None;
+                }
+                let quality = match parts.find_map(|param| param.strip_prefix("q=")) {
+                    Some(quality) => (quality.parse::<f32>().ok()? * 1000.0) as u16,
+                    None => 1000,
+                };
+                (quality > 0).then_some((quality, tag))
+            })
+            .collect();
+        // Stable sort keeps the order of the entries with the same quality.
+        entries.sort_by(|a, b| b.0.cmp(&a.0));
+        Self(entries.into_iter().map(|(_, tag)| tag).collect())
+    }
+
+    /// Reads the preferred languages from the request headers.
+    pub fn from_request<B>(request: &Request<B>) -> Self {
+        request
+            .headers()
+            .get(header::ACCEPT_LANGUAGE)
+            .and_then(|value| value.to_str().ok())
+            .map(Self::parse)
+            .unwrap_or_default()
+    }
+
+    /// Returns the language tags in order of preference.
+    pub fn as_slice(&self) -> &[String] {
+        &self.0
+    }
+
+    /// Returns a key identifying the preferences (e.g. for caching).
+    pub fn key(&self) -> String {
+        self.0.join(",")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing() {
+        assert_eq!(
+            Languages::parse("en;q=0.8, nl-BE, nl;q=0.9, *;q=0.1").as_slice(),
+            ["nl-be", "nl", "en"]
+        );
+        assert_eq!(Languages::parse("de, fr").as_slice(), ["de", "fr"]);
+        assert!(Languages::parse("de;q=0, fr;q=x, ,").as_slice().is_empty());
+        assert_eq!(Languages::parse("").key(), "");
+    }
+}
@@ -4,40 +4,75 @@
 
 use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
 
+mod analytics;
+mod auth;
+mod cache;
 mod config;
 mod context;
 mod db;
 mod errors;
+mod etag;
+mod export;
+mod feedback;
+mod languages;
+mod metrics;
 mod models;
+mod pool;
+mod problems;
+mod product_requests;
+mod query;
+mod rate_limit;
+mod reload;
 mod retrieve;
+mod routes;
 mod server;
+mod telemetry;
+#[cfg(test)]
+mod testing;
+mod timestamps;
+mod validation;
+mod webhooks;
 
 #[tokio::main]
 async fn main() {
-    if let Err(err) = fern::Dispatch::new()
-        .format(|out, message, record| {
-            out.finish(format_args!(
-                "[{} {: <5}] {}",
-                humantime::format_rfc3339_seconds(std::time::SystemTime::now()),
-                record.level(),
-                message
-            ))
-        })
-        .level(log::LevelFilter::Info)
-        .chain(std::io::stdout())
-        .apply()
-    {
+    if let Err(err) = telemetry::init() {
         println!("Logger error:\n{err}");
         return;
     }
 
-    log::info!("Starting Sustainity backend!");
+    tracing::info!("Starting Sustainity backend!");
 
     let config = config::SecretConfig::load_or_default();
+    let cache_config = config::CacheConfig::load_or_default();
+    let rate_limit_config = config::RateLimitConfig::load_or_default();
+    let dataset_config = config::DatasetConfig::load_or_default();
+    let webhooks = webhooks::Webhooks::new(config::load_webhooks());
+    let search_config = config::SearchConfig::load_or_default();
+    let query_config = config::QueryConfig::load_or_default();
+    let pool_config = config::PoolConfig::load_or_default();
+    let db = db::Db::new(
+        config,
+        pool_config,
+        &cache_config,
+        query_config,
+        search_config,
+        dataset_config.clone(),
+    );
+    tokio::spawn(reload::initialize(db.clone(), dataset_config, webhooks.clone()));
+    tokio::spawn(reload::initialize_snapshots(db.clone(), config::load_snapshots()));
+    tokio::spawn(reload::handle_signals(db.clone(), webhooks.clone()));
 
     let addr = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(0, 0, 0, 0), 8080));
     let server = server::Server::new();
     let service = sustainity_api::server::MakeService::new(server);
-    let service = context::MakeAddContext::<_, context::EmptyContext>::new(service, config);
+    let service = context::MakeAddContext::<_, context::EmptyContext>::new(service, db.clone());
+    let shared = std::sync::Arc::new(routes::Shared {
+        limiters: rate_limit::RateLimiters::new(&rate_limit_config),
+        api_keys: auth::ApiKeys::new(config::load_api_keys()),
+        webhooks,
+        site: config::SiteConfig::load_or_default(),
+        exporter: export::Exporter::new(&config::ExportConfig::load_or_default()),
+    });
+    let service = routes::MakeRouter::new(service, db, shared);
     hyper::server::Server::bind(&addr).serve(service).await.expect("Service failed")
 }
@@ -0,0 +1,224 @@
+//! Metrics in the Prometheus exposition format.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{Mutex, PoisonError},
+    time::Duration,
+};
+
+/// Upper bounds (in seconds) of the histogram buckets.
+const BUCKETS: [f64; 11] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Endpoints distinguished in the metrics, identified by the first path segment.
+///
+/// Requests to other paths are counted together, so that the number of metrics cannot be blown up
+/// by requesting random paths.
+const ENDPOINTS: &[&str] = &[
+    "admin",
+    "alternatives",
+    "categories",
+    "healthz",
+    "library",
+    "metrics",
+    "organisation",
+    "organisations",
+    "product",
+    "products",
+    "readyz",
+    "search",
+];
+
+/// Returns the endpoint label for the path.
+pub fn endpoint(path: &str) -> &'static str {
+    let segment = path.trim_start_matches('/').split('/').next().unwrap_or_default();
+    ENDPOINTS.iter().find(|endpoint| **endpoint == segment).copied().unwrap_or("other")
+}
+
+#[derive(Clone, Debug, Default)]
+struct Histogram {
+    /// Number of observations in each bucket (not cumulative).
+    buckets: [u64; BUCKETS.len()],
+
+    /// Number of all observations.
+    count: u64,
+
+    /// Sum of all observations in seconds.
+    sum: f64,
+}
+
+impl Histogram {
+    fn observe(&mut self, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        if let Some(index) = BUCKETS.iter().position(|bound| seconds <= *bound) {
+            self.buckets[index] += 1;
+        }
+        self.count += 1;
+        self.sum += seconds;
+    }
+
+    fn render(&self, out: &mut String, name: &str, labels: &str) {
+        let separator = if labels.is_empty() { "" } else { "," };
+        let mut cumulative = 0;
+        for (bound, count) in BUCKETS.iter().zip(self.buckets) {
+            cumulative += count;
+            let _ =
+                writeln!(out, "{name}_bucket{{{labels}{separator}le=\"{bound}\"}} {cumulative}");
+        }
+        let _ = writeln!(out, "{name}_bucket{{{labels}{separator}le=\"+Inf\"}} {}", self.count);
+        let _ = writeln!(out, "{name}_sum{{{labels}}} {}", self.sum);
+        let _ = writeln!(out, "{name}_count{{{labels}}} {}", self.count);
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    /// Number of requests per endpoint and status code.
+    requests: BTreeMap<(&'static str, u16), u64>,
+
+    /// Request durations per endpoint.
+    request_durations: BTreeMap<&'static str, Histogram>,
+
+    /// Durations of the DB queries per query type.
+    query_durations: BTreeMap<&'static str, Histogram>,
+
+    /// Number of failed DB queries per query type.
+    query_errors: BTreeMap<&'static str, u64>,
+}
+
+/// Collects the backend metrics.
+#[derive(Default)]
+pub struct Metrics {
+    inner: Mutex<Inner>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_request(&self, endpoint: &'static str, status: u16, duration: Duration) {
+        let mut inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        *inner.requests.entry((endpoint, status)).or_default() += 1;
+        inner.request_durations.entry(endpoint).or_default().observe(duration);
+    }
+
+    /// Records a DB query of the given type (e.g. `find_product_alternatives`).
+    pub fn record_query(&self, name: &'static str, duration: Duration, success: bool) {
+        let mut inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        inner.query_durations.entry(name).or_default().observe(duration);
+        if !success {
+            *inner.query_errors.entry(name).or_default() += 1;
+        }
+    }
+
+    /// Renders the metrics together with the state of the served dataset.
+    pub fn render(&self, ready: bool, database: &str, version: &str) -> String {
+        let inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut out = String::new();
+
+        out.push_str("# HELP sustainity_http_requests_total Number of handled HTTP requests.\n");
+        out.push_str("# TYPE sustainity_http_requests_total counter\n");
+        for ((endpoint, status), count) in &inner.requests {
+            let _ = writeln!(
+                out,
+                "sustainity_http_requests_total{{endpoint=\"{endpoint}\",status=\"{status}\"}} \
+                 {count}"
+            );
+        }
+
+        out.push_str(
+            "# HELP sustainity_http_request_duration_seconds Duration of HTTP requests.\n",
+        );
+        out.push_str("# TYPE sustainity_http_request_duration_seconds histogram\n");
+        for (endpoint, histogram) in &inner.request_durations {
+            let labels = format!("endpoint=\"{endpoint}\"");
+            histogram.render(&mut out, "sustainity_http_request_duration_seconds", &labels);
+        }
+
+        out.push_str("# HELP sustainity_db_query_duration_seconds Duration of DB queries.\n");
+        out.push_str("# TYPE sustainity_db_query_duration_seconds histogram\n");
+        for (name, histogram) in &inner.query_durations {
+            let labels = format!("query=\"{name}\"");
+            histogram.render(&mut out, "sustainity_db_query_duration_seconds", &labels);
+        }
+
+        out.push_str("# HELP sustainity_db_query_errors_total Number of failed DB queries.\n");
+        out.push_str("# TYPE sustainity_db_query_errors_total counter\n");
+        for (name, count) in &inner.query_errors {
+            let _ = writeln!(out, "sustainity_db_query_errors_total{{query=\"{name}\"}} {count}");
+        }
+
+        out.push_str("# HELP sustainity_dataset_ready Whether a complete dataset is served.\n");
+        out.push_str("# TYPE sustainity_dataset_ready gauge\n");
+        let _ = writeln!(out, "sustainity_dataset_ready {}", u8::from(ready));
+
+        out.push_str("# HELP sustainity_dataset_info The served dataset.\n");
+        out.push_str("# TYPE sustainity_dataset_info gauge\n");
+        let _ = writeln!(
+            out,
+            "sustainity_dataset_info{{database=\"{}\",version=\"{}\"}} 1",
+            escape(database),
+            escape(version)
+        );
+
+        out
+    }
+}
+
+/// Escapes a label value.
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn endpoint_labels() {
+        assert_eq!(endpoint("/product/gtin/123"), "product");
+        assert_eq!(endpoint("/search/text"), "search");
+        assert_eq!(endpoint("/"), "other");
+        assert_eq!(endpoint("/wp-admin/index.php"), "other");
+    }
+
+    #[test]
+    fn rendering() {
+        let metrics = Metrics::new();
+        metrics.record_request("product", 200, Duration::from_millis(20));
+        metrics.record_request("product", 200, Duration::from_millis(200));
+        metrics.record_request("product", 404, Duration::from_secs(20));
+        metrics.record_query("get_product", Duration::from_millis(1), false);
+        metrics.record_query("get_product", Duration::from_millis(30), true);
+        metrics.record_query("find_product_alternatives", Duration::from_secs(3), true);
+
+        let text = metrics.render(true, "sustainity", "v\"1");
+        assert!(text
+            .contains("sustainity_http_requests_total{endpoint=\"product\",status=\"200\"} 2\n"));
+        assert!(text.contains(
+            "sustainity_http_request_duration_seconds_bucket{endpoint=\"product\",le=\"0.025\"} 1\n"
+        ));
+        assert!(text.contains(
+            "sustainity_http_request_duration_seconds_bucket{endpoint=\"product\",le=\"10\"} 2\n"
+        ));
+        assert!(text.contains(
+            "sustainity_http_request_duration_seconds_bucket{endpoint=\"product\",le=\"+Inf\"} 3\n"
+        ));
+        assert!(text.contains(
+            "sustainity_db_query_duration_seconds_bucket{query=\"get_product\",le=\"0.005\"} 1\n"
+        ));
+        assert!(text.contains(
+            "sustainity_db_query_duration_seconds_bucket{query=\"get_product\",le=\"0.05\"} 2\n"
+        ));
+        assert!(text.contains(
+            "sustainity_db_query_duration_seconds_count{query=\"find_product_alternatives\"} 1\n"
+        ));
+        assert!(text.contains("sustainity_db_query_errors_total{query=\"get_product\"} 1\n"));
+        assert!(
+            !text.contains("sustainity_db_query_errors_total{query=\"find_product_alternatives\"}")
+        );
+        assert!(text.contains("sustainity_dataset_ready 1\n"));
+        assert!(text.contains("version=\"v\\\"1\"} 1\n"));
+    }
+}
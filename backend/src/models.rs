@@ -1,8 +1,9 @@
-use std::str::FromStr;
+use std::{collections::BTreeMap, str::FromStr};
 
 use serde::{Deserialize, Serialize};
 
 use sustainity_api::models as api;
+use sustainity_models::{changes::Change, feedback::FeedbackStatus, store::CertificationKind};
 
 fn hack(link: api::TextSearchLink) -> api::TextSearchLinkHack {
     match link {
@@ -25,6 +26,26 @@ pub enum SearchResultId {
     Product(String),
 }
 
+/// Signals of the quality of a search result used to boost better documented entities.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct SearchQuality {
+    /// Number of certifications.
+    #[serde(rename = "num_certifications", default)]
+    pub num_certifications: usize,
+
+    /// The Sustainity score (products only).
+    #[serde(rename = "score", default)]
+    pub score: Option<f64>,
+
+    /// Does the entity have a description?
+    #[serde(rename = "has_description", default)]
+    pub has_description: bool,
+
+    /// Does the entity have an image?
+    #[serde(rename = "has_image", default)]
+    pub has_image: bool,
+}
+
 /// Represents a search result.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct OrganisationSearchResult {
@@ -39,6 +60,10 @@ pub struct OrganisationSearchResult {
     /// Product name.
     #[serde(rename = "name")]
     pub name: Option<sustainity_models::store::Text>,
+
+    /// Quality signals.
+    #[serde(rename = "quality", default)]
+    pub quality: SearchQuality,
 }
 
 impl OrganisationSearchResult {
@@ -86,6 +111,14 @@ pub struct ProductSearchResult {
     /// Product name.
     #[serde(rename = "name")]
     pub name: Option<sustainity_models::store::Text>,
+
+    /// Quality signals.
+    #[serde(rename = "quality", default)]
+    pub quality: SearchQuality,
+
+    /// DB keys of the manufacturers of the product.
+    #[serde(rename = "manufacturer_ids", default)]
+    pub manufacturer_ids: Vec<String>,
 }
 
 impl ProductSearchResult {
@@ -118,3 +151,617 @@ impl ProductSearchResult {
         ))
     }
 }
+
+/// Field of an entity matched by a search query.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MatchedField {
+    /// The displayed name.
+    #[serde(rename = "label")]
+    Label,
+
+    /// Another name of the entity, not the displayed one.
+    #[serde(rename = "alias")]
+    Alias,
+
+    #[serde(rename = "vat")]
+    Vat,
+
+    #[serde(rename = "lei")]
+    Lei,
+
+    #[serde(rename = "registration_number")]
+    RegistrationNumber,
+
+    #[serde(rename = "website")]
+    Website,
+
+    #[serde(rename = "gtin")]
+    Gtin,
+}
+
+/// Range of UTF-16 code units in the label (start inclusive, end exclusive).
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MatchOffset {
+    #[serde(rename = "start")]
+    pub start: usize,
+
+    #[serde(rename = "end")]
+    pub end: usize,
+}
+
+/// Describes why an entity was found.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SearchMatch {
+    /// The matched field.
+    #[serde(rename = "field")]
+    pub field: MatchedField,
+
+    /// The matched token from the query.
+    #[serde(rename = "token")]
+    pub token: String,
+
+    /// Positions of the token in the label (empty if the label was not matched).
+    #[serde(rename = "offsets")]
+    pub offsets: Vec<MatchOffset>,
+}
+
+/// Search result together with information what was matched.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct DetailedSearchResult {
+    #[serde(flatten)]
+    pub result: api::TextSearchResult,
+
+    /// Matches sorted by the field and token.
+    #[serde(rename = "matches")]
+    pub matches: Vec<SearchMatch>,
+
+    /// Manufacturers of a found product (empty for organisations).
+    #[serde(rename = "manufacturers", skip_serializing_if = "Vec::is_empty")]
+    pub manufacturers: Vec<ManufacturerLabel>,
+}
+
+/// Name of a manufacturer of a listed product, taken from the lookup of organisation names.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ManufacturerLabel {
+    /// Preferred name of the organisation.
+    #[serde(rename = "name")]
+    pub name: String,
+
+    /// Wikidata ID of the organisation, if it has one.
+    #[serde(rename = "wiki", skip_serializing_if = "Option::is_none")]
+    pub wiki: Option<String>,
+}
+
+impl From<sustainity_models::store::OrganisationLabel> for ManufacturerLabel {
+    fn from(label: sustainity_models::store::OrganisationLabel) -> Self {
+        Self { name: label.name, wiki: label.wiki }
+    }
+}
+
+/// Product in a list together with the names of its manufacturers.
+#[derive(Serialize, Debug, Clone)]
+pub struct ProductListItem {
+    #[serde(flatten)]
+    pub product: api::ProductShort,
+
+    /// Manufacturers of the product (the ones missing in the lookup are left out).
+    #[serde(rename = "manufacturers")]
+    pub manufacturers: Vec<ManufacturerLabel>,
+}
+
+/// Alternatives to a product in one of its categories.
+#[derive(Serialize, Debug, Clone)]
+pub struct CategoryAlternatives {
+    /// Path of the category.
+    #[serde(rename = "category")]
+    pub category: String,
+
+    /// The alternatives, the best first.
+    #[serde(rename = "alternatives")]
+    pub alternatives: Vec<ProductListItem>,
+}
+
+/// One row of a product comparison.
+///
+/// Holds values of a single compared aspect, one per compared product.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ComparisonRow<T> {
+    /// Name of the compared aspect.
+    #[serde(rename = "name")]
+    pub name: String,
+
+    /// Values of the aspect in the same order as the compared products.
+    #[serde(rename = "values")]
+    pub values: Vec<T>,
+}
+
+impl<T> ComparisonRow<T> {
+    pub fn new(name: impl Into<String>, values: Vec<T>) -> Self {
+        Self { name: name.into(), values }
+    }
+}
+
+/// Side-by-side comparison of several products.
+#[derive(Serialize, Debug, Clone)]
+pub struct ProductComparison {
+    /// Compared products.
+    #[serde(rename = "products")]
+    pub products: Vec<api::ProductShort>,
+
+    /// Presence of certifications.
+    #[serde(rename = "certifications")]
+    pub certifications: Vec<ComparisonRow<bool>>,
+
+    /// Presence of certifications which expired or come from stale source snapshots.
+    ///
+    /// Such certifications are not included in `certifications`.
+    #[serde(rename = "stale_certifications")]
+    pub stale_certifications: Vec<ComparisonRow<bool>>,
+
+    /// Sustainity score and its main subscores.
+    #[serde(rename = "scores")]
+    pub scores: Vec<ComparisonRow<Option<f64>>>,
+
+    /// Other product attributes (categories, regions, manufacturers).
+    #[serde(rename = "attributes")]
+    pub attributes: Vec<ComparisonRow<Vec<String>>>,
+
+    /// Requested IDs for which no product was found.
+    #[serde(rename = "not_found")]
+    pub not_found: Vec<String>,
+}
+
+/// Node of the category tree.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CategoryNode {
+    /// Name of this level of the category.
+    #[serde(rename = "name")]
+    pub name: String,
+
+    /// Full path of the category, usable as its ID.
+    #[serde(rename = "path")]
+    pub path: String,
+
+    /// Number of products assigned directly to this category.
+    #[serde(rename = "num_products")]
+    pub num_products: usize,
+
+    /// Subcategories.
+    #[serde(rename = "children")]
+    pub children: Vec<CategoryNode>,
+}
+
+impl CategoryNode {
+    pub fn new(name: &str, path: &str) -> Self {
+        Self { name: name.to_owned(), path: path.to_owned(), num_products: 0, children: Vec::new() }
+    }
+
+    /// Sorts the subcategories recursively by name.
+    pub fn sort(nodes: &mut [CategoryNode]) {
+        nodes.sort_by(|a, b| a.name.cmp(&b.name));
+        for node in nodes {
+            Self::sort(&mut node.children);
+        }
+    }
+}
+
+/// Page of products from a single category.
+#[derive(Serialize, Debug, Clone)]
+pub struct CategoryProducts {
+    /// Path of the category.
+    #[serde(rename = "category")]
+    pub category: String,
+
+    /// Number of all products in the category.
+    #[serde(rename = "num_products")]
+    pub num_products: usize,
+
+    /// Offset of the first returned product.
+    #[serde(rename = "offset")]
+    pub offset: usize,
+
+    /// Maximal number of returned products.
+    #[serde(rename = "limit")]
+    pub limit: usize,
+
+    /// Products sorted by their Sustainity score.
+    #[serde(rename = "products")]
+    pub products: Vec<ProductListItem>,
+}
+
+/// Statistics of a category shown on its overview page.
+#[derive(Serialize, Debug, Clone)]
+pub struct CategoryStatistics {
+    /// Path of the category.
+    #[serde(rename = "category")]
+    pub category: String,
+
+    /// Number of all products in the category.
+    #[serde(rename = "num_products")]
+    pub num_products: usize,
+
+    /// Number of products with at least one certification.
+    #[serde(rename = "num_certified")]
+    pub num_certified: usize,
+
+    /// Percentage of products with at least one certification.
+    #[serde(rename = "certified_percentage")]
+    pub certified_percentage: f64,
+
+    /// Number of products with each of the certifications.
+    #[serde(rename = "certifications")]
+    pub certifications: BTreeMap<CertificationKind, usize>,
+
+    /// Products with the highest Sustainity score, the best first.
+    #[serde(rename = "best_products")]
+    pub best_products: Vec<api::ProductShort>,
+}
+
+/// Order of products listed on their manufacturer's page.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProductSort {
+    /// Best-scored first.
+    #[default]
+    #[serde(rename = "score")]
+    Score,
+
+    /// Alphabetically by the name.
+    #[serde(rename = "name")]
+    Name,
+
+    /// Grouped by the category, best-scored first within a category.
+    #[serde(rename = "category")]
+    Category,
+}
+
+impl FromStr for ProductSort {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "score" => Ok(Self::Score),
+            "name" => Ok(Self::Name),
+            "category" => Ok(Self::Category),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Page of products of a single organisation.
+#[derive(Serialize, Debug, Clone)]
+pub struct OrganisationProducts {
+    /// Number of all products of the organisation.
+    #[serde(rename = "num_products")]
+    pub num_products: usize,
+
+    /// Order of the products.
+    #[serde(rename = "sort")]
+    pub sort: ProductSort,
+
+    /// Offset of the first returned product.
+    #[serde(rename = "offset")]
+    pub offset: usize,
+
+    /// Maximal number of returned products.
+    #[serde(rename = "limit")]
+    pub limit: usize,
+
+    /// The products.
+    #[serde(rename = "products")]
+    pub products: Vec<api::ProductShort>,
+}
+
+/// Full organisation extended with the data the generated API models have no fields for.
+#[derive(Serialize, Debug, Clone)]
+pub struct OrganisationDetails {
+    /// The organisation as described by the API.
+    #[serde(flatten)]
+    pub organisation: api::OrganisationFull,
+
+    /// Generic attributes (e.g. links to sustainability reports).
+    #[serde(rename = "attributes")]
+    pub attributes: sustainity_models::store::Attributes,
+
+    /// Notable sustainability-related awards.
+    #[serde(rename = "recognitions")]
+    pub recognitions: Vec<sustainity_models::store::Recognition>,
+
+    /// Dated events from the sources (certifications, scores, reports, awards and controversies),
+    /// sorted by date.
+    #[serde(rename = "timeline")]
+    pub timeline: Vec<sustainity_models::store::TimelineEvent>,
+
+    /// Codes of the Textile Exchange material standards the organisation uses (e.g. `grs`).
+    #[serde(rename = "material_standards")]
+    pub material_standards: Vec<String>,
+}
+
+/// Organisation (and possibly product) a web page belongs to.
+#[derive(Serialize, Debug, Clone)]
+pub struct UrlResolution {
+    /// Domain under which the organisation was found.
+    #[serde(rename = "domain")]
+    pub domain: String,
+
+    /// The organisation.
+    #[serde(rename = "organisation")]
+    pub organisation: api::OrganisationShort,
+
+    /// Product of the organisation matching the product name hint, if any.
+    #[serde(rename = "product")]
+    pub product: Option<api::ProductShort>,
+}
+
+/// Estimated carbon footprint of a product.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct CarbonFootprint {
+    /// Emissions in kilograms of CO2 equivalent per 100 g of the product.
+    #[serde(rename = "kg_co2e_per_100g")]
+    pub kg_co2e_per_100g: f64,
+
+    /// Provenance of the value (`estimate` for values computed from average coefficients).
+    #[serde(rename = "provenance")]
+    pub provenance: String,
+
+    /// What the estimate is based on (a category tag or `ingredients`).
+    #[serde(rename = "basis", skip_serializing_if = "Option::is_none")]
+    pub basis: Option<String>,
+}
+
+/// Organisation found when walking the ownership chain of a product.
+#[derive(Deserialize, Debug, Clone)]
+pub struct OwnershipEntry {
+    /// Distance from the product (1 for the manufacturers, 2 for their parents and so on).
+    #[serde(rename = "depth")]
+    pub depth: usize,
+
+    /// DB key of the organisation owned by this one (not set for the manufacturers).
+    #[serde(rename = "owns")]
+    pub owns: Option<String>,
+
+    /// The organisation.
+    #[serde(rename = "organisation")]
+    pub organisation: sustainity_models::store::Organisation,
+}
+
+impl OwnershipEntry {
+    /// Converts the entry into a level of the ownership chain keeping only current certifications.
+    #[must_use]
+    pub fn into_level(self, today: &str) -> OwnershipLevel {
+        OwnershipLevel {
+            depth: self.depth,
+            id: self.organisation.db_key,
+            name: self.organisation.names.into_iter().next().map(|name| name.text),
+            ids: self.organisation.ids,
+            certifications: self.organisation.certifications.current(today),
+            owns: self.owns,
+        }
+    }
+}
+
+/// Single level of the ownership chain of a product with the certifications found there.
+#[derive(Serialize, Debug, Clone)]
+pub struct OwnershipLevel {
+    /// Distance from the product (1 for the manufacturers, 2 for their parents and so on).
+    #[serde(rename = "depth")]
+    pub depth: usize,
+
+    /// DB key of the organisation.
+    #[serde(rename = "id")]
+    pub id: String,
+
+    /// Name of the organisation.
+    #[serde(rename = "name", skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// IDs of the organisation.
+    #[serde(rename = "ids")]
+    pub ids: sustainity_models::store::OrganisationIds,
+
+    /// Current certifications of the organisation.
+    #[serde(rename = "certifications")]
+    pub certifications: sustainity_models::store::Certifications,
+
+    /// DB key of the organisation owned by this one (not set for the manufacturers).
+    #[serde(rename = "owns", skip_serializing_if = "Option::is_none")]
+    pub owns: Option<String>,
+}
+
+/// Options for choosing product alternatives.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlternativesOptions {
+    /// Skip products made by any of the manufacturers of the original product.
+    pub exclude_same_manufacturer: bool,
+
+    /// Skip products without any certification.
+    pub require_certification: bool,
+
+    /// Maximal number of alternatives per category.
+    pub max_per_category: usize,
+}
+
+impl AlternativesOptions {
+    /// Number of alternatives per category returned if not specified otherwise.
+    pub const DEFAULT_MAX_PER_CATEGORY: usize = 10;
+
+    /// Maximal number of alternatives per category that can be requested.
+    pub const MAX_PER_CATEGORY: usize = 50;
+}
+
+impl Default for AlternativesOptions {
+    fn default() -> Self {
+        Self {
+            exclude_same_manufacturer: false,
+            require_certification: false,
+            max_per_category: Self::DEFAULT_MAX_PER_CATEGORY,
+        }
+    }
+}
+
+/// Alternatives of a product precomputed for one of its categories.
+#[derive(Deserialize, Debug, Clone)]
+pub struct PrecomputedAlternatives<T> {
+    /// The alternatives, the best first.
+    #[serde(rename = "alternatives")]
+    pub alternatives: Vec<T>,
+
+    /// All the alternatives are listed, even if there are fewer of them than requested.
+    #[serde(rename = "exhaustive", default)]
+    pub exhaustive: bool,
+}
+
+/// Page of changes since a dataset version.
+#[derive(Serialize, Debug, Clone)]
+pub struct ChangesPage {
+    /// Version the changes are listed since.
+    #[serde(rename = "since")]
+    pub since: String,
+
+    /// Currently served version.
+    #[serde(rename = "version")]
+    pub version: String,
+
+    /// Offset of the first returned change.
+    #[serde(rename = "offset")]
+    pub offset: usize,
+
+    /// Maximal number of returned changes.
+    #[serde(rename = "limit")]
+    pub limit: usize,
+
+    /// Changes ordered from the oldest.
+    ///
+    /// An entity may be listed more than once if it changed in more versions.
+    #[serde(rename = "changes")]
+    pub changes: Vec<Change>,
+}
+
+/// Current and former slugs of a library topic.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct LibrarySlug {
+    /// ID of the topic.
+    #[serde(rename = "id")]
+    pub id: String,
+
+    /// Slug the article is currently available under.
+    #[serde(rename = "slug")]
+    pub slug: String,
+
+    /// Slugs redirected to the current one.
+    #[serde(rename = "former_slugs", skip_serializing_if = "Vec::is_empty")]
+    pub former_slugs: Vec<String>,
+}
+
+/// Result of looking up a library article by its slug.
+#[derive(Debug, Clone)]
+pub enum LibraryLookup {
+    /// The slug is the current slug of the article.
+    Found(Box<api::LibraryItemFull>),
+
+    /// The slug is a former slug of the article, which is now available under the given one.
+    Moved(String),
+}
+
+/// Permission granted to an API key.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Scope {
+    /// Requests are rate-limited per key instead of per IP address.
+    #[serde(rename = "partner")]
+    Partner,
+
+    /// Access to the administration endpoints.
+    #[serde(rename = "admin")]
+    Admin,
+
+    /// Access to the moderation endpoints.
+    #[serde(rename = "moderation")]
+    Moderation,
+}
+
+/// API key with its permissions.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ApiKey {
+    /// Name of the key owner (used only for logging).
+    #[serde(rename = "name")]
+    pub name: String,
+
+    /// The secret key.
+    #[serde(rename = "key")]
+    pub key: String,
+
+    /// Scopes granted to the key.
+    #[serde(rename = "scopes", default)]
+    pub scopes: Vec<Scope>,
+}
+
+impl ApiKey {
+    pub fn has_scope(&self, scope: Scope) -> bool {
+        self.scopes.contains(&scope)
+    }
+}
+
+/// Change of the moderation status of a feedback.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FeedbackStatusChange {
+    /// The new status.
+    #[serde(rename = "status")]
+    pub status: FeedbackStatus,
+
+    /// Note explaining the decision.
+    #[serde(rename = "note", default)]
+    pub note: Option<String>,
+}
+
+/// Response to a feedback submission.
+#[derive(Serialize, Debug, Clone)]
+pub struct FeedbackReceipt {
+    /// ID of the stored feedback.
+    #[serde(rename = "id")]
+    pub id: String,
+}
+
+/// Response to a product request submission.
+#[derive(Serialize, Debug, Clone)]
+pub struct ProductRequestReceipt {
+    /// ID of the stored request.
+    #[serde(rename = "id")]
+    pub id: String,
+}
+
+/// Webhook called when the served dataset changes.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Webhook {
+    /// URL the notification is posted to.
+    #[serde(rename = "url")]
+    pub url: String,
+
+    /// Token sent as a bearer token, so that the receiver can verify the sender.
+    #[serde(rename = "token", default)]
+    pub token: Option<String>,
+}
+
+/// Number of entries in a collection before and after a dataset update.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct CollectionChange {
+    #[serde(rename = "previous", skip_serializing_if = "Option::is_none")]
+    pub previous: Option<usize>,
+
+    #[serde(rename = "current")]
+    pub current: usize,
+}
+
+/// Notification sent to the webhooks after a dataset update.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct DatasetUpdate {
+    /// Version of the new dataset.
+    #[serde(rename = "version")]
+    pub version: String,
+
+    /// Version of the previously served dataset, if any.
+    #[serde(rename = "previous_version", skip_serializing_if = "Option::is_none")]
+    pub previous_version: Option<String>,
+
+    /// Changes in the sizes of the collections.
+    #[serde(rename = "changes")]
+    pub changes: BTreeMap<String, CollectionChange>,
+}
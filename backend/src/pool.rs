@@ -0,0 +1,123 @@
+//! Shared database handles and the limit of concurrent queries.
+//!
+//! Establishing a connection and opening a database costs two round trips to the server, so the
+//! opened database handles are kept and shared by the queries (the HTTP connections are managed by
+//! the HTTP client). The number of queries running at once is limited by a semaphore.
+//! A query waiting for a free slot longer than the acquire timeout fails with
+//! `DbError::PoolExhausted`, which is reported to the clients as 503 instead of piling up requests.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, PoisonError, RwLock},
+    time::Duration,
+};
+
+use arangors::Connection;
+use snafu::prelude::*;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::{
+    config::{PoolConfig, SecretConfig},
+    errors,
+};
+
+/// Handle to a database.
+pub type Database = arangors::Database<arangors::client::reqwest::ReqwestClient>;
+
+/// Database handle reserved for a single query.
+///
+/// The slot in the pool is released when the lease is dropped.
+pub struct Lease {
+    pub db: Database,
+    _permit: OwnedSemaphorePermit,
+}
+
+pub struct Pool {
+    secret: SecretConfig,
+    config: PoolConfig,
+    permits: Arc<Semaphore>,
+    databases: RwLock<HashMap<String, Database>>,
+}
+
+impl Pool {
+    pub fn new(secret: SecretConfig, config: PoolConfig) -> Self {
+        let permits = Arc::new(Semaphore::new(config.max_size));
+        Self { secret, config, permits, databases: RwLock::new(HashMap::new()) }
+    }
+
+    pub fn config(&self) -> &PoolConfig {
+        &self.config
+    }
+
+    /// Waits for a free slot and returns a handle to the named database.
+    pub async fn acquire(&self, name: &str) -> Result<Lease, errors::DbError> {
+        let timeout = self.config.acquire_timeout;
+        let permit = match tokio::time::timeout(timeout, self.permits.clone().acquire_owned()).await
+        {
+            Ok(Ok(permit)) => permit,
+            Ok(Err(_)) | Err(_) => return Err(errors::DbError::PoolExhausted { timeout }),
+        };
+        let db = self.database(name).await?;
+        Ok(Lease { db, _permit: permit })
+    }
+
+    /// Forgets the handle to the named database so that the next query opens it again.
+    ///
+    /// Called after connection errors, the handle might be bound to a server which went away.
+    pub fn invalidate(&self, name: &str) {
+        self.databases.write().unwrap_or_else(PoisonError::into_inner).remove(name);
+    }
+
+    async fn database(&self, name: &str) -> Result<Database, errors::DbError> {
+        if let Some(db) = self.databases.read().unwrap_or_else(PoisonError::into_inner).get(name) {
+            return Ok(db.clone());
+        }
+
+        let conn = Connection::establish_basic_auth(
+            &self.secret.host,
+            &self.secret.user,
+            &self.secret.password,
+        )
+        .await
+        .context(errors::ConnectSnafu)?;
+        let db = conn.db(name).await.context(errors::DatabaseSnafu { name })?;
+        self.databases
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(name.to_owned(), db.clone());
+        Ok(db)
+    }
+}
+
+/// Returns the delay before the given retry (counted from one), doubling with each retry.
+pub fn backoff(initial: Duration, retry: u32) -> Duration {
+    initial.saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles() {
+        let initial = Duration::from_millis(100);
+        assert_eq!(backoff(initial, 1), Duration::from_millis(100));
+        assert_eq!(backoff(initial, 2), Duration::from_millis(200));
+        assert_eq!(backoff(initial, 4), Duration::from_millis(800));
+    }
+
+    #[tokio::test]
+    async fn exhausted() {
+        let config = PoolConfig {
+            max_size: 1,
+            acquire_timeout: Duration::from_millis(10),
+            ..PoolConfig::default()
+        };
+        let pool = Pool::new(SecretConfig::new_debug(), config);
+        let _taken = pool.permits.clone().acquire_owned().await.unwrap();
+        assert!(matches!(
+            pool.acquire("sustainity").await,
+            Err(errors::DbError::PoolExhausted { .. })
+        ));
+    }
+}
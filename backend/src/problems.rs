@@ -0,0 +1,342 @@
+//! Error responses.
+//!
+//! All failures are reported with a problem details body (RFC 9457) carrying a machine-readable
+//! code and the ID of the request, so that clients can handle them programmatically and users can
+//! refer to them when reporting issues:
+//!
+//! ```json
+//! {
+//!   "type": "https://sustainity.dev/errors/INVALID_GTIN",
+//!   "code": "INVALID_GTIN",
+//!   "title": "Invalid GTIN",
+//!   "status": 400,
+//!   "detail": "`123` is not a valid GTIN",
+//!   "request_id": "7d2c..."
+//! }
+//! ```
+
+use hyper::StatusCode;
+use serde::Serialize;
+
+use crate::errors::BackendError;
+
+/// Content type of the error responses.
+pub const CONTENT_TYPE: &str = "application/problem+json";
+
+/// Prefix of the URIs identifying the error types.
+const TYPE_PREFIX: &str = "https://sustainity.dev/errors/";
+
+tokio::task_local! {
+    /// ID of the request being handled.
+    static REQUEST_ID: String;
+}
+
+/// Runs the future with the request ID available to the error responses.
+pub async fn with_request_id<F: std::future::Future>(id: String, future: F) -> F::Output {
+    REQUEST_ID.scope(id, future).await
+}
+
+/// Runs the function with the request ID available to the error responses.
+pub fn with_request_id_sync<R>(id: String, f: impl FnOnce() -> R) -> R {
+    REQUEST_ID.sync_scope(id, f)
+}
+
+/// Returns the ID of the request being handled, if any.
+fn request_id() -> Option<String> {
+    REQUEST_ID.try_with(Clone::clone).ok()
+}
+
+/// Machine-readable error codes.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    /// The requested entity does not exist.
+    NotFound,
+
+    /// The GTIN is malformed or its check digit is wrong.
+    InvalidGtin,
+
+    /// The region is not a known ISO 3166-1 country code.
+    InvalidRegion,
+
+    /// The ID of an entity is malformed.
+    InvalidId,
+
+    /// The ID variant (e.g. `wiki` or `gtin`) is unknown.
+    InvalidIdVariant,
+
+    /// A query parameter has an invalid value.
+    InvalidParameter,
+
+    /// A required query parameter is missing.
+    MissingParameter,
+
+    /// The request body cannot be read or parsed.
+    InvalidBody,
+
+    /// The request body exceeds the size limit.
+    PayloadTooLarge,
+
+    /// The API key is invalid or missing.
+    Unauthorized,
+
+    /// The API key lacks the scope required by the endpoint.
+    Forbidden,
+
+    /// The client made too many requests.
+    RateLimited,
+
+    /// The requested dataset snapshot is not served.
+    UnknownSnapshot,
+
+    /// The changes since the given version are no longer known.
+    ChangesExpired,
+
+    /// The HTTP method is not supported by the endpoint.
+    MethodNotAllowed,
+
+    /// The request cannot be handled for another reason (e.g. an unsupported media type).
+    BadRequest,
+
+    /// The request conflicts with the current state (e.g. an invalid status transition).
+    Conflict,
+
+    /// The dataset to be served is incomplete.
+    DatasetIncomplete,
+
+    /// No dataset is being served yet (e.g. it is being loaded).
+    DatasetReloading,
+
+    /// The backend is overloaded, the request should be repeated later.
+    ServiceBusy,
+
+    /// An unexpected error.
+    InternalError,
+}
+
+impl ErrorCode {
+    /// Returns the HTTP status of the responses with this code.
+    pub fn status(self) -> StatusCode {
+        match self {
+            Self::NotFound | Self::UnknownSnapshot => StatusCode::NOT_FOUND,
+            Self::InvalidGtin
+            | Self::InvalidRegion
+            | Self::InvalidId
+            | Self::InvalidIdVariant
+            | Self::InvalidParameter
+            | Self::MissingParameter
+            | Self::InvalidBody
+            | Self::BadRequest => StatusCode::BAD_REQUEST,
+            Self::MethodNotAllowed => StatusCode::METHOD_NOT_ALLOWED,
+            Self::PayloadTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            Self::Unauthorized => StatusCode::UNAUTHORIZED,
+            Self::Forbidden => StatusCode::FORBIDDEN,
+            Self::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+            Self::ChangesExpired => StatusCode::GONE,
+            Self::Conflict | Self::DatasetIncomplete => StatusCode::CONFLICT,
+            Self::DatasetReloading | Self::ServiceBusy => StatusCode::SERVICE_UNAVAILABLE,
+            Self::InternalError => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Returns a short human-readable summary of the error.
+    pub fn title(self) -> &'static str {
+        match self {
+            Self::NotFound => "Not found",
+            Self::InvalidGtin => "Invalid GTIN",
+            Self::InvalidRegion => "Invalid region",
+            Self::InvalidId => "Invalid ID",
+            Self::InvalidIdVariant => "Invalid ID variant",
+            Self::InvalidParameter => "Invalid parameter",
+            Self::MissingParameter => "Missing parameter",
+            Self::InvalidBody => "Invalid request body",
+            Self::PayloadTooLarge => "Request body too large",
+            Self::Unauthorized => "Unauthorized",
+            Self::Forbidden => "Forbidden",
+            Self::RateLimited => "Too many requests",
+            Self::UnknownSnapshot => "Unknown dataset snapshot",
+            Self::ChangesExpired => "Changes expired",
+            Self::MethodNotAllowed => "Method not allowed",
+            Self::BadRequest => "Bad request",
+            Self::Conflict => "Conflict",
+            Self::DatasetIncomplete => "Dataset incomplete",
+            Self::DatasetReloading => "Dataset reloading",
+            Self::ServiceBusy => "Service busy",
+            Self::InternalError => "Internal error",
+        }
+    }
+
+    /// Returns the code of a response with the given status which carries no problem details.
+    ///
+    /// Used for the responses of the generated service. The status of such a response is kept (see
+    /// `Problem::with_status`), so the codes of statuses without a code of their own only tell
+    /// whether it was the client or the server that failed.
+    pub fn from_status(status: StatusCode) -> Self {
+        match status {
+            StatusCode::NOT_FOUND => Self::NotFound,
+            StatusCode::BAD_REQUEST => Self::InvalidParameter,
+            StatusCode::UNAUTHORIZED => Self::Unauthorized,
+            StatusCode::FORBIDDEN => Self::Forbidden,
+            StatusCode::METHOD_NOT_ALLOWED => Self::MethodNotAllowed,
+            StatusCode::PAYLOAD_TOO_LARGE => Self::PayloadTooLarge,
+            StatusCode::TOO_MANY_REQUESTS => Self::RateLimited,
+            StatusCode::SERVICE_UNAVAILABLE => Self::ServiceBusy,
+            status if status.is_client_error() => Self::BadRequest,
+            _ => Self::InternalError,
+        }
+    }
+}
+
+impl From<&BackendError> for ErrorCode {
+    fn from(error: &BackendError) -> Self {
+        if error.is_unavailable() {
+            Self::ServiceBusy
+        } else {
+            Self::InternalError
+        }
+    }
+}
+
+/// Describes an invalid request parameter.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct FieldError {
+    /// Name of the parameter.
+    #[serde(rename = "field")]
+    pub field: &'static str,
+
+    /// Code of the error.
+    #[serde(rename = "code")]
+    pub code: ErrorCode,
+
+    /// What is wrong with the value.
+    #[serde(rename = "message")]
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: &'static str, code: ErrorCode, message: String) -> Self {
+        Self { field, code, message }
+    }
+}
+
+/// Body of an error response.
+#[derive(Serialize, Debug)]
+pub struct Problem<'a> {
+    #[serde(rename = "type")]
+    pub kind: String,
+
+    #[serde(rename = "code")]
+    pub code: ErrorCode,
+
+    #[serde(rename = "title")]
+    pub title: &'static str,
+
+    #[serde(rename = "status")]
+    pub status: u16,
+
+    #[serde(rename = "detail")]
+    pub detail: &'a str,
+
+    #[serde(rename = "request_id", skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+
+    /// Invalid parameters of the request.
+    #[serde(rename = "errors", skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<FieldError>,
+}
+
+impl<'a> Problem<'a> {
+    /// Describes an error of the request being handled.
+    pub fn new(code: ErrorCode, detail: &'a str) -> Self {
+        let name = serde_json::to_value(code)
+            .ok()
+            .and_then(|value| value.as_str().map(str::to_owned))
+            .unwrap_or_default();
+        Self {
+            kind: format!("{TYPE_PREFIX}{name}"),
+            code,
+            title: code.title(),
+            status: code.status().as_u16(),
+            detail,
+            request_id: request_id(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// Describes invalid parameters of the request being handled.
+    ///
+    /// The code is the code of the errors if they all agree, `INVALID_PARAMETER` otherwise.
+    pub fn invalid(detail: &'a str, errors: Vec<FieldError>) -> Self {
+        let code = match errors.first() {
+            Some(first) if errors.iter().all(|error| error.code == first.code) => first.code,
+            _ => ErrorCode::InvalidParameter,
+        };
+        Self { errors, ..Self::new(code, detail) }
+    }
+
+    /// Overrides the status (e.g. to match the status of a response the problem is attached to).
+    #[must_use]
+    pub fn with_status(self, status: StatusCode) -> Self {
+        Self { status: status.as_u16(), ..self }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serialization() {
+        let problem = with_request_id_sync("abc".to_owned(), || {
+            Problem::new(ErrorCode::InvalidGtin, "`123` is not a valid GTIN")
+        });
+        assert_eq!(
+            serde_json::to_value(&problem).unwrap(),
+            serde_json::json!({
+                "type": "https://sustainity.dev/errors/INVALID_GTIN",
+                "code": "INVALID_GTIN",
+                "title": "Invalid GTIN",
+                "status": 400,
+                "detail": "`123` is not a valid GTIN",
+                "request_id": "abc",
+            })
+        );
+
+        let problem = Problem::new(ErrorCode::DatasetReloading, "Dataset not ready");
+        assert_eq!(problem.status, 503);
+        assert_eq!(problem.request_id, None);
+    }
+
+    #[test]
+    fn invalid_fields() {
+        let error = |field, code| FieldError::new(field, code, String::new());
+        let problem = Problem::invalid("", vec![error("id", ErrorCode::InvalidGtin)]);
+        assert_eq!(problem.code, ErrorCode::InvalidGtin);
+        let problem = Problem::invalid(
+            "",
+            vec![error("id", ErrorCode::InvalidGtin), error("region", ErrorCode::InvalidRegion)],
+        );
+        assert_eq!(problem.code, ErrorCode::InvalidParameter);
+        assert_eq!(problem.status, 400);
+        assert_eq!(problem.errors.len(), 2);
+    }
+
+    #[test]
+    fn statuses() {
+        assert_eq!(ErrorCode::from_status(StatusCode::NOT_FOUND), ErrorCode::NotFound);
+        assert_eq!(ErrorCode::from_status(StatusCode::BAD_GATEWAY), ErrorCode::InternalError);
+        assert_eq!(
+            ErrorCode::from_status(StatusCode::METHOD_NOT_ALLOWED),
+            ErrorCode::MethodNotAllowed
+        );
+        assert_eq!(
+            ErrorCode::from_status(StatusCode::UNSUPPORTED_MEDIA_TYPE),
+            ErrorCode::BadRequest
+        );
+
+        let code = ErrorCode::from_status(StatusCode::BAD_GATEWAY);
+        let problem = Problem::new(code, code.title()).with_status(StatusCode::BAD_GATEWAY);
+        assert_eq!(problem.status, 502);
+        assert_eq!(ErrorCode::RateLimited.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+}
@@ -0,0 +1,117 @@
+//! Requests for products missing in the dataset.
+
+use sustainity_models::{
+    gtin,
+    product_requests::{ProductRequest, ProductRequestSubmission},
+};
+
+use crate::{db::Db, errors::BackendError, timestamps};
+
+/// Maximal length of the name and the brand.
+const MAX_NAME_LENGTH: usize = 200;
+
+/// Maximal length of the photo URL.
+const MAX_URL_LENGTH: usize = 500;
+
+/// Trims the value, turning empty values to `None`.
+fn clean(value: Option<String>) -> Option<String> {
+    value.map(|value| value.trim().to_owned()).filter(|value| !value.is_empty())
+}
+
+/// Checks the submission and brings it to the stored form (e.g. normalises the GTIN).
+///
+/// Returns a description of the problem if the submission is invalid.
+pub fn validate(
+    submission: ProductRequestSubmission,
+) -> Result<ProductRequestSubmission, &'static str> {
+    let submission = ProductRequestSubmission {
+        gtin: clean(submission.gtin),
+        name: clean(submission.name),
+        brand: clean(submission.brand),
+        photo_url: clean(submission.photo_url),
+    };
+    if submission.gtin.is_none() && submission.name.is_none() {
+        return Err("Either the GTIN or the name must be given");
+    }
+    let gtin = match &submission.gtin {
+        Some(gtin) => Some(gtin::normalize(gtin).map_err(|_| "The GTIN is invalid")?),
+        None => None,
+    };
+    for value in [&submission.name, &submission.brand].into_iter().flatten() {
+        if value.chars().count() > MAX_NAME_LENGTH {
+            return Err("The name or the brand is too long");
+        }
+    }
+    if let Some(url) = &submission.photo_url {
+        if !(url.starts_with("https://") || url.starts_with("http://")) {
+            return Err("The photo URL must be an HTTP(S) URL");
+        }
+        if url.chars().count() > MAX_URL_LENGTH {
+            return Err("The photo URL is too long");
+        }
+    }
+    Ok(ProductRequestSubmission { gtin, ..submission })
+}
+
+/// Stores the (validated) submission and returns the ID of the request.
+///
+/// Requests for the same GTIN are merged: they are counted and missing details are filled in.
+pub async fn submit(submission: ProductRequestSubmission, db: &Db) -> Result<String, BackendError> {
+    let created = timestamps::now();
+    let request = ProductRequest {
+        db_key: submission.gtin.clone(),
+        submission,
+        count: 1,
+        updated: created.clone(),
+        created,
+    };
+    let has_key = request.db_key.is_some();
+    let request = serde_json::to_value(&request)?;
+    let key = if has_key {
+        db.upsert_product_request(request).await?
+    } else {
+        db.insert_product_request(request).await?
+    };
+    Ok(key.unwrap_or_default())
+}
+
+/// Lists the requests, the most requested first.
+pub async fn list(
+    offset: usize,
+    limit: usize,
+    db: &Db,
+) -> Result<Vec<ProductRequest>, BackendError> {
+    Ok(db.get_product_requests(offset, limit).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn submission(gtin: Option<&str>, name: Option<&str>) -> ProductRequestSubmission {
+        ProductRequestSubmission {
+            gtin: gtin.map(String::from),
+            name: name.map(String::from),
+            ..ProductRequestSubmission::default()
+        }
+    }
+
+    #[test]
+    fn validation() {
+        let valid = validate(submission(Some(" 8712345000011 "), Some(" "))).unwrap();
+        assert_eq!(valid.gtin.as_deref(), Some("08712345000011"));
+        assert_eq!(valid.name, None);
+
+        assert!(validate(submission(None, Some("Fairphone 6"))).is_ok());
+        assert!(validate(submission(None, None)).is_err());
+        assert!(validate(submission(Some(""), Some(""))).is_err());
+        assert!(validate(submission(Some("8712345000012"), None)).is_err());
+        assert!(validate(submission(None, Some(&"x".repeat(201)))).is_err());
+
+        let mut with_photo = submission(None, Some("Fairphone 6"));
+        with_photo.photo_url = Some("https://example.com/fp6.jpg".to_owned());
+        assert!(validate(with_photo.clone()).is_ok());
+        with_photo.photo_url = Some("javascript:alert(1)".to_owned());
+        assert!(validate(with_photo).is_err());
+    }
+}
@@ -0,0 +1,292 @@
+//! Tokenizer and parser for text search queries.
+//!
+//! Supported syntax:
+//! - plain words: `fairphone`
+//! - quoted phrases: `"fair phone"`
+//! - field prefixes: `brand:fairphone`, `gtin:8712345678906`, `site:fairphone.com`
+//! - exclusions: `-samsung`, `-"galaxy s"`
+
+use std::{iter::Peekable, str::Chars};
+
+use sustainity_models::keywords::{self, StopWords};
+
+/// Field a search term is restricted to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Field {
+    /// Name of the brand or producer.
+    Brand,
+
+    /// GTIN of the product.
+    Gtin,
+
+    /// Website of the producer.
+    Site,
+}
+
+impl Field {
+    fn from_prefix(prefix: &str) -> Option<Self> {
+        match prefix.to_lowercase().as_str() {
+            "brand" => Some(Self::Brand),
+            "gtin" => Some(Self::Gtin),
+            "site" => Some(Self::Site),
+            _ => None,
+        }
+    }
+}
+
+/// Single token of a search query.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Token {
+    /// Plain word.
+    Word(String),
+
+    /// Quoted phrase.
+    Phrase(String),
+
+    /// Term restricted to a field.
+    Field(Field, String),
+
+    /// Term which must not appear in the results.
+    Exclusion(String),
+}
+
+/// Reads characters up to the closing quote (or the end of the input).
+fn read_quoted(chars: &mut Peekable<Chars>) -> String {
+    let mut text = String::new();
+    for c in chars.by_ref() {
+        if c == '"' {
+            break;
+        }
+        text.push(c);
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Reads one (possibly quoted or prefixed) term.
+fn read_term(chars: &mut Peekable<Chars>) -> (Option<Field>, String, bool) {
+    if chars.next_if_eq(&'"').is_some() {
+        return (None, read_quoted(chars), true);
+    }
+
+    let mut field = None;
+    let mut text = String::new();
+    while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+        if c == ':' && field.is_none() {
+            if let Some(prefix) = Field::from_prefix(&text) {
+                field = Some(prefix);
+                text.clear();
+                if chars.next_if_eq(&'"').is_some() {
+                    return (field, read_quoted(chars), true);
+                }
+                continue;
+            }
+        }
+        text.push(c);
+    }
+    (field, text, false)
+}
+
+/// Splits the query into tokens.
+pub fn tokenize(query: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = query.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.peek().is_none() {
+            break;
+        }
+
+        let excluded = chars.next_if_eq(&'-').is_some();
+        let (field, text, quoted) = read_term(&mut chars);
+        if text.is_empty() {
+            continue;
+        }
+
+        let token = if excluded {
+            Token::Exclusion(text)
+        } else if let Some(field) = field {
+            Token::Field(field, text)
+        } else if quoted {
+            Token::Phrase(text)
+        } else {
+            Token::Word(text)
+        };
+        tokens.push(token);
+    }
+    tokens
+}
+
+/// Parsed search query.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SearchQuery {
+    /// Folded keywords from plain words and phrases in order of appearance, without stop words.
+    pub keywords: Vec<String>,
+
+    /// Number of plain words.
+    pub num_words: usize,
+
+    /// Folded phrases which must appear in the results.
+    pub phrases: Vec<String>,
+
+    /// Terms restricted to fields.
+    pub fields: Vec<(Field, String)>,
+
+    /// Folded terms which must not appear in the results.
+    pub exclusions: Vec<String>,
+}
+
+impl SearchQuery {
+    pub fn parse(query: &str, stop_words: &StopWords) -> Self {
+        let mut result = Self::default();
+        for token in tokenize(query) {
+            match token {
+                Token::Word(word) => {
+                    result.keywords.extend(keywords::tokenize(&word));
+                    result.num_words += 1;
+                }
+                Token::Phrase(phrase) => {
+                    let phrase = keywords::fold(&phrase);
+                    result.keywords.extend(keywords::tokenize(&phrase));
+                    result.phrases.push(phrase);
+                }
+                Token::Field(field, value) => result.fields.push((field, value)),
+                Token::Exclusion(term) => result.exclusions.push(keywords::fold(&term)),
+            }
+        }
+        result.keywords = stop_words.filter(result.keywords);
+        result
+    }
+
+    /// Returns the word if the query consists only of a single plain word.
+    pub fn single_word(&self) -> Option<&str> {
+        let is_single = self.num_words == 1
+            && self.phrases.is_empty()
+            && self.fields.is_empty()
+            && self.exclusions.is_empty();
+        if is_single {
+            self.keywords.first().map(String::as_str)
+        } else {
+            None
+        }
+    }
+
+    /// Checks if the label satisfies the phrase and exclusion constraints.
+    pub fn accepts(&self, label: &str) -> bool {
+        let label = keywords::fold(label);
+        let words: Vec<&str> = label.split_whitespace().collect();
+        self.phrases.iter().all(|phrase| label.contains(phrase.as_str()))
+            && self.exclusions.iter().all(|excluded| {
+                if excluded.contains(' ') {
+                    !label.contains(excluded.as_str())
+                } else {
+                    !words.contains(&excluded.as_str())
+                }
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(text: &str) -> Token {
+        Token::Word(text.to_owned())
+    }
+
+    fn parse(query: &str) -> SearchQuery {
+        SearchQuery::parse(query, &StopWords::default())
+    }
+
+    #[test]
+    fn plain_words() {
+        assert_eq!(tokenize("  fair   phone "), vec![word("fair"), word("phone")]);
+        assert_eq!(tokenize(""), vec![]);
+        assert_eq!(tokenize("   "), vec![]);
+    }
+
+    #[test]
+    fn phrases() {
+        assert_eq!(
+            tokenize("\"fair  phone\" 4"),
+            vec![Token::Phrase("fair phone".to_owned()), word("4")]
+        );
+
+        // Unterminated quotes span to the end of the query
+        assert_eq!(tokenize("\"fair phone"), vec![Token::Phrase("fair phone".to_owned())]);
+
+        // Empty quotes are ignored
+        assert_eq!(tokenize("\"\" phone"), vec![word("phone")]);
+    }
+
+    #[test]
+    fn fields() {
+        assert_eq!(
+            tokenize("brand:fairphone gtin:8712345678906 site:fairphone.com"),
+            vec![
+                Token::Field(Field::Brand, "fairphone".to_owned()),
+                Token::Field(Field::Gtin, "8712345678906".to_owned()),
+                Token::Field(Field::Site, "fairphone.com".to_owned()),
+            ]
+        );
+        assert_eq!(
+            tokenize("BRAND:\"fair phone\""),
+            vec![Token::Field(Field::Brand, "fair phone".to_owned())]
+        );
+
+        // Unknown prefixes are a part of the word
+        assert_eq!(tokenize("color:red"), vec![word("color:red")]);
+
+        // Empty values are ignored
+        assert_eq!(tokenize("brand: phone"), vec![word("phone")]);
+    }
+
+    #[test]
+    fn exclusions() {
+        assert_eq!(
+            tokenize("phone -samsung -\"galaxy s\" - x"),
+            vec![
+                word("phone"),
+                Token::Exclusion("samsung".to_owned()),
+                Token::Exclusion("galaxy s".to_owned()),
+                word("x"),
+            ]
+        );
+    }
+
+    #[test]
+    fn search_query() {
+        let query = parse("Fairphone \"Fair Phone\" brand:x -Samsung");
+        assert_eq!(query.keywords, vec!["fairphone", "fair", "phone"]);
+        assert_eq!(query.phrases, vec!["fair phone"]);
+        assert_eq!(query.fields, vec![(Field::Brand, "x".to_owned())]);
+        assert_eq!(query.exclusions, vec!["samsung"]);
+        assert_eq!(query.single_word(), None);
+
+        assert_eq!(parse(" Fairphone ").single_word(), Some("fairphone"));
+        assert_eq!(parse("Fairphone, 4!").keywords, vec!["fairphone", "4"]);
+
+        assert!(query.accepts("Fair Phone 4"));
+        assert!(!query.accepts("Fairphone 4"));
+        assert!(!query.accepts("Fair phone by Samsung"));
+        assert!(parse("-sam").accepts("Samsung phone"));
+
+        let query = parse("Müller \"CRÈME brûlée\" -Straße");
+        assert_eq!(query.keywords, vec!["muller", "creme", "brulee"]);
+        assert!(query.accepts("Creme Brulee by Muller"));
+        assert!(!query.accepts("Crème brûlée aus der Strasse"));
+    }
+
+    #[test]
+    fn stop_words() {
+        assert_eq!(parse("the body shop soap").keywords, vec!["body", "shop", "soap"]);
+        assert_eq!(parse("Fairphone B.V.").keywords, vec!["fairphone"]);
+        assert_eq!(parse("\"The Body Shop\"").phrases, vec!["the body shop"]);
+
+        // Queries consisting only of stop words are kept
+        assert_eq!(parse("the").single_word(), Some("the"));
+
+        let english: StopWords = "en".parse().unwrap();
+        let query = SearchQuery::parse("die the", &english);
+        assert_eq!(query.keywords, vec!["die"]);
+    }
+}
@@ -0,0 +1,182 @@
+//! Token bucket rate limiting.
+
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    net::IpAddr,
+    sync::{Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use crate::config::RateLimitConfig;
+
+/// Interval between prunings of idle buckets.
+///
+/// Pruning visits all the tracked clients, so it's not done on every request.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Describes how many requests a single client can make.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Limit {
+    /// Maximal number of requests made at once.
+    pub burst: f64,
+
+    /// Number of requests restored per second.
+    pub per_second: f64,
+}
+
+impl Limit {
+    /// Creates a limit allowing `per_minute` requests per minute, all of which can be made at once.
+    ///
+    /// Returns `None` (no limit) if `per_minute` is zero.
+    pub fn per_minute(per_minute: u32) -> Option<Self> {
+        if per_minute == 0 {
+            None
+        } else {
+            Some(Self { burst: f64::from(per_minute), per_second: f64::from(per_minute) / 60.0 })
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn refill(&mut self, limit: &Limit, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * limit.per_second).min(limit.burst);
+        self.updated = now;
+    }
+}
+
+/// Buckets of the tracked clients.
+struct Buckets<K> {
+    clients: HashMap<K, Bucket>,
+
+    /// Time of the last pruning.
+    pruned: Instant,
+}
+
+/// Rate limiter tracking a separate token bucket for each client.
+pub struct RateLimiter<K> {
+    limit: Option<Limit>,
+    buckets: Mutex<Buckets<K>>,
+}
+
+impl<K> RateLimiter<K>
+where
+    K: Hash + Eq,
+{
+    pub fn new(limit: Option<Limit>) -> Self {
+        Self {
+            limit,
+            buckets: Mutex::new(Buckets { clients: HashMap::new(), pruned: Instant::now() }),
+        }
+    }
+
+    /// Takes one token from the client's bucket.
+    ///
+    /// Returns `Err` with the time after which the request can be retried if the bucket is empty.
+    pub fn check(&self, client: K, now: Instant) -> Result<(), Duration> {
+        let Some(limit) = self.limit else {
+            return Ok(());
+        };
+
+        let mut buckets = self.buckets.lock().unwrap_or_else(PoisonError::into_inner);
+        if now.saturating_duration_since(buckets.pruned) >= PRUNE_INTERVAL {
+            // Full buckets carry no information, the client can be forgotten.
+            buckets.clients.retain(|_, bucket| {
+                bucket.refill(&limit, now);
+                bucket.tokens < limit.burst
+            });
+            buckets.pruned = now;
+        }
+
+        let bucket =
+            buckets.clients.entry(client).or_insert(Bucket { tokens: limit.burst, updated: now });
+        bucket.refill(&limit, now);
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / limit.per_second))
+        }
+    }
+}
+
+/// All the rate limiters used by the backend.
+pub struct RateLimiters {
+    /// Limits requests per client IP address.
+    pub ip: RateLimiter<IpAddr>,
+
+    /// Limits requests per partner API key.
+    pub key: RateLimiter<String>,
+
+    /// Limits readiness checks per client IP address.
+    pub readiness: RateLimiter<IpAddr>,
+
+    /// Use the `X-Forwarded-For` header to identify clients.
+    pub trust_proxy: bool,
+}
+
+impl RateLimiters {
+    pub fn new(config: &RateLimitConfig) -> Self {
+        Self {
+            ip: RateLimiter::new(Limit::per_minute(config.per_ip_per_minute)),
+            key: RateLimiter::new(Limit::per_minute(config.per_key_per_minute)),
+            readiness: RateLimiter::new(Limit::per_minute(config.readiness_per_minute)),
+            trust_proxy: config.trust_proxy,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn burst_and_refill() {
+        let limiter = RateLimiter::<u8>::new(Limit::per_minute(2));
+        let start = Instant::now();
+
+        assert_eq!(limiter.check(1, start), Ok(()));
+        assert_eq!(limiter.check(1, start), Ok(()));
+        assert_eq!(limiter.check(1, start), Err(Duration::from_secs(30)));
+
+        // Other clients are not affected
+        assert_eq!(limiter.check(2, start), Ok(()));
+
+        // One token is restored every 30 seconds
+        assert!(limiter.check(1, start + Duration::from_secs(20)).is_err());
+        assert_eq!(limiter.check(1, start + Duration::from_secs(30)), Ok(()));
+        assert!(limiter.check(1, start + Duration::from_secs(31)).is_err());
+    }
+
+    #[test]
+    fn pruning() {
+        let limiter = RateLimiter::<u8>::new(Limit::per_minute(2));
+        let start = Instant::now();
+        let num_clients = || limiter.buckets.lock().unwrap().clients.len();
+
+        assert_eq!(limiter.check(1, start), Ok(()));
+        assert_eq!(limiter.check(2, start + Duration::from_secs(50)), Ok(()));
+        assert_eq!(num_clients(), 2);
+
+        // Only the buckets which are full again are dropped
+        assert_eq!(limiter.check(3, start + PRUNE_INTERVAL), Ok(()));
+        assert_eq!(num_clients(), 2);
+        assert!(limiter.buckets.lock().unwrap().clients.contains_key(&2));
+    }
+
+    #[test]
+    fn no_limit() {
+        let limiter = RateLimiter::<u8>::new(Limit::per_minute(0));
+        let now = Instant::now();
+        for _ in 0..1000 {
+            assert_eq!(limiter.check(1, now), Ok(()));
+        }
+    }
+}
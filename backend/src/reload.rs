@@ -0,0 +1,164 @@
+//! Hot reloading of the dataset.
+//!
+//! A new dataset is imported into a separate database while the old one is still being served.
+//! When the import is done, the backend is told to switch over, either with the
+//! `POST /admin/reload` endpoint or with the `SIGHUP` signal. The new database is checked before it
+//! is swapped in, so clients never see a half-imported dataset. Configured webhooks are notified
+//! after every successful reload.
+//!
+//! Besides the main dataset, named snapshots (e.g. a preview of a new pipeline run) can be served
+//! to clients selecting them with the `dataset` query parameter. Snapshots are checked the same
+//! way, but do not trigger the webhooks. A snapshot is promoted by reloading its dataset config.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::Duration,
+};
+
+use snafu::prelude::*;
+
+use crate::{
+    config::DatasetConfig,
+    db::Db,
+    errors::{self, ReloadError},
+    models::DatasetUpdate,
+    webhooks::Webhooks,
+};
+
+/// Collections which must not be empty in a complete dataset.
+const REQUIRED_COLLECTIONS: &[&str] = &["organisations", "products", "library"];
+
+/// Delay between attempts to load the initial dataset.
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(10);
+
+/// Checks that the dataset was completely imported.
+///
+/// Returns the number of entries in the collections.
+pub async fn check(
+    db: &Db,
+    dataset: &DatasetConfig,
+) -> Result<HashMap<String, usize>, ReloadError> {
+    let counts = db.count_dataset_entries(&dataset.database).await?.unwrap_or_default();
+    for collection in REQUIRED_COLLECTIONS {
+        let count = counts.get(*collection).copied().unwrap_or(0);
+        ensure!(
+            count > 0,
+            errors::IncompleteSnafu { database: &dataset.database, collection: *collection }
+        );
+    }
+    Ok(counts)
+}
+
+/// Checks the dataset, starts serving it and notifies the webhooks.
+///
+/// If the check fails, the previous dataset keeps being served. Reloading the same version again
+/// does not trigger the webhooks.
+pub async fn reload(
+    db: &Db,
+    dataset: DatasetConfig,
+    webhooks: &Webhooks,
+) -> Result<(), ReloadError> {
+    let counts = check(db, &dataset).await?;
+
+    let previous = db.is_ready().then(|| db.dataset());
+    let previous_counts = match &previous {
+        Some(previous) => match db.count_dataset_entries(&previous.database).await {
+            Ok(counts) => counts,
+            Err(err) => {
+                tracing::warn!("Failed to count entries of the previous dataset: {err}");
+                None
+            }
+        },
+        None => None,
+    };
+
+    tracing::info!("Serving dataset `{}` from `{}`", dataset.version, dataset.database);
+    let version = dataset.version.clone();
+    db.swap_dataset(dataset);
+
+    let previous_version = previous.map(|previous| previous.version.clone());
+    if previous_version.as_ref() != Some(&version) {
+        webhooks.notify(DatasetUpdate::new(
+            version,
+            previous_version,
+            previous_counts.as_ref(),
+            &counts,
+        ));
+    }
+    Ok(())
+}
+
+/// Checks the dataset and starts serving it as a named snapshot.
+pub async fn mount_snapshot(
+    db: &Db,
+    name: String,
+    dataset: DatasetConfig,
+) -> Result<(), ReloadError> {
+    check(db, &dataset).await?;
+    tracing::info!(
+        "Serving dataset `{}` from `{}` as snapshot `{name}`",
+        dataset.version,
+        dataset.database
+    );
+    db.mount_snapshot(name, dataset);
+    Ok(())
+}
+
+/// Mounts the configured snapshots, retrying until each is complete.
+pub async fn initialize_snapshots(db: Db, snapshots: BTreeMap<String, DatasetConfig>) {
+    for (name, dataset) in snapshots {
+        let db = db.clone();
+        tokio::spawn(async move {
+            while let Err(err) = mount_snapshot(&db, name.clone(), dataset.clone()).await {
+                tracing::warn!("Snapshot `{name}` not ready: {err}");
+                tokio::time::sleep(INITIAL_RETRY_DELAY).await;
+            }
+        });
+    }
+}
+
+/// Loads the initial dataset, retrying until it's complete or another dataset was loaded.
+pub async fn initialize(db: Db, dataset: DatasetConfig, webhooks: Webhooks) {
+    while !db.is_ready() {
+        match reload(&db, dataset.clone(), &webhooks).await {
+            Ok(()) => break,
+            Err(err) => {
+                tracing::warn!("Initial dataset not ready: {err}");
+                tokio::time::sleep(INITIAL_RETRY_DELAY).await;
+            }
+        }
+    }
+}
+
+/// Reloads the dataset (as configured in the dataset config) and the configured snapshots on every
+/// `SIGHUP`. Snapshots removed from the config stop being served.
+pub async fn handle_signals(db: Db, webhooks: Webhooks) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangups = match signal(SignalKind::hangup()) {
+        Ok(hangups) => hangups,
+        Err(err) => {
+            tracing::error!("Failed to register the SIGHUP handler: {err}");
+            return;
+        }
+    };
+
+    while hangups.recv().await.is_some() {
+        tracing::info!("Received SIGHUP, reloading the dataset");
+        if let Err(err) = reload(&db, DatasetConfig::load_or_default(), &webhooks).await {
+            tracing::error!("Dataset reload failed: {err}");
+        }
+        let snapshots = crate::config::load_snapshots();
+        for name in db.snapshots().into_keys() {
+            if !snapshots.contains_key(&name) {
+                tracing::info!("Snapshot `{name}` removed from the config, unmounting it");
+                db.unmount_snapshot(&name);
+            }
+        }
+        for (name, dataset) in snapshots {
+            if let Err(err) = mount_snapshot(&db, name.clone(), dataset).await {
+                tracing::error!("Snapshot `{name}` reload failed: {err}");
+            }
+        }
+    }
+}
@@ -1,14 +1,59 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 use sustainity_api::models as api;
-use sustainity_models::ids;
+use sustainity_models::{
+    attributes, dates, gtin, keywords,
+    store::{
+        AttributeValue, Attributes, Category, CertificationInfo, Certifications, Controversy,
+        EntityId, ExternalIdKind, IdentifierKind, LibraryItem, Organisation, PolicyPage, Product,
+        Regions, RelevanceMatrix, SusId,
+    },
+};
 
 use crate::{
+    analytics,
+    config::SearchConfig,
     db::Db,
     errors::BackendError,
-    models::{OrganisationSearchResult, ProductSearchResult, SearchResultId},
+    languages::Languages,
+    models::{
+        AlternativesOptions, CarbonFootprint, CategoryAlternatives, CategoryNode, CategoryProducts,
+        CategoryStatistics, ChangesPage, ComparisonRow, DetailedSearchResult, LibraryLookup,
+        LibrarySlug, ManufacturerLabel, MatchOffset, MatchedField, OrganisationDetails,
+        OrganisationProducts, OrganisationSearchResult, OwnershipLevel, PrecomputedAlternatives,
+        ProductComparison, ProductListItem, ProductSearchResult, ProductSort, SearchMatch,
+        SearchQuality, SearchResultId, UrlResolution,
+    },
+    query::{Field, SearchQuery},
 };
 
+/// Maximal number of products that can be compared at once.
+pub const MAX_COMPARED_PRODUCTS: usize = 10;
+
+/// Number of products returned in one page if not specified otherwise.
+pub const DEFAULT_PAGE_SIZE: usize = 20;
+
+/// Maximal number of products returned in one page.
+pub const MAX_PAGE_SIZE: usize = 100;
+
+/// Number of changes returned in one page if not specified otherwise.
+pub const DEFAULT_CHANGES_PAGE_SIZE: usize = 500;
+
+/// Maximal number of changes returned in one page.
+pub const MAX_CHANGES_PAGE_SIZE: usize = 1000;
+
+/// Number of featured entries returned if not specified otherwise.
+pub const DEFAULT_FEATURED: usize = 6;
+
+/// Maximal number of featured entries returned at once.
+pub const MAX_FEATURED: usize = 20;
+
+/// Number of the best-scored entries the featured ones are picked from.
+const FEATURED_POOL_SIZE: usize = 200;
+
+/// Maximal number of ownership levels walked from a product (manufacturers are the first level).
+const MAX_OWNERSHIP_DEPTH: usize = 5;
+
 #[derive(Clone, Debug, PartialEq)]
 struct ScoredResult {
     score: f64,
@@ -21,22 +66,83 @@ impl ScoredResult {
     }
 }
 
+/// Finds non-overlapping occurrences of the token in the label, comparing their folded forms.
+///
+/// The offsets are counted in UTF-16 code units of the original label, so that they can be used
+/// directly to index JavaScript strings.
+fn find_offsets(label: &str, token: &str) -> Vec<MatchOffset> {
+    let mut positions = vec![0];
+    positions.extend(label.chars().scan(0, |position, c| {
+        *position += c.len_utf16();
+        Some(*position)
+    }));
+    let label: Vec<String> =
+        label.chars().map(|c| keywords::fold(c.encode_utf8(&mut [0; 4]))).collect();
+    let token = keywords::fold(token);
+    let mut offsets = Vec::new();
+    if token.is_empty() {
+        return offsets;
+    }
+
+    let mut start = 0;
+    while start < label.len() {
+        let mut folded = String::new();
+        let mut end = start;
+        while end < label.len() && folded.len() < token.len() && token.starts_with(&folded) {
+            folded.push_str(&label[end]);
+            end += 1;
+        }
+        if folded == token {
+            offsets.push(MatchOffset { start: positions[start], end: positions[end] });
+            start = end;
+        } else {
+            start += 1;
+        }
+    }
+    offsets
+}
+
+/// Computes the boost of a search result based on its quality.
+fn quality_boost(quality: &SearchQuality, weights: &SearchConfig) -> f64 {
+    let mut boost = quality.num_certifications as f64 * weights.certification_weight;
+    boost += quality.score.unwrap_or(0.0) * weights.score_weight;
+    if quality.has_description {
+        boost += weights.description_weight;
+    }
+    if quality.has_image {
+        boost += weights.image_weight;
+    }
+    boost
+}
+
 #[derive(Clone, Debug, Default)]
 struct ResultCollector {
+    weights: SearchConfig,
     results: HashMap<SearchResultId, ScoredResult>,
+    matches: HashMap<SearchResultId, BTreeSet<SearchMatch>>,
+    boosts: HashMap<SearchResultId, f64>,
+    manufacturers: HashMap<SearchResultId, Vec<String>>,
 }
 
 impl ResultCollector {
+    pub fn new(weights: SearchConfig) -> Self {
+        Self { weights, ..Self::default() }
+    }
+
     // Adds results by giving them some score.
     //
     // The score is better if:
     // - the matched keyword is closer to the beginning of the query
     // - the matched keyword constitutes the longer part of the whole label
+    //
+    // The matched field is remembered so that the frontend can highlight the match. Keyword matches
+    // are reported as `Label` if the token is found in the label, otherwise as `Alias`.
     pub fn add(
         &mut self,
         results: &[(SearchResultId, api::TextSearchResult)],
         matching: &str,
         index: Option<usize>,
+        field: MatchedField,
     ) {
         let index_score = if let Some(index) = index { 1.0 / (index + 1) as f64 } else { 10.0 };
 
@@ -48,7 +154,39 @@ impl ResultCollector {
                 .entry(id.clone())
                 .and_modify(|e| e.with_added_score(total_score))
                 .or_insert_with(|| ScoredResult { score: total_score, result: result.clone() });
+
+            let (field, offsets) = match field {
+                MatchedField::Label | MatchedField::Alias => {
+                    let offsets = find_offsets(&result.label, matching);
+                    let field =
+                        if offsets.is_empty() { MatchedField::Alias } else { MatchedField::Label };
+                    (field, offsets)
+                }
+                field => (field, Vec::new()),
+            };
+            let token = matching.to_owned();
+            self.matches.entry(id.clone()).or_default().insert(SearchMatch {
+                field,
+                token,
+                offsets,
+            });
+        }
+    }
+
+    /// Remembers the quality boosts of the results and adds them.
+    fn add_boosted(
+        &mut self,
+        results: Vec<(f64, (SearchResultId, api::TextSearchResult))>,
+        matching: &str,
+        index: Option<usize>,
+        field: MatchedField,
+    ) {
+        let mut converted = Vec::with_capacity(results.len());
+        for (boost, (id, result)) in results {
+            self.boosts.insert(id.clone(), boost);
+            converted.push((id, result));
         }
+        self.add(&converted, matching, index, field)
     }
 
     pub fn add_organisations(
@@ -56,10 +194,14 @@ impl ResultCollector {
         results: Vec<OrganisationSearchResult>,
         matching: &str,
         index: Option<usize>,
+        field: MatchedField,
     ) {
-        let results: Vec<(SearchResultId, api::TextSearchResult)> =
-            results.into_iter().filter_map(|r| r.convert()).collect();
-        self.add(&results, matching, index)
+        let weights = &self.weights;
+        let results = results
+            .into_iter()
+            .filter_map(|r| Some((quality_boost(&r.quality, weights), r.convert()?)))
+            .collect();
+        self.add_boosted(results, matching, index, field)
     }
 
     pub fn add_products(
@@ -67,166 +209,1161 @@ impl ResultCollector {
         results: Vec<ProductSearchResult>,
         matching: &str,
         index: Option<usize>,
+        field: MatchedField,
     ) {
-        let results: Vec<(SearchResultId, api::TextSearchResult)> =
-            results.into_iter().filter_map(|r| r.convert()).collect();
-        self.add(&results, matching, index)
+        let mut converted = Vec::with_capacity(results.len());
+        for mut result in results {
+            let manufacturer_ids = std::mem::take(&mut result.manufacturer_ids);
+            let boost = quality_boost(&result.quality, &self.weights);
+            if let Some((id, result)) = result.convert() {
+                self.manufacturers.insert(id.clone(), manufacturer_ids);
+                converted.push((boost, (id, result)));
+            }
+        }
+        self.add_boosted(converted, matching, index, field)
+    }
+
+    /// Returns the DB keys of the manufacturers of the collected products.
+    pub fn manufacturer_keys(&self) -> Vec<String> {
+        let keys: BTreeSet<&String> =
+            self.results.keys().filter_map(|id| self.manufacturers.get(id)).flatten().collect();
+        keys.into_iter().cloned().collect()
+    }
+
+    /// Returns the DB keys of the collected products.
+    pub fn product_keys(&self) -> Vec<String> {
+        self.results
+            .keys()
+            .filter_map(|id| match id {
+                SearchResultId::Product(key) => Some(key.clone()),
+                SearchResultId::Organisation(_) => None,
+            })
+            .collect()
+    }
+
+    /// Keeps only the products with the given DB keys (and all the organisations).
+    pub fn retain_products(&mut self, keys: &HashSet<String>) {
+        let is_kept = |id: &SearchResultId| match id {
+            SearchResultId::Product(key) => keys.contains(key),
+            SearchResultId::Organisation(_) => true,
+        };
+        self.results.retain(|id, _| is_kept(id));
+        self.matches.retain(|id, _| is_kept(id));
     }
 
-    pub fn gather_scored_results(self) -> Vec<ScoredResult> {
+    /// Keeps only the results satisfying the predicate.
+    pub fn retain<F>(&mut self, predicate: F)
+    where
+        F: Fn(&api::TextSearchResult) -> bool,
+    {
+        self.results.retain(|_, scored| predicate(&scored.result));
+        let results = &self.results;
+        self.matches.retain(|id, _| results.contains_key(id));
+    }
+
+    /// Returns the results with their quality boosts applied, sorted by the score.
+    fn sorted_results(&mut self) -> Vec<(SearchResultId, ScoredResult)> {
         use std::cmp::Ordering;
 
-        let mut results: Vec<ScoredResult> = self.results.into_values().collect();
-        results.sort_by(|a, b| match PartialOrd::partial_cmp(&b.score, &a.score) {
+        let boosts = &self.boosts;
+        let mut results: Vec<_> = self
+            .results
+            .drain()
+            .map(|(id, mut scored)| {
+                scored.with_added_score(boosts.get(&id).copied().unwrap_or(0.0));
+                (id, scored)
+            })
+            .collect();
+        results.sort_by(|(_, a), (_, b)| match PartialOrd::partial_cmp(&b.score, &a.score) {
             None | Some(Ordering::Equal) => Ord::cmp(&a.result.label, &b.result.label),
             Some(ordering) => ordering,
         });
         results
     }
 
-    pub fn gather_results(self) -> Vec<api::TextSearchResult> {
-        self.gather_scored_results().into_iter().map(|r| r.result).collect()
+    pub fn gather_scored_results(mut self) -> Vec<ScoredResult> {
+        self.sorted_results().into_iter().map(|(_, r)| r).collect()
+    }
+
+    /// Returns the results with the matches and the manufacturers of the products found in
+    /// `labels`, sorted by the score.
+    pub fn gather_detailed_results(
+        mut self,
+        labels: &HashMap<String, ManufacturerLabel>,
+    ) -> Vec<DetailedSearchResult> {
+        self.sorted_results()
+            .into_iter()
+            .map(|(id, scored)| DetailedSearchResult {
+                manufacturers: self
+                    .manufacturers
+                    .get(&id)
+                    .map(|ids| manufacturers_of(ids, labels))
+                    .unwrap_or_default(),
+                result: scored.result,
+                matches: self.matches.remove(&id).unwrap_or_default().into_iter().collect(),
+            })
+            .collect()
     }
 }
 
-pub async fn library_contents(db: &Db) -> Result<Vec<api::LibraryItemShort>, BackendError> {
-    Ok(db
+pub async fn library_contents(
+    db: &Db,
+    languages: &Languages,
+) -> Result<Vec<api::LibraryItemShort>, BackendError> {
+    let key = format!("library:{}", languages.key());
+
+    if let Some(items) = db.cache().library.get(&key) {
+        return Ok(items);
+    }
+
+    let items: Vec<_> = db
         .get_library_contents()
         .await?
         .into_iter()
-        .filter_map(|i| i.try_into_api_short().ok())
-        .collect())
+        .filter_map(|i| i.localize(languages.as_slice()).try_into_api_short().ok())
+        .collect();
+    db.cache().library.insert(key, items.clone());
+    Ok(items)
 }
 
 pub async fn library_item(
     topic: api::LibraryTopic,
     db: &Db,
+    languages: &Languages,
 ) -> Result<Option<api::LibraryItemFull>, BackendError> {
     let topic_name = topic.to_string();
     if let Some(item) = db.get_library_item(&topic_name).await? {
-        let presentation = db.get_presentation(&topic_name).await?.map(|p| p.into_api());
-        let item = item.try_into_api_full(presentation)?;
-        Ok(Some(item))
+        Ok(Some(library_item_full(item, db, languages).await?))
     } else {
         Ok(None)
     }
 }
 
+/// Finds a library article by its current or former slug.
+///
+/// A former slug results in `LibraryLookup::Moved` with the current one, so that the clients can
+/// be redirected.
+pub async fn library_item_by_slug(
+    slug: &str,
+    db: &Db,
+    languages: &Languages,
+) -> Result<Option<LibraryLookup>, BackendError> {
+    let Some(item) = db.get_library_item_by_slug(slug).await? else {
+        return Ok(None);
+    };
+    if item.slug == slug {
+        let item = library_item_full(item, db, languages).await?;
+        Ok(Some(LibraryLookup::Found(Box::new(item))))
+    } else {
+        Ok(Some(LibraryLookup::Moved(item.slug)))
+    }
+}
+
+/// Lists the slugs of all the library topics.
+pub async fn library_slugs(db: &Db) -> Result<Vec<LibrarySlug>, BackendError> {
+    let mut slugs: Vec<LibrarySlug> = db
+        .get_library_contents()
+        .await?
+        .into_iter()
+        .map(|i| LibrarySlug { id: i.id, slug: i.slug, former_slugs: i.former_slugs })
+        .collect();
+    slugs.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(slugs)
+}
+
+/// Localizes the library item and attaches its presentation.
+async fn library_item_full(
+    item: LibraryItem,
+    db: &Db,
+    languages: &Languages,
+) -> Result<api::LibraryItemFull, BackendError> {
+    let presentation = db.get_presentation(&item.id).await?.map(|p| p.into_api());
+    Ok(item.localize(languages.as_slice()).try_into_api_full(presentation)?)
+}
+
+/// Key of the only entry in the relevance cache.
+const RELEVANCE_KEY: &str = "relevance";
+
+/// Returns the relevance of certifications for product categories.
+async fn relevance(db: &Db) -> Result<RelevanceMatrix, BackendError> {
+    if let Some(relevance) = db.cache().relevance.get(RELEVANCE_KEY) {
+        return Ok(relevance);
+    }
+    let relevance = RelevanceMatrix::from_categories(&db.get_categories().await?);
+    db.cache().relevance.insert(RELEVANCE_KEY.to_owned(), relevance.clone());
+    Ok(relevance)
+}
+
+/// Removes certifications irrelevant for the categories of the product, so that their badges
+/// are not shown.
+fn hide_irrelevant(mut product: Product, relevance: &RelevanceMatrix) -> Product {
+    relevance.retain_relevant(&mut product.certifications, &product.categories);
+    product
+}
+
+/// Converts the products to their short form, without irrelevant badges.
+fn into_api_shorts(products: Vec<Product>, relevance: &RelevanceMatrix) -> Vec<api::ProductShort> {
+    products.into_iter().map(|p| hide_irrelevant(p, relevance).into_api_short()).collect()
+}
+
+/// Looks up the names of the organisations with the given DB keys.
+///
+/// Organisations missing in the lookup are left out.
+async fn organisation_labels<'a>(
+    keys: impl IntoIterator<Item = &'a String>,
+    db: &Db,
+) -> Result<HashMap<String, ManufacturerLabel>, BackendError> {
+    let keys: BTreeSet<&String> = keys.into_iter().collect();
+    if keys.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let keys: Vec<String> = keys.into_iter().cloned().collect();
+    Ok(db
+        .get_organisation_labels(&keys)
+        .await?
+        .into_iter()
+        .map(|label| (label.db_key.clone(), label.into()))
+        .collect())
+}
+
+/// Returns the labels of the manufacturers found in `labels`, in the order of `ids`.
+fn manufacturers_of(
+    ids: &[String],
+    labels: &HashMap<String, ManufacturerLabel>,
+) -> Vec<ManufacturerLabel> {
+    ids.iter().filter_map(|id| labels.get(id).cloned()).collect()
+}
+
+/// Converts the products to list items with the names of their manufacturers, without irrelevant
+/// badges.
+async fn into_list_items(
+    products: Vec<Product>,
+    relevance: &RelevanceMatrix,
+    db: &Db,
+) -> Result<Vec<ProductListItem>, BackendError> {
+    let labels = organisation_labels(products.iter().flat_map(|p| &p.manufacturer_ids), db).await?;
+    Ok(products
+        .into_iter()
+        .map(|p| ProductListItem {
+            manufacturers: manufacturers_of(&p.manufacturer_ids, &labels),
+            product: hide_irrelevant(p, relevance).into_api_short(),
+        })
+        .collect())
+}
+
 pub async fn organisation(
     id_variant: api::OrganisationIdVariant,
     id: &str,
     db: &Db,
 ) -> Result<Option<api::OrganisationFull>, BackendError> {
     if let Some(org) = db.get_organisation(id_variant, id).await? {
-        let products = db
-            .find_organisation_products(&org.db_key)
-            .await?
-            .into_iter()
-            .map(|p| p.into_api_short())
-            .collect();
-        let org = org.into_api_full(products);
-        Ok(Some(org))
+        Ok(Some(expand_organisation(org, db).await?))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Returns the organisation together with the data not covered by the generated API models.
+pub async fn organisation_details(
+    id_variant: api::OrganisationIdVariant,
+    id: &str,
+    db: &Db,
+) -> Result<Option<OrganisationDetails>, BackendError> {
+    if let Some(org) = db.get_organisation(id_variant, id).await? {
+        Ok(Some(expand_organisation_details(org, db).await?))
     } else {
         Ok(None)
     }
 }
 
+/// Returns the organisation with the given namespaced ID.
+///
+/// Open Food Facts IDs identify only products, so no organisation is found for them. Sustainity IDs
+/// are resolved through the source IDs they are derived from.
+pub async fn organisation_by_entity(
+    id: &EntityId,
+    db: &Db,
+) -> Result<Option<OrganisationDetails>, BackendError> {
+    match id {
+        EntityId::Wiki(wiki) => {
+            let id = wiki.to_canonical_string();
+            organisation_details(api::OrganisationIdVariant::Wiki, &id, db).await
+        }
+        EntityId::Off(_) => Ok(None),
+        EntityId::Sus(SusId::Vat(vat)) => {
+            organisation_details(api::OrganisationIdVariant::Vat, vat.as_str(), db).await
+        }
+        EntityId::Sus(SusId::Domain(domain)) => {
+            organisation_details(api::OrganisationIdVariant::Www, domain, db).await
+        }
+    }
+}
+
+/// Completes the organisation with its products.
+async fn expand_organisation(
+    org: Organisation,
+    db: &Db,
+) -> Result<api::OrganisationFull, BackendError> {
+    let products = db.find_organisation_products(&org.db_key).await?;
+    let products = into_api_shorts(products, &relevance(db).await?);
+    Ok(org.into_api_full(products))
+}
+
+/// Completes the organisation with its products and the data missing in the API models.
+async fn expand_organisation_details(
+    org: Organisation,
+    db: &Db,
+) -> Result<OrganisationDetails, BackendError> {
+    let attributes = org.attributes.clone();
+    let recognitions = org.recognitions.clone();
+    let timeline = org.timeline.clone();
+    let material_standards = attributes::material_standards(&attributes);
+    let organisation = expand_organisation(org, db).await?;
+    Ok(OrganisationDetails { organisation, attributes, recognitions, timeline, material_standards })
+}
+
 pub async fn product(
     id_variant: api::ProductIdVariant,
     id: &str,
     region: Option<&str>,
     db: &Db,
 ) -> Result<Option<api::ProductFull>, BackendError> {
-    match ids::Gtin::try_from(id) {
-        Ok(gtin) => {
-            let gtin = gtin.as_number().to_string();
-            if let Some(prod) = db.get_product(id_variant, &gtin).await? {
-                let manufacturers = db
-                    .find_product_manufacturers(&prod.db_key)
-                    .await?
-                    .into_iter()
-                    .map(|m| m.into_api_short())
-                    .collect();
-                let alternatives = product_alternatives(&prod.db_key, region, db).await?;
-                let prod = prod.into_api_full(manufacturers, alternatives);
-                Ok(Some(prod))
+    let key = format!("{id_variant}/{id}/{}", region.unwrap_or_default());
+    if let Some(prod) = db.cache().products.get(&key) {
+        return Ok(prod);
+    }
+
+    let prod = find_product(id_variant, id, region, db).await?;
+    db.cache().products.insert(key, prod.clone());
+    Ok(prod)
+}
+
+async fn find_product(
+    id_variant: api::ProductIdVariant,
+    id: &str,
+    region: Option<&str>,
+    db: &Db,
+) -> Result<Option<api::ProductFull>, BackendError> {
+    match normalize_product_id(id_variant, id) {
+        Some(key) => {
+            if let Some(prod) = db.get_product(id_variant, &key).await? {
+                Ok(Some(expand_product(prod, region, db).await?))
             } else {
                 Ok(None)
             }
         }
-        Err(_) => Ok(None),
+        None => Ok(None),
     }
 }
 
+/// Returns the product with the given namespaced ID.
+///
+/// Sustainity IDs identify only organisations, so no product is found for them.
+pub async fn product_by_entity(
+    id: &EntityId,
+    region: Option<&str>,
+    db: &Db,
+) -> Result<Option<api::ProductFull>, BackendError> {
+    match id {
+        EntityId::Wiki(wiki) => {
+            product(api::ProductIdVariant::Wiki, &wiki.to_canonical_string(), region, db).await
+        }
+        EntityId::Off(gtin) => {
+            product(api::ProductIdVariant::Gtin, &gtin.to_canonical_string(), region, db).await
+        }
+        EntityId::Sus(_) => Ok(None),
+    }
+}
+
+/// Completes the product with its manufacturers and alternatives.
+async fn expand_product(
+    prod: Product,
+    region: Option<&str>,
+    db: &Db,
+) -> Result<api::ProductFull, BackendError> {
+    let prod = hide_irrelevant(prod, &relevance(db).await?);
+    let manufacturers = db
+        .find_product_manufacturers(&prod.db_key)
+        .await?
+        .into_iter()
+        .map(|m| m.into_api_short())
+        .collect();
+    let options = AlternativesOptions::default();
+    let alternatives = product_alternatives(&prod.db_key, region, &options, db).await?;
+    Ok(prod.into_api_full(manufacturers, alternatives))
+}
+
 pub async fn product_alternatives(
     id: &str,
     region_code: Option<&str>,
+    options: &AlternativesOptions,
     db: &Db,
 ) -> Result<Vec<api::CategoryAlternatives>, BackendError> {
+    let relevance = relevance(db).await?;
+    Ok(find_alternatives(id, region_code, options, db)
+        .await?
+        .into_iter()
+        .map(|(category, alternatives)| api::CategoryAlternatives {
+            category,
+            alternatives: into_api_shorts(alternatives, &relevance),
+        })
+        .collect())
+}
+
+/// Same as `product_alternatives`, but lists also the names of the manufacturers.
+pub async fn product_alternatives_with_manufacturers(
+    id: &str,
+    region_code: Option<&str>,
+    options: &AlternativesOptions,
+    db: &Db,
+) -> Result<Vec<CategoryAlternatives>, BackendError> {
+    let relevance = relevance(db).await?;
+    let mut result = Vec::new();
+    for (category, alternatives) in find_alternatives(id, region_code, options, db).await? {
+        let alternatives = into_list_items(alternatives, &relevance, db).await?;
+        result.push(CategoryAlternatives { category, alternatives });
+    }
+    Ok(result)
+}
+
+/// Finds the alternatives to the product in each of its categories.
+///
+/// The precomputed alternatives are used if the filtering options are the default ones and
+/// enough of them are stored (or all of them are), otherwise they are queried.
+async fn find_alternatives(
+    id: &str,
+    region_code: Option<&str>,
+    options: &AlternativesOptions,
+    db: &Db,
+) -> Result<Vec<(String, Vec<Product>)>, BackendError> {
     let mut result = Vec::new();
     let categories = db.find_product_categories(id).await?;
     for category in categories {
-        let alternatives = db
-            .find_product_alternatives(id, &category, region_code)
-            .await?
-            .into_iter()
-            .map(|a| a.into_api_short())
-            .collect();
-        result.push(api::CategoryAlternatives { category, alternatives });
+        let precomputed = if uses_precomputed(options) {
+            db.find_precomputed_product_alternatives(id, &category, region_code).await?
+        } else {
+            None
+        };
+        let precomputed = precomputed.and_then(|a| truncate_precomputed(a, options));
+        let alternatives = match precomputed {
+            Some(alternatives) => alternatives,
+            None => db.find_product_alternatives(id, &category, region_code, options).await?,
+        };
+        result.push((category, alternatives));
     }
     Ok(result)
 }
 
+/// Checks if the precomputed alternatives match the options.
+///
+/// The alternatives are precomputed with the default filters, only their number may differ.
+fn uses_precomputed(options: &AlternativesOptions) -> bool {
+    let max_per_category = AlternativesOptions::DEFAULT_MAX_PER_CATEGORY;
+    AlternativesOptions { max_per_category, ..options.clone() } == AlternativesOptions::default()
+}
+
+/// Limits the precomputed alternatives to the requested number.
+///
+/// Returns `None` if fewer alternatives are stored than requested and the stored ones are not all
+/// there are, as the precomputation may have kept fewer of them than requested.
+fn truncate_precomputed<T>(
+    precomputed: PrecomputedAlternatives<T>,
+    options: &AlternativesOptions,
+) -> Option<Vec<T>> {
+    let PrecomputedAlternatives { mut alternatives, exhaustive } = precomputed;
+    if alternatives.len() < options.max_per_category && !exhaustive {
+        return None;
+    }
+    alternatives.truncate(options.max_per_category);
+    Some(alternatives)
+}
+
+/// Brings an organisation identifier to its canonical form.
+///
+/// Identifiers are often written with spaces, dots or dashes separating groups of characters.
+fn normalize_identifier(id: &str) -> String {
+    id.chars().filter(char::is_ascii_alphanumeric).map(|c| c.to_ascii_uppercase()).collect()
+}
+
+/// Brings a VAT number to its canonical form.
+///
+/// Greek VAT numbers use the `EL` prefix instead of the ISO country code `GR`.
+fn normalize_vat(id: &str) -> String {
+    sustainity_models::vat::normalize(id)
+}
+
+/// Lists the domains an organisation with the given website may be indexed under.
+///
+/// Organisations are indexed by their registrable domains, but a page may also be hosted on one of
+/// their subdomains, so the host and its parent domains down to the registrable domain (according
+/// to the Public Suffix List, including its private section) are returned. For example
+/// `https://www.shop.example.co.uk/about` gives `shop.example.co.uk` and `example.co.uk`, while
+/// `https://someone.github.io` gives only `someone.github.io`.
+fn domain_candidates(website: &str) -> Vec<String> {
+    let mut host = website.trim();
+    if let Some((_scheme, rest)) = host.split_once("://") {
+        host = rest;
+    }
+    if let Some(end) = host.find(['/', '?', '#']) {
+        host = &host[..end];
+    }
+    if let Some((name, port)) = host.rsplit_once(':') {
+        if port.chars().all(|c| c.is_ascii_digit()) {
+            host = name;
+        }
+    }
+    let host = host.trim_end_matches('.').to_lowercase();
+    let host = host.strip_prefix("www.").unwrap_or(&host);
+    if host.parse::<std::net::IpAddr>().is_ok() {
+        return vec![host.to_owned()];
+    }
+    let Some(registrable) = psl::domain_str(host) else {
+        return Vec::new();
+    };
+    std::iter::successors(Some(host), |d| d.split_once('.').map(|(_, parent)| parent))
+        .take_while(|d| d.len() >= registrable.len())
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Checks if the (normalized) identifier is a valid Legal Entity Identifier.
+///
+/// LEI (ISO 17442) consists of 18 alphanumeric characters followed by two check digits
+/// validated using the ISO 7064 MOD 97-10 algorithm.
+fn is_lei(id: &str) -> bool {
+    if id.len() != 20 || !id.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return false;
+    }
+    if !id[18..].chars().all(|c| c.is_ascii_digit()) {
+        return false;
+    }
+
+    let mut remainder: u32 = 0;
+    for c in id.chars() {
+        // Letters are replaced with two-digit numbers: A = 10, B = 11, ..., Z = 35.
+        let value = c.to_digit(36).unwrap_or_default();
+        let shift = if value < 10 { 10 } else { 100 };
+        remainder = (remainder * shift + value) % 97;
+    }
+    remainder == 1
+}
+
+/// Brings the product ID to the form used as a DB key.
+pub(crate) fn normalize_product_id(id_variant: api::ProductIdVariant, id: &str) -> Option<String> {
+    match id_variant {
+        api::ProductIdVariant::Gtin => gtin::normalize(id).ok(),
+        api::ProductIdVariant::Ean | api::ProductIdVariant::Wiki => Some(id.to_string()),
+    }
+}
+
+fn regions_to_strings(regions: &Regions) -> Vec<String> {
+    match regions {
+        Regions::World => vec!["all".to_string()],
+        Regions::Unknown => Vec::new(),
+        Regions::List(list) => list.iter().map(|c| c.alpha3().to_string()).collect(),
+    }
+}
+
+/// Builds rows with presence of each certification.
+fn certification_rows(certifications: &[Certifications]) -> Vec<ComparisonRow<bool>> {
+    vec![
+        ComparisonRow::new("bcorp", certifications.iter().map(|c| c.bcorp.is_some()).collect()),
+        ComparisonRow::new(
+            "eu_ecolabel",
+            certifications.iter().map(|c| c.eu_ecolabel.is_some()).collect(),
+        ),
+        ComparisonRow::new("fti", certifications.iter().map(|c| c.fti.is_some()).collect()),
+        ComparisonRow::new("tco", certifications.iter().map(|c| c.tco.is_some()).collect()),
+    ]
+}
+
+/// Returns the certifications which are present in the data but are not current anymore.
+fn non_current(all: &Certifications, current: &Certifications) -> Certifications {
+    Certifications {
+        bcorp: all.bcorp.clone().filter(|_| current.bcorp.is_none()),
+        eu_ecolabel: all.eu_ecolabel.clone().filter(|_| current.eu_ecolabel.is_none()),
+        fti: all.fti.clone().filter(|_| current.fti.is_none()),
+        tco: all.tco.clone().filter(|_| current.tco.is_none()),
+    }
+}
+
+/// Builds a comparison of the passed products.
+///
+/// All the rows are aligned with the list of products, so frontends can render the comparison
+/// as a table without matching the values themselves.
+/// - expired certifications and certifications from stale sources are listed separately
+/// - manufacturers missing in `names` are listed by their DB keys in the `manufacturer_names` row
+fn compare(
+    products: Vec<Product>,
+    not_found: Vec<String>,
+    names: &HashMap<String, String>,
+) -> ProductComparison {
+    let today = dates::today();
+    let current: Vec<Certifications> =
+        products.iter().map(|p| p.certifications.clone().current(&today)).collect();
+    let stale: Vec<Certifications> = products
+        .iter()
+        .zip(&current)
+        .map(|(p, current)| non_current(&p.certifications, current))
+        .collect();
+    let certifications = certification_rows(&current);
+    let stale_certifications = certification_rows(&stale);
+
+    // Products may have different score trees, so gather all top-level categories first.
+    let mut categories = Vec::<&'static str>::new();
+    for product in &products {
+        for branch in &product.sustainity_score.tree {
+            if let Ok(name) = serde_variant::to_variant_name(&branch.category) {
+                if !categories.contains(&name) {
+                    categories.push(name);
+                }
+            }
+        }
+    }
+
+    let mut scores = Vec::with_capacity(categories.len() + 2);
+    scores.push(ComparisonRow::new(
+        "total",
+        products.iter().map(|p| Some(p.sustainity_score.total)).collect(),
+    ));
+    for category in categories {
+        let values = products
+            .iter()
+            .map(|p| {
+                p.sustainity_score
+                    .tree
+                    .iter()
+                    .find(|b| serde_variant::to_variant_name(&b.category).ok() == Some(category))
+                    .map(|b| b.score)
+            })
+            .collect();
+        scores.push(ComparisonRow::new(category, values));
+    }
+    scores.push(ComparisonRow::new(
+        "fti",
+        current.iter().map(|c| c.fti.as_ref().map(|f| f.score as f64)).collect(),
+    ));
+
+    let attributes = vec![
+        ComparisonRow::new("categories", products.iter().map(|p| p.categories.clone()).collect()),
+        ComparisonRow::new(
+            "regions",
+            products.iter().map(|p| regions_to_strings(&p.regions)).collect(),
+        ),
+        ComparisonRow::new(
+            "manufacturers",
+            products.iter().map(|p| p.manufacturer_ids.clone()).collect(),
+        ),
+        ComparisonRow::new(
+            "manufacturer_names",
+            products
+                .iter()
+                .map(|p| {
+                    p.manufacturer_ids
+                        .iter()
+                        .map(|id| names.get(id).unwrap_or(id).clone())
+                        .collect()
+                })
+                .collect(),
+        ),
+    ];
+
+    let products = products.into_iter().map(|p| p.into_api_short()).collect();
+    ProductComparison {
+        products,
+        certifications,
+        stale_certifications,
+        scores,
+        attributes,
+        not_found,
+    }
+}
+
+pub async fn compare_products(
+    id_variant: api::ProductIdVariant,
+    ids: &[String],
+    db: &Db,
+) -> Result<ProductComparison, BackendError> {
+    let mut products = Vec::with_capacity(ids.len());
+    let mut not_found = Vec::new();
+    for id in ids.iter().take(MAX_COMPARED_PRODUCTS) {
+        let product = if let Some(key) = normalize_product_id(id_variant, id) {
+            db.get_product(id_variant, &key).await?
+        } else {
+            None
+        };
+
+        match product {
+            Some(product) => products.push(product),
+            None => not_found.push(id.clone()),
+        }
+    }
+    let relevance = relevance(db).await?;
+    let products: Vec<Product> =
+        products.into_iter().map(|p| hide_irrelevant(p, &relevance)).collect();
+
+    let names = organisation_labels(products.iter().flat_map(|p| &p.manufacturer_ids), db)
+        .await?
+        .into_iter()
+        .map(|(key, label)| (key, label.name))
+        .collect();
+    Ok(compare(products, not_found, &names))
+}
+
+/// Builds a tree out of the flat list of categories.
+///
+/// Intermediate levels which have no products assigned directly are still included in the tree.
+fn build_category_tree(categories: Vec<Category>) -> Vec<CategoryNode> {
+    let mut roots = Vec::<CategoryNode>::new();
+    for category in categories {
+        let mut level = &mut roots;
+        let mut path = String::new();
+        let mut segments = category.db_key.split('/').filter(|s| !s.is_empty()).peekable();
+        while let Some(segment) = segments.next() {
+            if !path.is_empty() {
+                path.push('/');
+            }
+            path.push_str(segment);
+
+            let index = if let Some(index) = level.iter().position(|n| n.name == segment) {
+                index
+            } else {
+                level.push(CategoryNode::new(segment, &path));
+                level.len() - 1
+            };
+
+            let node = &mut level[index];
+            if segments.peek().is_none() {
+                node.num_products = category.num_products;
+            }
+            level = &mut node.children;
+        }
+    }
+    CategoryNode::sort(&mut roots);
+    roots
+}
+
+pub async fn category_tree(db: &Db) -> Result<Vec<CategoryNode>, BackendError> {
+    Ok(build_category_tree(db.get_categories().await?))
+}
+
+pub async fn certifications(db: &Db) -> Result<Vec<CertificationInfo>, BackendError> {
+    Ok(db.get_certifications().await?)
+}
+
+pub async fn category_products(
+    category: &str,
+    region_code: Option<&str>,
+    offset: usize,
+    limit: usize,
+    db: &Db,
+) -> Result<Option<CategoryProducts>, BackendError> {
+    if let Some(entry) = db.get_category(category).await? {
+        let limit = limit.min(MAX_PAGE_SIZE);
+        let products = db.find_category_products(category, region_code, offset, limit).await?;
+        let products = into_list_items(products, &relevance(db).await?, db).await?;
+        Ok(Some(CategoryProducts {
+            category: entry.db_key,
+            num_products: entry.num_products,
+            offset,
+            limit,
+            products,
+        }))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Returns statistics of the category for its overview page.
+pub async fn category_stats(
+    category: &str,
+    db: &Db,
+) -> Result<Option<CategoryStatistics>, BackendError> {
+    let Some(stats) = db.get_category_stats(category).await? else {
+        return Ok(None);
+    };
+
+    let best_products = db.get_products_by_keys(&stats.best_products).await?;
+    let best_products = into_api_shorts(best_products, &relevance(db).await?);
+    let certified_percentage = if stats.num_products == 0 {
+        0.0
+    } else {
+        100.0 * stats.num_certified as f64 / stats.num_products as f64
+    };
+    Ok(Some(CategoryStatistics {
+        category: stats.db_key,
+        num_products: stats.num_products,
+        num_certified: stats.num_certified,
+        certified_percentage,
+        certifications: stats.certifications,
+        best_products,
+    }))
+}
+
+/// Lists a page of products of the organisation.
+pub async fn organisation_products(
+    id_variant: api::OrganisationIdVariant,
+    id: &str,
+    sort: ProductSort,
+    offset: usize,
+    limit: usize,
+    db: &Db,
+) -> Result<Option<OrganisationProducts>, BackendError> {
+    let Some(org) = db.get_organisation(id_variant, id).await? else {
+        return Ok(None);
+    };
+
+    let limit = limit.min(MAX_PAGE_SIZE);
+    let num_products = db.count_organisation_products(&org.db_key).await?;
+    let products = db.find_organisation_products_page(&org.db_key, sort, offset, limit).await?;
+    let products = into_api_shorts(products, &relevance(db).await?);
+    Ok(Some(OrganisationProducts { num_products, sort, offset, limit, products }))
+}
+
+/// Returns the controversies of an organisation, the latest first.
+pub async fn organisation_controversies(
+    id_variant: api::OrganisationIdVariant,
+    id: &str,
+    db: &Db,
+) -> Result<Option<Vec<Controversy>>, BackendError> {
+    Ok(db.get_organisation(id_variant, id).await?.map(|org| org.controversies))
+}
+
+/// Returns the sustainability-related pages found on the websites of an organisation.
+pub async fn organisation_policy_pages(
+    id_variant: api::OrganisationIdVariant,
+    id: &str,
+    db: &Db,
+) -> Result<Option<Vec<PolicyPage>>, BackendError> {
+    Ok(db.get_organisation(id_variant, id).await?.map(|org| org.policy_pages))
+}
+
+/// Finds the organisation a web page (e.g. of a shop) belongs to.
+///
+/// The website index is tried first, from the most specific domain, then the domain identifiers.
+/// The returned domain is the one the organisation was found under.
+/// If a product name hint is given, the best matching product of the organisation is looked up too.
+pub async fn resolve_url(
+    url: &str,
+    product_hint: Option<&str>,
+    db: &Db,
+) -> Result<Option<UrlResolution>, BackendError> {
+    let domains = domain_candidates(url);
+    let mut found = None;
+    for domain in &domains {
+        if let Some(org) = db.get_organisation(api::OrganisationIdVariant::Www, domain).await? {
+            found = Some((domain.clone(), org));
+            break;
+        }
+    }
+    if found.is_none() {
+        for domain in &domains {
+            let values = std::slice::from_ref(domain);
+            let items =
+                db.search_organisations_exact_by_identifier(IdentifierKind::Domain, values).await?;
+            if let Some(item) = items.into_iter().next() {
+                if let Some(org) = db.get_organisation_by_key(&item.id).await? {
+                    found = Some((domain.clone(), org));
+                    break;
+                }
+            }
+        }
+    }
+    let Some((domain, org)) = found else {
+        return Ok(None);
+    };
+
+    let keywords = product_hint
+        .map(|hint| db.search_config().stop_words.filter(keywords::tokenize(hint)))
+        .unwrap_or_default();
+    let product = if keywords.is_empty() {
+        None
+    } else {
+        match db.find_organisation_product_by_keywords(&org.db_key, &keywords).await? {
+            Some(product) => Some(hide_irrelevant(product, &relevance(db).await?).into_api_short()),
+            None => None,
+        }
+    };
+    Ok(Some(UrlResolution { domain, organisation: org.into_api_short(), product }))
+}
+
+/// Walks the ownership chain of a product (its manufacturers, their parent organisations and so
+/// on) and reports the certifications found at each level.
+///
+/// This tells for example that a brand is not certified, but its parent company is a B Corp.
+pub async fn product_ownership(
+    id_variant: api::ProductIdVariant,
+    id: &str,
+    db: &Db,
+) -> Result<Option<Vec<OwnershipLevel>>, BackendError> {
+    let Some(key) = normalize_product_id(id_variant, id) else {
+        return Ok(None);
+    };
+    let Some(product) = db.get_product(id_variant, &key).await? else {
+        return Ok(None);
+    };
+
+    let today = dates::today();
+    let entries = db.find_product_ownership(&product.db_key, MAX_OWNERSHIP_DEPTH).await?;
+    Ok(Some(entries.into_iter().map(|entry| entry.into_level(&today)).collect()))
+}
+
+/// Extracts the carbon footprint from product attributes.
+fn extract_footprint(attributes: &Attributes) -> Option<CarbonFootprint> {
+    let text = |key: &str| match attributes.get(key) {
+        Some(AttributeValue::Text(text)) => Some(text.clone()),
+        _ => None,
+    };
+    let Some(AttributeValue::Number(kg_co2e_per_100g)) =
+        attributes.get(attributes::CARBON_FOOTPRINT)
+    else {
+        return None;
+    };
+    Some(CarbonFootprint {
+        kg_co2e_per_100g: *kg_co2e_per_100g,
+        provenance: text(attributes::CARBON_FOOTPRINT_PROVENANCE)
+            .unwrap_or_else(|| attributes::PROVENANCE_ESTIMATE.to_owned()),
+        basis: text(attributes::CARBON_FOOTPRINT_BASIS),
+    })
+}
+
+/// Returns the carbon footprint of a product.
+///
+/// Returns `None` if the product is not found or its footprint is not known.
+pub async fn product_footprint(
+    id_variant: api::ProductIdVariant,
+    id: &str,
+    db: &Db,
+) -> Result<Option<CarbonFootprint>, BackendError> {
+    let Some(key) = normalize_product_id(id_variant, id) else {
+        return Ok(None);
+    };
+    Ok(db
+        .get_product(id_variant, &key)
+        .await?
+        .and_then(|product| extract_footprint(&product.attributes)))
+}
+
+/// Lists changes since the given dataset version.
+///
+/// Returns `None` if no changes since the version are known, in which case the client has to
+/// download the whole dataset again.
+pub async fn changes(
+    since: &str,
+    offset: usize,
+    limit: usize,
+    db: &Db,
+) -> Result<Option<ChangesPage>, BackendError> {
+    let version = db.dataset().version.clone();
+    let limit = limit.min(MAX_CHANGES_PAGE_SIZE);
+    let changes = if since == version {
+        Vec::new()
+    } else {
+        let Some(start) = db.find_changes_start(since).await? else {
+            return Ok(None);
+        };
+        db.get_changes(&start, offset, limit).await?
+    };
+    Ok(Some(ChangesPage { since: since.to_owned(), version, offset, limit, changes }))
+}
+
+/// Returns a seed changing every day and with every dataset version.
+fn featured_seed(db: &Db) -> String {
+    const DAY: u64 = 24 * 60 * 60;
+    let day = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|since| since.as_secs() / DAY)
+        .unwrap_or_default();
+    format!("{day}/{}/", db.dataset().version)
+}
+
+/// Returns a daily rotating selection of well-scored products.
+pub async fn featured_products(
+    limit: usize,
+    db: &Db,
+) -> Result<Vec<api::ProductShort>, BackendError> {
+    let limit = limit.min(MAX_FEATURED);
+    let products = db.find_featured_products(&featured_seed(db), FEATURED_POOL_SIZE, limit).await?;
+    Ok(into_api_shorts(products, &relevance(db).await?))
+}
+
+/// Returns a daily rotating selection of well-certified organisations.
+pub async fn featured_organisations(
+    limit: usize,
+    db: &Db,
+) -> Result<Vec<api::OrganisationShort>, BackendError> {
+    let limit = limit.min(MAX_FEATURED);
+    let organisations =
+        db.find_featured_organisations(&featured_seed(db), FEATURED_POOL_SIZE, limit).await?;
+    Ok(organisations.into_iter().map(|o| o.into_api_short()).collect())
+}
+
+/// Returns the products with the given (canonical) external identifier.
+pub async fn products_by_external_id(
+    kind: ExternalIdKind,
+    id: &str,
+    db: &Db,
+) -> Result<Vec<api::ProductShort>, BackendError> {
+    let products = db.find_products_by_external_id(kind, id).await?;
+    Ok(into_api_shorts(products, &relevance(db).await?))
+}
+
+pub async fn organisations_by_identifier(
+    id: &str,
+    db: &Db,
+) -> Result<Vec<api::OrganisationShort>, BackendError> {
+    let identifier = normalize_identifier(id);
+    let vat = normalize_vat(id);
+    Ok(db
+        .find_organisations_by_identifier(&identifier, &vat)
+        .await?
+        .into_iter()
+        .map(|o| o.into_api_short())
+        .collect())
+}
+
 pub async fn search_by_text(
     query: String,
     db: &Db,
 ) -> Result<Vec<api::TextSearchResult>, BackendError> {
-    let mut collector = ResultCollector::default();
-    let mut matches: Vec<&str> = query.split(' ').collect();
-    matches.retain(|m| !m.is_empty());
+    let results = search_by_text_detailed(&query, db).await?;
+    Ok(results.into_iter().map(|r| r.result).collect())
+}
 
-    if matches.len() == 1 {
-        let lowercase_match = matches.first().unwrap().to_lowercase();
-        let uppercase_match = matches.first().unwrap().to_uppercase();
+/// Searches by text, reporting also which fields of the results were matched.
+///
+/// Queries with no results are recorded in the log of such queries (if enabled).
+pub async fn search_by_text_detailed(
+    query: &str,
+    db: &Db,
+) -> Result<Vec<DetailedSearchResult>, BackendError> {
+    let key = query.trim();
+    let results = match db.cache().search.get(key) {
+        Some(results) => results,
+        None => {
+            let results = find_by_text(key, db).await?;
+            db.cache().search.insert(key.to_owned(), results.clone());
+            results
+        }
+    };
+    if results.is_empty() {
+        analytics::record(key, db).await;
+    }
+    Ok(results)
+}
+
+async fn find_by_text(query: &str, db: &Db) -> Result<Vec<DetailedSearchResult>, BackendError> {
+    let query = SearchQuery::parse(query, &db.search_config().stop_words);
+    let mut collector = ResultCollector::new(db.search_config().clone());
+
+    if let Some(single_match) = query.single_word() {
+        let lowercase_match = single_match.to_lowercase();
+        let identifier = normalize_identifier(single_match);
 
         // Search organisation by VAT
         {
-            let items = db.search_organisations_substring_by_vat_number(&uppercase_match).await?;
-            collector.add_organisations(items, &uppercase_match, None);
+            let vat = normalize_vat(&identifier);
+            let items = db
+                .search_organisations_exact_by_identifier(IdentifierKind::Vat, &[vat.clone()])
+                .await?;
+            collector.add_organisations(items, &vat, None, MatchedField::Vat);
+        }
+
+        // Search organisation by LEI
+        if is_lei(&identifier) {
+            let items = db
+                .search_organisations_exact_by_identifier(
+                    IdentifierKind::Lei,
+                    &[identifier.clone()],
+                )
+                .await?;
+            collector.add_organisations(items, &identifier, None, MatchedField::Lei);
+        }
+
+        // Search organisation by registration number
+        if !identifier.is_empty() {
+            let kind = IdentifierKind::RegistrationNumber;
+            let items =
+                db.search_organisations_exact_by_identifier(kind, &[identifier.clone()]).await?;
+            let field = MatchedField::RegistrationNumber;
+            collector.add_organisations(items, &identifier, None, field);
         }
 
         // Search product by GTIN
-        if lowercase_match.len() < 15 {
-            // TODO: search only if the match can be a valid GTIN
-            // let gtin = format!("{lowercase_match:0>14}");
-            let items = db.search_products_exact_by_gtin(&lowercase_match).await?;
-            collector.add_products(items, &lowercase_match, None);
+        if let Ok(gtin) = gtin::normalize(single_match) {
+            let items = db.search_products_exact_by_gtin(&gtin).await?;
+            collector.add_products(items, &lowercase_match, None, MatchedField::Gtin);
         }
 
         // Search organisation by website
-        {
-            let items = db.search_organisations_substring_by_website(&lowercase_match).await?;
-            collector.add_organisations(items, &lowercase_match, None);
+        if lowercase_match.contains('.') {
+            let domains = domain_candidates(&lowercase_match);
+            let items = db
+                .search_organisations_exact_by_identifier(IdentifierKind::Domain, &domains)
+                .await?;
+            collector.add_organisations(items, &lowercase_match, None, MatchedField::Website);
+        }
+    }
+
+    // Search by explicitly specified fields
+    for (field, value) in &query.fields {
+        match field {
+            Field::Gtin => {
+                if let Ok(gtin) = gtin::normalize(value) {
+                    let items = db.search_products_exact_by_gtin(&gtin).await?;
+                    collector.add_products(items, value, None, MatchedField::Gtin);
+                }
+            }
+            Field::Site => {
+                let site = value.to_lowercase();
+                let domains = domain_candidates(&site);
+                let items = db
+                    .search_organisations_exact_by_identifier(IdentifierKind::Domain, &domains)
+                    .await?;
+                collector.add_organisations(items, &site, None, MatchedField::Website);
+            }
+            Field::Brand => {
+                let brand = db.search_config().stop_words.filter(keywords::tokenize(value));
+                for (i, m) in brand.iter().enumerate() {
+                    let items = db.search_organisations_exact_by_keyword(m).await?;
+                    collector.add_organisations(items, m, Some(i), MatchedField::Label);
+                }
+            }
         }
     }
 
     // Search organisations and products by keyword
-    let lowercase_matches: Vec<String> = matches.into_iter().map(|m| m.to_lowercase()).collect();
-    for (i, m) in lowercase_matches.iter().enumerate() {
+    for (i, m) in query.keywords.iter().enumerate() {
         let items = db.search_organisations_exact_by_keyword(m).await?;
-        collector.add_organisations(items, m, Some(i));
+        collector.add_organisations(items, m, Some(i), MatchedField::Label);
     }
-    for (i, m) in lowercase_matches.iter().enumerate() {
+    for (i, m) in query.keywords.iter().enumerate() {
         let items = db.search_products_exact_by_keyword(m).await?;
-        collector.add_products(items, m, Some(i));
+        collector.add_products(items, m, Some(i), MatchedField::Label);
     }
 
-    Ok(collector.gather_results())
+    // Apply phrase and exclusion constraints
+    collector.retain(|result| query.accepts(&result.label));
+
+    // Keep only the products made by the requested brands
+    for (field, value) in &query.fields {
+        if *field == Field::Brand {
+            let brand = db.search_config().stop_words.filter(keywords::tokenize(value));
+            if !brand.is_empty() {
+                let keys = collector.product_keys();
+                let kept = db.filter_products_by_manufacturer_keywords(&keys, &brand).await?;
+                collector.retain_products(&kept.into_iter().collect());
+            }
+        }
+    }
+
+    let labels = organisation_labels(&collector.manufacturer_keys(), db).await?;
+    Ok(collector.gather_detailed_results(&labels))
 }
 
 #[cfg(test)]
 mod tests {
-    use std::str::FromStr;
+    use std::{collections::BTreeMap, str::FromStr};
 
     use super::*;
 
@@ -289,15 +1426,15 @@ mod tests {
 
         {
             let mut collector = ResultCollector::default();
-            collector.add(&[r2.clone(), r1.clone()], "", None);
-            collector.add(&[r3.clone(), r1.clone()], "", None);
+            collector.add(&[r2.clone(), r1.clone()], "", None, MatchedField::Label);
+            collector.add(&[r3.clone(), r1.clone()], "", None, MatchedField::Label);
 
             assert_eq!(collector.gather_scored_results(), expected_results);
         }
         {
             let mut collector = ResultCollector::default();
-            collector.add(&[r1.clone(), r3.clone()], "", None);
-            collector.add(&[r1.clone(), r2.clone()], "", None);
+            collector.add(&[r1.clone(), r3.clone()], "", None, MatchedField::Label);
+            collector.add(&[r1.clone(), r2.clone()], "", None, MatchedField::Label);
 
             assert_eq!(collector.gather_scored_results(), expected_results);
         }
@@ -316,8 +1453,8 @@ mod tests {
         let expected_results = [s1, s3, s2];
 
         let mut collector = ResultCollector::default();
-        collector.add(&[r2.clone(), r1.clone()], "", Some(1));
-        collector.add(&[r3.clone(), r1.clone()], "", Some(0));
+        collector.add(&[r2.clone(), r1.clone()], "", Some(1), MatchedField::Label);
+        collector.add(&[r3.clone(), r1.clone()], "", Some(0), MatchedField::Label);
 
         assert_eq!(collector.gather_scored_results(), expected_results);
     }
@@ -336,9 +1473,369 @@ mod tests {
         let expected_results = [s1, s3, s2];
 
         let mut collector = ResultCollector::default();
-        collector.add(&[r2.clone(), r1.clone()], "4", None);
-        collector.add(&[r3.clone(), r1.clone()], "Fairphone", None);
+        collector.add(&[r2.clone(), r1.clone()], "4", None, MatchedField::Label);
+        collector.add(&[r3.clone(), r1.clone()], "Fairphone", None, MatchedField::Label);
 
         assert_eq!(collector.gather_scored_results(), expected_results);
     }
+
+    /// Quality signals break ties between equally matching results.
+    #[test]
+    fn quality() {
+        let weights = SearchConfig::default();
+        let good = SearchQuality {
+            num_certifications: 2,
+            score: Some(0.5),
+            has_description: true,
+            has_image: false,
+        };
+        let boost = quality_boost(&good, &weights);
+        assert!((boost - (0.2 + 0.1 + 0.05)).abs() < 1e-9);
+        assert_eq!(quality_boost(&SearchQuality::default(), &weights), 0.0);
+
+        let (r1, _, r3) = prepare_data();
+        let mut collector = ResultCollector::new(weights);
+        collector.boosts.insert(r3.0.clone(), boost);
+        collector.add(&[r1.clone(), r3.clone()], "Fairphone", None, MatchedField::Label);
+
+        let results = collector.gather_scored_results();
+        assert_eq!(results[0].result, r3.1);
+        assert_eq!(results[1].result, r1.1);
+    }
+
+    /// The matched fields and positions are remembered.
+    /// - keywords not found in the label are reported as aliases
+    /// - matches of results removed by constraints are dropped
+    #[test]
+    fn offsets() {
+        let offsets = |label, token| -> Vec<(usize, usize)> {
+            find_offsets(label, token).iter().map(|o| (o.start, o.end)).collect()
+        };
+        assert_eq!(offsets("Fairphone 4", "FAIR"), vec![(0, 4)]);
+        assert_eq!(offsets("Crème brûlée", "creme"), vec![(0, 5)]);
+        assert_eq!(offsets("Weißbier", "weiss"), vec![(0, 4)]);
+        assert_eq!(offsets("Ｓｏｎｙ Sony", "sony"), vec![(0, 4), (5, 9)]);
+        assert_eq!(offsets("aaa", "aa"), vec![(0, 2)]);
+        assert_eq!(offsets("😀 Sony", "sony"), vec![(3, 7)]);
+        assert_eq!(offsets("Fairphone", ""), vec![]);
+    }
+
+    #[test]
+    fn product_restriction() {
+        let (r1, r2, r3) = prepare_data();
+
+        let mut collector = ResultCollector::default();
+        collector.add(&[r1.clone(), r2.clone(), r3.clone()], "4", None, MatchedField::Label);
+        let mut keys = collector.product_keys();
+        keys.sort();
+        assert_eq!(keys, vec!["1".to_owned(), "2".to_owned(), "3".to_owned()]);
+
+        collector.retain_products(&HashSet::from(["1".to_owned(), "3".to_owned()]));
+        let results = collector.gather_detailed_results(&HashMap::new());
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|result| result.result != r2.1));
+    }
+
+    #[test]
+    fn matches() {
+        let (r1, r2, r3) = prepare_data();
+
+        let mut collector = ResultCollector::default();
+        collector.add(&[r1.clone(), r2.clone()], "4", Some(0), MatchedField::Label);
+        collector.add(&[r1.clone()], "fairphone", Some(1), MatchedField::Label);
+        collector.add(&[r3.clone()], "fp", Some(1), MatchedField::Label);
+        collector.add(&[r1.clone()], "123", None, MatchedField::Gtin);
+        collector.retain(|result| result.label.as_str() != "Samsung 4");
+
+        let results = collector.gather_detailed_results(&HashMap::new());
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].result, r1.1);
+        assert_eq!(
+            results[0].matches,
+            vec![
+                SearchMatch {
+                    field: MatchedField::Label,
+                    token: "4".to_owned(),
+                    offsets: vec![MatchOffset { start: 10, end: 11 }],
+                },
+                SearchMatch {
+                    field: MatchedField::Label,
+                    token: "fairphone".to_owned(),
+                    offsets: vec![MatchOffset { start: 0, end: 9 }],
+                },
+                SearchMatch { field: MatchedField::Gtin, token: "123".to_owned(), offsets: vec![] },
+            ]
+        );
+        assert_eq!(results[1].result, r3.1);
+        assert_eq!(
+            results[1].matches,
+            vec![SearchMatch {
+                field: MatchedField::Alias,
+                token: "fp".to_owned(),
+                offsets: vec![]
+            }]
+        );
+    }
+
+    /// Category paths are split into levels.
+    /// - intermediate levels are created even if they hold no products
+    /// - nodes are sorted by name
+    #[test]
+    fn category_tree_building() {
+        let categories = vec![
+            Category {
+                db_key: "food/dairy".to_owned(),
+                num_products: 3,
+                relevant_certifications: None,
+            },
+            Category {
+                db_key: "electronics".to_owned(),
+                num_products: 1,
+                relevant_certifications: None,
+            },
+            Category {
+                db_key: "food/bakery".to_owned(),
+                num_products: 2,
+                relevant_certifications: None,
+            },
+        ];
+
+        let mut dairy = CategoryNode::new("dairy", "food/dairy");
+        dairy.num_products = 3;
+        let mut bakery = CategoryNode::new("bakery", "food/bakery");
+        bakery.num_products = 2;
+        let mut food = CategoryNode::new("food", "food");
+        food.children = vec![bakery, dairy];
+        let mut electronics = CategoryNode::new("electronics", "electronics");
+        electronics.num_products = 1;
+
+        assert_eq!(build_category_tree(categories), vec![electronics, food]);
+    }
+
+    #[test]
+    fn irrelevant_certifications() {
+        use sustainity_models::store::{CertificationKind, TcoCert, Validity};
+
+        let mut product = prepare_product("1", None);
+        product.certifications.tco =
+            Some(TcoCert { brand_name: "A".to_owned(), validity: Validity::default() });
+        let categories = vec![Category {
+            db_key: "smartphone".to_owned(),
+            num_products: 1,
+            relevant_certifications: Some(vec![CertificationKind::Tco]),
+        }];
+        let relevance = RelevanceMatrix::from_categories(&categories);
+        assert!(hide_irrelevant(product.clone(), &relevance).certifications.tco.is_some());
+
+        let categories = vec![Category {
+            db_key: "smartphone".to_owned(),
+            num_products: 1,
+            relevant_certifications: Some(vec![CertificationKind::BCorp]),
+        }];
+        let relevance = RelevanceMatrix::from_categories(&categories);
+        assert!(hide_irrelevant(product, &relevance).certifications.tco.is_none());
+    }
+
+    #[test]
+    fn footprint_extraction() {
+        assert_eq!(extract_footprint(&Attributes::new()), None);
+        let attributes = Attributes::from([
+            (attributes::CARBON_FOOTPRINT.to_owned(), AttributeValue::Number(0.9)),
+            (
+                attributes::CARBON_FOOTPRINT_BASIS.to_owned(),
+                AttributeValue::Text("en:butters".to_owned()),
+            ),
+        ]);
+        assert_eq!(
+            extract_footprint(&attributes),
+            Some(CarbonFootprint {
+                kg_co2e_per_100g: 0.9,
+                provenance: "estimate".to_owned(),
+                basis: Some("en:butters".to_owned()),
+            })
+        );
+    }
+
+    #[test]
+    fn ownership_levels() {
+        use crate::models::OwnershipEntry;
+
+        let entry: OwnershipEntry = serde_json::from_value(serde_json::json!({
+            "depth": 2,
+            "owns": "7",
+            "organisation": {
+                "_key": "3",
+                "ids": {"wiki": ["42"], "vat_ids": [], "domains": []},
+                "names": [{"text": "Parent Inc.", "source": "wiki"}],
+                "descriptions": [],
+                "images": [],
+                "websites": [],
+                "certifications": {
+                    "bcorp": {"id": "parent-inc"},
+                    "tco": {"brand_name": "Parent", "validity": {"valid_until": "2020-01-01"}}
+                }
+            }
+        }))
+        .unwrap();
+
+        let level = entry.into_level("2024-01-01");
+        assert_eq!(level.depth, 2);
+        assert_eq!(level.id, "3");
+        assert_eq!(level.name.as_deref(), Some("Parent Inc."));
+        assert_eq!(level.owns.as_deref(), Some("7"));
+        assert!(level.certifications.bcorp.is_some());
+        assert!(level.certifications.tco.is_none());
+    }
+
+    #[test]
+    fn identifier_normalization() {
+        assert_eq!(normalize_identifier("de 123.456-789"), "DE123456789");
+        assert_eq!(normalize_vat("GR 094014201"), "EL094014201");
+        assert_eq!(normalize_vat("el094014201"), "EL094014201");
+        assert_eq!(normalize_vat("123456789"), "123456789");
+    }
+
+    #[test]
+    fn product_id_normalization() {
+        let gtin = api::ProductIdVariant::Gtin;
+        assert_eq!(normalize_product_id(gtin, "8717677339556").as_deref(), Some("08717677339556"));
+        assert_eq!(normalize_product_id(gtin, "8717677339557"), None);
+        assert_eq!(normalize_product_id(api::ProductIdVariant::Wiki, "Q1").as_deref(), Some("Q1"));
+    }
+
+    #[test]
+    fn lei_validation() {
+        assert!(is_lei("5493001KJTIIGC8Y1R12"));
+        assert!(is_lei("7LTWFZYICNSX8D621K86"));
+        assert!(!is_lei("5493001KJTIIGC8Y1R13"));
+        assert!(!is_lei("5493001KJTIIGC8Y1R1"));
+        assert!(!is_lei("5493001KJTIIGC8Y1RAB"));
+    }
+
+    #[test]
+    fn website_domains() {
+        assert_eq!(
+            domain_candidates("https://www.Shop.Example.co.uk:8080/about"),
+            vec!["shop.example.co.uk", "example.co.uk"]
+        );
+        assert_eq!(
+            domain_candidates("https://a.b.fairphone.com/shop"),
+            vec!["a.b.fairphone.com", "b.fairphone.com", "fairphone.com"]
+        );
+        assert_eq!(domain_candidates("fairphone.com"), vec!["fairphone.com"]);
+        assert_eq!(
+            domain_candidates("https://docs.someone.github.io"),
+            vec!["docs.someone.github.io", "someone.github.io"]
+        );
+        assert_eq!(domain_candidates("http://192.168.0.1:8080/"), vec!["192.168.0.1"]);
+        assert!(domain_candidates("github.io").is_empty());
+        assert!(domain_candidates("co.uk").is_empty());
+        assert!(domain_candidates("fairphone").is_empty());
+    }
+
+    fn prepare_product(key: &str, score: Option<f64>) -> Product {
+        use sustainity_models::{
+            models::SustainityScoreCategory,
+            store::{
+                Attributes, Certifications, ProductIds, SustainityScore, SustainityScoreBranch,
+            },
+        };
+
+        let tree = score
+            .map(|score| {
+                vec![SustainityScoreBranch {
+                    branches: Vec::new(),
+                    category: SustainityScoreCategory::NumCerts,
+                    weight: 1,
+                    score,
+                }]
+            })
+            .unwrap_or_default();
+
+        Product {
+            db_key: key.to_owned(),
+            ids: ProductIds { eans: Vec::new(), gtins: Vec::new(), wiki: Vec::new() },
+            names: Vec::new(),
+            keywords: Vec::new(),
+            descriptions: Vec::new(),
+            images: Vec::new(),
+            categories: vec!["smartphone".to_owned()],
+            regions: Regions::World,
+            certifications: Certifications::default(),
+            manufacturer_ids: vec![format!("m{key}")],
+            follows: Vec::new(),
+            followed_by: Vec::new(),
+            also_known_as: Vec::new(),
+            external_ids: BTreeMap::new(),
+            variant_of: None,
+            variants: Vec::new(),
+            attributes: Attributes::default(),
+            sustainity_score: SustainityScore { tree, total: score.unwrap_or_default() },
+        }
+    }
+
+    /// Every row of the comparison has one value per compared product.
+    /// - subscores missing in some products are reported as `None`
+    #[test]
+    fn comparison_alignment() {
+        let mut p1 = prepare_product("1", Some(0.5));
+        p1.certifications.bcorp = Some(sustainity_models::store::BCorpCert {
+            id: "b".to_owned(),
+            validity: sustainity_models::store::Validity::default(),
+        });
+        let p2 = prepare_product("2", None);
+
+        let names = HashMap::from([("m1".to_owned(), "Fairphone".to_owned())]);
+        let comparison = compare(vec![p1, p2], vec!["3".to_owned()], &names);
+
+        assert_eq!(comparison.products.len(), 2);
+        assert_eq!(comparison.not_found, vec!["3".to_owned()]);
+        assert_eq!(comparison.certifications[0], ComparisonRow::new("bcorp", vec![true, false]));
+        assert_eq!(
+            comparison.stale_certifications[0],
+            ComparisonRow::new("bcorp", vec![false, false])
+        );
+        assert_eq!(
+            comparison.scores,
+            vec![
+                ComparisonRow::new("total", vec![Some(0.5), Some(0.0)]),
+                ComparisonRow::new("num_certs", vec![Some(0.5), None]),
+                ComparisonRow::new("fti", vec![None, None]),
+            ]
+        );
+        assert_eq!(
+            comparison.attributes[2],
+            ComparisonRow::new("manufacturers", vec![vec!["m1".to_owned()], vec!["m2".to_owned()]])
+        );
+        assert_eq!(
+            comparison.attributes[3],
+            ComparisonRow::new(
+                "manufacturer_names",
+                vec![vec!["Fairphone".to_owned()], vec!["m2".to_owned()]]
+            )
+        );
+    }
+
+    #[test]
+    fn precomputed_alternatives() {
+        let options = AlternativesOptions::default();
+        assert!(uses_precomputed(&options));
+        assert!(uses_precomputed(&AlternativesOptions { max_per_category: 5, ..options.clone() }));
+        assert!(!uses_precomputed(&AlternativesOptions {
+            require_certification: !options.require_certification,
+            ..options.clone()
+        }));
+
+        let stored = |len: u32, exhaustive: bool| PrecomputedAlternatives {
+            alternatives: (0..len).collect::<Vec<_>>(),
+            exhaustive,
+        };
+        assert_eq!(truncate_precomputed(stored(20, false), &options).map(|a| a.len()), Some(10));
+        let options = AlternativesOptions { max_per_category: 5, ..options };
+        assert_eq!(truncate_precomputed(stored(20, false), &options), Some(vec![0, 1, 2, 3, 4]));
+        let options = AlternativesOptions { max_per_category: 30, ..options };
+        assert_eq!(truncate_precomputed(stored(20, false), &options), None);
+        // A small category has fewer alternatives than requested, but all of them are stored.
+        assert_eq!(truncate_precomputed(stored(3, true), &options), Some(vec![0, 1, 2]));
+    }
 }
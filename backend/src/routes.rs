@@ -0,0 +1,1789 @@
+//! Routes not covered by the generated API.
+//!
+//! The `sustainity_api` crate is generated from the OpenAPI specification. Endpoints which are not
+//! (yet) part of the specification are served here. All other requests are passed through to the
+//! generated service.
+
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::Arc,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use futures::future::BoxFuture;
+use hyper::{
+    body::HttpBody,
+    header::{self, HeaderValue},
+    server::conn::AddrStream,
+    service::Service,
+    Body, Method, Request, Response, StatusCode,
+};
+use percent_encoding::percent_decode_str;
+use serde::{de::IntoDeserializer, Deserialize, Serialize};
+use tracing::Instrument;
+
+use sustainity_api::models as api;
+use sustainity_models::{
+    feedback::{FeedbackStatus, FeedbackSubmission},
+    product_requests::ProductRequestSubmission,
+};
+
+use crate::{
+    analytics,
+    auth::{ApiKeys, Authentication},
+    cache::Caches,
+    config::{DatasetConfig, SiteConfig},
+    db::Db,
+    errors::{BackendError, ReloadError},
+    etag,
+    export::{self, Compression, ExportCollection, Exporter, Prepared},
+    feedback::{self, Moderation},
+    languages::Languages,
+    metrics,
+    models::{
+        AlternativesOptions, ChangesPage, FeedbackReceipt, FeedbackStatusChange, LibraryLookup,
+        ProductRequestReceipt, Scope,
+    },
+    problems::{self, ErrorCode, FieldError, Problem},
+    product_requests,
+    rate_limit::RateLimiters,
+    reload, retrieve,
+    seo::{self, SitemapCollection},
+    server::{CORS_HEADERS, CORS_METHODS, CORS_ORIGIN},
+    validation::{self, Validator},
+    webhooks::Webhooks,
+};
+
+/// Maximal size of accepted request bodies.
+const MAX_BODY_SIZE: usize = 64 * 1024;
+
+/// Query parameter selecting a dataset snapshot.
+const DATASET_PARAM: &str = "dataset";
+
+/// Header carrying the request ID.
+const SPAN_ID_HEADER: &str = "x-span-id";
+
+type Query = HashMap<String, String>;
+
+fn parse_query<B>(request: &Request<B>) -> Query {
+    request
+        .uri()
+        .query()
+        .map(|query| form_urlencoded::parse(query.as_bytes()).into_owned().collect())
+        .unwrap_or_default()
+}
+
+/// Parses an optional numeric query parameter.
+fn parse_number(query: &Query, name: &str) -> Result<Option<usize>, std::num::ParseIntError> {
+    query.get(name).map(|value| value.parse::<usize>()).transpose()
+}
+
+/// Parses an optional boolean query parameter.
+fn parse_flag(query: &Query, name: &str) -> Result<bool, ()> {
+    match query.get(name).map(String::as_str) {
+        None | Some("false" | "0") => Ok(false),
+        Some("true" | "1" | "") => Ok(true),
+        Some(_) => Err(()),
+    }
+}
+
+/// Parses the product ID variant from a path, checking also the format of the ID.
+///
+/// Returns `Err` with the response to send if the variant is unknown or the ID is malformed.
+fn parse_product_variant(variant: &str, id: &str) -> Result<api::ProductIdVariant, Response<Body>> {
+    validation::product_id(variant, id).map_err(|error| respond_invalid(vec![error]))
+}
+
+/// Checks the parameters of requests passed to the generated service.
+fn validate_generated(path: &str, query: &Query) -> Result<(), Vec<FieldError>> {
+    let decode = |param: &str| percent_decode_str(param).decode_utf8_lossy().into_owned();
+    let segments: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+    let mut validator = Validator::new();
+    match segments.as_slice() {
+        ["search", "text"] => {
+            validator.check(validation::search_query(query.get("query").map(String::as_str)));
+        }
+        ["library", topic] => {
+            validator.check(validation::topic(&decode(topic)));
+        }
+        ["product", variant, id] => {
+            validator.check(validation::product_id(&decode(variant), &decode(id)));
+        }
+        ["organisation", variant, id] => {
+            validator.check(validation::organisation_id(&decode(variant), &decode(id)));
+        }
+        _ => {}
+    }
+    validator.check(validation::region(query.get("region").map(String::as_str)));
+    validator.finish()
+}
+
+fn respond_invalid(errors: Vec<FieldError>) -> Response<Body> {
+    let problem = Problem::invalid("Invalid request parameters", errors);
+    let response = respond_json(problem.code.status(), &problem);
+    with_content_type(response, problems::CONTENT_TYPE)
+}
+
+/// Reads the whole request body.
+async fn read_body(request: Request<Body>) -> Result<Vec<u8>, Response<Body>> {
+    let mut body = request.into_body();
+    let mut content = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|err| {
+            tracing::warn!("Failed to read request body: {err}");
+            respond_error(ErrorCode::InvalidBody, "Failed to read the request body")
+        })?;
+        if content.len() + chunk.len() > MAX_BODY_SIZE {
+            return Err(respond_error(ErrorCode::PayloadTooLarge, "Request body too large"));
+        }
+        content.extend_from_slice(&chunk);
+    }
+    Ok(content)
+}
+
+/// Reads the request body as JSON.
+async fn read_json<T>(request: Request<Body>) -> Result<T, Response<Body>>
+where
+    for<'de> T: Deserialize<'de>,
+{
+    let body = read_body(request).await?;
+    serde_json::from_slice(&body)
+        .map_err(|err| respond_error(ErrorCode::InvalidBody, &format!("Invalid body: {err}")))
+}
+
+fn respond(status: StatusCode, content: Option<Vec<u8>>) -> Response<Body> {
+    let has_content = content.is_some();
+    let mut response = Response::new(content.map_or_else(Body::empty, Body::from));
+    *response.status_mut() = status;
+
+    let headers = response.headers_mut();
+    headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, HeaderValue::from_static(CORS_ORIGIN));
+    headers.insert(header::ACCESS_CONTROL_ALLOW_METHODS, HeaderValue::from_static(CORS_METHODS));
+    headers.insert(header::ACCESS_CONTROL_ALLOW_HEADERS, HeaderValue::from_static(CORS_HEADERS));
+    if has_content {
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    }
+    response
+}
+
+fn with_etag(mut response: Response<Body>, etag: &str) -> Response<Body> {
+    match HeaderValue::from_str(etag) {
+        Ok(value) => {
+            response.headers_mut().insert(header::ETAG, value);
+        }
+        Err(err) => tracing::error!("Invalid ETag `{etag}`: {err}"),
+    }
+    response
+}
+
+fn with_content_type(mut response: Response<Body>, content_type: &'static str) -> Response<Body> {
+    response.headers_mut().insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+    response
+}
+
+fn respond_json<T: Serialize>(status: StatusCode, body: &T) -> Response<Body> {
+    match serde_json::to_vec(body) {
+        Ok(content) => respond(status, Some(content)),
+        Err(err) => {
+            tracing::error!("Failed to serialize a response: {err}");
+            respond(StatusCode::INTERNAL_SERVER_ERROR, None)
+        }
+    }
+}
+
+/// Attaches problem details to an error response of the generated service.
+///
+/// The status and the headers (e.g. `Allow`) are kept, only the body is replaced.
+fn with_problem(response: Response<Body>) -> Response<Body> {
+    let (mut parts, _) = response.into_parts();
+    let code = ErrorCode::from_status(parts.status);
+    let problem = Problem::new(code, code.title()).with_status(parts.status);
+    match serde_json::to_vec(&problem) {
+        Ok(content) => {
+            parts.headers.remove(header::CONTENT_LENGTH);
+            parts
+                .headers
+                .insert(header::CONTENT_TYPE, HeaderValue::from_static(problems::CONTENT_TYPE));
+            Response::from_parts(parts, Body::from(content))
+        }
+        Err(err) => {
+            tracing::error!("Failed to serialize a problem: {err}");
+            Response::from_parts(parts, Body::empty())
+        }
+    }
+}
+
+fn respond_error(code: ErrorCode, detail: &str) -> Response<Body> {
+    let response = respond_json(code.status(), &Problem::new(code, detail));
+    with_content_type(response, problems::CONTENT_TYPE)
+}
+
+fn respond_too_many_requests(retry_after: Duration) -> Response<Body> {
+    let mut response = respond_error(ErrorCode::RateLimited, "Too many requests");
+    let seconds = retry_after.as_secs_f64().ceil().max(1.0);
+    response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(seconds as u64));
+    response
+}
+
+fn respond_result<T: Serialize>(result: Result<T, BackendError>) -> Response<Body> {
+    match result {
+        Ok(body) => respond_json(StatusCode::OK, &body),
+        Err(err) => match ErrorCode::from(&err) {
+            ErrorCode::ServiceBusy => {
+                tracing::warn!("{err}");
+                respond_error(ErrorCode::ServiceBusy, "The service is busy, try again later")
+            }
+            code => {
+                tracing::error!("{err}");
+                respond_error(code, "An internal error occurred")
+            }
+        },
+    }
+}
+
+fn respond_optional<T: Serialize>(result: Result<Option<T>, BackendError>) -> Response<Body> {
+    match result {
+        Ok(Some(body)) => respond_json(StatusCode::OK, &body),
+        Ok(None) => respond_error(ErrorCode::NotFound, "Not found"),
+        Err(err) => respond_result::<T>(Err(err)),
+    }
+}
+
+/// Lists the hand-written routes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Route {
+    /// `GET /products/compare?ids=a,b,c[&variant=gtin]`
+    CompareProducts,
+
+    /// `GET /search/detailed?query=<text>`
+    SearchDetailed,
+
+    /// `GET /products/featured[?limit=6]`
+    FeaturedProducts,
+
+    /// `GET /organisations/featured[?limit=6]`
+    FeaturedOrganisations,
+
+    /// `GET /products/{id}/alternatives[?region=XXX&limit=10]`
+    ///
+    /// Accepts also the `exclude_same_manufacturer` and `require_certification` flags.
+    ProductAlternatives(String),
+
+    /// `GET /changes?since=<version>[&offset=0&limit=500]`
+    Changes,
+
+    /// `GET /export/{collection}.jsonl.{gz|zst}` (supports the `Range` header)
+    Export(ExportCollection, Compression),
+
+    /// `GET /categories`
+    Categories,
+
+    /// `GET /certifications`
+    Certifications,
+
+    /// `GET /categories/{category}/products[?offset=0&limit=20&region=XXX]`
+    CategoryProducts(String),
+
+    /// `GET /categories/{category}/stats`
+    CategoryStats(String),
+
+    /// `GET /organisation/{variant}/{id}`
+    ///
+    /// Takes over the generated endpoint to add the data the generated models have no fields for.
+    Organisation(String, String),
+
+    /// `GET /organisation/{variant}/{id}/products[?sort=score&offset=0&limit=20]`
+    ///
+    /// The products can be sorted by `score`, `name` or `category`.
+    OrganisationProducts(String, String),
+
+    /// `GET /organisation/{variant}/{id}/controversies`
+    OrganisationControversies(String, String),
+
+    /// `GET /organisation/{variant}/{id}/policies`
+    ///
+    /// Lists the sustainability and report pages found on the organisation's own websites.
+    OrganisationPolicyPages(String, String),
+
+    /// `GET /product/{variant}/{id}/footprint`
+    ProductFootprint(String, String),
+
+    /// `GET /product/{variant}/{id}/ownership`
+    ///
+    /// Lists the manufacturers and their parent organisations with their certifications.
+    ProductOwnership(String, String),
+
+    /// `GET /product/entity/{id}[?region=XXX]` (namespaced ID, e.g. `wiki:Q123` or `off:123`)
+    ProductByEntity(String),
+
+    /// `GET /organisation/entity/{id}` (namespaced ID, e.g. `wiki:Q123` or `sus:vat-NL123`)
+    OrganisationByEntity(String),
+
+    /// `GET /organisations/identifier/{id}` (LEI, registration number or VAT number)
+    OrganisationsByIdentifier(String),
+
+    /// `GET /products/external/{kind}/{id}` (e.g. `asin/B0C1234567`)
+    ProductsByExternalId(String, String),
+
+    /// `GET /resolve?url=<page URL>[&product=<product name>]`
+    ///
+    /// Finds the organisation (and product) a web page belongs to, e.g. for a browser extension.
+    ResolveUrl,
+
+    /// `GET /library/slugs`
+    LibrarySlugs,
+
+    /// `GET /library/slug/{slug}`
+    ///
+    /// Former slugs of renamed articles are redirected to the current ones.
+    LibraryBySlug(String),
+
+    /// `POST /admin/cache/clear`
+    ClearCache,
+
+    /// `POST /admin/reload` (optionally with the dataset config in the body)
+    Reload,
+
+    /// `GET /admin/snapshots`
+    ListSnapshots,
+
+    /// `GET /admin/search/zero-results[?offset=0&limit=20]`
+    ///
+    /// Lists the logged queries which returned no results, the most frequent first.
+    ZeroResultQueries,
+
+    /// `POST /admin/snapshots/{name}` (with the dataset config in the body)
+    MountSnapshot(String),
+
+    /// `DELETE /admin/snapshots/{name}`
+    UnmountSnapshot(String),
+
+    /// `GET /healthz`
+    Health,
+
+    /// `GET /readyz`
+    Readiness,
+
+    /// `GET /metrics`
+    Metrics,
+
+    /// `POST /feedback`
+    SubmitFeedback,
+
+    /// `GET /feedback[?status=pending&offset=0&limit=20]`
+    ListFeedback,
+
+    /// `POST /feedback/{id}/status`
+    ModerateFeedback(String),
+
+    /// `POST /products/request`
+    ///
+    /// Requests a product missing in the dataset (by its GTIN or name).
+    SubmitProductRequest,
+
+    /// `GET /products/requests[?offset=0&limit=20]`
+    ///
+    /// Lists the requested products, the most requested first.
+    ListProductRequests,
+
+    /// `GET /sitemap.xml`
+    Sitemap,
+
+    /// `GET /sitemaps/{collection}/{page}.xml`
+    SitemapPage(SitemapCollection, usize),
+
+    /// `GET /jsonld/organisation/{variant}/{id}`
+    OrganisationJsonLd(String, String),
+
+    /// `GET /jsonld/product/{variant}/{id}`
+    ProductJsonLd(String, String),
+}
+
+impl Route {
+    fn find(method: &Method, path: &str) -> Option<Self> {
+        match (method, path) {
+            (&Method::GET, "/products/compare") => Some(Self::CompareProducts),
+            (&Method::GET, "/search/detailed") => Some(Self::SearchDetailed),
+            (&Method::GET, "/resolve") => Some(Self::ResolveUrl),
+            (&Method::GET, "/products/featured") => Some(Self::FeaturedProducts),
+            (&Method::GET, "/organisations/featured") => Some(Self::FeaturedOrganisations),
+            (&Method::GET, "/changes") => Some(Self::Changes),
+            (&Method::GET, "/categories") => Some(Self::Categories),
+            (&Method::GET, "/certifications") => Some(Self::Certifications),
+            (&Method::GET, "/library/slugs") => Some(Self::LibrarySlugs),
+            (&Method::GET, "/healthz") => Some(Self::Health),
+            (&Method::GET, "/readyz") => Some(Self::Readiness),
+            (&Method::GET, "/metrics") => Some(Self::Metrics),
+            (&Method::GET, "/feedback") => Some(Self::ListFeedback),
+            (&Method::GET, "/products/requests") => Some(Self::ListProductRequests),
+            (&Method::GET, "/sitemap.xml") => Some(Self::Sitemap),
+            (&Method::GET, "/admin/snapshots") => Some(Self::ListSnapshots),
+            (&Method::GET, "/admin/search/zero-results") => Some(Self::ZeroResultQueries),
+            (&Method::GET, path) => Self::find_with_params(path),
+            (&Method::POST, "/admin/cache/clear") => Some(Self::ClearCache),
+            (&Method::POST, "/admin/reload") => Some(Self::Reload),
+            (&Method::POST, "/feedback") => Some(Self::SubmitFeedback),
+            (&Method::POST, "/products/request") => Some(Self::SubmitProductRequest),
+            (&Method::POST, path) => {
+                if let Some(name) = snapshot_name(path) {
+                    return Some(Self::MountSnapshot(name.to_owned()));
+                }
+                let id = path.strip_prefix("/feedback/")?.strip_suffix("/status")?;
+                (!id.is_empty() && !id.contains('/')).then(|| Self::ModerateFeedback(id.to_owned()))
+            }
+            (&Method::DELETE, path) => {
+                snapshot_name(path).map(|name| Self::UnmountSnapshot(name.to_owned()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Finds routes with parameters in the path.
+    fn find_with_params(path: &str) -> Option<Self> {
+        let decode = |param: &str| percent_decode_str(param).decode_utf8_lossy().into_owned();
+
+        if let Some(id) = path.strip_prefix("/product/entity/") {
+            return (!id.is_empty() && !id.contains('/'))
+                .then(|| Self::ProductByEntity(decode(id)));
+        }
+        if let Some(id) = path.strip_prefix("/organisation/entity/") {
+            return (!id.is_empty() && !id.contains('/'))
+                .then(|| Self::OrganisationByEntity(decode(id)));
+        }
+        if let Some(id) = path.strip_prefix("/organisations/identifier/") {
+            return (!id.is_empty()).then(|| Self::OrganisationsByIdentifier(decode(id)));
+        }
+        if let Some(path) = path.strip_prefix("/products/external/") {
+            let (kind, id) = path.split_once('/')?;
+            return (!id.is_empty() && !id.contains('/'))
+                .then(|| Self::ProductsByExternalId(decode(kind), decode(id)));
+        }
+        if let Some(slug) = path.strip_prefix("/library/slug/") {
+            return (!slug.is_empty() && !slug.contains('/'))
+                .then(|| Self::LibraryBySlug(decode(slug)));
+        }
+        if let Some(category) =
+            path.strip_prefix("/categories/").and_then(|path| path.strip_suffix("/products"))
+        {
+            return (!category.is_empty()).then(|| Self::CategoryProducts(decode(category)));
+        }
+        if let Some(category) =
+            path.strip_prefix("/categories/").and_then(|path| path.strip_suffix("/stats"))
+        {
+            return (!category.is_empty()).then(|| Self::CategoryStats(decode(category)));
+        }
+        if let Some(id) =
+            path.strip_prefix("/products/").and_then(|path| path.strip_suffix("/alternatives"))
+        {
+            return (!id.is_empty() && !id.contains('/'))
+                .then(|| Self::ProductAlternatives(decode(id)));
+        }
+        if let Some(path) =
+            path.strip_prefix("/organisation/").and_then(|path| path.strip_suffix("/products"))
+        {
+            let (variant, id) = path.split_once('/')?;
+            return (!id.is_empty() && !id.contains('/'))
+                .then(|| Self::OrganisationProducts(decode(variant), decode(id)));
+        }
+        if let Some(path) =
+            path.strip_prefix("/organisation/").and_then(|path| path.strip_suffix("/controversies"))
+        {
+            let (variant, id) = path.split_once('/')?;
+            return (!id.is_empty() && !id.contains('/'))
+                .then(|| Self::OrganisationControversies(decode(variant), decode(id)));
+        }
+        if let Some(path) =
+            path.strip_prefix("/organisation/").and_then(|path| path.strip_suffix("/policies"))
+        {
+            let (variant, id) = path.split_once('/')?;
+            return (!id.is_empty() && !id.contains('/'))
+                .then(|| Self::OrganisationPolicyPages(decode(variant), decode(id)));
+        }
+        if let Some(path) = path.strip_prefix("/organisation/") {
+            let (variant, id) = path.split_once('/')?;
+            return (!id.is_empty() && !id.contains('/'))
+                .then(|| Self::Organisation(decode(variant), decode(id)));
+        }
+        if let Some(path) =
+            path.strip_prefix("/product/").and_then(|path| path.strip_suffix("/footprint"))
+        {
+            let (variant, id) = path.split_once('/')?;
+            return (!id.is_empty() && !id.contains('/'))
+                .then(|| Self::ProductFootprint(decode(variant), decode(id)));
+        }
+        if let Some(path) =
+            path.strip_prefix("/product/").and_then(|path| path.strip_suffix("/ownership"))
+        {
+            let (variant, id) = path.split_once('/')?;
+            return (!id.is_empty() && !id.contains('/'))
+                .then(|| Self::ProductOwnership(decode(variant), decode(id)));
+        }
+        if let Some(name) = path.strip_prefix("/export/") {
+            let (collection, compression) = export::parse_file_name(name)?;
+            return Some(Self::Export(collection, compression));
+        }
+        if let Some(page) = path.strip_prefix("/sitemaps/").and_then(|p| p.strip_suffix(".xml")) {
+            let (collection, page) = page.split_once('/')?;
+            let collection = SitemapCollection::from_name(collection)?;
+            return page.parse().ok().map(|page| Self::SitemapPage(collection, page));
+        }
+        if let Some(path) = path.strip_prefix("/jsonld/organisation/") {
+            let (variant, id) = path.split_once('/')?;
+            return (!id.is_empty()).then(|| Self::OrganisationJsonLd(decode(variant), decode(id)));
+        }
+        if let Some(path) = path.strip_prefix("/jsonld/product/") {
+            let (variant, id) = path.split_once('/')?;
+            return (!id.is_empty()).then(|| Self::ProductJsonLd(decode(variant), decode(id)));
+        }
+        None
+    }
+
+    /// Returns the scope required to access the route.
+    fn required_scope(&self) -> Option<Scope> {
+        match self {
+            Self::ClearCache
+            | Self::Reload
+            | Self::ListSnapshots
+            | Self::ZeroResultQueries
+            | Self::MountSnapshot(_)
+            | Self::UnmountSnapshot(_) => Some(Scope::Admin),
+            Self::ListFeedback | Self::ModerateFeedback(_) | Self::ListProductRequests => {
+                Some(Scope::Moderation)
+            }
+            Self::CompareProducts
+            | Self::SearchDetailed
+            | Self::FeaturedProducts
+            | Self::FeaturedOrganisations
+            | Self::ProductAlternatives(_)
+            | Self::Changes
+            | Self::Export(..)
+            | Self::Categories
+            | Self::Certifications
+            | Self::CategoryProducts(_)
+            | Self::CategoryStats(_)
+            | Self::Organisation(..)
+            | Self::OrganisationProducts(..)
+            | Self::OrganisationControversies(..)
+            | Self::OrganisationPolicyPages(..)
+            | Self::ProductFootprint(..)
+            | Self::ProductOwnership(..)
+            | Self::ProductByEntity(_)
+            | Self::OrganisationByEntity(_)
+            | Self::OrganisationsByIdentifier(_)
+            | Self::ProductsByExternalId(..)
+            | Self::ResolveUrl
+            | Self::LibrarySlugs
+            | Self::LibraryBySlug(_)
+            | Self::Health
+            | Self::Readiness
+            | Self::Metrics
+            | Self::SubmitFeedback
+            | Self::SubmitProductRequest
+            | Self::Sitemap
+            | Self::SitemapPage(..)
+            | Self::OrganisationJsonLd(..)
+            | Self::ProductJsonLd(..) => None,
+        }
+    }
+
+    /// Checks if the route is used for operating the backend.
+    ///
+    /// Such routes are not subject to the API rate limits (the readiness check has its own limit)
+    /// and are available even when the dataset is not ready.
+    fn is_operational(&self) -> bool {
+        matches!(self, Self::Health | Self::Readiness | Self::Metrics)
+    }
+
+    /// Checks the path and query parameters before the request is handled.
+    fn validate(&self, query: &Query) -> Result<(), Vec<FieldError>> {
+        let mut validator = Validator::new();
+        match self {
+            Self::SearchDetailed => {
+                validator.check(validation::search_query(query.get("query").map(String::as_str)));
+            }
+            Self::FeaturedProducts | Self::FeaturedOrganisations => {
+                validator.check(validation::limit(query, retrieve::MAX_FEATURED));
+            }
+            Self::ProductAlternatives(_) => {
+                validator.check(validation::limit(query, AlternativesOptions::MAX_PER_CATEGORY));
+            }
+            Self::Changes => {
+                validator.check(validation::offset(query));
+                validator.check(validation::limit(query, retrieve::MAX_CHANGES_PAGE_SIZE));
+            }
+            Self::CategoryProducts(_) | Self::ListFeedback | Self::ListProductRequests => {
+                validator.check(validation::offset(query));
+                validator.check(validation::limit(query, retrieve::MAX_PAGE_SIZE));
+            }
+            Self::OrganisationProducts(variant, id) => {
+                validator.check(validation::organisation_id(variant, id));
+                validator.check(validation::offset(query));
+                validator.check(validation::limit(query, retrieve::MAX_PAGE_SIZE));
+            }
+            Self::Organisation(variant, id)
+            | Self::OrganisationControversies(variant, id)
+            | Self::OrganisationPolicyPages(variant, id)
+            | Self::OrganisationJsonLd(variant, id) => {
+                validator.check(validation::organisation_id(variant, id));
+            }
+            Self::ProductFootprint(variant, id)
+            | Self::ProductOwnership(variant, id)
+            | Self::ProductJsonLd(variant, id) => {
+                validator.check(validation::product_id(variant, id));
+            }
+            Self::ProductByEntity(id) | Self::OrganisationByEntity(id) => {
+                validator.check(validation::entity_id(id));
+            }
+            Self::OrganisationsByIdentifier(id) => {
+                validator.check(validation::identifier(id));
+            }
+            Self::ProductsByExternalId(kind, id) => {
+                validator.check(validation::external_id(kind, id));
+            }
+            Self::ResolveUrl => {
+                validator.check(validation::page_url(query.get("url").map(String::as_str)));
+                validator.check(validation::product_hint(query.get("product").map(String::as_str)));
+            }
+            Self::LibraryBySlug(slug) => {
+                validator.check(validation::slug(slug));
+            }
+            _ => {}
+        }
+        validator.check(validation::region(query.get("region").map(String::as_str)));
+        validator.finish()
+    }
+
+    async fn handle(self, request: Request<Body>, db: Db, shared: Arc<Shared>) -> Response<Body> {
+        let query = parse_query(&request);
+        match self {
+            Self::CompareProducts => compare_products(&query, &db).await,
+            Self::SearchDetailed => match query.get("query") {
+                Some(text) => respond_result(retrieve::search_by_text_detailed(text, &db).await),
+                None => respond_error(ErrorCode::MissingParameter, "Missing `query` parameter"),
+            },
+            Self::FeaturedProducts => match parse_number(&query, "limit") {
+                Ok(limit) => {
+                    let limit = limit.unwrap_or(retrieve::DEFAULT_FEATURED);
+                    respond_result(retrieve::featured_products(limit, &db).await)
+                }
+                Err(_) => respond_error(ErrorCode::InvalidParameter, "Invalid `limit` parameter"),
+            },
+            Self::FeaturedOrganisations => match parse_number(&query, "limit") {
+                Ok(limit) => {
+                    let limit = limit.unwrap_or(retrieve::DEFAULT_FEATURED);
+                    respond_result(retrieve::featured_organisations(limit, &db).await)
+                }
+                Err(_) => respond_error(ErrorCode::InvalidParameter, "Invalid `limit` parameter"),
+            },
+            Self::ProductAlternatives(id) => product_alternatives(&id, &query, &db).await,
+            Self::Changes => changes(&query, &db).await,
+            Self::Export(collection, compression) => {
+                download(collection, compression, &request, &db, &shared.exporter).await
+            }
+            Self::Categories => respond_result(retrieve::category_tree(&db).await),
+            Self::Certifications => respond_result(retrieve::certifications(&db).await),
+            Self::CategoryProducts(category) => category_products(&category, &query, &db).await,
+            Self::CategoryStats(category) => {
+                respond_optional(retrieve::category_stats(&category, &db).await)
+            }
+            Self::Organisation(variant, id) => match variant.parse() {
+                Ok(variant) => {
+                    respond_optional(retrieve::organisation_details(variant, &id, &db).await)
+                }
+                Err(_) => {
+                    respond_error(ErrorCode::InvalidIdVariant, "Unknown organisation ID variant")
+                }
+            },
+            Self::OrganisationProducts(variant, id) => match variant.parse() {
+                Ok(variant) => organisation_products(variant, &id, &query, &db).await,
+                Err(_) => {
+                    respond_error(ErrorCode::InvalidIdVariant, "Unknown organisation ID variant")
+                }
+            },
+            Self::OrganisationControversies(variant, id) => match variant.parse() {
+                Ok(variant) => {
+                    respond_optional(retrieve::organisation_controversies(variant, &id, &db).await)
+                }
+                Err(_) => {
+                    respond_error(ErrorCode::InvalidIdVariant, "Unknown organisation ID variant")
+                }
+            },
+            Self::OrganisationPolicyPages(variant, id) => match variant.parse() {
+                Ok(variant) => {
+                    respond_optional(retrieve::organisation_policy_pages(variant, &id, &db).await)
+                }
+                Err(_) => {
+                    respond_error(ErrorCode::InvalidIdVariant, "Unknown organisation ID variant")
+                }
+            },
+            Self::ProductFootprint(variant, id) => match parse_product_variant(&variant, &id) {
+                Ok(variant) => {
+                    respond_optional(retrieve::product_footprint(variant, &id, &db).await)
+                }
+                Err(response) => response,
+            },
+            Self::ProductOwnership(variant, id) => match parse_product_variant(&variant, &id) {
+                Ok(variant) => {
+                    respond_optional(retrieve::product_ownership(variant, &id, &db).await)
+                }
+                Err(response) => response,
+            },
+            Self::ProductByEntity(id) => match id.parse() {
+                Ok(id) => {
+                    let region = query.get("region").map(String::as_str);
+                    respond_optional(retrieve::product_by_entity(&id, region, &db).await)
+                }
+                Err(_) => respond_error(ErrorCode::InvalidId, "Invalid entity ID"),
+            },
+            Self::OrganisationByEntity(id) => match id.parse() {
+                Ok(id) => respond_optional(retrieve::organisation_by_entity(&id, &db).await),
+                Err(_) => respond_error(ErrorCode::InvalidId, "Invalid entity ID"),
+            },
+            Self::OrganisationsByIdentifier(id) => {
+                respond_result(retrieve::organisations_by_identifier(&id, &db).await)
+            }
+            Self::ProductsByExternalId(kind, id) => match validation::external_id(&kind, &id) {
+                Ok((kind, id)) => {
+                    respond_result(retrieve::products_by_external_id(kind, &id, &db).await)
+                }
+                Err(err) => respond_error(err.code, &err.message),
+            },
+            Self::ResolveUrl => match query.get("url") {
+                Some(url) => {
+                    let product = query.get("product").map(String::as_str);
+                    respond_optional(retrieve::resolve_url(url, product, &db).await)
+                }
+                None => respond_error(ErrorCode::MissingParameter, "Missing `url` parameter"),
+            },
+            Self::LibrarySlugs => respond_result(retrieve::library_slugs(&db).await),
+            Self::LibraryBySlug(slug) => {
+                let languages = Languages::from_request(&request);
+                library_by_slug(retrieve::library_item_by_slug(&slug, &db, &languages).await)
+            }
+            Self::ClearCache => clear_cache(db.cache()),
+            Self::Reload => reload_dataset(request, &db, &shared.webhooks).await,
+            Self::ListSnapshots => respond_json(StatusCode::OK, &db.snapshots()),
+            Self::ZeroResultQueries => zero_result_queries(&query, &db).await,
+            Self::MountSnapshot(name) => mount_snapshot(name, request, &db).await,
+            Self::UnmountSnapshot(name) => {
+                if db.unmount_snapshot(&name) {
+                    tracing::info!("Snapshot `{name}` unmounted");
+                    respond(StatusCode::NO_CONTENT, None)
+                } else {
+                    respond_error(ErrorCode::NotFound, "Not found")
+                }
+            }
+            Self::Health => respond_json(StatusCode::OK, &Status { status: "ok" }),
+            Self::Readiness => readiness(&db).await,
+            Self::Metrics => render_metrics(&db),
+            Self::SubmitFeedback => submit_feedback(request, &db).await,
+            Self::ListFeedback => list_feedback(&query, &db).await,
+            Self::ModerateFeedback(id) => moderate_feedback(&id, request, &db).await,
+            Self::SubmitProductRequest => submit_product_request(request, &db).await,
+            Self::ListProductRequests => list_product_requests(&query, &db).await,
+            Self::Sitemap => sitemap(seo::sitemap_index(&db, &shared.site).await.map(Some)),
+            Self::SitemapPage(collection, page) => {
+                sitemap(seo::sitemap_page(collection, page, &db, &shared.site).await)
+            }
+            Self::OrganisationJsonLd(variant, id) => match variant.parse() {
+                Ok(variant) => {
+                    json_ld(seo::organisation_json_ld(variant, &id, &db, &shared.site).await)
+                }
+                Err(_) => {
+                    respond_error(ErrorCode::InvalidIdVariant, "Unknown organisation ID variant")
+                }
+            },
+            Self::ProductJsonLd(variant, id) => match parse_product_variant(&variant, &id) {
+                Ok(variant) => json_ld(seo::product_json_ld(variant, &id, &db, &shared.site).await),
+                Err(response) => response,
+            },
+        }
+    }
+}
+
+async fn compare_products(query: &Query, db: &Db) -> Response<Body> {
+    let id_variant = match query.get("variant") {
+        Some(variant) => match variant.parse::<api::ProductIdVariant>() {
+            Ok(id_variant) => id_variant,
+            Err(_) => {
+                return respond_error(ErrorCode::InvalidIdVariant, "Unknown product ID variant");
+            }
+        },
+        None => api::ProductIdVariant::Gtin,
+    };
+
+    let ids: Vec<String> = query
+        .get("ids")
+        .map(|ids| {
+            ids.split(',').map(str::trim).filter(|id| !id.is_empty()).map(String::from).collect()
+        })
+        .unwrap_or_default();
+
+    if ids.is_empty() {
+        return respond_error(ErrorCode::MissingParameter, "No product IDs given");
+    }
+    if ids.len() > retrieve::MAX_COMPARED_PRODUCTS {
+        return respond_error(ErrorCode::InvalidParameter, "Too many product IDs given");
+    }
+
+    respond_result(retrieve::compare_products(id_variant, &ids, db).await)
+}
+
+async fn category_products(category: &str, query: &Query, db: &Db) -> Response<Body> {
+    let Ok(offset) = parse_number(query, "offset") else {
+        return respond_error(ErrorCode::InvalidParameter, "Invalid `offset` parameter");
+    };
+    let Ok(limit) = parse_number(query, "limit") else {
+        return respond_error(ErrorCode::InvalidParameter, "Invalid `limit` parameter");
+    };
+
+    let offset = offset.unwrap_or(0);
+    let limit = limit.unwrap_or(retrieve::DEFAULT_PAGE_SIZE);
+    let region = query.get("region").map(String::as_str);
+    respond_optional(retrieve::category_products(category, region, offset, limit, db).await)
+}
+
+async fn product_alternatives(id: &str, query: &Query, db: &Db) -> Response<Body> {
+    let Ok(exclude_same_manufacturer) = parse_flag(query, "exclude_same_manufacturer") else {
+        return respond_error(
+            ErrorCode::InvalidParameter,
+            "Invalid `exclude_same_manufacturer` flag",
+        );
+    };
+    let Ok(require_certification) = parse_flag(query, "require_certification") else {
+        return respond_error(ErrorCode::InvalidParameter, "Invalid `require_certification` flag");
+    };
+    let Ok(limit) = parse_number(query, "limit") else {
+        return respond_error(ErrorCode::InvalidParameter, "Invalid `limit` parameter");
+    };
+
+    let options = AlternativesOptions {
+        exclude_same_manufacturer,
+        require_certification,
+        max_per_category: limit
+            .unwrap_or(AlternativesOptions::DEFAULT_MAX_PER_CATEGORY)
+            .clamp(1, AlternativesOptions::MAX_PER_CATEGORY),
+    };
+    let region = query.get("region").map(String::as_str);
+    respond_result(
+        retrieve::product_alternatives_with_manufacturers(id, region, &options, db).await,
+    )
+}
+
+async fn organisation_products(
+    id_variant: api::OrganisationIdVariant,
+    id: &str,
+    query: &Query,
+    db: &Db,
+) -> Response<Body> {
+    let Ok(sort) = query.get("sort").map(|sort| sort.parse()).transpose() else {
+        return respond_error(ErrorCode::InvalidParameter, "Invalid `sort` parameter");
+    };
+    let Ok(offset) = parse_number(query, "offset") else {
+        return respond_error(ErrorCode::InvalidParameter, "Invalid `offset` parameter");
+    };
+    let Ok(limit) = parse_number(query, "limit") else {
+        return respond_error(ErrorCode::InvalidParameter, "Invalid `limit` parameter");
+    };
+
+    let sort = sort.unwrap_or_default();
+    let offset = offset.unwrap_or(0);
+    let limit = limit.unwrap_or(retrieve::DEFAULT_PAGE_SIZE);
+    respond_optional(retrieve::organisation_products(id_variant, id, sort, offset, limit, db).await)
+}
+
+async fn changes(query: &Query, db: &Db) -> Response<Body> {
+    let Some(since) = query.get("since") else {
+        return respond_error(ErrorCode::MissingParameter, "Missing `since` parameter");
+    };
+    let Ok(offset) = parse_number(query, "offset") else {
+        return respond_error(ErrorCode::InvalidParameter, "Invalid `offset` parameter");
+    };
+    let Ok(limit) = parse_number(query, "limit") else {
+        return respond_error(ErrorCode::InvalidParameter, "Invalid `limit` parameter");
+    };
+
+    let offset = offset.unwrap_or(0);
+    let limit = limit.unwrap_or(retrieve::DEFAULT_CHANGES_PAGE_SIZE);
+    match retrieve::changes(since, offset, limit, db).await {
+        Ok(Some(page)) => respond_json(StatusCode::OK, &page),
+        Ok(None) => respond_error(ErrorCode::ChangesExpired, "No changes known since this version"),
+        Err(err) => respond_result::<ChangesPage>(Err(err)),
+    }
+}
+
+async fn download(
+    collection: ExportCollection,
+    compression: Compression,
+    request: &Request<Body>,
+    db: &Db,
+    exporter: &Exporter,
+) -> Response<Body> {
+    let file = match exporter.prepare(collection, compression, db).await {
+        Ok(Prepared::Ready(file)) => file,
+        Ok(Prepared::Generating) => {
+            let mut response = respond_error(
+                ErrorCode::ServiceBusy,
+                "The export is being generated, try again later",
+            );
+            let retry_after = HeaderValue::from(export::RETRY_AFTER.as_secs());
+            response.headers_mut().insert(header::RETRY_AFTER, retry_after);
+            return response;
+        }
+        Err(err) => {
+            tracing::error!("Export failed: {err}");
+            return respond_error(ErrorCode::InternalError, "An internal error occurred");
+        }
+    };
+
+    // A range of a file regenerated since the client started the download is useless to it.
+    let if_range =
+        request.headers().get(header::IF_RANGE).map(|value| value.to_str().unwrap_or(""));
+    let range = request
+        .headers()
+        .get(header::RANGE)
+        .filter(|_| if_range.map_or(true, |if_range| file.matches_if_range(if_range)))
+        .and_then(|range| range.to_str().ok())
+        .and_then(|range| export::parse_range(range, file.len));
+    let (status, start, end) = match range {
+        None => (StatusCode::OK, 0, file.len.saturating_sub(1)),
+        Some(Ok((start, end))) => (StatusCode::PARTIAL_CONTENT, start, end),
+        Some(Err(())) => {
+            let mut response = respond(StatusCode::RANGE_NOT_SATISFIABLE, None);
+            if let Ok(value) = HeaderValue::from_str(&format!("bytes */{}", file.len)) {
+                response.headers_mut().insert(header::CONTENT_RANGE, value);
+            }
+            return response;
+        }
+    };
+    let count = if file.len == 0 { 0 } else { end - start + 1 };
+
+    let mut response = respond(status, None);
+    *response.body_mut() = export::stream(file.path, start, count);
+    let headers = response.headers_mut();
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(file.content_type));
+    if let Some(encoding) = file.content_encoding {
+        headers.insert(header::CONTENT_ENCODING, HeaderValue::from_static(encoding));
+    }
+    if let Ok(value) = HeaderValue::from_str(&file.etag) {
+        headers.insert(header::ETAG, value);
+    }
+    if let Ok(value) = HeaderValue::from_str(&file.last_modified) {
+        headers.insert(header::LAST_MODIFIED, value);
+    }
+    headers.insert(header::CONTENT_LENGTH, HeaderValue::from(count));
+    headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+    let disposition = format!("attachment; filename=\"{}\"", file.file_name);
+    if let Ok(value) = HeaderValue::from_str(&disposition) {
+        headers.insert(header::CONTENT_DISPOSITION, value);
+    }
+    if status == StatusCode::PARTIAL_CONTENT {
+        let content_range = format!("bytes {start}-{end}/{}", file.len);
+        if let Ok(value) = HeaderValue::from_str(&content_range) {
+            headers.insert(header::CONTENT_RANGE, value);
+        }
+    }
+    response
+}
+
+fn clear_cache(cache: &Caches) -> Response<Body> {
+    cache.clear();
+    tracing::info!("Cache cleared");
+    respond(StatusCode::NO_CONTENT, None)
+}
+
+async fn reload_dataset(request: Request<Body>, db: &Db, webhooks: &Webhooks) -> Response<Body> {
+    let body = match read_body(request).await {
+        Ok(body) => body,
+        Err(response) => return response,
+    };
+
+    let dataset = if body.is_empty() {
+        DatasetConfig::load_or_default()
+    } else {
+        match serde_json::from_slice::<DatasetConfig>(&body) {
+            Ok(dataset) => dataset,
+            Err(_) => return respond_error(ErrorCode::InvalidBody, "Invalid dataset config"),
+        }
+    };
+
+    match reload::reload(db, dataset.clone(), webhooks).await {
+        Ok(()) => respond_json(StatusCode::OK, &dataset),
+        Err(ReloadError::Incomplete { database, collection }) => {
+            let message = format!("Dataset in `{database}` is incomplete: `{collection}` is empty");
+            respond_error(ErrorCode::DatasetIncomplete, &message)
+        }
+        Err(err) => {
+            tracing::error!("Dataset reload failed: {err}");
+            respond_error(ErrorCode::InternalError, "An internal error occurred")
+        }
+    }
+}
+
+async fn zero_result_queries(query: &Query, db: &Db) -> Response<Body> {
+    let Ok(offset) = parse_number(query, "offset") else {
+        return respond_error(ErrorCode::InvalidParameter, "Invalid `offset` parameter");
+    };
+    let Ok(limit) = parse_number(query, "limit") else {
+        return respond_error(ErrorCode::InvalidParameter, "Invalid `limit` parameter");
+    };
+
+    let offset = offset.unwrap_or(0);
+    let limit = limit.unwrap_or(retrieve::DEFAULT_PAGE_SIZE).min(retrieve::MAX_PAGE_SIZE);
+    match analytics::queries(offset, limit, db).await {
+        Ok(Some(queries)) => respond_json(StatusCode::OK, &queries),
+        Ok(None) => {
+            respond_error(ErrorCode::NotFound, "Logging of queries with no results is disabled")
+        }
+        Err(err) => respond_result::<Vec<analytics::ZeroResultQuery>>(Err(err)),
+    }
+}
+
+/// Extracts a valid snapshot name from `/admin/snapshots/{name}` paths.
+fn snapshot_name(path: &str) -> Option<&str> {
+    let name = path.strip_prefix("/admin/snapshots/")?;
+    let valid = !name.is_empty()
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        && name != "default";
+    valid.then_some(name)
+}
+
+async fn mount_snapshot(name: String, request: Request<Body>, db: &Db) -> Response<Body> {
+    let dataset = match read_json::<DatasetConfig>(request).await {
+        Ok(dataset) => dataset,
+        Err(response) => return response,
+    };
+
+    match reload::mount_snapshot(db, name, dataset.clone()).await {
+        Ok(()) => respond_json(StatusCode::OK, &dataset),
+        Err(ReloadError::Incomplete { database, collection }) => {
+            let message = format!("Dataset in `{database}` is incomplete: `{collection}` is empty");
+            respond_error(ErrorCode::DatasetIncomplete, &message)
+        }
+        Err(err) => {
+            tracing::error!("Mounting snapshot failed: {err}");
+            respond_error(ErrorCode::InternalError, "An internal error occurred")
+        }
+    }
+}
+
+/// Selects the dataset requested with the `dataset` query parameter.
+///
+/// Returns `Err` with the response to send if the snapshot is not served.
+fn select_dataset(db: &Db, request: &Request<Body>) -> Result<Db, Response<Body>> {
+    let query = parse_query(request);
+    match query.get(DATASET_PARAM).map(String::as_str) {
+        None | Some("" | "default") => Ok(db.clone()),
+        Some(name) => db
+            .snapshot(name)
+            .ok_or_else(|| respond_error(ErrorCode::UnknownSnapshot, "Unknown dataset snapshot")),
+    }
+}
+
+async fn submit_feedback(request: Request<Body>, db: &Db) -> Response<Body> {
+    let submission = match read_json::<FeedbackSubmission>(request).await {
+        Ok(submission) => submission,
+        Err(response) => return response,
+    };
+    if let Err(message) = feedback::validate(&submission) {
+        return respond_error(ErrorCode::InvalidBody, message);
+    }
+
+    match feedback::submit(submission, db).await {
+        Ok(id) => respond_json(StatusCode::CREATED, &FeedbackReceipt { id }),
+        Err(err) => respond_result::<FeedbackReceipt>(Err(err)),
+    }
+}
+
+async fn list_feedback(query: &Query, db: &Db) -> Response<Body> {
+    let status = match query.get("status") {
+        Some(status) => match FeedbackStatus::deserialize(status.as_str().into_deserializer()) {
+            Ok(status) => Some(status),
+            Err(serde::de::value::Error { .. }) => {
+                return respond_error(ErrorCode::InvalidParameter, "Unknown feedback status");
+            }
+        },
+        None => None,
+    };
+    let Ok(offset) = parse_number(query, "offset") else {
+        return respond_error(ErrorCode::InvalidParameter, "Invalid `offset` parameter");
+    };
+    let Ok(limit) = parse_number(query, "limit") else {
+        return respond_error(ErrorCode::InvalidParameter, "Invalid `limit` parameter");
+    };
+
+    let offset = offset.unwrap_or(0);
+    let limit = limit.unwrap_or(retrieve::DEFAULT_PAGE_SIZE).min(retrieve::MAX_PAGE_SIZE);
+    respond_result(feedback::list(status, offset, limit, db).await)
+}
+
+async fn moderate_feedback(id: &str, request: Request<Body>, db: &Db) -> Response<Body> {
+    let change = match read_json::<FeedbackStatusChange>(request).await {
+        Ok(change) => change,
+        Err(response) => return response,
+    };
+
+    match feedback::moderate(id, change, db).await {
+        Ok(Moderation::Changed(feedback)) => respond_json(StatusCode::OK, &feedback),
+        Ok(Moderation::NotFound) => respond_error(ErrorCode::NotFound, "Not found"),
+        Ok(Moderation::InvalidTransition { from, to }) => {
+            let message =
+                format!("Cannot change status from `{}` to `{}`", from.as_str(), to.as_str());
+            respond_error(ErrorCode::Conflict, &message)
+        }
+        Err(err) => respond_result::<()>(Err(err)),
+    }
+}
+
+async fn submit_product_request(request: Request<Body>, db: &Db) -> Response<Body> {
+    let submission = match read_json::<ProductRequestSubmission>(request).await {
+        Ok(submission) => submission,
+        Err(response) => return response,
+    };
+    let submission = match product_requests::validate(submission) {
+        Ok(submission) => submission,
+        Err(message) => return respond_error(ErrorCode::InvalidBody, message),
+    };
+
+    match product_requests::submit(submission, db).await {
+        Ok(id) => respond_json(StatusCode::CREATED, &ProductRequestReceipt { id }),
+        Err(err) => respond_result::<ProductRequestReceipt>(Err(err)),
+    }
+}
+
+async fn list_product_requests(query: &Query, db: &Db) -> Response<Body> {
+    let Ok(offset) = parse_number(query, "offset") else {
+        return respond_error(ErrorCode::InvalidParameter, "Invalid `offset` parameter");
+    };
+    let Ok(limit) = parse_number(query, "limit") else {
+        return respond_error(ErrorCode::InvalidParameter, "Invalid `limit` parameter");
+    };
+
+    let offset = offset.unwrap_or(0);
+    let limit = limit.unwrap_or(retrieve::DEFAULT_PAGE_SIZE).min(retrieve::MAX_PAGE_SIZE);
+    respond_result(product_requests::list(offset, limit, db).await)
+}
+
+/// Body of the health and readiness responses.
+#[derive(Serialize, Debug)]
+struct Status {
+    #[serde(rename = "status")]
+    status: &'static str,
+}
+
+/// Body of the readiness response.
+#[derive(Serialize, Debug)]
+struct Readiness {
+    #[serde(rename = "ready")]
+    ready: bool,
+
+    #[serde(rename = "database_reachable")]
+    database_reachable: bool,
+
+    #[serde(rename = "dataset")]
+    dataset: DatasetConfig,
+}
+
+async fn readiness(db: &Db) -> Response<Body> {
+    let database_reachable = match db.ping().await {
+        Ok(()) => true,
+        Err(err) => {
+            tracing::warn!("Database not reachable: {err}");
+            false
+        }
+    };
+    let dataset_loaded = db.is_ready();
+    let ready = database_reachable && dataset_loaded;
+    let body = Readiness { ready, database_reachable, dataset: db.dataset().as_ref().clone() };
+    let status = if ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    respond_json(status, &body)
+}
+
+fn render_metrics(db: &Db) -> Response<Body> {
+    let dataset = db.dataset();
+    let text = db.metrics().render(db.is_ready(), &dataset.database, &dataset.version);
+    with_content_type(
+        respond(StatusCode::OK, Some(text.into_bytes())),
+        "text/plain; version=0.0.4; charset=utf-8",
+    )
+}
+
+fn library_by_slug(result: Result<Option<LibraryLookup>, BackendError>) -> Response<Body> {
+    match result {
+        Ok(Some(LibraryLookup::Found(item))) => respond_json(StatusCode::OK, &item),
+        Ok(Some(LibraryLookup::Moved(slug))) => {
+            let mut response = respond(StatusCode::MOVED_PERMANENTLY, None);
+            if let Ok(location) = HeaderValue::from_str(&format!("/library/slug/{slug}")) {
+                response.headers_mut().insert(header::LOCATION, location);
+            }
+            response
+        }
+        Ok(None) => respond_error(ErrorCode::NotFound, "Not found"),
+        Err(err) => respond_result::<()>(Err(err)),
+    }
+}
+
+fn sitemap(result: Result<Option<String>, BackendError>) -> Response<Body> {
+    match result {
+        Ok(Some(xml)) => with_content_type(
+            respond(StatusCode::OK, Some(xml.into_bytes())),
+            "application/xml; charset=utf-8",
+        ),
+        result => respond_optional(result),
+    }
+}
+
+fn json_ld(result: Result<Option<serde_json::Value>, BackendError>) -> Response<Body> {
+    match result {
+        Ok(Some(value)) => {
+            with_content_type(respond_json(StatusCode::OK, &value), "application/ld+json")
+        }
+        result => respond_optional(result),
+    }
+}
+
+/// Returns the IP address identifying the client.
+///
+/// If the backend runs behind a trusted reverse proxy, the last address in the `X-Forwarded-For`
+/// header (the one appended by the proxy) is used. Otherwise the address of the connection is used.
+fn client_ip<B>(request: &Request<B>, remote_ip: IpAddr, trust_proxy: bool) -> IpAddr {
+    if trust_proxy {
+        let forwarded = request
+            .headers()
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .last()
+            .and_then(|ip| ip.trim().parse().ok());
+        if let Some(ip) = forwarded {
+            return ip;
+        }
+    }
+    remote_ip
+}
+
+/// Checks the rate limits.
+///
+/// Requests authenticated with a partner key are limited per key, all other requests per IP
+/// address. Returns `Err` with the time after which the request can be retried if the limit is
+/// exceeded.
+fn check_rate_limits<B>(
+    request: &Request<B>,
+    auth: Authentication<'_>,
+    remote_ip: IpAddr,
+    limiters: &RateLimiters,
+) -> Result<(), Duration> {
+    let now = Instant::now();
+    match auth.key() {
+        Some(key) if key.has_scope(Scope::Partner) => limiters.key.check(key.key.clone(), now),
+        _ => limiters.ip.check(client_ip(request, remote_ip, limiters.trust_proxy), now),
+    }
+}
+
+/// State shared by all connections.
+pub struct Shared {
+    pub limiters: RateLimiters,
+    pub api_keys: ApiKeys,
+    pub webhooks: Webhooks,
+    pub site: SiteConfig,
+    pub exporter: Exporter,
+}
+
+pub struct MakeRouter<T> {
+    inner: T,
+    db: Db,
+    shared: Arc<Shared>,
+}
+
+impl<T> MakeRouter<T> {
+    pub fn new(inner: T, db: Db, shared: Arc<Shared>) -> Self {
+        Self { inner, db, shared }
+    }
+}
+
+impl<'a, T> Service<&'a AddrStream> for MakeRouter<T>
+where
+    T: Service<&'a AddrStream>,
+    T::Future: Send + 'static,
+{
+    type Error = T::Error;
+    type Response = Router<T::Response>;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, target: &'a AddrStream) -> Self::Future {
+        let remote_ip = target.remote_addr().ip();
+        let service = self.inner.call(target);
+        let db = self.db.clone();
+        let shared = self.shared.clone();
+        Box::pin(async move { Ok(Router::new(service.await?, db, shared, remote_ip)) })
+    }
+}
+
+pub struct Router<T> {
+    inner: T,
+    db: Db,
+    shared: Arc<Shared>,
+    remote_ip: IpAddr,
+}
+
+impl<T> Router<T> {
+    pub fn new(inner: T, db: Db, shared: Arc<Shared>, remote_ip: IpAddr) -> Self {
+        Self { inner, db, shared, remote_ip }
+    }
+
+    /// Checks authentication, authorization and rate limits.
+    ///
+    /// Returns `Err` with the response to send if the request must not be handled.
+    fn check_access(
+        &self,
+        request: &Request<Body>,
+        auth: Authentication<'_>,
+        route: Option<&Route>,
+    ) -> Result<(), Response<Body>> {
+        if let Authentication::Invalid = auth {
+            return Err(respond_error(ErrorCode::Unauthorized, "Invalid API key"));
+        }
+
+        if let Err(retry_after) =
+            check_rate_limits(request, auth, self.remote_ip, &self.shared.limiters)
+        {
+            tracing::info!("Rate limited: {} {}", request.method(), request.uri());
+            return Err(respond_too_many_requests(retry_after));
+        }
+
+        if let Some(scope) = route.and_then(Route::required_scope) {
+            match auth.key() {
+                None => return Err(respond_error(ErrorCode::Unauthorized, "API key required")),
+                Some(key) if !key.has_scope(scope) => {
+                    tracing::warn!("API key `{}` lacks scope {scope:?}", key.name);
+                    return Err(respond_error(ErrorCode::Forbidden, "Insufficient permissions"));
+                }
+                Some(_) => {}
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<T> Service<Request<Body>> for Router<T>
+where
+    T: Service<Request<Body>, Response = Response<Body>>,
+    T::Future: Send + 'static,
+{
+    type Error = T::Error;
+    type Response = Response<Body>;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<Body>) -> Self::Future {
+        // The ID is passed on in the header so that the generated service uses the same one.
+        let span_id = swagger::XSpanIdString::get_or_generate(&request);
+        let span_header = HeaderValue::from_str(&span_id.0).ok();
+        if let Some(value) = &span_header {
+            request.headers_mut().insert(SPAN_ID_HEADER, value.clone());
+        }
+
+        let span = tracing::info_span!(
+            "request",
+            id = %span_id.0,
+            method = %request.method(),
+            path = %request.uri().path(),
+        );
+        let endpoint = metrics::endpoint(request.uri().path());
+        let metrics = self.db.metrics().clone();
+        let start = Instant::now();
+        let request_id = span_id.0.clone();
+        let future = span.in_scope(|| {
+            problems::with_request_id_sync(request_id.clone(), || self.dispatch(request))
+        });
+        Box::pin(
+            async move {
+                let mut response = problems::with_request_id(request_id, future).await?;
+                let elapsed = start.elapsed();
+                let status = response.status().as_u16();
+                tracing::info!(status, elapsed_ms = elapsed.as_millis() as u64, "Request handled");
+                metrics.record_request(endpoint, status, elapsed);
+                if let Some(value) = span_header {
+                    response.headers_mut().insert(SPAN_ID_HEADER, value);
+                }
+                Ok(response)
+            }
+            .instrument(span),
+        )
+    }
+}
+
+impl<T> Router<T>
+where
+    T: Service<Request<Body>, Response = Response<Body>>,
+    T::Future: Send + 'static,
+{
+    fn dispatch(
+        &mut self,
+        request: Request<Body>,
+    ) -> BoxFuture<'static, Result<Response<Body>, T::Error>> {
+        let route = match Route::find(request.method(), request.uri().path()) {
+            Some(route) if route.is_operational() => {
+                if route == Route::Readiness {
+                    let limiters = &self.shared.limiters;
+                    let client = client_ip(&request, self.remote_ip, limiters.trust_proxy);
+                    if let Err(retry_after) = limiters.readiness.check(client, Instant::now()) {
+                        let response = respond_too_many_requests(retry_after);
+                        return Box::pin(futures::future::ready(Ok(response)));
+                    }
+                }
+                let (db, shared) = (self.db.clone(), self.shared.clone());
+                return Box::pin(async move { Ok(route.handle(request, db, shared).await) });
+            }
+            route => route,
+        };
+
+        if request.method() != Method::OPTIONS {
+            let auth = self.shared.api_keys.authenticate(&request);
+            if let Err(response) = self.check_access(&request, auth, route.as_ref()) {
+                return Box::pin(futures::future::ready(Ok(response)));
+            }
+        }
+
+        // Administration endpoints must stay available so that a dataset can be loaded.
+        let is_admin = route.as_ref().and_then(Route::required_scope).is_some();
+        if !is_admin && !self.db.is_ready() {
+            let response = respond_error(ErrorCode::DatasetReloading, "Dataset not ready");
+            return Box::pin(futures::future::ready(Ok(response)));
+        }
+
+        // Administration endpoints always operate on the main dataset.
+        let db = if is_admin {
+            self.db.clone()
+        } else {
+            match select_dataset(&self.db, &request) {
+                Ok(db) => db,
+                Err(response) => return Box::pin(futures::future::ready(Ok(response))),
+            }
+        };
+
+        // Malformed parameters are rejected before reaching the database.
+        if request.method() != Method::OPTIONS {
+            let query = parse_query(&request);
+            let validation = match &route {
+                Some(route) => route.validate(&query),
+                None => validate_generated(request.uri().path(), &query),
+            };
+            if let Err(errors) = validation {
+                return Box::pin(futures::future::ready(Ok(respond_invalid(errors))));
+            }
+        }
+
+        let etag = (request.method() == Method::GET && etag::is_tagged(request.uri().path()))
+            .then(|| etag::compute(&db.dataset().version, &request));
+        let localized = etag::is_localized(request.uri().path());
+
+        let mut is_wildcard = false;
+        if let Some(etag) = &etag {
+            if let Some(if_none_match) = request.headers().get(header::IF_NONE_MATCH) {
+                if etag::matches(if_none_match, etag) {
+                    let response = with_etag(respond(StatusCode::NOT_MODIFIED, None), etag);
+                    return Box::pin(futures::future::ready(Ok(response)));
+                }
+                is_wildcard = etag::is_wildcard(if_none_match);
+            }
+        }
+
+        let future: BoxFuture<'static, Result<Response<Body>, T::Error>> =
+            if let Some(route) = route {
+                let shared = self.shared.clone();
+                Box::pin(async move { Ok(route.handle(request, db, shared).await) })
+            } else {
+                // The generated service takes the `Db` from the request context.
+                let mut request = request;
+                request.extensions_mut().insert(db);
+                let future = self.inner.call(request);
+                Box::pin(async move {
+                    let response = future.await?;
+                    // Error responses of the generated service carry no details.
+                    let status = response.status();
+                    if status.is_client_error() || status.is_server_error() {
+                        Ok(with_problem(response))
+                    } else {
+                        Ok(response)
+                    }
+                })
+            };
+
+        let future: BoxFuture<'static, Result<Response<Body>, T::Error>> = if localized {
+            Box::pin(async move {
+                let mut response = future.await?;
+                response
+                    .headers_mut()
+                    .insert(header::VARY, HeaderValue::from_static("Accept-Language"));
+                Ok(response)
+            })
+        } else {
+            future
+        };
+
+        match etag {
+            Some(etag) => Box::pin(async move {
+                let response = future.await?;
+                if response.status() != StatusCode::OK {
+                    // Also `If-None-Match: *` doesn't match resources which don't exist.
+                    Ok(response)
+                } else if is_wildcard {
+                    Ok(with_etag(respond(StatusCode::NOT_MODIFIED, None), &etag))
+                } else {
+                    Ok(with_etag(response, &etag))
+                }
+            }),
+            None => future,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use super::*;
+    use crate::models::ProductSort;
+
+    #[test]
+    fn client_identification() {
+        let remote = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+        let request =
+            Request::builder().header("x-forwarded-for", "1.1.1.1, 2.2.2.2").body(()).unwrap();
+        assert_eq!(client_ip(&request, remote, false), remote);
+        assert_eq!(client_ip(&request, remote, true), IpAddr::V4(Ipv4Addr::new(2, 2, 2, 2)));
+
+        let request = Request::builder().header("x-forwarded-for", "garbage").body(()).unwrap();
+        assert_eq!(client_ip(&request, remote, true), remote);
+    }
+
+    #[test]
+    fn seo_routes() {
+        assert_eq!(Route::find(&Method::GET, "/sitemap.xml"), Some(Route::Sitemap));
+        assert_eq!(
+            Route::find(&Method::GET, "/sitemaps/products/3.xml"),
+            Some(Route::SitemapPage(SitemapCollection::Products, 3))
+        );
+        assert_eq!(Route::find(&Method::GET, "/sitemaps/products/x.xml"), None);
+        assert_eq!(Route::find(&Method::GET, "/sitemaps/people/0.xml"), None);
+        assert_eq!(
+            Route::find(&Method::GET, "/jsonld/product/gtin/123"),
+            Some(Route::ProductJsonLd("gtin".to_owned(), "123".to_owned()))
+        );
+        assert_eq!(Route::find(&Method::GET, "/jsonld/organisation/wiki/"), None);
+    }
+
+    #[test]
+    fn product_footprint_route() {
+        assert_eq!(
+            Route::find(&Method::GET, "/product/gtin/05900000000001/footprint"),
+            Some(Route::ProductFootprint("gtin".to_owned(), "05900000000001".to_owned()))
+        );
+        assert_eq!(Route::find(&Method::GET, "/product/gtin//footprint"), None);
+        assert_eq!(
+            Route::find(&Method::GET, "/product/wiki/Q1/ownership"),
+            Some(Route::ProductOwnership("wiki".to_owned(), "Q1".to_owned()))
+        );
+    }
+
+    #[test]
+    fn entity_routes() {
+        assert_eq!(
+            Route::find(&Method::GET, "/product/entity/off:05900000000001"),
+            Some(Route::ProductByEntity("off:05900000000001".to_owned()))
+        );
+        assert_eq!(
+            Route::find(&Method::GET, "/organisation/entity/wiki%3AQ1"),
+            Some(Route::OrganisationByEntity("wiki:Q1".to_owned()))
+        );
+        assert_eq!(Route::find(&Method::GET, "/product/entity/"), None);
+        assert_eq!(
+            Route::find(&Method::GET, "/products/external/asin/B0C1234567"),
+            Some(Route::ProductsByExternalId("asin".to_owned(), "B0C1234567".to_owned()))
+        );
+        assert_eq!(Route::find(&Method::GET, "/products/external/asin/"), None);
+        assert_eq!(Route::find(&Method::GET, "/organisation/entity/wiki:Q1/products"), None);
+    }
+
+    #[tokio::test]
+    async fn generated_service_errors() {
+        let mut response = Response::new(Body::empty());
+        *response.status_mut() = StatusCode::METHOD_NOT_ALLOWED;
+        response.headers_mut().insert(header::ALLOW, HeaderValue::from_static("GET"));
+        response.headers_mut().insert(header::CONTENT_LENGTH, HeaderValue::from_static("0"));
+
+        let response = with_problem(response);
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(response.headers()[header::ALLOW], "GET");
+        assert_eq!(response.headers()[header::CONTENT_TYPE], problems::CONTENT_TYPE);
+        assert!(response.headers().get(header::CONTENT_LENGTH).is_none());
+
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let problem: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(problem["status"], 405);
+        assert_eq!(problem["code"], "METHOD_NOT_ALLOWED");
+    }
+
+    #[test]
+    fn product_variant_errors() {
+        assert!(parse_product_variant("gtin", "8717677339556").is_ok());
+        assert!(parse_product_variant("wiki", "Q1").is_ok());
+
+        let response = parse_product_variant("gtin", "8717677339557").unwrap_err();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(response.headers().get(header::CONTENT_TYPE).unwrap(), problems::CONTENT_TYPE);
+        let response = parse_product_variant("isbn", "1").unwrap_err();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn parameter_validation() {
+        let query = |pairs: &[(&str, &str)]| -> Query {
+            pairs.iter().map(|(k, v)| ((*k).to_owned(), (*v).to_owned())).collect()
+        };
+        assert!(validate_generated("/product/gtin/8717677339556", &query(&[])).is_ok());
+        assert!(validate_generated("/product/gtin/123", &query(&[])).is_err());
+        assert!(validate_generated("/search/text", &query(&[("query", "fairphone")])).is_ok());
+        assert!(validate_generated("/search/text", &query(&[])).is_err());
+
+        let route = Route::CategoryProducts("phone".to_owned());
+        assert!(route.validate(&query(&[("region", "NLD"), ("limit", "20")])).is_ok());
+        let errors = route.validate(&query(&[("region", "Mars"), ("offset", "-1")])).unwrap_err();
+        let fields: Vec<_> = errors.iter().map(|error| error.field).collect();
+        assert_eq!(fields, vec!["offset", "region"]);
+
+        assert_eq!(Route::find(&Method::GET, "/resolve"), Some(Route::ResolveUrl));
+        let url = ("url", "https://shop.fairphone.com/fairphone-6");
+        assert!(Route::ResolveUrl.validate(&query(&[url, ("product", "Fairphone 6")])).is_ok());
+        let errors = Route::ResolveUrl.validate(&query(&[("url", "ftp://x.com")])).unwrap_err();
+        assert_eq!(errors[0].field, "url");
+        assert!(Route::ResolveUrl.validate(&query(&[])).is_err());
+    }
+
+    #[test]
+    fn category_routes() {
+        assert_eq!(Route::find(&Method::GET, "/categories"), Some(Route::Categories));
+        assert_eq!(
+            Route::find(&Method::GET, "/categories/electronics%2Fsmartphone/stats"),
+            Some(Route::CategoryStats("electronics/smartphone".to_owned()))
+        );
+        assert_eq!(
+            Route::find(&Method::GET, "/categories/smartphone/products"),
+            Some(Route::CategoryProducts("smartphone".to_owned()))
+        );
+        assert_eq!(Route::find(&Method::GET, "/categories//stats"), None);
+    }
+
+    #[test]
+    fn library_slug_routes() {
+        assert_eq!(Route::find(&Method::GET, "/library/slugs"), Some(Route::LibrarySlugs));
+        assert_eq!(
+            Route::find(&Method::GET, "/library/slug/bcorp"),
+            Some(Route::LibraryBySlug("bcorp".to_owned()))
+        );
+        assert_eq!(Route::find(&Method::GET, "/library/slug/"), None);
+        assert_eq!(Route::find(&Method::GET, "/library/cert:bcorp"), None);
+        assert!(Route::LibraryBySlug("cert:bcorp".to_owned()).validate(&Query::new()).is_err());
+
+        let response = library_by_slug(Ok(Some(LibraryLookup::Moved("b-corp".to_owned()))));
+        assert_eq!(response.status(), StatusCode::MOVED_PERMANENTLY);
+        assert_eq!(response.headers()[header::LOCATION], "/library/slug/b-corp");
+    }
+
+    #[test]
+    fn certifications_route() {
+        assert_eq!(Route::find(&Method::GET, "/certifications"), Some(Route::Certifications));
+        assert_eq!(Route::find(&Method::POST, "/certifications"), None);
+    }
+
+    #[test]
+    fn organisation_products_route() {
+        assert_eq!(
+            Route::find(&Method::GET, "/organisation/vat/PL123/products"),
+            Some(Route::OrganisationProducts("vat".to_owned(), "PL123".to_owned()))
+        );
+        assert_eq!(Route::find(&Method::GET, "/organisation/vat//products"), None);
+        assert_eq!(
+            Route::find(&Method::GET, "/organisation/wiki/Q1/controversies"),
+            Some(Route::OrganisationControversies("wiki".to_owned(), "Q1".to_owned()))
+        );
+        assert_eq!(
+            Route::find(&Method::GET, "/organisation/wiki/Q1/policies"),
+            Some(Route::OrganisationPolicyPages("wiki".to_owned(), "Q1".to_owned()))
+        );
+        assert_eq!(
+            Route::find(&Method::GET, "/organisation/vat/PL123"),
+            Some(Route::Organisation("vat".to_owned(), "PL123".to_owned()))
+        );
+        assert_eq!(Route::find(&Method::GET, "/organisation/vat/"), None);
+        assert_eq!(Route::find(&Method::GET, "/organisation/vat/PL123/other"), None);
+        assert_eq!("category".parse(), Ok(ProductSort::Category));
+        assert_eq!("price".parse::<ProductSort>(), Err(()));
+    }
+
+    #[test]
+    fn snapshot_routes() {
+        assert_eq!(Route::find(&Method::GET, "/admin/snapshots"), Some(Route::ListSnapshots));
+        assert_eq!(
+            Route::find(&Method::POST, "/admin/snapshots/preview-2"),
+            Some(Route::MountSnapshot("preview-2".to_owned()))
+        );
+        assert_eq!(
+            Route::find(&Method::DELETE, "/admin/snapshots/preview"),
+            Some(Route::UnmountSnapshot("preview".to_owned()))
+        );
+        assert_eq!(Route::find(&Method::POST, "/admin/snapshots/default"), None);
+        assert_eq!(Route::find(&Method::DELETE, "/admin/snapshots/a/b"), None);
+        assert_eq!(
+            Route::find(&Method::POST, "/admin/snapshots/preview").and_then(|r| r.required_scope()),
+            Some(Scope::Admin)
+        );
+    }
+
+    #[test]
+    fn product_request_routes() {
+        let submit = Route::find(&Method::POST, "/products/request");
+        assert_eq!(submit, Some(Route::SubmitProductRequest));
+        assert_eq!(submit.and_then(|r| r.required_scope()), None);
+
+        let list = Route::find(&Method::GET, "/products/requests");
+        assert_eq!(list, Some(Route::ListProductRequests));
+        assert_eq!(list.and_then(|r| r.required_scope()), Some(Scope::Moderation));
+    }
+
+    #[test]
+    fn zero_result_route() {
+        let route = Route::find(&Method::GET, "/admin/search/zero-results");
+        assert_eq!(route, Some(Route::ZeroResultQueries));
+        assert_eq!(route.and_then(|r| r.required_scope()), Some(Scope::Admin));
+        assert_eq!(Route::find(&Method::POST, "/admin/search/zero-results"), None);
+    }
+
+    #[test]
+    fn alternatives_route() {
+        assert_eq!(
+            Route::find(&Method::GET, "/products/abc/alternatives"),
+            Some(Route::ProductAlternatives("abc".to_owned()))
+        );
+        assert_eq!(Route::find(&Method::GET, "/products//alternatives"), None);
+        assert_eq!(Route::find(&Method::GET, "/products/featured"), Some(Route::FeaturedProducts));
+
+        let query =
+            Query::from([("a".to_owned(), "true".to_owned()), ("b".to_owned(), "x".to_owned())]);
+        assert_eq!(parse_flag(&query, "a"), Ok(true));
+        assert_eq!(parse_flag(&query, "b"), Err(()));
+        assert_eq!(parse_flag(&query, "c"), Ok(false));
+    }
+}
@@ -0,0 +1,309 @@
+//! Sitemaps and structured data for search engines.
+//!
+//! The sitemap index lists one sitemap per page of organisations and products. Each of them lists
+//! URLs of the frontend pages of the entities. Per-entity [schema.org](https://schema.org) data
+//! in JSON-LD format can be embedded by the frontend into the pages.
+//!
+//! All URLs are relative to the configured site URL, so the frontend is expected to proxy the
+//! `/sitemap.xml` and `/sitemaps/` paths to the backend.
+
+use serde_json::{json, Map, Value};
+
+use sustainity_api::models as api;
+use sustainity_models::store::{
+    Certifications, Image, Organisation, OrganisationIds, Product, ProductIds, SocialLink, Source,
+};
+
+use crate::{config::SiteConfig, db::Db, errors::BackendError, retrieve};
+
+/// Maximal number of URLs in a single sitemap (as defined by the sitemap protocol).
+pub const SITEMAP_PAGE_SIZE: usize = 50_000;
+
+/// Collections listed in the sitemaps.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SitemapCollection {
+    Organisations,
+    Products,
+}
+
+impl SitemapCollection {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "organisations" => Some(Self::Organisations),
+            "products" => Some(Self::Products),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Organisations => "organisations",
+            Self::Products => "products",
+        }
+    }
+}
+
+/// Escapes text for use in XML content.
+fn escape_xml(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&apos;"),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+fn render_xml(root: &str, item: &str, locations: &[String]) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!("<{root} xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n"));
+    for location in locations {
+        xml.push_str(&format!("  <{item}><loc>{}</loc></{item}>\n", escape_xml(location)));
+    }
+    xml.push_str(&format!("</{root}>\n"));
+    xml
+}
+
+/// Returns the path of the organisation page.
+fn organisation_path(ids: &OrganisationIds) -> Option<String> {
+    let (variant, id) = if let Some(id) = ids.vat_ids.first() {
+        (api::OrganisationIdVariant::Vat, id)
+    } else if let Some(id) = ids.wiki.first() {
+        (api::OrganisationIdVariant::Wiki, id)
+    } else if let Some(id) = ids.domains.first() {
+        (api::OrganisationIdVariant::Www, id)
+    } else {
+        return None;
+    };
+    Some(format!("/organisation/{variant}/{id}"))
+}
+
+/// Returns the path of the product page.
+fn product_path(ids: &ProductIds) -> Option<String> {
+    let (variant, id) = if let Some(id) = ids.gtins.first() {
+        (api::ProductIdVariant::Gtin, id)
+    } else if let Some(id) = ids.eans.first() {
+        (api::ProductIdVariant::Ean, id)
+    } else if let Some(id) = ids.wiki.first() {
+        (api::ProductIdVariant::Wiki, id)
+    } else {
+        return None;
+    };
+    Some(format!("/product/{variant}/{id}"))
+}
+
+fn wikidata_urls(ids: &[String]) -> Vec<String> {
+    ids.iter().map(|id| format!("https://www.wikidata.org/wiki/Q{id}")).collect()
+}
+
+/// Returns URL of the image.
+///
+/// Images from Wikidata are stored as names of files on Wikimedia Commons.
+fn image_url(image: &Image) -> String {
+    match image.source {
+        Source::Wikidata => format!(
+            "https://commons.wikimedia.org/wiki/Special:FilePath/{}",
+            image.image.replace(' ', "_")
+        ),
+        _ => image.image.clone(),
+    }
+}
+
+/// Lists names of the certifications as schema.org awards.
+fn awards(certifications: &Certifications) -> Vec<&str> {
+    let mut awards = Vec::new();
+    if certifications.bcorp.is_some() {
+        awards.push("B Corporation");
+    }
+    if certifications.eu_ecolabel.is_some() {
+        awards.push("EU Ecolabel");
+    }
+    if certifications.tco.is_some() {
+        awards.push("TCO Certified");
+    }
+    awards
+}
+
+/// Inserts the value into the object unless it's empty.
+fn insert_nonempty(object: &mut Map<String, Value>, key: &str, value: Value) {
+    let is_empty = match &value {
+        Value::Null => true,
+        Value::Array(array) => array.is_empty(),
+        Value::String(string) => string.is_empty(),
+        _ => false,
+    };
+    if !is_empty {
+        object.insert(key.to_owned(), value);
+    }
+}
+
+/// Converts the organisation to a schema.org `Organization`.
+fn organisation_to_json_ld(organisation: &Organisation, site: &SiteConfig) -> Map<String, Value> {
+    let mut object = Map::new();
+    object.insert("@type".to_owned(), json!("Organization"));
+    if let Some(path) = organisation_path(&organisation.ids) {
+        object.insert("url".to_owned(), json!(site.url(&path)));
+    }
+    insert_nonempty(&mut object, "name", json!(organisation.names.first().map(|n| &n.text)));
+    insert_nonempty(
+        &mut object,
+        "description",
+        json!(organisation.descriptions.first().map(|d| &d.text)),
+    );
+    insert_nonempty(&mut object, "vatID", json!(organisation.ids.vat_ids.first()));
+    insert_nonempty(&mut object, "leiCode", json!(organisation.ids.leis.first()));
+    insert_nonempty(&mut object, "logo", json!(organisation.images.first().map(image_url)));
+    let mut same_as = wikidata_urls(&organisation.ids.wiki);
+    same_as.extend(organisation.social.iter().map(SocialLink::url));
+    insert_nonempty(&mut object, "sameAs", json!(same_as));
+    let mut awards = awards(&organisation.certifications);
+    awards.extend(organisation.recognitions.iter().map(|recognition| recognition.name.as_str()));
+    insert_nonempty(&mut object, "award", json!(awards));
+    object
+}
+
+/// Converts the product to a schema.org `Product`.
+fn product_to_json_ld(
+    product: &Product,
+    manufacturers: &[Organisation],
+    site: &SiteConfig,
+) -> Map<String, Value> {
+    let mut object = Map::new();
+    object.insert("@type".to_owned(), json!("Product"));
+    if let Some(path) = product_path(&product.ids) {
+        object.insert("url".to_owned(), json!(site.url(&path)));
+    }
+    insert_nonempty(&mut object, "name", json!(product.names.first().map(|n| &n.text)));
+    insert_nonempty(
+        &mut object,
+        "description",
+        json!(product.descriptions.first().map(|d| &d.text)),
+    );
+    insert_nonempty(&mut object, "gtin", json!(product.ids.gtins.first()));
+    insert_nonempty(&mut object, "category", json!(product.categories.first()));
+    insert_nonempty(&mut object, "sameAs", json!(wikidata_urls(&product.ids.wiki)));
+    insert_nonempty(&mut object, "award", json!(awards(&product.certifications)));
+    let manufacturers: Vec<_> =
+        manufacturers.iter().map(|m| organisation_to_json_ld(m, site)).collect();
+    insert_nonempty(&mut object, "manufacturer", json!(manufacturers));
+    object
+}
+
+fn with_context(mut object: Map<String, Value>) -> Value {
+    object.insert("@context".to_owned(), json!("https://schema.org"));
+    Value::Object(object)
+}
+
+/// Renders the sitemap index.
+pub async fn sitemap_index(db: &Db, site: &SiteConfig) -> Result<String, BackendError> {
+    let counts = db.count_dataset_entries(&db.dataset().database).await?.unwrap_or_default();
+    let mut locations = Vec::new();
+    for collection in [SitemapCollection::Organisations, SitemapCollection::Products] {
+        let count = counts.get(collection.name()).copied().unwrap_or(0);
+        for page in 0..count.div_ceil(SITEMAP_PAGE_SIZE) {
+            locations.push(site.url(&format!("/sitemaps/{}/{page}.xml", collection.name())));
+        }
+    }
+    Ok(render_xml("sitemapindex", "sitemap", &locations))
+}
+
+/// Renders a single page of the sitemap.
+pub async fn sitemap_page(
+    collection: SitemapCollection,
+    page: usize,
+    db: &Db,
+    site: &SiteConfig,
+) -> Result<Option<String>, BackendError> {
+    let offset = page.saturating_mul(SITEMAP_PAGE_SIZE);
+    let paths: Vec<String> = match collection {
+        SitemapCollection::Organisations => db
+            .get_organisation_ids_page(offset, SITEMAP_PAGE_SIZE)
+            .await?
+            .iter()
+            .filter_map(organisation_path)
+            .collect(),
+        SitemapCollection::Products => db
+            .get_product_ids_page(offset, SITEMAP_PAGE_SIZE)
+            .await?
+            .iter()
+            .filter_map(product_path)
+            .collect(),
+    };
+    if paths.is_empty() {
+        return Ok(None);
+    }
+
+    let locations: Vec<String> = paths.iter().map(|path| site.url(path)).collect();
+    Ok(Some(render_xml("urlset", "url", &locations)))
+}
+
+/// Returns structured data describing the organisation.
+pub async fn organisation_json_ld(
+    id_variant: api::OrganisationIdVariant,
+    id: &str,
+    db: &Db,
+    site: &SiteConfig,
+) -> Result<Option<Value>, BackendError> {
+    let organisation = db.get_organisation(id_variant, id).await?;
+    Ok(organisation.map(|organisation| with_context(organisation_to_json_ld(&organisation, site))))
+}
+
+/// Returns structured data describing the product and its manufacturers.
+pub async fn product_json_ld(
+    id_variant: api::ProductIdVariant,
+    id: &str,
+    db: &Db,
+    site: &SiteConfig,
+) -> Result<Option<Value>, BackendError> {
+    let Some(key) = retrieve::normalize_product_id(id_variant, id) else {
+        return Ok(None);
+    };
+    let Some(product) = db.get_product(id_variant, &key).await? else {
+        return Ok(None);
+    };
+    let manufacturers = db.find_product_manufacturers(&product.db_key).await?;
+    Ok(Some(with_context(product_to_json_ld(&product, &manufacturers, site))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sitemap_rendering() {
+        let locations = vec!["https://example.org/a?b=1&c=2".to_owned()];
+        let xml = render_xml("urlset", "url", &locations);
+        assert!(xml.starts_with("<?xml"));
+        assert!(xml.contains("<url><loc>https://example.org/a?b=1&amp;c=2</loc></url>"));
+        assert!(xml.trim_end().ends_with("</urlset>"));
+    }
+
+    #[test]
+    fn page_paths() {
+        let ids = ProductIds {
+            eans: vec!["123".to_owned()],
+            gtins: Vec::new(),
+            wiki: vec!["42".to_owned()],
+        };
+        assert_eq!(product_path(&ids).as_deref(), Some("/product/ean/123"));
+        assert_eq!(wikidata_urls(&ids.wiki), vec!["https://www.wikidata.org/wiki/Q42"]);
+        assert_eq!(
+            image_url(&Image { image: "ACME logo.svg".to_owned(), source: Source::Wikidata }),
+            "https://commons.wikimedia.org/wiki/Special:FilePath/ACME_logo.svg"
+        );
+
+        let ids = OrganisationIds {
+            wiki: Vec::new(),
+            vat_ids: Vec::new(),
+            domains: Vec::new(),
+            leis: Vec::new(),
+            registration_numbers: Vec::new(),
+        };
+        assert_eq!(organisation_path(&ids), None);
+    }
+}
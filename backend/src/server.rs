@@ -11,11 +11,11 @@ use sustainity_api::{
     GetOrganisationResponse, GetProductResponse, SearchByTextResponse,
 };
 
-use crate::{db::Db, retrieve};
+use crate::{db::Db, languages::Languages, models::AlternativesOptions, retrieve};
 
-const CORS_ORIGIN: &str = "*";
-const CORS_METHODS: &str = "GET, POST, DELETE, OPTIONS";
-const CORS_HEADERS: &str = "Origin, Content-Type";
+pub const CORS_ORIGIN: &str = "*";
+pub const CORS_METHODS: &str = "GET, POST, DELETE, OPTIONS";
+pub const CORS_HEADERS: &str = "Origin, Content-Type, X-Api-Key";
 
 fn get<T, C: swagger::Has<T>>(context: &C) -> &T {
     <C as swagger::Has<T>>::get(context)
@@ -35,7 +35,11 @@ impl<C> Server<C> {
 #[async_trait]
 impl<C> Api<C> for Server<C>
 where
-    C: swagger::Has<swagger::XSpanIdString> + swagger::Has<Db> + Send + Sync,
+    C: swagger::Has<swagger::XSpanIdString>
+        + swagger::Has<Db>
+        + swagger::Has<Languages>
+        + Send
+        + Sync,
 {
     async fn check_health(&self, _context: &C) -> Result<CheckHealthResponse, ApiError> {
         Ok(CheckHealthResponse::Ok {
@@ -47,7 +51,8 @@ where
 
     async fn get_library(&self, context: &C) -> Result<GetLibraryResponse, ApiError> {
         let db = get::<Db, C>(context);
-        let items = retrieve::library_contents(db).await?;
+        let languages = get::<Languages, C>(context);
+        let items = retrieve::library_contents(db, languages).await?;
         Ok(GetLibraryResponse::Ok {
             body: LibraryContents { items },
             access_control_allow_origin: CORS_ORIGIN.to_string(),
@@ -62,7 +67,8 @@ where
         context: &C,
     ) -> Result<GetLibraryItemResponse, ApiError> {
         let db = get::<Db, C>(context);
-        if let Some(item) = retrieve::library_item(topic, db).await? {
+        let languages = get::<Languages, C>(context);
+        if let Some(item) = retrieve::library_item(topic, db, languages).await? {
             Ok(GetLibraryItemResponse::Ok {
                 body: item,
                 access_control_allow_origin: CORS_ORIGIN.to_string(),
@@ -147,7 +153,9 @@ where
         context: &C,
     ) -> Result<GetAlternativesResponse, ApiError> {
         let db = get::<Db, C>(context);
-        let alternatives = retrieve::product_alternatives(&id, region.as_deref(), db).await?;
+        let options = AlternativesOptions::default();
+        let alternatives =
+            retrieve::product_alternatives(&id, region.as_deref(), &options, db).await?;
         Ok(GetAlternativesResponse::Ok {
             body: alternatives,
             access_control_allow_origin: CORS_ORIGIN.to_string(),
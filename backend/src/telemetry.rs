@@ -0,0 +1,65 @@
+//! Setup of logging and tracing.
+//!
+//! Events and spans are written to the standard output. The verbosity can be adjusted with the
+//! `RUST_LOG` environment variable. When built with the `otel` feature and the
+//! `OTEL_EXPORTER_OTLP_ENDPOINT` environment variable is set, the spans are also exported to an
+//! OpenTelemetry collector.
+
+use snafu::prelude::*;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
+pub enum TelemetryError {
+    #[snafu(context(false), display("Failed to initialize the subscriber: {source}"))]
+    Init { source: tracing_subscriber::util::TryInitError },
+}
+
+/// Installs the global subscriber.
+///
+/// Records emitted with the `log` crate (e.g. by the database driver) are forwarded to it as well.
+pub fn init() -> Result<(), TelemetryError> {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let registry =
+        tracing_subscriber::registry().with(filter).with(tracing_subscriber::fmt::layer());
+
+    #[cfg(feature = "otel")]
+    let registry = registry.with(otel::layer());
+
+    registry.try_init()?;
+    Ok(())
+}
+
+#[cfg(feature = "otel")]
+mod otel {
+    use opentelemetry::KeyValue;
+    use opentelemetry_sdk::{runtime, trace, Resource};
+    use tracing::Subscriber;
+    use tracing_subscriber::{registry::LookupSpan, Layer};
+
+    const SERVICE_NAME: &str = "sustainity-backend";
+
+    /// Creates the OpenTelemetry layer if the exporter endpoint is configured.
+    ///
+    /// The export is optional, so failing to set it up is reported but not fatal.
+    pub fn layer<S>() -> Option<impl Layer<S>>
+    where
+        S: Subscriber + for<'span> LookupSpan<'span>,
+    {
+        std::env::var_os("OTEL_EXPORTER_OTLP_ENDPOINT")?;
+
+        let resource = Resource::new(vec![KeyValue::new("service.name", SERVICE_NAME)]);
+        let tracer = opentelemetry_otlp::new_pipeline()
+            .tracing()
+            .with_exporter(opentelemetry_otlp::new_exporter().tonic())
+            .with_trace_config(trace::config().with_resource(resource))
+            .install_batch(runtime::Tokio);
+        match tracer {
+            Ok(tracer) => Some(tracing_opentelemetry::layer().with_tracer(tracer)),
+            Err(err) => {
+                println!("Failed to initialize OpenTelemetry: {err}");
+                None
+            }
+        }
+    }
+}
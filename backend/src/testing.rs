@@ -0,0 +1,521 @@
+//! End-to-end tests of the HTTP API.
+//!
+//! The tests start the whole service against a small dataset checked in to `tests/data`: a few
+//! dozen products and organisations, a couple of library articles and the certifications. The
+//! dataset has the layout of the lab output, one file per collection including the search
+//! indexes and the edges, so that the backend is tested on the same data it imports in
+//! production. Each test imports the dataset into a fresh database which is dropped when the
+//! test finishes.
+//!
+//! The tests need a running ArangoDB, so they are ignored by default. Run them with:
+//!
+//! ```sh
+//! SUSTAINITY_TEST_ARANGO_URL=http://localhost:8529 cargo test -p sustainity-backend -- --ignored
+//! ```
+//!
+//! The credentials are read from `SUSTAINITY_TEST_ARANGO_USER` (`root` by default) and
+//! `SUSTAINITY_TEST_ARANGO_PASSWORD` (empty by default).
+
+use std::{
+    collections::BTreeMap,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use serde_json::{json, Value};
+
+use crate::{
+    auth,
+    config::{
+        CacheConfig, DatasetConfig, ExportConfig, PoolConfig, QueryConfig, RateLimitConfig,
+        SearchConfig, SecretConfig, SiteConfig,
+    },
+    context,
+    db::Db,
+    export, rate_limit, reload, routes, server, webhooks,
+};
+
+/// Directory with the fixture dataset.
+const DATA_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data");
+
+/// Prefix of the names of the test databases.
+const DATABASE_PREFIX: &str = "sustainity_test";
+
+/// Counter making the names of the databases created by one test run unique.
+static DATABASE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Contents of the collections of a dataset.
+#[derive(Default)]
+struct Dataset {
+    collections: BTreeMap<String, Vec<Value>>,
+}
+
+impl Dataset {
+    /// Builds the dataset from the fixture files, one collection per file.
+    fn load() -> Self {
+        let mut dataset = Self::default();
+        let entries = std::fs::read_dir(DATA_DIR).unwrap_or_else(|err| panic!("{DATA_DIR}: {err}"));
+        for entry in entries {
+            let path = entry.expect("Reading the fixture directory").path();
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else { continue };
+            let Some(name) = name.strip_suffix(".jsonl") else { continue };
+            dataset.collections.insert(name.to_owned(), read_fixture(name));
+        }
+        dataset
+    }
+}
+
+fn read_fixture(name: &str) -> Vec<Value> {
+    let path = format!("{DATA_DIR}/{name}.jsonl");
+    let contents = std::fs::read_to_string(&path).unwrap_or_else(|err| panic!("{path}: {err}"));
+    contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).unwrap_or_else(|err| panic!("{path}: {err}")))
+        .collect()
+}
+
+/// Minimal client of the ArangoDB HTTP API used to prepare the test databases.
+struct Arango {
+    client: reqwest::Client,
+    secret: SecretConfig,
+}
+
+impl Arango {
+    fn from_env() -> Self {
+        let var = |name: &str, default: &str| std::env::var(name).unwrap_or(default.to_owned());
+        let secret = SecretConfig::new(
+            var("SUSTAINITY_TEST_ARANGO_URL", "http://localhost:8529"),
+            var("SUSTAINITY_TEST_ARANGO_USER", "root"),
+            var("SUSTAINITY_TEST_ARANGO_PASSWORD", ""),
+        );
+        Self { client: reqwest::Client::new(), secret }
+    }
+
+    async fn send(&self, request: reqwest::RequestBuilder) {
+        let response = request
+            .basic_auth(&self.secret.user, Some(&self.secret.password))
+            .send()
+            .await
+            .expect("ArangoDB not reachable (is `SUSTAINITY_TEST_ARANGO_URL` set?)");
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        assert!(status.is_success(), "ArangoDB request failed with {status}: {body}");
+    }
+
+    /// Creates the database and imports the dataset into it.
+    async fn import(&self, database: &str, dataset: &Dataset) {
+        let url = &self.secret.host;
+        self.send(
+            self.client.post(format!("{url}/_api/database")).json(&json!({"name": database})),
+        )
+        .await;
+        for (name, entries) in &dataset.collections {
+            let kind = if name.ends_with("_edges") { 3 } else { 2 };
+            let request = self
+                .client
+                .post(format!("{url}/_db/{database}/_api/collection"))
+                .json(&json!({"name": name, "type": kind}));
+            self.send(request).await;
+            if entries.is_empty() {
+                continue;
+            }
+            let body: String = entries.iter().map(|entry| format!("{entry}\n")).collect();
+            let request = self
+                .client
+                .post(format!("{url}/_db/{database}/_api/import"))
+                .query(&[
+                    ("collection", name.as_str()),
+                    ("type", "documents"),
+                    ("complete", "true"),
+                ])
+                .body(body);
+            self.send(request).await;
+        }
+    }
+
+    async fn drop(&self, database: &str) {
+        let url = &self.secret.host;
+        self.send(self.client.delete(format!("{url}/_api/database/{database}"))).await;
+    }
+}
+
+/// The service running against the fixture dataset.
+pub struct TestBackend {
+    addr: SocketAddr,
+    client: reqwest::Client,
+    arango: Arango,
+    database: String,
+}
+
+impl TestBackend {
+    /// Imports the fixture dataset into a fresh database and starts the service serving it.
+    pub async fn start() -> Self {
+        let arango = Arango::from_env();
+        let counter = DATABASE_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let database = format!("{DATABASE_PREFIX}_{}_{counter}", std::process::id());
+        arango.import(&database, &Dataset::load()).await;
+
+        let dataset = DatasetConfig { database: database.clone(), version: "test".to_owned() };
+        let db = Db::new(
+            arango.secret.clone(),
+            PoolConfig::default(),
+            &CacheConfig { capacity: 1000, ttl: Duration::from_secs(60) },
+            QueryConfig { slow_threshold: Duration::from_secs(1) },
+            SearchConfig::default(),
+            dataset.clone(),
+        );
+        let webhooks = webhooks::Webhooks::new(Vec::new());
+        reload::reload(&db, dataset, &webhooks).await.expect("Fixture dataset incomplete");
+
+        // Assembled the same way as in `main`, only without rate limits and API keys.
+        let service = sustainity_api::server::MakeService::new(server::Server::new());
+        let service = context::MakeAddContext::<_, context::EmptyContext>::new(service, db.clone());
+        let rate_limits = RateLimitConfig {
+            per_ip_per_minute: 0,
+            per_key_per_minute: 0,
+            readiness_per_minute: 0,
+            trust_proxy: false,
+        };
+        let shared = Arc::new(routes::Shared {
+            limiters: rate_limit::RateLimiters::new(&rate_limits),
+            api_keys: auth::ApiKeys::new(Vec::new()),
+            webhooks,
+            site: SiteConfig { base_url: "https://sustainity.test".to_owned() },
+            exporter: export::Exporter::new(&ExportConfig {
+                dir: std::env::temp_dir().join(&database),
+            }),
+        });
+        let service = routes::MakeRouter::new(service, db, shared);
+        let server = hyper::Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(service);
+        let addr = server.local_addr();
+        tokio::spawn(server);
+
+        // Redirects are checked by the tests themselves.
+        let client = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .expect("Building the HTTP client");
+        Self { addr, client, arango, database }
+    }
+
+    /// Sends a `GET` request with the given headers.
+    pub async fn get_with(&self, path: &str, headers: &[(&str, &str)]) -> reqwest::Response {
+        let mut request = self.client.get(format!("http://{}{path}", self.addr));
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        request.send().await.expect("Sending a request to the backend")
+    }
+
+    /// Sends a `GET` request and returns the status with the parsed JSON body.
+    pub async fn get(&self, path: &str) -> (reqwest::StatusCode, Value) {
+        let response = self.get_with(path, &[]).await;
+        let status = response.status();
+        let body = response.json().await.unwrap_or(Value::Null);
+        (status, body)
+    }
+
+    /// Drops the test database.
+    pub async fn finish(self) {
+        self.arango.drop(&self.database).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use reqwest::StatusCode;
+
+    use super::*;
+
+    /// Finds the entry with the given label or name in the list.
+    fn find_named<'a>(list: &'a Value, name: &str) -> &'a Value {
+        let items = list.as_array().expect("A list");
+        items
+            .iter()
+            .find(|item| item["label"] == name || item["name"] == name)
+            .unwrap_or_else(|| panic!("{name} not found in {list}"))
+    }
+
+    /// Collects all the string values in the JSON value (e.g. to look for names in results).
+    fn strings(value: &Value) -> BTreeSet<String> {
+        let mut result = BTreeSet::new();
+        let mut stack = vec![value];
+        while let Some(value) = stack.pop() {
+            match value {
+                Value::String(string) => {
+                    result.insert(string.clone());
+                }
+                Value::Array(items) => stack.extend(items),
+                Value::Object(fields) => stack.extend(fields.values()),
+                _ => {}
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn fixture_dataset() {
+        let dataset = Dataset::load();
+        let count = |name: &str| dataset.collections.get(name).map_or(0, Vec::len);
+        assert!(count("organisations") >= 20);
+        assert!(count("products") >= 30);
+        assert_eq!(count("product_gtins"), count("products"));
+        assert_eq!(count("manufacturing_edges"), count("products"));
+        for collection in ["organisation_keywords", "product_keywords", "categories", "library"] {
+            assert!(count(collection) > 0, "{collection} is empty");
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "requires ArangoDB"]
+    async fn search() {
+        let backend = TestBackend::start().await;
+
+        let (status, body) = backend.get("/search/text?query=fairphone").await;
+        assert_eq!(status, StatusCode::OK);
+        let found = strings(&body);
+        assert!(found.contains("Fairphone"));
+        assert!(found.contains("Fairphone 5"));
+        assert!(!found.contains("iPhone 15"));
+
+        let (status, body) = backend.get("/search/text?query=08712345000011").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(strings(&body).contains("Fairphone 5"));
+
+        let (status, body) = backend.get("/search/text?query=www.fairphone.com").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(strings(&body).contains("Fairphone"));
+
+        let (status, body) = backend.get("/search/text?query=air%20brand:apple").await;
+        assert_eq!(status, StatusCode::OK);
+        let found = strings(&body);
+        assert!(found.contains("MacBook Air"));
+        assert!(!found.contains("Air Force 1"));
+
+        let (status, body) = backend.get("/search/text?query=NL852486413B01").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(strings(&body).contains("Fairphone"));
+
+        let (status, body) = backend.get("/search/detailed?query=fairphone").await;
+        assert_eq!(status, StatusCode::OK);
+        let manufacturers = json!([{"name": "Fairphone", "wiki": "5430485"}]);
+        assert_eq!(find_named(&body, "Fairphone 5")["manufacturers"], manufacturers);
+        assert!(find_named(&body, "Fairphone").get("manufacturers").is_none());
+
+        let (status, body) = backend.get("/search/text?query=").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], "INVALID_PARAMETER");
+
+        backend.finish().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "requires ArangoDB"]
+    async fn products() {
+        let backend = TestBackend::start().await;
+
+        for path in ["/product/gtin/08712345000011", "/product/gtin/8712345000011"] {
+            let (status, body) = backend.get(path).await;
+            assert_eq!(status, StatusCode::OK, "{path}");
+            assert!(strings(&body).contains("Fairphone 5"), "{path}");
+        }
+        let (status, body) = backend.get("/product/wiki/900011").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(strings(&body).contains("Framework Laptop 13"));
+
+        let (status, body) = backend.get("/product/gtin/4006381333931").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["code"], "NOT_FOUND");
+
+        let any = [("If-None-Match", "*")];
+        let response = backend.get_with("/product/gtin/08712345000011", &any).await;
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        let response = backend.get_with("/product/gtin/4006381333931", &any).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let (status, body) = backend.get("/product/gtin/8712345000012").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], "INVALID_GTIN");
+
+        let (status, body) = backend.get("/categories/smartphone/products?limit=5").await;
+        assert_eq!(status, StatusCode::OK);
+        let fairphone = find_named(&body["products"], "Fairphone 5");
+        assert_eq!(fairphone["manufacturers"][0]["name"], "Fairphone");
+
+        let (status, body) = backend.get("/products/1004/alternatives").await;
+        assert_eq!(status, StatusCode::OK);
+        let smartphones = &body[0];
+        assert_eq!(smartphones["category"], "smartphone");
+        let fairphone = find_named(&smartphones["alternatives"], "Fairphone 5");
+        assert_eq!(fairphone["manufacturers"][0]["name"], "Fairphone");
+
+        let (status, body) = backend.get("/categories/smartphone/stats").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body["num_products"].as_u64().unwrap() > 0);
+        assert!(!body["best_products"].as_array().unwrap().is_empty());
+
+        let (status, _) = backend.get("/categories/unknown/stats").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        for path in [
+            "/jsonld/product/gtin/08712345000011",
+            "/jsonld/product/gtin/8712345000011",
+            "/jsonld/product/wiki/900001",
+        ] {
+            let (status, body) = backend.get(path).await;
+            assert_eq!(status, StatusCode::OK, "{path}");
+            assert_eq!(body["@type"], "Product", "{path}");
+            assert_eq!(body["name"], "Fairphone 5", "{path}");
+            assert_eq!(body["manufacturer"][0]["name"], "Fairphone", "{path}");
+        }
+        let (status, _) = backend.get("/jsonld/product/gtin/4006381333931").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        backend.finish().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "requires ArangoDB"]
+    async fn organisations() {
+        let backend = TestBackend::start().await;
+
+        for path in [
+            "/organisation/wiki/5430485",
+            "/organisation/vat/NL852486413B01",
+            "/organisation/www/fairphone.com",
+            "/organisation/entity/wiki:Q5430485",
+            "/organisation/entity/sus:vat-NL852486413B01",
+            "/organisation/entity/sus:www-fairphone.com",
+        ] {
+            let (status, body) = backend.get(path).await;
+            assert_eq!(status, StatusCode::OK, "{path}");
+            let found = strings(&body);
+            assert!(found.contains("Fairphone"), "{path}");
+            assert!(found.contains("Fairphone 4"), "{path}: products missing");
+            assert_eq!(body["attributes"]["csrd_report_year"], 2024, "{path}: attributes missing");
+            assert_eq!(body["recognitions"][0]["name"], "Sustainability Award", "{path}");
+            let timeline: Vec<_> =
+                body["timeline"].as_array().unwrap().iter().map(|e| &e["kind"]).collect();
+            assert_eq!(
+                timeline,
+                ["certification_granted", "award_received", "report_published"],
+                "{path}"
+            );
+        }
+
+        let (status, body) = backend.get("/organisation/wiki/1154249").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["material_standards"], json!(["grs", "rws"]));
+
+        let (status, body) = backend.get("/organisations/identifier/724500GN4FAFEKGGEU27").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(strings(&body).contains("Fairphone"));
+
+        let (status, _) = backend.get("/organisation/wiki/1").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        backend.finish().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "requires ArangoDB"]
+    async fn resolve() {
+        let backend = TestBackend::start().await;
+
+        let (status, body) = backend
+            .get("/resolve?url=https://shop.fairphone.com/fairphone-5&product=fairphone%205")
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["domain"], "fairphone.com");
+        assert!(strings(&body["organisation"]).contains("Fairphone"));
+        assert!(strings(&body["product"]).contains("Fairphone 5"));
+
+        let (status, body) = backend.get("/resolve?url=https://www.github.com/sustainity").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["domain"], "github.com");
+        assert!(strings(&body).contains("GitHub"));
+
+        // `github.io` is a private public suffix, so its sites do not belong to GitHub.
+        let (status, _) = backend.get("/resolve?url=https://someone.github.io/").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        backend.finish().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "requires ArangoDB"]
+    async fn library() {
+        let backend = TestBackend::start().await;
+
+        let (status, body) = backend.get("/library").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(strings(&body).contains("B Corporation"));
+
+        let (status, body) = backend.get("/library/cert:bcorp").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["title"], "B Corporation");
+
+        let response = backend.get_with("/library/cert:bcorp", &[("Accept-Language", "nl")]).await;
+        assert_eq!(response.headers()["vary"], "Accept-Language");
+        let body: Value = response.json().await.unwrap();
+        assert_eq!(body["title"], "B-Corporatie");
+
+        let (status, body) = backend.get("/library/slug/bcorp").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["title"], "B Corporation");
+
+        let response = backend.get_with("/library/slug/b-corp", &[]).await;
+        assert_eq!(response.status(), StatusCode::MOVED_PERMANENTLY);
+        assert_eq!(response.headers()["location"], "/library/slug/bcorp");
+
+        let (status, body) = backend.get("/library/slugs").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(strings(&body).contains("tco-certified"));
+
+        let (status, _) = backend.get("/library/slug/missing").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        backend.finish().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "requires ArangoDB"]
+    async fn export() {
+        let backend = TestBackend::start().await;
+
+        let path = "/export/organisations.jsonl.gz";
+        let mut response = backend.get_with(path, &[]).await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert!(response.headers().contains_key("retry-after"));
+        for _ in 0..100 {
+            if response.status() == StatusCode::OK {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            response = backend.get_with(path, &[]).await;
+        }
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "application/jsonl");
+        assert_eq!(response.headers()["content-encoding"], "gzip");
+        let etag = response.headers()["etag"].to_str().unwrap().to_owned();
+        assert!(response.headers().contains_key("last-modified"));
+
+        let response = backend.get_with(path, &[("Range", "bytes=0-9"), ("If-Range", &etag)]).await;
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(response.bytes().await.unwrap().len(), 10);
+
+        let response =
+            backend.get_with(path, &[("Range", "bytes=0-9"), ("If-Range", "\"stale\"")]).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.bytes().await.unwrap().len() > 10);
+
+        backend.finish().await;
+    }
+}
@@ -0,0 +1,6 @@
+//! Timestamps of records stored by the backend (e.g. feedback or product requests).
+
+/// Returns the current time as an RFC 3339 timestamp with a precision of seconds.
+pub fn now() -> String {
+    humantime::format_rfc3339_seconds(std::time::SystemTime::now()).to_string()
+}
@@ -0,0 +1,342 @@
+//! Validation of path and query parameters.
+//!
+//! Parameters are checked before any database query is made. Malformed ones are rejected with 400
+//! and a message for each invalid field, instead of being passed on to the queries where they would
+//! only result in "not found".
+
+use std::collections::HashMap;
+
+use sustainity_api::models as api;
+use sustainity_models::{
+    gtin,
+    store::{EntityId, ExternalIdKind, LibraryItem},
+};
+
+use crate::problems::{ErrorCode, FieldError};
+
+/// Maximal length (in characters) of search queries.
+pub const MAX_QUERY_LENGTH: usize = 200;
+
+/// Maximal length of organisation identifiers (VAT numbers, LEIs, registration numbers).
+pub const MAX_IDENTIFIER_LENGTH: usize = 64;
+
+/// Maximal offset of paged results.
+pub const MAX_OFFSET: usize = 100_000;
+
+/// Maximal length of a domain name.
+const MAX_DOMAIN_LENGTH: usize = 253;
+
+/// Maximal length of a page URL.
+const MAX_URL_LENGTH: usize = 2048;
+
+/// Collects errors of all the checked fields.
+#[derive(Default)]
+pub struct Validator {
+    errors: Vec<FieldError>,
+}
+
+impl Validator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the error, if any, and returns the checked value.
+    pub fn check<T>(&mut self, result: Result<T, FieldError>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(error) => {
+                self.errors.push(error);
+                None
+            }
+        }
+    }
+
+    /// Returns `Err` with all the recorded errors if there were any.
+    pub fn finish(self) -> Result<(), Vec<FieldError>> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(self.errors)
+        }
+    }
+}
+
+fn is_digits(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Checks the format of a product ID and returns its variant.
+pub fn product_id(variant: &str, id: &str) -> Result<api::ProductIdVariant, FieldError> {
+    let Ok(variant) = variant.parse::<api::ProductIdVariant>() else {
+        let message = format!("Unknown product ID variant `{variant}`");
+        return Err(FieldError::new("variant", ErrorCode::InvalidIdVariant, message));
+    };
+    match variant {
+        api::ProductIdVariant::Gtin if gtin::normalize(id).is_err() => {
+            let message = format!("`{id}` is not a valid GTIN");
+            Err(FieldError::new("id", ErrorCode::InvalidGtin, message))
+        }
+        api::ProductIdVariant::Ean if !is_digits(id) => {
+            let message = format!("`{id}` is not a valid EAN");
+            Err(FieldError::new("id", ErrorCode::InvalidId, message))
+        }
+        api::ProductIdVariant::Wiki if !is_digits(id) => {
+            let message = format!("`{id}` is not a numeric Wikidata ID");
+            Err(FieldError::new("id", ErrorCode::InvalidId, message))
+        }
+        _ => Ok(variant),
+    }
+}
+
+/// Checks the format of an organisation ID and returns its variant.
+pub fn organisation_id(variant: &str, id: &str) -> Result<api::OrganisationIdVariant, FieldError> {
+    let Ok(variant) = variant.parse::<api::OrganisationIdVariant>() else {
+        let message = format!("Unknown organisation ID variant `{variant}`");
+        return Err(FieldError::new("variant", ErrorCode::InvalidIdVariant, message));
+    };
+    let valid = match variant {
+        api::OrganisationIdVariant::Wiki => is_digits(id),
+        api::OrganisationIdVariant::Vat => {
+            id.len() <= MAX_IDENTIFIER_LENGTH
+                && id.chars().all(|c| c.is_ascii_alphanumeric() || c == ' ' || c == '.' || c == '-')
+        }
+        api::OrganisationIdVariant::Www => {
+            id.len() <= MAX_DOMAIN_LENGTH
+                && id.contains('.')
+                && id.chars().all(|c| c.is_alphanumeric() || c == '.' || c == '-')
+        }
+    };
+    if valid {
+        Ok(variant)
+    } else {
+        let message = format!("`{id}` is not a valid `{variant}` organisation ID");
+        Err(FieldError::new("id", ErrorCode::InvalidId, message))
+    }
+}
+
+/// Parses a namespaced entity ID (e.g. `wiki:Q42`).
+pub fn entity_id(id: &str) -> Result<EntityId, FieldError> {
+    id.parse().map_err(|_| {
+        let message = format!("`{id}` is not a valid entity ID (expected e.g. `wiki:Q42`)");
+        FieldError::new("id", ErrorCode::InvalidId, message)
+    })
+}
+
+/// Checks an organisation identifier (VAT number, LEI or registration number).
+pub fn identifier(id: &str) -> Result<(), FieldError> {
+    if id.trim().is_empty() || id.chars().count() > MAX_IDENTIFIER_LENGTH {
+        let message = format!("Identifiers must have 1 to {MAX_IDENTIFIER_LENGTH} characters");
+        Err(FieldError::new("id", ErrorCode::InvalidId, message))
+    } else {
+        Ok(())
+    }
+}
+
+/// Parses an external product identifier (e.g. an ASIN) and brings it to its canonical form.
+pub fn external_id(kind: &str, id: &str) -> Result<(ExternalIdKind, String), FieldError> {
+    let Some(kind) = ExternalIdKind::from_name(kind) else {
+        let message = format!("Unknown external ID kind `{kind}`");
+        return Err(FieldError::new("kind", ErrorCode::InvalidIdVariant, message));
+    };
+    kind.normalize(id).map(|id| (kind, id)).ok_or_else(|| {
+        let message = format!("`{id}` is not a valid `{}` ID", kind.as_str());
+        FieldError::new("id", ErrorCode::InvalidId, message)
+    })
+}
+
+/// Checks that the region is an ISO 3166-1 alpha-3 country code.
+pub fn region(region: Option<&str>) -> Result<(), FieldError> {
+    match region {
+        Some(region) if isocountry::CountryCode::for_alpha3_caseless(region).is_err() => {
+            let message = format!("`{region}` is not an ISO 3166-1 alpha-3 country code");
+            Err(FieldError::new("region", ErrorCode::InvalidRegion, message))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Checks a library topic.
+pub fn topic(topic: &str) -> Result<api::LibraryTopic, FieldError> {
+    topic.parse().map_err(|_| {
+        let message = format!("Unknown library topic `{topic}`");
+        FieldError::new("topic", ErrorCode::InvalidParameter, message)
+    })
+}
+
+/// Checks the format of a library article slug.
+pub fn slug(slug: &str) -> Result<(), FieldError> {
+    if LibraryItem::is_valid_slug(slug) {
+        Ok(())
+    } else {
+        let message = format!("`{slug}` is not a valid slug");
+        Err(FieldError::new("slug", ErrorCode::InvalidParameter, message))
+    }
+}
+
+/// Checks that the search query is not empty nor too long.
+pub fn search_query(query: Option<&str>) -> Result<(), FieldError> {
+    let Some(query) = query else {
+        let message = "Missing `query` parameter".to_owned();
+        return Err(FieldError::new("query", ErrorCode::MissingParameter, message));
+    };
+    if query.trim().is_empty() {
+        let message = "The query must not be empty".to_owned();
+        Err(FieldError::new("query", ErrorCode::InvalidParameter, message))
+    } else if query.chars().count() > MAX_QUERY_LENGTH {
+        let message = format!("The query must not be longer than {MAX_QUERY_LENGTH} characters");
+        Err(FieldError::new("query", ErrorCode::InvalidParameter, message))
+    } else {
+        Ok(())
+    }
+}
+
+/// Checks that the page URL is given, is not too long and is an HTTP(S) URL (or a bare domain).
+pub fn page_url(url: Option<&str>) -> Result<(), FieldError> {
+    let Some(url) = url else {
+        let message = "Missing `url` parameter".to_owned();
+        return Err(FieldError::new("url", ErrorCode::MissingParameter, message));
+    };
+    let url = url.trim().to_lowercase();
+    let host = match url.split_once("://") {
+        Some(("http" | "https", rest)) => rest,
+        Some(_) => {
+            let message = "The URL must be an HTTP(S) URL".to_owned();
+            return Err(FieldError::new("url", ErrorCode::InvalidParameter, message));
+        }
+        None => url.as_str(),
+    };
+    if url.chars().count() > MAX_URL_LENGTH {
+        let message = format!("The URL must not be longer than {MAX_URL_LENGTH} characters");
+        Err(FieldError::new("url", ErrorCode::InvalidParameter, message))
+    } else if !host.split(['/', '?', '#']).next().is_some_and(|host| host.contains('.')) {
+        let message = "The URL must contain a domain name".to_owned();
+        Err(FieldError::new("url", ErrorCode::InvalidParameter, message))
+    } else {
+        Ok(())
+    }
+}
+
+/// Checks that the product name hint, if given, is not too long.
+pub fn product_hint(hint: Option<&str>) -> Result<(), FieldError> {
+    match hint {
+        Some(hint) if hint.chars().count() > MAX_QUERY_LENGTH => {
+            let message =
+                format!("The product name must not be longer than {MAX_QUERY_LENGTH} characters");
+            Err(FieldError::new("product", ErrorCode::InvalidParameter, message))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Checks the `offset` query parameter.
+pub fn offset(query: &HashMap<String, String>) -> Result<(), FieldError> {
+    match query.get("offset").map(|offset| offset.parse::<usize>()) {
+        None => Ok(()),
+        Some(Ok(offset)) if offset <= MAX_OFFSET => Ok(()),
+        Some(_) => {
+            let message = format!("`offset` must be a number from 0 to {MAX_OFFSET}");
+            Err(FieldError::new("offset", ErrorCode::InvalidParameter, message))
+        }
+    }
+}
+
+/// Checks the `limit` query parameter.
+pub fn limit(query: &HashMap<String, String>, max: usize) -> Result<(), FieldError> {
+    match query.get("limit").map(|limit| limit.parse::<usize>()) {
+        None => Ok(()),
+        Some(Ok(limit)) if (1..=max).contains(&limit) => Ok(()),
+        Some(_) => {
+            let message = format!("`limit` must be a number from 1 to {max}");
+            Err(FieldError::new("limit", ErrorCode::InvalidParameter, message))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| ((*k).to_owned(), (*v).to_owned())).collect()
+    }
+
+    #[test]
+    fn ids() {
+        assert!(product_id("gtin", "8717677339556").is_ok());
+        assert_eq!(product_id("gtin", "8717677339557").unwrap_err().code, ErrorCode::InvalidGtin);
+        assert_eq!(product_id("wiki", "Q1").unwrap_err().code, ErrorCode::InvalidId);
+        assert_eq!(product_id("isbn", "1").unwrap_err().code, ErrorCode::InvalidIdVariant);
+
+        assert!(organisation_id("wiki", "123").is_ok());
+        assert!(organisation_id("vat", "PL 123-456").is_ok());
+        assert!(organisation_id("www", "fairphone.com").is_ok());
+        assert!(organisation_id("www", "fairphone.com/about").is_err());
+        assert!(organisation_id("vat", "PL123'; DROP").is_err());
+
+        assert!(slug("cert-bcorp").is_ok());
+        assert_eq!(slug("cert:bcorp").unwrap_err().field, "slug");
+
+        assert!(entity_id("wiki:Q42").is_ok());
+        assert_eq!(entity_id("foo:42").unwrap_err().field, "id");
+
+        assert_eq!(
+            external_id("asin", " b0c1234567").unwrap(),
+            (ExternalIdKind::Asin, "B0C1234567".to_owned())
+        );
+        assert_eq!(external_id("asin", "B0C123").unwrap_err().code, ErrorCode::InvalidId);
+        assert_eq!(external_id("ean", "1").unwrap_err().code, ErrorCode::InvalidIdVariant);
+    }
+
+    #[test]
+    fn regions() {
+        assert!(region(None).is_ok());
+        assert!(region(Some("NLD")).is_ok());
+        assert!(region(Some("nld")).is_ok());
+        assert_eq!(region(Some("XXX")).unwrap_err().code, ErrorCode::InvalidRegion);
+        assert!(region(Some("NL")).is_err());
+    }
+
+    #[test]
+    fn queries() {
+        assert!(search_query(Some("fairphone")).is_ok());
+        assert_eq!(search_query(None).unwrap_err().code, ErrorCode::MissingParameter);
+        assert!(search_query(Some("  ")).is_err());
+        assert!(search_query(Some(&"a".repeat(MAX_QUERY_LENGTH + 1))).is_err());
+    }
+
+    #[test]
+    fn urls() {
+        assert!(page_url(Some("https://shop.fairphone.com/fairphone-6")).is_ok());
+        assert!(page_url(Some("www.fairphone.com")).is_ok());
+        assert_eq!(page_url(None).unwrap_err().code, ErrorCode::MissingParameter);
+        assert_eq!(page_url(Some("javascript:alert(1)")).unwrap_err().field, "url");
+        assert!(page_url(Some("ftp://fairphone.com")).is_err());
+        assert!(page_url(Some("http://localhost/a.b")).is_err());
+        assert!(page_url(Some(&format!("https://a.com/{}", "a".repeat(MAX_URL_LENGTH)))).is_err());
+
+        assert!(product_hint(None).is_ok());
+        assert!(product_hint(Some("Fairphone 6")).is_ok());
+        assert!(product_hint(Some(&"a".repeat(MAX_QUERY_LENGTH + 1))).is_err());
+    }
+
+    #[test]
+    fn pagination() {
+        assert!(offset(&query(&[])).is_ok());
+        assert!(offset(&query(&[("offset", "20")])).is_ok());
+        assert!(offset(&query(&[("offset", "-1")])).is_err());
+        assert!(offset(&query(&[("offset", "1000000")])).is_err());
+        assert!(limit(&query(&[("limit", "100")]), 100).is_ok());
+        assert!(limit(&query(&[("limit", "0")]), 100).is_err());
+        assert!(limit(&query(&[("limit", "101")]), 100).is_err());
+    }
+
+    #[test]
+    fn collecting() {
+        let mut validator = Validator::new();
+        assert_eq!(validator.check(region(Some("NLD"))), Some(()));
+        validator.check(offset(&query(&[("offset", "x")])));
+        validator.check(limit(&query(&[("limit", "x")]), 10));
+        let errors = validator.finish().unwrap_err();
+        assert_eq!(errors.iter().map(|e| e.field).collect::<Vec<_>>(), vec!["offset", "limit"]);
+    }
+}
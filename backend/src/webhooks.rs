@@ -0,0 +1,135 @@
+//! Notifications about dataset updates.
+//!
+//! After a new dataset is swapped in, every configured webhook receives a `POST` request with a
+//! `DatasetUpdate` in the body, so that downstream caches and partner integrations can refresh.
+//! The notifications are sent in the background and failed deliveries are retried a few times.
+
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use crate::models::{CollectionChange, DatasetUpdate, Webhook};
+
+/// Header identifying the kind of the event.
+const EVENT_HEADER: &str = "x-sustainity-event";
+
+/// Name of the dataset update event.
+const DATASET_UPDATED_EVENT: &str = "dataset.updated";
+
+/// Number of attempts to deliver a notification.
+const MAX_ATTEMPTS: u32 = 3;
+
+/// Delay before the first retry, doubled on each following one.
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Timeout of a single delivery attempt.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+impl DatasetUpdate {
+    /// Summarises the update from the collection sizes of the previous and the new dataset.
+    pub fn new(
+        version: String,
+        previous_version: Option<String>,
+        previous: Option<&HashMap<String, usize>>,
+        current: &HashMap<String, usize>,
+    ) -> Self {
+        let changes = current
+            .iter()
+            .map(|(name, count)| {
+                let previous = previous.and_then(|previous| previous.get(name)).copied();
+                (name.clone(), CollectionChange { previous, current: *count })
+            })
+            .collect();
+        Self { version, previous_version, changes }
+    }
+}
+
+/// Sends notifications to the configured webhooks.
+#[derive(Clone)]
+pub struct Webhooks {
+    client: reqwest::Client,
+    hooks: Arc<[Webhook]>,
+}
+
+impl Webhooks {
+    pub fn new(hooks: Vec<Webhook>) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .unwrap_or_else(|_| reqwest::Client::new());
+        Self { client, hooks: hooks.into() }
+    }
+
+    /// Notifies all the webhooks about the update in the background.
+    pub fn notify(&self, update: DatasetUpdate) {
+        if self.hooks.is_empty() {
+            return;
+        }
+
+        let update = Arc::new(update);
+        for hook in self.hooks.iter().cloned() {
+            let client = self.client.clone();
+            let update = update.clone();
+            tokio::spawn(async move { deliver(&client, &hook, &update).await });
+        }
+    }
+}
+
+/// Posts the update to the webhook, retrying on failure.
+async fn deliver(client: &reqwest::Client, hook: &Webhook, update: &DatasetUpdate) {
+    let mut delay = RETRY_DELAY;
+    for attempt in 1..=MAX_ATTEMPTS {
+        let mut request =
+            client.post(&hook.url).header(EVENT_HEADER, DATASET_UPDATED_EVENT).json(update);
+        if let Some(token) = &hook.token {
+            request = request.bearer_auth(token);
+        }
+
+        match request.send().await.and_then(reqwest::Response::error_for_status) {
+            Ok(_) => {
+                tracing::info!(
+                    "Notified webhook `{}` about dataset `{}`",
+                    hook.url,
+                    update.version
+                );
+                return;
+            }
+            Err(err) => {
+                tracing::warn!(
+                    "Webhook `{}` failed (attempt {attempt}/{MAX_ATTEMPTS}): {err}",
+                    hook.url
+                );
+            }
+        }
+
+        if attempt < MAX_ATTEMPTS {
+            tokio::time::sleep(delay).await;
+            delay *= 2;
+        }
+    }
+    tracing::error!("Giving up notifying webhook `{}`", hook.url);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn update_summary() {
+        let previous = HashMap::from([("products".to_owned(), 10), ("library".to_owned(), 3)]);
+        let current = HashMap::from([("products".to_owned(), 12), ("organisations".to_owned(), 5)]);
+
+        let update = DatasetUpdate::new("2".into(), Some("1".into()), Some(&previous), &current);
+        assert_eq!(update.changes.len(), 2);
+        assert_eq!(
+            update.changes["products"],
+            CollectionChange { previous: Some(10), current: 12 }
+        );
+        assert_eq!(
+            update.changes["organisations"],
+            CollectionChange { previous: None, current: 5 }
+        );
+
+        let json = serde_json::to_value(&update).unwrap();
+        assert_eq!(json["previous_version"], "1");
+        assert!(json["changes"]["organisations"].get("previous").is_none());
+    }
+}
@@ -0,0 +1,36 @@
+/// Data structures for parsing the list of sustainability-related awards.
+pub mod data {
+    use serde::{Deserialize, Serialize};
+
+    /// Award considered relevant for sustainability.
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct Entry {
+        /// Wikidata ID of the award.
+        #[serde(
+            rename = "wiki",
+            deserialize_with = "sustainity_wikidata::data::Id::deserialize_from_string"
+        )]
+        pub wikidata_id: sustainity_wikidata::data::Id,
+
+        /// Name of the award.
+        #[serde(rename = "name")]
+        pub name: String,
+    }
+}
+
+/// Reader to loading the list of awards.
+pub mod reader {
+    use super::data::Entry;
+    use crate::errors::{IoOrSerdeError, MapSerde};
+
+    /// Loads the list of awards from a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if fails to read from `path` or parse the contents.
+    pub fn parse(path: &std::path::Path) -> Result<Vec<Entry>, IoOrSerdeError> {
+        let contents = std::fs::read_to_string(path)?;
+        let parsed: Vec<Entry> = serde_yaml::from_str(&contents).map_with_path(path)?;
+        Ok(parsed)
+    }
+}
@@ -32,7 +32,11 @@ pub mod data {
 /// Reader to loading `BCorp` data.
 pub mod reader {
     use super::data::Record;
-    use crate::errors::{IoOrSerdeError, MapSerde};
+    use crate::{
+        errors::{IoOrSerdeError, MapSerde},
+        rows::{self, RowError, RowMode},
+        spreadsheets::{self, data::SheetMapping},
+    };
 
     /// Loads the `BCorp` data from a file.
     ///
@@ -40,11 +44,35 @@ pub mod reader {
     ///
     /// Returns `Err` if fails to read from `path` or parse the contents.
     pub fn parse(path: &std::path::Path) -> Result<Vec<Record>, IoOrSerdeError> {
-        let mut parsed = Vec::<Record>::new();
+        Ok(parse_with(path, RowMode::Strict)?.0)
+    }
+
+    /// Loads the `BCorp` data from a file, handling malformed rows according to `mode`.
+    ///
+    /// Returns the records and the skipped malformed rows.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if fails to read from `path` or parse the contents.
+    pub fn parse_with(
+        path: &std::path::Path,
+        mode: RowMode,
+    ) -> Result<(Vec<Record>, Vec<RowError>), IoOrSerdeError> {
         let mut reader = csv::Reader::from_path(path).map_with_path(path)?;
-        for result in reader.deserialize() {
-            parsed.push(result.map_with_path(path)?);
-        }
-        Ok(parsed)
+        rows::deserialize_all(&mut reader, mode, path)
+    }
+
+    /// Loads the `BCorp` data from a spreadsheet, handling malformed rows according to `mode`.
+    ///
+    /// Returns the records and the skipped malformed rows.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if fails to read the workbook or parse the contents.
+    pub fn parse_sheet(
+        mapping: &SheetMapping,
+        mode: RowMode,
+    ) -> Result<(Vec<Record>, Vec<RowError>), IoOrSerdeError> {
+        spreadsheets::reader::deserialize_all(mapping, mode)
     }
 }
@@ -0,0 +1,65 @@
+/// Data structures for parsing the curated list of company controversies.
+pub mod data {
+    use serde::{Deserialize, Serialize};
+
+    /// Severity of a controversy.
+    #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Severity {
+        #[serde(rename = "low")]
+        Low,
+
+        #[serde(rename = "medium")]
+        Medium,
+
+        #[serde(rename = "high")]
+        High,
+    }
+
+    /// Controversy (e.g. greenwashing) a company was involved in.
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct Entry {
+        /// Company name.
+        #[serde(rename = "company")]
+        pub company_name: String,
+
+        /// Wikidata ID of the company.
+        #[serde(
+            rename = "wiki",
+            deserialize_with = "sustainity_wikidata::data::Id::deserialize_from_string"
+        )]
+        pub wikidata_id: sustainity_wikidata::data::Id,
+
+        /// Short description of the issue.
+        #[serde(rename = "issue")]
+        pub issue: String,
+
+        /// URL of the source documenting the issue.
+        #[serde(rename = "url")]
+        pub url: String,
+
+        /// Severity of the issue.
+        #[serde(rename = "severity")]
+        pub severity: Severity,
+
+        /// Date of the issue (`YYYY-MM-DD`, or just `YYYY` if only the year is known).
+        #[serde(rename = "date")]
+        pub date: String,
+    }
+}
+
+/// Reader to loading the list of controversies.
+pub mod reader {
+    use super::data::Entry;
+    use crate::errors::{IoOrSerdeError, MapSerde};
+
+    /// Loads the list of controversies from a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if fails to read from `path` or parse the contents.
+    pub fn parse(path: &std::path::Path) -> Result<Vec<Entry>, IoOrSerdeError> {
+        let contents = std::fs::read_to_string(path)?;
+        let parsed: Vec<Entry> = serde_yaml::from_str(&contents).map_with_path(path)?;
+        Ok(parsed)
+    }
+}
@@ -0,0 +1,44 @@
+/// Data structures for parsing the index of CSRD (ESRS) sustainability reports.
+pub mod data {
+    use serde::{Deserialize, Serialize};
+
+    /// Sustainability report published by a company.
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct Entry {
+        /// Company name.
+        #[serde(rename = "name")]
+        pub company_name: String,
+
+        /// Wikidata ID of the company.
+        #[serde(
+            rename = "wiki",
+            deserialize_with = "sustainity_wikidata::data::Id::deserialize_from_string"
+        )]
+        pub wikidata_id: sustainity_wikidata::data::Id,
+
+        /// Reporting year.
+        #[serde(rename = "year")]
+        pub year: u32,
+
+        /// URL of the report.
+        #[serde(rename = "url")]
+        pub url: String,
+    }
+}
+
+/// Reader to loading the index of CSRD reports.
+pub mod reader {
+    use super::data::Entry;
+    use crate::errors::{IoOrSerdeError, MapSerde};
+
+    /// Loads the index of CSRD reports from a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if fails to read from `path` or parse the contents.
+    pub fn parse(path: &std::path::Path) -> Result<Vec<Entry>, IoOrSerdeError> {
+        let contents = std::fs::read_to_string(path)?;
+        let parsed: Vec<Entry> = serde_yaml::from_str(&contents).map_with_path(path)?;
+        Ok(parsed)
+    }
+}
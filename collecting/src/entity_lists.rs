@@ -0,0 +1,69 @@
+/// Data structures for parsing lists of blocked and allowed entities.
+pub mod data {
+    use serde::{Deserialize, Serialize};
+
+    /// Entities excluded from the output.
+    #[derive(Serialize, Deserialize, Debug, Default)]
+    pub struct Blocklist {
+        /// Wikidata IDs of the blocked entities.
+        #[serde(
+            default,
+            deserialize_with = "sustainity_wikidata::data::deserialize_vec_id_from_vec_string"
+        )]
+        pub wiki: Vec<sustainity_wikidata::data::Id>,
+
+        /// Patterns of names of the blocked entities.
+        ///
+        /// The patterns are matched against normalised names; `*` matches any sequence of
+        /// characters.
+        #[serde(default)]
+        pub names: Vec<String>,
+    }
+
+    /// Entities included in the output even if the heuristics don't recognise them.
+    #[derive(Serialize, Deserialize, Debug, Default)]
+    pub struct Allowlist {
+        /// Wikidata IDs of entities to be treated as organisations.
+        #[serde(
+            default,
+            deserialize_with = "sustainity_wikidata::data::deserialize_vec_id_from_vec_string"
+        )]
+        pub organisations: Vec<sustainity_wikidata::data::Id>,
+
+        /// Wikidata IDs of entities to be treated as products.
+        #[serde(
+            default,
+            deserialize_with = "sustainity_wikidata::data::deserialize_vec_id_from_vec_string"
+        )]
+        pub products: Vec<sustainity_wikidata::data::Id>,
+    }
+
+    /// Lists of blocked and allowed entities.
+    #[derive(Serialize, Deserialize, Debug, Default)]
+    pub struct Lists {
+        /// Blocked entities.
+        #[serde(default)]
+        pub block: Blocklist,
+
+        /// Allowed entities.
+        #[serde(default)]
+        pub allow: Allowlist,
+    }
+}
+
+/// Reader to loading lists of blocked and allowed entities.
+pub mod reader {
+    use super::data::Lists;
+    use crate::errors::{IoOrSerdeError, MapSerde};
+
+    /// Loads the lists from a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if fails to read from `path` or parse the contents.
+    pub fn parse(path: &std::path::Path) -> Result<Lists, IoOrSerdeError> {
+        let contents = std::fs::read_to_string(path)?;
+        let parsed: Lists = serde_yaml::from_str(&contents).map_with_path(path)?;
+        Ok(parsed)
+    }
+}
@@ -17,6 +17,15 @@ pub enum IoOrSerdeError {
     #[error("In file `{1}`.\nYAML parsing error: {0}")]
     ReadYaml(serde_yaml::Error, std::path::PathBuf),
 
+    #[error("In file `{1}`.\nSpreadsheet reading error: {0}")]
+    ReadSpreadsheet(calamine::Error, std::path::PathBuf),
+
+    #[error("In file `{1}`.\nUnexpected spreadsheet layout: {0}")]
+    SpreadsheetLayout(String, std::path::PathBuf),
+
+    #[error("Invalid CSS selector `{0}`: {1}")]
+    InvalidSelector(String, String),
+
     #[error("CSV serialization error: {0}")]
     WriteCsv(csv::Error),
 
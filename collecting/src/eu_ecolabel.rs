@@ -49,32 +49,16 @@ pub mod data {
         pub vat_number: Option<String>,
         pub extract_date: String,
     }
-
-    impl Record {
-        /// Prepares VAT numebr for easy comparison.
-        ///
-        /// - ensures country prefix is always present
-        /// - the number does not contains special sympbols like " ", "." or "-"
-        #[must_use]
-        pub fn prepare_vat_number(&self) -> Option<String> {
-            if let Some(vat_number) = &self.vat_number {
-                let vat_number = vat_number.replace([' ', '.', '-'], "");
-                if vat_number.starts_with(&self.company_country) {
-                    Some(vat_number)
-                } else {
-                    Some(format!("{}{}", self.company_country, vat_number))
-                }
-            } else {
-                None
-            }
-        }
-    }
 }
 
 /// Reader to loading EU Ecolabel data.
 pub mod reader {
     use super::data::Record;
-    use crate::errors::{IoOrSerdeError, MapSerde};
+    use crate::{
+        errors::{IoOrSerdeError, MapSerde},
+        rows::{self, RowError, RowMode, RowRecovery},
+        spreadsheets::{self, data::SheetMapping},
+    };
 
     /// Loads the EU Ecolabel data from a file synchroneusly.
     ///
@@ -82,33 +66,81 @@ pub mod reader {
     ///
     /// Returns `Err` if fails to read from `path` or parse the contents.
     pub fn parse(path: &std::path::Path) -> Result<Vec<Record>, IoOrSerdeError> {
-        let mut parsed = Vec::<Record>::new();
+        Ok(parse_with(path, RowMode::Strict)?.0)
+    }
+
+    /// Loads the EU Ecolabel data from a file synchroneusly, handling malformed rows according to
+    /// `mode`.
+    ///
+    /// Returns the records and the skipped malformed rows.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if fails to read from `path` or parse the contents.
+    pub fn parse_with(
+        path: &std::path::Path,
+        mode: RowMode,
+    ) -> Result<(Vec<Record>, Vec<RowError>), IoOrSerdeError> {
         let mut reader =
             csv::ReaderBuilder::new().delimiter(b';').from_path(path).map_with_path(path)?;
-        for result in reader.deserialize() {
-            parsed.push(result.map_with_path(path)?);
-        }
-        Ok(parsed)
+        rows::deserialize_all(&mut reader, mode, path)
+    }
+
+    /// Loads the EU Ecolabel data from a spreadsheet synchroneusly, handling malformed rows
+    /// according to `mode`.
+    ///
+    /// Returns the records and the skipped malformed rows.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if fails to read the workbook or parse the contents.
+    pub fn parse_sheet(
+        mapping: &SheetMapping,
+        mode: RowMode,
+    ) -> Result<(Vec<Record>, Vec<RowError>), IoOrSerdeError> {
+        spreadsheets::reader::deserialize_all(mapping, mode)
     }
 
     /// Loads the EU Ecolabel data from a file asynchroneusly.
     ///
+    /// If `sheet` is given, the data is read from the mapped spreadsheet instead of `path`.
+    ///
+    /// Returns the number of read records and the skipped malformed rows.
+    ///
     /// # Errors
     ///
     /// Returns `Err` if fails to read from `path` or parse the contents.
-    pub async fn load<C, F>(path: &std::path::Path, callback: C) -> Result<usize, IoOrSerdeError>
+    pub async fn load<C, F>(
+        path: &std::path::Path,
+        sheet: Option<&SheetMapping>,
+        mode: RowMode,
+        callback: C,
+    ) -> Result<(usize, Vec<RowError>), IoOrSerdeError>
     where
         C: Fn(csv::StringRecord, csv::StringRecord) -> F,
         F: std::future::Future<Output = ()>,
     {
+        if let Some(sheet) = sheet {
+            // Rows which fail to deserialize are logged by the workers.
+            let (headers, records) = spreadsheets::reader::read_records(sheet)?;
+            let result = records.len();
+            for record in records {
+                callback(headers.clone(), record).await;
+            }
+            return Ok((result, Vec::new()));
+        }
+
         let mut result: usize = 0;
+        let mut recovery = RowRecovery::new(mode, path);
         let mut reader =
             csv::ReaderBuilder::new().delimiter(b';').from_path(path).map_with_path(path)?;
         let headers = reader.headers().map_with_path(path)?.clone();
         for record in reader.into_records() {
-            callback(headers.clone(), record.map_with_path(path)?).await;
-            result += 1;
+            if let Some(record) = recovery.handle(record)? {
+                callback(headers.clone(), record).await;
+                result += 1;
+            }
         }
-        Ok(result)
+        Ok((result, recovery.into_errors()))
     }
 }
@@ -0,0 +1,80 @@
+/// Data structures for parsing EUIPO trade mark open data.
+pub mod data {
+    use serde::{Deserialize, Serialize};
+
+    /// Statuses of trade marks which are registered or on their way to be registered.
+    const LIVE_STATUSES: &[&str] = &[
+        "Application filed",
+        "Application published",
+        "Registration pending",
+        "Opposition pending",
+        "Appeal pending",
+        "Registered",
+    ];
+
+    /// Trade mark in the EUIPO open data.
+    #[derive(Serialize, Deserialize, Debug, Clone)]
+    pub struct Record {
+        /// Application number of the trade mark.
+        #[serde(rename = "ApplicationNumber")]
+        pub application_number: String,
+
+        /// Verbal element of the mark (empty for purely figurative marks).
+        #[serde(rename = "MarkVerbalElementText", default)]
+        pub mark: String,
+
+        /// Name of the owner (or applicant if not registered yet).
+        #[serde(rename = "ApplicantName")]
+        pub owner_name: String,
+
+        /// Current status of the trade mark (e.g. "Registered" or "Expired").
+        #[serde(rename = "MarkCurrentStatusCode")]
+        pub status: String,
+
+        /// Nice classes of the goods and services the mark is registered for (comma-separated
+        /// numbers, e.g. "29, 30").
+        #[serde(rename = "NiceClassNumbers", default)]
+        pub nice_classes: String,
+    }
+
+    impl Record {
+        /// Checks if the trade mark is registered or pending registration.
+        #[must_use]
+        pub fn is_live(&self) -> bool {
+            LIVE_STATUSES.contains(&self.status.trim())
+        }
+
+        /// Returns the Nice classes of the mark, skipping malformed numbers.
+        #[must_use]
+        pub fn classes(&self) -> Vec<u32> {
+            self.nice_classes
+                .split([',', ';', ' '])
+                .filter_map(|class| class.trim().parse().ok())
+                .collect()
+        }
+    }
+}
+
+/// Reader to loading EUIPO trade mark open data.
+pub mod reader {
+    use super::data::Record;
+    use crate::{
+        errors::{IoOrSerdeError, MapSerde},
+        rows::{self, RowError, RowMode},
+    };
+
+    /// Loads the EUIPO trade mark data from a file, handling malformed rows according to `mode`.
+    ///
+    /// Returns the records and the skipped malformed rows.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if fails to read from `path` or parse the contents.
+    pub fn parse_with(
+        path: &std::path::Path,
+        mode: RowMode,
+    ) -> Result<(Vec<Record>, Vec<RowError>), IoOrSerdeError> {
+        let mut reader = csv::Reader::from_path(path).map_with_path(path)?;
+        rows::deserialize_all(&mut reader, mode, path)
+    }
+}
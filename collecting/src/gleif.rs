@@ -0,0 +1,48 @@
+/// Data structures for parsing the GLEIF golden copy of Legal Entity Identifiers.
+pub mod data {
+    use serde::{Deserialize, Serialize};
+
+    /// Legal entity in the GLEIF golden copy (LEI-CDF format).
+    #[derive(Serialize, Deserialize, Debug, Clone)]
+    pub struct Record {
+        /// Legal Entity Identifier.
+        #[serde(rename = "LEI")]
+        pub lei: String,
+
+        /// Legal name of the entity.
+        #[serde(rename = "Entity.LegalName", default)]
+        pub legal_name: String,
+
+        /// Jurisdiction the entity is registered in (e.g. "NL" or "US-DE").
+        #[serde(rename = "Entity.LegalJurisdiction", default)]
+        pub jurisdiction: String,
+
+        /// Number of the entity in its business register.
+        #[serde(rename = "Entity.RegistrationAuthority.RegistrationAuthorityEntityID", default)]
+        pub registration_number: Option<String>,
+    }
+}
+
+/// Reader to loading the GLEIF golden copy.
+pub mod reader {
+    use super::data::Record;
+    use crate::{
+        errors::{IoOrSerdeError, MapSerde},
+        rows::{self, RowError, RowMode},
+    };
+
+    /// Loads the GLEIF data from a file, handling malformed rows according to `mode`.
+    ///
+    /// Returns the records and the skipped malformed rows.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if fails to read from `path` or parse the contents.
+    pub fn parse_with(
+        path: &std::path::Path,
+        mode: RowMode,
+    ) -> Result<(Vec<Record>, Vec<RowError>), IoOrSerdeError> {
+        let mut reader = csv::Reader::from_path(path).map_with_path(path)?;
+        rows::deserialize_all(&mut reader, mode, path)
+    }
+}
@@ -0,0 +1,39 @@
+/// Data structures for parsing GS1 company prefix licence data.
+pub mod data {
+    use serde::{Deserialize, Serialize};
+
+    /// Licence of a GS1 company prefix.
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct Entry {
+        /// GS1 company prefix (4 to 12 digits).
+        pub prefix: String,
+
+        /// Name of the licensee.
+        #[serde(rename = "name")]
+        pub company_name: String,
+
+        /// Wikidata ID of the licensee.
+        #[serde(
+            rename = "wiki",
+            deserialize_with = "sustainity_wikidata::data::Id::deserialize_from_string"
+        )]
+        pub wikidata_id: sustainity_wikidata::data::Id,
+    }
+}
+
+/// Reader to loading GS1 company prefix licence data.
+pub mod reader {
+    use super::data::Entry;
+    use crate::errors::{IoOrSerdeError, MapSerde};
+
+    /// Loads the GS1 company prefix licence data from a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if fails to read from `path` or parse the contents.
+    pub fn parse(path: &std::path::Path) -> Result<Vec<Entry>, IoOrSerdeError> {
+        let contents = std::fs::read_to_string(path)?;
+        let parsed: Vec<Entry> = serde_yaml::from_str(&contents).map_with_path(path)?;
+        Ok(parsed)
+    }
+}
@@ -0,0 +1,33 @@
+/// Data structures for parsing life-cycle assessment (LCA) coefficients.
+pub mod data {
+    use serde::{Deserialize, Serialize};
+
+    /// Carbon footprint coefficient of a food category or ingredient (e.g. from Agribalyse).
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct Entry {
+        /// Open Food Facts category or ingredient tag, e.g. `en:butters`.
+        #[serde(rename = "tag")]
+        pub tag: String,
+
+        /// Emissions in kilograms of CO2 equivalent per kilogram of the product.
+        #[serde(rename = "kg_co2e_per_kg")]
+        pub kg_co2e_per_kg: f64,
+    }
+}
+
+/// Reader to loading LCA coefficients.
+pub mod reader {
+    use super::data::Entry;
+    use crate::errors::{IoOrSerdeError, MapSerde};
+
+    /// Loads the LCA coefficients from a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if fails to read from `path` or parse the contents.
+    pub fn parse(path: &std::path::Path) -> Result<Vec<Entry>, IoOrSerdeError> {
+        let contents = std::fs::read_to_string(path)?;
+        let parsed: Vec<Entry> = serde_yaml::from_str(&contents).map_with_path(path)?;
+        Ok(parsed)
+    }
+}
@@ -8,9 +8,23 @@ pub mod data {
 
 pub mod errors;
 
+pub mod awards;
 pub mod bcorp;
+pub mod controversies;
+pub mod csrd;
+pub mod entity_lists;
 pub mod eu_ecolabel;
+pub mod euipo;
 pub mod fashion_transparency_index;
+pub mod gleif;
+pub mod gs1;
+pub mod lca;
 pub mod open_food_facts;
+pub mod policy_pages;
+pub mod repairability;
+pub mod rows;
+pub mod scraping;
+pub mod spreadsheets;
 pub mod sustainity;
 pub mod tco;
+pub mod textile_exchange;
@@ -39,9 +39,39 @@ pub mod data {
         pub image_ingredients_small_url: String,
         pub image_nutrition_url: String,
         pub image_nutrition_small_url: String,
+
+        /// Packaging shapes, materials and recycling instructions, e.g. `en:glass,en:recycle`.
+        ///
+        /// Missing in older exports.
+        #[serde(default)]
+        pub packaging_tags: String,
     }
 
     impl Record {
+        /// Returns the bar code in the canonical GTIN form if it is a valid GTIN.
+        ///
+        /// Some products have very long or otherwise invalid bar codes. Those are probably some
+        /// internal codes, not GTINs.
+        #[must_use]
+        pub fn gtin(&self) -> Option<String> {
+            sustainity_models::gtin::normalize(&self.code).ok()
+        }
+
+        /// Extracts the name of the producer: the brand owner or, if not known, the first brand.
+        #[must_use]
+        pub fn extract_producer_name(&self) -> Option<String> {
+            let brand_owner = self.brand_owner.trim();
+            if brand_owner.is_empty() {
+                self.brands
+                    .split(',')
+                    .map(str::trim)
+                    .find(|brand| !brand.is_empty())
+                    .map(str::to_owned)
+            } else {
+                Some(brand_owner.to_owned())
+            }
+        }
+
         /// Extracts brand owner names and brand names.
         #[must_use]
         pub fn extract_brand_labels(&self) -> Vec<String> {
@@ -66,6 +96,109 @@ pub mod data {
                 self.countries_tags.split(',').map(String::from).collect()
             }
         }
+
+        /// Extracts category tags.
+        #[must_use]
+        pub fn extract_category_tags(&self) -> Vec<String> {
+            self.categories_tags
+                .split(',')
+                .map(str::trim)
+                .filter(|tag| !tag.is_empty())
+                .map(String::from)
+                .collect()
+        }
+
+        /// Extracts ingredient tags, in the order of decreasing amount.
+        #[must_use]
+        pub fn extract_ingredient_tags(&self) -> Vec<String> {
+            self.ingredients_tags
+                .split(',')
+                .map(str::trim)
+                .filter(|tag| !tag.is_empty())
+                .map(String::from)
+                .collect()
+        }
+
+        /// Extracts packaging tags.
+        #[must_use]
+        pub fn extract_packaging_tags(&self) -> Vec<String> {
+            self.packaging_tags
+                .split(',')
+                .map(str::trim)
+                .filter(|tag| !tag.is_empty())
+                .map(String::from)
+                .collect()
+        }
+
+        /// Extracts the packaging materials, sorted and without duplicates.
+        #[must_use]
+        pub fn extract_packaging_materials(&self) -> Vec<&'static str> {
+            let mut materials: Vec<&'static str> = self
+                .extract_packaging_tags()
+                .iter()
+                .filter_map(|tag| packaging_material(tag))
+                .collect();
+            materials.sort_unstable();
+            materials.dedup();
+            materials
+        }
+
+        /// Extracts the recyclability of the packaging.
+        ///
+        /// Returns `Some(false)` if any part is marked as non-recyclable, `Some(true)` if some part
+        /// is marked as recyclable and `None` if the recyclability is unknown.
+        #[must_use]
+        pub fn extract_packaging_recyclability(&self) -> Option<bool> {
+            let tags = self.extract_packaging_tags();
+            if tags.iter().any(|tag| NON_RECYCLABLE_TAGS.contains(&tag.as_str())) {
+                Some(false)
+            } else if tags.iter().any(|tag| RECYCLABLE_TAGS.contains(&tag.as_str())) {
+                Some(true)
+            } else {
+                None
+            }
+        }
+    }
+
+    /// Fragments of packaging tags and the materials they indicate.
+    const PACKAGING_MATERIALS: &[(&str, &str)] = &[
+        ("aluminium", "metal"),
+        ("cardboard", "paper"),
+        ("glass", "glass"),
+        ("hdpe", "plastic"),
+        ("ldpe", "plastic"),
+        ("metal", "metal"),
+        ("paper", "paper"),
+        ("pet", "plastic"),
+        ("plastic", "plastic"),
+        ("polyethylene", "plastic"),
+        ("polypropylene", "plastic"),
+        ("polystyrene", "plastic"),
+        ("steel", "metal"),
+        ("tin", "metal"),
+        ("wood", "wood"),
+    ];
+
+    /// Packaging tags marking recyclable packaging.
+    const RECYCLABLE_TAGS: &[&str] =
+        &["en:recyclable", "en:recyclable-packaging", "en:recycle", "en:to-recycle"];
+
+    /// Packaging tags marking non-recyclable packaging.
+    const NON_RECYCLABLE_TAGS: &[&str] =
+        &["en:discard", "en:non-recyclable", "en:non-recyclable-packaging", "en:not-recyclable"];
+
+    /// Returns the material indicated by a packaging tag.
+    ///
+    /// The tag is split into words (e.g. `en:pet-1-polyethylene-terephthalate`) and the first word
+    /// naming a known material wins.
+    fn packaging_material(tag: &str) -> Option<&'static str> {
+        let name = tag.split_once(':').map_or(tag, |(_, name)| name);
+        name.split('-').find_map(|word| {
+            PACKAGING_MATERIALS
+                .iter()
+                .find(|(fragment, _)| *fragment == word)
+                .map(|(_, material)| *material)
+        })
     }
 
     #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
@@ -87,12 +220,31 @@ pub mod data {
         pub regions: Option<Regions>,
         pub count: usize,
     }
+
+    /// Maps an Open Food Facts category tag to a Sustainity category.
+    ///
+    /// Tags without a category are not mapped yet; they are listed to guide the maintenance.
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    pub struct CategoryEntry {
+        /// Open Food Facts category tag, e.g. `en:breakfast-cereals`.
+        #[serde(rename = "tag")]
+        pub category_tag: String,
+
+        /// Sustainity category with levels separated by `/`, e.g. `food/cereals`.
+        pub category: Option<String>,
+
+        /// Number of products with the tag.
+        pub count: usize,
+    }
 }
 
 /// Reader for loading Open Food Facts data.
 pub mod reader {
-    use super::data::{CountryEntry, Record};
-    use crate::errors::{IoOrSerdeError, MapSerde};
+    use super::data::{CategoryEntry, CountryEntry, Record};
+    use crate::{
+        errors::{IoOrSerdeError, MapSerde},
+        rows::{RowError, RowMode, RowRecovery},
+    };
 
     /// Iterator over Open Food Facts CSV file records.
     pub struct Iter {
@@ -124,23 +276,38 @@ pub mod reader {
 
     /// Loads the Open Food Facts data from a file asynchroneusly.
     ///
+    /// If `limit` is given, at most that many records are read. The file is read with a buffer of
+    /// `buffer_size` bytes. Returns the number of read records and the skipped malformed rows.
+    ///
     /// # Errors
     ///
     /// Returns `Err` if fails to read from `path` or parse the contents.
-    pub async fn load<C, F>(path: std::path::PathBuf, callback: C) -> Result<usize, IoOrSerdeError>
+    pub async fn load<C, F>(
+        path: std::path::PathBuf,
+        limit: Option<usize>,
+        buffer_size: usize,
+        mode: RowMode,
+        callback: C,
+    ) -> Result<(usize, Vec<RowError>), IoOrSerdeError>
     where
         C: Fn(csv::StringRecord, csv::StringRecord) -> F,
         F: std::future::Future<Output = ()>,
     {
         let mut result: usize = 0;
-        let mut reader =
-            csv::ReaderBuilder::new().delimiter(b'\t').from_path(&path).map_with_path(&path)?;
+        let mut recovery = RowRecovery::new(mode, &path);
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(b'\t')
+            .buffer_capacity(buffer_size)
+            .from_path(&path)
+            .map_with_path(&path)?;
         let headers = reader.headers().map_with_path(&path)?.clone();
-        for record in reader.into_records() {
-            callback(headers.clone(), record.map_with_path(&path)?).await;
-            result += 1;
+        for record in reader.into_records().take(limit.unwrap_or(usize::MAX)) {
+            if let Some(record) = recovery.handle(record)? {
+                callback(headers.clone(), record).await;
+                result += 1;
+            }
         }
-        Ok(result)
+        Ok((result, recovery.into_errors()))
     }
 
     /// Loads the file with mapping from Open Food Facts sell country tags to Sustainity regions.
@@ -153,4 +320,15 @@ pub mod reader {
         let parsed: Vec<CountryEntry> = serde_yaml::from_str(&contents).map_with_path(path)?;
         Ok(parsed)
     }
+
+    /// Loads the file with mapping from Open Food Facts category tags to Sustainity categories.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if fails to read from `path` or parse the contents.
+    pub fn parse_categories(path: &std::path::Path) -> Result<Vec<CategoryEntry>, IoOrSerdeError> {
+        let contents = std::fs::read_to_string(path)?;
+        let parsed: Vec<CategoryEntry> = serde_yaml::from_str(&contents).map_with_path(path)?;
+        Ok(parsed)
+    }
 }
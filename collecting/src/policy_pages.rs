@@ -0,0 +1,69 @@
+/// Data structures for parsing the pre-crawled policy pages of companies.
+pub mod data {
+    use serde::{Deserialize, Serialize};
+
+    /// Kind of a policy page.
+    #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Kind {
+        /// General page about the sustainability efforts (e.g. "Our impact").
+        #[serde(rename = "sustainability")]
+        Sustainability,
+
+        /// Sustainability, impact or annual report.
+        #[serde(rename = "report")]
+        Report,
+
+        /// Policy document (e.g. code of conduct, supplier policy).
+        #[serde(rename = "policy")]
+        Policy,
+    }
+
+    /// Page found on a company website.
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct Page {
+        /// URL of the page.
+        #[serde(rename = "url")]
+        pub url: String,
+
+        /// Title of the page.
+        #[serde(rename = "title")]
+        pub title: String,
+
+        /// Kind of the page.
+        #[serde(rename = "kind")]
+        pub kind: Kind,
+    }
+
+    /// Sustainability and report pages found by crawling a single company domain.
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct Entry {
+        /// The crawled domain (e.g. `fairphone.com`).
+        #[serde(rename = "domain")]
+        pub domain: String,
+
+        /// Date of the crawl (`YYYY-MM-DD`).
+        #[serde(rename = "crawled", default)]
+        pub crawled: Option<String>,
+
+        /// Pages found on the domain.
+        #[serde(rename = "pages")]
+        pub pages: Vec<Page>,
+    }
+}
+
+/// Reader to loading the pre-crawled policy pages.
+pub mod reader {
+    use super::data::Entry;
+    use crate::errors::{IoOrSerdeError, MapSerde};
+
+    /// Loads the pre-crawled policy pages from a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if fails to read from `path` or parse the contents.
+    pub fn parse(path: &std::path::Path) -> Result<Vec<Entry>, IoOrSerdeError> {
+        let contents = std::fs::read_to_string(path)?;
+        let parsed: Vec<Entry> = serde_yaml::from_str(&contents).map_with_path(path)?;
+        Ok(parsed)
+    }
+}
@@ -0,0 +1,69 @@
+/// Data structures for parsing repairability data.
+pub mod data {
+    use serde::{Deserialize, Serialize};
+
+    /// Repairability of a product (e.g. as published by iFixit).
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct Entry {
+        /// Wikidata ID of the product.
+        #[serde(
+            rename = "wiki",
+            deserialize_with = "sustainity_wikidata::data::Id::deserialize_from_string"
+        )]
+        pub wikidata_id: sustainity_wikidata::data::Id,
+
+        /// Repairability score (from 0 to 10).
+        #[serde(rename = "score")]
+        pub score: Option<f64>,
+
+        /// Whether the battery can be replaced by the user.
+        #[serde(rename = "replaceable_battery")]
+        pub replaceable_battery: Option<bool>,
+    }
+
+    /// Wikidata item describing a kind of battery (used as "has characteristic" or "has part").
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct BatteryCharacteristic {
+        /// Wikidata ID of the characteristic.
+        #[serde(
+            rename = "wiki",
+            deserialize_with = "sustainity_wikidata::data::Id::deserialize_from_string"
+        )]
+        pub wikidata_id: sustainity_wikidata::data::Id,
+
+        /// Whether products with this characteristic have a user-replaceable battery.
+        #[serde(rename = "replaceable_battery")]
+        pub replaceable_battery: bool,
+    }
+}
+
+/// Reader to loading repairability data.
+pub mod reader {
+    use super::data::{BatteryCharacteristic, Entry};
+    use crate::errors::{IoOrSerdeError, MapSerde};
+
+    /// Loads the repairability data from a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if fails to read from `path` or parse the contents.
+    pub fn parse(path: &std::path::Path) -> Result<Vec<Entry>, IoOrSerdeError> {
+        let contents = std::fs::read_to_string(path)?;
+        let parsed: Vec<Entry> = serde_yaml::from_str(&contents).map_with_path(path)?;
+        Ok(parsed)
+    }
+
+    /// Loads the battery characteristics from a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if fails to read from `path` or parse the contents.
+    pub fn parse_battery_characteristics(
+        path: &std::path::Path,
+    ) -> Result<Vec<BatteryCharacteristic>, IoOrSerdeError> {
+        let contents = std::fs::read_to_string(path)?;
+        let parsed: Vec<BatteryCharacteristic> =
+            serde_yaml::from_str(&contents).map_with_path(path)?;
+        Ok(parsed)
+    }
+}
@@ -0,0 +1,102 @@
+//! Recovery from malformed rows in CSV sources.
+//!
+//! By default reading a CSV source fails on the first malformed row. In the lenient mode malformed
+//! rows are skipped and collected (with their line numbers and the reasons) for a report, so that
+//! a single broken row in a large export doesn't block the whole pipeline.
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{IoOrSerdeError, MapSerde};
+
+/// How to handle malformed rows.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RowMode {
+    /// Fail on the first malformed row.
+    #[default]
+    Strict,
+
+    /// Skip and collect malformed rows.
+    Lenient,
+}
+
+/// Malformed row skipped in the lenient mode.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RowError {
+    /// Line of the row in the file (if known).
+    pub line: Option<u64>,
+
+    /// Why the row could not be read.
+    pub reason: String,
+}
+
+/// Handles results of reading rows according to the row mode.
+#[derive(Debug)]
+pub struct RowRecovery<'a> {
+    mode: RowMode,
+    path: &'a std::path::Path,
+    errors: Vec<RowError>,
+}
+
+impl<'a> RowRecovery<'a> {
+    #[must_use]
+    pub fn new(mode: RowMode, path: &'a std::path::Path) -> Self {
+        Self { mode, path, errors: Vec::new() }
+    }
+
+    /// Handles a result of reading a row.
+    ///
+    /// Returns `Ok(None)` if the row is malformed and was skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the row is malformed and the mode is strict.
+    pub fn handle<T>(&mut self, result: csv::Result<T>) -> Result<Option<T>, IoOrSerdeError> {
+        match (result, self.mode) {
+            (Ok(value), _) => Ok(Some(value)),
+            (Err(err), RowMode::Lenient) if !is_io(&err) => {
+                let line = err.position().map(csv::Position::line);
+                self.errors.push(RowError { line, reason: err.to_string() });
+                Ok(None)
+            }
+            (Err(err), _) => Err(err).map_with_path(self.path),
+        }
+    }
+
+    /// Returns the collected malformed rows.
+    #[must_use]
+    pub fn into_errors(self) -> Vec<RowError> {
+        self.errors
+    }
+}
+
+/// Checks if the error was caused by reading the file rather than by its contents.
+///
+/// Such errors are not recoverable.
+fn is_io(error: &csv::Error) -> bool {
+    matches!(error.kind(), csv::ErrorKind::Io(_))
+}
+
+/// Reads all the rows, deserializing them into records.
+///
+/// # Errors
+///
+/// Returns `Err` if reading fails or a row is malformed and the mode is strict.
+pub fn deserialize_all<R, T>(
+    reader: &mut csv::Reader<R>,
+    mode: RowMode,
+    path: &std::path::Path,
+) -> Result<(Vec<T>, Vec<RowError>), IoOrSerdeError>
+where
+    R: std::io::Read,
+    T: serde::de::DeserializeOwned,
+{
+    let mut recovery = RowRecovery::new(mode, path);
+    let mut records = Vec::new();
+    for result in reader.deserialize() {
+        if let Some(record) = recovery.handle(result)? {
+            records.push(record);
+        }
+    }
+    Ok((records, recovery.into_errors()))
+}
@@ -0,0 +1,123 @@
+//! Extraction of tables from saved web pages.
+//!
+//! Some sources (e.g. lists of TCO certified brands or Fashion Transparency Index tables) are
+//! published only as web pages. To refresh them reproducibly, the pages are saved to disk and
+//! a table scraper configured with CSS selectors tells which elements form the rows and where in
+//! each row the values of the columns are.
+
+/// Data structures for parsing the scraper configurations.
+pub mod data {
+    use std::collections::BTreeMap;
+
+    use serde::{Deserialize, Serialize};
+
+    /// Where to find the value of a column in a row.
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+    pub struct Column {
+        /// CSS selector of the element relative to the row (the row itself if not given).
+        #[serde(rename = "selector", default)]
+        pub selector: Option<String>,
+
+        /// Attribute holding the value (e.g. `href`), the text of the element if not given.
+        #[serde(rename = "attribute", default)]
+        pub attribute: Option<String>,
+    }
+
+    /// Configuration of a scraper of a single table.
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+    pub struct TableScraper {
+        /// Path to the saved page (relative paths are relative to the configuration file).
+        #[serde(rename = "page")]
+        pub page: std::path::PathBuf,
+
+        /// CSS selector of the rows of the table.
+        #[serde(rename = "rows")]
+        pub rows: String,
+
+        /// Columns to extract from each row.
+        #[serde(rename = "columns")]
+        pub columns: BTreeMap<String, Column>,
+    }
+
+    /// Table scrapers of the sources (e.g. `tco`, `fashion_transparency_index`).
+    pub type TableScrapers = BTreeMap<String, TableScraper>;
+
+    /// Values of the columns of a single scraped row.
+    pub type Row = BTreeMap<String, String>;
+}
+
+/// Reader to loading scraper configurations and scraping the pages.
+pub mod reader {
+    use super::data::{Row, TableScraper, TableScrapers};
+    use crate::errors::{IoOrSerdeError, MapSerde};
+
+    /// Loads the table scrapers from a file.
+    ///
+    /// Paths to the pages are resolved relative to the directory of the file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if fails to read from `path` or parse the contents.
+    pub fn parse(path: &std::path::Path) -> Result<TableScrapers, IoOrSerdeError> {
+        let contents = std::fs::read_to_string(path)?;
+        let mut parsed: TableScrapers = serde_yaml::from_str(&contents).map_with_path(path)?;
+        if let Some(dir) = path.parent() {
+            for scraper in parsed.values_mut() {
+                scraper.page = dir.join(&scraper.page);
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// Parses a CSS selector.
+    fn selector(selector: &str) -> Result<scraper::Selector, IoOrSerdeError> {
+        scraper::Selector::parse(selector)
+            .map_err(|e| IoOrSerdeError::InvalidSelector(selector.to_owned(), e.to_string()))
+    }
+
+    /// Joins the text of the element collapsing the white space.
+    fn text(element: scraper::ElementRef<'_>) -> String {
+        element.text().flat_map(str::split_whitespace).collect::<Vec<_>>().join(" ")
+    }
+
+    /// Extracts the rows of the table from the page.
+    ///
+    /// Rows without any value (e.g. header rows when the columns select cells) are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if fails to read the page or the selectors are invalid.
+    pub fn scrape(table: &TableScraper) -> Result<Vec<Row>, IoOrSerdeError> {
+        let contents = std::fs::read_to_string(&table.page)?;
+        let document = scraper::Html::parse_document(&contents);
+
+        let rows = selector(&table.rows)?;
+        let mut columns = Vec::with_capacity(table.columns.len());
+        for (name, column) in &table.columns {
+            let cell = column.selector.as_deref().map(selector).transpose()?;
+            columns.push((name, cell, column.attribute.as_deref()));
+        }
+
+        let mut result = Vec::new();
+        for row in document.select(&rows) {
+            let mut values = Row::new();
+            for (name, cell, attribute) in &columns {
+                let element = match cell {
+                    Some(cell) => row.select(cell).next(),
+                    None => Some(row),
+                };
+                let value = element.map(|element| match attribute {
+                    Some(attribute) => {
+                        element.value().attr(attribute).unwrap_or_default().trim().to_owned()
+                    }
+                    None => text(element),
+                });
+                values.insert((*name).clone(), value.unwrap_or_default());
+            }
+            if values.values().any(|value| !value.is_empty()) {
+                result.push(values);
+            }
+        }
+        Ok(result)
+    }
+}
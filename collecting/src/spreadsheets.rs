@@ -0,0 +1,190 @@
+//! Reading of sources distributed as spreadsheets.
+//!
+//! Some sources publish their data only as Excel workbooks (`.xlsx`, `.xls`, `.ods`). Instead of
+//! converting them to CSV by hand, a sheet mapping tells which workbook and sheet hold the data,
+//! on which row the header is and how the columns are named. The rows are then converted to CSV
+//! records with the headers the CSV readers of the source expect, so the same data structures can
+//! be used for both formats.
+
+/// Data structures for parsing the sheet mappings.
+pub mod data {
+    use std::collections::BTreeMap;
+
+    use serde::{Deserialize, Serialize};
+
+    /// Location and layout of the data of a source in a workbook.
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+    pub struct SheetMapping {
+        /// Path to the workbook (relative paths are relative to the mapping file).
+        #[serde(rename = "workbook")]
+        pub workbook: std::path::PathBuf,
+
+        /// Name of the sheet (the first sheet if not given).
+        #[serde(rename = "sheet", default)]
+        pub sheet: Option<String>,
+
+        /// Index of the header row counted from zero (rows above it are ignored).
+        #[serde(rename = "header_row", default)]
+        pub header_row: usize,
+
+        /// Map from the column names expected by the source reader to the headers in the sheet.
+        ///
+        /// Columns not listed here keep their headers.
+        #[serde(rename = "columns", default)]
+        pub columns: BTreeMap<String, String>,
+    }
+
+    /// Sheet mappings of the sources (e.g. `bcorp`, `eu_ecolabel`).
+    pub type SheetMappings = BTreeMap<String, SheetMapping>;
+}
+
+/// Reader to loading sheet mappings and the spreadsheets they describe.
+pub mod reader {
+    use calamine::Reader;
+
+    use super::data::{SheetMapping, SheetMappings};
+    use crate::{
+        errors::{IoOrSerdeError, MapSerde},
+        rows::{RowError, RowMode, RowRecovery},
+    };
+
+    /// Serial number of 1970-01-01 in the Excel date system.
+    const UNIX_EPOCH_SERIAL: i64 = 25_569;
+
+    /// Loads the sheet mappings from a file.
+    ///
+    /// Paths to the workbooks are resolved relative to the directory of the file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if fails to read from `path` or parse the contents.
+    pub fn parse(path: &std::path::Path) -> Result<SheetMappings, IoOrSerdeError> {
+        let contents = std::fs::read_to_string(path)?;
+        let mut parsed: SheetMappings = serde_yaml::from_str(&contents).map_with_path(path)?;
+        if let Some(dir) = path.parent() {
+            for mapping in parsed.values_mut() {
+                mapping.workbook = dir.join(&mapping.workbook);
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// Formats a date given as an Excel serial number as `YYYY-MM-DD`.
+    ///
+    /// The time of the day is dropped.
+    #[allow(clippy::cast_possible_truncation)]
+    pub(super) fn format_date(serial: f64) -> String {
+        // Days to civil date conversion by Howard Hinnant.
+        let days = serial.floor() as i64 - UNIX_EPOCH_SERIAL + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_index = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+        let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+        format!("{year:04}-{month:02}-{day:02}")
+    }
+
+    /// Formats a cell the way it would appear in a CSV export.
+    fn format_cell(cell: &calamine::Data) -> String {
+        match cell {
+            calamine::Data::Empty => String::new(),
+            calamine::Data::DateTime(date) => format_date(date.as_f64()),
+            other => other.to_string(),
+        }
+    }
+
+    /// Reads the rows of the mapped sheet as CSV records.
+    ///
+    /// Returns the headers (renamed according to the mapping) and the records. The records have
+    /// their positions set to the lines of the rows in the sheet.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if fails to read the workbook or the sheet doesn't match the mapping.
+    pub fn read_records(
+        mapping: &SheetMapping,
+    ) -> Result<(csv::StringRecord, Vec<csv::StringRecord>), IoOrSerdeError> {
+        let path = &mapping.workbook;
+        let read_error = |err: calamine::Error| IoOrSerdeError::ReadSpreadsheet(err, path.clone());
+        let layout_error =
+            |message: String| IoOrSerdeError::SpreadsheetLayout(message, path.clone());
+
+        let mut workbook = calamine::open_workbook_auto(path).map_err(read_error)?;
+        let sheet = match &mapping.sheet {
+            Some(sheet) => sheet.clone(),
+            None => workbook
+                .sheet_names()
+                .first()
+                .cloned()
+                .ok_or_else(|| layout_error("the workbook has no sheets".to_owned()))?,
+        };
+        let range = workbook.worksheet_range(&sheet).map_err(read_error)?;
+        let first_row = range.start().map_or(0, |(row, _)| row as usize);
+
+        let mut rows = range.rows().skip(mapping.header_row);
+        let header_cells =
+            rows.next().ok_or_else(|| layout_error(format!("sheet `{sheet}` has no header")))?;
+
+        let mut headers: Vec<String> = header_cells.iter().map(format_cell).collect();
+        for (column, header) in &mapping.columns {
+            let position = headers.iter().position(|h| h.trim() == header.trim());
+            let Some(position) = position else {
+                return Err(layout_error(format!("sheet `{sheet}` has no column `{header}`")));
+            };
+            headers[position].clone_from(column);
+        }
+
+        let mut records = Vec::new();
+        for (index, row) in rows.enumerate() {
+            if row.iter().all(|cell| matches!(cell, calamine::Data::Empty)) {
+                continue;
+            }
+            let mut record: csv::StringRecord = row.iter().map(format_cell).collect();
+            let mut position = csv::Position::new();
+            position.set_line((first_row + mapping.header_row + index + 2) as u64);
+            record.set_position(Some(position));
+            records.push(record);
+        }
+
+        Ok((csv::StringRecord::from(headers), records))
+    }
+
+    /// Reads all the rows of the mapped sheet, deserializing them into records.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if reading fails or a row is malformed and the mode is strict.
+    pub fn deserialize_all<T>(
+        mapping: &SheetMapping,
+        mode: RowMode,
+    ) -> Result<(Vec<T>, Vec<RowError>), IoOrSerdeError>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let (headers, rows) = read_records(mapping)?;
+        let mut recovery = RowRecovery::new(mode, &mapping.workbook);
+        let mut records = Vec::new();
+        for row in rows {
+            if let Some(record) = recovery.handle(row.deserialize(Some(&headers)))? {
+                records.push(record);
+            }
+        }
+        Ok((records, recovery.into_errors()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::reader::format_date;
+
+    #[test]
+    fn date_formatting() {
+        assert_eq!(format_date(25_569.0), "1970-01-01");
+        assert_eq!(format_date(45_306.75), "2024-01-15");
+        assert_eq!(format_date(45_351.0), "2024-02-29");
+    }
+}
@@ -57,6 +57,16 @@ pub mod data {
 
         #[serde(rename = "summary")]
         pub summary: String,
+
+        /// Slug used in the URL of the article.
+        ///
+        /// Required, so that the published URLs don't change when the topic IDs are renamed.
+        #[serde(rename = "slug")]
+        pub slug: String,
+
+        /// Slugs used before the article was renamed, kept so that the old URLs still work.
+        #[serde(rename = "former_slugs", default, skip_serializing_if = "Vec::is_empty")]
+        pub former_slugs: Vec<String>,
     }
 
     /// Mapping connecting company or product name to curresponding Wikidata ID.
@@ -0,0 +1,88 @@
+/// Data structures for parsing Textile Exchange material standard data.
+pub mod data {
+    use serde::{Deserialize, Serialize};
+
+    /// Material standards of Textile Exchange.
+    #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum MaterialStandard {
+        /// Global Recycled Standard.
+        #[serde(rename = "grs")]
+        Grs,
+
+        /// Recycled Claim Standard.
+        #[serde(rename = "rcs")]
+        Rcs,
+
+        /// Organic Content Standard.
+        #[serde(rename = "ocs")]
+        Ocs,
+
+        /// Responsible Wool Standard.
+        #[serde(rename = "rws")]
+        Rws,
+
+        /// Responsible Alpaca Standard.
+        #[serde(rename = "ras")]
+        Ras,
+
+        /// Responsible Down Standard.
+        #[serde(rename = "rds")]
+        Rds,
+
+        /// Responsible Mohair Standard.
+        #[serde(rename = "rms")]
+        Rms,
+    }
+
+    impl MaterialStandard {
+        /// Returns the short lowercase code of the standard.
+        #[must_use]
+        pub fn code(self) -> &'static str {
+            match self {
+                Self::Grs => "grs",
+                Self::Rcs => "rcs",
+                Self::Ocs => "ocs",
+                Self::Rws => "rws",
+                Self::Ras => "ras",
+                Self::Rds => "rds",
+                Self::Rms => "rms",
+            }
+        }
+    }
+
+    /// Brand using materials certified to Textile Exchange standards.
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct Entry {
+        /// Brand name.
+        #[serde(rename = "name")]
+        pub brand_name: String,
+
+        /// Wikidata ID of the brand.
+        #[serde(
+            rename = "wiki",
+            deserialize_with = "sustainity_wikidata::data::Id::deserialize_from_string"
+        )]
+        pub wikidata_id: sustainity_wikidata::data::Id,
+
+        /// Standards the brand is certified to.
+        #[serde(rename = "standards")]
+        pub standards: Vec<MaterialStandard>,
+    }
+}
+
+/// Reader to loading Textile Exchange data.
+pub mod reader {
+    use super::data::Entry;
+    use crate::errors::{IoOrSerdeError, MapSerde};
+
+    /// Loads the Textile Exchange data from a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if fails to read from `path` or parse the contents.
+    pub fn parse(path: &std::path::Path) -> Result<Vec<Entry>, IoOrSerdeError> {
+        let contents = std::fs::read_to_string(path)?;
+        let parsed: Vec<Entry> = serde_yaml::from_str(&contents).map_with_path(path)?;
+        Ok(parsed)
+    }
+}
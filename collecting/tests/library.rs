@@ -0,0 +1,16 @@
+use sustainity_collecting::sustainity::data::{LibraryInfo, LibraryTopic};
+
+#[test]
+fn library_info_slugs() {
+    let info: LibraryInfo = serde_yaml::from_str(
+        "id: cert:bcorp\ntitle: B Corp\nsummary: Summary\nslug: bcorp\nformer_slugs: [b-corp]\n",
+    )
+    .unwrap();
+    assert_eq!(info.id, LibraryTopic::CertBcorp);
+    assert_eq!(info.slug, "bcorp");
+    assert_eq!(info.former_slugs, ["b-corp"]);
+
+    // Slugs are not derived from the IDs, which may be renamed.
+    let result = serde_yaml::from_str::<LibraryInfo>("id: cert:bcorp\ntitle: B Corp\nsummary: S\n");
+    assert!(result.is_err());
+}
@@ -0,0 +1,41 @@
+use sustainity_collecting::{
+    bcorp,
+    errors::IoOrSerdeError,
+    rows::{RowError, RowMode},
+};
+
+const BCORP_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/bcorp.csv");
+
+#[test]
+fn strict_mode_fails_on_malformed_row() {
+    let path = std::path::Path::new(BCORP_PATH);
+    let result = bcorp::reader::parse_with(path, RowMode::Strict);
+    assert!(matches!(result, Err(IoOrSerdeError::ReadCsv(_, ref p)) if p == path));
+}
+
+#[test]
+fn lenient_mode_skips_malformed_rows() {
+    let path = std::path::Path::new(BCORP_PATH);
+    let (records, errors) = bcorp::reader::parse_with(path, RowMode::Lenient).unwrap();
+
+    let ids: Vec<&str> = records.iter().map(|record| record.company_id.as_str()).collect();
+    assert_eq!(ids, ["fairphone", "patagonia"]);
+
+    let lines: Vec<Option<u64>> = errors.iter().map(|error| error.line).collect();
+    assert_eq!(lines, [Some(3), Some(4)]);
+    assert!(errors.iter().all(|error| !error.reason.is_empty()));
+}
+
+#[test]
+fn lenient_mode_fails_on_missing_file() {
+    let path = std::path::Path::new(BCORP_PATH).with_file_name("missing.csv");
+    assert!(bcorp::reader::parse_with(&path, RowMode::Lenient).is_err());
+}
+
+#[test]
+fn row_error_serde() {
+    let error = RowError { line: Some(3), reason: "bad".to_owned() };
+    let json = serde_json::to_string(&error).unwrap();
+    assert_eq!(json, r#"{"line":3,"reason":"bad"}"#);
+    assert_eq!(serde_json::from_str::<RowError>(&json).unwrap(), error);
+}
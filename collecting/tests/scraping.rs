@@ -0,0 +1,68 @@
+use sustainity_collecting::{
+    errors::IoOrSerdeError,
+    scraping::{self, data::TableScraper},
+};
+
+const SCRAPERS_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/scrapers.yaml");
+
+fn tco_scraper() -> TableScraper {
+    let mut scrapers = scraping::reader::parse(std::path::Path::new(SCRAPERS_PATH)).unwrap();
+    scrapers.remove("tco").unwrap()
+}
+
+fn column<'a>(rows: &'a [scraping::data::Row], name: &str) -> Vec<&'a str> {
+    rows.iter().map(|row| row[name].as_str()).collect()
+}
+
+#[test]
+fn page_paths_are_relative_to_config_file() {
+    let scraper = tco_scraper();
+    let dir = std::path::Path::new(SCRAPERS_PATH).parent().unwrap();
+    assert_eq!(scraper.page, dir.join("tco.html"));
+    assert_eq!(scraper.rows, "#brands tr");
+    assert_eq!(scraper.columns.len(), 3);
+}
+
+#[test]
+fn scraping_skips_rows_without_values() {
+    // The header row has only `th` cells, so none of the columns select anything in it.
+    let rows = scraping::reader::scrape(&tco_scraper()).unwrap();
+    assert_eq!(column(&rows, "name"), ["Fairphone B.V.", "Lenovo", "Dell"]);
+}
+
+#[test]
+fn scraping_reads_attributes_and_missing_values() {
+    let rows = scraping::reader::scrape(&tco_scraper()).unwrap();
+    assert_eq!(
+        column(&rows, "website"),
+        ["https://www.fairphone.com/", "", "https://www.dell.com/"]
+    );
+    assert_eq!(column(&rows, "product_type"), ["Smartphones", "Notebooks", ""]);
+}
+
+#[test]
+fn scraping_whole_rows() {
+    let mut scraper = tco_scraper();
+    scraper.rows = "#brands td.type".to_owned();
+    scraper.columns =
+        [("text".to_owned(), scraping::data::Column { selector: None, attribute: None })]
+            .into_iter()
+            .collect();
+    let rows = scraping::reader::scrape(&scraper).unwrap();
+    assert_eq!(column(&rows, "text"), ["Smartphones", "Notebooks"]);
+}
+
+#[test]
+fn invalid_selector_fails() {
+    let mut scraper = tco_scraper();
+    scraper.rows = "#brands tr[".to_owned();
+    let result = scraping::reader::scrape(&scraper);
+    assert!(matches!(result, Err(IoOrSerdeError::InvalidSelector(ref s, _)) if s == "#brands tr["));
+}
+
+#[test]
+fn missing_page_fails() {
+    let mut scraper = tco_scraper();
+    scraper.page.set_file_name("missing.html");
+    assert!(matches!(scraping::reader::scrape(&scraper), Err(IoOrSerdeError::Io(_))));
+}
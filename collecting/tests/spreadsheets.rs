@@ -0,0 +1,75 @@
+use sustainity_collecting::{
+    bcorp,
+    errors::IoOrSerdeError,
+    rows::RowMode,
+    spreadsheets::{self, data::SheetMapping},
+};
+
+const SHEETS_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/sheets.yaml");
+
+fn bcorp_mapping() -> SheetMapping {
+    let mut mappings = spreadsheets::reader::parse(std::path::Path::new(SHEETS_PATH)).unwrap();
+    mappings.remove("bcorp").unwrap()
+}
+
+#[test]
+fn mapping_paths_are_relative_to_mapping_file() {
+    let mapping = bcorp_mapping();
+    let dir = std::path::Path::new(SHEETS_PATH).parent().unwrap();
+    assert_eq!(mapping.workbook, dir.join("bcorp.xlsx"));
+    assert_eq!(mapping.sheet, None);
+    assert_eq!(mapping.header_row, 1);
+    assert_eq!(mapping.columns.get("company_id").map(String::as_str), Some("Company ID"));
+}
+
+#[test]
+fn records_have_renamed_headers_and_sheet_lines() {
+    let (headers, records) = spreadsheets::reader::read_records(&bcorp_mapping()).unwrap();
+    assert_eq!(
+        headers.iter().collect::<Vec<_>>(),
+        ["company_id", "company_name", "current_status", "website", "Certified on"]
+    );
+
+    // The empty row is skipped and dates are formatted like in a CSV export.
+    let ids: Vec<&str> = records.iter().map(|record| &record[0]).collect();
+    assert_eq!(ids, ["fairphone", "acme", "patagonia"]);
+    assert_eq!(&records[0][4], "2024-01-15");
+    assert_eq!(&records[1][4], "");
+
+    let lines: Vec<Option<u64>> =
+        records.iter().map(|record| record.position().map(csv::Position::line)).collect();
+    assert_eq!(lines, [Some(3), Some(5), Some(6)]);
+}
+
+#[test]
+fn lenient_sheet_reading_skips_malformed_rows() {
+    let (records, errors) = bcorp::reader::parse_sheet(&bcorp_mapping(), RowMode::Lenient).unwrap();
+
+    let ids: Vec<&str> = records.iter().map(|record| record.company_id.as_str()).collect();
+    assert_eq!(ids, ["fairphone", "patagonia"]);
+
+    let lines: Vec<Option<u64>> = errors.iter().map(|error| error.line).collect();
+    assert_eq!(lines, [Some(5)]);
+}
+
+#[test]
+fn strict_sheet_reading_fails_on_malformed_row() {
+    let result = bcorp::reader::parse_sheet(&bcorp_mapping(), RowMode::Strict);
+    assert!(matches!(result, Err(IoOrSerdeError::ReadCsv(..))));
+}
+
+#[test]
+fn missing_column_is_a_layout_error() {
+    let mut mapping = bcorp_mapping();
+    mapping.columns.insert("country".to_owned(), "Country".to_owned());
+    let result = spreadsheets::reader::read_records(&mapping);
+    assert!(matches!(result, Err(IoOrSerdeError::SpreadsheetLayout(..))));
+}
+
+#[test]
+fn missing_workbook_fails() {
+    let mut mapping = bcorp_mapping();
+    mapping.workbook.set_file_name("missing.xlsx");
+    let result = spreadsheets::reader::read_records(&mapping);
+    assert!(matches!(result, Err(IoOrSerdeError::ReadSpreadsheet(..))));
+}
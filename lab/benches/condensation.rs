@@ -0,0 +1,149 @@
+//! Benchmarks of the hot paths of the condensation.
+//!
+//! The inputs are derived from the miniature data set of the golden-data test, with each Wikidata
+//! entity copied many times under distinct IDs to get measurable timings. See `README.md` for how
+//! to compare the results against the baseline.
+
+use std::{path::Path, sync::Arc};
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use merge::Merge;
+
+use sustainity_lab::{
+    condensing::{CatalogerCollector, Collector, CondensingWikidataWorker},
+    config, parallel,
+    runners::WikidataWorker,
+    sources::{FullSources, Sourceable},
+};
+use sustainity_schema as schema;
+use sustainity_wikidata::data::Entity;
+
+/// Directory with the miniature input data.
+const DATA_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/condensing");
+
+/// Number of copies of each fixture entity.
+const COPIES: usize = 1000;
+
+/// Number of collectors merged together (as if produced by that many workers).
+const SHARDS: usize = 8;
+
+/// Reads the fixture Wikidata entities as raw lines, each copied `COPIES` times.
+fn load_lines() -> Vec<String> {
+    let path = Path::new(DATA_DIR).join("cache/wikidata.jsonl");
+    let contents = std::fs::read_to_string(path).expect("reading the Wikidata excerpt");
+    let mut lines = Vec::new();
+    for copy in 0..COPIES {
+        for line in contents.lines() {
+            let mut entity: serde_json::Value = serde_json::from_str(line).expect("valid JSON");
+            if entity["type"] == "item" {
+                let id = entity["id"].as_str().unwrap_or_default().to_owned();
+                entity["id"] = format!("{id}{copy:04}").into();
+            }
+            lines.push(entity.to_string());
+        }
+    }
+    lines
+}
+
+fn load_entities(lines: &[String]) -> Vec<Entity> {
+    lines.iter().map(|line| serde_json::from_str(line).expect("valid entity")).collect()
+}
+
+fn load_sources() -> Arc<FullSources> {
+    let data = Path::new(DATA_DIR);
+    let path = |name: &str| data.join(name).to_string_lossy().into_owned();
+    let config = config::SourcesConfig::new(&path("origin"), &path("source"), &path("cache"));
+    Arc::new(FullSources::load(&config).expect("loading the sources"))
+}
+
+/// Processes the entities with a fresh worker and returns the collected data.
+fn process(sources: &Arc<FullSources>, entities: Vec<Entity>) -> CatalogerCollector {
+    let (tx, rx) = parallel::bounded();
+    let mut worker = CondensingWikidataWorker::new(sources.clone());
+    futures::executor::block_on(async {
+        for entity in entities {
+            worker.process("", entity, tx.clone()).await.expect("processing an entity");
+        }
+        worker.finish(tx).await.expect("finishing the worker");
+        match rx.recv().await {
+            parallel::Recv::Value(collector) => collector,
+            parallel::Recv::Closed => panic!("the worker sent no collector"),
+        }
+    })
+}
+
+fn about() -> schema::AboutCataloger {
+    schema::AboutCataloger {
+        id: "wikidata".to_owned(),
+        name: "Wikidata".to_owned(),
+        description: None,
+        variant: schema::CatalogVariant::Database,
+        website: "https://www.wikidata.org/".to_owned(),
+    }
+}
+
+fn wikidata(c: &mut Criterion) {
+    let lines = load_lines();
+    let entities = load_entities(&lines);
+    let sources = load_sources();
+
+    let mut group = c.benchmark_group("wikidata");
+    group.throughput(Throughput::Elements(lines.len() as u64));
+    group.bench_function("deserialize", |b| {
+        b.iter(|| {
+            for line in &lines {
+                black_box(serde_json::from_str::<Entity>(line).expect("valid entity"));
+            }
+        });
+    });
+    group.bench_function("process", |b| {
+        b.iter_batched(
+            || entities.clone(),
+            |entities| black_box(process(&sources, entities)),
+            BatchSize::LargeInput,
+        );
+    });
+    group.finish();
+}
+
+fn collector(c: &mut Criterion) {
+    let entities = load_entities(&load_lines());
+    let sources = load_sources();
+    let shard_size = entities.len().div_ceil(SHARDS);
+    let collectors: Vec<CatalogerCollector> =
+        entities.chunks(shard_size).map(|shard| process(&sources, shard.to_vec())).collect();
+    let whole = process(&sources, entities);
+
+    let mut group = c.benchmark_group("collector");
+    group.bench_function("merge", |b| {
+        b.iter_batched(
+            || collectors.clone(),
+            |collectors| {
+                let mut result = CatalogerCollector::default();
+                for collector in collectors {
+                    result.merge(collector);
+                }
+                black_box(result)
+            },
+            BatchSize::LargeInput,
+        );
+    });
+
+    let dir = tempfile::tempdir().expect("creating a temporary directory");
+    let path = dir.path().join("wikidata.jsonl");
+    group.bench_function("finalize", |b| {
+        b.iter_batched(
+            || whole.clone(),
+            |collector| {
+                let mut substrate = collector.build_substrate(about());
+                substrate.sort();
+                substrate.save(&path).expect("saving the substrate");
+            },
+            BatchSize::LargeInput,
+        );
+    });
+    group.finish();
+}
+
+criterion_group!(benches, wikidata, collector);
+criterion_main!(benches);
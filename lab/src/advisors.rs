@@ -3,11 +3,51 @@
 use std::collections::{HashMap, HashSet};
 
 use sustainity_collecting::{
-    bcorp, eu_ecolabel, fashion_transparency_index, open_food_facts, sustainity, tco,
+    awards, bcorp, controversies, csrd, entity_lists, eu_ecolabel, euipo,
+    fashion_transparency_index, gleif, gs1, lca, open_food_facts, policy_pages, repairability,
+    rows::RowMode, spreadsheets, sustainity, tco, textile_exchange,
 };
-use sustainity_models::gather as models;
+use sustainity_models::{attributes, gather as models, gtin};
 
-use crate::{cache, convert, errors, utils, wikidata::WikiId};
+use crate::{
+    cache, commands::CsvSource, config, convert, errors, matching, rows, utils, wikidata::WikiId,
+};
+
+/// Holds the sheet mappings of sources distributed as spreadsheets.
+#[derive(Default)]
+pub struct SpreadsheetsAdvisor {
+    /// Map from source names to their sheet mappings.
+    mappings: spreadsheets::data::SheetMappings,
+}
+
+impl SpreadsheetsAdvisor {
+    /// Constructs a new `SpreadsheetsAdvisor`.
+    #[must_use]
+    pub fn new(mappings: spreadsheets::data::SheetMappings) -> Self {
+        Self { mappings }
+    }
+
+    /// Loads a new `SpreadsheetsAdvisor` from a file.
+    ///
+    /// The file is optional: without it all the sources are read from CSV files.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if fails to read from `path` or parse the contents.
+    pub fn load(path: &std::path::Path) -> Result<Self, errors::ProcessingError> {
+        if utils::is_path_ok(path) {
+            Ok(Self::new(spreadsheets::reader::parse(path)?))
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    /// Returns the sheet mapping of the source if it's read from a spreadsheet.
+    #[must_use]
+    pub fn get(&self, source: CsvSource) -> Option<&spreadsheets::data::SheetMapping> {
+        self.mappings.get(source.name())
+    }
+}
 
 /// Holds the information read from the `BCorp` data.
 pub struct BCorpAdvisor {
@@ -26,14 +66,22 @@ impl BCorpAdvisor {
         Self { domain_to_name }
     }
 
-    /// Loads a new `BCorpAdvisor` from a file.
+    /// Loads a new `BCorpAdvisor` from a file (or from a spreadsheet if `sheet` is given).
     ///
     /// # Errors
     ///
     /// Returns `Err` if fails to read from `path` or parse the contents.
-    pub fn load(path: &std::path::Path) -> Result<Self, errors::ProcessingError> {
-        if utils::is_path_ok(path) {
-            let data = bcorp::reader::parse(path)?;
+    pub fn load(
+        path: &std::path::Path,
+        sheet: Option<&spreadsheets::data::SheetMapping>,
+        mode: RowMode,
+    ) -> Result<Self, errors::ProcessingError> {
+        // Malformed rows are reported when condensing the data.
+        if let Some(sheet) = sheet {
+            let (data, _skipped) = bcorp::reader::parse_sheet(sheet, mode)?;
+            Ok(Self::new(&data))
+        } else if utils::is_path_ok(path) {
+            let (data, _skipped) = bcorp::reader::parse_with(path, mode)?;
             Ok(Self::new(&data))
         } else {
             log::warn!("Could not access {path:?}. BCorp data won't be loaded!");
@@ -42,14 +90,13 @@ impl BCorpAdvisor {
     }
 
     /// Checks if at least one of the passed domains corresponds to a `BCorp` company.
+    ///
+    /// Subdomains of the company domains match as well.
     #[must_use]
     pub fn has_domains(&self, domains: &HashSet<String>) -> bool {
-        for domain in domains {
-            if self.domain_to_name.contains_key(domain) {
-                return true;
-            }
-        }
-        false
+        domains.iter().any(|domain| {
+            utils::domain_with_parents(domain).any(|d| self.domain_to_name.contains_key(d))
+        })
     }
 }
 
@@ -85,58 +132,65 @@ pub struct EuEcolabelAdvisor {
 impl EuEcolabelAdvisor {
     /// Constructs a new `EuEcolabelAdvisor`.
     ///
-    /// # Errors
-    ///
-    /// Returns `Err` the records contain invalid data, e.g. VAT number.
+    /// VAT numbers with invalid format or check digits are reported but still used.
+    #[must_use]
     pub fn new(
         records: &[eu_ecolabel::data::Record],
         map: &[sustainity::data::NameMatching],
-    ) -> Result<Self, models::ParseIdError> {
-        let mut name_to_wiki = HashMap::<String, sustainity::data::Match>::new();
+    ) -> Self {
+        let mut name_to_wiki = matching::NameIndex::<sustainity::data::Match>::new();
         for entry in map {
             if let Some(wiki_match) = entry.matched() {
-                name_to_wiki.insert(entry.name.clone(), wiki_match);
+                name_to_wiki.insert(&entry.name, wiki_match);
             }
         }
 
         let mut vat_to_wiki = HashMap::<models::VatId, sustainity::data::Match>::new();
         for r in records {
             // We assume each company has only one VAT number.
-            if let Some(vat_number) = &r.prepare_vat_number() {
-                let vat_id: models::VatId = vat_number.try_into()?;
+            if let Some(vat_number) = &r.vat_number {
+                let vat_id = models::VatId::with_country(vat_number, &r.company_country);
+                if let Err(err) = vat_id.validate(true) {
+                    log::warn!("EU Ecolabel: {err}");
+                }
                 if let Some(wiki_match) = name_to_wiki.get(&r.product_or_service_name) {
                     vat_to_wiki.insert(vat_id, wiki_match.clone());
                 }
             }
         }
 
-        Ok(Self { vat_to_wiki })
+        Self { vat_to_wiki }
     }
 
-    /// Loads a new `EuEcolabelAdvisor` from a file.
+    /// Loads a new `EuEcolabelAdvisor` from a file (or from a spreadsheet if `sheet` is given).
     ///
     /// # Errors
     ///
-    /// Returns `Err` if fails to read from `path`, fails to parse the contents or the contents
-    /// contain invalid data.
+    /// Returns `Err` if fails to read from `path` or parse the contents.
     pub fn load(
         original_path: &std::path::Path,
+        sheet: Option<&spreadsheets::data::SheetMapping>,
         match_path: &std::path::Path,
+        mode: RowMode,
     ) -> Result<Self, errors::ProcessingError> {
-        if utils::is_path_ok(original_path) {
-            let data = eu_ecolabel::reader::parse(original_path)?;
+        if sheet.is_some() || utils::is_path_ok(original_path) {
+            // Malformed rows are reported when condensing the data.
+            let (data, _skipped) = match sheet {
+                Some(sheet) => eu_ecolabel::reader::parse_sheet(sheet, mode)?,
+                None => eu_ecolabel::reader::parse_with(original_path, mode)?,
+            };
             if utils::is_path_ok(match_path) {
                 let map = sustainity::reader::parse_id_map(match_path)?;
-                Ok(Self::new(&data, &map)?)
+                Ok(Self::new(&data, &map))
             } else {
                 log::warn!(
                     "Could not access {match_path:?}. Sustainity match data won't be loaded!"
                 );
-                Ok(Self::new(&[], &[])?)
+                Ok(Self::new(&[], &[]))
             }
         } else {
             log::warn!("Could not access {original_path:?}. EU Ecolabel data won't be loaded!");
-            Ok(Self::new(&[], &[])?)
+            Ok(Self::new(&[], &[]))
         }
     }
 
@@ -147,33 +201,139 @@ impl EuEcolabelAdvisor {
     }
 }
 
+/// Holds the information read from the EUIPO trade mark data.
+pub struct EuipoAdvisor {
+    /// Map from the marks (see `mark_key`) to the Wikidata IDs of their owners.
+    mark_to_owner: HashMap<String, WikiId>,
+}
+
+impl EuipoAdvisor {
+    /// Nice classes of foodstuffs and beverages (29 to 33), the goods listed in Open Food Facts.
+    pub const FOOD_CLASSES: &'static [u32] = &[29, 30, 31, 32, 33];
+
+    /// Constructs a new `EuipoAdvisor`.
+    ///
+    /// Only live marks registered for at least one of the given Nice classes are used, as the same
+    /// mark may belong to unrelated companies for other goods. The owners are matched by name using
+    /// the Sustainity matches. Marks registered by several different owners in those classes are
+    /// ambiguous and left out.
+    #[must_use]
+    pub fn new(
+        records: &[euipo::data::Record],
+        map: &[sustainity::data::NameMatching],
+        classes: &[u32],
+    ) -> Self {
+        let mut name_to_wiki = matching::NameIndex::<WikiId>::new();
+        for entry in map {
+            if let Some(wiki_match) = entry.matched() {
+                name_to_wiki.insert(&entry.name, wiki_match.wiki_id);
+            }
+        }
+
+        let mut mark_to_owners = HashMap::<String, HashSet<String>>::new();
+        for record in records.iter().filter(|record| record.is_live()) {
+            if !record.classes().iter().any(|class| classes.contains(class)) {
+                continue;
+            }
+            let mark = Self::mark_key(&record.mark);
+            if !mark.is_empty() {
+                let owner = matching::normalize(&record.owner_name);
+                mark_to_owners.entry(mark).or_default().insert(owner);
+            }
+        }
+
+        let mut mark_to_owner = HashMap::<String, WikiId>::new();
+        for (mark, owners) in mark_to_owners {
+            if owners.len() == 1 {
+                if let Some(owner) = owners.iter().next().and_then(|o| name_to_wiki.get(o)) {
+                    mark_to_owner.insert(mark, *owner);
+                }
+            }
+        }
+
+        Self { mark_to_owner }
+    }
+
+    /// Loads a new `EuipoAdvisor` from a file, keeping the marks of foodstuffs and beverages.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if fails to read from `path` or parse the contents.
+    pub fn load(
+        path: &std::path::Path,
+        match_path: &std::path::Path,
+        rows_config: &config::RowsConfig,
+    ) -> Result<Self, errors::ProcessingError> {
+        if utils::is_path_ok(path) {
+            let (data, skipped) = euipo::reader::parse_with(path, rows_config.mode)?;
+            rows::report("EUIPO", &skipped, rows_config)?;
+            if utils::is_path_ok(match_path) {
+                let map = sustainity::reader::parse_id_map(match_path)?;
+                Ok(Self::new(&data, &map, Self::FOOD_CLASSES))
+            } else {
+                log::warn!(
+                    "Could not access {match_path:?}. Sustainity match data won't be loaded!"
+                );
+                Ok(Self::new(&[], &[], &[]))
+            }
+        } else {
+            log::warn!("Could not access {path:?}. EUIPO data won't be loaded!");
+            Ok(Self::new(&[], &[], &[]))
+        }
+    }
+
+    /// Brings the mark to the form it's looked up by: lower-cased, with whitespace collapsed.
+    fn mark_key(mark: &str) -> String {
+        mark.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+    }
+
+    /// Returns the Wikidata ID of the owner of the mark.
+    ///
+    /// The mark must match exactly (apart from letter case and whitespace).
+    #[must_use]
+    pub fn owner(&self, mark: &str) -> Option<&WikiId> {
+        self.mark_to_owner.get(&Self::mark_key(mark))
+    }
+}
+
 /// Holds the information read from the Open Food Facts data.
 pub struct OpenFoodFactsAdvisor {
     /// Map from Open Food facts countries to Sustainity regionss.
     country_to_regions: HashMap<String, models::Regions>,
+
+    /// Map from Open Food Facts category tags to Sustainity categories (split into levels).
+    tag_to_category: HashMap<String, Vec<String>>,
 }
 
 impl OpenFoodFactsAdvisor {
     /// Constructs a new empty `OpenFoodFactsAdvisor`.
     #[must_use]
     pub fn new_empty() -> Self {
-        Self { country_to_regions: HashMap::new() }
+        Self { country_to_regions: HashMap::new(), tag_to_category: HashMap::new() }
     }
 
     /// Constructs a new `OpenFoodFactsAdvisor`.
     #[must_use]
-    pub fn new(country_to_regions: HashMap<String, models::Regions>) -> Self {
-        Self { country_to_regions }
+    pub fn new(
+        country_to_regions: HashMap<String, models::Regions>,
+        tag_to_category: HashMap<String, Vec<String>>,
+    ) -> Self {
+        Self { country_to_regions, tag_to_category }
     }
 
-    /// Loads a new `OpenFoodFactsdvisor` from a file.
+    /// Loads a new `OpenFoodFactsdvisor` from files.
+    ///
+    /// The category mapping is optional.
     ///
     /// # Errors
     ///
     /// Returns `Err` if fails to read from `path` or parse the contents.
-    pub fn load(path: &std::path::Path) -> Result<Self, errors::ProcessingError> {
-        if utils::is_path_ok(path) {
-            let data = open_food_facts::reader::parse_countries(path)?;
+    pub fn load(
+        countries_path: &std::path::Path,
+        categories_path: &std::path::Path,
+    ) -> Result<Self, errors::ProcessingError> {
+        if utils::is_path_ok(countries_path) {
+            let data = open_food_facts::reader::parse_countries(countries_path)?;
             let mut country_to_regions = HashMap::new();
             for entry in data {
                 if let Some(regions) = entry.regions {
@@ -181,9 +341,27 @@ impl OpenFoodFactsAdvisor {
                         .insert(entry.country_tag, convert::to_model_regions(&regions)?);
                 }
             }
-            Ok(Self::new(country_to_regions))
+
+            let mut tag_to_category = HashMap::new();
+            if utils::is_path_ok(categories_path) {
+                for entry in open_food_facts::reader::parse_categories(categories_path)? {
+                    if let Some(category) = entry.category {
+                        let levels = category.split('/').map(ToOwned::to_owned).collect();
+                        tag_to_category.insert(entry.category_tag, levels);
+                    }
+                }
+            } else {
+                log::warn!(
+                    "Could not access {categories_path:?}. \
+                     Open Food Facts categories won't be mapped!"
+                );
+            }
+
+            Ok(Self::new(country_to_regions, tag_to_category))
         } else {
-            log::warn!("Could not access {path:?}. Open Food Facts data won't be loaded!");
+            log::warn!(
+                "Could not access {countries_path:?}. Open Food Facts data won't be loaded!"
+            );
             Ok(Self::new_empty())
         }
     }
@@ -192,6 +370,12 @@ impl OpenFoodFactsAdvisor {
     pub fn get_countries(&self, country_tag: &str) -> Option<&models::Regions> {
         self.country_to_regions.get(country_tag)
     }
+
+    /// Returns the Sustainity category (split into levels) mapped to the category tag.
+    #[must_use]
+    pub fn get_category(&self, category_tag: &str) -> Option<&Vec<String>> {
+        self.tag_to_category.get(category_tag)
+    }
 }
 
 /// Holds the information read from the `BCorp` data.
@@ -234,6 +418,614 @@ impl TcoAdvisor {
     }
 }
 
+/// Holds the information read from the GS1 company prefix licence data.
+pub struct Gs1Advisor {
+    /// Map from GS1 company prefixes to Wikidata IDs of their licensees.
+    prefix_to_wiki: HashMap<String, WikiId>,
+}
+
+impl Gs1Advisor {
+    /// Constructs a new `Gs1Advisor`.
+    #[must_use]
+    pub fn new(entries: &[gs1::data::Entry]) -> Self {
+        Self {
+            prefix_to_wiki: entries
+                .iter()
+                .map(|entry| (entry.prefix.clone(), entry.wikidata_id))
+                .collect(),
+        }
+    }
+
+    /// Loads a new `Gs1Advisor` from a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if fails to read from `path` or parse the contents.
+    pub fn load(path: &std::path::Path) -> Result<Self, errors::ProcessingError> {
+        if utils::is_path_ok(path) {
+            let data = gs1::reader::parse(path)?;
+            Ok(Self::new(&data))
+        } else {
+            log::warn!("Could not access {path:?}. GS1 licence data won't be loaded!");
+            Ok(Self::new(&[]))
+        }
+    }
+
+    /// Returns the Wikidata ID of the company which licensed the GTIN.
+    ///
+    /// `gtin` is expected in the canonical form. The longest matching company prefix is used.
+    #[must_use]
+    pub fn licensee(&self, gtin: &str) -> Option<&WikiId> {
+        (gtin::MIN_COMPANY_PREFIX_LENGTH..=gtin::MAX_COMPANY_PREFIX_LENGTH)
+            .rev()
+            .filter_map(|length| gtin::company_prefix(gtin, length))
+            .find_map(|prefix| self.prefix_to_wiki.get(prefix))
+    }
+}
+
+/// Holds the information read from the GLEIF golden copy.
+pub struct GleifAdvisor {
+    /// Map from Legal Entity Identifiers to the registration numbers of the entities.
+    lei_to_registration_number: HashMap<String, String>,
+}
+
+impl GleifAdvisor {
+    /// Constructs a new `GleifAdvisor`.
+    ///
+    /// The identifiers are brought to their canonical form (upper-case, alphanumeric only).
+    #[must_use]
+    pub fn new(records: &[gleif::data::Record]) -> Self {
+        let lei_to_registration_number = records
+            .iter()
+            .filter_map(|record| {
+                let number = canonical_identifier(record.registration_number.as_deref()?);
+                (!number.is_empty()).then(|| (canonical_identifier(&record.lei), number))
+            })
+            .collect();
+        Self { lei_to_registration_number }
+    }
+
+    /// Loads a new `GleifAdvisor` from a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if fails to read from `path` or parse the contents.
+    pub fn load(
+        path: &std::path::Path,
+        rows_config: &config::RowsConfig,
+    ) -> Result<Self, errors::ProcessingError> {
+        if utils::is_path_ok(path) {
+            let (data, skipped) = gleif::reader::parse_with(path, rows_config.mode)?;
+            rows::report("GLEIF", &skipped, rows_config)?;
+            Ok(Self::new(&data))
+        } else {
+            log::warn!("Could not access {path:?}. GLEIF data won't be loaded!");
+            Ok(Self::new(&[]))
+        }
+    }
+
+    /// Returns the registration numbers of the entities with the given (canonical) LEIs.
+    #[must_use]
+    pub fn registration_numbers(&self, leis: &[String]) -> Vec<String> {
+        leis.iter().filter_map(|lei| self.lei_to_registration_number.get(lei)).cloned().collect()
+    }
+}
+
+/// Brings an organisation identifier to its canonical form (as expected by the backend).
+fn canonical_identifier(id: &str) -> String {
+    id.chars().filter(char::is_ascii_alphanumeric).map(|c| c.to_ascii_uppercase()).collect()
+}
+
+/// Holds the list of sustainability-related awards.
+pub struct AwardsAdvisor {
+    /// Map from Wikidata IDs of the awards to their names.
+    awards: HashMap<WikiId, String>,
+}
+
+impl AwardsAdvisor {
+    /// Constructs a new `AwardsAdvisor`.
+    #[must_use]
+    pub fn new(entries: &[awards::data::Entry]) -> Self {
+        Self {
+            awards: entries.iter().map(|entry| (entry.wikidata_id, entry.name.clone())).collect(),
+        }
+    }
+
+    /// Loads a new `AwardsAdvisor` from a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if fails to read from `path` or parse the contents.
+    pub fn load(path: &std::path::Path) -> Result<Self, errors::ProcessingError> {
+        if utils::is_path_ok(path) {
+            let data = awards::reader::parse(path)?;
+            Ok(Self::new(&data))
+        } else {
+            log::warn!("Could not access {path:?}. Awards won't be extracted!");
+            Ok(Self::new(&[]))
+        }
+    }
+
+    /// Selects the sustainability-related awards among the passed ones (with the dates they were
+    /// received, if known).
+    #[must_use]
+    pub fn recognitions(&self, awards: &[(WikiId, Option<String>)]) -> Vec<models::Recognition> {
+        awards
+            .iter()
+            .filter_map(|(id, date)| {
+                self.awards.get(id).map(|name| models::Recognition {
+                    wiki_id: models::WikiId::from(*id).to_canonical_string(),
+                    name: name.clone(),
+                    date: date.clone(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Holds the information read from the index of CSRD sustainability reports.
+pub struct CsrdAdvisor {
+    /// Map from Wikidata IDs of companies to their latest reports (year and URL).
+    reports: HashMap<WikiId, (u32, String)>,
+}
+
+impl CsrdAdvisor {
+    /// Constructs a new `CsrdAdvisor`.
+    #[must_use]
+    pub fn new(entries: &[csrd::data::Entry]) -> Self {
+        let mut reports = HashMap::<WikiId, (u32, String)>::new();
+        for entry in entries {
+            let report = (entry.year, entry.url.clone());
+            reports
+                .entry(entry.wikidata_id)
+                .and_modify(|latest| {
+                    if report.0 > latest.0 {
+                        *latest = report.clone();
+                    }
+                })
+                .or_insert(report);
+        }
+        Self { reports }
+    }
+
+    /// Loads a new `CsrdAdvisor` from a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if fails to read from `path` or parse the contents.
+    pub fn load(path: &std::path::Path) -> Result<Self, errors::ProcessingError> {
+        if utils::is_path_ok(path) {
+            let data = csrd::reader::parse(path)?;
+            Ok(Self::new(&data))
+        } else {
+            log::warn!("Could not access {path:?}. CSRD report index won't be loaded!");
+            Ok(Self::new(&[]))
+        }
+    }
+
+    /// Returns attributes describing the latest report of the company.
+    #[must_use]
+    pub fn get_attributes(&self, company_id: &WikiId) -> Option<models::Attributes> {
+        self.reports.get(company_id).map(|(year, url)| {
+            models::Attributes::from([
+                (attributes::CSRD_REPORT_URL.to_owned(), models::AttributeValue::Text(url.clone())),
+                (
+                    attributes::CSRD_REPORT_YEAR.to_owned(),
+                    models::AttributeValue::Number(f64::from(*year)),
+                ),
+            ])
+        })
+    }
+}
+
+/// Holds the information read from the repairability data.
+pub struct RepairabilityAdvisor {
+    /// Map from Wikidata IDs of products to their repairability attributes.
+    products: HashMap<WikiId, models::Attributes>,
+
+    /// Map from Wikidata IDs of battery characteristics to battery replaceability.
+    battery_characteristics: HashMap<WikiId, bool>,
+}
+
+impl RepairabilityAdvisor {
+    /// Constructs a new `RepairabilityAdvisor`.
+    #[must_use]
+    pub fn new(
+        entries: &[repairability::data::Entry],
+        battery_characteristics: &[repairability::data::BatteryCharacteristic],
+    ) -> Self {
+        let mut products = HashMap::<WikiId, models::Attributes>::new();
+        for entry in entries {
+            let product = products.entry(entry.wikidata_id).or_default();
+            if let Some(score) = entry.score {
+                product.insert(
+                    attributes::REPAIRABILITY_SCORE.to_owned(),
+                    models::AttributeValue::Number(score),
+                );
+            }
+            if let Some(replaceable) = entry.replaceable_battery {
+                product.insert(
+                    attributes::REPLACEABLE_BATTERY.to_owned(),
+                    models::AttributeValue::Flag(replaceable),
+                );
+            }
+        }
+        let battery_characteristics = battery_characteristics
+            .iter()
+            .map(|entry| (entry.wikidata_id, entry.replaceable_battery))
+            .collect();
+        Self { products, battery_characteristics }
+    }
+
+    /// Loads a new `RepairabilityAdvisor` from files.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if fails to read from the paths or parse the contents.
+    pub fn load(
+        path: &std::path::Path,
+        battery_characteristics_path: &std::path::Path,
+    ) -> Result<Self, errors::ProcessingError> {
+        let data = if utils::is_path_ok(path) {
+            repairability::reader::parse(path)?
+        } else {
+            log::warn!("Could not access {path:?}. Repairability data won't be loaded!");
+            Vec::new()
+        };
+        let characteristics = if utils::is_path_ok(battery_characteristics_path) {
+            repairability::reader::parse_battery_characteristics(battery_characteristics_path)?
+        } else {
+            log::warn!(
+                "Could not access {battery_characteristics_path:?}. \
+                 Battery characteristics won't be loaded!"
+            );
+            Vec::new()
+        };
+        Ok(Self::new(&data, &characteristics))
+    }
+
+    /// Returns the battery attributes implied by the Wikidata characteristics of a product.
+    ///
+    /// Conflicting characteristics are ignored.
+    #[must_use]
+    pub fn get_battery_attributes(&self, characteristics: &[WikiId]) -> models::Attributes {
+        let mut replaceable = characteristics
+            .iter()
+            .filter_map(|id| self.battery_characteristics.get(id).copied())
+            .collect::<HashSet<bool>>()
+            .into_iter();
+        let mut result = models::Attributes::new();
+        if let (Some(replaceable), None) = (replaceable.next(), replaceable.next()) {
+            result.insert(
+                attributes::REPLACEABLE_BATTERY.to_owned(),
+                models::AttributeValue::Flag(replaceable),
+            );
+        }
+        result
+    }
+
+    /// Returns the repairability attributes of the product.
+    #[must_use]
+    pub fn get_attributes(&self, product_id: &WikiId) -> Option<&models::Attributes> {
+        self.products.get(product_id)
+    }
+}
+
+/// Holds the curated list of company controversies.
+pub struct ControversiesAdvisor {
+    /// Map from Wikidata IDs of companies to their controversies.
+    companies: HashMap<WikiId, Vec<models::Controversy>>,
+}
+
+impl ControversiesAdvisor {
+    /// Constructs a new `ControversiesAdvisor`.
+    #[must_use]
+    pub fn new(entries: &[controversies::data::Entry]) -> Self {
+        let mut companies = HashMap::<WikiId, Vec<models::Controversy>>::new();
+        for entry in entries {
+            companies.entry(entry.wikidata_id).or_default().push(models::Controversy {
+                date: entry.date.clone(),
+                issue: entry.issue.clone(),
+                severity: match entry.severity {
+                    controversies::data::Severity::Low => models::ControversySeverity::Low,
+                    controversies::data::Severity::Medium => models::ControversySeverity::Medium,
+                    controversies::data::Severity::High => models::ControversySeverity::High,
+                },
+                url: entry.url.clone(),
+            });
+        }
+        Self { companies }
+    }
+
+    /// Loads a new `ControversiesAdvisor` from a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if fails to read from `path` or parse the contents.
+    pub fn load(path: &std::path::Path) -> Result<Self, errors::ProcessingError> {
+        if utils::is_path_ok(path) {
+            let data = controversies::reader::parse(path)?;
+            Ok(Self::new(&data))
+        } else {
+            log::warn!("Could not access {path:?}. Controversies won't be loaded!");
+            Ok(Self::new(&[]))
+        }
+    }
+
+    /// Returns the controversies of the company.
+    #[must_use]
+    pub fn controversies(&self, company_id: &WikiId) -> Vec<models::Controversy> {
+        self.companies.get(company_id).cloned().unwrap_or_default()
+    }
+}
+
+/// Holds the pre-crawled sustainability and report pages of companies.
+pub struct PolicyPagesAdvisor {
+    /// Map from registrable domains to the pages found on them.
+    domains: HashMap<String, Vec<models::PolicyPage>>,
+}
+
+impl PolicyPagesAdvisor {
+    /// Constructs a new `PolicyPagesAdvisor`.
+    ///
+    /// Pages outside of the crawled domain (e.g. reports hosted by third parties) are skipped, as
+    /// they cannot be attributed to the company.
+    #[must_use]
+    pub fn new(entries: &[policy_pages::data::Entry]) -> Self {
+        let mut domains = HashMap::<String, Vec<models::PolicyPage>>::new();
+        for entry in entries {
+            let domain = utils::extract_domain_from_url(&entry.domain);
+            let mut skipped = 0;
+            for page in &entry.pages {
+                if utils::extract_domain_from_url(&page.url) != domain {
+                    skipped += 1;
+                    continue;
+                }
+                domains.entry(domain.clone()).or_default().push(models::PolicyPage {
+                    kind: match page.kind {
+                        policy_pages::data::Kind::Sustainability => {
+                            models::PolicyPageKind::Sustainability
+                        }
+                        policy_pages::data::Kind::Report => models::PolicyPageKind::Report,
+                        policy_pages::data::Kind::Policy => models::PolicyPageKind::Policy,
+                    },
+                    title: page.title.clone(),
+                    url: page.url.clone(),
+                });
+            }
+            if skipped > 0 {
+                log::warn!("Skipped {skipped} policy pages outside of {}", entry.domain);
+            }
+        }
+        Self { domains }
+    }
+
+    /// Loads a new `PolicyPagesAdvisor` from a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if fails to read from `path` or parse the contents.
+    pub fn load(path: &std::path::Path) -> Result<Self, errors::ProcessingError> {
+        if utils::is_path_ok(path) {
+            let data = policy_pages::reader::parse(path)?;
+            Ok(Self::new(&data))
+        } else {
+            log::warn!("Could not access {path:?}. Policy pages won't be loaded!");
+            Ok(Self::new(&[]))
+        }
+    }
+
+    /// Returns the pages found on the given domains.
+    ///
+    /// The domains are expected to be extracted from the company's known websites, so only the
+    /// pages crawled from the company's own domains are returned.
+    #[must_use]
+    pub fn pages(&self, domains: &HashSet<String>) -> Vec<models::PolicyPage> {
+        domains.iter().filter_map(|domain| self.domains.get(domain)).flatten().cloned().collect()
+    }
+}
+
+/// Holds the information read from the Textile Exchange data.
+pub struct TextileExchangeAdvisor {
+    /// Map from Wikidata IDs of brands to their material standard flags.
+    brands: HashMap<WikiId, models::Attributes>,
+}
+
+impl TextileExchangeAdvisor {
+    /// Constructs a new `TextileExchangeAdvisor`.
+    #[must_use]
+    pub fn new(entries: &[textile_exchange::data::Entry]) -> Self {
+        let mut brands = HashMap::<WikiId, models::Attributes>::new();
+        for entry in entries {
+            let brand = brands.entry(entry.wikidata_id).or_default();
+            for standard in &entry.standards {
+                brand.insert(
+                    attributes::material_standard(standard.code()),
+                    models::AttributeValue::Flag(true),
+                );
+            }
+        }
+        Self { brands }
+    }
+
+    /// Loads a new `TextileExchangeAdvisor` from a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if fails to read from `path` or parse the contents.
+    pub fn load(path: &std::path::Path) -> Result<Self, errors::ProcessingError> {
+        if utils::is_path_ok(path) {
+            let data = textile_exchange::reader::parse(path)?;
+            Ok(Self::new(&data))
+        } else {
+            log::warn!("Could not access {path:?}. Textile Exchange data won't be loaded!");
+            Ok(Self::new(&[]))
+        }
+    }
+
+    /// Returns the material standard flags of the brand.
+    #[must_use]
+    pub fn get_attributes(&self, brand_id: &WikiId) -> Option<&models::Attributes> {
+        self.brands.get(brand_id)
+    }
+}
+
+/// Minimal share of the (order-weighted) ingredients with known coefficients needed to estimate
+/// the carbon footprint from the ingredients.
+const MIN_INGREDIENT_COVERAGE: f64 = 0.6;
+
+/// Holds the LCA coefficients used to estimate carbon footprints of food products.
+pub struct LcaAdvisor {
+    /// Map from Open Food Facts category and ingredient tags to kg CO2e per kg.
+    coefficients: HashMap<String, f64>,
+}
+
+impl LcaAdvisor {
+    /// Constructs a new `LcaAdvisor`.
+    #[must_use]
+    pub fn new(entries: &[lca::data::Entry]) -> Self {
+        let coefficients =
+            entries.iter().map(|entry| (entry.tag.clone(), entry.kg_co2e_per_kg)).collect();
+        Self { coefficients }
+    }
+
+    /// Loads a new `LcaAdvisor` from a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if fails to read from `path` or parse the contents.
+    pub fn load(path: &std::path::Path) -> Result<Self, errors::ProcessingError> {
+        if utils::is_path_ok(path) {
+            let data = lca::reader::parse(path)?;
+            Ok(Self::new(&data))
+        } else {
+            log::warn!("Could not access {path:?}. Carbon footprints won't be estimated!");
+            Ok(Self::new(&[]))
+        }
+    }
+
+    /// Estimates the carbon footprint of a product per 100 g.
+    ///
+    /// The most specific category (the last one in the Open Food Facts order) with a known
+    /// coefficient is used. If no category is known, the coefficients of the ingredients are
+    /// averaged, weighted by their order, as ingredients are listed by decreasing amount.
+    ///
+    /// Returns the attributes with the estimate, or `None` if nothing is known or if the known
+    /// ingredients cover less than `MIN_INGREDIENT_COVERAGE` of the weighted ingredients.
+    #[must_use]
+    pub fn estimate(
+        &self,
+        categories: &[String],
+        ingredients: &[String],
+    ) -> Option<models::Attributes> {
+        let (per_kg, basis) = if let Some((tag, per_kg)) = categories
+            .iter()
+            .rev()
+            .find_map(|tag| self.coefficients.get(tag).map(|per_kg| (tag, *per_kg)))
+        {
+            (per_kg, tag.clone())
+        } else {
+            let mut total = 0.0;
+            let mut known_weight = 0.0;
+            let mut total_weight = 0.0;
+            for (position, tag) in ingredients.iter().enumerate() {
+                #[allow(clippy::cast_precision_loss)]
+                let weight = 1.0 / (position + 1) as f64;
+                total_weight += weight;
+                if let Some(per_kg) = self.coefficients.get(tag) {
+                    total += per_kg * weight;
+                    known_weight += weight;
+                }
+            }
+            if known_weight <= 0.0 || known_weight / total_weight < MIN_INGREDIENT_COVERAGE {
+                return None;
+            }
+            (total / known_weight, "ingredients".to_owned())
+        };
+
+        Some(models::Attributes::from([
+            (
+                attributes::CARBON_FOOTPRINT.to_owned(),
+                models::AttributeValue::Number(per_kg / 10.0),
+            ),
+            (
+                attributes::CARBON_FOOTPRINT_PROVENANCE.to_owned(),
+                models::AttributeValue::Text(attributes::PROVENANCE_ESTIMATE.to_owned()),
+            ),
+            (attributes::CARBON_FOOTPRINT_BASIS.to_owned(), models::AttributeValue::Text(basis)),
+        ]))
+    }
+}
+
+/// Holds the lists of blocked and allowed entities.
+pub struct EntityListsAdvisor {
+    /// Wikidata IDs of blocked entities.
+    blocked_ids: HashSet<WikiId>,
+
+    /// Patterns of names of blocked entities.
+    blocked_names: Vec<matching::NamePattern>,
+
+    /// Wikidata IDs of entities to be treated as organisations.
+    allowed_organisations: HashSet<WikiId>,
+
+    /// Wikidata IDs of entities to be treated as products.
+    allowed_products: HashSet<WikiId>,
+}
+
+impl EntityListsAdvisor {
+    /// Constructs a new `EntityListsAdvisor`.
+    #[must_use]
+    pub fn new(lists: &entity_lists::data::Lists) -> Self {
+        Self {
+            blocked_ids: lists.block.wiki.iter().copied().collect(),
+            blocked_names: lists
+                .block
+                .names
+                .iter()
+                .map(|p| matching::NamePattern::new(p))
+                .collect(),
+            allowed_organisations: lists.allow.organisations.iter().copied().collect(),
+            allowed_products: lists.allow.products.iter().copied().collect(),
+        }
+    }
+
+    /// Loads a new `EntityListsAdvisor` from a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if fails to read from `path` or parse the contents.
+    pub fn load(path: &std::path::Path) -> Result<Self, errors::ProcessingError> {
+        if utils::is_path_ok(path) {
+            let data = entity_lists::reader::parse(path)?;
+            Ok(Self::new(&data))
+        } else {
+            log::warn!("Could not access {path:?}. Entity lists won't be loaded!");
+            Ok(Self::new(&entity_lists::data::Lists::default()))
+        }
+    }
+
+    /// Checks if the entity should be excluded from the output.
+    ///
+    /// Blocking takes precedence over allowing.
+    #[must_use]
+    pub fn is_blocked(&self, id: &WikiId, names: &[&str]) -> bool {
+        self.blocked_ids.contains(id)
+            || names.iter().any(|name| self.blocked_names.iter().any(|p| p.matches(name)))
+    }
+
+    /// Checks if the entity should be treated as an organisation.
+    #[must_use]
+    pub fn is_allowed_organisation(&self, id: &WikiId) -> bool {
+        self.allowed_organisations.contains(id)
+    }
+
+    /// Checks if the entity should be treated as a product.
+    #[must_use]
+    pub fn is_allowed_product(&self, id: &WikiId) -> bool {
+        self.allowed_products.contains(id)
+    }
+}
+
 /// Holds the information read from the `Fashion Transparency Index` data.
 pub struct FashionTransparencyIndexAdvisor {
     entries: HashMap<WikiId, fashion_transparency_index::data::Entry>,
@@ -406,7 +1198,7 @@ impl SustainityLibraryAdvisor {
 
 /// Holds the informatiion about mapping from (company, brand, etc.) name to their Wikidata ID.
 pub struct SustainityMatchesAdvisor {
-    name_to_wiki: HashMap<String, WikiId>,
+    name_to_wiki: matching::NameIndex<WikiId>,
 }
 
 impl SustainityMatchesAdvisor {
@@ -416,10 +1208,10 @@ impl SustainityMatchesAdvisor {
     ///
     /// Returns `Err` if passed data is invalid, e.g. contains invalida IDs.
     pub fn new(map: &[sustainity::data::NameMatching]) -> Result<Self, errors::ProcessingError> {
-        let mut name_to_wiki = HashMap::<String, WikiId>::new();
+        let mut name_to_wiki = matching::NameIndex::<WikiId>::new();
         for entry in map {
             if let Some(wiki_id) = entry.matched() {
-                name_to_wiki.insert(entry.name.clone(), wiki_id.wiki_id);
+                name_to_wiki.insert(&entry.name, wiki_id.wiki_id);
             }
         }
 
@@ -442,8 +1234,219 @@ impl SustainityMatchesAdvisor {
     }
 
     /// Returns Wikidata ID given a name.
+    ///
+    /// The names are compared after normalisation, tolerating small differences.
     #[must_use]
     pub fn name_to_wiki(&self, name: &str) -> Option<&WikiId> {
         self.name_to_wiki.get(name)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gleif_registration_numbers() {
+        let record = |lei: &str, number: Option<&str>| gleif::data::Record {
+            lei: lei.to_owned(),
+            legal_name: String::new(),
+            jurisdiction: "NL".to_owned(),
+            registration_number: number.map(String::from),
+        };
+        let advisor = GleifAdvisor::new(&[
+            record("724500Q8BGKHK0MR7E63", Some("30 18 01 62")),
+            record("5493001KJTIIGC8Y1R12", None),
+            record("529900T8BM49AURSDO55", Some(" ")),
+        ]);
+        let leis = ["724500Q8BGKHK0MR7E63", "5493001KJTIIGC8Y1R12", "529900T8BM49AURSDO55"];
+        let leis: Vec<String> = leis.iter().map(|lei| (*lei).to_owned()).collect();
+        assert_eq!(advisor.registration_numbers(&leis), vec!["30180162".to_owned()]);
+    }
+
+    #[test]
+    fn recognitions() {
+        let advisor = AwardsAdvisor::new(&[awards::data::Entry {
+            wikidata_id: WikiId::new(2),
+            name: "Zayed Sustainability Prize".to_owned(),
+        }]);
+        assert!(advisor.recognitions(&[]).is_empty());
+        assert_eq!(
+            advisor.recognitions(&[
+                (WikiId::new(1), Some("2020".to_owned())),
+                (WikiId::new(2), Some("2021-01-15".to_owned())),
+                (WikiId::new(3), None),
+            ]),
+            vec![models::Recognition {
+                wiki_id: "2".to_owned(),
+                name: "Zayed Sustainability Prize".to_owned(),
+                date: Some("2021-01-15".to_owned()),
+            }]
+        );
+    }
+
+    #[test]
+    fn lca_estimates() {
+        let entry = |tag: &str, kg_co2e_per_kg: f64| lca::data::Entry {
+            tag: tag.to_owned(),
+            kg_co2e_per_kg,
+        };
+        let advisor = LcaAdvisor::new(&[
+            entry("en:butters", 9.0),
+            entry("en:milk", 1.0),
+            entry("en:sugar", 4.0),
+        ]);
+        let tags = |tags: &[&str]| tags.iter().map(|tag| (*tag).to_owned()).collect::<Vec<_>>();
+        let footprint = |attributes: models::Attributes| match (
+            &attributes[attributes::CARBON_FOOTPRINT],
+            &attributes[attributes::CARBON_FOOTPRINT_BASIS],
+        ) {
+            (models::AttributeValue::Number(footprint), models::AttributeValue::Text(basis)) => {
+                (*footprint, basis.clone())
+            }
+            _ => panic!("unexpected attributes: {attributes:?}"),
+        };
+
+        let estimate = advisor.estimate(&tags(&["en:dairies", "en:butters"]), &tags(&["en:x"]));
+        assert_eq!(footprint(estimate.unwrap()), (0.9, "en:butters".to_owned()));
+
+        // Weights 1 and 1/2: (1.0 + 4.0 / 2) / 1.5 = 2.0 per kg.
+        let estimate = advisor.estimate(&[], &tags(&["en:milk", "en:sugar"]));
+        let (per_100g, basis) = footprint(estimate.unwrap());
+        assert!((per_100g - 0.2).abs() < 1e-9);
+        assert_eq!(basis, "ingredients");
+
+        // The known milk covers 1 / (1 + 1/2 + 1/3) of the weights, only about 55 %.
+        assert!(advisor.estimate(&[], &tags(&["en:milk", "en:x", "en:y"])).is_none());
+        assert!(advisor.estimate(&[], &tags(&["en:x", "en:milk"])).is_none());
+        assert!(advisor.estimate(&[], &[]).is_none());
+    }
+
+    #[test]
+    fn battery_characteristics() {
+        let characteristic =
+            |id: u64, replaceable_battery: bool| repairability::data::BatteryCharacteristic {
+                wikidata_id: WikiId::new(id),
+                replaceable_battery,
+            };
+        let advisor = RepairabilityAdvisor::new(
+            &[],
+            &[characteristic(1, true), characteristic(2, true), characteristic(3, false)],
+        );
+        let replaceable = |value: bool| {
+            models::Attributes::from([(
+                attributes::REPLACEABLE_BATTERY.to_owned(),
+                models::AttributeValue::Flag(value),
+            )])
+        };
+
+        assert_eq!(advisor.get_battery_attributes(&[WikiId::new(1)]), replaceable(true));
+        assert_eq!(
+            advisor.get_battery_attributes(&[WikiId::new(2), WikiId::new(4)]),
+            replaceable(true)
+        );
+        assert_eq!(advisor.get_battery_attributes(&[WikiId::new(3)]), replaceable(false));
+        assert!(advisor.get_battery_attributes(&[WikiId::new(4)]).is_empty());
+        assert!(advisor.get_battery_attributes(&[WikiId::new(1), WikiId::new(3)]).is_empty());
+    }
+
+    #[test]
+    fn euipo_owners() {
+        let record = |mark: &str, owner: &str, status: &str, classes: &str| euipo::data::Record {
+            application_number: String::new(),
+            mark: mark.to_owned(),
+            owner_name: owner.to_owned(),
+            status: status.to_owned(),
+            nice_classes: classes.to_owned(),
+        };
+        let matching = |name: &str, id: u64| sustainity::data::NameMatching {
+            name: name.to_owned(),
+            ids: vec![WikiId::new(id)],
+            similarity: 1.0,
+        };
+        let advisor = EuipoAdvisor::new(
+            &[
+                record("Choco Crisp", "Sweet Foods GmbH", "Registered", "30"),
+                // The same mark for unrelated goods belongs to another company
+                record("Choco Crisp", "Crisp Electronics", "Registered", "9, 11"),
+                // Two companies share the mark for food
+                record("Golden Drop", "Sweet Foods GmbH", "Registered", "30"),
+                record("Golden Drop", "Honey Farm", "Application filed", "29,30"),
+                // Marks which are no longer live don't count
+                record("Pure Spring", "Spring Water AG", "Registered", "32"),
+                record("Pure Spring", "Honey Farm", "Expired", "32"),
+                record("Old Mill", "Sweet Foods GmbH", "Withdrawn", "30"),
+                // Owners without a match can't be resolved
+                record("Nutty", "Unknown Nuts", "Registered", "29"),
+            ],
+            &[
+                matching("Sweet Foods", 1),
+                matching("Crisp Electronics", 2),
+                matching("Honey Farm", 3),
+                matching("Spring Water", 4),
+            ],
+            EuipoAdvisor::FOOD_CLASSES,
+        );
+
+        assert_eq!(advisor.owner("Choco Crisp"), Some(&WikiId::new(1)));
+        assert_eq!(advisor.owner(" choco  CRISP "), Some(&WikiId::new(1)));
+        assert_eq!(advisor.owner("Pure Spring"), Some(&WikiId::new(4)));
+        assert_eq!(advisor.owner("Golden Drop"), None);
+        assert_eq!(advisor.owner("Old Mill"), None);
+        assert_eq!(advisor.owner("Nutty"), None);
+
+        // Only exact marks are matched, not names starting with them
+        assert_eq!(advisor.owner("Choco Crisp Dark"), None);
+        assert_eq!(advisor.owner("Choco"), None);
+    }
+
+    #[test]
+    fn policy_pages() {
+        use policy_pages::data::{Entry, Kind, Page};
+
+        let page =
+            |url: &str| Page { url: url.to_owned(), title: String::new(), kind: Kind::Report };
+        let entry = |domain: &str, urls: &[&str]| Entry {
+            domain: domain.to_owned(),
+            crawled: None,
+            pages: urls.iter().map(|url| page(url)).collect(),
+        };
+        let advisor = PolicyPagesAdvisor::new(&[
+            entry(
+                "www.fairphone.com",
+                &[
+                    "https://fairphone.com/impact",
+                    "https://www.fairphone.com/en/policy",
+                    "https://support.fairphone.com/report.pdf",
+                    "https://reports.example.org/fairphone.pdf",
+                    "https://fairphone.com.example.org/report.pdf",
+                ],
+            ),
+            entry(
+                "acme.github.io",
+                &["https://acme.github.io/impact", "https://other.github.io/acme.pdf"],
+            ),
+        ]);
+        let urls = |domain: &str| -> Vec<String> {
+            let domains = HashSet::from([domain.to_owned()]);
+            advisor.pages(&domains).into_iter().map(|page| page.url).collect()
+        };
+
+        // Pages on subdomains belong to the crawled domain, pages hosted elsewhere are skipped
+        assert_eq!(
+            urls("fairphone.com"),
+            [
+                "https://fairphone.com/impact",
+                "https://www.fairphone.com/en/policy",
+                "https://support.fairphone.com/report.pdf",
+            ]
+        );
+        assert!(urls("example.org").is_empty());
+        assert!(urls("support.fairphone.com").is_empty());
+
+        // Sites on a shared hosting are told apart by the private public suffix
+        assert_eq!(urls("acme.github.io"), ["https://acme.github.io/impact"]);
+        assert!(urls("other.github.io").is_empty());
+        assert!(urls("github.io").is_empty());
+    }
+}
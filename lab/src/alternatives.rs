@@ -0,0 +1,247 @@
+//! Precomputation of product alternatives.
+//!
+//! Finding alternatives of a product at request time requires scanning all products of each of its
+//! categories, which is slow for the large ones. When enabled, the best alternatives of every
+//! product are computed during crystalization for each of its categories, both for any region and
+//! for each region the product is available in. The backend serves them directly and falls back to
+//! the live queries only for the combinations that were not precomputed.
+//!
+//! The ranking mirrors the live query in the backend, except that ties are broken by the product
+//! key instead of randomly.
+
+use std::collections::BTreeMap;
+
+use sustainity_models::{collections, gather};
+
+/// Score of an alternative followed by the product (i.e. its successor).
+const FOLLOWS_SCORE: i64 = 10_000;
+
+/// Score of an alternative with the `BCorp` certification.
+const BCORP_SCORE: i64 = 9_000;
+
+/// Score of an alternative with the EU Ecolabel certification.
+const EU_ECOLABEL_SCORE: i64 = 9_000;
+
+/// Score of an alternative per percent of its Fashion Transparency Index.
+const FTI_SCORE: i64 = 60;
+
+/// Score of an alternative with the TCO certification.
+const TCO_SCORE: i64 = 3_000;
+
+/// Alternative candidate with its score.
+type Candidate<'a> = (i64, &'a gather::Product);
+
+/// Scores the product regardless of which product it is an alternative to.
+fn base_score(product: &gather::Product) -> i64 {
+    let certifications = &product.certifications;
+    let mut score = 0;
+    if certifications.bcorp.is_some() {
+        score += BCORP_SCORE;
+    }
+    if certifications.eu_ecolabel.is_some() {
+        score += EU_ECOLABEL_SCORE;
+    }
+    if let Some(fti) = &certifications.fti {
+        score += FTI_SCORE * fti.score;
+    }
+    if certifications.tco.is_some() {
+        score += TCO_SCORE;
+    }
+    score
+}
+
+/// Sorts the candidates from the best one, breaking ties by the key.
+fn sort(candidates: &mut [Candidate<'_>]) {
+    candidates.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.db_key.cmp(&b.1.db_key)));
+}
+
+/// Checks if the product is available in the region (`None` meaning any region).
+fn is_available_in(product: &gather::Product, region: Option<isocountry::CountryCode>) -> bool {
+    match (region, &product.regions) {
+        (None, _) | (Some(_), gather::Regions::World) => true,
+        (Some(region), gather::Regions::List(list)) => list.contains(&region),
+        (Some(_), gather::Regions::Unknown) => false,
+    }
+}
+
+/// Lists the regions for which the alternatives of the product are precomputed.
+fn regions_of(product: &gather::Product) -> Vec<Option<isocountry::CountryCode>> {
+    let mut regions = vec![None];
+    if let gather::Regions::List(list) = &product.regions {
+        regions.extend(list.iter().copied().map(Some));
+    }
+    regions
+}
+
+/// Picks the best alternatives of the product.
+///
+/// `candidates` are all non-variant products of the category sorted by their base score and
+/// `followers` are indices of those of them following the product. Also tells if all the eligible
+/// alternatives were picked, i.e. there are fewer of them than the limit.
+fn rank(
+    product: &gather::Product,
+    candidates: &[Candidate<'_>],
+    followers: &[usize],
+    region: Option<isocountry::CountryCode>,
+    limit: usize,
+) -> (Vec<String>, bool) {
+    let parent = product.variant_of.as_ref().unwrap_or(&product.db_key);
+    let is_eligible = |candidate: &gather::Product| {
+        candidate.db_key != product.db_key
+            && &candidate.db_key != parent
+            && is_available_in(candidate, region)
+    };
+
+    let mut ranked: Vec<Candidate<'_>> = followers
+        .iter()
+        .map(|&index| candidates[index])
+        .filter(|(_, candidate)| is_eligible(candidate))
+        .map(|(score, candidate)| (score + FOLLOWS_SCORE, candidate))
+        .collect();
+    ranked.extend(
+        candidates
+            .iter()
+            .filter(|(_, candidate)| {
+                is_eligible(candidate) && !candidate.follows.contains(&product.db_key)
+            })
+            .take(limit)
+            .copied(),
+    );
+    let exhaustive = ranked.len() < limit;
+    sort(&mut ranked);
+    ranked.truncate(limit);
+    let ranked =
+        ranked.into_iter().map(|(_, candidate)| collections::product(&candidate.db_key).key);
+    (ranked.collect(), exhaustive)
+}
+
+/// Computes up to `limit` best alternatives of each product per category and region.
+///
+/// Categories with `max_category_size` or more products are skipped, as they are not indexed.
+#[must_use]
+pub fn precompute(
+    products: &BTreeMap<gather::ProductId, gather::Product>,
+    limit: usize,
+    max_category_size: usize,
+) -> Vec<gather::ProductAlternatives> {
+    let mut categories = BTreeMap::<&str, Vec<&gather::Product>>::new();
+    for product in products.values() {
+        for category in &product.categories {
+            categories.entry(category.as_str()).or_default().push(product);
+        }
+    }
+
+    let mut result = Vec::new();
+    for (category, members) in categories {
+        if members.len() >= max_category_size {
+            continue;
+        }
+
+        let mut candidates: Vec<Candidate<'_>> = members
+            .iter()
+            .filter(|product| product.variant_of.is_none())
+            .map(|product| (base_score(product), *product))
+            .collect();
+        sort(&mut candidates);
+
+        let mut followers = BTreeMap::<&gather::ProductId, Vec<usize>>::new();
+        for (index, (_, candidate)) in candidates.iter().enumerate() {
+            for followed in &candidate.follows {
+                followers.entry(followed).or_default().push(index);
+            }
+        }
+
+        for product in members {
+            let product_followers =
+                followers.get(&product.db_key).map_or(&[] as &[usize], Vec::as_slice);
+            let product_key = collections::product(&product.db_key).key;
+            for region in regions_of(product) {
+                let (alternatives, exhaustive) =
+                    rank(product, &candidates, product_followers, region, limit);
+                let region = region.map(|region| region.alpha3().to_owned());
+                let key =
+                    collections::product_alternatives(&product_key, category, region.as_deref());
+                result.push(gather::ProductAlternatives {
+                    db_key: key.key,
+                    product: product_key.clone(),
+                    category: category.to_owned(),
+                    region,
+                    alternatives,
+                    exhaustive,
+                });
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn product(id: usize, categories: &[&str], regions: gather::Regions) -> gather::Product {
+        gather::Product {
+            db_key: gather::ProductId::from_value(id),
+            categories: categories.iter().map(|c| (*c).to_owned()).collect(),
+            regions,
+            ..gather::Product::default()
+        }
+    }
+
+    fn key(id: usize) -> String {
+        collections::product(&gather::ProductId::from_value(id)).key
+    }
+
+    #[test]
+    fn ranking() {
+        let nld = isocountry::CountryCode::NLD;
+        let mut products = vec![
+            product(1, &["phone"], gather::Regions::List(vec![nld])),
+            product(2, &["phone"], gather::Regions::World),
+            product(3, &["phone"], gather::Regions::Unknown),
+            product(4, &["phone"], gather::Regions::World),
+            product(5, &["phone"], gather::Regions::World),
+            product(6, &["laptop"], gather::Regions::World),
+        ];
+        products[1].certifications.tco = Some(gather::TcoCert {
+            brand_name: "Two".to_owned(),
+            validity: gather::Validity::default(),
+        });
+        products[2].certifications.eu_ecolabel = Some(gather::EuEcolabelCert::default());
+        products[3].follows.insert(gather::ProductId::from_value(1));
+        products[4].variant_of = Some(gather::ProductId::from_value(2));
+        let products: BTreeMap<_, _> =
+            products.into_iter().map(|product| (product.db_key.clone(), product)).collect();
+
+        let alternatives = precompute(&products, 2, 100);
+        let entry =
+            |alternatives: &[gather::ProductAlternatives], id: usize, region: Option<&str>| {
+                alternatives
+                    .iter()
+                    .find(|a| a.product == key(id) && a.region.as_deref() == region)
+                    .cloned()
+            };
+        let find = |id: usize, region: Option<&str>| {
+            entry(&alternatives, id, region).map(|a| a.alternatives)
+        };
+
+        // The follower comes first, variants are never alternatives.
+        assert_eq!(find(1, None), Some(vec![key(4), key(3)]));
+        // Products of unknown regions are not available in the Netherlands.
+        assert_eq!(find(1, Some("NLD")), Some(vec![key(4), key(2)]));
+        // The parent of a variant is not its alternative.
+        assert_eq!(find(5, None), Some(vec![key(3), key(1)]));
+        assert_eq!(find(6, None), Some(Vec::new()));
+        assert_eq!(find(2, Some("NLD")), None);
+        assert_eq!(alternatives.len(), 7);
+
+        // Only lists shorter than the limit are known to contain all the alternatives.
+        assert!(!entry(&alternatives, 1, None).unwrap().exhaustive);
+        assert!(entry(&alternatives, 6, None).unwrap().exhaustive);
+        let all = entry(&precompute(&products, 4, 100), 1, None).unwrap();
+        assert_eq!(all.alternatives, vec![key(4), key(3), key(2)]);
+        assert!(all.exhaustive);
+
+        assert!(precompute(&products, 2, 5).iter().all(|a| a.category == "laptop"));
+    }
+}
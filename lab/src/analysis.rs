@@ -5,12 +5,13 @@ use std::{
 
 use async_trait::async_trait;
 use merge::Merge;
+use serde::Serialize;
 
 use sustainity_collecting::data::WikiId;
 use sustainity_wikidata::data::{Entity, Item, Language};
 
 use crate::{
-    advisors, config, errors, parallel, runners,
+    advisors, commands, config, errors, outputs, parallel, runners,
     sources::Sourceable,
     wikidata::{self, ItemExt},
 };
@@ -293,6 +294,64 @@ impl Class {
     }
 }
 
+/// Class frequent among the products not belonging to any category.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct UncategorisedClass {
+    /// Position in the ranking (starting from 1).
+    pub rank: usize,
+
+    /// Wikidata ID of the class.
+    pub id: String,
+
+    /// English label of the class (if known).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+
+    /// Number of the uncategorised products belonging to the class.
+    pub num_products: usize,
+}
+
+/// Tallies the classes of the products which do not belong to any category.
+///
+/// Returns the number of such products and up to `top` of their most frequent classes, ties
+/// broken by the ID. The ignored classes are not counted.
+fn rank_uncategorised(
+    products: &[Product],
+    labels: &HashMap<WikiId, String>,
+    categories: &HashSet<WikiId>,
+    ignored_classes: &HashSet<WikiId>,
+    top: usize,
+) -> (usize, Vec<UncategorisedClass>) {
+    let mut num_uncategorised = 0;
+    let mut counts = HashMap::<WikiId, usize>::new();
+    for product in products {
+        if product.classes.iter().any(|class| categories.contains(class)) {
+            continue;
+        }
+        num_uncategorised += 1;
+        for class in &product.classes {
+            if !ignored_classes.contains(class) {
+                *counts.entry(*class).or_insert(0) += 1;
+            }
+        }
+    }
+
+    let mut counts: Vec<(WikiId, usize)> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    let ranked = counts
+        .into_iter()
+        .take(top)
+        .enumerate()
+        .map(|(index, (id, num_products))| UncategorisedClass {
+            rank: index + 1,
+            id: id.to_str_id().as_str().to_owned(),
+            label: labels.get(&id).cloned(),
+            num_products,
+        })
+        .collect();
+    (num_uncategorised, ranked)
+}
+
 /// Holds all the supplementary source data.
 pub struct AnalysisSources {
     /// Wikidata data.
@@ -417,28 +476,34 @@ impl runners::WikidataWorker for AnalysisWorker {
     }
 }
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug)]
 pub struct AnalysisStash {
     /// Collected data.
     collector: AnalysisCollector,
-}
 
-#[async_trait]
-impl runners::Stash for AnalysisStash {
-    type Input = AnalysisCollector;
+    /// Kind of the analysis.
+    mode: commands::AnalysisMode,
 
-    fn stash(&mut self, input: Self::Input) -> Result<(), errors::ProcessingError> {
-        self.collector.merge(input);
-        Ok(())
-    }
+    /// Number of the most frequent classes to report.
+    top: usize,
 
-    fn finish(self) -> Result<(), errors::ProcessingError> {
-        log::info!("Found {} products", self.collector.products.len());
-        log::info!("Found {} classes", self.collector.classes.len());
+    /// Path to save the report into.
+    report_path: Option<std::path::PathBuf>,
+}
 
-        let categories: HashSet<WikiId> =
-            wikidata::items::ALL.iter().filter_map(|s| WikiId::try_from(*s).ok()).collect();
-        let ignored_classes = HashSet::<WikiId>::from(IGNORED_CLASSES);
+impl AnalysisStash {
+    #[must_use]
+    pub fn new(config: &config::AnalysisConfig) -> Self {
+        Self {
+            collector: AnalysisCollector::default(),
+            mode: config.mode,
+            top: config.top,
+            report_path: config.report_path.clone(),
+        }
+    }
+
+    /// Prints the frequent classes of the products which do not correspond to any category.
+    fn report_classes(&self, categories: &HashSet<WikiId>, ignored_classes: &HashSet<WikiId>) {
         let classes: HashMap<WikiId, Class> =
             self.collector.classes.iter().map(|c| (c.id, c.clone())).collect();
 
@@ -466,11 +531,64 @@ impl runners::Stash for AnalysisStash {
         if !found {
             log::info!("All classes categorized!");
         }
+    }
 
+    /// Prints (and optionally saves) the ranking of the classes of the uncategorised products.
+    fn report_uncategorised(
+        &self,
+        categories: &HashSet<WikiId>,
+        ignored_classes: &HashSet<WikiId>,
+    ) -> Result<(), errors::ProcessingError> {
+        let labels: HashMap<WikiId, String> =
+            self.collector.classes.iter().map(|c| (c.id, c.label.clone())).collect();
+        let (num_uncategorised, ranked) = rank_uncategorised(
+            &self.collector.products,
+            &labels,
+            categories,
+            ignored_classes,
+            self.top,
+        );
+
+        log::info!("Found {num_uncategorised} products not belonging to any category");
+        for class in &ranked {
+            let label = class.label.as_deref().unwrap_or("?");
+            println!("{:>5}. {} {label} ({})", class.rank, class.id, class.num_products);
+        }
+        if let Some(report_path) = &self.report_path {
+            outputs::write_json_lines(report_path, &ranked, commands::Compression::None)?;
+        }
         Ok(())
     }
 }
 
+#[async_trait]
+impl runners::Stash for AnalysisStash {
+    type Input = AnalysisCollector;
+
+    fn stash(&mut self, input: Self::Input) -> Result<(), errors::ProcessingError> {
+        self.collector.merge(input);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<(), errors::ProcessingError> {
+        log::info!("Found {} products", self.collector.products.len());
+        log::info!("Found {} classes", self.collector.classes.len());
+
+        let categories: HashSet<WikiId> =
+            wikidata::items::ALL.iter().filter_map(|s| WikiId::try_from(*s).ok()).collect();
+        let ignored_classes = HashSet::<WikiId>::from(IGNORED_CLASSES);
+        match self.mode {
+            commands::AnalysisMode::Classes => {
+                self.report_classes(&categories, &ignored_classes);
+                Ok(())
+            }
+            commands::AnalysisMode::Uncategorised => {
+                self.report_uncategorised(&categories, &ignored_classes)
+            }
+        }
+    }
+}
+
 pub struct AnalysisRunner;
 
 impl AnalysisRunner {
@@ -478,7 +596,7 @@ impl AnalysisRunner {
         let sources = Arc::new(AnalysisSources::load(config)?);
 
         let worker = AnalysisWorker::new(sources);
-        let stash = AnalysisStash::default();
+        let stash = AnalysisStash::new(config);
 
         let flow = parallel::Flow::new();
         runners::WikidataRunner::flow(flow, config, worker, stash)?.join();
@@ -486,3 +604,36 @@ impl AnalysisRunner {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uncategorised_ranking() {
+        let product = |classes: &[u64]| Product {
+            classes: classes.iter().map(|id| WikiId::new(*id)).collect(),
+        };
+        let products = vec![
+            product(&[1, 10]),
+            product(&[2, 10]),
+            product(&[2, 11, 99]),
+            product(&[3, 12]),
+            product(&[100]),
+        ];
+        let labels = HashMap::from([(WikiId::new(2), "printer".to_owned())]);
+        let categories = HashSet::from([WikiId::new(100)]);
+        let ignored = HashSet::from([WikiId::new(99)]);
+
+        let (num, ranked) = rank_uncategorised(&products, &labels, &categories, &ignored, 3);
+        assert_eq!(num, 4);
+        let summary: Vec<(usize, &str, Option<&str>, usize)> = ranked
+            .iter()
+            .map(|c| (c.rank, c.id.as_str(), c.label.as_deref(), c.num_products))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![(1, "Q2", Some("printer"), 2), (2, "Q10", None, 2), (3, "Q1", None, 1)]
+        );
+    }
+}
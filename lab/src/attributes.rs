@@ -0,0 +1,253 @@
+//! Attributes of products and organisations passed from condensation to crystalization.
+//!
+//! The substrate files have no field for generic attributes (nor for awards, parents and legal
+//! identifiers of organisations or variants and shop identifiers of products), so they are saved
+//! next to the substrates into gzipped `attributes/<substrate name>.jsonl.gz` files. The
+//! subdirectory is ignored when listing the substrates and cleared at the start of every
+//! condensation, so that attributes of a previous run never outlive their substrates.
+//!
+//! Information about the source data set itself (e.g. the date of the snapshot) is saved there as
+//! `attributes/<substrate name>.json`.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use sustainity_models::gather as models;
+
+use crate::{commands::Compression, errors, outputs};
+
+/// Name of the subdirectory of the substrate directory holding the attributes.
+const DIRECTORY: &str = "attributes";
+
+/// Compression of the attribute files.
+const COMPRESSION: Compression = Compression::Gzip;
+
+/// Kind of the entity the attributes belong to.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Subject {
+    /// A product.
+    #[default]
+    Product,
+
+    /// A producer (organisation).
+    Producer,
+}
+
+/// Attributes of a single product or producer.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Record {
+    /// Kind of the entity.
+    #[serde(default)]
+    pub subject: Subject,
+
+    /// ID of the product or producer in the substrate.
+    pub id: String,
+
+    /// The attributes.
+    pub attributes: models::Attributes,
+
+    /// Notable awards (only for producers).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recognitions: Vec<models::Recognition>,
+
+    /// Known controversies (only for producers).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub controversies: Vec<models::Controversy>,
+
+    /// Sustainability-related pages on the websites (only for producers).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub policy_pages: Vec<models::PolicyPage>,
+
+    /// Identifiers in online shops (only for products).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub external_ids: BTreeMap<models::ExternalIdKind, Vec<String>>,
+
+    /// IDs (in the substrate) of products this product is a variant of (only for products).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variant_of: Vec<String>,
+
+    /// IDs (in the substrate) of parent organisations (only for producers).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub owned_by: Vec<String>,
+
+    /// Legal Entity Identifiers (only for producers).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub leis: Vec<String>,
+
+    /// National company registration numbers (only for producers).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub registration_numbers: Vec<String>,
+
+    /// Validity of the certification given by the source (only for producers).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validity: Option<models::Validity>,
+}
+
+/// Information about the source data set of a substrate.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct DataSet {
+    /// Date of the snapshot of the source data, if the source provides it.
+    #[serde(default)]
+    pub snapshot_date: Option<String>,
+}
+
+/// Returns path to the attributes of the substrate with the given name.
+#[must_use]
+pub fn path(substrate_dir: &std::path::Path, name: &str) -> std::path::PathBuf {
+    substrate_dir.join(DIRECTORY).join(name).with_extension("jsonl")
+}
+
+/// Removes all the attributes and data set information from the substrate directory.
+///
+/// # Errors
+///
+/// Returns `Err` if removing the directory fails.
+pub fn clear(substrate_dir: &std::path::Path) -> Result<(), errors::ProcessingError> {
+    match std::fs::remove_dir_all(substrate_dir.join(DIRECTORY)) {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Writes the records of the substrate with the given name.
+///
+/// # Errors
+///
+/// Returns `Err` if writing to the file fails.
+pub fn write(
+    substrate_dir: &std::path::Path,
+    name: &str,
+    records: Vec<Record>,
+) -> Result<(), errors::ProcessingError> {
+    let path = path(substrate_dir, name);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    outputs::write_json_lines(&path, records, COMPRESSION)
+}
+
+/// Returns path to the data set information of the substrate with the given name.
+#[must_use]
+pub fn data_set_path(substrate_dir: &std::path::Path, name: &str) -> std::path::PathBuf {
+    substrate_dir.join(DIRECTORY).join(name).with_extension("json")
+}
+
+/// Writes the data set information of the substrate with the given name.
+///
+/// # Errors
+///
+/// Returns `Err` if writing to the file fails.
+pub fn write_data_set(
+    substrate_dir: &std::path::Path,
+    name: &str,
+    data_set: &DataSet,
+) -> Result<(), errors::ProcessingError> {
+    let path = data_set_path(substrate_dir, name);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let contents =
+        serde_json::to_string_pretty(data_set).map_err(errors::ProcessingError::WriteJson)?;
+    std::fs::write(&path, contents)?;
+    Ok(())
+}
+
+/// Reads the data set information of the substrate with the given name.
+///
+/// Returns the default (no known dates) if the substrate has no such information.
+///
+/// # Errors
+///
+/// Returns `Err` if the file exists but cannot be read or parsed.
+pub fn read_data_set(
+    substrate_dir: &std::path::Path,
+    name: &str,
+) -> Result<DataSet, errors::ProcessingError> {
+    let path = data_set_path(substrate_dir, name);
+    if path.exists() {
+        let contents = std::fs::read_to_string(&path)?;
+        serde_json::from_str(&contents).map_err(|e| errors::ProcessingError::ReadJson(e, path))
+    } else {
+        Ok(DataSet::default())
+    }
+}
+
+/// Reads the records of the substrate with the given name.
+///
+/// Returns no records if the substrate has no attributes.
+///
+/// # Errors
+///
+/// Returns `Err` if the file exists but cannot be read.
+pub fn read(
+    substrate_dir: &std::path::Path,
+    name: &str,
+) -> Result<Vec<Record>, errors::ProcessingError> {
+    let path = path(substrate_dir, name);
+    if outputs::resolve(&path).exists() {
+        Ok(outputs::read_json_lines(&path)?.collect::<Result<_, _>>()?)
+    } else {
+        Ok(Vec::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let records = vec![Record {
+            subject: Subject::Product,
+            id: "Q1".to_owned(),
+            attributes: models::Attributes::from([(
+                sustainity_models::attributes::MASS_KG.to_owned(),
+                models::AttributeValue::Number(0.2),
+            )]),
+            recognitions: Vec::new(),
+            controversies: Vec::new(),
+            policy_pages: Vec::new(),
+            external_ids: BTreeMap::from([(
+                models::ExternalIdKind::Asin,
+                vec!["B0C1234567".to_owned()],
+            )]),
+            variant_of: vec!["Q2".to_owned()],
+            owned_by: Vec::new(),
+            leis: Vec::new(),
+            registration_numbers: Vec::new(),
+            validity: None,
+        }];
+        assert!(read(dir.path(), "wikidata").unwrap().is_empty());
+        write(dir.path(), "wikidata", records.clone()).unwrap();
+        assert!(outputs::compressed_path(&path(dir.path(), "wikidata"), COMPRESSION).exists());
+        assert_eq!(read(dir.path(), "wikidata").unwrap(), records);
+    }
+
+    #[test]
+    fn clearing() {
+        let dir = tempfile::tempdir().unwrap();
+        clear(dir.path()).unwrap();
+
+        write(dir.path(), "wikidata", Vec::new()).unwrap();
+        let data_set = DataSet { snapshot_date: Some("2024-01-15".to_owned()) };
+        write_data_set(dir.path(), "eu_ecolabel", &data_set).unwrap();
+        clear(dir.path()).unwrap();
+
+        assert!(!dir.path().join(DIRECTORY).exists());
+        assert!(read(dir.path(), "wikidata").unwrap().is_empty());
+        assert_eq!(read_data_set(dir.path(), "eu_ecolabel").unwrap(), DataSet::default());
+    }
+
+    #[test]
+    fn data_set() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(read_data_set(dir.path(), "eu_ecolabel").unwrap(), DataSet::default());
+
+        let data_set = DataSet { snapshot_date: Some("2024-01-15".to_owned()) };
+        write_data_set(dir.path(), "eu_ecolabel", &data_set).unwrap();
+        assert_eq!(read_data_set(dir.path(), "eu_ecolabel").unwrap(), data_set);
+    }
+}
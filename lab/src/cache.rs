@@ -5,17 +5,30 @@ use serde::{Deserialize, Serialize};
 use sustainity_collecting::errors::{IoOrSerdeError, MapSerde};
 
 /// Cached data from search over Wikidata data.
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, Default, schemars::JsonSchema)]
 pub struct Wikidata {
     /// Manufacturer IDs.
     #[serde(deserialize_with = "sustainity_wikidata::data::deserialize_vec_id_from_vec_string")]
+    #[schemars(with = "Vec<String>")]
     pub manufacturer_ids: Vec<sustainity_wikidata::data::Id>,
 
     /// All entry classes.
     #[serde(deserialize_with = "sustainity_wikidata::data::deserialize_vec_id_from_vec_string")]
+    #[schemars(with = "Vec<String>")]
     pub classes: Vec<sustainity_wikidata::data::Id>,
 }
 
+/// Writes the cache data.
+///
+/// # Errors
+///
+/// Returns `Err` if fails to serialize the data or write to `path`.
+pub fn save(path: &std::path::Path, cache: &Wikidata) -> Result<(), IoOrSerdeError> {
+    let contents = serde_json::to_string_pretty(cache).map_serde()?;
+    std::fs::write(path, contents)?;
+    Ok(())
+}
+
 /// Reads in the cache data.
 ///
 /// # Errors
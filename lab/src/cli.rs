@@ -0,0 +1,311 @@
+//! Parsing of the command line.
+//!
+//! Values of the arguments can be given (in order of precedence):
+//! - on the command line (`--origin /data/origin`),
+//! - in environment variables named after the argument (`SUSTAINITY_ORIGIN=/data/origin`),
+//! - in a YAML configuration file passed with `--config` (or `SUSTAINITY_CONFIG`), e.g.:
+//!
+//! ```yaml
+//! defaults:
+//!   origin: /data/origin
+//!   cache: /data/cache
+//! condense:
+//!   substrate: /data/substrate
+//! ```
+//!
+//! Values in the `defaults` section apply to all the commands accepting the given argument.
+//!
+//! The values may refer to variables (`${DATA_DIR}/bcorp.csv`) defined in the `variables` section
+//! or in the environment (which takes precedence). Sections of a profile selected with `--profile`
+//! (or `SUSTAINITY_PROFILE`) override the top-level ones:
+//!
+//! ```yaml
+//! variables:
+//!   DATA_DIR: /data
+//!   DUMP_DATE: "20231120"
+//! defaults:
+//!   origin: ${DATA_DIR}/origin
+//! profiles:
+//!   dev:
+//!     variables:
+//!       DATA_DIR: /home/me/data
+//!     condense:
+//!       sample: 1/100
+//! ```
+
+use std::{collections::BTreeMap, ffi::OsString};
+
+use clap::{parser::ValueSource, ArgMatches, CommandFactory, Parser};
+use serde::Deserialize;
+
+use crate::{commands::Args, errors::ConfigCheckError};
+
+/// Prefix of the environment variables overriding the arguments.
+const ENV_PREFIX: &str = "SUSTAINITY_";
+
+/// ID of the argument with the path to the configuration file.
+const CONFIG_ARG: &str = "config";
+
+/// ID of the argument with the name of the profile.
+const PROFILE_ARG: &str = "profile";
+
+/// Values of arguments of a single command.
+type Section = BTreeMap<String, serde_yaml::Value>;
+
+/// Contents of the configuration file.
+#[derive(Deserialize, Debug, Default)]
+pub struct ConfigFile {
+    /// Variables which can be referred to in the values.
+    #[serde(default)]
+    pub variables: BTreeMap<String, String>,
+
+    /// Values common for all the commands.
+    #[serde(default)]
+    pub defaults: Section,
+
+    /// Named sets of values overriding the top-level ones.
+    #[serde(default)]
+    pub profiles: BTreeMap<String, ConfigFile>,
+
+    /// Values for specific commands.
+    #[serde(flatten)]
+    pub commands: BTreeMap<String, Section>,
+}
+
+impl ConfigFile {
+    /// Loads the configuration file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if fails to read from `path` or parse the contents.
+    pub fn load(path: &std::path::Path) -> Result<Self, ConfigCheckError> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| ConfigCheckError::ReadConfigFile(path.to_owned(), e))?;
+        serde_yaml::from_str(&contents)
+            .map_err(|e| ConfigCheckError::ParseConfigFile(path.to_owned(), e))
+    }
+
+    /// Overrides the top-level values with the values from the profile.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the profile is not defined.
+    pub fn select_profile(mut self, profile: &str) -> Result<Self, ConfigCheckError> {
+        let Some(overrides) = self.profiles.remove(profile) else {
+            return Err(ConfigCheckError::UnknownProfile(profile.to_owned()));
+        };
+        self.variables.extend(overrides.variables);
+        self.defaults.extend(overrides.defaults);
+        for (command, section) in overrides.commands {
+            self.commands.entry(command).or_default().extend(section);
+        }
+        Ok(self)
+    }
+
+    /// Replaces references to variables (`${NAME}`) with their values.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if a variable is not defined.
+    pub fn interpolate(&self, text: &str) -> Result<String, ConfigCheckError> {
+        let mut result = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find("${") {
+            let Some(len) = rest[start..].find('}') else {
+                break;
+            };
+            let name = &rest[start + 2..start + len];
+            let value = match std::env::var(name) {
+                Ok(value) => value,
+                Err(_) => self
+                    .variables
+                    .get(name)
+                    .cloned()
+                    .ok_or_else(|| ConfigCheckError::UndefinedVariable(name.to_owned()))?,
+            };
+            result.push_str(&rest[..start]);
+            result.push_str(&value);
+            rest = &rest[start + len + 1..];
+        }
+        result.push_str(rest);
+        Ok(result)
+    }
+
+    /// Returns the value of the argument for the command.
+    fn get(&self, command: &str, arg: &str) -> Option<&serde_yaml::Value> {
+        self.commands.get(command).and_then(|section| section.get(arg)).or(self.defaults.get(arg))
+    }
+}
+
+/// Name of the environment variable overriding the argument.
+fn env_name(arg: &str) -> String {
+    format!("{ENV_PREFIX}{}", arg.to_uppercase().replace('-', "_"))
+}
+
+/// Converts a value from the configuration file into argument values.
+fn to_strings(value: &serde_yaml::Value) -> Vec<String> {
+    match value {
+        serde_yaml::Value::String(string) => vec![string.clone()],
+        serde_yaml::Value::Number(number) => vec![number.to_string()],
+        serde_yaml::Value::Bool(boolean) => vec![boolean.to_string()],
+        serde_yaml::Value::Sequence(values) => values.iter().flat_map(to_strings).collect(),
+        serde_yaml::Value::Null | serde_yaml::Value::Mapping(_) | serde_yaml::Value::Tagged(_) => {
+            Vec::new()
+        }
+    }
+}
+
+/// Returns the value of a global argument (given on the command line or in the environment).
+fn get_global(matches: &ArgMatches, sub_matches: &ArgMatches, id: &str) -> Option<String> {
+    sub_matches
+        .try_get_one::<String>(id)
+        .ok()
+        .flatten()
+        .or(matches.try_get_one::<String>(id).ok().flatten())
+        .cloned()
+        .or_else(|| std::env::var(env_name(id)).ok())
+}
+
+/// Appends values of the arguments not given on the command line, taking them from the
+/// environment variables and the configuration file.
+///
+/// # Errors
+///
+/// Returns `Err` if the configuration file could not be loaded, the profile does not exist or
+/// a variable is not defined.
+pub fn complete_args(args: Vec<OsString>) -> Result<Vec<OsString>, ConfigCheckError> {
+    let command = Args::command();
+    let matches = command.clone().ignore_errors(true).get_matches_from(args.clone());
+    let Some((name, sub_matches)) = matches.subcommand() else {
+        return Ok(args);
+    };
+    let Some(subcommand) = command.find_subcommand(name) else {
+        return Ok(args);
+    };
+
+    let mut config_file = match get_global(&matches, sub_matches, CONFIG_ARG) {
+        Some(path) => ConfigFile::load(std::path::Path::new(&path))?,
+        None => ConfigFile::default(),
+    };
+    if let Some(profile) = get_global(&matches, sub_matches, PROFILE_ARG) {
+        config_file = config_file.select_profile(&profile)?;
+    }
+
+    let mut result = args;
+    for arg in subcommand.get_arguments() {
+        let id = arg.get_id().as_str();
+        let Some(long) = arg.get_long() else {
+            continue;
+        };
+        if id == CONFIG_ARG
+            || id == PROFILE_ARG
+            || sub_matches.value_source(id) == Some(ValueSource::CommandLine)
+        {
+            continue;
+        }
+
+        let values = match std::env::var(env_name(long)) {
+            Ok(value) => vec![value],
+            Err(_) => config_file.get(name, long).map(to_strings).unwrap_or_default(),
+        };
+        for value in values {
+            let value = config_file.interpolate(&value)?;
+            if arg.get_action().takes_values() {
+                result.push(format!("--{long}={value}").into());
+            } else if value == "true" {
+                result.push(format!("--{long}").into());
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Parses the program arguments completing them from the environment and the configuration file.
+///
+/// Exits the program on errors, the same way `Args::parse()` does.
+#[must_use]
+pub fn parse() -> Args {
+    match complete_args(std::env::args_os().collect()) {
+        Ok(args) => Args::parse_from(args),
+        Err(err) => Args::command().error(clap::error::ErrorKind::Io, err).exit(),
+    }
+}
+
+/// Prints the shell completion script to the standard output.
+pub fn print_completions(shell: clap_complete::Shell) {
+    let mut command = Args::command();
+    let name = command.get_name().to_owned();
+    clap_complete::generate(shell, &mut command, name, &mut std::io::stdout());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<OsString> {
+        args.iter().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn completion_from_config_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.yaml");
+        std::fs::write(&path, "defaults:\n  output: /default\nvalidate:\n  input: /input\n")
+            .unwrap();
+        let path = path.to_str().unwrap();
+
+        assert_eq!(
+            complete_args(args(&["lab", "schemas", "--config", path])).unwrap(),
+            args(&["lab", "schemas", "--config", path, "--output=/default"])
+        );
+        assert_eq!(
+            complete_args(args(&["lab", "--config", path, "schemas", "--output", "/cli"])).unwrap(),
+            args(&["lab", "--config", path, "schemas", "--output", "/cli"])
+        );
+        assert_eq!(
+            complete_args(args(&["lab", "validate", "--config", path])).unwrap(),
+            args(&["lab", "validate", "--config", path, "--input=/input"])
+        );
+        assert!(complete_args(args(&["lab", "schemas", "--config", "/missing.yaml"])).is_err());
+    }
+
+    #[test]
+    fn profiles_and_variables() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.yaml");
+        std::fs::write(
+            &path,
+            "variables:\n  LAB_TEST_DIR: /data\n\
+             schemas:\n  output: ${LAB_TEST_DIR}/schemas\n\
+             profiles:\n  dev:\n    variables:\n      LAB_TEST_DIR: /dev\n\
+             \x20 broken:\n    schemas:\n      output: ${LAB_TEST_UNDEFINED}\n",
+        )
+        .unwrap();
+        let path = path.to_str().unwrap();
+
+        assert_eq!(
+            complete_args(args(&["lab", "schemas", "--config", path])).unwrap(),
+            args(&["lab", "schemas", "--config", path, "--output=/data/schemas"])
+        );
+        assert_eq!(
+            complete_args(args(&["lab", "schemas", "--config", path, "--profile", "dev"])).unwrap(),
+            args(&[
+                "lab",
+                "schemas",
+                "--config",
+                path,
+                "--profile",
+                "dev",
+                "--output=/dev/schemas"
+            ])
+        );
+        assert!(matches!(
+            complete_args(args(&["lab", "schemas", "--config", path, "--profile", "prod"])),
+            Err(ConfigCheckError::UnknownProfile(_))
+        ));
+        assert!(matches!(
+            complete_args(args(&["lab", "schemas", "--config", path, "--profile", "broken"])),
+            Err(ConfigCheckError::UndefinedVariable(_))
+        ));
+    }
+}
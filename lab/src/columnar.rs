@@ -0,0 +1,369 @@
+//! Export of a crystalized data set into Parquet files.
+//!
+//! Products and organisations are written into separate files with one row per entity. Lists (like
+//! IDs or categories) are stored as list columns and the certifications as nested structs, so the
+//! files can be queried directly with tools like DuckDB or Spark.
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{
+        ArrayRef, BooleanArray, Float64Array, Int64Array, ListBuilder, StringArray, StringBuilder,
+        StructArray,
+    },
+    buffer::NullBuffer,
+    datatypes::{DataType, Field, Fields, Schema, SchemaRef},
+    error::ArrowError,
+    record_batch::RecordBatch,
+};
+use parquet::{
+    arrow::ArrowWriter,
+    basic::{Compression, ZstdLevel},
+    file::properties::WriterProperties,
+};
+
+use sustainity_models::store as models;
+
+use crate::{config, errors, outputs};
+
+/// Number of rows written at once.
+const BATCH_SIZE: usize = 10_000;
+
+fn strings<'a>(values: impl Iterator<Item = Option<&'a str>>) -> ArrayRef {
+    Arc::new(values.collect::<StringArray>())
+}
+
+fn string_lists<L, S>(lists: impl Iterator<Item = L>) -> ArrayRef
+where
+    L: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut builder = ListBuilder::new(StringBuilder::new());
+    for list in lists {
+        for value in list {
+            builder.values().append_value(value);
+        }
+        builder.append(true);
+    }
+    Arc::new(builder.finish())
+}
+
+/// Builds a struct column out of the given columns.
+///
+/// If `valid` is given, the struct is null in rows where it is `false`.
+fn structs(
+    columns: Vec<(&str, ArrayRef)>,
+    valid: Option<Vec<bool>>,
+) -> Result<ArrayRef, ArrowError> {
+    let fields: Fields = columns
+        .iter()
+        .map(|(name, array)| Field::new(*name, array.data_type().clone(), true))
+        .collect();
+    let arrays = columns.into_iter().map(|(_, array)| array).collect();
+    Ok(Arc::new(StructArray::try_new(fields, arrays, valid.map(NullBuffer::from))?))
+}
+
+fn certifications(certifications: &[&models::Certifications]) -> Result<ArrayRef, ArrowError> {
+    let bcorp = structs(
+        vec![(
+            "id",
+            strings(certifications.iter().map(|c| c.bcorp.as_ref().map(|b| b.id.as_str()))),
+        )],
+        Some(certifications.iter().map(|c| c.bcorp.is_some()).collect()),
+    )?;
+    let eu_ecolabel: ArrayRef = Arc::new(
+        certifications.iter().map(|c| Some(c.eu_ecolabel.is_some())).collect::<BooleanArray>(),
+    );
+    let fti = structs(
+        vec![(
+            "score",
+            Arc::new(
+                certifications
+                    .iter()
+                    .map(|c| c.fti.as_ref().map(|f| f.score))
+                    .collect::<Int64Array>(),
+            ),
+        )],
+        Some(certifications.iter().map(|c| c.fti.is_some()).collect()),
+    )?;
+    let tco = structs(
+        vec![(
+            "brand_name",
+            strings(certifications.iter().map(|c| c.tco.as_ref().map(|t| t.brand_name.as_str()))),
+        )],
+        Some(certifications.iter().map(|c| c.tco.is_some()).collect()),
+    )?;
+    structs(vec![("bcorp", bcorp), ("eu_ecolabel", eu_ecolabel), ("fti", fti), ("tco", tco)], None)
+}
+
+/// Returns the kind of the regions and the list of the country codes.
+fn regions(regions: &models::Regions) -> (&'static str, Vec<&'static str>) {
+    match regions {
+        models::Regions::World => ("all", Vec::new()),
+        models::Regions::Unknown => ("unknown", Vec::new()),
+        models::Regions::List(list) => ("list", list.iter().map(|code| code.alpha3()).collect()),
+    }
+}
+
+/// Type of list columns.
+fn string_list() -> DataType {
+    DataType::new_list(DataType::Utf8, true)
+}
+
+/// Type of the certifications column, as built by `certifications`.
+fn certifications_type() -> DataType {
+    let optional = |name: &str, field: Field| {
+        Field::new(name, DataType::Struct(Fields::from(vec![field])), true)
+    };
+    DataType::Struct(Fields::from(vec![
+        optional("bcorp", Field::new("id", DataType::Utf8, true)),
+        Field::new("eu_ecolabel", DataType::Boolean, true),
+        optional("fti", Field::new("score", DataType::Int64, true)),
+        optional("tco", Field::new("brand_name", DataType::Utf8, true)),
+    ]))
+}
+
+/// Schema of the organisations file.
+///
+/// The schemas are declared up front instead of being inferred from the data, so that the optional
+/// fields are nullable regardless of the values in any particular batch.
+fn organisations_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("key", DataType::Utf8, false),
+        Field::new("name", DataType::Utf8, true),
+        Field::new("description", DataType::Utf8, true),
+        Field::new("wiki", string_list(), false),
+        Field::new("vat_ids", string_list(), false),
+        Field::new("domains", string_list(), false),
+        Field::new("leis", string_list(), false),
+        Field::new("registration_numbers", string_list(), false),
+        Field::new("websites", string_list(), false),
+        Field::new("certifications", certifications_type(), false),
+    ]))
+}
+
+/// Schema of the products file.
+fn products_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("key", DataType::Utf8, false),
+        Field::new("name", DataType::Utf8, true),
+        Field::new("description", DataType::Utf8, true),
+        Field::new("eans", string_list(), false),
+        Field::new("gtins", string_list(), false),
+        Field::new("wiki", string_list(), false),
+        Field::new("categories", string_list(), false),
+        Field::new("region_kind", DataType::Utf8, false),
+        Field::new("regions", string_list(), false),
+        Field::new("manufacturer_ids", string_list(), false),
+        Field::new("score", DataType::Float64, false),
+        Field::new("certifications", certifications_type(), false),
+    ]))
+}
+
+/// Builds a batch of the given schema out of named columns.
+fn batch(schema: SchemaRef, columns: Vec<(&str, ArrayRef)>) -> Result<RecordBatch, ArrowError> {
+    debug_assert!(schema
+        .fields()
+        .iter()
+        .map(|f| f.name().as_str())
+        .eq(columns.iter().map(|(n, _)| *n)));
+    RecordBatch::try_new(schema, columns.into_iter().map(|(_, column)| column).collect())
+}
+
+fn organisations_batch(organisations: &[models::Organisation]) -> Result<RecordBatch, ArrowError> {
+    let certs: Vec<_> = organisations.iter().map(|o| &o.certifications).collect();
+    batch(
+        organisations_schema(),
+        vec![
+            ("key", strings(organisations.iter().map(|o| Some(o.db_key.as_str())))),
+            (
+                "name",
+                strings(organisations.iter().map(|o| o.names.first().map(|n| n.text.as_str()))),
+            ),
+            (
+                "description",
+                strings(
+                    organisations.iter().map(|o| o.descriptions.first().map(|d| d.text.as_str())),
+                ),
+            ),
+            ("wiki", string_lists(organisations.iter().map(|o| &o.ids.wiki))),
+            ("vat_ids", string_lists(organisations.iter().map(|o| &o.ids.vat_ids))),
+            ("domains", string_lists(organisations.iter().map(|o| &o.ids.domains))),
+            ("leis", string_lists(organisations.iter().map(|o| &o.ids.leis))),
+            (
+                "registration_numbers",
+                string_lists(organisations.iter().map(|o| &o.ids.registration_numbers)),
+            ),
+            ("websites", string_lists(organisations.iter().map(|o| &o.websites))),
+            ("certifications", certifications(&certs)?),
+        ],
+    )
+}
+
+fn products_batch(products: &[models::Product]) -> Result<RecordBatch, ArrowError> {
+    let certs: Vec<_> = products.iter().map(|p| &p.certifications).collect();
+    let regions: Vec<_> = products.iter().map(|p| regions(&p.regions)).collect();
+    let scores: Float64Array = products.iter().map(|p| Some(p.sustainity_score.total)).collect();
+    batch(
+        products_schema(),
+        vec![
+            ("key", strings(products.iter().map(|p| Some(p.db_key.as_str())))),
+            ("name", strings(products.iter().map(|p| p.names.first().map(|n| n.text.as_str())))),
+            (
+                "description",
+                strings(products.iter().map(|p| p.descriptions.first().map(|d| d.text.as_str()))),
+            ),
+            ("eans", string_lists(products.iter().map(|p| &p.ids.eans))),
+            ("gtins", string_lists(products.iter().map(|p| &p.ids.gtins))),
+            ("wiki", string_lists(products.iter().map(|p| &p.ids.wiki))),
+            ("categories", string_lists(products.iter().map(|p| &p.categories))),
+            ("region_kind", strings(regions.iter().map(|(kind, _)| Some(*kind)))),
+            ("regions", string_lists(regions.iter().map(|(_, codes)| codes))),
+            ("manufacturer_ids", string_lists(products.iter().map(|p| &p.manufacturer_ids))),
+            ("score", Arc::new(scores) as ArrayRef),
+            ("certifications", certifications(&certs)?),
+        ],
+    )
+}
+
+pub struct ParquetExporter;
+
+impl ParquetExporter {
+    /// Writes entries read from the JSON Lines file at `input` into a Parquet file at `output`.
+    ///
+    /// Returns the number of written entries.
+    fn write<T, F>(
+        input: &std::path::Path,
+        output: &std::path::Path,
+        schema: SchemaRef,
+        to_batch: F,
+    ) -> Result<usize, errors::ProcessingError>
+    where
+        T: serde::de::DeserializeOwned,
+        F: Fn(&[T]) -> Result<RecordBatch, ArrowError>,
+    {
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::ZSTD(ZstdLevel::default()))
+            .build();
+        let file = std::fs::File::create(output)?;
+        let mut writer = ArrowWriter::try_new(file, schema, Some(properties))?;
+
+        let mut count = 0;
+        let mut buffer = Vec::with_capacity(BATCH_SIZE);
+        for entry in outputs::read_json_lines::<T>(input)? {
+            buffer.push(entry?);
+            if buffer.len() == BATCH_SIZE {
+                writer.write(&to_batch(&buffer)?)?;
+                count += buffer.len();
+                buffer.clear();
+            }
+        }
+        if !buffer.is_empty() {
+            writer.write(&to_batch(&buffer)?)?;
+            count += buffer.len();
+        }
+
+        writer.close()?;
+        Ok(count)
+    }
+
+    /// Writes `organisations.parquet` and `products.parquet` into the output directory.
+    pub fn export(config: &config::ExportConfig) -> Result<(), errors::ProcessingError> {
+        std::fs::create_dir(&config.output_path)?;
+
+        log::info!("Exporting organisations");
+        let count = Self::write(
+            &config.organisations_path,
+            &config.output_path.join("organisations.parquet"),
+            organisations_schema(),
+            organisations_batch,
+        )?;
+        log::info!(" - {count} organisations");
+
+        log::info!("Exporting products");
+        let count = Self::write(
+            &config.products_path,
+            &config.output_path.join("products.parquet"),
+            products_schema(),
+            products_batch,
+        )?;
+        log::info!(" - {count} products");
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::{Array, AsArray};
+
+    use super::*;
+
+    #[test]
+    fn nested_certifications() {
+        let certified = models::Certifications {
+            bcorp: Some(models::BCorpCert {
+                id: "acme".to_owned(),
+                validity: models::Validity::default(),
+            }),
+            tco: Some(models::TcoCert {
+                brand_name: "Acme".to_owned(),
+                validity: models::Validity::default(),
+            }),
+            ..models::Certifications::default()
+        };
+        let uncertified = models::Certifications::default();
+
+        let array = certifications(&[&certified, &uncertified]).unwrap();
+        let array = array.as_struct();
+        assert_eq!(array.len(), 2);
+        assert_eq!(array.null_count(), 0);
+
+        let bcorp = array.column_by_name("bcorp").unwrap().as_struct();
+        assert!(bcorp.is_valid(0));
+        assert!(bcorp.is_null(1));
+        assert_eq!(bcorp.column(0).as_string::<i32>().value(0), "acme");
+
+        let fti = array.column_by_name("fti").unwrap();
+        assert_eq!(fti.null_count(), 2);
+
+        let eu_ecolabel = array.column_by_name("eu_ecolabel").unwrap().as_boolean();
+        assert!(!eu_ecolabel.value(0));
+    }
+
+    /// Batches with and without missing optional values fit the same schema.
+    #[test]
+    fn nullable_schema() {
+        let product = |names: serde_json::Value| -> models::Product {
+            serde_json::from_value(serde_json::json!({
+                "_key": "p1",
+                "ids": {"eans": [], "gtins": ["1"], "wiki": []},
+                "names": names,
+                "descriptions": [],
+                "images": [],
+                "categories": [],
+                "regions": {"variant": "all"},
+                "certifications": {"bcorp": null, "eu_ecolabel": null, "fti": null, "tco": null},
+                "manufacturer_ids": [],
+                "follows": [],
+                "followed_by": [],
+                "sustainity_score": {"tree": [], "total": 0.5},
+            }))
+            .unwrap()
+        };
+        let named =
+            products_batch(&[product(serde_json::json!([{"text": "A", "source": "wiki"}]))])
+                .unwrap();
+        let unnamed = products_batch(&[product(serde_json::json!([]))]).unwrap();
+        assert_eq!(named.schema(), products_schema());
+        assert_eq!(unnamed.schema(), products_schema());
+
+        let mut writer = ArrowWriter::try_new(Vec::new(), products_schema(), None).unwrap();
+        writer.write(&named).unwrap();
+        writer.write(&unnamed).unwrap();
+        writer.close().unwrap();
+
+        let organisations = organisations_batch(&[]).unwrap();
+        assert_eq!(organisations.schema(), organisations_schema());
+    }
+}
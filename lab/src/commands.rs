@@ -1,4 +1,6 @@
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
+
+use sustainity_models::keywords::StopWords;
 
 /// Arguments of the `filter1` command.
 #[derive(Parser, Debug)]
@@ -16,6 +18,14 @@ pub struct Filtering1Args {
     /// Cache directory.
     #[arg(long)]
     pub cache: String,
+
+    /// Populate the cache from the Wikidata Query Service instead of the dump.
+    #[arg(long)]
+    pub sparql: bool,
+
+    /// SPARQL endpoint to use with `--sparql`.
+    #[arg(long)]
+    pub sparql_endpoint: Option<String>,
 }
 
 /// Arguments of the `filter2` command.
@@ -105,8 +115,62 @@ pub struct CondensationArgs {
     pub cache: String,
 
     /// Substrate directory.
+    #[arg(long, required_unless_present = "postgres")]
+    pub substrate: Option<String>,
+
+    /// URL of a PostgreSQL database to store the substrates in, instead of the substrate directory.
     #[arg(long)]
-    pub substrate: String,
+    pub postgres: Option<String>,
+
+    /// Directory to export records not matched to Wikidata into (one CSV file per source) and to
+    /// write the report of the statuses of the supplementary sources (`sources.csv`) into.
+    #[arg(long)]
+    pub unmatched: Option<String>,
+
+    /// Maximal number of entries read from each of the large data dumps (for development).
+    #[arg(long)]
+    pub max_entities: Option<usize>,
+
+    /// Process only a deterministic sample of the entities, e.g. `1/100` (for development).
+    #[arg(long)]
+    pub sample: Option<crate::limits::Sample>,
+
+    /// Abort on malformed Wikidata entities and unknown fields instead of skipping them.
+    #[arg(long)]
+    pub strict: bool,
+
+    /// CSV sources whose malformed rows are skipped instead of aborting the condensation.
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub lenient: Vec<CsvSource>,
+
+    /// Directory to write reports of malformed rows skipped in the lenient sources into.
+    #[arg(long)]
+    pub row_errors: Option<String>,
+
+    /// Supplementary sources left out with a warning if they fail to load, instead of aborting
+    /// the condensation.
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub skip_failed: Vec<SupplementarySource>,
+
+    /// Supplementary sources loaded from the last successfully loaded copy if they fail to load,
+    /// instead of aborting the condensation.
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub use_previous: Vec<SupplementarySource>,
+
+    /// Number of worker threads processing each of the large sources (defaults to the number of
+    /// CPUs).
+    #[arg(long)]
+    pub workers: Option<usize>,
+
+    /// Capacity of the channels between the pipeline stages (defaults to a value derived from the
+    /// size of memory).
+    #[arg(long)]
+    pub channel_capacity: Option<usize>,
+
+    /// Size in bytes of the buffers for reading the large data dumps (defaults to a value derived
+    /// from the size of memory).
+    #[arg(long)]
+    pub io_buffer_size: Option<usize>,
 }
 
 /// Arguments of the `crystalize` command.
@@ -126,6 +190,32 @@ pub struct CrystalizationArgs {
     /// Target data directory.
     #[arg(long)]
     pub target: String,
+
+    /// Compression of the output files.
+    #[arg(long, value_enum, default_value = "none")]
+    pub compression: Compression,
+
+    /// YAML file with source priorities for merged fields.
+    #[arg(long)]
+    pub policy: Option<String>,
+
+    /// YAML file with manual corrections of the data.
+    #[arg(long)]
+    pub overrides: Option<String>,
+
+    /// YAML file with certifications relevant for product categories.
+    #[arg(long)]
+    pub relevance: Option<String>,
+
+    /// Precompute this many best alternatives of each product per category and region (the
+    /// backend shows 10 by default).
+    #[arg(long)]
+    pub alternatives: Option<usize>,
+
+    /// Comma-separated languages of the stop words left out of the search keywords (e.g. "en,de").
+    /// All the supported languages are used if not set. Must match the backend configuration.
+    #[arg(long)]
+    pub stop_words: Option<StopWords>,
 }
 
 /// Arguments of the `oxidize` command.
@@ -147,6 +237,10 @@ pub struct OxidationArgs {
     /// Target data directory.
     #[arg(long)]
     pub target: String,
+
+    /// Compression of the output files.
+    #[arg(long, value_enum, default_value = "none")]
+    pub compression: Compression,
 }
 
 /// Arguments of the `analyse` command.
@@ -155,12 +249,26 @@ pub struct OxidationArgs {
     about = "Run an analysis of input data",
     long_about = "Runs an analysis of input data to find ways to improve the processing of those data.\n\n\
                   Currently this command only looks for entry classes in Wikidata and looks for those \
-                  contain but do not correspond to any product category."
+                  contain but do not correspond to any product category. The `uncategorised` mode \
+                  ranks the classes of the products which do not belong to any category, to help \
+                  deciding which categories to add next."
 )]
 pub struct AnalysisArgs {
     /// Cache directory.
     #[arg(long)]
     pub cache: String,
+
+    /// Kind of the analysis.
+    #[arg(long, value_enum, default_value = "classes")]
+    pub mode: AnalysisMode,
+
+    /// Number of the most frequent classes to report (in the `uncategorised` mode).
+    #[arg(long, default_value_t = 100)]
+    pub top: usize,
+
+    /// JSON Lines file to save the report into (in the `uncategorised` mode).
+    #[arg(long)]
+    pub report: Option<String>,
 }
 
 /// Arguments of the `connect` command.
@@ -183,6 +291,47 @@ pub struct ConnectionArgs {
     pub source: String,
 }
 
+/// Arguments of the `match` command.
+#[derive(Parser, Debug)]
+#[command(
+    about = "Propose Wikidata matches for companies known only by name",
+    long_about = "Ranks Wikidata organisations as candidates for the not yet matched companies from \
+                  BCorp, EU Ecolabel and Fashion Transparency Index data (and optionally companies \
+                  of products requested by the users) using name similarity, website domains and \
+                  countries. The candidates are written to a file which can be reviewed and merged \
+                  into the matches file."
+)]
+pub struct MatchArgs {
+    /// Path to the Wikidata dump (full or filtered).
+    #[arg(long)]
+    pub wikidata_path: String,
+
+    /// Directory with the original data.
+    #[arg(long)]
+    pub origin: String,
+
+    /// Directory with the source data (including the matches file).
+    #[arg(long)]
+    pub source: String,
+
+    /// Maximal number of candidates proposed for each company.
+    #[arg(long, default_value_t = 5)]
+    pub limit: usize,
+
+    /// Wikidata IDs of items missing in the dump to fetch from the API and consider as well.
+    #[arg(long, value_delimiter = ',')]
+    pub fetch: Vec<String>,
+
+    /// Cache directory for the fetched items.
+    #[arg(long)]
+    pub cache: Option<String>,
+
+    /// Product requests exported by the `requests` command (`product_requests.jsonl`). Their brands
+    /// (or the product names if the brand is not known) are matched as well.
+    #[arg(long)]
+    pub requests: Option<String>,
+}
+
 /// Arguments of the `sample` command.
 #[derive(Parser, Debug)]
 #[command(
@@ -197,6 +346,396 @@ pub struct SampleArgs {
     pub url: Option<String>,
 }
 
+/// Arguments of the `feedback` command.
+#[derive(Parser, Debug)]
+#[command(
+    about = "Turn accepted user feedback into matching overrides",
+    long_about = "Users can report wrong matches through the web service. After a moderator accepts \
+                  them, they can be exported from the backend (`GET /feedback?status=accepted`). \
+                  This command reads such an export and adds the corrections to the name matches \
+                  in the source directory, overriding any automatically found matches."
+)]
+pub struct FeedbackArgs {
+    /// Path to the exported feedback (JSON).
+    #[arg(long)]
+    pub input: String,
+
+    /// Source data directory.
+    #[arg(long)]
+    pub source: String,
+}
+
+/// Arguments of the `diff` command.
+#[derive(Parser, Debug)]
+#[command(
+    about = "Compare two crystalized data sets",
+    long_about = "Compares the target directories of two crystalizations and lists organisations \
+                  and products which were added, removed or re-scored. The changes are written to \
+                  the new target directory and after importing them the backend serves them on \
+                  the `/changes` endpoint."
+)]
+pub struct DiffArgs {
+    /// Target data directory of the previous data set.
+    #[arg(long)]
+    pub old: String,
+
+    /// Target data directory of the new data set.
+    #[arg(long)]
+    pub new: String,
+
+    /// Version of the previous data set.
+    #[arg(long)]
+    pub old_version: String,
+
+    /// Version of the new data set.
+    #[arg(long)]
+    pub new_version: String,
+
+    /// Compression of the output files.
+    #[arg(long, value_enum, default_value = "none")]
+    pub compression: Compression,
+}
+
+/// Arguments of the `import` command.
+#[derive(Parser, Debug)]
+#[command(
+    about = "Import a crystalized data set into the database",
+    long_about = "Loads the results of crystalization and oxidation from the target directory into \
+                  a database of the backend, creating the collections and indexes. Running the \
+                  command again replaces the previously imported data. The changes found by the \
+                  `diff` command (if present) are added to the shared changes database. The \
+                  password is read from the `SUSTAINITY_DB_PASSWORD` environment variable."
+)]
+pub struct ImportArgs {
+    /// Target data directory.
+    #[arg(long)]
+    pub target: String,
+
+    /// Name of the database to import into.
+    #[arg(long)]
+    pub database: String,
+
+    /// URL of the database server.
+    #[arg(long, default_value = "http://localhost:8529")]
+    pub url: String,
+
+    /// Database user.
+    #[arg(long, default_value = "root")]
+    pub user: String,
+}
+
+/// Arguments of the `requests` command.
+#[derive(Parser, Debug)]
+#[command(
+    about = "Export what the users looked for from the backend database",
+    long_about = "Reads all the requests for missing products and the search queries which \
+                  returned no results (counted over the days the backend retains them) from the \
+                  feedback database and writes them to the output directory. The product requests \
+                  can then be passed to the `match` command. The password is read from the \
+                  `SUSTAINITY_DB_PASSWORD` environment variable."
+)]
+pub struct RequestsArgs {
+    /// Output directory.
+    #[arg(long)]
+    pub output: String,
+
+    /// URL of the database server.
+    #[arg(long, default_value = "http://localhost:8529")]
+    pub url: String,
+
+    /// Database user.
+    #[arg(long, default_value = "root")]
+    pub user: String,
+}
+
+/// Sources distributed as CSV files.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CsvSource {
+    Bcorp,
+    EuEcolabel,
+    Euipo,
+    Gleif,
+    OpenFoodFacts,
+}
+
+impl CsvSource {
+    /// Returns the name of the source used in file names.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Bcorp => "bcorp",
+            Self::EuEcolabel => "eu_ecolabel",
+            Self::Euipo => "euipo",
+            Self::Gleif => "gleif",
+            Self::OpenFoodFacts => "open_food_facts",
+        }
+    }
+}
+
+/// Supplementary sources loaded before processing the large data dumps.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SupplementarySource {
+    WikidataCache,
+    Matches,
+    Spreadsheets,
+    Bcorp,
+    EuEcolabel,
+    Tco,
+    FashionTransparencyIndex,
+    OpenFoodFacts,
+    Gs1Prefixes,
+    Repairability,
+    Controversies,
+    TextileExchange,
+    LcaCoefficients,
+    CsrdReports,
+    Awards,
+    EntityLists,
+    PolicyPages,
+    Euipo,
+    Gleif,
+}
+
+impl SupplementarySource {
+    /// Returns the name of the source used in logs and file names.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::WikidataCache => "wikidata_cache",
+            Self::Matches => "matches",
+            Self::Spreadsheets => "spreadsheets",
+            Self::Bcorp => "bcorp",
+            Self::EuEcolabel => "eu_ecolabel",
+            Self::Tco => "tco",
+            Self::FashionTransparencyIndex => "fashion_transparency_index",
+            Self::OpenFoodFacts => "open_food_facts",
+            Self::Gs1Prefixes => "gs1_prefixes",
+            Self::Repairability => "repairability",
+            Self::Controversies => "controversies",
+            Self::TextileExchange => "textile_exchange",
+            Self::LcaCoefficients => "lca_coefficients",
+            Self::CsrdReports => "csrd_reports",
+            Self::Awards => "awards",
+            Self::EntityLists => "entity_lists",
+            Self::PolicyPages => "policy_pages",
+            Self::Euipo => "euipo",
+            Self::Gleif => "gleif",
+        }
+    }
+}
+
+/// Compression of the pipeline outputs.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+/// Kinds of the analysis run by the `analyze` command.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnalysisMode {
+    /// Classes of products not corresponding to any category, for the list of categories.
+    Classes,
+
+    /// Classes most frequent among products not belonging to any category, as a ranked report.
+    Uncategorised,
+}
+
+/// Output formats of the `export` command.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Single SQLite database with full-text search tables.
+    Sqlite,
+
+    /// Directory with one Parquet file per collection.
+    Parquet,
+
+    /// RDF graph in Turtle.
+    Turtle,
+
+    /// RDF graph in N-Triples.
+    NTriples,
+
+    /// Flat CSV file with one product per row.
+    Csv,
+}
+
+/// Columns of the CSV export.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CsvColumn {
+    Key,
+    Name,
+    Description,
+    Gtins,
+    Eans,
+    Wiki,
+    Categories,
+    Regions,
+    Manufacturers,
+    Score,
+    Bcorp,
+    EuEcolabel,
+    Fti,
+    Tco,
+}
+
+/// Arguments of the `export` command.
+#[derive(Parser, Debug)]
+#[command(
+    about = "Export a crystalized data set",
+    long_about = "Converts the results of crystalization from the target directory into a format \
+                  which can be used without running the backend, e.g. for offline apps or \
+                  analysis."
+)]
+pub struct ExportArgs {
+    /// Target data directory.
+    #[arg(long)]
+    pub target: String,
+
+    /// Path to the output file (or directory, for formats producing multiple files).
+    #[arg(long)]
+    pub output: String,
+
+    /// Output format.
+    #[arg(long, value_enum, default_value = "sqlite")]
+    pub format: ExportFormat,
+
+    /// Comma-separated list of columns to write (CSV only, all columns by default).
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub columns: Vec<CsvColumn>,
+}
+
+/// Kinds of the intermediate files.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileKind {
+    /// Crystalized products (`products.jsonl`).
+    Products,
+
+    /// Crystalized organisations (`organisations.jsonl`).
+    Organisations,
+
+    /// Library items (`library.jsonl`).
+    Library,
+
+    /// Presentations (`presentations.jsonl`).
+    Presentations,
+
+    /// Wikidata cache (`wikidata_cache.json`).
+    Cache,
+}
+
+/// Arguments of the `schemas` command.
+#[derive(Parser, Debug)]
+#[command(
+    about = "Write JSON Schemas of the intermediate files",
+    long_about = "Writes one JSON Schema per kind of the intermediate files into the output \
+                  directory, so the files can be verified by other tools."
+)]
+pub struct SchemasArgs {
+    /// Output directory.
+    #[arg(long)]
+    pub output: String,
+}
+
+/// Arguments of the `validate` command.
+#[derive(Parser, Debug)]
+#[command(
+    about = "Validate an intermediate file against its schema",
+    long_about = "Checks every record of the file against the JSON Schema of its kind and reports \
+                  the line and the location in the record of each violation."
+)]
+pub struct ValidationArgs {
+    /// Path to the file to validate (possibly compressed).
+    #[arg(long)]
+    pub input: String,
+
+    /// Kind of the file (inferred from the file name if not given).
+    #[arg(long, value_enum)]
+    pub kind: Option<FileKind>,
+}
+
+/// Arguments of the `fetch` command.
+#[derive(Parser, Debug)]
+#[command(
+    about = "Download the external data sources",
+    long_about = "Downloads the sources listed in the manifest into the origin directory, resuming \
+                  interrupted downloads and verifying checksums, and reports how old the files are."
+)]
+pub struct FetchArgs {
+    /// Origin data directory.
+    #[arg(long)]
+    pub origin: String,
+
+    /// YAML file listing the sources to download.
+    #[arg(long)]
+    pub manifest: String,
+
+    /// Age (in days) after which a downloaded file is considered stale.
+    #[arg(long, default_value_t = 30)]
+    pub max_age: i64,
+
+    /// Download again also the files which are stale.
+    #[arg(long)]
+    pub refresh: bool,
+}
+
+/// Arguments of the `fetch-wikidata` command.
+#[derive(Parser, Debug)]
+#[command(
+    about = "Fetch individual Wikidata entities",
+    long_about = "Fetches the given entities from the Wikidata API into the cache, e.g. items \
+                  created after the last dump. With `--output` the entities are appended to \
+                  the given dump file, one entity per line."
+)]
+pub struct EntityFetchArgs {
+    /// Cache directory.
+    #[arg(long)]
+    pub cache: String,
+
+    /// Wikidata IDs of the entities.
+    #[arg(long, value_delimiter = ',', required = true)]
+    pub ids: Vec<String>,
+
+    /// Fetch the entities again even if they are cached.
+    #[arg(long)]
+    pub refresh: bool,
+
+    /// Dump file to append the entities to.
+    #[arg(long)]
+    pub output: Option<String>,
+}
+
+/// Arguments of the `scrape` command.
+#[derive(Parser, Debug)]
+#[command(
+    about = "Extract tables from saved web pages",
+    long_about = "Scrapes the tables listed in the configuration from saved web pages using CSS \
+                  selectors and writes their rows as YAML files into the output directory."
+)]
+pub struct ScrapeArgs {
+    /// YAML file listing the table scrapers.
+    #[arg(long)]
+    pub config: String,
+
+    /// Output directory.
+    #[arg(long)]
+    pub output: String,
+}
+
+/// Arguments of the `completions` command.
+#[derive(Parser, Debug)]
+#[command(
+    about = "Print a shell completion script",
+    long_about = "Prints the completion script for the given shell to the standard output, e.g. \
+                  `sustainity-lab completions --shell bash > sustainity-lab.bash`."
+)]
+pub struct CompletionsArgs {
+    /// Shell to generate the script for.
+    #[arg(long, value_enum)]
+    pub shell: clap_complete::Shell,
+}
+
 /// All arguments of the program.
 #[derive(Subcommand, Debug)]
 pub enum Commands {
@@ -209,13 +748,37 @@ pub enum Commands {
     Oxidize(OxidationArgs),
     Analyze(AnalysisArgs),
     Connect(ConnectionArgs),
+    Match(MatchArgs),
     Sample(SampleArgs),
+    Feedback(FeedbackArgs),
+    Diff(DiffArgs),
+    Import(ImportArgs),
+    Requests(RequestsArgs),
+    Export(ExportArgs),
+    Schemas(SchemasArgs),
+    Validate(ValidationArgs),
+    Fetch(FetchArgs),
+    FetchWikidata(EntityFetchArgs),
+    Scrape(ScrapeArgs),
+    Completions(CompletionsArgs),
 }
 
 /// Program arguments.
+///
+/// Arguments not given on the command line can be provided in environment variables or
+/// a configuration file (see `cli`).
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 pub struct Args {
+    /// YAML file with default values of the arguments (environment variables named
+    /// `SUSTAINITY_<ARGUMENT>` take precedence over it).
+    #[arg(long, global = true)]
+    pub config: Option<String>,
+
+    /// Profile from the configuration file to use (e.g. `production` or `dev`).
+    #[arg(long, global = true)]
+    pub profile: Option<String>,
+
     /// Commands.
     #[command(subcommand)]
     pub command: Commands,
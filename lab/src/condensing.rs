@@ -1,5 +1,5 @@
 use std::{
-    collections::{hash_map::Entry, HashMap, HashSet},
+    collections::{hash_map::Entry, BTreeMap, HashMap, HashSet},
     sync::Arc,
 };
 
@@ -7,14 +7,20 @@ use async_trait::async_trait;
 use merge::Merge;
 
 use sustainity_collecting::{bcorp, eu_ecolabel, fashion_transparency_index, open_food_facts, tco};
-use sustainity_models::{gather as models, ids::WikiId};
+use sustainity_models::{
+    dates, gather as models, gtin,
+    ids::{self, WikiId},
+    social::SocialLink,
+};
 use sustainity_schema as schema;
 use sustainity_wikidata::data::{Entity, Item};
 
 use crate::{
-    advisors, categories, config, errors, parallel, runners, sources,
+    advisors, attributes, categories,
+    commands::CsvSource,
+    config, errors, parallel, postgresql, rows, runners, sources,
     sources::Sourceable,
-    utils,
+    unmatched, utils,
     wikidata::{ignored, ItemExt},
 };
 
@@ -47,6 +53,15 @@ pub trait Collector: Clone + Default + Send + merge::Merge {
     type About: Clone + Send;
 
     fn build_substrate(self, about: Self::About) -> schema::Root;
+
+    /// Takes out the records which could not be matched.
+    fn take_unmatched(&mut self) -> Vec<unmatched::Record>;
+
+    /// Takes out the product attributes.
+    fn take_attributes(&mut self) -> Vec<attributes::Record>;
+
+    /// Takes out the date of the snapshot of the source data, if the source provides it.
+    fn take_snapshot_date(&mut self) -> Option<String>;
 }
 
 /// Data storage for gathered data from a cataloger.
@@ -56,12 +71,18 @@ pub trait Collector: Clone + Default + Send + merge::Merge {
 pub struct CatalogerCollector {
     producers: HashMap<String, schema::CatalogProducer>,
     products: Vec<schema::CatalogProduct>,
+    unmatched: Vec<unmatched::Record>,
+    insufficient_data: unmatched::InsufficientData,
+    attributes: Vec<attributes::Record>,
 }
 
 impl merge::Merge for CatalogerCollector {
     fn merge(&mut self, other: Self) {
         utils::merge_hashmaps_with(&mut self.producers, other.producers, merge_catalog_producers);
         merge::vec::append(&mut self.products, other.products);
+        merge::vec::append(&mut self.unmatched, other.unmatched);
+        self.insufficient_data.merge(other.insufficient_data);
+        merge::vec::append(&mut self.attributes, other.attributes);
     }
 }
 
@@ -79,6 +100,20 @@ impl Collector for CatalogerCollector {
             products: self.products,
         })
     }
+
+    fn take_unmatched(&mut self) -> Vec<unmatched::Record> {
+        let mut unmatched = std::mem::take(&mut self.unmatched);
+        unmatched.extend(std::mem::take(&mut self.insufficient_data).into_records());
+        unmatched
+    }
+
+    fn take_attributes(&mut self) -> Vec<attributes::Record> {
+        std::mem::take(&mut self.attributes)
+    }
+
+    fn take_snapshot_date(&mut self) -> Option<String> {
+        None
+    }
 }
 
 impl CatalogerCollector {
@@ -101,6 +136,19 @@ impl CatalogerCollector {
     pub fn add_product(&mut self, product: schema::CatalogProduct) {
         self.products.push(product);
     }
+
+    pub fn add_unmatched(&mut self, record: unmatched::Record) {
+        self.unmatched.push(record);
+    }
+
+    /// Counts a record lacking the given data.
+    pub fn add_insufficient_data(&mut self, missing: &str) {
+        self.insufficient_data.add(missing);
+    }
+
+    pub fn add_attributes(&mut self, record: attributes::Record) {
+        self.attributes.push(record);
+    }
 }
 
 /// Data storage for gathered data from a reviewer.
@@ -110,12 +158,20 @@ impl CatalogerCollector {
 pub struct ReviewerCollector {
     producers: HashMap<String, schema::ReviewProducer>,
     products: Vec<schema::ReviewProduct>,
+    unmatched: Vec<unmatched::Record>,
+    attributes: Vec<attributes::Record>,
+
+    /// The latest snapshot date found in the source data.
+    snapshot_date: Option<String>,
 }
 
 impl merge::Merge for ReviewerCollector {
     fn merge(&mut self, other: Self) {
         utils::merge_hashmaps_with(&mut self.producers, other.producers, merge_review_producers);
         merge::vec::append(&mut self.products, other.products);
+        merge::vec::append(&mut self.unmatched, other.unmatched);
+        merge::vec::append(&mut self.attributes, other.attributes);
+        self.snapshot_date = self.snapshot_date.take().max(other.snapshot_date);
     }
 }
 
@@ -133,6 +189,18 @@ impl Collector for ReviewerCollector {
             products: self.products,
         })
     }
+
+    fn take_unmatched(&mut self) -> Vec<unmatched::Record> {
+        std::mem::take(&mut self.unmatched)
+    }
+
+    fn take_attributes(&mut self) -> Vec<attributes::Record> {
+        std::mem::take(&mut self.attributes)
+    }
+
+    fn take_snapshot_date(&mut self) -> Option<String> {
+        self.snapshot_date.take()
+    }
 }
 
 impl ReviewerCollector {
@@ -150,6 +218,19 @@ impl ReviewerCollector {
     pub fn push_product(&mut self, product: schema::ReviewProduct) {
         self.products.push(product);
     }
+
+    pub fn add_unmatched(&mut self, record: unmatched::Record) {
+        self.unmatched.push(record);
+    }
+
+    pub fn add_attributes(&mut self, record: attributes::Record) {
+        self.attributes.push(record);
+    }
+
+    /// Records the date of the snapshot of (a part of) the source data.
+    pub fn add_snapshot_date(&mut self, date: String) {
+        self.snapshot_date = self.snapshot_date.take().max(Some(date));
+    }
 }
 
 pub trait About {
@@ -160,6 +241,56 @@ pub trait About {
     fn build() -> <<Self as About>::Collector as Collector>::About;
 }
 
+/// Describes a certification substrate for the registry of supported certifications.
+fn describe_certification<A>(
+    issuer: &str,
+    covers: &str,
+    ingestion: &str,
+) -> models::CertificationInfo
+where
+    A: About<Collector = ReviewerCollector>,
+{
+    let about = A::build();
+    models::CertificationInfo {
+        db_key: A::name().to_owned(),
+        name: about.name,
+        issuer: issuer.to_owned(),
+        covers: covers.to_owned(),
+        website: about.website,
+        ingestion: ingestion.to_owned(),
+        snapshot_date: None,
+    }
+}
+
+/// Lists all the certifications supported by Sustainity.
+///
+/// The snapshot dates are not known at this point and are left unset.
+#[must_use]
+pub fn certification_registry() -> Vec<models::CertificationInfo> {
+    vec![
+        describe_certification::<AboutBCorp>(
+            "B Lab",
+            "Social and environmental performance of whole companies",
+            "Imported from the B Lab directory of certified companies (CSV export)",
+        ),
+        describe_certification::<AboutEu>(
+            "European Commission",
+            "Environmental impact of products during their whole life cycle",
+            "Imported from the EU Ecolabel product catalogue (CSV export)",
+        ),
+        describe_certification::<AboutFti>(
+            "Fashion Revolution",
+            "Public disclosure of human rights and environmental policies of fashion brands",
+            "Scores transcribed from the yearly Fashion Transparency Index report",
+        ),
+        describe_certification::<AboutTco>(
+            "TCO Development",
+            "Social and environmental responsibility of IT products and their manufacturers",
+            "Certified brands transcribed from the TCO Certified product finder",
+        ),
+    ]
+}
+
 #[derive(Clone)]
 struct AboutBCorp;
 
@@ -369,6 +500,10 @@ impl runners::WikidataWorker for CondensingWikidataWorker {
     ) -> Result<(), errors::ProcessingError> {
         match entity {
             Entity::Item(item) => {
+                if self.sources.is_blocked(&item) {
+                    return Ok(());
+                }
+
                 // Gather all products
                 if self.sources.is_product(&item) {
                     let categories = Self::extract_wikidata_categories(&item);
@@ -419,6 +554,44 @@ impl runners::WikidataWorker for CondensingWikidataWorker {
                             }),
                         };
 
+                        let mut attributes = item.get_attributes();
+                        attributes.extend(
+                            self.sources
+                                .repairability
+                                .get_battery_attributes(&item.get_characteristics()),
+                        );
+                        if let Some(repairability) =
+                            self.sources.repairability.get_attributes(&item.id)
+                        {
+                            attributes.extend(repairability.clone());
+                        }
+                        let variant_of: Vec<String> = item
+                            .get_edition_of()?
+                            .unwrap_or_default()
+                            .iter()
+                            .map(sustainity_collecting::data::WikiId::to_id)
+                            .collect();
+                        let external_ids = item.get_external_ids();
+                        if !attributes.is_empty()
+                            || !external_ids.is_empty()
+                            || !variant_of.is_empty()
+                        {
+                            self.collector.add_attributes(attributes::Record {
+                                subject: attributes::Subject::Product,
+                                id: product.id.clone(),
+                                attributes,
+                                recognitions: Vec::new(),
+                                controversies: Vec::new(),
+                                policy_pages: Vec::new(),
+                                external_ids,
+                                variant_of,
+                                owned_by: Vec::new(),
+                                leis: Vec::new(),
+                                registration_numbers: Vec::new(),
+                                validity: None,
+                            });
+                        }
+
                         self.collector.add_product(product);
                     }
                 }
@@ -438,8 +611,57 @@ impl runners::WikidataWorker for CondensingWikidataWorker {
                             .get(LANG_EN)
                             .map(|label| label.value.clone()),
                         images: item.get_logo_images().unwrap_or_default(),
-                        websites: item.get_official_websites().unwrap_or_default(),
+                        // The substrate has no field for the social media profiles,
+                        // they are recognised from the websites during crystalization.
+                        websites: item
+                            .get_official_websites()
+                            .unwrap_or_default()
+                            .into_iter()
+                            .chain(item.get_social_links().iter().map(SocialLink::url))
+                            .collect(),
                     };
+                    let mut attributes =
+                        self.sources.csrd.get_attributes(&item.id).unwrap_or_default();
+                    if let Some(standards) = self.sources.textile_exchange.get_attributes(&item.id)
+                    {
+                        attributes.extend(standards.clone());
+                    }
+                    let recognitions = self.sources.awards.recognitions(&item.get_awards());
+                    let controversies = self.sources.controversies.controversies(&item.id);
+                    let policy_pages = self
+                        .sources
+                        .policy_pages
+                        .pages(&item.extract_domains().unwrap_or_default());
+                    let owned_by: Vec<String> = item
+                        .get_parent_organisation_ids()?
+                        .unwrap_or_default()
+                        .iter()
+                        .map(sustainity_collecting::data::WikiId::to_id)
+                        .collect();
+                    let leis = item.get_leis();
+                    let registration_numbers = self.sources.gleif.registration_numbers(&leis);
+                    if !attributes.is_empty()
+                        || !recognitions.is_empty()
+                        || !controversies.is_empty()
+                        || !policy_pages.is_empty()
+                        || !owned_by.is_empty()
+                        || !leis.is_empty()
+                    {
+                        self.collector.add_attributes(attributes::Record {
+                            subject: attributes::Subject::Producer,
+                            id: producer.id.clone(),
+                            attributes,
+                            recognitions,
+                            controversies,
+                            policy_pages,
+                            external_ids: BTreeMap::new(),
+                            variant_of: Vec::new(),
+                            owned_by,
+                            leis,
+                            registration_numbers,
+                            validity: None,
+                        });
+                    }
                     self.collector.insert_producer(producer);
                 }
             }
@@ -469,11 +691,26 @@ impl CondensingOpenFoodFactsWorker {
         Self { collector: CatalogerCollector::default(), sources }
     }
 
-    /// Extracts categories from a Wikidata item.
+    /// Extracts categories from an Open Food Facts record.
+    ///
+    /// The category tags are mapped to the Sustainity categories. If none of them is mapped, the
+    /// food groups are used instead.
     fn extract_open_food_facts_categories(
         record: &open_food_facts::data::Record,
+        off: &advisors::OpenFoodFactsAdvisor,
     ) -> Vec<Vec<String>> {
         let mut result = Vec::<Vec<String>>::new();
+        for tag in record.extract_category_tags() {
+            if let Some(category) = off.get_category(&tag) {
+                if !result.contains(category) {
+                    result.push(category.clone());
+                }
+            }
+        }
+        if !result.is_empty() {
+            return result;
+        }
+
         for tag in record.food_groups_tags.split(',') {
             if tag.len() > 3 && tag.starts_with("en:") {
                 result.push(vec![tag[3..].to_string()]);
@@ -507,26 +744,61 @@ impl CondensingOpenFoodFactsWorker {
         }
     }
 
-    fn get_producer_id(record: &open_food_facts::data::Record) -> String {
-        utils::disambiguate_name(&record.brand_owner)
+    /// Extracts the packaging attributes and the estimated carbon footprint from an Open Food
+    /// Facts record.
+    fn extract_open_food_facts_attributes(
+        record: &open_food_facts::data::Record,
+        lca: &advisors::LcaAdvisor,
+    ) -> models::Attributes {
+        let mut attributes = lca
+            .estimate(&record.extract_category_tags(), &record.extract_ingredient_tags())
+            .unwrap_or_default();
+        let materials = record.extract_packaging_materials();
+        if !materials.is_empty() {
+            attributes.insert(
+                sustainity_models::attributes::PACKAGING_MATERIALS.to_owned(),
+                models::AttributeValue::Text(materials.join(",")),
+            );
+        }
+        if let Some(recyclable) = record.extract_packaging_recyclability() {
+            attributes.insert(
+                sustainity_models::attributes::RECYCLABLE_PACKAGING.to_owned(),
+                models::AttributeValue::Flag(recyclable),
+            );
+        }
+        attributes
     }
 
-    fn guess_producer_wiki_id(&self, record: &open_food_facts::data::Record) -> Option<WikiId> {
-        let name = Self::get_producer_id(record);
-        if let Some(wiki_id) = self.sources.matches.name_to_wiki(&name) {
+    /// Guesses the producer from its name, brand names, owners of trade marks matching the brand
+    /// names or (as the last resort) the GS1 company prefix of the product GTIN.
+    fn guess_producer_wiki_id(
+        &self,
+        record: &open_food_facts::data::Record,
+        producer_id: &str,
+        gtin: &str,
+    ) -> Option<WikiId> {
+        if let Some(wiki_id) = self.sources.matches.name_to_wiki(producer_id) {
             Some(WikiId::from(*wiki_id))
         } else {
+            let brands = record.extract_brand_labels();
             let mut matches = HashSet::<WikiId>::new();
-            for name in record.extract_brand_labels() {
-                let name = utils::disambiguate_name(&name);
-                if let Some(id) = self.sources.matches.name_to_wiki(&name) {
+            for name in &brands {
+                if let Some(id) = self.sources.matches.name_to_wiki(name) {
                     matches.insert(WikiId::from(*id));
                 }
             }
             if matches.len() == 1 {
                 return matches.iter().next().copied();
             }
-            None
+            let owners: HashSet<WikiId> = brands
+                .iter()
+                .filter_map(|name| self.sources.euipo.owner(name))
+                .map(|id| WikiId::from(*id))
+                .collect();
+            if owners.len() == 1 {
+                return owners.iter().next().copied();
+            }
+            self.sources.gs1.licensee(gtin).map(|id| WikiId::from(*id))
         }
     }
 }
@@ -540,53 +812,94 @@ impl runners::OpenFoodFactsWorker for CondensingOpenFoodFactsWorker {
         record: open_food_facts::data::Record,
         _tx: parallel::Sender<Self::Output>,
     ) -> Result<(), errors::ProcessingError> {
-        // Some products have very long bar code.
-        // Those are probably some internal bar codes, not GTINs.
-        // Let's ignore them for now.
-        if let Ok(gtin) = models::Gtin::try_from(&record.code) {
-            let categories = Self::extract_open_food_facts_categories(&record);
-            let producer_id = Self::get_producer_id(&record);
-
-            let product = schema::CatalogProduct {
-                id: gtin.to_string(),
-                ids: schema::ProductIds {
-                    ean: None,
-                    gtin: Some(vec![gtin.to_string()]),
-                    wiki: None,
+        // Products without a valid GTIN are ignored for now.
+        let Some(gtin) = record.gtin() else {
+            self.collector.add_unmatched(unmatched::Record::new(
+                unmatched::Reason::InvalidId,
+                record.code.clone(),
+                record.product_name,
+                record.code,
+            ));
+            return Ok(());
+        };
+
+        // Most Open Food Facts products have no Wikidata item, so they become products on their
+        // own. Only those which can be presented (have a name) and linked to a producer (have
+        // a brand) are kept, the rest would only clutter the search results.
+        if record.product_name.trim().is_empty() {
+            self.collector.add_insufficient_data("product_name");
+            return Ok(());
+        }
+        let Some(producer_name) = record.extract_producer_name() else {
+            self.collector.add_insufficient_data("brand");
+            return Ok(());
+        };
+
+        let categories = Self::extract_open_food_facts_categories(&record, &self.sources.off);
+        let producer_id = utils::disambiguate_name(&producer_name);
+        let product_id = ids::EntityId::from(ids::Gtin::try_from(&gtin)?).to_string();
+
+        let product = schema::CatalogProduct {
+            id: product_id.clone(),
+            ids: schema::ProductIds { ean: None, gtin: Some(vec![gtin.clone()]), wiki: None },
+            names: vec![record.product_name.clone()],
+            description: None,
+            images: vec![record.image_small_url.clone()],
+            categorisation: Some(schema::ProductCategorisation {
+                categories: categories.into_iter().map(schema::ProductCategory).collect(),
+            }),
+            origins: Some(schema::ProductOrigins { producer_ids: vec![producer_id.clone()] }),
+            availability: Some(schema::ProductAvailability {
+                regions: Self::extract_open_food_facts_sell_regions(&record, &self.sources.off),
+            }),
+            related: None,
+        };
+
+        self.collector.add_product(product);
+
+        let attributes = Self::extract_open_food_facts_attributes(&record, &self.sources.lca);
+        if !attributes.is_empty() {
+            self.collector.add_attributes(attributes::Record {
+                subject: attributes::Subject::Product,
+                id: product_id,
+                attributes,
+                recognitions: Vec::new(),
+                controversies: Vec::new(),
+                policy_pages: Vec::new(),
+                external_ids: BTreeMap::new(),
+                variant_of: Vec::new(),
+                owned_by: Vec::new(),
+                leis: Vec::new(),
+                registration_numbers: Vec::new(),
+                validity: None,
+            });
+        }
+
+        if !self.collector.has_producer(&producer_id) {
+            let wiki_id = self.guess_producer_wiki_id(&record, &producer_id, &gtin);
+            if wiki_id.is_none() {
+                self.collector.add_unmatched(unmatched::Record::new(
+                    unmatched::Reason::NoNameMatch,
+                    producer_id.clone(),
+                    producer_name.clone(),
+                    producer_name,
+                ));
+            }
+
+            let producer = schema::CatalogProducer {
+                id: producer_id,
+                ids: schema::ProducerIds {
+                    vat: None,
+                    wiki: wiki_id.map(|id| vec![id.to_canonical_string()]),
+                    domains: None,
                 },
-                names: vec![record.product_name.clone()],
                 description: None,
-                images: vec![record.image_small_url.clone()],
-                categorisation: Some(schema::ProductCategorisation {
-                    categories: categories.into_iter().map(schema::ProductCategory).collect(),
-                }),
-                origins: Some(schema::ProductOrigins { producer_ids: vec![producer_id.clone()] }),
-                availability: Some(schema::ProductAvailability {
-                    regions: Self::extract_open_food_facts_sell_regions(&record, &self.sources.off),
-                }),
-                related: None,
+                images: Vec::new(),
+                names: record.extract_brand_labels(),
+                websites: Vec::new(),
             };
 
-            self.collector.add_product(product);
-
-            if !self.collector.has_producer(&producer_id) {
-                let producer = schema::CatalogProducer {
-                    id: producer_id,
-                    ids: schema::ProducerIds {
-                        vat: None,
-                        wiki: self
-                            .guess_producer_wiki_id(&record)
-                            .map(|id| vec![id.to_canonical_string()]),
-                        domains: None,
-                    },
-                    description: None,
-                    images: Vec::new(),
-                    names: record.extract_brand_labels(),
-                    websites: Vec::new(),
-                };
-
-                self.collector.insert_producer(producer);
-            }
+            self.collector.insert_producer(producer);
         }
         Ok(())
     }
@@ -611,6 +924,22 @@ impl CondensingEuEcolabelWorker {
     pub fn new(sources: Arc<sources::FullSources>) -> Self {
         Self { collector: ReviewerCollector::default(), sources }
     }
+
+    /// Returns the date if it is in the expected `YYYY-MM-DD` form.
+    fn date(date: &str) -> Option<String> {
+        dates::parse(date).map(|_| date.to_owned())
+    }
+
+    /// Extracts the validity of the licence.
+    ///
+    /// The start of the validity is not provided.
+    fn extract_validity(record: &eu_ecolabel::data::Record) -> models::Validity {
+        models::Validity {
+            valid_from: None,
+            valid_until: Self::date(&record.expiration_date),
+            source_snapshot_date: Self::date(&record.extract_date),
+        }
+    }
 }
 
 #[async_trait]
@@ -622,17 +951,32 @@ impl runners::EuEcolabelWorker for CondensingEuEcolabelWorker {
         record: eu_ecolabel::data::Record,
         _tx: parallel::Sender<Self::Output>,
     ) -> Result<(), errors::ProcessingError> {
-        if let Some(vat_number) = record.vat_number {
+        if let Some(vat_number) = &record.vat_number {
+            let vat_id = models::VatId::with_country(vat_number, &record.company_country);
             let wiki_ids = self
                 .sources
                 .eu_ecolabel
-                .vat_to_wiki(&models::VatId::try_from(&vat_number)?)
+                .vat_to_wiki(&vat_id)
                 .map(|matching| vec![matching.wiki_id.to_id()]);
+            let vat_number = vat_id.to_canonical_string();
+            if wiki_ids.is_none() {
+                let reason = if vat_id.validate(false).is_ok() {
+                    unmatched::Reason::NoVatMatch
+                } else {
+                    unmatched::Reason::InvalidId
+                };
+                self.collector.add_unmatched(unmatched::Record::new(
+                    reason,
+                    record.licence_number.clone(),
+                    record.company_name.clone(),
+                    vat_number.clone(),
+                ));
+            }
 
             let producer = schema::ReviewProducer {
-                id: vat_number.to_string(),
+                id: vat_number.clone(),
                 ids: schema::ProducerIds {
-                    vat: Some(vec![vat_number.to_string()]),
+                    vat: Some(vec![vat_number.clone()]),
                     wiki: wiki_ids,
                     domains: None,
                 },
@@ -647,6 +991,20 @@ impl runners::EuEcolabelWorker for CondensingEuEcolabelWorker {
             };
 
             self.collector.insert_producer(producer);
+            self.collector.add_attributes(attributes::Record {
+                subject: attributes::Subject::Producer,
+                id: vat_number.clone(),
+                attributes: models::Attributes::default(),
+                recognitions: Vec::new(),
+                controversies: Vec::new(),
+                policy_pages: Vec::new(),
+                external_ids: BTreeMap::new(),
+                variant_of: Vec::new(),
+                owned_by: Vec::new(),
+                leis: Vec::new(),
+                registration_numbers: Vec::new(),
+                validity: Some(Self::extract_validity(&record)),
+            });
 
             let ids = match record.code {
                 Some(eu_ecolabel::data::Code::Ean13(code)) => Some((
@@ -657,14 +1015,14 @@ impl runners::EuEcolabelWorker for CondensingEuEcolabelWorker {
                         wiki: None,
                     },
                 )),
-                Some(eu_ecolabel::data::Code::Gtin14(code)) => Some((
-                    code.to_string(),
-                    schema::ProductIds {
-                        ean: None,
-                        gtin: Some(vec![code.to_string()]),
-                        wiki: None,
-                    },
-                )),
+                Some(eu_ecolabel::data::Code::Gtin14(code)) => {
+                    gtin::normalize(&format!("{code:0>14}")).ok().map(|gtin| {
+                        (
+                            gtin.clone(),
+                            schema::ProductIds { ean: None, gtin: Some(vec![gtin]), wiki: None },
+                        )
+                    })
+                }
                 Some(eu_ecolabel::data::Code::Internal(_) | eu_ecolabel::data::Code::Other(_))
                 | None => None,
             };
@@ -677,9 +1035,7 @@ impl runners::EuEcolabelWorker for CondensingEuEcolabelWorker {
                     summary: None,
                     images: Vec::new(),
                     categorisation: None,
-                    origins: Some(schema::ProductOrigins {
-                        producer_ids: vec![vat_number.to_string()],
-                    }),
+                    origins: Some(schema::ProductOrigins { producer_ids: vec![vat_number] }),
                     availability: None,
                     related: None,
                     report: None,
@@ -690,6 +1046,16 @@ impl runners::EuEcolabelWorker for CondensingEuEcolabelWorker {
 
                 self.collector.push_product(product);
             }
+        } else {
+            self.collector.add_unmatched(unmatched::Record::new(
+                unmatched::Reason::NoVat,
+                record.licence_number,
+                record.company_name,
+                String::new(),
+            ));
+        }
+        if let Some(date) = Self::date(&record.extract_date) {
+            self.collector.add_snapshot_date(date);
         }
         Ok(())
     }
@@ -725,6 +1091,18 @@ impl BCorpCondenser {
         ]
         .join("")
     }
+
+    /// Returns the unmatched record if the company has no website to match it by.
+    fn find_unmatched(record: &bcorp::data::Record, domain: &str) -> Option<unmatched::Record> {
+        domain.is_empty().then(|| {
+            unmatched::Record::new(
+                unmatched::Reason::NoDomain,
+                record.company_id.clone(),
+                record.company_name.clone(),
+                record.website.clone(),
+            )
+        })
+    }
 }
 
 #[async_trait]
@@ -735,14 +1113,26 @@ impl parallel::RefProducer for BCorpCondenser {
     async fn produce(&self, tx: parallel::Sender<Self::Output>) -> Result<(), Self::Error> {
         let mut collector = ReviewerCollector::default();
 
-        let data = bcorp::reader::parse(&self.config.bcorp_path)?;
+        let sheets = advisors::SpreadsheetsAdvisor::load(&self.config.spreadsheets_path)?;
+        let (data, skipped) = match sheets.get(CsvSource::Bcorp) {
+            Some(sheet) => bcorp::reader::parse_sheet(sheet, self.config.bcorp_rows.mode)?,
+            None => {
+                bcorp::reader::parse_with(&self.config.bcorp_path, self.config.bcorp_rows.mode)?
+            }
+        };
+        rows::report("BCorp", &skipped, &self.config.bcorp_rows)?;
         for record in data {
+            let domain = utils::extract_domain_from_url(&record.website);
+            if let Some(unmatched) = Self::find_unmatched(&record, &domain) {
+                collector.add_unmatched(unmatched);
+            }
+
             collector.insert_producer(schema::ReviewProducer {
                 id: record.company_id,
                 ids: schema::ProducerIds {
                     vat: None,
                     wiki: None,
-                    domains: Some(vec![utils::extract_domain_from_url(&record.website)]),
+                    domains: (!domain.is_empty()).then(|| vec![domain]),
                 },
                 names: vec![record.company_name.clone()],
                 description: None,
@@ -757,11 +1147,15 @@ impl parallel::RefProducer for BCorpCondenser {
             });
         }
 
+        let unmatched = collector.take_unmatched();
         let substrate = collector.build_substrate(AboutBCorp::build());
         tx.send(SaveMessage {
             name: AboutBCorp::name().to_owned(),
             variant: AboutBCorp::variant(),
             substrate,
+            unmatched,
+            attributes: Vec::new(),
+            snapshot_date: None,
         })
         .await;
 
@@ -792,6 +1186,15 @@ impl parallel::RefProducer for FtiCondenser {
             &self.config.fashion_transparency_index_path,
         )?;
         for entry in data {
+            if entry.wikidata_id.is_none() {
+                collector.add_unmatched(unmatched::Record::new(
+                    unmatched::Reason::NoNameMatch,
+                    entry.name.clone(),
+                    entry.name.clone(),
+                    entry.name.clone(),
+                ));
+            }
+
             collector.insert_producer(schema::ReviewProducer {
                 id: entry.name.clone(),
                 ids: schema::ProducerIds {
@@ -810,11 +1213,15 @@ impl parallel::RefProducer for FtiCondenser {
             });
         }
 
+        let unmatched = collector.take_unmatched();
         let substrate = collector.build_substrate(AboutFti::build());
         tx.send(SaveMessage {
             name: AboutFti::name().to_owned(),
             variant: AboutFti::variant(),
             substrate,
+            unmatched,
+            attributes: Vec::new(),
+            snapshot_date: None,
         })
         .await;
 
@@ -831,6 +1238,21 @@ impl TcoCondenser {
     pub fn new(config: config::SourcesConfig) -> Self {
         Self { config }
     }
+
+    /// Returns the unmatched record if the company is not a manufacturer known from Wikidata.
+    fn find_unmatched(
+        entry: &tco::data::Entry,
+        wikidata: &advisors::WikidataAdvisor,
+    ) -> Option<unmatched::Record> {
+        (!wikidata.has_manufacturer_id(&entry.wikidata_id)).then(|| {
+            unmatched::Record::new(
+                unmatched::Reason::UnknownWikiId,
+                entry.company_name.clone(),
+                entry.company_name.clone(),
+                entry.wikidata_id.to_id(),
+            )
+        })
+    }
 }
 
 #[async_trait]
@@ -841,8 +1263,13 @@ impl parallel::RefProducer for TcoCondenser {
     async fn produce(&self, tx: parallel::Sender<Self::Output>) -> Result<(), Self::Error> {
         let mut collector = ReviewerCollector::default();
 
+        let wikidata = advisors::WikidataAdvisor::load(&self.config.wikidata_cache_path)?;
         let data = tco::reader::parse(&self.config.tco_path)?;
         for entry in data {
+            if let Some(unmatched) = Self::find_unmatched(&entry, &wikidata) {
+                collector.add_unmatched(unmatched);
+            }
+
             collector.insert_producer(schema::ReviewProducer {
                 id: entry.company_name.clone(),
                 ids: schema::ProducerIds {
@@ -861,11 +1288,15 @@ impl parallel::RefProducer for TcoCondenser {
             });
         }
 
+        let unmatched = collector.take_unmatched();
         let substrate = collector.build_substrate(AboutTco::build());
         tx.send(SaveMessage {
             name: AboutTco::name().to_owned(),
             variant: AboutTco::variant(),
             substrate,
+            unmatched,
+            attributes: Vec::new(),
+            snapshot_date: None,
         })
         .await;
 
@@ -900,12 +1331,16 @@ where
         Ok(())
     }
 
-    async fn finish(self, tx: parallel::Sender<Self::Output>) -> Result<(), Self::Error> {
+    async fn finish(mut self, tx: parallel::Sender<Self::Output>) -> Result<(), Self::Error> {
         let name = A::name().to_owned();
         let about = A::build();
         let variant = A::variant();
+        let unmatched = self.collector.take_unmatched();
+        let attributes = self.collector.take_attributes();
+        let snapshot_date = self.collector.take_snapshot_date();
         let substrate = self.collector.build_substrate(about);
-        tx.send(SaveMessage { name, variant, substrate }).await;
+        tx.send(SaveMessage { name, variant, substrate, unmatched, attributes, snapshot_date })
+            .await;
         Ok(())
     }
 }
@@ -915,16 +1350,31 @@ pub struct SaveMessage {
     name: String,
     variant: schema::SubstrateExtension,
     substrate: schema::Root,
+
+    /// Records which could not be matched to Wikidata.
+    unmatched: Vec<unmatched::Record>,
+
+    /// Product attributes (saved next to the substrate).
+    attributes: Vec<attributes::Record>,
+
+    /// Date of the snapshot of the source data, if the source provides it.
+    snapshot_date: Option<String>,
 }
 
 pub struct SubstrateSaver {
     config: config::CondensationConfig,
+
+    /// Database to save the substrates to, if configured.
+    ///
+    /// Connected only in the saver thread, as the blocking client cannot be created from within
+    /// the async runtime.
+    store: Option<postgresql::SubstrateStore>,
 }
 
 impl SubstrateSaver {
     #[must_use]
     pub fn new(config: config::CondensationConfig) -> Self {
-        Self { config }
+        Self { config, store: None }
     }
 }
 
@@ -934,6 +1384,39 @@ impl parallel::Consumer for SubstrateSaver {
     type Error = errors::ProcessingError;
 
     async fn consume(&mut self, mut input: Self::Input) -> Result<(), Self::Error> {
+        if let Some(unmatched_path) = &self.config.unmatched_path {
+            if !input.unmatched.is_empty() {
+                let path = unmatched_path.join(&input.name).with_extension("csv");
+                log::info!("Saving {} unmatched records to {:?}", input.unmatched.len(), path);
+                unmatched::write(&path, std::mem::take(&mut input.unmatched))?;
+            }
+        }
+
+        if !input.attributes.is_empty() {
+            log::info!("Saving attributes of {} products", input.attributes.len());
+            let substrate_path = &self.config.substrate.substrate_path;
+            attributes::write(substrate_path, &input.name, std::mem::take(&mut input.attributes))?;
+        }
+
+        if let Some(snapshot_date) = input.snapshot_date.take() {
+            let substrate_path = &self.config.substrate.substrate_path;
+            let data_set = attributes::DataSet { snapshot_date: Some(snapshot_date) };
+            attributes::write_data_set(substrate_path, &input.name, &data_set)?;
+        }
+
+        input.substrate.sort();
+        if let Some(postgres) = &self.config.postgres {
+            let store = match self.store.take() {
+                Some(store) => store,
+                None => postgresql::SubstrateStore::connect(postgres)?,
+            };
+            let store = self.store.insert(store);
+            log::info!("Saving {} to the database", input.name);
+            store.save(&input.name, input.variant.as_str(), &input.substrate)?;
+            log::info!("Saved");
+            return Ok(());
+        }
+
         let path = self
             .config
             .substrate
@@ -941,7 +1424,6 @@ impl parallel::Consumer for SubstrateSaver {
             .join(&input.name)
             .with_extension(input.variant.as_str());
         log::info!("Saving {:?}", path);
-        input.substrate.sort();
         input.substrate.save(&path)?;
         log::info!("Saved");
         Ok(())
@@ -957,21 +1439,38 @@ pub struct CondensingRunner;
 
 impl CondensingRunner {
     pub fn run(config: &config::CondensationConfig) -> Result<(), errors::ProcessingError> {
-        let (wiki_process_tx, wiki_process_rx) = parallel::bounded::<String>();
-        let (wiki_combine_tx, wiki_combine_rx) = parallel::bounded::<CatalogerCollector>();
+        let limits = config.full_producer.wiki.limits;
+        if limits.max_entities.is_some() || limits.sample.is_some() {
+            log::warn!("Processing only a part of the data: {limits:?}");
+        }
+
+        let runtime = config.runtime;
+        log::info!("Runtime: {runtime:?}");
+        let cap = runtime.channel_capacity;
+
+        let (wiki_process_tx, wiki_process_rx) = parallel::bounded_with_capacity::<String>(cap);
+        let (wiki_combine_tx, wiki_combine_rx) =
+            parallel::bounded_with_capacity::<CatalogerCollector>(cap);
         let (off_process_tx, off_process_rx) =
-            parallel::bounded::<runners::OpenFoodFactsRunnerMessage>();
-        let (off_combine_tx, off_combine_rx) = parallel::bounded::<CatalogerCollector>();
+            parallel::bounded_with_capacity::<runners::OpenFoodFactsRunnerMessage>(cap);
+        let (off_combine_tx, off_combine_rx) =
+            parallel::bounded_with_capacity::<CatalogerCollector>(cap);
         let (eu_process_tx, eu_process_rx) =
-            parallel::bounded::<runners::EuEcolabelRunnerMessage>();
-        let (eu_combine_tx, eu_combine_rx) = parallel::bounded::<ReviewerCollector>();
-        let (save_tx, save_rx) = parallel::bounded::<SaveMessage>();
+            parallel::bounded_with_capacity::<runners::EuEcolabelRunnerMessage>(cap);
+        let (eu_combine_tx, eu_combine_rx) =
+            parallel::bounded_with_capacity::<ReviewerCollector>(cap);
+        let (save_tx, save_rx) = parallel::bounded_with_capacity::<SaveMessage>(cap);
 
         let sources = Arc::new(sources::FullSources::load(&config.into())?);
+        if let Some(unmatched_path) = &config.unmatched_path {
+            let path = unmatched_path.join(sources::REPORT_FILE_NAME);
+            sources::write_report(&path, &sources.statuses)?;
+        }
 
         let wiki_producer = runners::WikidataProducer::new(&config.into())?;
         let wiki_worker = CondensingWikidataWorker::new(sources.clone());
-        let wiki_worker = runners::WikidataProcessor::new(wiki_worker);
+        let wiki_worker =
+            runners::WikidataProcessor::new(wiki_worker, config.full_producer.wiki.strict);
         let wiki_combiner = Combiner::<AboutWiki>::default();
 
         let off_producer = runners::OpenFoodFactsProducer::new(config.into())?;
@@ -988,9 +1487,11 @@ impl CondensingRunner {
         let fti_producer = Box::new(FtiCondenser::new(config.sources.clone()));
         let tco_producer = Box::new(TcoCondenser::new(config.sources.clone()));
 
+        attributes::clear(&config.substrate.substrate_path)?;
         let saver = SubstrateSaver::new(config.clone());
 
         parallel::Flow::new()
+            .workers(runtime.workers)
             .name("saver")
             .spawn_consumer(saver, save_rx)?
             .name("wiki")
@@ -1012,3 +1513,219 @@ impl CondensingRunner {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use serde_json::Value;
+
+    use super::*;
+    use crate::commands::CondensationArgs;
+
+    /// Directory with the miniature input data and the golden outputs.
+    const DATA_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/condensing");
+
+    /// If set, the golden files are regenerated from the current outputs.
+    const UPDATE_VAR: &str = "SUSTAINITY_UPDATE_GOLDEN";
+
+    #[test]
+    fn unmatched_bcorp() {
+        let record = |website: &str| bcorp::data::Record {
+            company_id: "001".to_owned(),
+            company_name: "Acme".to_owned(),
+            current_status: bcorp::data::Status::Certified,
+            website: website.to_owned(),
+        };
+
+        let matched = record("https://www.acme.com/");
+        let domain = utils::extract_domain_from_url(&matched.website);
+        assert_eq!(BCorpCondenser::find_unmatched(&matched, &domain), None);
+
+        let unmatched = record("");
+        let domain = utils::extract_domain_from_url(&unmatched.website);
+        assert_eq!(
+            BCorpCondenser::find_unmatched(&unmatched, &domain),
+            Some(unmatched::Record::new(
+                unmatched::Reason::NoDomain,
+                "001".to_owned(),
+                "Acme".to_owned(),
+                String::new(),
+            ))
+        );
+    }
+
+    #[test]
+    fn unmatched_tco() {
+        use crate::wikidata::WikiId;
+
+        let wikidata = advisors::WikidataAdvisor::new(&crate::cache::Wikidata {
+            manufacturer_ids: vec![WikiId::new(1)],
+            classes: Vec::new(),
+        });
+        let entry = |id| tco::data::Entry { company_name: "Acme".to_owned(), wikidata_id: id };
+
+        assert_eq!(TcoCondenser::find_unmatched(&entry(WikiId::new(1)), &wikidata), None);
+        assert_eq!(
+            TcoCondenser::find_unmatched(&entry(WikiId::new(2)), &wikidata),
+            Some(unmatched::Record::new(
+                unmatched::Reason::UnknownWikiId,
+                "Acme".to_owned(),
+                "Acme".to_owned(),
+                WikiId::new(2).to_id(),
+            ))
+        );
+    }
+
+    /// Lists paths (relative to `root`) of all the files in `dir`, recursively.
+    ///
+    /// Manifests are skipped, as their checksums depend on the order of the lines.
+    fn list_files(root: &Path, dir: &Path, result: &mut Vec<PathBuf>) {
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                list_files(root, &path, result);
+            } else if path.file_name() != Some(crate::outputs::MANIFEST_FILE_NAME.as_ref()) {
+                result.push(path.strip_prefix(root).unwrap().to_owned());
+            }
+        }
+    }
+
+    /// Drops the parts of the output which differ between runs.
+    ///
+    /// The creation timestamps are removed and arrays of strings are sorted, as some of them are
+    /// collected from hash sets.
+    fn normalize(value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                map.remove("creation_timestamp");
+                map.values_mut().for_each(normalize);
+            }
+            Value::Array(items) => {
+                items.iter_mut().for_each(normalize);
+                if items.iter().all(Value::is_string) {
+                    items.sort_by(|a, b| a.as_str().cmp(&b.as_str()));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Reads an output file as JSON.
+    ///
+    /// Lines of JSON Lines files are sorted as the workers may finish in any order. CSV files are
+    /// read as arrays of lines. Gzipped files are decompressed first.
+    fn read_output(path: &Path) -> Value {
+        use std::io::Read;
+
+        let mut contents = String::new();
+        let path = if path.extension().and_then(std::ffi::OsStr::to_str) == Some("gz") {
+            let file = std::fs::File::open(path).unwrap();
+            flate2::read::GzDecoder::new(file).read_to_string(&mut contents).unwrap();
+            path.with_extension("")
+        } else {
+            contents = std::fs::read_to_string(path).unwrap();
+            path.to_owned()
+        };
+        let mut value = match path.extension().and_then(std::ffi::OsStr::to_str) {
+            Some("json") => serde_json::from_str(&contents).unwrap(),
+            Some("jsonl") => {
+                let mut lines: Vec<Value> =
+                    contents.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+                lines.iter_mut().for_each(normalize);
+                lines.sort_by_key(ToString::to_string);
+                Value::Array(lines)
+            }
+            _ => Value::Array(contents.lines().map(Value::from).collect()),
+        };
+        normalize(&mut value);
+        value
+    }
+
+    /// Returns the path of the golden file for the output.
+    fn golden_path(golden: &Path, output: &Path) -> PathBuf {
+        let mut path = golden.join(output).into_os_string();
+        path.push(".json");
+        PathBuf::from(path)
+    }
+
+    /// Runs the condensation on a tiny excerpt of the Wikidata dump and miniature source files and
+    /// compares the outputs with the golden files.
+    ///
+    /// Run with `SUSTAINITY_UPDATE_GOLDEN=1` after an intended change of the outputs and review the
+    /// diff of the golden files. Missing golden files are an error otherwise, so that a new output
+    /// cannot pass unreviewed.
+    #[test]
+    fn golden_data() {
+        let data = Path::new(DATA_DIR);
+        let output = tempfile::tempdir().unwrap();
+        let substrate = output.path().join("substrate");
+        let unmatched = output.path().join("unmatched");
+        std::fs::create_dir(&substrate).unwrap();
+        std::fs::create_dir(&unmatched).unwrap();
+
+        let path = |path: &Path| path.to_str().unwrap().to_owned();
+        let args = CondensationArgs {
+            origin: path(&data.join("origin")),
+            source: path(&data.join("source")),
+            cache: path(&data.join("cache")),
+            substrate: Some(path(&substrate)),
+            postgres: None,
+            unmatched: Some(path(&unmatched)),
+            max_entities: None,
+            sample: None,
+            strict: true,
+            lenient: Vec::new(),
+            row_errors: None,
+            skip_failed: Vec::new(),
+            use_previous: Vec::new(),
+            workers: Some(2),
+            channel_capacity: None,
+            io_buffer_size: None,
+        };
+        let config = config::CondensationConfig::new(&args);
+        config.check().unwrap();
+        CondensingRunner::run(&config).unwrap();
+
+        let golden = data.join("golden");
+        let update = std::env::var_os(UPDATE_VAR).is_some();
+        if update && golden.exists() {
+            std::fs::remove_dir_all(&golden).unwrap();
+        }
+
+        let mut outputs = Vec::new();
+        list_files(output.path(), output.path(), &mut outputs);
+        outputs.sort();
+        assert!(!outputs.is_empty());
+        for file in &outputs {
+            let actual = read_output(&output.path().join(file));
+            let expected_path = golden_path(&golden, file);
+            if update {
+                std::fs::create_dir_all(expected_path.parent().unwrap()).unwrap();
+                let contents = serde_json::to_string_pretty(&actual).unwrap() + "\n";
+                std::fs::write(&expected_path, contents).unwrap();
+                continue;
+            }
+            assert!(
+                expected_path.exists(),
+                "{expected_path:?} is missing, rerun with {UPDATE_VAR}=1 and commit it"
+            );
+            let expected: Value =
+                serde_json::from_str(&std::fs::read_to_string(&expected_path).unwrap()).unwrap();
+            assert!(
+                actual == expected,
+                "{file:?} differs from {expected_path:?}, rerun with {UPDATE_VAR}=1 if intended"
+            );
+        }
+
+        let mut goldens = Vec::new();
+        list_files(&golden, &golden, &mut goldens);
+        for file in goldens {
+            let expected = golden.join(&file);
+            assert!(
+                outputs.iter().any(|output| golden_path(&golden, output) == expected),
+                "{file:?} was not produced"
+            );
+        }
+    }
+}
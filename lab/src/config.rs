@@ -1,6 +1,96 @@
 use clap::Parser;
 
-use crate::{commands, errors::ConfigCheckError, utils};
+use sustainity_collecting::rows::RowMode;
+use sustainity_models::keywords::StopWords;
+
+use crate::{commands, errors::ConfigCheckError, limits, memory, utils};
+
+/// Size of the buffers for reading the input files, unless configured otherwise.
+pub const DEFAULT_IO_BUFFER_SIZE: usize = 8 * 1024;
+
+/// Capacity of the channels between the pipeline stages on machines with little memory.
+const MIN_CHANNEL_CAPACITY: usize = 100;
+
+/// Capacity of the channels between the pipeline stages on machines with plenty of memory.
+const MAX_CHANNEL_CAPACITY: usize = 10_000;
+
+/// Channel capacity added per GiB of memory.
+const CHANNEL_CAPACITY_PER_GIB: usize = 25;
+
+/// Memory above which large IO buffers are used.
+const LARGE_MEMORY: usize = 16 * 1024 * 1024 * 1024;
+
+/// Size of the IO buffers on machines with plenty of memory.
+const LARGE_IO_BUFFER_SIZE: usize = 1024 * 1024;
+
+/// Sizing of the threads, channels and buffers of a processing pipeline.
+///
+/// The defaults are derived from the number of CPUs and the size of memory of the machine.
+#[must_use]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RuntimeConfig {
+    /// Number of worker threads processing each of the large sources and of the async runtime.
+    pub workers: usize,
+
+    /// Capacity of the channels between the pipeline stages.
+    pub channel_capacity: usize,
+
+    /// Size of the buffers for reading the large input files.
+    pub io_buffer_size: usize,
+}
+
+impl RuntimeConfig {
+    /// Derives the sizing for a machine with `cpus` CPUs and `memory` bytes of memory.
+    pub fn for_machine(cpus: usize, memory: Option<usize>) -> Self {
+        let gib = memory.unwrap_or(0) / (1024 * 1024 * 1024);
+        let channel_capacity =
+            (gib * CHANNEL_CAPACITY_PER_GIB).clamp(MIN_CHANNEL_CAPACITY, MAX_CHANNEL_CAPACITY);
+        let io_buffer_size = if memory.is_some_and(|memory| memory >= LARGE_MEMORY) {
+            LARGE_IO_BUFFER_SIZE
+        } else {
+            DEFAULT_IO_BUFFER_SIZE
+        };
+        Self { workers: cpus.max(1), channel_capacity, io_buffer_size }
+    }
+
+    /// Derives the sizing for the current machine and applies the values given by the user.
+    pub fn new(
+        workers: Option<usize>,
+        channel_capacity: Option<usize>,
+        io_buffer_size: Option<usize>,
+    ) -> Self {
+        let default = Self::default();
+        Self {
+            workers: workers.unwrap_or(default.workers),
+            channel_capacity: channel_capacity.unwrap_or(default.channel_capacity),
+            io_buffer_size: io_buffer_size.unwrap_or(default.io_buffer_size),
+        }
+    }
+
+    /// Checks validity of the configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if any of the sizes is zero.
+    pub fn check(&self) -> Result<(), ConfigCheckError> {
+        for (name, value) in [
+            ("workers", self.workers),
+            ("channel capacity", self.channel_capacity),
+            ("IO buffer size", self.io_buffer_size),
+        ] {
+            if value == 0 {
+                return Err(ConfigCheckError::ZeroSize(name.to_owned()));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for RuntimeConfig {
+    fn default() -> Self {
+        Self::for_machine(num_cpus::get(), memory::total_memory())
+    }
+}
 
 /// Configuration for `WikidataGather`.
 #[must_use]
@@ -8,25 +98,49 @@ use crate::{commands, errors::ConfigCheckError, utils};
 pub struct WikidataProducerConfig {
     /// Path to Wikidata data.
     pub wikidata_path: std::path::PathBuf,
+
+    /// Limits for development runs.
+    pub limits: limits::Limits,
+
+    /// Abort on malformed entities and unknown fields instead of skipping them.
+    pub strict: bool,
+
+    /// Size of the buffer for reading the dump.
+    pub io_buffer_size: usize,
 }
 
 impl WikidataProducerConfig {
     /// Constructs a new `WikidataProducerConfig` with filteresd Wikidata dump.
     pub fn new_filtered(cache: &str) -> WikidataProducerConfig {
         let cache = std::path::PathBuf::from(&cache);
-        Self { wikidata_path: cache.join("wikidata.jsonl") }
+        Self {
+            wikidata_path: cache.join("wikidata.jsonl"),
+            limits: limits::Limits::default(),
+            strict: false,
+            io_buffer_size: DEFAULT_IO_BUFFER_SIZE,
+        }
     }
 
     /// Constructs a new `WikidataProducerConfig` with full Wikidata dump.
     pub fn new_full(origin: &str) -> WikidataProducerConfig {
         let origin = std::path::PathBuf::from(&origin);
-        Self { wikidata_path: origin.join("wikidata-20231120-all.json.gz") }
+        Self {
+            wikidata_path: origin.join("wikidata-20231120-all.json.gz"),
+            limits: limits::Limits::default(),
+            strict: false,
+            io_buffer_size: DEFAULT_IO_BUFFER_SIZE,
+        }
     }
 
     /// Constructs a new `WikidataProducerConfig`.
     pub fn new_with_path(path: &str) -> WikidataProducerConfig {
         let wikidata_path = std::path::PathBuf::from(&path);
-        Self { wikidata_path }
+        Self {
+            wikidata_path,
+            limits: limits::Limits::default(),
+            strict: false,
+            io_buffer_size: DEFAULT_IO_BUFFER_SIZE,
+        }
     }
 
     /// Checks validity of the configuration.
@@ -40,18 +154,77 @@ impl WikidataProducerConfig {
     }
 }
 
+/// Handling of malformed rows of a CSV source.
+#[must_use]
+#[derive(Debug, Clone, Default)]
+pub struct RowsConfig {
+    /// How to handle malformed rows.
+    pub mode: RowMode,
+
+    /// Path to the report of skipped malformed rows.
+    pub report_path: Option<std::path::PathBuf>,
+}
+
+impl RowsConfig {
+    /// Constructs a new `RowsConfig` for the source, lenient if it's listed in `lenient`.
+    pub fn new(
+        source: commands::CsvSource,
+        lenient: &[commands::CsvSource],
+        report_dir: Option<&str>,
+    ) -> Self {
+        if lenient.contains(&source) {
+            Self {
+                mode: RowMode::Lenient,
+                report_path: report_dir.map(|dir| {
+                    std::path::PathBuf::from(dir).join(source.name()).with_extension("csv")
+                }),
+            }
+        } else {
+            Self::default()
+        }
+    }
+}
+
+/// What to do when a supplementary source fails to load.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SourcePolicy {
+    /// Abort the processing.
+    #[default]
+    Fail,
+
+    /// Continue without the source, with a warning.
+    Skip,
+
+    /// Load the last copy of the source which was loaded successfully.
+    UsePrevious,
+}
+
 /// Configuration for `WikidataGatherer`.
 #[must_use]
 #[derive(Debug, Clone)]
 pub struct OpenFoodFactsProducerConfig {
     /// Path to Open Food Facts data.
     pub open_food_facts_path: std::path::PathBuf,
+
+    /// Limits for development runs.
+    pub limits: limits::Limits,
+
+    /// Handling of malformed rows.
+    pub rows: RowsConfig,
+
+    /// Size of the buffer for reading the data.
+    pub io_buffer_size: usize,
 }
 
 impl OpenFoodFactsProducerConfig {
     pub fn new(origin: &str) -> Self {
         let origin = std::path::PathBuf::from(origin);
-        Self { open_food_facts_path: origin.join("en.openfoodfacts.org.products.csv") }
+        Self {
+            open_food_facts_path: origin.join("en.openfoodfacts.org.products.csv"),
+            limits: limits::Limits::default(),
+            rows: RowsConfig::default(),
+            io_buffer_size: DEFAULT_IO_BUFFER_SIZE,
+        }
     }
 
     /// Checks validity of the configuration.
@@ -71,21 +244,35 @@ impl OpenFoodFactsProducerConfig {
 pub struct EuEcolabelProducerConfig {
     /// Path to Open Food Facts data.
     pub eu_ecolabel_path: std::path::PathBuf,
+
+    /// Path to sheet mappings of sources distributed as spreadsheets (optional).
+    pub spreadsheets_path: std::path::PathBuf,
+
+    /// Handling of malformed rows.
+    pub rows: RowsConfig,
 }
 
 impl EuEcolabelProducerConfig {
     pub fn new(origin: &str) -> Self {
         let origin = std::path::PathBuf::from(origin);
-        Self { eu_ecolabel_path: origin.join("eu_ecolabel_products.csv") }
+        Self {
+            eu_ecolabel_path: origin.join("eu_ecolabel_products.csv"),
+            spreadsheets_path: origin.join("spreadsheets.yaml"),
+            rows: RowsConfig::default(),
+        }
     }
 
     /// Checks validity of the configuration.
     ///
+    /// The CSV file is not required if the data may come from a spreadsheet.
+    ///
     /// # Errors
     ///
     /// Returns `Err` if paths expected to exist do not exist or paths expected to not exist do exist.
     pub fn check(&self) -> Result<(), ConfigCheckError> {
-        utils::path_exists(&self.eu_ecolabel_path)?;
+        if !utils::is_path_ok(&self.spreadsheets_path) {
+            utils::path_exists(&self.eu_ecolabel_path)?;
+        }
         Ok(())
     }
 }
@@ -114,6 +301,38 @@ impl FullProducerConfig {
         }
     }
 
+    /// Applies the limits to the large data dumps.
+    pub fn with_limits(mut self, limits: limits::Limits) -> FullProducerConfig {
+        self.wiki.limits = limits;
+        self.off.limits = limits;
+        self
+    }
+
+    /// Makes reading of the listed CSV sources lenient.
+    pub fn with_lenient(
+        mut self,
+        lenient: &[commands::CsvSource],
+        report_dir: Option<&str>,
+    ) -> FullProducerConfig {
+        self.off.rows = RowsConfig::new(commands::CsvSource::OpenFoodFacts, lenient, report_dir);
+        self.eu_ecolabel.rows =
+            RowsConfig::new(commands::CsvSource::EuEcolabel, lenient, report_dir);
+        self
+    }
+
+    /// Sets the size of the buffers for reading the large data dumps.
+    pub fn with_io_buffer_size(mut self, size: usize) -> FullProducerConfig {
+        self.wiki.io_buffer_size = size;
+        self.off.io_buffer_size = size;
+        self
+    }
+
+    /// Sets whether malformed Wikidata entities abort the processing.
+    pub fn with_strict(mut self, strict: bool) -> FullProducerConfig {
+        self.wiki.strict = strict;
+        self
+    }
+
     /// Checks validity of the configuration.
     ///
     /// # Errors
@@ -137,9 +356,30 @@ pub struct SourcesConfig {
     /// Path to B-Corp data.
     pub bcorp_path: std::path::PathBuf,
 
+    /// Handling of malformed rows of the B-Corp data.
+    pub bcorp_rows: RowsConfig,
+
     /// Path to original EU Ecolabel data.
     pub eu_ecolabel_original_path: std::path::PathBuf,
 
+    /// Handling of malformed rows of the original EU Ecolabel data.
+    pub eu_ecolabel_rows: RowsConfig,
+
+    /// Path to EUIPO trade mark data (optional).
+    pub euipo_path: std::path::PathBuf,
+
+    /// Handling of malformed rows of the EUIPO trade mark data.
+    pub euipo_rows: RowsConfig,
+
+    /// Path to the GLEIF golden copy of Legal Entity Identifiers (optional).
+    pub gleif_path: std::path::PathBuf,
+
+    /// Handling of malformed rows of the GLEIF data.
+    pub gleif_rows: RowsConfig,
+
+    /// Path to sheet mappings of sources distributed as spreadsheets (optional).
+    pub spreadsheets_path: std::path::PathBuf,
+
     /// Path to mapping from names to Wikidata IDs.
     pub match_path: std::path::PathBuf,
 
@@ -151,6 +391,45 @@ pub struct SourcesConfig {
 
     /// Path to file mapping Open Food Facts sell countries to Sustainity regions.
     pub open_food_facts_countries_path: std::path::PathBuf,
+
+    /// Path to file mapping Open Food Facts category tags to Sustainity categories (optional).
+    pub open_food_facts_categories_path: std::path::PathBuf,
+
+    /// Path to GS1 company prefix licence data (optional).
+    pub gs1_prefixes_path: std::path::PathBuf,
+
+    /// Path to repairability scores of products (optional).
+    pub repairability_path: std::path::PathBuf,
+
+    /// Path to Wikidata characteristics implying battery replaceability (optional).
+    pub battery_characteristics_path: std::path::PathBuf,
+
+    /// Path to the curated list of company controversies (optional).
+    pub controversies_path: std::path::PathBuf,
+
+    /// Path to the list of brands using Textile Exchange certified materials (optional).
+    pub textile_exchange_path: std::path::PathBuf,
+
+    /// Path to LCA coefficients of food categories and ingredients (optional).
+    pub lca_coefficients_path: std::path::PathBuf,
+
+    /// Path to the index of CSRD sustainability reports (optional).
+    pub csrd_reports_path: std::path::PathBuf,
+
+    /// Path to the list of sustainability-related awards (optional).
+    pub awards_path: std::path::PathBuf,
+
+    /// Path to lists of blocked and allowed entities (optional).
+    pub entity_lists_path: std::path::PathBuf,
+
+    /// Path to the pre-crawled sustainability and report pages of companies (optional).
+    pub policy_pages_path: std::path::PathBuf,
+
+    /// Policies for sources failing to load (`Fail` for the unlisted ones).
+    pub policies: std::collections::HashMap<commands::SupplementarySource, SourcePolicy>,
+
+    /// Directory with the last successfully loaded copies of the sources.
+    pub previous_path: std::path::PathBuf,
 }
 
 impl SourcesConfig {
@@ -162,23 +441,84 @@ impl SourcesConfig {
         Self {
             wikidata_cache_path: cache.join("wikidata_cache.json"),
             bcorp_path: origin.join("bcorp.csv"),
+            bcorp_rows: RowsConfig::default(),
             eu_ecolabel_original_path: origin.join("eu_ecolabel_products.csv"),
+            eu_ecolabel_rows: RowsConfig::default(),
+            euipo_path: origin.join("euipo_trademarks.csv"),
+            euipo_rows: RowsConfig::default(),
+            gleif_path: origin.join("gleif_lei.csv"),
+            gleif_rows: RowsConfig::default(),
+            spreadsheets_path: origin.join("spreadsheets.yaml"),
             match_path: source.join("matches.yaml"),
             tco_path: source.join("tco.yaml"),
             fashion_transparency_index_path: source.join("fashion_transparency_index.yaml"),
             open_food_facts_countries_path: source.join("open_food_facts_countries.yaml"),
+            open_food_facts_categories_path: source.join("open_food_facts_categories.yaml"),
+            gs1_prefixes_path: source.join("gs1_prefixes.yaml"),
+            repairability_path: source.join("repairability.yaml"),
+            battery_characteristics_path: source.join("battery_characteristics.yaml"),
+            controversies_path: source.join("controversies.yaml"),
+            textile_exchange_path: source.join("textile_exchange.yaml"),
+            lca_coefficients_path: source.join("lca_coefficients.yaml"),
+            csrd_reports_path: source.join("csrd_reports.yaml"),
+            awards_path: source.join("awards.yaml"),
+            entity_lists_path: source.join("entity_lists.yaml"),
+            policy_pages_path: source.join("policy_pages.yaml"),
+            policies: std::collections::HashMap::new(),
+            previous_path: cache.join("previous_sources"),
+        }
+    }
+
+    /// Sets the policies for sources failing to load.
+    ///
+    /// If a source is listed in both, `use_previous` takes precedence.
+    pub fn with_policies(
+        mut self,
+        skip_failed: &[commands::SupplementarySource],
+        use_previous: &[commands::SupplementarySource],
+    ) -> SourcesConfig {
+        for source in skip_failed {
+            self.policies.insert(*source, SourcePolicy::Skip);
+        }
+        for source in use_previous {
+            self.policies.insert(*source, SourcePolicy::UsePrevious);
         }
+        self
+    }
+
+    /// Returns the policy for the source failing to load.
+    #[must_use]
+    pub fn policy(&self, source: commands::SupplementarySource) -> SourcePolicy {
+        self.policies.get(&source).copied().unwrap_or_default()
+    }
+
+    /// Makes reading of the listed CSV sources lenient.
+    pub fn with_lenient(
+        mut self,
+        lenient: &[commands::CsvSource],
+        report_dir: Option<&str>,
+    ) -> SourcesConfig {
+        self.bcorp_rows = RowsConfig::new(commands::CsvSource::Bcorp, lenient, report_dir);
+        self.eu_ecolabel_rows =
+            RowsConfig::new(commands::CsvSource::EuEcolabel, lenient, report_dir);
+        self.euipo_rows = RowsConfig::new(commands::CsvSource::Euipo, lenient, report_dir);
+        self.gleif_rows = RowsConfig::new(commands::CsvSource::Gleif, lenient, report_dir);
+        self
     }
 
     /// Checks validity of the configuration.
     ///
+    /// The CSV files are not required if the data may come from spreadsheets.
+    ///
     /// # Errors
     ///
     /// Returns `Err` if paths expected to exist do not exist or paths expected to not exist do exist.
     pub fn check(&self) -> Result<(), ConfigCheckError> {
         utils::path_exists(&self.wikidata_cache_path)?;
-        utils::path_exists(&self.bcorp_path)?;
-        utils::path_exists(&self.eu_ecolabel_original_path)?;
+        if !utils::is_path_ok(&self.spreadsheets_path) {
+            utils::path_exists(&self.bcorp_path)?;
+            utils::path_exists(&self.eu_ecolabel_original_path)?;
+        }
         utils::path_exists(&self.match_path)?;
         utils::path_exists(&self.tco_path)?;
         utils::path_exists(&self.fashion_transparency_index_path)?;
@@ -223,6 +563,45 @@ impl SubstrateConfig {
     }
 }
 
+/// Subconfiguration related to the PostgreSQL database.
+#[must_use]
+#[derive(Debug, Clone)]
+pub struct PostgresConfig {
+    /// Connection URL.
+    pub url: String,
+}
+
+/// Configuration for populating the Wikidata cache with SPARQL queries.
+#[must_use]
+#[derive(Debug, Clone)]
+pub struct SparqlConfig {
+    /// URL of the SPARQL endpoint.
+    pub endpoint: String,
+
+    /// Directory for caching the fetched pages of results.
+    pub cache_path: std::path::PathBuf,
+
+    /// Number of results fetched with a single query.
+    pub page_size: usize,
+}
+
+impl SparqlConfig {
+    /// Endpoint of the Wikidata Query Service.
+    pub const DEFAULT_ENDPOINT: &'static str = "https://query.wikidata.org/sparql";
+
+    /// Default number of results fetched with a single query.
+    pub const DEFAULT_PAGE_SIZE: usize = 100_000;
+
+    /// Constructs a new `SparqlConfig`.
+    pub fn new(endpoint: Option<&str>, cache: &std::path::Path) -> SparqlConfig {
+        Self {
+            endpoint: endpoint.unwrap_or(Self::DEFAULT_ENDPOINT).to_owned(),
+            cache_path: cache.join("sparql"),
+            page_size: Self::DEFAULT_PAGE_SIZE,
+        }
+    }
+}
+
 /// Configuration for the `filter1` command.
 #[must_use]
 #[derive(Debug, Clone)]
@@ -232,6 +611,9 @@ pub struct Filtering1Config {
 
     /// `WikidataGatherer` config.
     pub wikidata_gatherer: WikidataProducerConfig,
+
+    /// Populate the cache with SPARQL queries instead of processing the dump.
+    pub sparql: Option<SparqlConfig>,
 }
 
 impl Filtering1Config {
@@ -241,17 +623,22 @@ impl Filtering1Config {
         Self {
             wikidata_cache_path: cache.join("wikidata_cache.json"),
             wikidata_gatherer: WikidataProducerConfig::new_full(&args.origin),
+            sparql: args.sparql.then(|| SparqlConfig::new(args.sparql_endpoint.as_deref(), &cache)),
         }
     }
 
     /// Checks validity of the configuration.
     ///
+    /// The dump is not required when populating the cache with SPARQL queries.
+    ///
     /// # Errors
     ///
     /// Returns `Err` if paths expected to exist do not exist or paths expected to not exist do exist.
     pub fn check(&self) -> Result<(), ConfigCheckError> {
         utils::path_creatable(&self.wikidata_cache_path)?;
-        self.wikidata_gatherer.check()?;
+        if self.sparql.is_none() {
+            self.wikidata_gatherer.check()?;
+        }
         Ok(())
     }
 }
@@ -308,8 +695,12 @@ pub struct FilteringConfig {
 impl FilteringConfig {
     /// Constructs a new `Filtering2Config`.
     pub fn new(args: &commands::FilteringArgs) -> FilteringConfig {
-        let filter1 =
-            commands::Filtering1Args { origin: args.origin.clone(), cache: args.cache.clone() };
+        let filter1 = commands::Filtering1Args {
+            origin: args.origin.clone(),
+            cache: args.cache.clone(),
+            sparql: false,
+            sparql_endpoint: None,
+        };
         let filter2 = commands::Filtering2Args {
             origin: args.origin.clone(),
             source: args.source.clone(),
@@ -368,15 +759,35 @@ pub struct CondensationConfig {
 
     /// Substrate config.
     pub substrate: SubstrateConfig,
+
+    /// Database to store the substrates in instead of the substrate directory.
+    pub postgres: Option<PostgresConfig>,
+
+    /// Directory for the exports of unmatched records and the report of the sources.
+    pub unmatched_path: Option<std::path::PathBuf>,
+
+    /// Sizing of the pipeline.
+    pub runtime: RuntimeConfig,
 }
 
 impl CondensationConfig {
     /// Constructs a new `CondensationConfig`.
     pub fn new(args: &commands::CondensationArgs) -> CondensationConfig {
+        let limits = limits::Limits::new(args.max_entities, args.sample);
+        let runtime = RuntimeConfig::new(args.workers, args.channel_capacity, args.io_buffer_size);
         Self {
-            sources: SourcesConfig::new(&args.origin, &args.source, &args.cache),
-            full_producer: FullProducerConfig::new(&args.origin, &args.cache),
-            substrate: SubstrateConfig::new(&args.substrate),
+            sources: SourcesConfig::new(&args.origin, &args.source, &args.cache)
+                .with_lenient(&args.lenient, args.row_errors.as_deref())
+                .with_policies(&args.skip_failed, &args.use_previous),
+            full_producer: FullProducerConfig::new(&args.origin, &args.cache)
+                .with_limits(limits)
+                .with_strict(args.strict)
+                .with_lenient(&args.lenient, args.row_errors.as_deref())
+                .with_io_buffer_size(runtime.io_buffer_size),
+            substrate: SubstrateConfig::new(args.substrate.as_deref().unwrap_or_default()),
+            postgres: args.postgres.as_ref().map(|url| PostgresConfig { url: url.clone() }),
+            unmatched_path: args.unmatched.as_ref().map(std::path::PathBuf::from),
+            runtime,
         }
     }
 
@@ -388,7 +799,13 @@ impl CondensationConfig {
     pub fn check(&self) -> Result<(), ConfigCheckError> {
         self.sources.check()?;
         self.full_producer.check()?;
-        self.substrate.check_write()?;
+        self.runtime.check()?;
+        if self.postgres.is_none() {
+            self.substrate.check_write()?;
+        }
+        if let Some(unmatched_path) = &self.unmatched_path {
+            utils::dir_exists(unmatched_path)?;
+        }
         Ok(())
     }
 }
@@ -407,6 +824,8 @@ pub struct TargetConfig {
     pub organisation_wiki_id_edges_path: std::path::PathBuf,
     pub organisation_wwws_path: std::path::PathBuf,
     pub organisation_www_edges_path: std::path::PathBuf,
+    pub organisation_identifiers_path: std::path::PathBuf,
+    pub organisation_labels_path: std::path::PathBuf,
     pub products_path: std::path::PathBuf,
     pub product_keywords_path: std::path::PathBuf,
     pub product_keyword_edges_path: std::path::PathBuf,
@@ -418,7 +837,16 @@ pub struct TargetConfig {
     pub product_wiki_id_edges_path: std::path::PathBuf,
     pub categories_path: std::path::PathBuf,
     pub category_edges_path: std::path::PathBuf,
+    pub category_stats_path: std::path::PathBuf,
+    pub product_alternatives_path: std::path::PathBuf,
+    pub certifications_path: std::path::PathBuf,
     pub manufacturing_edges_path: std::path::PathBuf,
+    pub graph_edges_path: std::path::PathBuf,
+    pub organisation_resolutions_path: std::path::PathBuf,
+    pub conflicts_path: std::path::PathBuf,
+
+    /// Compression of the output files.
+    pub compression: commands::Compression,
 }
 
 /// Configuration for the `crystalize` command.
@@ -433,6 +861,21 @@ pub struct CrystalizationConfig {
 
     /// Data substrate.
     pub substrate: SubstrateConfig,
+
+    /// Policy for resolving conflicts between sources (the default policy is used if not set).
+    pub policy_path: Option<std::path::PathBuf>,
+
+    /// Manual corrections applied as the last step.
+    pub overrides_path: Option<std::path::PathBuf>,
+
+    /// Certifications relevant for product categories (all are relevant if not set).
+    pub relevance_path: Option<std::path::PathBuf>,
+
+    /// Number of precomputed alternatives per product, category and region (none if not set).
+    pub num_alternatives: Option<usize>,
+
+    /// Words left out of the search keywords.
+    pub stop_words: StopWords,
 }
 
 impl CrystalizationConfig {
@@ -450,6 +893,8 @@ impl CrystalizationConfig {
                 organisation_wiki_id_edges_path: target.join("organisation_wiki_id_edges.jsonl"),
                 organisation_wwws_path: target.join("organisation_wwws.jsonl"),
                 organisation_www_edges_path: target.join("organisation_www_edges.jsonl"),
+                organisation_identifiers_path: target.join("organisation_identifiers.jsonl"),
+                organisation_labels_path: target.join("organisation_labels.jsonl"),
                 products_path: target.join("products.jsonl"),
                 product_keywords_path: target.join("product_keywords.jsonl"),
                 product_keyword_edges_path: target.join("product_keyword_edges.jsonl"),
@@ -461,10 +906,22 @@ impl CrystalizationConfig {
                 product_wiki_id_edges_path: target.join("product_wiki_id_edges.jsonl"),
                 categories_path: target.join("categories.jsonl"),
                 category_edges_path: target.join("category_edges.jsonl"),
+                category_stats_path: target.join("category_stats.jsonl"),
+                product_alternatives_path: target.join("product_alternatives.jsonl"),
+                certifications_path: target.join("certifications.jsonl"),
                 manufacturing_edges_path: target.join("manufacturing_edges.jsonl"),
+                graph_edges_path: target.join("graph_edges.jsonl"),
+                organisation_resolutions_path: target.join("organisation_resolutions.jsonl"),
+                conflicts_path: target.join("conflicts.jsonl"),
+                compression: args.compression,
             }),
             local_storage_runtime: target.join("local_storage_runtime"),
             substrate: SubstrateConfig::new(&args.substrate),
+            policy_path: args.policy.as_ref().map(std::path::PathBuf::from),
+            overrides_path: args.overrides.as_ref().map(std::path::PathBuf::from),
+            relevance_path: args.relevance.as_ref().map(std::path::PathBuf::from),
+            num_alternatives: args.alternatives,
+            stop_words: args.stop_words.clone().unwrap_or_default(),
         }
     }
 
@@ -483,6 +940,8 @@ impl CrystalizationConfig {
         utils::path_creatable(&self.target.organisation_wiki_id_edges_path)?;
         utils::path_creatable(&self.target.organisation_wwws_path)?;
         utils::path_creatable(&self.target.organisation_www_edges_path)?;
+        utils::path_creatable(&self.target.organisation_identifiers_path)?;
+        utils::path_creatable(&self.target.organisation_labels_path)?;
         utils::path_creatable(&self.target.products_path)?;
         utils::path_creatable(&self.target.product_keywords_path)?;
         utils::path_creatable(&self.target.product_keyword_edges_path)?;
@@ -494,7 +953,22 @@ impl CrystalizationConfig {
         utils::path_creatable(&self.target.product_wiki_id_edges_path)?;
         utils::path_creatable(&self.target.categories_path)?;
         utils::path_creatable(&self.target.category_edges_path)?;
+        utils::path_creatable(&self.target.category_stats_path)?;
+        utils::path_creatable(&self.target.product_alternatives_path)?;
+        utils::path_creatable(&self.target.certifications_path)?;
         utils::path_creatable(&self.target.manufacturing_edges_path)?;
+        utils::path_creatable(&self.target.graph_edges_path)?;
+        utils::path_creatable(&self.target.organisation_resolutions_path)?;
+        utils::path_creatable(&self.target.conflicts_path)?;
+        if let Some(policy_path) = &self.policy_path {
+            utils::path_exists(policy_path)?;
+        }
+        if let Some(overrides_path) = &self.overrides_path {
+            utils::path_exists(overrides_path)?;
+        }
+        if let Some(relevance_path) = &self.relevance_path {
+            utils::path_exists(relevance_path)?;
+        }
         self.substrate.check_read()?;
         utils::path_creatable(&self.local_storage_runtime)?;
         Ok(())
@@ -517,8 +991,17 @@ pub struct OxidationConfig {
     /// Path to Fashion Transparency Index data.
     pub fashion_transparency_index_path: std::path::PathBuf,
 
+    /// Path to the configuration of the presentations (optional).
+    pub presentations_config_path: std::path::PathBuf,
+
+    /// Path to the crystalized organisations.
+    pub organisations_path: std::path::PathBuf,
+
     /// Path to the output presentations file.
     pub presentations_path: std::path::PathBuf,
+
+    /// Compression of the output files.
+    pub compression: commands::Compression,
 }
 
 impl OxidationConfig {
@@ -532,7 +1015,10 @@ impl OxidationConfig {
             library_dir_path: library,
             library_target_path: target.join("library.jsonl"),
             fashion_transparency_index_path: source.join("fashion_transparency_index.yaml"),
+            presentations_config_path: source.join("presentations.yaml"),
+            organisations_path: target.join("organisations.jsonl"),
             presentations_path: target.join("presentations.jsonl"),
+            compression: args.compression,
         }
     }
 
@@ -560,6 +1046,15 @@ pub struct AnalysisConfig {
 
     /// `Wikidatagatherer` config.
     pub wikidata_gatherer: WikidataProducerConfig,
+
+    /// Kind of the analysis.
+    pub mode: commands::AnalysisMode,
+
+    /// Number of the most frequent classes to report.
+    pub top: usize,
+
+    /// Path to save the report into (the report is only printed if not set).
+    pub report_path: Option<std::path::PathBuf>,
 }
 
 impl AnalysisConfig {
@@ -569,6 +1064,9 @@ impl AnalysisConfig {
         Self {
             wikidata_cache_path: cache.join("wikidata_cache.json"),
             wikidata_gatherer: WikidataProducerConfig::new_filtered(&args.cache),
+            mode: args.mode,
+            top: args.top,
+            report_path: args.report.as_ref().map(std::path::PathBuf::from),
         }
     }
 
@@ -580,6 +1078,9 @@ impl AnalysisConfig {
     pub fn check(&self) -> Result<(), ConfigCheckError> {
         utils::path_exists(&self.wikidata_cache_path)?;
         self.wikidata_gatherer.check()?;
+        if let Some(report_path) = &self.report_path {
+            utils::path_creatable(report_path)?;
+        }
         Ok(())
     }
 }
@@ -628,6 +1129,78 @@ impl ConnectionConfig {
     }
 }
 
+/// Configuration for the `match` command.
+#[must_use]
+#[derive(Clone, Debug)]
+pub struct MatchConfig {
+    /// Path to B-Corp data.
+    pub bcorp_path: std::path::PathBuf,
+
+    /// Path to original EU Ecolabel data.
+    pub eu_ecolabel_path: std::path::PathBuf,
+
+    /// Path to Fashion Transparency Index data.
+    pub fti_path: std::path::PathBuf,
+
+    /// Path to mapping from names to Wikidata IDs.
+    pub match_path: std::path::PathBuf,
+
+    /// Path to the output candidates file.
+    pub output_path: std::path::PathBuf,
+
+    /// Maximal number of candidates per company.
+    pub limit: usize,
+
+    /// Wikidata IDs of items to fetch from the API in addition to the dump.
+    pub fetch_ids: Vec<String>,
+
+    /// Cache directory for the fetched items.
+    pub cache_path: Option<std::path::PathBuf>,
+
+    /// Path to the product requests exported by the `requests` command.
+    pub requests_path: Option<std::path::PathBuf>,
+
+    /// `WikidataGatherer` config.
+    pub wikidata_gatherer: WikidataProducerConfig,
+}
+
+impl MatchConfig {
+    /// Constructs a new `MatchConfig`.
+    pub fn new(args: &commands::MatchArgs) -> MatchConfig {
+        let origin = std::path::PathBuf::from(&args.origin);
+        let source = std::path::PathBuf::from(&args.source);
+        Self {
+            bcorp_path: origin.join("bcorp.csv"),
+            eu_ecolabel_path: origin.join("eu_ecolabel_products.csv"),
+            fti_path: source.join("fashion_transparency_index.yaml"),
+            match_path: source.join("matches.yaml"),
+            output_path: source.join("match_candidates.yaml"),
+            limit: args.limit,
+            fetch_ids: args.fetch.clone(),
+            cache_path: args.cache.as_ref().map(std::path::PathBuf::from),
+            requests_path: args.requests.as_ref().map(std::path::PathBuf::from),
+            wikidata_gatherer: WikidataProducerConfig::new_with_path(&args.wikidata_path),
+        }
+    }
+
+    /// Checks validity of the configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if paths expected to exist do not exist.
+    pub fn check(&self) -> Result<(), ConfigCheckError> {
+        utils::path_exists(&self.bcorp_path)?;
+        utils::path_exists(&self.eu_ecolabel_path)?;
+        utils::path_exists(&self.fti_path)?;
+        utils::path_exists(&self.match_path)?;
+        if let Some(requests_path) = &self.requests_path {
+            utils::path_exists(requests_path)?;
+        }
+        self.wikidata_gatherer.check()?;
+        Ok(())
+    }
+}
+
 /// Configuration for the target part of the `sample` command.
 #[must_use]
 #[derive(Clone, Debug)]
@@ -701,13 +1274,316 @@ impl SamplingConfig {
     /// Returns `Err` if paths expected to exist do not exist or paths expected to not exist do exist.
     pub fn check(&self) -> Result<(), ConfigCheckError> {
         if let Some(target) = &self.target {
-            utils::path_exists(&target.products_path)?;
-            utils::path_exists(&target.organisations_path)?;
+            utils::output_exists(&target.products_path)?;
+            utils::output_exists(&target.organisations_path)?;
         }
         Ok(())
     }
 }
 
+/// Configuration for the `feedback` command.
+#[must_use]
+#[derive(Clone, Debug)]
+pub struct FeedbackConfig {
+    /// Path to the exported feedback.
+    pub feedback_path: std::path::PathBuf,
+
+    /// Path to mapping from names to Wikidata IDs.
+    pub match_path: std::path::PathBuf,
+}
+
+impl FeedbackConfig {
+    /// Constructs a new `FeedbackConfig`.
+    pub fn new(args: &commands::FeedbackArgs) -> FeedbackConfig {
+        let source = std::path::PathBuf::from(&args.source);
+        Self {
+            feedback_path: std::path::PathBuf::from(&args.input),
+            match_path: source.join("matches.yaml"),
+        }
+    }
+
+    /// Checks validity of the configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if paths expected to exist do not exist or paths expected to not exist do exist.
+    pub fn check(&self) -> Result<(), ConfigCheckError> {
+        utils::path_exists(&self.feedback_path)?;
+        if let Some(source) = self.match_path.parent() {
+            utils::dir_exists(source)?;
+        }
+        Ok(())
+    }
+}
+
+/// Configuration for the `diff` command.
+#[must_use]
+#[derive(Clone, Debug)]
+pub struct DiffConfig {
+    /// Path to the products of the previous data set.
+    pub old_products_path: std::path::PathBuf,
+
+    /// Path to the organisations of the previous data set.
+    pub old_organisations_path: std::path::PathBuf,
+
+    /// Path to the products of the new data set.
+    pub new_products_path: std::path::PathBuf,
+
+    /// Path to the organisations of the new data set.
+    pub new_organisations_path: std::path::PathBuf,
+
+    /// Path to the output file with the changes.
+    pub changes_path: std::path::PathBuf,
+
+    /// Version of the previous data set.
+    pub old_version: String,
+
+    /// Version of the new data set.
+    pub new_version: String,
+
+    /// Compression of the output file.
+    pub compression: commands::Compression,
+}
+
+impl DiffConfig {
+    /// Constructs a new `DiffConfig`.
+    pub fn new(args: &commands::DiffArgs) -> DiffConfig {
+        let old = std::path::PathBuf::from(&args.old);
+        let new = std::path::PathBuf::from(&args.new);
+        Self {
+            old_products_path: old.join("products.jsonl"),
+            old_organisations_path: old.join("organisations.jsonl"),
+            new_products_path: new.join("products.jsonl"),
+            new_organisations_path: new.join("organisations.jsonl"),
+            changes_path: new.join("changes.jsonl"),
+            old_version: args.old_version.clone(),
+            new_version: args.new_version.clone(),
+            compression: args.compression,
+        }
+    }
+
+    /// Checks validity of the configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if paths expected to exist do not exist or paths expected to not exist do exist.
+    pub fn check(&self) -> Result<(), ConfigCheckError> {
+        utils::output_exists(&self.old_products_path)?;
+        utils::output_exists(&self.old_organisations_path)?;
+        utils::output_exists(&self.new_products_path)?;
+        utils::output_exists(&self.new_organisations_path)?;
+        utils::path_creatable(&self.changes_path)?;
+        Ok(())
+    }
+}
+
+/// Configuration for the `import` command.
+#[must_use]
+#[derive(Clone, Debug)]
+pub struct ImportConfig {
+    /// Directory with the crystalized data set.
+    pub target_path: std::path::PathBuf,
+
+    /// Path to the changes found by the `diff` command.
+    pub changes_path: std::path::PathBuf,
+
+    /// Name of the database to import into.
+    pub database: String,
+
+    /// URL of the database server.
+    pub url: String,
+
+    /// Database user.
+    pub user: String,
+
+    /// Database password.
+    pub password: String,
+}
+
+impl ImportConfig {
+    /// Constructs a new `ImportConfig`.
+    pub fn new(args: &commands::ImportArgs) -> ImportConfig {
+        let target = std::path::PathBuf::from(&args.target);
+        Self {
+            changes_path: target.join("changes.jsonl"),
+            target_path: target,
+            database: args.database.clone(),
+            url: args.url.clone(),
+            user: args.user.clone(),
+            password: std::env::var("SUSTAINITY_DB_PASSWORD").unwrap_or_default(),
+        }
+    }
+
+    /// Checks validity of the configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if paths expected to exist do not exist or paths expected to not exist do exist.
+    pub fn check(&self) -> Result<(), ConfigCheckError> {
+        utils::dir_exists(&self.target_path)?;
+        utils::output_exists(&self.target_path.join("organisations.jsonl"))?;
+        utils::output_exists(&self.target_path.join("products.jsonl"))?;
+        utils::output_exists(&self.target_path.join("library.jsonl"))?;
+        Ok(())
+    }
+}
+
+/// Configuration for the `requests` command.
+#[must_use]
+#[derive(Clone, Debug)]
+pub struct RequestsConfig {
+    /// Output directory.
+    pub output_path: std::path::PathBuf,
+
+    /// Path to the output product requests.
+    pub product_requests_path: std::path::PathBuf,
+
+    /// Path to the output search queries with no results.
+    pub zero_results_path: std::path::PathBuf,
+
+    /// URL of the database server.
+    pub url: String,
+
+    /// Database user.
+    pub user: String,
+
+    /// Database password.
+    pub password: String,
+}
+
+impl RequestsConfig {
+    /// Constructs a new `RequestsConfig`.
+    pub fn new(args: &commands::RequestsArgs) -> RequestsConfig {
+        let output = std::path::PathBuf::from(&args.output);
+        Self {
+            product_requests_path: output.join("product_requests.jsonl"),
+            zero_results_path: output.join("zero_result_queries.jsonl"),
+            output_path: output,
+            url: args.url.clone(),
+            user: args.user.clone(),
+            password: std::env::var("SUSTAINITY_DB_PASSWORD").unwrap_or_default(),
+        }
+    }
+
+    /// Checks validity of the configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if paths expected to exist do not exist or paths expected to not exist do exist.
+    pub fn check(&self) -> Result<(), ConfigCheckError> {
+        utils::dir_exists(&self.output_path)?;
+        Ok(())
+    }
+}
+
+/// Configuration for the `export` command.
+#[must_use]
+#[derive(Clone, Debug)]
+pub struct ExportConfig {
+    /// Path to the crystalized organisations.
+    pub organisations_path: std::path::PathBuf,
+
+    /// Path to the crystalized products.
+    pub products_path: std::path::PathBuf,
+
+    /// Path to the output file or directory.
+    pub output_path: std::path::PathBuf,
+
+    /// Output format.
+    pub format: commands::ExportFormat,
+
+    /// Selected columns of the CSV export (all if empty).
+    pub columns: Vec<commands::CsvColumn>,
+}
+
+impl ExportConfig {
+    /// Constructs a new `ExportConfig`.
+    pub fn new(args: &commands::ExportArgs) -> ExportConfig {
+        let target = std::path::PathBuf::from(&args.target);
+        Self {
+            organisations_path: target.join("organisations.jsonl"),
+            products_path: target.join("products.jsonl"),
+            output_path: std::path::PathBuf::from(&args.output),
+            format: args.format,
+            columns: args.columns.clone(),
+        }
+    }
+
+    /// Checks validity of the configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if paths expected to exist do not exist or paths expected to not exist do exist.
+    /// Columns can be selected only for the CSV format.
+    pub fn check(&self) -> Result<(), ConfigCheckError> {
+        utils::output_exists(&self.organisations_path)?;
+        utils::output_exists(&self.products_path)?;
+        utils::path_creatable(&self.output_path)?;
+        if !self.columns.is_empty() && self.format != commands::ExportFormat::Csv {
+            return Err(ConfigCheckError::UnsupportedOption(
+                "--columns".to_owned(),
+                format!("{:?}", self.format),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Configuration for the `schemas` command.
+#[must_use]
+#[derive(Clone, Debug)]
+pub struct SchemasConfig {
+    /// Path to the output directory.
+    pub output_path: std::path::PathBuf,
+}
+
+impl SchemasConfig {
+    /// Constructs a new `SchemasConfig`.
+    pub fn new(args: &commands::SchemasArgs) -> SchemasConfig {
+        Self { output_path: std::path::PathBuf::from(&args.output) }
+    }
+
+    /// Checks validity of the configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the output path exists but is not a directory.
+    pub fn check(&self) -> Result<(), ConfigCheckError> {
+        if self.output_path.exists() {
+            utils::dir_exists(&self.output_path)?;
+        }
+        Ok(())
+    }
+}
+
+/// Configuration for the `validate` command.
+#[must_use]
+#[derive(Clone, Debug)]
+pub struct ValidationConfig {
+    /// Path to the validated file.
+    pub input_path: std::path::PathBuf,
+
+    /// Kind of the file, if given explicitly.
+    pub kind: Option<commands::FileKind>,
+}
+
+impl ValidationConfig {
+    /// Constructs a new `ValidationConfig`.
+    pub fn new(args: &commands::ValidationArgs) -> ValidationConfig {
+        Self { input_path: std::path::PathBuf::from(&args.input), kind: args.kind }
+    }
+
+    /// Checks validity of the configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the input file does not exist.
+    pub fn check(&self) -> Result<(), ConfigCheckError> {
+        utils::path_exists(&self.input_path)?;
+        Ok(())
+    }
+}
+
 impl From<&FullProducerConfig> for WikidataProducerConfig {
     fn from(config: &FullProducerConfig) -> WikidataProducerConfig {
         config.wiki.clone()
@@ -780,6 +1656,12 @@ impl From<&ConnectionConfig> for WikidataProducerConfig {
     }
 }
 
+impl From<&MatchConfig> for WikidataProducerConfig {
+    fn from(config: &MatchConfig) -> WikidataProducerConfig {
+        config.wikidata_gatherer.clone()
+    }
+}
+
 impl From<&CondensationConfig> for SourcesConfig {
     fn from(config: &CondensationConfig) -> SourcesConfig {
         config.sources.clone()
@@ -804,6 +1686,132 @@ impl From<&CrystalizationConfig> for SubstrateConfig {
     }
 }
 
+/// Configuration for the `fetch` command.
+#[must_use]
+#[derive(Clone, Debug)]
+pub struct FetchConfig {
+    /// Directory to download the sources into.
+    pub origin_path: std::path::PathBuf,
+
+    /// Path to the manifest listing the sources.
+    pub manifest_path: std::path::PathBuf,
+
+    /// Age (in days) after which a file is considered stale.
+    pub max_age_days: i64,
+
+    /// Download again the stale files.
+    pub refresh: bool,
+}
+
+impl FetchConfig {
+    /// Constructs a new `FetchConfig`.
+    pub fn new(args: &commands::FetchArgs) -> FetchConfig {
+        Self {
+            origin_path: std::path::PathBuf::from(&args.origin),
+            manifest_path: std::path::PathBuf::from(&args.manifest),
+            max_age_days: args.max_age,
+            refresh: args.refresh,
+        }
+    }
+
+    /// Checks validity of the configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if paths expected to exist do not exist.
+    pub fn check(&self) -> Result<(), ConfigCheckError> {
+        utils::dir_exists(&self.origin_path)?;
+        utils::path_exists(&self.manifest_path)?;
+        Ok(())
+    }
+}
+
+/// Configuration for the `fetch-wikidata` command.
+#[must_use]
+#[derive(Clone, Debug)]
+pub struct EntityFetchConfig {
+    /// Cache directory.
+    pub cache_path: std::path::PathBuf,
+
+    /// Wikidata IDs of the entities to fetch.
+    pub ids: Vec<String>,
+
+    /// Fetch the entities even if they are cached.
+    pub refresh: bool,
+
+    /// Dump file to append the entities to.
+    pub output_path: Option<std::path::PathBuf>,
+}
+
+impl EntityFetchConfig {
+    /// Constructs a new `EntityFetchConfig`.
+    pub fn new(args: &commands::EntityFetchArgs) -> EntityFetchConfig {
+        Self {
+            cache_path: std::path::PathBuf::from(&args.cache),
+            ids: args.ids.clone(),
+            refresh: args.refresh,
+            output_path: args.output.as_ref().map(std::path::PathBuf::from),
+        }
+    }
+
+    /// Checks validity of the configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if paths expected to exist do not exist.
+    pub fn check(&self) -> Result<(), ConfigCheckError> {
+        utils::dir_exists(&self.cache_path)?;
+        Ok(())
+    }
+}
+
+/// Configuration for the `scrape` command.
+#[must_use]
+#[derive(Clone, Debug)]
+pub struct ScrapingConfig {
+    /// Path to the configuration of the table scrapers.
+    pub config_path: std::path::PathBuf,
+
+    /// Directory to write the scraped tables into.
+    pub output_path: std::path::PathBuf,
+}
+
+impl ScrapingConfig {
+    /// Constructs a new `ScrapingConfig`.
+    pub fn new(args: &commands::ScrapeArgs) -> ScrapingConfig {
+        Self {
+            config_path: std::path::PathBuf::from(&args.config),
+            output_path: std::path::PathBuf::from(&args.output),
+        }
+    }
+
+    /// Checks validity of the configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if paths expected to exist do not exist.
+    pub fn check(&self) -> Result<(), ConfigCheckError> {
+        utils::path_exists(&self.config_path)?;
+        utils::dir_exists(&self.output_path)?;
+        Ok(())
+    }
+}
+
+/// Configuration for the `completions` command.
+#[must_use]
+#[derive(Clone, Debug)]
+pub struct CompletionsConfig {
+    /// Shell to generate the completion script for.
+    pub shell: clap_complete::Shell,
+}
+
+impl CompletionsConfig {
+    /// Constructs a new `CompletionsConfig`.
+    pub fn new(args: &commands::CompletionsArgs) -> CompletionsConfig {
+        Self { shell: args.shell }
+    }
+}
+
 /// Configuration for the program.
 #[must_use]
 #[derive(Debug, Clone)]
@@ -817,15 +1825,27 @@ pub enum Config {
     Oxidation(OxidationConfig),
     Analysis(AnalysisConfig),
     Connection(ConnectionConfig),
+    Match(MatchConfig),
     Sample(SamplingConfig),
+    Feedback(FeedbackConfig),
+    Diff(DiffConfig),
+    Import(ImportConfig),
+    Requests(RequestsConfig),
+    Export(ExportConfig),
+    Schemas(SchemasConfig),
+    Validation(ValidationConfig),
+    Fetch(FetchConfig),
+    EntityFetch(EntityFetchConfig),
+    Scraping(ScrapingConfig),
+    Completions(CompletionsConfig),
 }
 
 impl Config {
-    /// Constructs a new config from `Args::parse()`.
+    /// Constructs a new config from the program arguments (see `cli::parse()`).
     pub fn new_from_args() -> Config {
-        use commands::{Args, Commands};
+        use commands::Commands;
 
-        let args = Args::parse();
+        let args = crate::cli::parse();
         match args.command {
             Commands::Filter1(args) => Config::Filtering1(Filtering1Config::new(&args)),
             Commands::Filter2(args) => Config::Filtering2(Filtering2Config::new(&args)),
@@ -836,7 +1856,79 @@ impl Config {
             Commands::Oxidize(args) => Config::Oxidation(OxidationConfig::new(&args)),
             Commands::Analyze(args) => Config::Analysis(AnalysisConfig::new(&args)),
             Commands::Connect(args) => Config::Connection(ConnectionConfig::new(&args)),
+            Commands::Match(args) => Config::Match(MatchConfig::new(&args)),
             Commands::Sample(args) => Config::Sample(SamplingConfig::new(&args)),
+            Commands::Feedback(args) => Config::Feedback(FeedbackConfig::new(&args)),
+            Commands::Diff(args) => Config::Diff(DiffConfig::new(&args)),
+            Commands::Import(args) => Config::Import(ImportConfig::new(&args)),
+            Commands::Requests(args) => Config::Requests(RequestsConfig::new(&args)),
+            Commands::Export(args) => Config::Export(ExportConfig::new(&args)),
+            Commands::Schemas(args) => Config::Schemas(SchemasConfig::new(&args)),
+            Commands::Validate(args) => Config::Validation(ValidationConfig::new(&args)),
+            Commands::Fetch(args) => Config::Fetch(FetchConfig::new(&args)),
+            Commands::FetchWikidata(args) => Config::EntityFetch(EntityFetchConfig::new(&args)),
+            Commands::Scrape(args) => Config::Scraping(ScrapingConfig::new(&args)),
+            Commands::Completions(args) => Config::Completions(CompletionsConfig::new(&args)),
+        }
+    }
+
+    /// Returns the sizing of the runtime, if the command is configurable.
+    #[must_use]
+    pub fn runtime(&self) -> Option<&RuntimeConfig> {
+        match self {
+            Config::Condensation(config) => Some(&config.runtime),
+            _ => None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runtime_sizing() {
+        const GIB: usize = 1024 * 1024 * 1024;
+
+        let small = RuntimeConfig::for_machine(2, Some(2 * GIB));
+        assert_eq!(small.workers, 2);
+        assert_eq!(small.channel_capacity, MIN_CHANNEL_CAPACITY);
+        assert_eq!(small.io_buffer_size, DEFAULT_IO_BUFFER_SIZE);
+
+        let large = RuntimeConfig::for_machine(64, Some(256 * GIB));
+        assert_eq!(large.workers, 64);
+        assert_eq!(large.channel_capacity, 6400);
+        assert_eq!(large.io_buffer_size, LARGE_IO_BUFFER_SIZE);
+
+        let unknown = RuntimeConfig::for_machine(0, None);
+        assert_eq!(unknown.workers, 1);
+        assert_eq!(unknown.channel_capacity, MIN_CHANNEL_CAPACITY);
+        assert!(unknown.check().is_ok());
+
+        let zero = RuntimeConfig { channel_capacity: 0, ..unknown };
+        assert!(zero.check().is_err());
+    }
+
+    #[test]
+    fn export_columns() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("organisations.jsonl"), "").unwrap();
+        std::fs::write(dir.path().join("products.jsonl"), "").unwrap();
+        let args = commands::ExportArgs {
+            target: dir.path().to_string_lossy().into_owned(),
+            output: dir.path().join("export").to_string_lossy().into_owned(),
+            format: commands::ExportFormat::Csv,
+            columns: vec![commands::CsvColumn::Name],
+        };
+        assert!(ExportConfig::new(&args).check().is_ok());
+
+        let args = commands::ExportArgs { format: commands::ExportFormat::Parquet, ..args };
+        assert!(matches!(
+            ExportConfig::new(&args).check(),
+            Err(ConfigCheckError::UnsupportedOption(..))
+        ));
+
+        let args = commands::ExportArgs { columns: Vec::new(), ..args };
+        assert!(ExportConfig::new(&args).check().is_ok());
+    }
+}
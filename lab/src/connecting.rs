@@ -5,9 +5,17 @@ use merge::Merge;
 use serde::Serialize;
 
 use sustainity_collecting::{errors::MapSerde, eu_ecolabel, open_food_facts, sustainity};
+use sustainity_models::ids::VatId;
 use sustainity_wikidata::data::{Entity, Item};
 
-use crate::{config, errors, parallel, runners, sources::Sourceable, utils, wikidata::ItemExt};
+use crate::{
+    config, errors, matching, parallel, runners, sources::Sourceable, utils, wikidata::ItemExt,
+};
+
+/// Extracts the VAT number of the company from the EU Ecolabel record.
+fn prepare_vat_number(record: &eu_ecolabel::data::Record) -> Option<VatId> {
+    record.vat_number.as_ref().map(|vat| VatId::with_country(vat, &record.company_country))
+}
 
 /// Calculates similarity of entry in some data to entry in Wikidata.
 #[derive(Serialize, Clone, Debug, Hash, PartialEq, Eq)]
@@ -15,37 +23,41 @@ struct Matcher {
     /// Name of a company.
     name: String,
 
+    /// Normalised name of a company.
+    #[serde(skip)]
+    key: String,
+
     /// VAT ID number of a company,
-    vat_number: Option<String>,
+    vat_number: Option<VatId>,
 }
 
 impl Matcher {
     /// Constructs a new `Matcher`.
-    pub fn new(name: String, vat_number: Option<String>) -> Self {
-        Self { name, vat_number }
+    pub fn new(name: String, vat_number: Option<VatId>) -> Self {
+        let key = matching::normalize(&name);
+        Self { name, key, vat_number }
     }
 
     /// Integrates more data from the given record if that record has more detailed info.
     pub fn absorb_eu_ecolabel_record(&mut self, record: &eu_ecolabel::data::Record) {
         if record.vat_number.is_some() && self.vat_number.is_none() {
-            self.vat_number = record.prepare_vat_number();
+            self.vat_number = prepare_vat_number(record);
         }
     }
 
     /// Calculates similarity with an item.
-    pub fn calc_similarity(&self, item: &Item) -> f64 {
+    ///
+    /// `labels` are the normalised labels and aliases of the item.
+    pub fn calc_similarity(&self, item: &Item, labels: &[String]) -> f64 {
         if let Some(item_vat_numbers) = item.get_eu_vat_numbers() {
             if let Some(self_vat_number) = &self.vat_number {
-                if item_vat_numbers.contains(self_vat_number) {
+                if item_vat_numbers.iter().any(|v| VatId::new(v) == *self_vat_number) {
                     return 10.0;
                 }
             }
         }
 
-        item.get_all_labels_and_aliases()
-            .iter()
-            .map(|l| strsim::normalized_levenshtein(&self.name, &utils::disambiguate_name(l)))
-            .fold(0.0, f64::max)
+        labels.iter().map(|l| matching::normalized_similarity(&self.key, l)).fold(0.0, f64::max)
     }
 }
 
@@ -81,8 +93,8 @@ impl Entry {
     }
 
     /// Evaluates the item and updates self if the item fits the matcher better than the best item found so far.
-    pub fn process(&mut self, item: &Item) {
-        let similarity = self.matcher.calc_similarity(item);
+    pub fn process(&mut self, item: &Item, labels: &[String]) {
+        let similarity = self.matcher.calc_similarity(item, labels);
         match self.similarity.partial_cmp(&similarity) {
             Some(std::cmp::Ordering::Equal) => {
                 self.ids.insert(item.id);
@@ -138,7 +150,7 @@ impl Sourceable for ConnectionSources {
             eu_data
                 .entry(name.clone())
                 .and_modify(|matcher| matcher.absorb_eu_ecolabel_record(&record))
-                .or_insert_with(|| Matcher::new(name, record.prepare_vat_number()));
+                .or_insert_with(|| Matcher::new(name, prepare_vat_number(&record)));
         }
         log::info!("Found {} companies in the EU Ecolabel dataset", eu_data.len());
 
@@ -222,8 +234,13 @@ impl runners::WikidataWorker for ConnectionWorker {
         match entity {
             Entity::Item(item) => {
                 if item.is_organisation() {
+                    let labels: Vec<String> = item
+                        .get_all_labels_and_aliases()
+                        .iter()
+                        .map(|label| matching::normalize(label))
+                        .collect();
                     for entry in self.collector.data.values_mut() {
-                        entry.process(&item);
+                        entry.process(&item, &labels);
                     }
                 }
             }
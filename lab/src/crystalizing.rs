@@ -1,16 +1,24 @@
-use std::collections::{btree_map::Entry, BTreeMap, BTreeSet, HashSet};
+use std::collections::{btree_map::Entry, BTreeMap, BTreeSet, HashMap, HashSet};
 
 use merge::Merge;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use thiserror::Error;
 
-use sustainity_models::{collections, gather, ids, store};
+use sustainity_models::{collections, gather, ids, keywords, store};
 use sustainity_schema as schema;
 
-use crate::{config, errors, utils};
+use crate::{
+    alternatives, attributes, condensing, config, dedup, errors, matching, outputs,
+    overrides::Overrides,
+    policy::{Conflict, ConflictPolicy, Provenance, SourcedValue},
+    relevance, resolution, utils, variants,
+};
 
 const MAX_CATEGORY_PRODUCT_NUM: usize = 300_000;
 
+/// Number of the best-scored products listed in the statistics of a category.
+const CATEGORY_BEST_PRODUCT_NUM: usize = 10;
+
 fn extract_regions(
     availability: Option<&schema::ProductAvailability>,
 ) -> Result<gather::Regions, isocountry::CountryCodeParseErr> {
@@ -39,6 +47,23 @@ fn convert_regions(
     })
 }
 
+/// Separates links to social media profiles from the other websites.
+fn split_websites(websites: Vec<String>) -> (BTreeSet<String>, BTreeSet<gather::SocialLink>) {
+    let mut other = BTreeSet::new();
+    let mut social = BTreeSet::new();
+    for website in websites {
+        match gather::SocialLink::from_url(&website) {
+            Some(link) => {
+                social.insert(link);
+            }
+            None => {
+                other.insert(website);
+            }
+        }
+    }
+    (other, social)
+}
+
 // Indentifies a substrate file.
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
 #[serde(transparent)]
@@ -56,6 +81,50 @@ pub struct Substrate {
     id: DataSetId,
     path: std::path::PathBuf,
     source: gather::Source,
+
+    /// Date of the snapshot of the source data, if the source provides it.
+    snapshot_date: Option<String>,
+}
+
+impl Substrate {
+    /// Validity of certifications from this substrate.
+    ///
+    /// Substrates don't carry validity periods of the certifications, they are loaded later with
+    /// the attributes. Only the snapshot date is known at this point.
+    fn validity(&self) -> gather::Validity {
+        gather::Validity::from_snapshot(self.snapshot_date.clone())
+    }
+
+    /// Returns the validity of the certification given by this substrate, if it was given.
+    fn validity_mut<'a>(
+        &self,
+        certifications: &'a mut gather::Certifications,
+    ) -> Option<&'a mut gather::Validity> {
+        if self.source.is_bcorp() {
+            certifications.bcorp.as_mut().map(|cert| &mut cert.validity)
+        } else if self.source.is_euecolabel() {
+            certifications.eu_ecolabel.as_mut().map(|cert| &mut cert.validity)
+        } else if self.source.is_fti() {
+            certifications.fti.as_mut().map(|cert| &mut cert.validity)
+        } else if self.source.is_tco() {
+            certifications.tco.as_mut().map(|cert| &mut cert.validity)
+        } else {
+            None
+        }
+    }
+}
+
+/// Merges validity of the same certification given by several records (e.g. several licences).
+///
+/// The certification is considered valid as long as any of the records is.
+fn merge_validity(validity: &mut gather::Validity, other: gather::Validity) {
+    validity.valid_from = match (validity.valid_from.take(), other.valid_from) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    };
+    validity.valid_until = validity.valid_until.take().max(other.valid_until);
+    validity.source_snapshot_date =
+        validity.source_snapshot_date.take().max(other.source_snapshot_date);
 }
 
 pub struct Substrates {
@@ -75,10 +144,13 @@ impl Substrates {
             if path.is_file() {
                 if let Some(stem) = path.file_stem() {
                     if let Some(stem) = stem.to_str() {
+                        let snapshot_date =
+                            attributes::read_data_set(directory, stem)?.snapshot_date;
                         list.push(Substrate {
                             id: DataSetId::new(list.len()),
                             path: path.clone(),
                             source: gather::Source::from_string(stem),
+                            snapshot_date,
                         });
                     } else {
                         report.add_path_not_unicode(path.clone());
@@ -106,6 +178,24 @@ impl Substrates {
         }
         None
     }
+
+    #[must_use]
+    pub fn get_source_for_id(&self, data_set_id: DataSetId) -> Option<&gather::Source> {
+        self.list.iter().find(|substrate| substrate.id == data_set_id).map(|s| &s.source)
+    }
+}
+
+/// Describes an organisation merged from records of multiple sources.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct OrganisationResolution {
+    /// DB key of the organisation.
+    pub organisation: String,
+
+    /// Sources of the merged records.
+    pub sources: BTreeSet<gather::Source>,
+
+    /// Keys which linked the records, e.g. `wiki:Q95` or `vat:PL5260250274`.
+    pub keys: BTreeSet<String>,
 }
 
 /// Warnings specific to the crystalisation command.
@@ -126,6 +216,8 @@ pub struct CrystalizationReport {
     invalid_ids: BTreeMap<DataSetId, BTreeSet<String>>,
     empty_ids: BTreeMap<DataSetId, BTreeSet<InnerId>>,
     missing_inner_ids: BTreeMap<DataSetId, BTreeSet<InnerId>>,
+    resolution_conflicts: BTreeSet<String>,
+    product_merges: Vec<dedup::ProductMerge>,
 }
 
 impl CrystalizationReport {
@@ -164,6 +256,14 @@ impl CrystalizationReport {
         }
     }
 
+    pub fn add_resolution_conflict(&mut self, key: String) {
+        self.resolution_conflicts.insert(key);
+    }
+
+    pub fn add_product_merges(&mut self, merges: Vec<dedup::ProductMerge>) {
+        self.product_merges.extend(merges);
+    }
+
     pub fn add_missing_external_id(&mut self, external_id: ExternalId) {
         let (data_set, inner) = external_id.decompose();
         self.add_missing_inner_id(data_set, inner);
@@ -198,6 +298,8 @@ impl CrystalizationReport {
                 a.extend(b.iter().cloned());
             },
         );
+        self.resolution_conflicts.extend(other.resolution_conflicts);
+        self.product_merges.extend(other.product_merges);
     }
 
     pub fn report(&self, substrates: &Substrates) {
@@ -244,6 +346,25 @@ impl CrystalizationReport {
                 log::warn!("  - {}: {}", path, ids.len());
             }
         }
+        if !self.resolution_conflicts.is_empty() {
+            log::warn!(" conflicting resolution keys: {}", self.resolution_conflicts.len());
+            for key in &self.resolution_conflicts {
+                log::debug!("  - {key}");
+            }
+        }
+        if !self.product_merges.is_empty() {
+            log::warn!(" merged near-duplicate products: {}", self.product_merges.len());
+            for merge in &self.product_merges {
+                let merged: Vec<_> = merge.merged.iter().map(ToString::to_string).collect();
+                let keys: Vec<_> = merge.keys.iter().map(String::as_str).collect();
+                log::debug!(
+                    "  - {} <- {} ({})",
+                    merge.canonical,
+                    merged.join(", "),
+                    keys.join(", ")
+                );
+            }
+        }
         log::warn!("End of the report");
     }
 }
@@ -458,22 +579,6 @@ impl KvStore {
         Ok(Self { store: kv::Store::new(kv::Config::new(path))? })
     }
 
-    pub fn get_producer_external_to_individuals_bucket(
-        &self,
-    ) -> Result<Bucket<ExternalId, Vec<IndividualProducerId>>, errors::KvStoreError> {
-        let bucket =
-            self.store.bucket::<Vec<u8>, Vec<u8>>(Some("producers_external_to_individuals"))?;
-        Ok(Bucket { bucket, phantom: std::marker::PhantomData })
-    }
-
-    pub fn get_producer_individual_to_externals_bucket(
-        &self,
-    ) -> Result<Bucket<IndividualProducerId, Vec<ExternalId>>, errors::KvStoreError> {
-        let bucket =
-            self.store.bucket::<Vec<u8>, Vec<u8>>(Some("producers_individual_to_externals"))?;
-        Ok(Bucket { bucket, phantom: std::marker::PhantomData })
-    }
-
     pub fn get_product_external_to_individuals_bucket(
         &self,
     ) -> Result<Bucket<ExternalId, Vec<IndividualProductId>>, errors::KvStoreError> {
@@ -658,10 +763,36 @@ enum IndividualProducerId {
     /// Web domains.
     // TODO: Introduce dedicated type.
     Domain(String),
+
+    /// Normalised name.
+    Name(String),
 }
 
 impl IndividualId for IndividualProducerId {}
 
+impl resolution::Key for IndividualProducerId {
+    /// Producers are resolved by Wikidata IDs first, then by VAT numbers, domains and names.
+    fn rank(&self) -> usize {
+        match self {
+            Self::Wiki(_) => 0,
+            Self::Vat(_) => 1,
+            Self::Domain(_) => 2,
+            Self::Name(_) => 3,
+        }
+    }
+}
+
+impl std::fmt::Display for IndividualProducerId {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Vat(id) => write!(f, "vat:{}", id.as_str()),
+            Self::Wiki(id) => write!(f, "wiki:Q{}", id.get_value()),
+            Self::Domain(domain) => write!(f, "domain:{domain}"),
+            Self::Name(name) => write!(f, "name:{name}"),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct ProducerIds {
     external: ExternalId,
@@ -675,7 +806,8 @@ impl ProducerIds {
         data_set_id: DataSetId,
     ) -> (Self, Vec<CrystalizationWarning>) {
         let external = ExternalId::new(data_set_id, InnerId::new(producer.id.clone()));
-        let (individual, warnings) = Self::extract_individual_ids(&producer.ids, data_set_id);
+        let (individual, warnings) =
+            Self::extract_individual_ids(&producer.ids, &producer.names, data_set_id);
         (Self { external, individual }, warnings)
     }
 
@@ -685,13 +817,15 @@ impl ProducerIds {
         data_set_id: DataSetId,
     ) -> (Self, Vec<CrystalizationWarning>) {
         let external = ExternalId::new(data_set_id, InnerId::new(producer.id.clone()));
-        let (individual, warnings) = Self::extract_individual_ids(&producer.ids, data_set_id);
+        let (individual, warnings) =
+            Self::extract_individual_ids(&producer.ids, &producer.names, data_set_id);
         (Self { external, individual }, warnings)
     }
 
     #[must_use]
     fn extract_individual_ids(
         ids: &schema::ProducerIds,
+        names: &[String],
         data_set_id: DataSetId,
     ) -> (Vec<IndividualProducerId>, Vec<CrystalizationWarning>) {
         let mut individual = Vec::new();
@@ -724,6 +858,12 @@ impl ProducerIds {
                 individual.push(IndividualProducerId::Domain(domain.clone()));
             }
         }
+        let names: BTreeSet<String> = names.iter().map(|name| matching::normalize(name)).collect();
+        for name in names {
+            if !name.is_empty() {
+                individual.push(IndividualProducerId::Name(name));
+            }
+        }
 
         (individual, warnings)
     }
@@ -814,7 +954,10 @@ impl ProductIds {
         }
         if let Some(gtin) = &ids.gtin {
             for id in gtin {
-                match ids::Gtin::try_from(id) {
+                match ids::Gtin::try_from(id).and_then(|gtin| gtin.validate().map(|()| gtin)) {
+                    // Restricted circulation numbers are not unique, so they can't identify
+                    // the same product in different data sets.
+                    Ok(id) if id.is_restricted() => {}
                     Ok(id) => individual.push(IndividualProductId::Gtin(id.to_canonical_string())),
                     Err(_) => warnings.push(CrystalizationWarning::InvalidIndividualId {
                         data_set_id,
@@ -887,6 +1030,12 @@ pub struct CrystalizationCollector {
 
     /// Found products.
     products: BTreeMap<gather::ProductId, gather::Product>,
+
+    /// Sources of the organisation fields stored without them, for the conflict policy.
+    organisation_provenance: HashMap<gather::OrganisationId, Provenance>,
+
+    /// Sources of the product fields stored without them, for the conflict policy.
+    product_provenance: HashMap<gather::ProductId, Provenance>,
 }
 
 impl CrystalizationCollector {
@@ -894,7 +1043,16 @@ impl CrystalizationCollector {
         &mut self,
         id: gather::OrganisationId,
         organisation: gather::Organisation,
+        source: &gather::Source,
     ) {
+        let provenance = self.organisation_provenance.entry(id.clone()).or_default();
+        provenance.websites.extend(
+            organisation
+                .websites
+                .iter()
+                .map(|value| SourcedValue { value: value.clone(), source: source.clone() }),
+        );
+
         match self.organisations.entry(id) {
             Entry::Occupied(mut entry) => {
                 entry.get_mut().merge(organisation);
@@ -905,7 +1063,20 @@ impl CrystalizationCollector {
         }
     }
 
-    pub fn update_product(&mut self, id: gather::ProductId, product: gather::Product) {
+    pub fn update_product(
+        &mut self,
+        id: gather::ProductId,
+        product: gather::Product,
+        source: &gather::Source,
+    ) {
+        if let gather::Regions::List(countries) = &product.regions {
+            let provenance = self.product_provenance.entry(id.clone()).or_default();
+            provenance.countries.extend(countries.iter().map(|country| SourcedValue {
+                value: country.alpha3().to_owned(),
+                source: source.clone(),
+            }));
+        }
+
         match self.products.entry(id) {
             Entry::Occupied(mut entry) => entry.get_mut().merge(product),
             Entry::Vacant(entry) => {
@@ -913,6 +1084,79 @@ impl CrystalizationCollector {
             }
         }
     }
+
+    /// Loads the attributes of products and organisations (and awards, parents and legal
+    /// identifiers of organisations and shop identifiers of products) saved next to the substrates.
+    ///
+    /// Parents are known only if they are producers in the same substrate.
+    pub fn load_attributes(
+        &mut self,
+        substrates: &Substrates,
+        groups: &GroupedIds,
+    ) -> Result<(), errors::ProcessingError> {
+        for substrate in substrates.list() {
+            let (Some(directory), Some(name)) =
+                (substrate.path.parent(), substrate.path.file_stem().and_then(|s| s.to_str()))
+            else {
+                continue;
+            };
+            for record in attributes::read(directory, name)? {
+                let external_id = ExternalId::new(substrate.id, InnerId::new(record.id));
+                match record.subject {
+                    attributes::Subject::Product => {
+                        let Ok(id) = groups.get_unique_id_for_product_external_id(&external_id)
+                        else {
+                            continue;
+                        };
+                        let variant_of = record.variant_of.into_iter().find_map(|parent| {
+                            let parent = ExternalId::new(substrate.id, InnerId::new(parent));
+                            groups.get_unique_id_for_product_external_id(&parent).ok()
+                        });
+                        if let Some(product) = self.products.get_mut(&id) {
+                            product.attributes.extend(record.attributes);
+                            for (kind, ids) in record.external_ids {
+                                product.external_ids.entry(kind).or_default().extend(ids);
+                            }
+                            if product.variant_of.is_none() {
+                                product.variant_of = variant_of.filter(|parent| *parent != id);
+                            }
+                        }
+                    }
+                    attributes::Subject::Producer => {
+                        let Ok(id) = groups.get_unique_id_for_producer_external_id(&external_id)
+                        else {
+                            continue;
+                        };
+                        let parent_ids = record.owned_by.into_iter().filter_map(|parent| {
+                            let parent = ExternalId::new(substrate.id, InnerId::new(parent));
+                            groups.get_unique_id_for_producer_external_id(&parent).ok()
+                        });
+                        if let Some(organisation) = self.organisations.get_mut(&id) {
+                            organisation.attributes.extend(record.attributes);
+                            organisation.recognitions.extend(record.recognitions);
+                            organisation.controversies.extend(record.controversies);
+                            organisation.policy_pages.extend(record.policy_pages);
+                            organisation.ids.leis.extend(record.leis);
+                            organisation
+                                .ids
+                                .registration_numbers
+                                .extend(record.registration_numbers);
+                            organisation
+                                .parent_ids
+                                .extend(parent_ids.filter(|parent| *parent != id));
+                            if let (Some(validity), Some(other)) = (
+                                substrate.validity_mut(&mut organisation.certifications),
+                                record.validity,
+                            ) {
+                                merge_validity(validity, other);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 #[derive(Default)]
@@ -928,9 +1172,12 @@ impl Grouper {
     fn group(
         substrates: &Substrates,
         config: &config::CrystalizationConfig,
-    ) -> Result<(GroupedIds, CrystalizationReport), errors::CrystalizationError> {
+    ) -> Result<
+        (GroupedIds, Vec<OrganisationResolution>, CrystalizationReport),
+        errors::CrystalizationError,
+    > {
         log::info!("Gathering IDs");
-        let (summary, report) = Self::summarize(substrates)?;
+        let (summary, mut report) = Self::summarize(substrates)?;
 
         if config.local_storage_runtime.exists() {
             std::fs::remove_dir_all(&config.local_storage_runtime).map_err(|e| {
@@ -939,20 +1186,48 @@ impl Grouper {
         }
         let store = KvStore::new(&config.local_storage_runtime)?;
 
-        log::info!("Grouping producer IDs");
-        let producer = {
-            let combiner =
-                IdCombiner::<ProducerIds, IndividualProducerId, gather::OrganisationId>::new(
-                    store.get_producer_external_to_individuals_bucket()?,
-                    store.get_producer_individual_to_externals_bucket()?,
-                );
-            let result = combiner.combine(&summary.producer_ids)?;
+        log::info!("Resolving producer IDs");
+        let (producer, resolutions) = {
+            let records: Vec<_> = summary
+                .producer_ids
+                .into_iter()
+                .map(|ids| (ids.external, ids.individual))
+                .collect();
+            let num_empty_ids = records.iter().filter(|(_, ids)| ids.is_empty()).count();
+            let result = resolution::resolve(records);
 
             log::info!("Producers:");
-            log::info!(" - unique IDs: {}", result.num_unique_ids);
-            log::info!(" - empty IDs: {}", result.num_empty_ids);
+            log::info!(" - unique IDs: {}", result.clusters.len());
+            log::info!(" - empty IDs: {num_empty_ids}");
+            log::info!(" - conflicting IDs: {}", result.conflicts.len());
+            for conflict in &result.conflicts {
+                report.add_resolution_conflict(conflict.to_string());
+            }
 
-            IdMap { external_to_unique: result.external_to_unique }
+            let mut unique_id = gather::OrganisationId::zero();
+            let mut external_to_unique = BTreeMap::new();
+            let mut resolutions = Vec::new();
+            for cluster in result.clusters {
+                unique_id.increment();
+                let sources: BTreeSet<gather::Source> = cluster
+                    .records
+                    .iter()
+                    .filter_map(|external| substrates.get_source_for_id(external.data_set_id))
+                    .cloned()
+                    .collect();
+                if sources.len() > 1 {
+                    resolutions.push(OrganisationResolution {
+                        organisation: unique_id.to_string(),
+                        sources,
+                        keys: cluster.links.iter().map(ToString::to_string).collect(),
+                    });
+                }
+                for external in cluster.records {
+                    external_to_unique.insert(external, unique_id.clone());
+                }
+            }
+
+            (IdMap { external_to_unique }, resolutions)
         };
 
         log::info!("Grouping product IDs");
@@ -970,7 +1245,7 @@ impl Grouper {
             IdMap { external_to_unique: result.external_to_unique }
         };
 
-        Ok((GroupedIds { producer, product }, report))
+        Ok((GroupedIds { producer, product }, resolutions, report))
     }
 
     pub fn summarize(
@@ -1110,6 +1385,7 @@ impl Processor {
             .get_unique_id_for_producer_external_id(&external_id)
             .map_err(|id| id.to_error_not_found(substrate, "processing catalog producer"))?;
         let ids = self.convert_organisation_ids(producer.ids, substrate);
+        let (websites, social) = split_websites(producer.websites);
 
         self.collector.update_organisation(
             unique_id.clone(),
@@ -1121,6 +1397,7 @@ impl Processor {
                     .into_iter()
                     .map(|text| gather::Text { text, source: substrate.source.clone() })
                     .collect(),
+                keywords: BTreeSet::new(),
                 descriptions: producer
                     .description
                     .into_iter()
@@ -1131,9 +1408,17 @@ impl Processor {
                     .into_iter()
                     .map(|image| gather::Image { image, source: substrate.source.clone() })
                     .collect(),
-                websites: producer.websites.into_iter().collect(),
+                websites,
+                social,
+                attributes: gather::Attributes::default(), //< Loaded later
+                recognitions: BTreeSet::new(),             //< Loaded later
+                controversies: BTreeSet::new(),            //< Loaded later
+                policy_pages: BTreeSet::new(),             //< Loaded later
                 certifications: gather::Certifications::default(),
+                timeline: Vec::new(),        //< Built later
+                parent_ids: BTreeSet::new(), //< Loaded later
             },
+            &substrate.source,
         );
 
         Ok(())
@@ -1165,6 +1450,7 @@ impl Processor {
                     .into_iter()
                     .map(|text| gather::Text { text, source: substrate.source.clone() })
                     .collect(),
+                keywords: BTreeSet::new(),
                 descriptions: product
                     .description
                     .into_iter()
@@ -1182,9 +1468,15 @@ impl Processor {
                 manufacturer_ids,
                 follows,
                 followed_by,
+                also_known_as: BTreeSet::new(),
+                external_ids: BTreeMap::new(),
+                variant_of: None,                          //< Loaded later
+                variants: BTreeSet::new(),                 //< Grouped later
+                attributes: gather::Attributes::default(), //< Loaded later
                 sustainity_score: gather::SustainityScore::default(), //< Calculated later
                 certifications: gather::Certifications::default(),
             },
+            &substrate.source,
         );
 
         Ok(())
@@ -1216,6 +1508,7 @@ impl Processor {
                     .into_iter()
                     .map(|text| gather::Text { text, source: substrate.source.clone() })
                     .collect(),
+                keywords: BTreeSet::new(),
                 descriptions: BTreeSet::new(),
                 images: product
                     .images
@@ -1227,9 +1520,15 @@ impl Processor {
                 manufacturer_ids,
                 follows,
                 followed_by,
+                also_known_as: BTreeSet::new(),
+                external_ids: BTreeMap::new(),
+                variant_of: None,                          //< Loaded later
+                variants: BTreeSet::new(),                 //< Grouped later
+                attributes: gather::Attributes::default(), //< Loaded later
                 sustainity_score: gather::SustainityScore::default(), //< Calculated later
                 certifications: gather::Certifications::default(),
             },
+            &substrate.source,
         );
 
         Ok(())
@@ -1253,6 +1552,7 @@ impl Processor {
             .get_unique_id_for_producer_external_id(&external_id)
             .map_err(|id| id.to_error_not_found(substrate, "processing review producer"))?;
         let ids = self.convert_organisation_ids(producer.ids, substrate);
+        let (websites, social) = split_websites(producer.websites);
 
         self.collector.update_organisation(
             unique_id.clone(),
@@ -1264,6 +1564,7 @@ impl Processor {
                     .into_iter()
                     .map(|text| gather::Text { text, source: substrate.source.clone() })
                     .collect(),
+                keywords: BTreeSet::new(),
                 descriptions: producer
                     .description
                     .into_iter()
@@ -1274,9 +1575,17 @@ impl Processor {
                     .into_iter()
                     .map(|image| gather::Image { image, source: substrate.source.clone() })
                     .collect(),
-                websites: producer.websites.into_iter().collect(),
+                websites,
+                social,
+                attributes: gather::Attributes::default(), //< Loaded later
+                recognitions: BTreeSet::new(),             //< Loaded later
+                controversies: BTreeSet::new(),            //< Loaded later
+                policy_pages: BTreeSet::new(),             //< Loaded later
                 certifications,
+                timeline: Vec::new(),        //< Built later
+                parent_ids: BTreeSet::new(), //< Loaded later
             },
+            &substrate.source,
         );
 
         Ok(())
@@ -1308,6 +1617,7 @@ impl Processor {
                     .into_iter()
                     .map(|text| gather::Text { text, source: substrate.source.clone() })
                     .collect(),
+                keywords: BTreeSet::new(),
                 descriptions: BTreeSet::new(),
                 images: product
                     .images
@@ -1321,9 +1631,15 @@ impl Processor {
                 manufacturer_ids,
                 follows,
                 followed_by,
+                also_known_as: BTreeSet::new(),
+                external_ids: BTreeMap::new(),
+                variant_of: None,                          //< Loaded later
+                variants: BTreeSet::new(),                 //< Grouped later
+                attributes: gather::Attributes::default(), //< Loaded later
                 sustainity_score: gather::SustainityScore::default(), //< Calculated later
                 certifications: gather::Certifications::default(), //< Assigned later from producers
             },
+            &substrate.source,
         );
 
         Ok(())
@@ -1397,7 +1713,7 @@ impl Processor {
             return None;
         }
 
-        Some(gather::BCorpCert { id: producer.id.clone() })
+        Some(gather::BCorpCert { id: producer.id.clone(), validity: substrate.validity() })
     }
 
     fn extract_euecolabel_cert(substrate: &Substrate) -> Option<gather::EuEcolabelCert> {
@@ -1405,7 +1721,7 @@ impl Processor {
             return None;
         }
 
-        Some(gather::EuEcolabelCert {})
+        Some(gather::EuEcolabelCert { validity: substrate.validity() })
     }
 
     fn extract_fti_cert(
@@ -1418,7 +1734,7 @@ impl Processor {
 
         match &producer.review {
             Some(schema::Review::ScoreReview(review)) => {
-                Some(gather::FtiCert { score: review.value })
+                Some(gather::FtiCert { score: review.value, validity: substrate.validity() })
             }
             _ => None,
         }
@@ -1433,7 +1749,11 @@ impl Processor {
         }
 
         // TODO: which name to pick?
-        producer.names.first().cloned().map(|brand_name| gather::TcoCert { brand_name })
+        producer
+            .names
+            .first()
+            .cloned()
+            .map(|brand_name| gather::TcoCert { brand_name, validity: substrate.validity() })
     }
 
     fn convert_product_ids(
@@ -1456,7 +1776,7 @@ impl Processor {
         let mut gtins = BTreeSet::<gather::Gtin>::new();
         if let Some(ids) = ids.gtin {
             for id in ids {
-                match gather::Gtin::try_from(&id) {
+                match gather::Gtin::try_from(&id).and_then(|gtin| gtin.validate().map(|()| gtin)) {
                     Ok(gtin) => {
                         gtins.insert(gtin);
                     }
@@ -1516,7 +1836,14 @@ impl Processor {
             }
         }
 
-        gather::OrganisationIds { vat_ids, wiki, domains }
+        gather::OrganisationIds {
+            vat_ids,
+            wiki,
+            domains,
+            // The substrates have no fields for these, they are loaded with the attributes.
+            leis: BTreeSet::new(),
+            registration_numbers: BTreeSet::new(),
+        }
     }
 }
 
@@ -1527,22 +1854,16 @@ pub struct Saver {
 }
 
 impl Saver {
-    /// Extracts keywords for DB text search from passed texts.
-    fn extract_keywords(texts: &BTreeSet<gather::Text>) -> BTreeSet<String> {
-        let mut result = BTreeSet::new();
-        for text in texts {
-            for word in text.text.split_whitespace() {
-                result.insert(word.to_lowercase());
-            }
-        }
-        result.remove("");
-        result
-    }
-
     fn finalize(
-        organisations: &BTreeMap<gather::OrganisationId, gather::Organisation>,
+        organisations: &mut BTreeMap<gather::OrganisationId, gather::Organisation>,
         products: &mut BTreeMap<gather::ProductId, gather::Product>,
+        overrides: &Overrides,
+        relevance: &gather::RelevanceMatrix,
+        stop_words: &keywords::StopWords,
     ) {
+        log::info!("Applying overrides");
+        overrides.apply_to_organisations(organisations);
+
         log::info!("Finalizing products");
 
         // Assign certifications to products
@@ -1557,11 +1878,38 @@ impl Saver {
                 //       Make sure all organisations are found.
             }
         }
+        overrides.apply_to_products(products);
+
+        // Extract search keywords from names and (for products) brand names
+        log::info!(" -> extracting keywords");
+        for organisation in organisations.values_mut() {
+            organisation.keywords = keywords::extract(
+                organisation.names.iter().map(|name| name.text.as_str()),
+                stop_words,
+            );
+        }
+        for product in products.values_mut() {
+            let brands = product
+                .manufacturer_ids
+                .iter()
+                .filter_map(|id| organisations.get(id))
+                .flat_map(|organisation| organisation.names.iter());
+            product.keywords = keywords::extract(
+                product.names.iter().chain(brands).map(|name| name.text.as_str()),
+                stop_words,
+            );
+        }
 
         // Calculate product Sustainity score
         log::info!(" -> calculating Sustainity scores");
         for product in products.values_mut() {
-            product.sustainity_score = crate::score::calculate(product);
+            product.sustainity_score = crate::score::calculate(product, relevance);
+        }
+
+        // Build sustainability timelines from the dated records of organisations
+        log::info!(" -> building organisation timelines");
+        for organisation in organisations.values_mut() {
+            organisation.timeline = crate::timeline::build(organisation);
         }
     }
 
@@ -1586,12 +1934,23 @@ impl Saver {
     ///
     /// - fills left-over certifications
     /// - converts into a vector
+    /// - orders names, descriptions, images and websites according to the conflict policy
     fn prepare_organisations(
         organisations: BTreeMap<gather::OrganisationId, gather::Organisation>,
+        provenance: &HashMap<gather::OrganisationId, Provenance>,
+        policy: &ConflictPolicy,
+        conflicts: &mut Vec<Conflict>,
     ) -> Vec<store::Organisation> {
         log::info!("Preparing organisations");
 
-        organisations.into_values().map(|o| o.clone().store()).collect()
+        organisations
+            .into_iter()
+            .map(|(id, o)| {
+                let mut organisation = o.store();
+                policy.apply_to_organisation(&mut organisation, provenance.get(&id), conflicts);
+                organisation
+            })
+            .collect()
     }
 
     /// Prepares organsation keywords data.
@@ -1607,9 +1966,9 @@ impl Saver {
 
         let mut keywords = BTreeMap::<String, BTreeSet<gather::OrganisationId>>::new();
         for organisation in organisations.values() {
-            for keyword in Self::extract_keywords(&organisation.names) {
+            for keyword in &organisation.keywords {
                 keywords
-                    .entry(keyword)
+                    .entry(keyword.clone())
                     .and_modify(|ids| {
                         ids.insert(organisation.db_key.clone());
                     })
@@ -1728,16 +2087,43 @@ impl Saver {
         Ok((ids, id_edges))
     }
 
+    /// Prepares the index of organisation identifiers.
+    ///
+    /// This data is needed to find organisations by exact values of their VAT numbers, LEIs,
+    /// registration numbers and domains.
+    fn prepare_organisation_identifiers(
+        organisations: &BTreeMap<gather::OrganisationId, gather::Organisation>,
+    ) -> Vec<gather::Identifier> {
+        log::info!("Preparing organisation identifiers");
+        organisations
+            .values()
+            .flat_map(|organisation| {
+                organisation.ids.identifiers(&collections::organisation(&organisation.db_key).key)
+            })
+            .collect()
+    }
+
     /// Prepares product data.
     ///
     /// - fills left-over certifications
     /// - converts into a vector
+    /// - orders names, descriptions and images according to the conflict policy
     fn prepare_products(
         products: BTreeMap<gather::ProductId, gather::Product>,
+        provenance: &HashMap<gather::ProductId, Provenance>,
+        policy: &ConflictPolicy,
+        conflicts: &mut Vec<Conflict>,
     ) -> Vec<store::Product> {
         log::info!("Preparing products");
 
-        products.into_values().map(|p| p.clone().store()).collect()
+        products
+            .into_iter()
+            .map(|(id, p)| {
+                let mut product = p.store();
+                policy.apply_to_product(&mut product, provenance.get(&id), conflicts);
+                product
+            })
+            .collect()
     }
 
     /// Prepares product keywords data.
@@ -1753,9 +2139,9 @@ impl Saver {
 
         let mut keywords = BTreeMap::<String, BTreeSet<gather::ProductId>>::new();
         for product in products.values() {
-            for keyword in Self::extract_keywords(&product.names) {
+            for keyword in &product.keywords {
                 keywords
-                    .entry(keyword)
+                    .entry(keyword.clone())
                     .and_modify(|ids| {
                         ids.insert(product.db_key.clone());
                     })
@@ -1880,7 +2266,8 @@ impl Saver {
     /// Data is composed from category vertex collection and edge collection connecting them to products.
     fn prepare_categories(
         products: &BTreeMap<gather::ProductId, gather::Product>,
-    ) -> Result<(Vec<gather::IdEntry>, Vec<gather::Edge>), errors::CrystalizationError> {
+        relevance: &gather::RelevanceMatrix,
+    ) -> Result<(Vec<gather::Category>, Vec<gather::Edge>), errors::CrystalizationError> {
         const COMMENT: &str = "categories";
 
         log::info!("Preparing {COMMENT}");
@@ -1902,13 +2289,19 @@ impl Saver {
         }
 
         let mut uniqueness_check = HashSet::new();
-        let mut categories = Vec::<gather::IdEntry>::new();
+        let mut categories = Vec::<gather::Category>::new();
         let mut category_edges = Vec::<gather::Edge>::new();
         for (category, product_ids) in category_to_products {
             if product_ids.len() < MAX_CATEGORY_PRODUCT_NUM {
                 let category_ki = collections::category(&category);
                 uniqueness_check.insert(category_ki.key.clone());
-                categories.push(gather::IdEntry { db_key: category_ki.key });
+                let relevant_certifications =
+                    relevance.lookup(&category).map(|relevant| relevant.iter().copied().collect());
+                categories.push(gather::Category {
+                    db_key: category_ki.key,
+                    num_products: product_ids.len(),
+                    relevant_certifications,
+                });
                 for product_id in product_ids {
                     let product_ki = collections::product(&product_id);
                     category_edges
@@ -1929,6 +2322,58 @@ impl Saver {
         Ok((categories, category_edges))
     }
 
+    /// Prepares statistics of categories for their overview pages.
+    ///
+    /// The best products are picked the same way the backend sorts the products of a category.
+    fn prepare_category_stats(
+        products: &BTreeMap<gather::ProductId, gather::Product>,
+    ) -> Vec<gather::CategoryStats> {
+        log::info!("Preparing category statistics");
+
+        let mut category_to_products = BTreeMap::<&str, Vec<&gather::Product>>::new();
+        for product in products.values() {
+            for category in &product.categories {
+                category_to_products.entry(category).or_default().push(product);
+            }
+        }
+
+        let mut stats = Vec::new();
+        for (category, products) in category_to_products {
+            if products.len() >= MAX_CATEGORY_PRODUCT_NUM {
+                continue;
+            }
+
+            let mut num_certified = 0;
+            let mut certifications = BTreeMap::new();
+            for product in &products {
+                let kinds = product.certifications.kinds();
+                if !kinds.is_empty() {
+                    num_certified += 1;
+                }
+                for kind in kinds {
+                    *certifications.entry(kind).or_insert(0) += 1;
+                }
+            }
+
+            let mut scored: Vec<(f64, String)> = products
+                .iter()
+                .map(|p| (p.sustainity_score.total, collections::product(&p.db_key).key))
+                .collect();
+            scored.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+            let best_products =
+                scored.into_iter().take(CATEGORY_BEST_PRODUCT_NUM).map(|(_, key)| key).collect();
+
+            stats.push(gather::CategoryStats {
+                db_key: collections::category(category).key,
+                num_products: products.len(),
+                num_certified,
+                certifications,
+                best_products,
+            });
+        }
+        stats
+    }
+
     /// Prepares manufacturing data.
     ///
     /// Data is composed from edges connecting produects to their manufacturers.
@@ -1948,6 +2393,79 @@ impl Saver {
         manufacturing_edges
     }
 
+    /// Saves the index of organisation identifiers.
+    fn save_organisation_identifiers(
+        &self,
+        mut identifiers: Vec<gather::Identifier>,
+    ) -> Result<(), errors::ProcessingError> {
+        log::info!("Saving {} organisation identifiers", identifiers.len());
+        identifiers.sort();
+        outputs::write_json_lines(
+            &self.config.organisation_identifiers_path,
+            &identifiers,
+            self.config.compression,
+        )?;
+        Ok(())
+    }
+
+    /// Prepares the graph of relationships between entities.
+    ///
+    /// Data is composed from typed edges connecting products to their manufacturers, parent
+    /// products and categories, and organisations to their parent organisations. Categories too
+    /// big to be saved are skipped.
+    fn prepare_graph_edges(
+        organisations: &BTreeMap<gather::OrganisationId, gather::Organisation>,
+        products: &BTreeMap<gather::ProductId, gather::Product>,
+    ) -> Vec<gather::GraphEdge> {
+        log::info!("Preparing graph edges");
+
+        let mut category_sizes = HashMap::<&str, usize>::new();
+        for category in products.values().flat_map(|product| &product.categories) {
+            *category_sizes.entry(category.as_str()).or_default() += 1;
+        }
+
+        let mut edges = Vec::<gather::GraphEdge>::new();
+        for organisation in organisations.values() {
+            let organisation_ki = collections::organisation(&organisation.db_key);
+            for parent_id in &organisation.parent_ids {
+                edges.push(gather::GraphEdge {
+                    from: organisation_ki.id.clone(),
+                    to: collections::organisation(parent_id).id,
+                    relation: gather::Relation::OwnedBy,
+                });
+            }
+        }
+        for product in products.values() {
+            let product_ki = collections::product(&product.db_key);
+            for organisation_id in &product.manufacturer_ids {
+                edges.push(gather::GraphEdge {
+                    from: product_ki.id.clone(),
+                    to: collections::organisation(organisation_id).id,
+                    relation: gather::Relation::ManufacturedBy,
+                });
+            }
+            if let Some(parent_id) = &product.variant_of {
+                edges.push(gather::GraphEdge {
+                    from: product_ki.id.clone(),
+                    to: collections::product(parent_id).id,
+                    relation: gather::Relation::VariantOf,
+                });
+            }
+            for category in &product.categories {
+                if category_sizes.get(category.as_str()).copied().unwrap_or_default()
+                    < MAX_CATEGORY_PRODUCT_NUM
+                {
+                    edges.push(gather::GraphEdge {
+                        from: product_ki.id.clone(),
+                        to: collections::category(category).id,
+                        relation: gather::Relation::InCategory,
+                    });
+                }
+            }
+        }
+        edges
+    }
+
     /// Saves organisations.
     fn save_organisations(
         &self,
@@ -1955,7 +2473,31 @@ impl Saver {
     ) -> Result<(), errors::ProcessingError> {
         log::info!("Saving {} organisations", organisations.len());
         organisations.sort_by(|a, b| a.ids.cmp(&b.ids));
-        serde_jsonlines::write_json_lines(&self.config.organisations_path, &organisations)?;
+        outputs::write_json_lines(
+            &self.config.organisations_path,
+            &organisations,
+            self.config.compression,
+        )?;
+        Ok(())
+    }
+
+    /// Saves the lookup of organisation names.
+    ///
+    /// The names are taken after applying the conflict policy, so they match the names of the
+    /// saved organisations.
+    fn save_organisation_labels(
+        &self,
+        organisations: &[store::Organisation],
+    ) -> Result<(), errors::ProcessingError> {
+        let mut labels: Vec<store::OrganisationLabel> =
+            organisations.iter().filter_map(store::Organisation::label).collect();
+        log::info!("Saving {} organisation labels", labels.len());
+        labels.sort();
+        outputs::write_json_lines(
+            &self.config.organisation_labels_path,
+            &labels,
+            self.config.compression,
+        )?;
         Ok(())
     }
 
@@ -1968,16 +2510,18 @@ impl Saver {
 
         log::info!("Saving {} organisation keywords", organisation_keywords.len());
         organisation_keywords.sort();
-        serde_jsonlines::write_json_lines(
+        outputs::write_json_lines(
             &self.config.organisation_keywords_path,
             &organisation_keywords,
+            self.config.compression,
         )?;
 
         log::info!("Saving {} organisation keyword edges", organisation_keyword_edges.len());
         organisation_keyword_edges.sort();
-        serde_jsonlines::write_json_lines(
+        outputs::write_json_lines(
             &self.config.organisation_keyword_edges_path,
             &organisation_keyword_edges,
+            self.config.compression,
         )?;
 
         Ok(())
@@ -1992,13 +2536,18 @@ impl Saver {
 
         log::info!("Saving {} VAT IDs", vat_ids.len());
         vat_ids.sort();
-        serde_jsonlines::write_json_lines(&self.config.organisation_vat_ids_path, &vat_ids)?;
+        outputs::write_json_lines(
+            &self.config.organisation_vat_ids_path,
+            &vat_ids,
+            self.config.compression,
+        )?;
 
         log::info!("Saving {} VAT ID edges", vat_id_edges.len());
         vat_id_edges.sort();
-        serde_jsonlines::write_json_lines(
+        outputs::write_json_lines(
             &self.config.organisation_vat_id_edges_path,
             &vat_id_edges,
+            self.config.compression,
         )?;
 
         Ok(())
@@ -2013,16 +2562,18 @@ impl Saver {
 
         log::info!("Saving {} organisation Wiki IDs", organisation_wiki_ids.len());
         organisation_wiki_ids.sort();
-        serde_jsonlines::write_json_lines(
+        outputs::write_json_lines(
             &self.config.organisation_wiki_ids_path,
             &organisation_wiki_ids,
+            self.config.compression,
         )?;
 
         log::info!("Saving {} organisation Wiki ID edges", organisation_wiki_id_edges.len());
         organisation_wiki_id_edges.sort();
-        serde_jsonlines::write_json_lines(
+        outputs::write_json_lines(
             &self.config.organisation_wiki_id_edges_path,
             &organisation_wiki_id_edges,
+            self.config.compression,
         )?;
 
         Ok(())
@@ -2037,13 +2588,18 @@ impl Saver {
 
         log::info!("Saving {} organisation WWW domains", organisation_wwws.len());
         organisation_wwws.sort();
-        serde_jsonlines::write_json_lines(&self.config.organisation_wwws_path, &organisation_wwws)?;
+        outputs::write_json_lines(
+            &self.config.organisation_wwws_path,
+            &organisation_wwws,
+            self.config.compression,
+        )?;
 
         log::info!("Saving {} organisation WWW domain edges", organisation_www_edges.len());
         organisation_www_edges.sort();
-        serde_jsonlines::write_json_lines(
+        outputs::write_json_lines(
             &self.config.organisation_www_edges_path,
             &organisation_www_edges,
+            self.config.compression,
         )?;
 
         Ok(())
@@ -2056,7 +2612,7 @@ impl Saver {
     ) -> Result<(), errors::ProcessingError> {
         log::info!("Saving {} products.", products.len());
         products.sort_by(|a, b| a.ids.cmp(&b.ids));
-        serde_jsonlines::write_json_lines(&self.config.products_path, &products)?;
+        outputs::write_json_lines(&self.config.products_path, &products, self.config.compression)?;
         Ok(())
     }
 
@@ -2069,13 +2625,18 @@ impl Saver {
 
         log::info!("Saving {} product keywords", product_keywords.len());
         product_keywords.sort();
-        serde_jsonlines::write_json_lines(&self.config.product_keywords_path, &product_keywords)?;
+        outputs::write_json_lines(
+            &self.config.product_keywords_path,
+            &product_keywords,
+            self.config.compression,
+        )?;
 
         log::info!("Saving {} product keyword edges", product_keyword_edges.len());
         product_keyword_edges.sort();
-        serde_jsonlines::write_json_lines(
+        outputs::write_json_lines(
             &self.config.product_keyword_edges_path,
             &product_keyword_edges,
+            self.config.compression,
         )?;
 
         Ok(())
@@ -2090,11 +2651,15 @@ impl Saver {
 
         log::info!("Saving {} product EANs", eans.len());
         eans.sort();
-        serde_jsonlines::write_json_lines(&self.config.product_eans_path, &eans)?;
+        outputs::write_json_lines(&self.config.product_eans_path, &eans, self.config.compression)?;
 
         log::info!("Saving {} product EAN edges", ean_edges.len());
         ean_edges.sort();
-        serde_jsonlines::write_json_lines(&self.config.product_ean_edges_path, &ean_edges)?;
+        outputs::write_json_lines(
+            &self.config.product_ean_edges_path,
+            &ean_edges,
+            self.config.compression,
+        )?;
 
         Ok(())
     }
@@ -2108,11 +2673,19 @@ impl Saver {
 
         log::info!("Saving {} product GTINs", gtins.len());
         gtins.sort();
-        serde_jsonlines::write_json_lines(&self.config.product_gtins_path, &gtins)?;
+        outputs::write_json_lines(
+            &self.config.product_gtins_path,
+            &gtins,
+            self.config.compression,
+        )?;
 
         log::info!("Saving {} product GTIN edges", gtin_edges.len());
         gtin_edges.sort();
-        serde_jsonlines::write_json_lines(&self.config.product_gtin_edges_path, &gtin_edges)?;
+        outputs::write_json_lines(
+            &self.config.product_gtin_edges_path,
+            &gtin_edges,
+            self.config.compression,
+        )?;
 
         Ok(())
     }
@@ -2126,13 +2699,18 @@ impl Saver {
 
         log::info!("Saving {} product Wiki IDs", product_wiki_ids.len());
         product_wiki_ids.sort();
-        serde_jsonlines::write_json_lines(&self.config.product_wiki_ids_path, &product_wiki_ids)?;
+        outputs::write_json_lines(
+            &self.config.product_wiki_ids_path,
+            &product_wiki_ids,
+            self.config.compression,
+        )?;
 
         log::info!("Saving {} product Wiki ID edges", product_wiki_id_edges.len());
         product_wiki_id_edges.sort();
-        serde_jsonlines::write_json_lines(
+        outputs::write_json_lines(
             &self.config.product_wiki_id_edges_path,
             &product_wiki_id_edges,
+            self.config.compression,
         )?;
 
         Ok(())
@@ -2141,21 +2719,115 @@ impl Saver {
     /// Saves categories.
     fn save_categories(
         &self,
-        categories: (Vec<gather::IdEntry>, Vec<gather::Edge>),
+        categories: (Vec<gather::Category>, Vec<gather::Edge>),
     ) -> Result<(), errors::ProcessingError> {
         let (mut categories, mut category_edges) = categories;
 
         log::info!("Saving {} product categories", categories.len());
         categories.sort();
-        serde_jsonlines::write_json_lines(&self.config.categories_path, &categories)?;
+        outputs::write_json_lines(
+            &self.config.categories_path,
+            &categories,
+            self.config.compression,
+        )?;
 
         log::info!("Saving {} product category edges", category_edges.len());
         category_edges.sort();
-        serde_jsonlines::write_json_lines(&self.config.category_edges_path, &category_edges)?;
+        outputs::write_json_lines(
+            &self.config.category_edges_path,
+            &category_edges,
+            self.config.compression,
+        )?;
 
         Ok(())
     }
 
+    /// Saves statistics of categories.
+    fn save_category_stats(
+        &self,
+        mut stats: Vec<gather::CategoryStats>,
+    ) -> Result<(), errors::ProcessingError> {
+        log::info!("Saving {} category statistics", stats.len());
+        stats.sort();
+        outputs::write_json_lines(
+            &self.config.category_stats_path,
+            &stats,
+            self.config.compression,
+        )?;
+        Ok(())
+    }
+
+    /// Saves precomputed product alternatives.
+    ///
+    /// The file is saved even if the precomputation is disabled, so that the collection exists.
+    fn save_product_alternatives(
+        &self,
+        mut alternatives: Vec<gather::ProductAlternatives>,
+    ) -> Result<(), errors::ProcessingError> {
+        log::info!("Saving {} precomputed product alternatives", alternatives.len());
+        alternatives.sort();
+        outputs::write_json_lines(
+            &self.config.product_alternatives_path,
+            &alternatives,
+            self.config.compression,
+        )?;
+        Ok(())
+    }
+
+    /// Saves the registry of supported certifications.
+    ///
+    /// The snapshot date of a certification is the date of the source data of its substrate.
+    fn save_certifications(&self, substrates: &Substrates) -> Result<(), errors::ProcessingError> {
+        let mut certifications = condensing::certification_registry();
+        for certification in &mut certifications {
+            certification.snapshot_date = substrates
+                .list()
+                .iter()
+                .find(|substrate| {
+                    substrate.path.file_stem().and_then(std::ffi::OsStr::to_str)
+                        == Some(certification.db_key.as_str())
+                })
+                .and_then(|substrate| substrate.snapshot_date.clone());
+        }
+
+        log::info!("Saving {} certifications", certifications.len());
+        certifications.sort();
+        outputs::write_json_lines(
+            &self.config.certifications_path,
+            &certifications,
+            self.config.compression,
+        )?;
+
+        Ok(())
+    }
+
+    /// Saves the list of organisations merged from records of multiple sources.
+    fn save_organisation_resolutions(
+        &self,
+        mut resolutions: Vec<OrganisationResolution>,
+    ) -> Result<(), errors::ProcessingError> {
+        log::info!("Saving {} organisation resolutions", resolutions.len());
+        resolutions.sort();
+        outputs::write_json_lines(
+            &self.config.organisation_resolutions_path,
+            &resolutions,
+            self.config.compression,
+        )?;
+        Ok(())
+    }
+
+    /// Saves the list of disagreements between sources about values of merged fields.
+    fn save_conflicts(&self, mut conflicts: Vec<Conflict>) -> Result<(), errors::ProcessingError> {
+        log::info!("Saving {} conflicts", conflicts.len());
+        conflicts.sort();
+        outputs::write_json_lines(
+            &self.config.conflicts_path,
+            &conflicts,
+            self.config.compression,
+        )?;
+        Ok(())
+    }
+
     /// Saves product to organisation edges.
     fn save_manufacturing(
         &self,
@@ -2163,9 +2835,25 @@ impl Saver {
     ) -> Result<(), errors::ProcessingError> {
         log::info!("Saving {} manufacturing edges", manufacturing_edges.len());
         manufacturing_edges.sort();
-        serde_jsonlines::write_json_lines(
+        outputs::write_json_lines(
             &self.config.manufacturing_edges_path,
             &manufacturing_edges,
+            self.config.compression,
+        )?;
+        Ok(())
+    }
+
+    /// Saves the graph of relationships between entities.
+    fn save_graph_edges(
+        &self,
+        mut graph_edges: Vec<gather::GraphEdge>,
+    ) -> Result<(), errors::ProcessingError> {
+        log::info!("Saving {} graph edges", graph_edges.len());
+        graph_edges.sort();
+        outputs::write_json_lines(
+            &self.config.graph_edges_path,
+            &graph_edges,
+            self.config.compression,
         )?;
         Ok(())
     }
@@ -2173,15 +2861,33 @@ impl Saver {
     fn save_all(
         self,
         mut collector: CrystalizationCollector,
+        policy: &ConflictPolicy,
+        overrides: &Overrides,
+        relevance: &gather::RelevanceMatrix,
+        num_alternatives: Option<usize>,
+        stop_words: &keywords::StopWords,
     ) -> Result<(), errors::ProcessingError> {
         log::info!("Saving");
 
-        Self::finalize(&collector.organisations, &mut collector.products);
+        let mut conflicts = Vec::new();
+
+        Self::finalize(
+            &mut collector.organisations,
+            &mut collector.products,
+            overrides,
+            relevance,
+            stop_words,
+        );
 
         {
             let manufacturing_edges = Self::prepare_manufacturing(&collector.products);
             self.save_manufacturing(manufacturing_edges)?;
         }
+        {
+            let graph_edges =
+                Self::prepare_graph_edges(&collector.organisations, &collector.products);
+            self.save_graph_edges(graph_edges)?;
+        }
         {
             let organisation_keywords =
                 Self::prepare_organisation_keywords(&collector.organisations)?;
@@ -2202,7 +2908,17 @@ impl Saver {
             self.save_organisation_wwws(organisation_wwws)?;
         }
         {
-            let organisations = Self::prepare_organisations(collector.organisations);
+            let identifiers = Self::prepare_organisation_identifiers(&collector.organisations);
+            self.save_organisation_identifiers(identifiers)?;
+        }
+        {
+            let organisations = Self::prepare_organisations(
+                collector.organisations,
+                &collector.organisation_provenance,
+                policy,
+                &mut conflicts,
+            );
+            self.save_organisation_labels(&organisations)?;
             self.save_organisations(organisations)?;
         }
         {
@@ -2222,13 +2938,30 @@ impl Saver {
             self.save_product_wiki_ids(product_wiki_ids)?;
         }
         {
-            let categories = Self::prepare_categories(&collector.products)?;
+            let categories = Self::prepare_categories(&collector.products, relevance)?;
             self.save_categories(categories)?;
         }
         {
-            let products = Self::prepare_products(collector.products);
+            let stats = Self::prepare_category_stats(&collector.products);
+            self.save_category_stats(stats)?;
+        }
+        {
+            let alternatives = num_alternatives.map_or_else(Vec::new, |limit| {
+                log::info!("Precomputing product alternatives");
+                alternatives::precompute(&collector.products, limit, MAX_CATEGORY_PRODUCT_NUM)
+            });
+            self.save_product_alternatives(alternatives)?;
+        }
+        {
+            let products = Self::prepare_products(
+                collector.products,
+                &collector.product_provenance,
+                policy,
+                &mut conflicts,
+            );
             self.save_products(products)?;
         }
+        self.save_conflicts(conflicts)?;
 
         log::info!("Condensation finished");
 
@@ -2241,13 +2974,30 @@ pub struct Crystalizer;
 impl Crystalizer {
     pub fn run(config: &config::CrystalizationConfig) -> Result<(), errors::ProcessingError> {
         futures::executor::block_on(async {
+            let policy = ConflictPolicy::load(config.policy_path.as_deref())?;
+            let overrides = Overrides::load(config.overrides_path.as_deref())?;
+            let relevance = relevance::load(config.relevance_path.as_deref())?;
             let (substrates, mut report1) = Substrates::prepare(&config.substrate.substrate_path)?;
-            let (groups, report2) = Grouper::group(&substrates, config)?;
-            let (collector, report3) = Processor::new().process(&substrates, &groups)?;
+            let (groups, resolutions, report2) = Grouper::group(&substrates, config)?;
+            let (mut collector, report3) = Processor::new().process(&substrates, &groups)?;
+            collector.load_attributes(&substrates, &groups)?;
+            report1.add_product_merges(dedup::deduplicate(&mut collector.products));
+            let num_variants = variants::group(&mut collector.products);
+            log::info!("Grouped {num_variants} product variants");
             report1.merge(report2);
             report1.merge(report3);
             report1.report(&substrates);
-            Saver::new((*config.target).clone()).save_all(collector)?;
+            let saver = Saver::new((*config.target).clone());
+            saver.save_organisation_resolutions(resolutions)?;
+            saver.save_certifications(&substrates)?;
+            saver.save_all(
+                collector,
+                &policy,
+                &overrides,
+                &relevance,
+                config.num_alternatives,
+                &config.stop_words,
+            )?;
             Ok(())
         })
     }
@@ -2257,9 +3007,11 @@ impl Crystalizer {
 mod test {
     use serde::{Deserialize, Serialize};
 
+    use std::collections::{BTreeMap, BTreeSet};
+
     use super::{
         gather, Bucket, DataSetId, ExternalId, IdCombiner, IdStructure, IndividualId, InnerId,
-        UniqueId,
+        Saver, UniqueId,
     };
 
     fn e(data_set_id: usize, inner_id: usize) -> ExternalId {
@@ -2435,4 +3187,199 @@ mod test {
         assert_eq!(result.external_to_unique, expected_external_to_unique);
         assert_eq!(result.num_empty_ids, 1);
     }
+
+    #[test]
+    fn certification_registry() {
+        // Keys of the registry must match the certifications the relevance can be configured for.
+        for certification in crate::condensing::certification_registry() {
+            let key = serde_json::Value::String(certification.db_key);
+            assert!(serde_json::from_value::<gather::CertificationKind>(key).is_ok());
+        }
+    }
+
+    #[test]
+    fn graph_edges() {
+        let product = |id: usize, variant_of: Option<usize>| gather::Product {
+            db_key: gather::ProductId::from_value(id),
+            categories: BTreeSet::from(["phone".to_owned()]),
+            manufacturer_ids: BTreeSet::from([gather::OrganisationId::from_value(7)]),
+            variant_of: variant_of.map(gather::ProductId::from_value),
+            ..gather::Product::default()
+        };
+        let products = BTreeMap::from([
+            (gather::ProductId::from_value(1), product(1, None)),
+            (gather::ProductId::from_value(2), product(2, Some(1))),
+        ]);
+
+        let edge = |from: &str, to: &str, relation| gather::GraphEdge {
+            from: from.to_owned(),
+            to: to.to_owned(),
+            relation,
+        };
+        let mut edges = Saver::prepare_graph_edges(&BTreeMap::new(), &products);
+        edges.sort();
+        assert_eq!(
+            edges,
+            vec![
+                edge("products/1", "categories/phone", gather::Relation::InCategory),
+                edge("products/1", "organisations/7", gather::Relation::ManufacturedBy),
+                edge("products/2", "categories/phone", gather::Relation::InCategory),
+                edge("products/2", "organisations/7", gather::Relation::ManufacturedBy),
+                edge("products/2", "products/1", gather::Relation::VariantOf),
+            ]
+        );
+    }
+
+    #[test]
+    fn category_stats() {
+        let product = |id: usize, total: f64| gather::Product {
+            db_key: gather::ProductId::from_value(id),
+            categories: BTreeSet::from(["phone".to_owned()]),
+            sustainity_score: gather::SustainityScore { tree: Vec::new(), total },
+            ..gather::Product::default()
+        };
+        let bcorp =
+            gather::BCorpCert { id: "fairphone".to_owned(), validity: gather::Validity::default() };
+        let tco = gather::TcoCert {
+            brand_name: "Fairphone".to_owned(),
+            validity: gather::Validity::default(),
+        };
+        let mut products: BTreeMap<gather::ProductId, gather::Product> =
+            [product(1, 3.0), product(2, 5.0), product(3, 5.0)]
+                .into_iter()
+                .map(|product| (product.db_key.clone(), product))
+                .collect();
+        for id in [1, 3] {
+            let product = products.get_mut(&gather::ProductId::from_value(id)).unwrap();
+            product.certifications.bcorp = Some(bcorp.clone());
+        }
+        products.get_mut(&gather::ProductId::from_value(3)).unwrap().certifications.tco = Some(tco);
+
+        let stats = Saver::prepare_category_stats(&products);
+        assert_eq!(
+            stats,
+            vec![gather::CategoryStats {
+                db_key: "phone".to_owned(),
+                num_products: 3,
+                num_certified: 2,
+                certifications: BTreeMap::from([
+                    (gather::CertificationKind::BCorp, 2),
+                    (gather::CertificationKind::Tco, 1),
+                ]),
+                best_products: vec!["2".to_owned(), "3".to_owned(), "1".to_owned()],
+            }]
+        );
+    }
+
+    /// Overrides are applied as a part of the finalization, so that removed certifications are not
+    /// inherited by the products and the replaced names end up in the keywords.
+    #[test]
+    fn overrides() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("overrides.yaml");
+        std::fs::write(
+            &path,
+            "organisations:\n  - wiki: Q123\n    remove_certifications: [tco]\n    \
+             names: [Globex]\n\
+             products:\n  - wiki: Q456\n    remove_certifications: [bcorp]\n    \
+             categories: [laptop]\n",
+        )
+        .unwrap();
+        let overrides = Overrides::load(Some(path.as_path())).unwrap();
+
+        let text =
+            |text: &str| gather::Text { text: text.to_owned(), source: gather::Source::Wikidata };
+        let organisation_id = gather::OrganisationId::from_value(7);
+        let organisation = gather::Organisation {
+            db_key: organisation_id.clone(),
+            ids: gather::OrganisationIds {
+                vat_ids: BTreeSet::new(),
+                wiki: BTreeSet::from([gather::WikiId::new(123)]),
+                domains: BTreeSet::new(),
+                leis: BTreeSet::new(),
+                registration_numbers: BTreeSet::new(),
+            },
+            names: BTreeSet::from([text("Acme")]),
+            keywords: BTreeSet::new(),
+            descriptions: BTreeSet::new(),
+            images: BTreeSet::new(),
+            websites: BTreeSet::new(),
+            social: BTreeSet::new(),
+            attributes: gather::Attributes::default(),
+            recognitions: BTreeSet::new(),
+            controversies: BTreeSet::new(),
+            policy_pages: BTreeSet::new(),
+            certifications: gather::Certifications {
+                bcorp: Some(gather::BCorpCert {
+                    id: "acme".to_owned(),
+                    validity: gather::Validity::default(),
+                }),
+                eu_ecolabel: None,
+                fti: None,
+                tco: Some(gather::TcoCert {
+                    brand_name: "Acme".to_owned(),
+                    validity: gather::Validity::default(),
+                }),
+            },
+            timeline: Vec::new(),
+            parent_ids: BTreeSet::new(),
+        };
+        let product = |id: usize, wiki: u64| gather::Product {
+            db_key: gather::ProductId::from_value(id),
+            ids: gather::ProductIds {
+                wiki: BTreeSet::from([gather::WikiId::new(wiki)]),
+                ..gather::ProductIds::default()
+            },
+            names: BTreeSet::from([text("Phone")]),
+            categories: BTreeSet::from(["phone".to_owned()]),
+            manufacturer_ids: BTreeSet::from([organisation_id.clone()]),
+            ..gather::Product::default()
+        };
+
+        let mut organisations = BTreeMap::from([(organisation_id.clone(), organisation)]);
+        let mut products: BTreeMap<gather::ProductId, gather::Product> =
+            [product(1, 456), product(2, 789)]
+                .into_iter()
+                .map(|product| (product.db_key.clone(), product))
+                .collect();
+        Saver::finalize(
+            &mut organisations,
+            &mut products,
+            &overrides,
+            &gather::RelevanceMatrix::default(),
+            &keywords::StopWords::default(),
+        );
+
+        let organisation = &organisations[&organisation_id];
+        assert_eq!(
+            organisation.names.iter().map(|n| n.text.as_str()).collect::<Vec<_>>(),
+            ["Globex"]
+        );
+        assert!(organisation.certifications.bcorp.is_some());
+        assert!(organisation.certifications.tco.is_none());
+
+        let overridden = &products[&gather::ProductId::from_value(1)];
+        assert_eq!(overridden.categories, BTreeSet::from(["laptop".to_owned()]));
+        assert_eq!(overridden.certifications, gather::Certifications::default());
+        assert!(overridden.keywords.contains("globex"));
+
+        let untouched = &products[&gather::ProductId::from_value(2)];
+        assert_eq!(untouched.categories, BTreeSet::from(["phone".to_owned()]));
+        assert!(untouched.certifications.bcorp.is_some());
+        assert!(untouched.certifications.tco.is_none());
+    }
+
+    #[test]
+    fn validity_merging() {
+        let validity = |from: Option<&str>, until: Option<&str>, snapshot: &str| gather::Validity {
+            valid_from: from.map(str::to_owned),
+            valid_until: until.map(str::to_owned),
+            source_snapshot_date: Some(snapshot.to_owned()),
+        };
+
+        let mut merged = gather::Validity::default();
+        merge_validity(&mut merged, validity(None, Some("2025-12-31"), "2024-01-15"));
+        merge_validity(&mut merged, validity(Some("2021-03-01"), Some("2024-06-30"), "2024-02-01"));
+        assert_eq!(merged, validity(Some("2021-03-01"), Some("2025-12-31"), "2024-02-01"));
+    }
 }
@@ -0,0 +1,218 @@
+//! Detection and merging of near-duplicate products.
+//!
+//! The same physical product sometimes comes from several sources under IDs which were not
+//! recognised as equal when grouping the IDs, e.g. an EAN in one source and the same number as a
+//! GTIN-14 in another, or two Wikidata items describing the same model. Such products are
+//! clustered by their GTINs and by normalised model names of the same manufacturer, then merged
+//! into a single canonical product remembering the public IDs of the merged products (their
+//! Wikidata IDs and GTINs, not the database keys which change between runs) in `also_known_as`.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use merge::Merge;
+
+use sustainity_models::gather;
+
+use crate::resolution;
+
+/// Key identifying a product for deduplication.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DuplicateKey {
+    /// Numeric value of a GTIN or EAN (leading zeros don't matter).
+    Gtin(usize),
+
+    /// Manufacturer ID and the normalised model name.
+    Model(gather::OrganisationId, String),
+}
+
+impl resolution::Key for DuplicateKey {
+    fn rank(&self) -> usize {
+        match self {
+            Self::Gtin(_) => 0,
+            Self::Model(..) => 1,
+        }
+    }
+}
+
+impl std::fmt::Display for DuplicateKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Gtin(gtin) => write!(f, "gtin:{gtin:014}"),
+            Self::Model(manufacturer, name) => write!(f, "model:{manufacturer}:{name}"),
+        }
+    }
+}
+
+/// Record of products merged into a single one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProductMerge {
+    /// ID of the product the others were merged into.
+    pub canonical: gather::ProductId,
+
+    /// IDs of the merged products.
+    pub merged: BTreeSet<gather::ProductId>,
+
+    /// Keys which linked the products.
+    pub keys: BTreeSet<String>,
+}
+
+/// Normalises a model name: lowercase alphanumeric words separated by single spaces.
+#[must_use]
+pub fn normalise_name(name: &str) -> String {
+    name.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Extracts the deduplication keys of a product.
+fn extract_keys(product: &gather::Product) -> Vec<DuplicateKey> {
+    let mut keys = BTreeSet::new();
+    keys.extend(product.ids.eans.iter().map(|ean| DuplicateKey::Gtin(ean.as_number())));
+    keys.extend(product.ids.gtins.iter().map(|gtin| DuplicateKey::Gtin(gtin.as_number())));
+    for name in &product.names {
+        let name = normalise_name(&name.text);
+        if !name.is_empty() {
+            for manufacturer in &product.manufacturer_ids {
+                keys.insert(DuplicateKey::Model(manufacturer.clone(), name.clone()));
+            }
+        }
+    }
+    keys.into_iter().collect()
+}
+
+/// Returns the public IDs of a product: `wiki:Q<number>` for Wikidata items and `gtin:<GTIN-14>`
+/// for GTINs and EANs.
+fn public_ids(product: &gather::Product) -> BTreeSet<String> {
+    let wiki = product.ids.wiki.iter().map(|id| gather::EntityId::Wiki(*id).to_string());
+    let gtins = product
+        .ids
+        .eans
+        .iter()
+        .map(gather::Ean::as_number)
+        .chain(product.ids.gtins.iter().map(gather::Gtin::as_number))
+        .map(|number| format!("gtin:{}", gather::Gtin::new(number).to_canonical_string()));
+    wiki.chain(gtins).collect()
+}
+
+/// Replaces IDs of merged products with IDs of their canonical products.
+fn remap(
+    ids: &mut BTreeSet<gather::ProductId>,
+    canonical: &BTreeMap<gather::ProductId, gather::ProductId>,
+) {
+    *ids = std::mem::take(ids)
+        .into_iter()
+        .map(|id| canonical.get(&id).cloned().unwrap_or(id))
+        .collect();
+}
+
+/// Merges near-duplicate products.
+///
+/// Model names link only products without conflicting GTINs. Returns the performed merges.
+pub fn deduplicate(
+    products: &mut BTreeMap<gather::ProductId, gather::Product>,
+) -> Vec<ProductMerge> {
+    let records: Vec<_> =
+        products.iter().map(|(id, product)| (id.clone(), extract_keys(product))).collect();
+    let resolution = resolution::resolve(records);
+
+    let mut merges = Vec::new();
+    let mut canonical_ids = BTreeMap::new();
+    for cluster in resolution.clusters {
+        if cluster.records.len() < 2 {
+            continue;
+        }
+        let mut ids = cluster.records.into_iter();
+        let Some(canonical) = ids.next() else { continue };
+        let merged: BTreeSet<_> = ids.collect();
+        for id in &merged {
+            if let Some(duplicate) = products.remove(id) {
+                if let Some(product) = products.get_mut(&canonical) {
+                    product.also_known_as.extend(public_ids(&duplicate));
+                    product.merge(duplicate);
+                }
+            }
+            canonical_ids.insert(id.clone(), canonical.clone());
+        }
+        let keys = cluster.links.iter().map(ToString::to_string).collect();
+        merges.push(ProductMerge { canonical, merged, keys });
+    }
+
+    if !canonical_ids.is_empty() {
+        for (id, product) in products.iter_mut() {
+            remap(&mut product.follows, &canonical_ids);
+            remap(&mut product.followed_by, &canonical_ids);
+            product.follows.remove(id);
+            product.followed_by.remove(id);
+            if let Some(parent) = product.variant_of.take() {
+                let parent = canonical_ids.get(&parent).cloned().unwrap_or(parent);
+                product.variant_of = (parent != *id).then_some(parent);
+            }
+        }
+    }
+
+    merges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn product(id: usize, names: &[&str], gtins: &[usize], eans: &[usize]) -> gather::Product {
+        gather::Product {
+            db_key: gather::ProductId::from_value(id),
+            ids: gather::ProductIds {
+                eans: eans.iter().map(|ean| gather::Ean::new(*ean)).collect(),
+                gtins: gtins.iter().map(|gtin| gather::Gtin::new(*gtin)).collect(),
+                wiki: BTreeSet::new(),
+            },
+            names: names
+                .iter()
+                .map(|name| gather::Text {
+                    text: (*name).to_owned(),
+                    source: gather::Source::Wikidata,
+                })
+                .collect(),
+            manufacturer_ids: BTreeSet::from([gather::OrganisationId::from_value(1)]),
+            ..gather::Product::default()
+        }
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(normalise_name("  Fairphone-5 (5G) "), "fairphone 5 5g");
+        assert_eq!(normalise_name("--"), "");
+    }
+
+    #[test]
+    fn merging() {
+        let mut products: BTreeMap<_, _> = [
+            product(1, &["Fairphone 5"], &[], &[8_720_818_020_007]),
+            product(2, &["FP5"], &[8_720_818_020_007], &[]),
+            product(3, &["fairphone-5"], &[], &[]),
+            product(4, &["Fairphone 4"], &[8_720_818_020_001], &[]),
+            product(5, &["Fairphone 4"], &[8_720_818_020_002], &[]),
+        ]
+        .into_iter()
+        .map(|product| (product.db_key.clone(), product))
+        .collect();
+
+        products.get_mut(&gather::ProductId::from_value(3)).unwrap().ids.wiki =
+            BTreeSet::from([gather::WikiId::new(900_001)]);
+        let merges = deduplicate(&mut products);
+        assert_eq!(merges.len(), 1);
+        let canonical = gather::ProductId::from_value(1);
+        assert_eq!(merges[0].canonical, canonical);
+        assert_eq!(
+            merges[0].merged,
+            BTreeSet::from([gather::ProductId::from_value(2), gather::ProductId::from_value(3)])
+        );
+        assert_eq!(products.len(), 3);
+        assert_eq!(
+            products[&canonical].also_known_as,
+            BTreeSet::from(["gtin:08720818020007".to_owned(), "wiki:Q900001".to_owned()])
+        );
+        assert_eq!(products[&canonical].names.len(), 3);
+    }
+}
@@ -0,0 +1,181 @@
+//! Comparing two crystalized data sets.
+
+use std::collections::HashMap;
+
+use sustainity_models::{
+    changes::{Change, ChangeKind, ChangedEntity},
+    store as models,
+};
+
+use crate::{config, errors, outputs};
+
+/// Returns the ID variant and the ID used to identify the product in the API.
+fn product_id(ids: &models::ProductIds) -> Option<(&'static str, String)> {
+    if let Some(id) = ids.gtins.first() {
+        Some(("gtin", id.clone()))
+    } else if let Some(id) = ids.eans.first() {
+        Some(("ean", id.clone()))
+    } else {
+        ids.wiki.first().map(|id| ("wiki", id.clone()))
+    }
+}
+
+/// Returns the ID variant and the ID used to identify the organisation in the API.
+fn organisation_id(ids: &models::OrganisationIds) -> Option<(&'static str, String)> {
+    if let Some(id) = ids.vat_ids.first() {
+        Some(("vat", id.clone()))
+    } else if let Some(id) = ids.wiki.first() {
+        Some(("wiki", id.clone()))
+    } else {
+        ids.domains.first().map(|id| ("www", id.clone()))
+    }
+}
+
+/// Entities mapped by their API ID variant and ID.
+type Snapshot<T> = HashMap<(&'static str, String), T>;
+
+/// Difference of a single entity between two snapshots.
+#[derive(Debug)]
+struct Difference<'a, T> {
+    variant: &'static str,
+    id: String,
+    kind: ChangeKind,
+    old: Option<&'a T>,
+    new: Option<&'a T>,
+}
+
+/// Compares two snapshots of entities.
+///
+/// An entity is considered re-scored if `rescored` returns `true` for its old and new values.
+fn compare<'a, T, F>(
+    old: &'a Snapshot<T>,
+    new: &'a Snapshot<T>,
+    rescored: F,
+) -> Vec<Difference<'a, T>>
+where
+    F: Fn(&T, &T) -> bool,
+{
+    let mut differences = Vec::new();
+    for (key, new_value) in new {
+        let kind = match old.get(key) {
+            None => ChangeKind::Added,
+            Some(old_value) if rescored(old_value, new_value) => ChangeKind::Rescored,
+            Some(_) => continue,
+        };
+        let (variant, id) = key.clone();
+        differences.push(Difference { variant, id, kind, old: old.get(key), new: Some(new_value) });
+    }
+    for (key, old_value) in old {
+        if !new.contains_key(key) {
+            let (variant, id) = key.clone();
+            let kind = ChangeKind::Removed;
+            differences.push(Difference { variant, id, kind, old: Some(old_value), new: None });
+        }
+    }
+    differences.sort_by(|a, b| (a.variant, &a.id).cmp(&(b.variant, &b.id)));
+    differences
+}
+
+fn read_product_scores(path: &std::path::Path) -> Result<Snapshot<f64>, errors::ProcessingError> {
+    let mut scores = HashMap::new();
+    for product in outputs::read_json_lines::<models::Product>(path)? {
+        let product = product?;
+        if let Some(id) = product_id(&product.ids) {
+            scores.insert(id, product.sustainity_score.total);
+        }
+    }
+    Ok(scores)
+}
+
+fn read_organisation_certifications(
+    path: &std::path::Path,
+) -> Result<Snapshot<models::Certifications>, errors::ProcessingError> {
+    let mut certifications = HashMap::new();
+    for organisation in outputs::read_json_lines::<models::Organisation>(path)? {
+        let organisation = organisation?;
+        if let Some(id) = organisation_id(&organisation.ids) {
+            certifications.insert(id, organisation.certifications);
+        }
+    }
+    Ok(certifications)
+}
+
+pub struct DiffRunner;
+
+impl DiffRunner {
+    pub fn run(config: &config::DiffConfig) -> Result<(), errors::ProcessingError> {
+        let created = humantime::format_rfc3339_seconds(std::time::SystemTime::now()).to_string();
+        let make_change = |entity, variant: &str, id: String, kind, previous_score, score| Change {
+            db_key: Change::make_key(&config.new_version, entity, variant, &id),
+            version: config.new_version.clone(),
+            previous_version: config.old_version.clone(),
+            created: created.clone(),
+            entity,
+            id_variant: variant.to_owned(),
+            id,
+            kind,
+            previous_score,
+            score,
+        };
+
+        log::info!("Comparing organisations");
+        let old = read_organisation_certifications(&config.old_organisations_path)?;
+        let new = read_organisation_certifications(&config.new_organisations_path)?;
+        let mut changes: Vec<Change> = compare(&old, &new, |old, new| old != new)
+            .into_iter()
+            .map(|d| make_change(ChangedEntity::Organisation, d.variant, d.id, d.kind, None, None))
+            .collect();
+        log::info!(" - {} organisation changes", changes.len());
+
+        log::info!("Comparing products");
+        let old = read_product_scores(&config.old_products_path)?;
+        let new = read_product_scores(&config.new_products_path)?;
+        let product_changes = compare(&old, &new, |old, new| (old - new).abs() > f64::EPSILON);
+        log::info!(" - {} product changes", product_changes.len());
+        changes.extend(product_changes.into_iter().map(|d| {
+            make_change(
+                ChangedEntity::Product,
+                d.variant,
+                d.id,
+                d.kind,
+                d.old.copied(),
+                d.new.copied(),
+            )
+        }));
+
+        outputs::write_json_lines(&config.changes_path, &changes, config.compression)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn comparing() {
+        let old = HashMap::from([
+            (("gtin", "1".to_owned()), 1.0),
+            (("gtin", "2".to_owned()), 2.0),
+            (("gtin", "3".to_owned()), 3.0),
+        ]);
+        let new = HashMap::from([
+            (("gtin", "2".to_owned()), 2.0),
+            (("gtin", "3".to_owned()), 4.0),
+            (("gtin", "4".to_owned()), 5.0),
+        ]);
+
+        let changes: Vec<_> = compare(&old, &new, |old, new| (old - new).abs() > f64::EPSILON)
+            .into_iter()
+            .map(|d| (d.id, d.kind, d.old.copied(), d.new.copied()))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                ("1".to_owned(), ChangeKind::Removed, Some(1.0), None),
+                ("3".to_owned(), ChangeKind::Rescored, Some(3.0), Some(4.0)),
+                ("4".to_owned(), ChangeKind::Added, None, Some(5.0)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,144 @@
+//! Fetching of individual Wikidata entities.
+//!
+//! Items created or fixed after the last dump are missing from the filtered dump. Such items can be
+//! fetched one by one from the Wikidata API (`Special:EntityData`), which returns them in the same
+//! JSON format as the dump. Fetched entities are cached to `<cache>/entities/<id>.json`, so
+//! repeated runs don't hit the API again unless asked to refresh.
+
+use std::io::Write;
+
+use sustainity_collecting::errors::MapSerde;
+use sustainity_wikidata::data::{Entity, Id as WikiId};
+
+use crate::{config, errors};
+
+/// URL of the entity data endpoint.
+const ENTITY_DATA_URL: &str = "https://www.wikidata.org/wiki/Special:EntityData";
+
+/// User agent identifying the requests as required by the Wikimedia API policy.
+const USER_AGENT: &str = "sustainity-lab (https://sustainity.dev)";
+
+/// Fetches entities from the Wikidata API, caching them on disk.
+pub struct EntityFetcher {
+    client: reqwest::Client,
+    cache_dir: Option<std::path::PathBuf>,
+}
+
+impl EntityFetcher {
+    /// Constructs a new `EntityFetcher`.
+    ///
+    /// Without `cache_dir` the entities are always fetched and not cached.
+    #[must_use]
+    pub fn new(cache_dir: Option<&std::path::Path>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            cache_dir: cache_dir.map(|dir| dir.join("entities")),
+        }
+    }
+
+    fn cache_path(&self, id: &str) -> Option<std::path::PathBuf> {
+        self.cache_dir.as_ref().map(|dir| dir.join(id).with_extension("json"))
+    }
+
+    /// Returns the entity as a single line of JSON (the same as in the dump).
+    ///
+    /// The cached entity is used unless `refresh` is set.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the request fails or the cache cannot be read or written.
+    pub async fn fetch_raw(
+        &self,
+        id: WikiId,
+        refresh: bool,
+    ) -> Result<String, errors::ProcessingError> {
+        let id = format!("Q{}", id.get_value());
+        let cache_path = self.cache_path(&id);
+        if let Some(path) = &cache_path {
+            if !refresh && path.exists() {
+                return Ok(std::fs::read_to_string(path)?);
+            }
+        }
+
+        let url = format!("{ENTITY_DATA_URL}/{id}.json");
+        let response =
+            self.client.get(&url).header(reqwest::header::USER_AGENT, USER_AGENT).send().await?;
+        if !response.status().is_success() {
+            return Err(errors::ProcessingError::Download {
+                url,
+                status: response.status().as_u16(),
+            });
+        }
+
+        // The response wraps the entity in `{"entities": {"Q42": {...}}}`. Redirected items are
+        // returned under their new IDs, so the only entity is taken whatever its key is.
+        let mut body: serde_json::Value = response.json().await?;
+        let entity = body
+            .get_mut("entities")
+            .and_then(serde_json::Value::as_object_mut)
+            .and_then(|entities| entities.values_mut().next())
+            .map(serde_json::Value::take)
+            .ok_or_else(|| errors::ProcessingError::WikidataEntity {
+                id: id.clone(),
+                message: "the response contains no entity".to_owned(),
+            })?;
+        let raw = serde_json::to_string(&entity).map_serde()?;
+
+        if let Some(path) = &cache_path {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            std::fs::write(path, &raw)?;
+        }
+        Ok(raw)
+    }
+
+    /// Returns the parsed entity.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the request fails or the entity cannot be parsed.
+    pub async fn fetch(&self, id: WikiId) -> Result<Entity, errors::ProcessingError> {
+        let raw = self.fetch_raw(id, false).await?;
+        serde_json::from_str(&raw).map_err(|e| errors::ProcessingError::WikidataEntity {
+            id: format!("Q{}", id.get_value()),
+            message: e.to_string(),
+        })
+    }
+}
+
+/// Parses the Wikidata IDs given on the command line.
+///
+/// # Errors
+///
+/// Returns `Err` if any of the IDs is not a valid item ID.
+pub fn parse_ids(ids: &[String]) -> Result<Vec<WikiId>, errors::ProcessingError> {
+    Ok(ids.iter().map(|id| WikiId::try_from(id.trim())).collect::<Result<_, _>>()?)
+}
+
+pub struct EntityFetchRunner;
+
+impl EntityFetchRunner {
+    /// Fetches the entities and optionally appends them to a dump file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if fetching an entity or writing the output fails.
+    pub async fn run(config: &config::EntityFetchConfig) -> Result<(), errors::ProcessingError> {
+        let fetcher = EntityFetcher::new(Some(&config.cache_path));
+        let mut output = match &config.output_path {
+            Some(path) => Some(std::fs::OpenOptions::new().create(true).append(true).open(path)?),
+            None => None,
+        };
+
+        for id in parse_ids(&config.ids)? {
+            let raw = fetcher.fetch_raw(id, config.refresh).await?;
+            log::info!("Fetched Q{}", id.get_value());
+            if let Some(file) = &mut output {
+                file.write_all(raw.as_bytes())?;
+                file.write_all(b"\n")?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -28,6 +28,24 @@ pub enum ConfigCheckError {
 
     #[error("Path '{0}' has no parent")]
     PathHasNoParent(std::path::PathBuf),
+
+    #[error("Failed to read config file '{0}': {1}")]
+    ReadConfigFile(std::path::PathBuf, std::io::Error),
+
+    #[error("Failed to parse config file '{0}': {1}")]
+    ParseConfigFile(std::path::PathBuf, serde_yaml::Error),
+
+    #[error("Profile '{0}' is not defined in the config file")]
+    UnknownProfile(String),
+
+    #[error("Variable '{0}' is not defined")]
+    UndefinedVariable(String),
+
+    #[error("The {0} must be greater than zero")]
+    ZeroSize(String),
+
+    #[error("Option '{0}' is not supported by the {1} format")]
+    UnsupportedOption(String, String),
 }
 
 /// Error related to validating the input data.
@@ -63,6 +81,16 @@ pub enum CrystalizationError {
     NotUniqueKeys { comment: String, unique: usize, all: usize },
 }
 
+/// Errors specific to the import command.
+#[derive(Error, Debug)]
+pub enum ImportError {
+    #[error("HTTP: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("Database responded with {status}: {message}")]
+    Database { status: u16, message: String },
+}
+
 /// Errors related to key-value store.
 #[derive(Error, Debug)]
 pub enum KvStoreError {
@@ -88,6 +116,18 @@ pub enum ProcessingError {
     #[error("In file `{1}`.\nYAML parsing error: {0}")]
     ReadYaml(serde_yaml::Error, std::path::PathBuf),
 
+    #[error("In stored substrate `{1}`.\nJSON parsing error: {0}")]
+    ReadStoredSubstrate(serde_json::Error, String),
+
+    #[error("In file `{1}`.\nSpreadsheet reading error: {0}")]
+    ReadSpreadsheet(String, std::path::PathBuf),
+
+    #[error("In file `{1}`.\nUnexpected spreadsheet layout: {0}")]
+    SpreadsheetLayout(String, std::path::PathBuf),
+
+    #[error("Invalid CSS selector `{0}`: {1}")]
+    InvalidSelector(String, String),
+
     #[error("Reading Substrate error: {0}")]
     ReadSubstrate(#[from] sustainity_schema::errors::ReadError),
 
@@ -112,6 +152,38 @@ pub enum ProcessingError {
     #[error("Unknown compression method")]
     CompressionMethod,
 
+    #[error(
+        "Checksum of `{path:?}` does not match the manifest (expected {expected}, found {actual})"
+    )]
+    Checksum { path: std::path::PathBuf, expected: String, actual: String },
+
+    #[error("Size of the downloaded `{path:?}` is {actual} bytes, expected {expected}")]
+    DownloadSize { path: std::path::PathBuf, expected: u64, actual: u64 },
+
+    #[error("In article `{path:?}` at line {line}: {message}")]
+    Article { path: std::path::PathBuf, line: usize, message: String },
+
+    #[error("Library topic `{topic}` has an invalid slug `{slug}`: {message}")]
+    LibrarySlug { topic: String, slug: String, message: String },
+
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("Downloading `{url}` failed with status {status}")]
+    Download { url: String, status: u16 },
+
+    #[error("Cannot infer the kind of `{0:?}` from its name, pass it explicitly")]
+    UnknownFileKind(std::path::PathBuf),
+
+    #[error("JSON Schema error: {0}")]
+    Schema(String),
+
+    #[error("File `{path:?}` does not match its schema ({errors} violations)")]
+    Validation { path: std::path::PathBuf, errors: usize },
+
+    #[error("Malformed Wikidata entity `{id}`: {message}")]
+    WikidataEntity { id: String, message: String },
+
     #[error("Channel sending error: {0}")]
     Channel(#[from] async_channel::SendError<std::string::String>),
 
@@ -124,6 +196,21 @@ pub enum ProcessingError {
     #[error("Crystalization error: {0}")]
     Crystalization(#[from] CrystalizationError),
 
+    #[error("Import error: {0}")]
+    Import(#[from] ImportError),
+
+    #[error("SQLite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+
+    #[error("Arrow error: {0}")]
+    Arrow(#[from] arrow::error::ArrowError),
+
+    #[error("Parquet error: {0}")]
+    Parquet(#[from] parquet::errors::ParquetError),
+
+    #[error("PostgreSQL error: {0}")]
+    Postgres(#[from] postgres::Error),
+
     #[error("ID parsing: {0}")]
     IdParsing(#[from] sustainity_models::ids::ParseIdError),
 
@@ -147,6 +234,15 @@ impl From<IoOrSerdeError> for ProcessingError {
             IoOrSerdeError::ReadCsv(error, path) => Self::ReadCsv(error, path),
             IoOrSerdeError::ReadJson(error, path) => Self::ReadJson(error, path),
             IoOrSerdeError::ReadYaml(error, path) => Self::ReadYaml(error, path),
+            IoOrSerdeError::ReadSpreadsheet(error, path) => {
+                Self::ReadSpreadsheet(error.to_string(), path)
+            }
+            IoOrSerdeError::SpreadsheetLayout(message, path) => {
+                Self::SpreadsheetLayout(message, path)
+            }
+            IoOrSerdeError::InvalidSelector(selector, message) => {
+                Self::InvalidSelector(selector, message)
+            }
             IoOrSerdeError::WriteCsv(error) => Self::WriteCsv(error),
             IoOrSerdeError::WriteJson(error) => Self::WriteJson(error),
             IoOrSerdeError::WriteYaml(error) => Self::WriteYaml(error),
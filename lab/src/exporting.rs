@@ -0,0 +1,90 @@
+//! Exporting crystalized data sets into formats usable without the backend.
+
+use std::collections::BTreeSet;
+
+use sustainity_models::store as models;
+
+use crate::{
+    columnar::ParquetExporter, commands::ExportFormat, config, errors, rdf::RdfExporter,
+    sqlite::SqliteExporter, tabular::CsvExporter,
+};
+
+/// IDs and names of the known certifications.
+pub const CERTIFICATIONS: [(&str, &str); 4] = [
+    ("bcorp", "B Corporation"),
+    ("eu_ecolabel", "EU Ecolabel"),
+    ("fti", "Fashion Transparency Index"),
+    ("tco", "TCO Certified"),
+];
+
+/// Lists the given certifications as pairs of the certification ID and its details (if any).
+#[must_use]
+pub fn certification_entries(
+    certifications: &models::Certifications,
+) -> Vec<(&'static str, Option<String>)> {
+    let mut entries = Vec::new();
+    if let Some(bcorp) = &certifications.bcorp {
+        entries.push(("bcorp", Some(bcorp.id.clone())));
+    }
+    if certifications.eu_ecolabel.is_some() {
+        entries.push(("eu_ecolabel", None));
+    }
+    if let Some(fti) = &certifications.fti {
+        entries.push(("fti", Some(fti.score.to_string())));
+    }
+    if let Some(tco) = &certifications.tco {
+        entries.push(("tco", Some(tco.brand_name.clone())));
+    }
+    entries
+}
+
+/// Returns the parent of the category or `None` for top-level categories.
+#[must_use]
+pub fn category_parent(path: &str) -> Option<&str> {
+    path.rsplit_once('/').map(|(parent, _)| parent)
+}
+
+/// Completes the given categories with all their ancestors.
+///
+/// The result is ordered so that every parent comes before its children.
+#[must_use]
+pub fn category_tree<'a>(categories: impl IntoIterator<Item = &'a String>) -> BTreeSet<String> {
+    let mut tree = BTreeSet::new();
+    for category in categories {
+        let mut path = Some(category.as_str());
+        while let Some(current) = path {
+            if !tree.insert(current.to_owned()) {
+                break;
+            }
+            path = category_parent(current);
+        }
+    }
+    tree
+}
+
+pub struct ExportRunner;
+
+impl ExportRunner {
+    pub fn run(config: &config::ExportConfig) -> Result<(), errors::ProcessingError> {
+        match config.format {
+            ExportFormat::Sqlite => SqliteExporter::export(config),
+            ExportFormat::Parquet => ParquetExporter::export(config),
+            ExportFormat::Turtle | ExportFormat::NTriples => RdfExporter::export(config),
+            ExportFormat::Csv => CsvExporter::export(config),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tree() {
+        let categories = ["a/b/c".to_owned(), "a/d".to_owned(), "e".to_owned()];
+        let tree: Vec<String> = category_tree(&categories).into_iter().collect();
+        assert_eq!(tree, vec!["a", "a/b", "a/b/c", "a/d", "e"]);
+        assert_eq!(category_parent("a/b/c"), Some("a/b"));
+        assert_eq!(category_parent("a"), None);
+    }
+}
@@ -0,0 +1,84 @@
+//! Turns accepted user feedback into matching overrides.
+
+use std::collections::BTreeMap;
+
+use sustainity_collecting::{errors::MapSerde, sustainity};
+use sustainity_models::feedback::{Feedback, FeedbackKind, FeedbackStatus};
+use sustainity_wikidata::data::Id as WikiId;
+
+use crate::{config, errors, utils};
+
+/// Similarity assigned to matches confirmed by a moderator.
+const CONFIRMED_SIMILARITY: f64 = 1.0;
+
+/// Extracts the matching overrides from accepted feedback.
+///
+/// Returns the overrides by name and the keys of the feedback entries they were created from.
+fn collect_overrides(feedback: &[Feedback]) -> (BTreeMap<String, WikiId>, Vec<String>) {
+    let mut overrides = BTreeMap::new();
+    let mut applied = Vec::new();
+    for entry in feedback {
+        if entry.status != FeedbackStatus::Accepted
+            || entry.submission.kind != FeedbackKind::WrongMatch
+        {
+            continue;
+        }
+
+        let Some(correction) = &entry.submission.correction else {
+            log::warn!("Feedback {:?} has no correction", entry.db_key);
+            continue;
+        };
+        match WikiId::try_from(correction.trim()) {
+            Ok(id) => {
+                overrides.insert(entry.submission.subject.trim().to_owned(), id);
+                applied.extend(entry.db_key.clone());
+            }
+            Err(err) => log::warn!("Feedback {:?} has invalid correction: {err}", entry.db_key),
+        }
+    }
+    (overrides, applied)
+}
+
+/// Replaces or adds matches for the overridden names.
+fn apply_overrides(
+    matches: &mut Vec<sustainity::data::NameMatching>,
+    mut overrides: BTreeMap<String, WikiId>,
+) {
+    for entry in matches.iter_mut() {
+        if let Some(id) = overrides.remove(&entry.name) {
+            entry.ids = vec![id];
+            entry.similarity = CONFIRMED_SIMILARITY;
+        }
+    }
+    matches.extend(overrides.into_iter().map(|(name, id)| sustainity::data::NameMatching {
+        name,
+        ids: vec![id],
+        similarity: CONFIRMED_SIMILARITY,
+    }));
+}
+
+pub struct FeedbackRunner;
+
+impl FeedbackRunner {
+    pub fn run(config: &config::FeedbackConfig) -> Result<(), errors::ProcessingError> {
+        let contents = std::fs::read_to_string(&config.feedback_path)?;
+        let feedback: Vec<Feedback> = serde_json::from_str(&contents)
+            .map_err(|e| errors::ProcessingError::ReadJson(e, config.feedback_path.clone()))?;
+
+        let mut matches = if utils::is_path_ok(&config.match_path) {
+            sustainity::reader::parse_id_map(&config.match_path)?
+        } else {
+            Vec::new()
+        };
+
+        let (overrides, applied) = collect_overrides(&feedback);
+        log::info!(" - {} overrides from {} feedback entries", overrides.len(), feedback.len());
+        apply_overrides(&mut matches, overrides);
+
+        let contents = serde_yaml::to_string(&matches).map_serde()?;
+        std::fs::write(&config.match_path, contents)?;
+
+        log::info!("Mark the following feedback as applied: {}", applied.join(", "));
+        Ok(())
+    }
+}
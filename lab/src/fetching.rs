@@ -0,0 +1,329 @@
+//! Downloading of the external data sources.
+//!
+//! The sources to download are listed in a manifest, e.g.:
+//!
+//! ```yaml
+//! - name: wikidata
+//!   url: https://dumps.wikimedia.org/wikidatawiki/entities/latest-all.json.gz
+//!   file: wikidata-20231120-all.json.gz
+//! - name: bcorp
+//!   url: https://example.org/bcorp.csv
+//!   file: bcorp.csv
+//!   sha256: 9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08
+//! ```
+//!
+//! The files are first downloaded into `<file>.part`, so an interrupted download can be resumed
+//! (if the server supports range requests). The ETag and the size of the file are stored in
+//! `<file>.part.info`; a download is resumed only if the file on the server still has the same
+//! ETag and the result is checked against the size (and the digest, if given in the manifest).
+
+use std::io::Write;
+
+use serde::{Deserialize, Serialize};
+
+use sustainity_models::dates;
+
+use crate::{config, errors, outputs};
+
+/// Suffix of files being downloaded.
+const PART_SUFFIX: &str = "part";
+
+/// Suffix of files with information about the files being downloaded.
+const INFO_SUFFIX: &str = "info";
+
+/// A single source to download.
+#[derive(Deserialize, Debug, Clone)]
+pub struct Download {
+    /// Name of the source (used in the logs).
+    pub name: String,
+
+    /// URL to download the file from.
+    pub url: String,
+
+    /// Name of the file in the origin directory.
+    pub file: String,
+
+    /// Expected SHA-256 digest of the file.
+    #[serde(default)]
+    pub sha256: Option<String>,
+}
+
+/// State of a downloaded file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Freshness {
+    /// The file was not downloaded yet.
+    Missing,
+
+    /// The file was downloaded on the given date.
+    Fresh { date: String, age: i64 },
+
+    /// The file was downloaded on the given date, which is too long ago.
+    Stale { date: String, age: i64 },
+}
+
+impl Freshness {
+    /// Checks the freshness of the file.
+    fn check(path: &std::path::Path, max_age_days: i64, today: &str) -> std::io::Result<Self> {
+        if !path.exists() {
+            return Ok(Self::Missing);
+        }
+        let date = dates::format(std::fs::metadata(path)?.modified()?);
+        let age = dates::days_between(&date, today).unwrap_or_default();
+        if age > max_age_days {
+            Ok(Self::Stale { date, age })
+        } else {
+            Ok(Self::Fresh { date, age })
+        }
+    }
+}
+
+/// Information about a file being downloaded, needed to resume the download safely.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+struct PartInfo {
+    /// ETag of the file on the server.
+    #[serde(default)]
+    etag: Option<String>,
+
+    /// Total size of the file.
+    #[serde(default)]
+    size: Option<u64>,
+}
+
+impl PartInfo {
+    /// Reads the information, returning `None` if it is missing or unreadable.
+    fn load(path: &std::path::Path) -> Option<Self> {
+        serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()
+    }
+
+    fn save(&self, path: &std::path::Path) -> Result<(), errors::ProcessingError> {
+        let contents = serde_json::to_string(self).map_err(errors::ProcessingError::WriteJson)?;
+        std::fs::write(path, contents)?;
+        Ok(())
+    }
+
+    /// Extracts the information from a response to a request for the whole file.
+    fn from_response(response: &reqwest::Response) -> Self {
+        let etag = response
+            .headers()
+            .get(reqwest::header::ETAG)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_owned);
+        Self { etag, size: response.content_length() }
+    }
+}
+
+/// Extracts the total size from a `Content-Range` header value (e.g. `bytes 100-999/1000`).
+fn total_size(content_range: &str) -> Option<u64> {
+    content_range.rsplit_once('/')?.1.parse().ok()
+}
+
+/// Removes the partially downloaded file and its information, so the next download starts over.
+fn discard(part_path: &std::path::Path, info_path: &std::path::Path) -> std::io::Result<()> {
+    for path in [part_path, info_path] {
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Downloads the sources.
+struct Fetcher {
+    client: reqwest::Client,
+}
+
+impl Fetcher {
+    fn new() -> Self {
+        Self { client: reqwest::Client::new() }
+    }
+
+    /// Downloads the file, resuming a previously interrupted download if possible.
+    async fn download(
+        &self,
+        download: &Download,
+        path: &std::path::Path,
+    ) -> Result<(), errors::ProcessingError> {
+        let part_path = path.with_extension(match path.extension() {
+            Some(extension) => format!("{}.{PART_SUFFIX}", extension.to_string_lossy()),
+            None => PART_SUFFIX.to_owned(),
+        });
+        let mut info_path = part_path.clone().into_os_string();
+        info_path.push(format!(".{INFO_SUFFIX}"));
+        let info_path = std::path::PathBuf::from(info_path);
+
+        let (mut response, mut file, info) = loop {
+            // Without the ETag there is no way to tell if the file changed in the meantime
+            let stored = PartInfo::load(&info_path).filter(|info| info.etag.is_some());
+            let offset = match &stored {
+                Some(_) => std::fs::metadata(&part_path).map(|m| m.len()).unwrap_or(0),
+                None => 0,
+            };
+
+            let mut request = self.client.get(&download.url);
+            let etag = stored.as_ref().and_then(|info| info.etag.as_ref());
+            if let Some(etag) = etag.filter(|_| offset > 0) {
+                log::info!(" -> resuming {} from byte {offset}", download.name);
+                request = request
+                    .header(reqwest::header::RANGE, format!("bytes={offset}-"))
+                    .header(reqwest::header::IF_RANGE, etag);
+            }
+            let response = request.send().await?;
+            let status = response.status();
+            match status {
+                reqwest::StatusCode::PARTIAL_CONTENT if offset > 0 => {
+                    let mut info = stored.unwrap_or_default();
+                    if info.size.is_none() {
+                        info.size = response
+                            .headers()
+                            .get(reqwest::header::CONTENT_RANGE)
+                            .and_then(|value| value.to_str().ok())
+                            .and_then(total_size);
+                    }
+                    let file = std::fs::OpenOptions::new().append(true).open(&part_path)?;
+                    break (response, file, info);
+                }
+                // The part file does not match the file on the server
+                reqwest::StatusCode::RANGE_NOT_SATISFIABLE if offset > 0 => {
+                    log::warn!(" -> cannot resume {}, starting over", download.name);
+                    discard(&part_path, &info_path)?;
+                }
+                // Also when the file changed and the server ignored the range
+                reqwest::StatusCode::OK => {
+                    let info = PartInfo::from_response(&response);
+                    info.save(&info_path)?;
+                    let file = std::fs::File::create(&part_path)?;
+                    break (response, file, info);
+                }
+                _ => {
+                    return Err(errors::ProcessingError::Download {
+                        url: download.url.clone(),
+                        status: status.as_u16(),
+                    })
+                }
+            }
+        };
+
+        while let Some(chunk) = response.chunk().await? {
+            file.write_all(&chunk)?;
+        }
+        file.flush()?;
+
+        if let Some(expected) = info.size {
+            let actual = std::fs::metadata(&part_path)?.len();
+            if actual != expected {
+                // Start from scratch next time
+                discard(&part_path, &info_path)?;
+                return Err(errors::ProcessingError::DownloadSize {
+                    path: path.to_owned(),
+                    expected,
+                    actual,
+                });
+            }
+        }
+        if let Some(expected) = &download.sha256 {
+            let actual = outputs::checksum(&part_path)?;
+            if !actual.eq_ignore_ascii_case(expected) {
+                // Start from scratch next time
+                discard(&part_path, &info_path)?;
+                return Err(errors::ProcessingError::Checksum {
+                    path: path.to_owned(),
+                    expected: expected.clone(),
+                    actual,
+                });
+            }
+        }
+        std::fs::rename(&part_path, path)?;
+        discard(&part_path, &info_path)?;
+        Ok(())
+    }
+}
+
+pub struct FetchRunner;
+
+impl FetchRunner {
+    /// Downloads missing (and with `refresh` also stale) sources and reports their freshness.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the manifest cannot be read or a download fails.
+    pub async fn run(config: &config::FetchConfig) -> Result<(), errors::ProcessingError> {
+        let contents = std::fs::read_to_string(&config.manifest_path)?;
+        let downloads: Vec<Download> = serde_yaml::from_str(&contents)
+            .map_err(|e| errors::ProcessingError::ReadYaml(e, config.manifest_path.clone()))?;
+
+        let fetcher = Fetcher::new();
+        let today = dates::today();
+        for download in &downloads {
+            let path = config.origin_path.join(&download.file);
+            let freshness = Freshness::check(&path, config.max_age_days, &today)?;
+            let needed = match freshness {
+                Freshness::Missing => true,
+                Freshness::Stale { .. } => config.refresh,
+                Freshness::Fresh { .. } => false,
+            };
+            if needed {
+                log::info!("Downloading {} from {}", download.name, download.url);
+                fetcher.download(download, &path).await?;
+            }
+        }
+
+        log::info!("Freshness of the sources:");
+        for download in &downloads {
+            let path = config.origin_path.join(&download.file);
+            match Freshness::check(&path, config.max_age_days, &today)? {
+                Freshness::Missing => log::warn!(" - {}: missing", download.name),
+                Freshness::Fresh { date, age } => {
+                    log::info!(" - {}: {date} ({age} days old)", download.name);
+                }
+                Freshness::Stale { date, age } => {
+                    log::warn!(" - {}: {date} ({age} days old, stale)", download.name);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn content_range() {
+        assert_eq!(total_size("bytes 100-999/1000"), Some(1000));
+        assert_eq!(total_size("bytes 100-999/*"), None);
+        assert_eq!(total_size("bytes */1000"), Some(1000));
+    }
+
+    #[test]
+    fn part_info() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bcorp.csv.part.info");
+        assert_eq!(PartInfo::load(&path), None);
+
+        let info = PartInfo { etag: Some("\"abc\"".to_owned()), size: Some(1000) };
+        info.save(&path).unwrap();
+        assert_eq!(PartInfo::load(&path), Some(info));
+
+        std::fs::write(&path, "not json").unwrap();
+        assert_eq!(PartInfo::load(&path), None);
+    }
+
+    #[test]
+    fn freshness() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bcorp.csv");
+        assert_eq!(Freshness::check(&path, 30, "2024-03-01").unwrap(), Freshness::Missing);
+
+        std::fs::write(&path, "").unwrap();
+        let today = dates::today();
+        assert!(matches!(
+            Freshness::check(&path, 30, &today).unwrap(),
+            Freshness::Fresh { age: 0, .. }
+        ));
+        assert!(matches!(
+            Freshness::check(&path, 30, "2999-01-01").unwrap(),
+            Freshness::Stale { .. }
+        ));
+    }
+}
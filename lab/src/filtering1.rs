@@ -3,8 +3,8 @@ use std::collections::HashSet;
 use async_trait::async_trait;
 use merge::Merge;
 
-use sustainity_collecting::{data::WikiId, errors::MapSerde};
-use sustainity_wikidata::data::Entity;
+use sustainity_collecting::data::WikiId;
+use sustainity_wikidata::data::{Entity, Item};
 
 use crate::{cache, config, errors, parallel, runners, sources::Sourceable, wikidata::ItemExt};
 
@@ -40,6 +40,36 @@ impl FilteringCollector {
     pub fn add_classes(&mut self, classes: &[WikiId]) {
         self.classes.extend(classes.iter().copied());
     }
+
+    /// Collects the manufacturers and classes the item refers to.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the item contains malformed IDs.
+    pub fn add_item(&mut self, item: &Item) -> Result<(), errors::ProcessingError> {
+        if let Some(manufacturer_ids) = item.get_manufacturer_ids()? {
+            self.add_manufacturer_ids(&manufacturer_ids);
+        }
+        if let Some(class_ids) = item.get_superclasses()? {
+            self.add_classes(&class_ids);
+        }
+        if let Some(class_ids) = item.get_classes()? {
+            self.add_classes(&class_ids);
+        }
+        Ok(())
+    }
+
+    /// Converts the collected IDs to the cache data.
+    #[must_use]
+    pub fn into_cache(self) -> cache::Wikidata {
+        let mut cache = cache::Wikidata {
+            manufacturer_ids: self.manufacturer_ids.into_iter().collect(),
+            classes: self.classes.into_iter().collect(),
+        };
+        cache.manufacturer_ids.sort();
+        cache.classes.sort();
+        cache
+    }
 }
 
 impl merge::Merge for FilteringCollector {
@@ -73,17 +103,7 @@ impl runners::WikidataWorker for FilteringWorker {
         _tx: parallel::Sender<Self::Output>,
     ) -> Result<(), errors::ProcessingError> {
         match entity {
-            Entity::Item(item) => {
-                if let Some(manufacturer_ids) = item.get_manufacturer_ids()? {
-                    self.collector.add_manufacturer_ids(&manufacturer_ids);
-                }
-                if let Some(class_ids) = item.get_superclasses()? {
-                    self.collector.add_classes(&class_ids);
-                }
-                if let Some(class_ids) = item.get_classes()? {
-                    self.collector.add_classes(&class_ids);
-                }
-            }
+            Entity::Item(item) => self.collector.add_item(&item)?,
             Entity::Property(_property) => (),
         }
         Ok(())
@@ -132,19 +152,8 @@ impl runners::Stash for FilteringStash {
         log::info!("Found {} manufacturers", self.collector.manufacturer_ids.len());
         log::info!("Found {} products or classes", self.collector.classes.len());
 
-        let mut cache = cache::Wikidata {
-            manufacturer_ids: self.collector.manufacturer_ids.iter().copied().collect(),
-            classes: self.collector.classes.iter().copied().collect(),
-        };
-
-        cache.manufacturer_ids.sort();
-        cache.classes.sort();
-
-        log::info!("Serializing...");
-        let contents = serde_json::to_string_pretty(&cache).map_serde()?;
-
         log::info!("Writing to {:?}", self.config.wikidata_cache_path);
-        std::fs::write(&self.config.wikidata_cache_path, contents)?;
+        cache::save(&self.config.wikidata_cache_path, &self.collector.into_cache())?;
 
         Ok(())
     }
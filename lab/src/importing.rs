@@ -0,0 +1,365 @@
+//! Importing crystalized data sets into the backend database.
+//!
+//! Each data set is imported into its own database, so that the backend can keep serving the
+//! previous one until it's told to switch over. Importing is idempotent: the database and its
+//! collections are created if needed and truncated otherwise. The changes found by the `diff`
+//! command are added to the database shared by all data sets.
+//!
+//! The database in which the backend stores what the users submit (feedback, product requests and
+//! the searches with no results) is prepared as well, but never truncated.
+
+use std::io::BufRead;
+
+use serde_json::{json, Value};
+
+use crate::{config, errors, errors::ImportError, outputs};
+
+/// Number of entries sent in a single import request.
+const BATCH_SIZE: usize = 10_000;
+
+/// Database holding changes between all the imported data sets (shared with the backend).
+const CHANGES_DATABASE: &str = "sustainity_changes";
+
+/// Collection holding the changes.
+const CHANGES_COLLECTION: &str = "changes";
+
+/// Database holding the data submitted by the users (shared with the backend).
+pub(crate) const FEEDBACK_DATABASE: &str = "sustainity_feedback";
+
+/// Collection holding the requests for products missing in the data sets.
+pub(crate) const PRODUCT_REQUESTS_COLLECTION: &str = "product_requests";
+
+/// Collection holding the daily counts of search queries with no results.
+pub(crate) const ZERO_RESULTS_COLLECTION: &str = "zero_result_queries";
+
+/// Collections of the feedback database.
+const FEEDBACK_COLLECTIONS: &[Collection] = &[
+    Collection::document("feedback", &[&["status", "created"]]),
+    Collection::document(PRODUCT_REQUESTS_COLLECTION, &[&["count", "created"]]),
+    Collection::document(ZERO_RESULTS_COLLECTION, &[&["day", "query"]]),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CollectionKind {
+    Document,
+    Edge,
+}
+
+impl CollectionKind {
+    /// Collection type as understood by ArangoDB.
+    fn code(self) -> u8 {
+        match self {
+            Self::Document => 2,
+            Self::Edge => 3,
+        }
+    }
+}
+
+/// Description of an imported collection.
+struct Collection {
+    name: &'static str,
+    kind: CollectionKind,
+
+    /// Persistent indexes (lists of indexed fields) speeding up the backend queries.
+    indexes: &'static [&'static [&'static str]],
+}
+
+impl Collection {
+    const fn document(name: &'static str, indexes: &'static [&'static [&'static str]]) -> Self {
+        Self { name, kind: CollectionKind::Document, indexes }
+    }
+
+    const fn edge(name: &'static str) -> Self {
+        Self { name, kind: CollectionKind::Edge, indexes: &[] }
+    }
+}
+
+/// Collections of a data set. Each is imported from a file of the same name.
+const COLLECTIONS: &[Collection] = &[
+    Collection::document(
+        "organisations",
+        &[&["ids.leis[*]"], &["ids.registration_numbers[*]"], &["ids.vat_ids[*]"]],
+    ),
+    Collection::document("organisation_keywords", &[&["keyword"]]),
+    Collection::edge("organisation_keyword_edges"),
+    Collection::document("organisation_vat_ids", &[]),
+    Collection::edge("organisation_vat_id_edges"),
+    Collection::document("organisation_wiki_ids", &[]),
+    Collection::edge("organisation_wiki_id_edges"),
+    Collection::document("organisation_wwws", &[]),
+    Collection::edge("organisation_www_edges"),
+    Collection::document("organisation_identifiers", &[&["kind", "value"]]),
+    Collection::document("organisation_labels", &[]),
+    Collection::document("products", &[&["sustainity_score.total"], &["external_ids.asin[*]"]]),
+    Collection::document("product_keywords", &[&["keyword"]]),
+    Collection::edge("product_keyword_edges"),
+    Collection::document("product_eans", &[]),
+    Collection::edge("product_ean_edges"),
+    Collection::document("product_gtins", &[]),
+    Collection::edge("product_gtin_edges"),
+    Collection::document("product_wiki_ids", &[]),
+    Collection::edge("product_wiki_id_edges"),
+    Collection::document("categories", &[]),
+    Collection::edge("category_edges"),
+    Collection::document("category_stats", &[]),
+    Collection::document("product_alternatives", &[]),
+    Collection::document("certifications", &[]),
+    Collection::edge("manufacturing_edges"),
+    Collection::edge("graph_edges"),
+    Collection::document("library", &[&["slug"]]),
+    Collection::document("presentations", &[]),
+];
+
+/// Reads up to `size` non-empty lines into a single JSON Lines request body.
+///
+/// Returns the body and the number of read lines, or `None` if there are no more lines.
+fn read_batch<R: BufRead>(
+    lines: &mut std::io::Lines<R>,
+    size: usize,
+) -> Result<Option<(String, usize)>, std::io::Error> {
+    let mut body = String::new();
+    let mut count = 0;
+    while count < size {
+        let Some(line) = lines.next() else {
+            break;
+        };
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        body.push_str(&line);
+        body.push('\n');
+        count += 1;
+    }
+    Ok((count > 0).then_some((body, count)))
+}
+
+/// Minimal client of the ArangoDB HTTP API.
+pub(crate) struct Arango {
+    client: reqwest::Client,
+    url: String,
+    user: String,
+    password: String,
+}
+
+impl Arango {
+    pub(crate) fn new(url: &str, user: &str, password: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: url.trim_end_matches('/').to_owned(),
+            user: user.to_owned(),
+            password: password.to_owned(),
+        }
+    }
+
+    /// Sends the request, treating the `allowed` statuses as success.
+    async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+        allowed: &[reqwest::StatusCode],
+    ) -> Result<Value, ImportError> {
+        let response = request.basic_auth(&self.user, Some(&self.password)).send().await?;
+        let status = response.status();
+        let body: Value = response.json().await.unwrap_or_default();
+        if status.is_success() || allowed.contains(&status) {
+            Ok(body)
+        } else {
+            let message = body.get("errorMessage").and_then(Value::as_str).unwrap_or_default();
+            Err(ImportError::Database { status: status.as_u16(), message: message.to_owned() })
+        }
+    }
+
+    async fn create_database(&self, database: &str) -> Result<(), ImportError> {
+        let request = self
+            .client
+            .post(format!("{}/_api/database", self.url))
+            .json(&json!({"name": database}));
+        self.send(request, &[reqwest::StatusCode::CONFLICT]).await?;
+        Ok(())
+    }
+
+    /// Creates the collection or, if it exists, removes all its entries.
+    async fn prepare_collection(
+        &self,
+        database: &str,
+        name: &str,
+        kind: CollectionKind,
+        truncate: bool,
+    ) -> Result<(), ImportError> {
+        let url = format!("{}/_db/{database}/_api/collection", self.url);
+        let request = self.client.post(&url).json(&json!({"name": name, "type": kind.code()}));
+        let response = self.send(request, &[reqwest::StatusCode::CONFLICT]).await?;
+        let existed = response.get("error").and_then(Value::as_bool).unwrap_or(false);
+        if existed && truncate {
+            let request = self.client.put(format!("{url}/{name}/truncate"));
+            self.send(request, &[]).await?;
+        }
+        Ok(())
+    }
+
+    /// Creates a persistent index (creating an existing one does nothing).
+    async fn create_index(
+        &self,
+        database: &str,
+        collection: &str,
+        fields: &[&str],
+    ) -> Result<(), ImportError> {
+        let request = self
+            .client
+            .post(format!("{}/_db/{database}/_api/index", self.url))
+            .query(&[("collection", collection)])
+            .json(&json!({"type": "persistent", "fields": fields}));
+        self.send(request, &[]).await?;
+        Ok(())
+    }
+
+    /// Creates a TTL index removing the entries once the time in the field (in seconds since the
+    /// Unix epoch) passes.
+    async fn create_ttl_index(
+        &self,
+        database: &str,
+        collection: &str,
+        field: &str,
+    ) -> Result<(), ImportError> {
+        let request = self
+            .client
+            .post(format!("{}/_db/{database}/_api/index", self.url))
+            .query(&[("collection", collection)])
+            .json(&json!({"type": "ttl", "fields": [field], "expireAfter": 0}));
+        self.send(request, &[]).await?;
+        Ok(())
+    }
+
+    /// Runs the AQL query and returns all its results, reading them in batches.
+    pub(crate) async fn query(
+        &self,
+        database: &str,
+        query: &str,
+        bind_vars: Value,
+    ) -> Result<Vec<Value>, ImportError> {
+        let url = format!("{}/_db/{database}/_api/cursor", self.url);
+        let body = json!({"query": query, "bindVars": bind_vars, "batchSize": BATCH_SIZE});
+        let mut response = self.send(self.client.post(&url).json(&body), &[]).await?;
+        let mut results = Vec::new();
+        loop {
+            if let Some(Value::Array(batch)) = response.get_mut("result").map(Value::take) {
+                results.extend(batch);
+            }
+            let has_more = response.get("hasMore").and_then(Value::as_bool).unwrap_or(false);
+            let Some(id) = response.get("id").and_then(Value::as_str).filter(|_| has_more) else {
+                break;
+            };
+            response = self.send(self.client.put(format!("{url}/{id}")), &[]).await?;
+        }
+        Ok(results)
+    }
+
+    /// Imports the JSON Lines file into the collection, replacing entries with the same keys.
+    ///
+    /// Returns the number of imported entries.
+    async fn import(
+        &self,
+        database: &str,
+        collection: &str,
+        path: &std::path::Path,
+    ) -> Result<usize, errors::ProcessingError> {
+        let mut lines = outputs::open(path)?.lines();
+        let url = format!("{}/_db/{database}/_api/import", self.url);
+        let mut imported = 0;
+        while let Some((body, count)) = read_batch(&mut lines, BATCH_SIZE)? {
+            let request = self
+                .client
+                .post(&url)
+                .query(&[
+                    ("collection", collection),
+                    ("type", "documents"),
+                    ("onDuplicate", "replace"),
+                    ("complete", "true"),
+                ])
+                .body(body);
+            self.send(request, &[]).await?;
+            imported += count;
+        }
+        Ok(imported)
+    }
+}
+
+pub struct ImportRunner;
+
+impl ImportRunner {
+    /// Runs the `import` command.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if reading the files failed or the database rejected any of the requests.
+    pub async fn run(config: &config::ImportConfig) -> Result<(), errors::ProcessingError> {
+        let arango = Arango::new(&config.url, &config.user, &config.password);
+
+        log::info!("Preparing database `{}`", config.database);
+        arango.create_database(&config.database).await?;
+
+        for collection in COLLECTIONS {
+            let path = config.target_path.join(collection.name).with_extension("jsonl");
+            arango
+                .prepare_collection(&config.database, collection.name, collection.kind, true)
+                .await?;
+            let count = arango.import(&config.database, collection.name, &path).await?;
+            for fields in collection.indexes {
+                arango.create_index(&config.database, collection.name, fields).await?;
+            }
+            log::info!(" - {}: {count} entries", collection.name);
+        }
+
+        if crate::utils::is_path_ok(&outputs::resolve(&config.changes_path)) {
+            log::info!("Importing changes");
+            arango.create_database(CHANGES_DATABASE).await?;
+            let kind = CollectionKind::Document;
+            arango.prepare_collection(CHANGES_DATABASE, CHANGES_COLLECTION, kind, false).await?;
+            let count =
+                arango.import(CHANGES_DATABASE, CHANGES_COLLECTION, &config.changes_path).await?;
+            for fields in [["previous_version"], ["created"]] {
+                arango.create_index(CHANGES_DATABASE, CHANGES_COLLECTION, &fields).await?;
+            }
+            log::info!(" - {CHANGES_COLLECTION}: {count} entries");
+        }
+
+        log::info!("Preparing database `{FEEDBACK_DATABASE}`");
+        arango.create_database(FEEDBACK_DATABASE).await?;
+        for collection in FEEDBACK_COLLECTIONS {
+            arango
+                .prepare_collection(FEEDBACK_DATABASE, collection.name, collection.kind, false)
+                .await?;
+            for fields in collection.indexes {
+                arango.create_index(FEEDBACK_DATABASE, collection.name, fields).await?;
+            }
+        }
+        arango.create_ttl_index(FEEDBACK_DATABASE, ZERO_RESULTS_COLLECTION, "expires").await?;
+
+        log::info!(
+            "Imported into `{}`; tell the backend to serve it with `POST /admin/reload`",
+            config.database
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batching() {
+        let input = "{\"a\":1}\n\n{\"a\":2}\n{\"a\":3}\n";
+        let mut lines = std::io::Cursor::new(input).lines();
+
+        let (body, count) = read_batch(&mut lines, 2).unwrap().unwrap();
+        assert_eq!(body, "{\"a\":1}\n{\"a\":2}\n");
+        assert_eq!(count, 2);
+
+        let (body, count) = read_batch(&mut lines, 2).unwrap().unwrap();
+        assert_eq!(body, "{\"a\":3}\n");
+        assert_eq!(count, 1);
+
+        assert!(read_batch(&mut lines, 2).unwrap().is_none());
+    }
+}
@@ -4,24 +4,58 @@
 #![allow(clippy::missing_errors_doc)]
 
 pub mod advisors;
+pub mod alternatives;
 pub mod analysis;
+pub mod attributes;
 pub mod cache;
 pub mod categories;
+pub mod cli;
+pub mod columnar;
 pub mod commands;
 pub mod condensing;
 pub mod config;
 pub mod connecting;
 pub mod convert;
 pub mod crystalizing;
+pub mod dedup;
+pub mod diffing;
+pub mod entities;
 pub mod errors;
+pub mod exporting;
+pub mod feedback;
+pub mod fetching;
 pub mod filtering1;
 pub mod filtering2;
+pub mod importing;
+pub mod library;
+pub mod limits;
+pub mod matching;
+pub mod memory;
+pub mod outputs;
+pub mod overrides;
 pub mod oxidation;
 pub mod parallel;
+pub mod policy;
+pub mod postgresql;
+pub mod presentations;
+pub mod rdf;
+pub mod relevance;
+pub mod requests;
+pub mod resolution;
+pub mod rows;
 pub mod runners;
 pub mod sampling;
 pub mod score;
+pub mod scraping;
 pub mod sources;
+pub mod sparql;
+pub mod sqlite;
+pub mod tabular;
+pub mod timeline;
+pub mod unmatched;
 pub mod updating;
 pub mod utils;
+pub mod validation;
+pub mod variants;
 pub mod wikidata;
+pub mod workbench;
@@ -0,0 +1,487 @@
+//! Processing of the library articles.
+//!
+//! An article is a markdown file which may start with a YAML front matter overriding the values
+//! from the library info file:
+//!
+//! ```markdown
+//! ---
+//! title: Fashion Transparency Index
+//! summary: How transparent are the fashion brands?
+//! tags: [fashion, transparency]
+//! last_reviewed: 2024-03-01
+//! ---
+//!
+//! # Fashion Transparency Index
+//! ```
+//!
+//! The articles are also rendered to sanitised HTML with anchors on the headings, so that the
+//! clients don't need their own markdown renderers.
+//!
+//! Variants of an article in other languages are stored next to it with the language tag before
+//! the extension (`cert:bcorp.nl.md`). If there is no article without a language tag, the variant
+//! in the default language (`cert:bcorp.en.md`) is used instead.
+//!
+//! Articles are addressed by slugs instead of the topic IDs, so that their URLs survive renaming
+//! of the topics. Each topic must have its slug set in the library info file (deriving it from the
+//! ID would change the URL whenever the topic is renamed). When a slug changes, the old one should be listed in `former_slugs` to keep the old URLs
+//! working.
+
+use std::collections::HashMap;
+
+use pulldown_cmark::{Event, Options, Parser, Tag};
+use serde::Deserialize;
+
+use sustainity_models::{
+    dates,
+    gather::{LibraryItem, LibraryTocEntry},
+};
+
+use crate::errors;
+
+/// Line delimiting the front matter.
+const FRONT_MATTER_DELIMITER: &str = "---";
+
+/// Extension of the article files.
+const ARTICLE_EXTENSION: &str = "md";
+
+/// Language of the articles without a language tag.
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// Metadata of an article.
+#[derive(Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct FrontMatter {
+    /// Article title.
+    #[serde(default)]
+    pub title: Option<String>,
+
+    /// Short one line summary of the article.
+    #[serde(default)]
+    pub summary: Option<String>,
+
+    /// Tags of the article.
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    /// Date of the last review of the article (`YYYY-MM-DD`).
+    #[serde(default)]
+    pub last_reviewed: Option<String>,
+}
+
+/// A parsed article.
+#[derive(Debug, Clone)]
+pub struct Article {
+    /// Metadata of the article.
+    pub front_matter: FrontMatter,
+
+    /// Contents of the article (without the front matter).
+    pub body: String,
+
+    /// Line number of the first line of the body (for error messages).
+    body_start: usize,
+}
+
+impl Article {
+    /// Parses the article.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the front matter is not terminated, is not valid YAML or contains an
+    /// invalid date.
+    pub fn parse(path: &std::path::Path, contents: &str) -> Result<Self, errors::ProcessingError> {
+        let mut lines = contents.split_inclusive('\n');
+        if lines.next().map(str::trim_end) != Some(FRONT_MATTER_DELIMITER) {
+            return Ok(Self {
+                front_matter: FrontMatter::default(),
+                body: contents.to_owned(),
+                body_start: 1,
+            });
+        }
+
+        let mut yaml = String::new();
+        let mut num_lines = 1;
+        let mut terminated = false;
+        for line in lines.by_ref() {
+            num_lines += 1;
+            if line.trim_end() == FRONT_MATTER_DELIMITER {
+                terminated = true;
+                break;
+            }
+            yaml.push_str(line);
+        }
+        if !terminated {
+            return Err(errors::ProcessingError::Article {
+                path: path.to_owned(),
+                line: 1,
+                message: "the front matter is not terminated with `---`".to_owned(),
+            });
+        }
+
+        let front_matter: FrontMatter = if yaml.trim().is_empty() {
+            FrontMatter::default()
+        } else {
+            serde_yaml::from_str(&yaml)
+                .map_err(|e| errors::ProcessingError::ReadYaml(e, path.to_owned()))?
+        };
+        if let Some(date) = &front_matter.last_reviewed {
+            if dates::parse(date).is_none() {
+                return Err(errors::ProcessingError::Article {
+                    path: path.to_owned(),
+                    line: 1,
+                    message: format!("`last_reviewed` is not a valid date: {date:?}"),
+                });
+            }
+        }
+
+        Ok(Self { front_matter, body: lines.collect(), body_start: num_lines + 1 })
+    }
+
+    /// Checks that all the local images and links refer to existing files in `dir`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` pointing to the first reference to a missing file.
+    pub fn check_references(
+        &self,
+        path: &std::path::Path,
+        dir: &std::path::Path,
+    ) -> Result<(), errors::ProcessingError> {
+        for (i, line) in self.body.lines().enumerate() {
+            for reference in find_references(line) {
+                if is_local(reference) && !dir.join(strip_fragment(reference)).exists() {
+                    return Err(errors::ProcessingError::Article {
+                        path: path.to_owned(),
+                        line: self.body_start + i,
+                        message: format!("referenced file {reference:?} does not exist"),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Article rendered to HTML.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rendered {
+    /// Sanitised HTML.
+    pub html: String,
+
+    /// Table of contents.
+    pub toc: Vec<LibraryTocEntry>,
+}
+
+/// Converts the heading text into an HTML ID, e.g. "Why it matters?" into "why-it-matters".
+fn slugify(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.trim().chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            result.push(c);
+        } else if (c.is_whitespace() || c == '-') && !result.ends_with('-') {
+            result.push('-');
+        }
+    }
+    result.trim_end_matches('-').to_owned()
+}
+
+/// Generates unique anchors for headings.
+#[derive(Default)]
+struct Anchors {
+    used: HashMap<String, usize>,
+}
+
+impl Anchors {
+    fn make(&mut self, title: &str) -> String {
+        let slug = slugify(title);
+        let slug = if slug.is_empty() { "section".to_owned() } else { slug };
+        let count = self.used.entry(slug.clone()).or_insert(0);
+        *count += 1;
+        if *count == 1 {
+            slug
+        } else {
+            format!("{slug}-{}", *count - 1)
+        }
+    }
+}
+
+/// Renders the markdown into sanitised HTML and extracts the table of contents.
+#[must_use]
+pub fn render(markdown: &str) -> Rendered {
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH;
+    let mut events = Vec::new();
+    let mut toc = Vec::new();
+    let mut anchors = Anchors::default();
+    let mut heading: Option<Vec<Event<'_>>> = None;
+    for event in Parser::new_ext(markdown, options) {
+        match event {
+            Event::Start(Tag::Heading(..)) => heading = Some(Vec::new()),
+            Event::End(Tag::Heading(level, ..)) => {
+                let inner = heading.take().unwrap_or_default();
+                let title: String = inner
+                    .iter()
+                    .filter_map(|event| match event {
+                        Event::Text(text) | Event::Code(text) => Some(text.as_ref()),
+                        _ => None,
+                    })
+                    .collect();
+                let anchor = anchors.make(&title);
+                let level = level as u8;
+                events.push(Event::Html(format!("<h{level} id=\"{anchor}\">").into()));
+                events.extend(inner);
+                events.push(Event::Html(format!("</h{level}>\n").into()));
+                toc.push(LibraryTocEntry { level, title, anchor });
+            }
+            event => match &mut heading {
+                Some(inner) => inner.push(event),
+                None => events.push(event),
+            },
+        }
+    }
+
+    let mut html = String::new();
+    pulldown_cmark::html::push_html(&mut html, events.into_iter());
+
+    let mut sanitizer = ammonia::Builder::default();
+    for tag in ["h1", "h2", "h3", "h4", "h5", "h6"] {
+        sanitizer.add_tag_attributes(tag, ["id"]);
+    }
+    Rendered { html: sanitizer.clean(&html).to_string(), toc }
+}
+
+/// Checks if the text is a (simplified) BCP 47 language tag, e.g. `nl` or `pt-br`.
+fn is_language_tag(text: &str) -> bool {
+    let mut parts = text.split('-');
+    let primary = parts.next().unwrap_or_default();
+    (2..=3).contains(&primary.len())
+        && primary.chars().all(|c| c.is_ascii_lowercase())
+        && parts.all(|part| {
+            (2..=8).contains(&part.len()) && part.chars().all(|c| c.is_ascii_alphanumeric())
+        })
+}
+
+/// Returns the path to the article about the topic.
+///
+/// Prefers the article without a language tag, falling back to the one in the default language.
+#[must_use]
+pub fn find_article(dir: &std::path::Path, id: &str) -> std::path::PathBuf {
+    let path = dir.join(format!("{id}.{ARTICLE_EXTENSION}"));
+    let fallback = dir.join(format!("{id}.{DEFAULT_LANGUAGE}.{ARTICLE_EXTENSION}"));
+    if !path.exists() && fallback.exists() {
+        fallback
+    } else {
+        path
+    }
+}
+
+/// Finds variants of the article about the topic in other languages.
+///
+/// Returns the language tags with paths to the articles, sorted by the language.
+///
+/// # Errors
+///
+/// Returns `Err` if the directory cannot be read.
+pub fn find_translations(
+    dir: &std::path::Path,
+    id: &str,
+) -> Result<Vec<(String, std::path::PathBuf)>, errors::ProcessingError> {
+    let primary = find_article(dir, id);
+    let mut result = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path == primary {
+            continue;
+        }
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        let language = name
+            .strip_prefix(id)
+            .and_then(|rest| rest.strip_prefix('.'))
+            .and_then(|rest| rest.strip_suffix(ARTICLE_EXTENSION))
+            .and_then(|rest| rest.strip_suffix('.'));
+        if let Some(language) = language {
+            if is_language_tag(language) {
+                result.push((language.to_owned(), path));
+            }
+        }
+    }
+    result.sort();
+    Ok(result)
+}
+
+/// Keeps track of the slugs of the library topics to make sure they stay unique.
+#[derive(Default)]
+pub struct Slugs {
+    /// Maps slugs to the topics using them.
+    owners: HashMap<String, String>,
+}
+
+impl Slugs {
+    /// Registers a current or former slug of the topic.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the slug is malformed or already used by another topic.
+    pub fn register(&mut self, topic: &str, slug: &str) -> Result<(), errors::ProcessingError> {
+        let error = |message: String| errors::ProcessingError::LibrarySlug {
+            topic: topic.to_owned(),
+            slug: slug.to_owned(),
+            message,
+        };
+        if !LibraryItem::is_valid_slug(slug) {
+            return Err(error("only lowercase letters, digits and hyphens are allowed".into()));
+        }
+        match self.owners.get(slug) {
+            Some(owner) if owner != topic => Err(error(format!("already used by `{owner}`"))),
+            Some(_) => Ok(()),
+            None => {
+                self.owners.insert(slug.to_owned(), topic.to_owned());
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Finds targets of markdown links and images (`[text](target)`, `![alt](target)`) in the line.
+fn find_references(line: &str) -> Vec<&str> {
+    let mut result = Vec::new();
+    let mut rest = line;
+    while let Some(start) = rest.find("](") {
+        rest = &rest[start + 2..];
+        let Some(end) = rest.find(')') else {
+            break;
+        };
+        // Skip an optional title: `[text](target "title")`
+        let target = rest[..end].split_whitespace().next().unwrap_or_default();
+        result.push(target.trim_start_matches('<').trim_end_matches('>'));
+        rest = &rest[end..];
+    }
+    result
+}
+
+/// Checks if the reference points to a file in the library (and not to a website or an anchor).
+fn is_local(reference: &str) -> bool {
+    !(reference.is_empty()
+        || reference.starts_with('#')
+        || reference.starts_with('/')
+        || reference.contains("://")
+        || reference.starts_with("mailto:"))
+}
+
+/// Removes the `#fragment` or `?query` part of the reference.
+fn strip_fragment(reference: &str) -> &str {
+    reference.split(['#', '?']).next().unwrap_or(reference)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn front_matter() {
+        let path = std::path::Path::new("fti.md");
+        let article = Article::parse(
+            path,
+            "---\ntitle: FTI\ntags: [fashion]\nlast_reviewed: 2024-03-01\n---\n# FTI\n",
+        )
+        .unwrap();
+        assert_eq!(article.front_matter.title, Some("FTI".to_owned()));
+        assert_eq!(article.front_matter.tags, vec!["fashion".to_owned()]);
+        assert_eq!(article.body, "# FTI\n");
+        assert_eq!(article.body_start, 6);
+
+        let article = Article::parse(path, "# FTI\n").unwrap();
+        assert_eq!(article.front_matter, FrontMatter::default());
+        assert_eq!(article.body, "# FTI\n");
+
+        assert!(Article::parse(path, "---\ntitle: FTI\n").is_err());
+        assert!(Article::parse(path, "---\nauthor: me\n---\n").is_err());
+        assert!(Article::parse(path, "---\nlast_reviewed: soon\n---\n").is_err());
+    }
+
+    #[test]
+    fn rendering() {
+        assert_eq!(slugify("Why it matters?"), "why-it-matters");
+        assert_eq!(slugify(" A -- B "), "a-b");
+
+        let rendered =
+            render("# Index\n\nText <script>alert(1)</script>\n\n## `Scores`\n\n## Scores\n");
+        assert_eq!(
+            rendered.toc,
+            vec![
+                LibraryTocEntry { level: 1, title: "Index".to_owned(), anchor: "index".to_owned() },
+                LibraryTocEntry {
+                    level: 2,
+                    title: "Scores".to_owned(),
+                    anchor: "scores".to_owned()
+                },
+                LibraryTocEntry {
+                    level: 2,
+                    title: "Scores".to_owned(),
+                    anchor: "scores-1".to_owned()
+                },
+            ]
+        );
+        assert!(rendered.html.contains("<h1 id=\"index\">Index</h1>"));
+        assert!(rendered.html.contains("<h2 id=\"scores-1\">Scores</h2>"));
+        assert!(!rendered.html.contains("script"));
+    }
+
+    #[test]
+    fn translations() {
+        assert!(is_language_tag("nl"));
+        assert!(is_language_tag("pt-br"));
+        assert!(!is_language_tag("NL"));
+        assert!(!is_language_tag("draft"));
+
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["about.en.md", "about.nl.md", "about.pt-br.md", "about.draft.md", "faq.md"] {
+            std::fs::write(dir.path().join(name), "").unwrap();
+        }
+        assert_eq!(find_article(dir.path(), "about"), dir.path().join("about.en.md"));
+        assert_eq!(find_article(dir.path(), "faq"), dir.path().join("faq.md"));
+        assert_eq!(
+            find_translations(dir.path(), "about").unwrap(),
+            vec![
+                ("nl".to_owned(), dir.path().join("about.nl.md")),
+                ("pt-br".to_owned(), dir.path().join("about.pt-br.md")),
+            ]
+        );
+        assert!(find_translations(dir.path(), "faq").unwrap().is_empty());
+    }
+
+    #[test]
+    fn slugs() {
+        let mut slugs = Slugs::default();
+        assert!(slugs.register("cert:bcorp", "bcorp").is_ok());
+        assert!(slugs.register("cert:bcorp", "bcorp").is_ok());
+        assert!(slugs.register("cert:fti", "fti").is_ok());
+        assert!(matches!(
+            slugs.register("cert:fti", "bcorp"),
+            Err(errors::ProcessingError::LibrarySlug { .. })
+        ));
+        assert!(slugs.register("cert:tco", "TCO").is_err());
+    }
+
+    #[test]
+    fn references() {
+        assert_eq!(
+            find_references("See ![logo](img/logo.png \"Logo\") and [site](https://x.org)."),
+            vec!["img/logo.png", "https://x.org"]
+        );
+        assert!(is_local("img/logo.png"));
+        assert!(!is_local("#section"));
+        assert!(!is_local("https://x.org"));
+        assert_eq!(strip_fragment("other.md#section"), "other.md");
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("logo.png"), "").unwrap();
+        let path = dir.path().join("fti.md");
+        let article =
+            Article::parse(&path, "---\n---\n![ok](logo.png)\n\n![bad](x.png)\n").unwrap();
+        match article.check_references(&path, dir.path()) {
+            Err(errors::ProcessingError::Article { line, .. }) => assert_eq!(line, 5),
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,114 @@
+//! Limits for development runs.
+//!
+//! Processing the full data dumps takes hours. When iterating on the processing logic it's enough
+//! to process only a fraction of the entries. The sampling is deterministic: the same entity is
+//! either always or never selected, so the results of two runs can be compared.
+
+/// Selects `part` out of every `whole` entities.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sample {
+    part: u64,
+    whole: u64,
+}
+
+impl Sample {
+    /// Checks if the entity with the given ID is selected.
+    #[must_use]
+    pub fn accepts(&self, id: &str) -> bool {
+        hash(id) % self.whole < self.part
+    }
+}
+
+impl std::str::FromStr for Sample {
+    type Err = String;
+
+    /// Parses the sample in the `part/whole` form, e.g. `1/100`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid sample '{s}', expected e.g. '1/100'");
+        let (part, whole) = s.split_once('/').ok_or_else(invalid)?;
+        let part = part.trim().parse::<u64>().map_err(|_| invalid())?;
+        let whole = whole.trim().parse::<u64>().map_err(|_| invalid())?;
+        if part == 0 || part > whole {
+            return Err(invalid());
+        }
+        Ok(Self { part, whole })
+    }
+}
+
+/// FNV-1a hash.
+///
+/// Unlike the standard library hashers it's guaranteed to be stable between Rust versions.
+fn hash(id: &str) -> u64 {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+    id.bytes().fold(OFFSET, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(PRIME))
+}
+
+/// Limits on the number of processed entries.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Limits {
+    /// Maximal number of entries read from a data dump.
+    pub max_entities: Option<usize>,
+
+    /// Sample of entities to process (applied to the read entries).
+    pub sample: Option<Sample>,
+}
+
+impl Limits {
+    #[must_use]
+    pub fn new(max_entities: Option<usize>, sample: Option<Sample>) -> Self {
+        Self { max_entities, sample }
+    }
+
+    /// Checks if the entity with the given ID should be processed.
+    ///
+    /// Entities with unknown ID are always processed.
+    #[must_use]
+    pub fn accepts(&self, id: Option<&str>) -> bool {
+        match (self.sample, id) {
+            (Some(sample), Some(id)) => sample.accepts(id),
+            _ => true,
+        }
+    }
+}
+
+/// Extracts the entity ID from a raw Wikidata dump entry without parsing the whole entry.
+///
+/// The ID is the first `id` field in the entry (IDs of claims come after it).
+#[must_use]
+pub fn extract_wikidata_id(entry: &str) -> Option<&str> {
+    const KEY: &str = "\"id\":\"";
+    let start = entry.find(KEY)? + KEY.len();
+    let len = entry[start..].find('"')?;
+    Some(&entry[start..start + len])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sampling() {
+        assert_eq!("1/100".parse::<Sample>(), Ok(Sample { part: 1, whole: 100 }));
+        assert!("0/100".parse::<Sample>().is_err());
+        assert!("2/1".parse::<Sample>().is_err());
+        assert!("half".parse::<Sample>().is_err());
+
+        let sample: Sample = "1/10".parse().unwrap();
+        let ids: Vec<String> = (1..=10_000).map(|i| format!("Q{i}")).collect();
+        let selected: Vec<&String> = ids.iter().filter(|id| sample.accepts(id)).collect();
+        assert!((800..1200).contains(&selected.len()));
+        assert_eq!(selected, ids.iter().filter(|id| sample.accepts(id)).collect::<Vec<_>>());
+
+        assert!(Limits::default().accepts(Some("Q1")));
+        assert!(Limits::new(None, "1/1".parse().ok()).accepts(Some("Q1")));
+        assert!(Limits::new(None, "1/1000000".parse().ok()).accepts(None));
+    }
+
+    #[test]
+    fn wikidata_id() {
+        let entry = r#"{"type":"item","id":"Q42","claims":{"P31":[{"id":"Q42$abc"}]}}"#;
+        assert_eq!(extract_wikidata_id(entry), Some("Q42"));
+        assert_eq!(extract_wikidata_id("{}"), None);
+    }
+}
@@ -3,7 +3,11 @@
 #![deny(clippy::expect_used)]
 #![allow(clippy::module_name_repetitions)]
 
-use sustainity_lab::{config, errors};
+use sustainity_lab::{config, errors, memory};
+
+#[cfg(feature = "alloc-tracking")]
+#[global_allocator]
+static ALLOCATOR: memory::TrackingAllocator = memory::TrackingAllocator;
 
 /// Formats duration to a human-readable format.
 #[must_use]
@@ -15,66 +19,161 @@ pub fn format_elapsed_time(duration: std::time::Duration) -> String {
     format!("{hours}h {minutes}m {seconds}s")
 }
 
-async fn run() -> Result<(), errors::ProcessingError> {
-    match config::Config::new_from_args() {
+async fn run(
+    config: config::Config,
+    memory: &mut memory::MemoryReport,
+) -> Result<(), errors::ProcessingError> {
+    match config {
         config::Config::Filtering1(config) => {
             config.check()?;
-            log::info!("Start filtering, phase 1");
-            sustainity_lab::filtering1::FilteringRunner::run(&config)?;
+            memory.start("filtering, phase 1");
+            if let Some(sparql) = &config.sparql {
+                log::info!("Start filtering, phase 1 (SPARQL)");
+                sustainity_lab::sparql::SparqlRunner::run(&config, sparql).await?;
+            } else {
+                log::info!("Start filtering, phase 1");
+                sustainity_lab::filtering1::FilteringRunner::run(&config)?;
+            }
         }
         config::Config::Filtering2(config) => {
             config.check()?;
+            memory.start("filtering, phase 2");
             log::info!("Start filtering, phase 2");
             sustainity_lab::filtering2::FilteringRunner::run(&config)?;
         }
         config::Config::Filtering(config) => {
             config.check()?;
+            memory.start("filtering, phase 1");
             log::info!("Start filtering, phase 1");
             sustainity_lab::filtering1::FilteringRunner::run(&config.filter1)?;
             log::info!("Continue filtering, phase 2");
+            memory.start("filtering, phase 2");
             sustainity_lab::filtering2::FilteringRunner::run(&config.filter2)?;
         }
         config::Config::Updating(config) => {
             config.check()?;
+            memory.start("updating");
             log::info!("Start updating!");
             sustainity_lab::updating::UpdateRunner::run(&config)?;
         }
         config::Config::Condensation(config) => {
             config.check()?;
+            memory.start("condensation");
             log::info!("Start condensation!");
             sustainity_lab::condensing::CondensingRunner::run(&config)?;
         }
         config::Config::Crystalization(config) => {
             config.check()?;
+            memory.start("crystalization");
             log::info!("Start crystalization!");
             sustainity_lab::crystalizing::Crystalizer::run(&config)?;
         }
         config::Config::Oxidation(config) => {
             config.check()?;
+            memory.start("oxidation");
             log::info!("Start oxidizing!");
             sustainity_lab::oxidation::Oxidizer::run(&config)?;
         }
         config::Config::Analysis(config) => {
             config.check()?;
+            memory.start("analysis");
             log::info!("Start analysis!");
             sustainity_lab::analysis::AnalysisRunner::run(&config)?;
         }
         config::Config::Connection(config) => {
             config.check()?;
+            memory.start("connection");
             log::info!("Start connecting!");
             sustainity_lab::connecting::ConnectionRunner::run(&config)?;
         }
+        config::Config::Match(config) => {
+            config.check()?;
+            memory.start("match");
+            log::info!("Start matching!");
+            sustainity_lab::workbench::MatchRunner::run(&config).await?;
+        }
         config::Config::Sample(config) => {
             config.check()?;
+            memory.start("sample");
             log::info!("Start sampling!");
             sustainity_lab::sampling::SamplingRunner::run(&config).await?;
         }
+        config::Config::Feedback(config) => {
+            config.check()?;
+            memory.start("feedback");
+            log::info!("Start applying feedback!");
+            sustainity_lab::feedback::FeedbackRunner::run(&config)?;
+        }
+        config::Config::Diff(config) => {
+            config.check()?;
+            memory.start("diff");
+            log::info!("Start diffing!");
+            sustainity_lab::diffing::DiffRunner::run(&config)?;
+        }
+        config::Config::Import(config) => {
+            config.check()?;
+            memory.start("import");
+            log::info!("Start importing!");
+            sustainity_lab::importing::ImportRunner::run(&config).await?;
+        }
+        config::Config::Requests(config) => {
+            config.check()?;
+            memory.start("requests");
+            log::info!("Start exporting requests!");
+            sustainity_lab::requests::RequestsRunner::run(&config).await?;
+        }
+        config::Config::Export(config) => {
+            config.check()?;
+            memory.start("export");
+            log::info!("Start exporting!");
+            sustainity_lab::exporting::ExportRunner::run(&config)?;
+        }
+        config::Config::Schemas(config) => {
+            config.check()?;
+            memory.start("schemas");
+            log::info!("Start writing schemas!");
+            sustainity_lab::validation::SchemasRunner::run(&config)?;
+        }
+        config::Config::Validation(config) => {
+            config.check()?;
+            memory.start("validation");
+            log::info!("Start validating!");
+            sustainity_lab::validation::ValidationRunner::run(&config)?;
+        }
+        config::Config::Fetch(config) => {
+            config.check()?;
+            memory.start("fetch");
+            log::info!("Start fetching!");
+            sustainity_lab::fetching::FetchRunner::run(&config).await?;
+        }
+        config::Config::EntityFetch(config) => {
+            config.check()?;
+            memory.start("entity fetch");
+            log::info!("Start fetching Wikidata entities!");
+            sustainity_lab::entities::EntityFetchRunner::run(&config).await?;
+        }
+        config::Config::Scraping(config) => {
+            config.check()?;
+            memory.start("scraping");
+            log::info!("Start scraping!");
+            sustainity_lab::scraping::ScrapingRunner::run(&config)?;
+        }
+        config::Config::Completions(config) => {
+            sustainity_lab::cli::print_completions(config.shell);
+        }
     }
     Ok(())
 }
 
-#[tokio::main]
-async fn main() {
+fn main() {
+    let config = config::Config::new_from_args();
+
+    // The completion script must not be mixed with log messages
+    if let config::Config::Completions(config) = config {
+        sustainity_lab::cli::print_completions(config.shell);
+        return;
+    }
+
     if let Err(err) = fern::Dispatch::new()
         .format(|out, message, record| {
             out.finish(format_args!(
@@ -92,12 +191,28 @@ async fn main() {
         return;
     }
 
+    let mut builder = tokio::runtime::Builder::new_multi_thread();
+    builder.enable_all();
+    if let Some(runtime) = config.runtime() {
+        builder.worker_threads(runtime.workers);
+    }
+    let runtime = match builder.build() {
+        Ok(runtime) => runtime,
+        Err(err) => {
+            log::error!("Runtime error:\n{err}");
+            return;
+        }
+    };
+
     let start_time = std::time::Instant::now();
+    let mut memory = memory::MemoryReport::new();
 
-    if let Err(err) = run().await {
+    if let Err(err) = runtime.block_on(run(config, &mut memory)) {
         log::error!("Processing error:\n{err}");
     }
 
+    memory.report();
+
     log::info!(
         "Done! Elapsed time: {}",
         sustainity_lab::utils::format_elapsed_time(start_time.elapsed())
@@ -0,0 +1,287 @@
+//! Normalisation and fuzzy comparison of company names.
+//!
+//! Company names differ between the data sources in ways irrelevant for matching: letter case,
+//! diacritics, punctuation or legal form suffixes ("ACME Inc." vs "Acme"). `normalize` removes
+//! these differences and `NameIndex` uses it to look up values by name; it should be used by every
+//! component matching companies by name.
+//!
+//! `similarity` scores how close two names are. Fuzzy matches are too unreliable to be applied
+//! automatically, so they are used only to propose candidates for review (see `workbench`).
+
+use std::collections::HashMap;
+
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+/// Legal form suffixes removed from the end of the names (after removing punctuation).
+const LEGAL_SUFFIXES: &[&str] = &[
+    "ab",
+    "ag",
+    "as",
+    "asa",
+    "bv",
+    "bvba",
+    "co",
+    "company",
+    "corp",
+    "corporation",
+    "cv",
+    "gmbh",
+    "group",
+    "inc",
+    "incorporated",
+    "kg",
+    "kk",
+    "limited",
+    "llc",
+    "llp",
+    "lp",
+    "ltd",
+    "ltda",
+    "nv",
+    "oy",
+    "oyj",
+    "plc",
+    "pty",
+    "sa",
+    "sab",
+    "sarl",
+    "sas",
+    "se",
+    "spa",
+    "sl",
+    "sp",
+    "sro",
+    "srl",
+    "zoo",
+];
+
+/// Number of leading characters names must share to be compared fuzzily.
+const BLOCK_PREFIX_LENGTH: usize = 3;
+
+/// Normalises the company name for comparison.
+///
+/// The name is folded to lower-case ASCII where possible, punctuation is removed, whitespace is
+/// collapsed and legal form suffixes are stripped. If the name consists only of a legal form, the
+/// suffix is kept.
+#[must_use]
+pub fn normalize(name: &str) -> String {
+    let folded: String = name
+        .nfkd()
+        .filter(|c| !is_combining_mark(*c))
+        .flat_map(char::to_lowercase)
+        .filter(|c| !matches!(c, '.' | '\'' | '’'))
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect();
+
+    let mut tokens: Vec<&str> = folded.split_whitespace().collect();
+    while tokens.len() > 1 && tokens.last().is_some_and(|t| LEGAL_SUFFIXES.contains(t)) {
+        tokens.pop();
+    }
+    tokens.join(" ")
+}
+
+/// Compares sorted tokens of the names, ignoring tokens present in only one of them.
+///
+/// Equivalent of the "token set ratio" known from other fuzzy matching libraries.
+fn token_set_ratio(a: &str, b: &str) -> f64 {
+    let mut tokens_a: Vec<&str> = a.split_whitespace().collect();
+    let mut tokens_b: Vec<&str> = b.split_whitespace().collect();
+    tokens_a.sort_unstable();
+    tokens_a.dedup();
+    tokens_b.sort_unstable();
+    tokens_b.dedup();
+
+    let common: Vec<&str> = tokens_a.iter().filter(|t| tokens_b.contains(*t)).copied().collect();
+    if common.is_empty() {
+        return 0.0;
+    }
+    let only_a: Vec<&str> = tokens_a.iter().filter(|t| !common.contains(*t)).copied().collect();
+    let only_b: Vec<&str> = tokens_b.iter().filter(|t| !common.contains(*t)).copied().collect();
+
+    let common = common.join(" ");
+    let with_a = [common.as_str(), &only_a.join(" ")].join(" ").trim_end().to_owned();
+    let with_b = [common.as_str(), &only_b.join(" ")].join(" ").trim_end().to_owned();
+    [
+        strsim::normalized_levenshtein(&common, &with_a),
+        strsim::normalized_levenshtein(&common, &with_b),
+        strsim::normalized_levenshtein(&with_a, &with_b),
+    ]
+    .into_iter()
+    .fold(0.0, f64::max)
+}
+
+/// Scores similarity of two already normalised names from 0 (different) to 1 (equal).
+#[must_use]
+pub fn normalized_similarity(a: &str, b: &str) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    strsim::jaro_winkler(a, b).max(token_set_ratio(a, b))
+}
+
+/// Scores similarity of two company names from 0 (different) to 1 (equal after normalisation).
+#[must_use]
+pub fn similarity(a: &str, b: &str) -> f64 {
+    normalized_similarity(&normalize(a), &normalize(b))
+}
+
+/// Pattern of company names with `*` standing for any sequence of whole words.
+///
+/// Both the pattern and the matched names are normalised, so e.g. `"Test *"` matches
+/// `"TEST Product Inc."`, but not `"Testing Product"`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamePattern {
+    /// Tokens of the normalised parts of the pattern between the wildcards.
+    parts: Vec<Vec<String>>,
+}
+
+impl NamePattern {
+    /// Constructs a new `NamePattern`.
+    #[must_use]
+    pub fn new(pattern: &str) -> Self {
+        let tokenize = |part: &str| -> Vec<String> {
+            normalize(part).split_whitespace().map(str::to_owned).collect()
+        };
+        Self { parts: pattern.split('*').map(tokenize).collect() }
+    }
+
+    /// Checks if the name matches the pattern.
+    #[must_use]
+    pub fn matches(&self, name: &str) -> bool {
+        let tokens: Vec<String> = normalize(name).split_whitespace().map(str::to_owned).collect();
+        let mut rest = tokens.as_slice();
+        let last = self.parts.len() - 1;
+        for (i, part) in self.parts.iter().enumerate() {
+            if i == 0 {
+                let Some(stripped) = rest.strip_prefix(part.as_slice()) else {
+                    return false;
+                };
+                rest = stripped;
+            } else if i == last {
+                return rest.ends_with(part);
+            } else if let Some(position) = find_tokens(rest, part) {
+                rest = &rest[position + part.len()..];
+            } else {
+                return false;
+            }
+        }
+        rest.is_empty()
+    }
+}
+
+/// Finds the position of the first occurrence of `needle` in `tokens`.
+fn find_tokens(tokens: &[String], needle: &[String]) -> Option<usize> {
+    if needle.is_empty() {
+        return Some(0);
+    }
+    tokens.windows(needle.len()).position(|window| window == needle)
+}
+
+/// Returns the prefix of the normalised name used to limit fuzzy comparisons.
+///
+/// Only names in the same block are compared fuzzily.
+#[must_use]
+pub fn block_of(key: &str) -> String {
+    key.chars().take(BLOCK_PREFIX_LENGTH).collect()
+}
+
+/// Map from company names to values with lookups tolerant to formatting differences.
+///
+/// Names are compared after normalisation, but otherwise must match exactly.
+#[derive(Debug, Clone)]
+pub struct NameIndex<V> {
+    /// Values by normalised names.
+    values: HashMap<String, V>,
+}
+
+impl<V> Default for NameIndex<V> {
+    fn default() -> Self {
+        Self { values: HashMap::new() }
+    }
+}
+
+impl<V> NameIndex<V> {
+    /// Constructs a new empty `NameIndex`.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts the value under the name. Returns the previous value with the same normalised name.
+    pub fn insert(&mut self, name: &str, value: V) -> Option<V> {
+        let key = normalize(name);
+        if key.is_empty() {
+            return None;
+        }
+        self.values.insert(key, value)
+    }
+
+    /// Returns the number of distinct normalised names.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Checks if the index is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Looks up the value by the normalised name.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&V> {
+        self.values.get(&normalize(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalization() {
+        assert_eq!(normalize("ACME Inc."), "acme");
+        assert_eq!(normalize("  Société Générale S.A. "), "societe generale");
+        assert_eq!(normalize("Müller GmbH & Co. KG"), "muller");
+        assert_eq!(normalize("Ben & Jerry's"), "ben jerrys");
+        assert_eq!(normalize("Ltd"), "ltd");
+    }
+
+    #[test]
+    fn similarities() {
+        assert!((similarity("ACME Inc.", "Acme") - 1.0).abs() < f64::EPSILON);
+        assert!(similarity("Procter & Gamble", "Procter and Gamble") > 0.9);
+        assert!(similarity("Nestle Waters", "Nestlé") > 0.9);
+        assert!(similarity("Apple", "Samsung") < 0.6);
+        assert!(similarity("", "Apple") < f64::EPSILON);
+    }
+
+    #[test]
+    fn patterns() {
+        assert!(NamePattern::new("Test *").matches("TEST Product Inc."));
+        assert!(NamePattern::new("*fictional*").matches("A Fictional Company"));
+        assert!(NamePattern::new("acme").matches("ACME Ltd."));
+        assert!(!NamePattern::new("acme").matches("Acme Foods"));
+        assert!(!NamePattern::new("* test").matches("Test Kitchen"));
+        assert!(NamePattern::new("*").matches("Anything"));
+        assert!(!NamePattern::new("Test *").matches("Testing Product"));
+        assert!(!NamePattern::new("*fictional*").matches("Nonfictional Books"));
+        assert!(!NamePattern::new("* foods").matches("Seafoods"));
+        assert!(NamePattern::new("* foods").matches("Sea Foods"));
+        assert!(NamePattern::new("acme * foods").matches("Acme Fine Foods"));
+        assert!(!NamePattern::new("acme * foods").matches("Acme Seafoods"));
+    }
+
+    #[test]
+    fn index() {
+        let mut index = NameIndex::new();
+        index.insert("ACME Inc.", 1);
+        index.insert("Globex Corporation", 2);
+        assert_eq!(index.len(), 2);
+        assert_eq!(index.get("acme"), Some(&1));
+        assert_eq!(index.get("Globex Corp"), Some(&2));
+        assert_eq!(index.get("Globexx"), None);
+        assert_eq!(index.get("Initech"), None);
+    }
+}
@@ -0,0 +1,286 @@
+//! Memory profiling of the pipeline stages.
+//!
+//! Full runs need a lot of memory and tend to run out of it on smaller machines. To see which
+//! stage is responsible, the peak memory usage of each stage is logged when the stage finishes and
+//! summarized at the end of the run.
+//!
+//! Two measures are reported:
+//! - peak resident set size, read from `/proc/self/status` (Linux only),
+//! - peak heap usage, counted by [`TrackingAllocator`] when the lab is built with the
+//!   `alloc-tracking` feature. Counting costs a few atomic operations per allocation, so it's
+//!   disabled by default.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Number of bytes currently allocated.
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+
+/// Maximal number of bytes allocated at once since the last reset.
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+/// Mount point of the cgroup v2 hierarchy.
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+/// Allocator counting the allocated bytes.
+///
+/// Installed as the global allocator by the lab binary when the `alloc-tracking` feature is
+/// enabled.
+pub struct TrackingAllocator;
+
+impl TrackingAllocator {
+    fn add(size: usize) {
+        let current = CURRENT.fetch_add(size, Ordering::Relaxed) + size;
+        PEAK.fetch_max(current, Ordering::Relaxed);
+    }
+
+    fn sub(size: usize) {
+        CURRENT.fetch_sub(size, Ordering::Relaxed);
+    }
+}
+
+unsafe impl GlobalAlloc for TrackingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            Self::add(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            Self::add(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        Self::sub(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            Self::sub(layout.size());
+            Self::add(new_size);
+        }
+        new_ptr
+    }
+}
+
+/// Returns the peak heap usage since the last reset.
+///
+/// Returns `None` if the allocations are not tracked.
+#[must_use]
+pub fn peak_heap() -> Option<usize> {
+    if cfg!(feature = "alloc-tracking") {
+        Some(PEAK.load(Ordering::Relaxed))
+    } else {
+        None
+    }
+}
+
+/// Returns the peak resident set size since the last reset.
+///
+/// Returns `None` if it cannot be read (e.g. on other systems than Linux).
+#[must_use]
+pub fn peak_rss() -> Option<usize> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    parse_peak_rss(&status)
+}
+
+/// Extracts the peak resident set size (`VmHWM`) from the contents of `/proc/self/status`.
+fn parse_peak_rss(status: &str) -> Option<usize> {
+    parse_kib_field(status, "VmHWM:")
+}
+
+/// Extracts a size in bytes from a `<field> <size> kB` line of a `/proc` file.
+fn parse_kib_field(contents: &str, field: &str) -> Option<usize> {
+    let line = contents.lines().find_map(|line| line.strip_prefix(field))?;
+    let kib = line.trim().strip_suffix("kB")?.trim().parse::<usize>().ok()?;
+    Some(kib * 1024)
+}
+
+/// Returns the memory available to the process: the total physical memory of the machine or the
+/// memory limit of the cgroup of the process (e.g. of a container), whichever is lower.
+///
+/// Returns `None` if it cannot be read (e.g. on other systems than Linux).
+#[must_use]
+pub fn total_memory() -> Option<usize> {
+    let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+    let physical = parse_kib_field(&meminfo, "MemTotal:")?;
+    Some(cgroup_memory_limit().map_or(physical, |limit| limit.min(physical)))
+}
+
+/// Returns the memory limit (`memory.max`) of the cgroup v2 of the process.
+///
+/// Returns `None` if the cgroup has no limit or the limit cannot be read.
+fn cgroup_memory_limit() -> Option<usize> {
+    let cgroup = std::fs::read_to_string("/proc/self/cgroup").ok()?;
+    let path = parse_cgroup_path(&cgroup)?;
+    let max_path = std::path::Path::new(CGROUP_ROOT).join(path).join("memory.max");
+    parse_memory_max(&std::fs::read_to_string(max_path).ok()?)
+}
+
+/// Extracts the path (relative to the cgroup root) of the cgroup v2 from `/proc/self/cgroup`.
+fn parse_cgroup_path(contents: &str) -> Option<&str> {
+    let path = contents.lines().find_map(|line| line.strip_prefix("0::"))?;
+    Some(path.trim_start_matches('/'))
+}
+
+/// Parses the contents of `memory.max`, which is either a number of bytes or `max`.
+fn parse_memory_max(contents: &str) -> Option<usize> {
+    contents.trim().parse().ok()
+}
+
+/// Resets the peaks to the current memory usage.
+pub fn reset_peaks() {
+    PEAK.store(CURRENT.load(Ordering::Relaxed), Ordering::Relaxed);
+
+    // Writing "5" resets the peak resident set size (Linux 4.0+). Failure only means the
+    // reported peak may come from an earlier stage.
+    let _ = std::fs::write("/proc/self/clear_refs", "5");
+}
+
+/// Formats a number of bytes to a human-readable format.
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+/// Peak memory usage of a finished stage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StageMemory {
+    /// Name of the stage.
+    pub name: String,
+
+    /// Peak resident set size.
+    pub rss: Option<usize>,
+
+    /// Peak heap usage.
+    pub heap: Option<usize>,
+}
+
+impl std::fmt::Display for StageMemory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let format = |bytes: Option<usize>| bytes.map_or_else(|| "n/a".to_owned(), format_bytes);
+        write!(f, "{}: peak RSS {}, peak heap {}", self.name, format(self.rss), format(self.heap))
+    }
+}
+
+/// Collects peak memory usage of the pipeline stages.
+#[derive(Debug, Default)]
+pub struct MemoryReport {
+    /// Name of the currently running stage.
+    current: Option<String>,
+
+    /// Finished stages.
+    stages: Vec<StageMemory>,
+}
+
+impl MemoryReport {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Finishes the current stage (if any) and starts measuring a new one.
+    pub fn start(&mut self, name: &str) {
+        self.finish_stage();
+        reset_peaks();
+        self.current = Some(name.to_owned());
+    }
+
+    /// Finishes the current stage (if any) and logs its peak memory usage.
+    pub fn finish_stage(&mut self) {
+        if let Some(name) = self.current.take() {
+            let stage = StageMemory { name, rss: peak_rss(), heap: peak_heap() };
+            log::info!("Memory usage of {stage}");
+            self.stages.push(stage);
+        }
+    }
+
+    /// Returns the finished stages.
+    #[must_use]
+    pub fn stages(&self) -> &[StageMemory] {
+        &self.stages
+    }
+
+    /// Finishes the current stage and logs the summary of all the stages.
+    pub fn report(&mut self) {
+        self.finish_stage();
+        if self.stages.is_empty() {
+            return;
+        }
+        log::info!("Peak memory usage per stage:");
+        for stage in &self.stages {
+            log::info!(" - {stage}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rss_parsing() {
+        let status = "Name:\tlab\nVmPeak:\t  2048 kB\nVmHWM:\t  1024 kB\nVmRSS:\t   512 kB\n";
+        assert_eq!(parse_peak_rss(status), Some(1024 * 1024));
+        assert_eq!(parse_peak_rss("Name:\tlab\n"), None);
+        assert_eq!(parse_peak_rss("VmHWM:\t  many kB\n"), None);
+
+        let meminfo = "MemTotal:       16314020 kB\nMemFree:         1101460 kB\n";
+        assert_eq!(parse_kib_field(meminfo, "MemTotal:"), Some(16_314_020 * 1024));
+    }
+
+    #[test]
+    fn cgroup_parsing() {
+        assert_eq!(parse_cgroup_path("0::/\n"), Some(""));
+        assert_eq!(
+            parse_cgroup_path("0::/system.slice/lab.service\n"),
+            Some("system.slice/lab.service")
+        );
+        assert_eq!(parse_cgroup_path("12:memory:/docker/abc\n"), None);
+
+        assert_eq!(parse_memory_max("4294967296\n"), Some(4 * 1024 * 1024 * 1024));
+        assert_eq!(parse_memory_max("max\n"), None);
+    }
+
+    #[test]
+    fn bytes_formatting() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(1023), "1023 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024), "3.0 MiB");
+        assert_eq!(format_bytes(5 * 1024 * 1024 * 1024 * 1024), "5120.0 GiB");
+    }
+
+    #[test]
+    fn stages() {
+        let mut report = MemoryReport::new();
+        report.start("filtering");
+        report.start("condensation");
+        report.report();
+        let names: Vec<&str> = report.stages().iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["filtering", "condensation"]);
+        assert_eq!(report.stages()[0].heap.is_some(), cfg!(feature = "alloc-tracking"));
+    }
+}
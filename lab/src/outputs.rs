@@ -0,0 +1,393 @@
+//! Writing and reading the pipeline outputs.
+//!
+//! The JSON Lines outputs can optionally be compressed with gzip or zstd. The compression is marked
+//! by an extension (`.gz` or `.zst`) appended to the plain file name, e.g. `products.jsonl.gz`.
+//!
+//! Writing a file removes its variants in the other compressions, so that a stale variant is never
+//! read instead. If several variants exist anyway (e.g. copied in by hand), the newest one is read.
+//!
+//! Every directory with outputs contains a manifest (`manifest.json`) listing the SHA-256 checksums
+//! and record counts of the files written there. Readers find the file in whichever compression it
+//! was written and verify its checksum and record count against the manifest while reading it.
+//! Files missing from the manifest (e.g. written by older versions) are read without verification.
+
+use std::{
+    collections::BTreeMap,
+    io::{BufRead, Write},
+    path::{Path, PathBuf},
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{commands::Compression, errors};
+
+/// Name of the manifest file.
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+/// Compression level of gzip.
+const GZIP_LEVEL: u32 = 6;
+
+/// Compression level of zstd.
+const ZSTD_LEVEL: i32 = 6;
+
+/// All the compressions an output may be written in.
+const COMPRESSIONS: [Compression; 3] = [Compression::None, Compression::Gzip, Compression::Zstd];
+
+impl Compression {
+    #[must_use]
+    pub fn extension(self) -> Option<&'static str> {
+        match self {
+            Self::None => None,
+            Self::Gzip => Some("gz"),
+            Self::Zstd => Some("zst"),
+        }
+    }
+
+    fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(std::ffi::OsStr::to_str) {
+            Some("gz") => Self::Gzip,
+            Some("zst") => Self::Zstd,
+            _ => Self::None,
+        }
+    }
+}
+
+/// Returns the path of the file written with the given compression.
+#[must_use]
+pub fn compressed_path(path: &Path, compression: Compression) -> PathBuf {
+    match compression.extension() {
+        Some(extension) => {
+            let mut path = path.as_os_str().to_owned();
+            path.push(".");
+            path.push(extension);
+            PathBuf::from(path)
+        }
+        None => path.to_owned(),
+    }
+}
+
+/// Finds the file written at the given plain path in any of the compressions.
+///
+/// If more of them exist, the most recently modified one is returned. Returns the plain path if
+/// none of them exists.
+#[must_use]
+pub fn resolve(path: &Path) -> PathBuf {
+    COMPRESSIONS
+        .into_iter()
+        .map(|compression| compressed_path(path, compression))
+        .filter_map(|path| Some((path.metadata().and_then(|m| m.modified()).ok()?, path)))
+        .max_by_key(|(modified, _)| *modified)
+        .map_or_else(|| path.to_owned(), |(_, path)| path)
+}
+
+/// Checksum and size of a single output file.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ManifestEntry {
+    /// Hex-encoded SHA-256 digest of the file (after compression).
+    pub sha256: String,
+
+    /// Number of records in the file.
+    pub records: usize,
+}
+
+/// Lists the outputs written in a directory.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Manifest {
+    /// Entries by file name.
+    pub files: BTreeMap<String, ManifestEntry>,
+}
+
+impl Manifest {
+    /// Loads the manifest from the directory, or returns an empty one if there is none.
+    pub fn load(dir: &Path) -> Result<Self, errors::ProcessingError> {
+        let path = dir.join(MANIFEST_FILE_NAME);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = std::fs::read_to_string(&path)?;
+        serde_json::from_str(&contents).map_err(|e| errors::ProcessingError::ReadJson(e, path))
+    }
+
+    fn save(&self, dir: &Path) -> Result<(), errors::ProcessingError> {
+        let contents =
+            serde_json::to_string_pretty(self).map_err(errors::ProcessingError::WriteJson)?;
+        std::fs::write(dir.join(MANIFEST_FILE_NAME), contents)?;
+        Ok(())
+    }
+
+    /// Records the file in the manifest of its directory, replacing its other compressions.
+    fn record(
+        plain: &Path,
+        compression: Compression,
+        entry: ManifestEntry,
+    ) -> Result<(), errors::ProcessingError> {
+        let (dir, name) = split_path(&compressed_path(plain, compression));
+        let mut manifest = Self::load(dir)?;
+        for other in COMPRESSIONS {
+            manifest.files.remove(&split_path(&compressed_path(plain, other)).1);
+        }
+        manifest.files.insert(name, entry);
+        manifest.save(dir)
+    }
+}
+
+fn split_path(path: &Path) -> (&Path, String) {
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    (dir, name)
+}
+
+/// Passes the written data through while computing their digest.
+struct HashingWriter<W: Write> {
+    inner: W,
+    hasher: Sha256,
+}
+
+impl<W: Write> HashingWriter<W> {
+    fn new(inner: W) -> Self {
+        Self { inner, hasher: Sha256::new() }
+    }
+
+    fn finish(mut self) -> std::io::Result<String> {
+        self.inner.flush()?;
+        Ok(format!("{:x}", self.hasher.finalize()))
+    }
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+type FileHasher = HashingWriter<std::io::BufWriter<std::fs::File>>;
+
+/// Optionally compressing writer of the output files.
+enum Encoder {
+    Plain(FileHasher),
+    Gzip(flate2::write::GzEncoder<FileHasher>),
+    Zstd(zstd::Encoder<'static, FileHasher>),
+}
+
+impl Encoder {
+    fn new(path: &Path, compression: Compression) -> std::io::Result<Self> {
+        let file = HashingWriter::new(std::io::BufWriter::new(std::fs::File::create(path)?));
+        Ok(match compression {
+            Compression::None => Self::Plain(file),
+            Compression::Gzip => Self::Gzip(flate2::write::GzEncoder::new(
+                file,
+                flate2::Compression::new(GZIP_LEVEL),
+            )),
+            Compression::Zstd => Self::Zstd(zstd::Encoder::new(file, ZSTD_LEVEL)?),
+        })
+    }
+
+    fn writer(&mut self) -> &mut dyn Write {
+        match self {
+            Self::Plain(writer) => writer,
+            Self::Gzip(encoder) => encoder,
+            Self::Zstd(encoder) => encoder,
+        }
+    }
+
+    /// Finishes the compression and returns the digest of the file.
+    fn finish(self) -> std::io::Result<String> {
+        match self {
+            Self::Plain(writer) => writer.finish(),
+            Self::Gzip(encoder) => encoder.finish()?.finish(),
+            Self::Zstd(encoder) => encoder.finish()?.finish(),
+        }
+    }
+}
+
+/// Writes the entries as JSON Lines and records the file in the manifest.
+///
+/// `path` is the plain path; the compression extension is appended to it if needed. The file
+/// written previously in other compressions is removed.
+pub fn write_json_lines<T, I>(
+    plain: &Path,
+    entries: I,
+    compression: Compression,
+) -> Result<(), errors::ProcessingError>
+where
+    T: Serialize,
+    I: IntoIterator<Item = T>,
+{
+    for other in COMPRESSIONS.into_iter().filter(|other| *other != compression) {
+        match std::fs::remove_file(compressed_path(plain, other)) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err.into()),
+            _ => {}
+        }
+    }
+
+    let path = compressed_path(plain, compression);
+    let mut encoder = Encoder::new(&path, compression)?;
+    let mut records = 0;
+    for entry in entries {
+        let writer = encoder.writer();
+        serde_json::to_writer(&mut *writer, &entry).map_err(errors::ProcessingError::WriteJson)?;
+        writer.write_all(b"\n")?;
+        records += 1;
+    }
+    let sha256 = encoder.finish()?;
+    Manifest::record(plain, compression, ManifestEntry { sha256, records })
+}
+
+/// Computes the hex-encoded SHA-256 digest of the file.
+pub fn checksum(path: &Path) -> Result<String, errors::ProcessingError> {
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut std::fs::File::open(path)?, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Verifies the file against the manifest in its directory.
+///
+/// Returns the manifest entry of the file, if it's listed there.
+pub fn verify(path: &Path) -> Result<Option<ManifestEntry>, errors::ProcessingError> {
+    let (dir, name) = split_path(path);
+    let mut manifest = Manifest::load(dir)?;
+    let entry = manifest.files.remove(&name);
+    if let Some(entry) = &entry {
+        let actual = checksum(path)?;
+        if actual != entry.sha256 {
+            return Err(errors::ProcessingError::Checksum {
+                path: path.to_owned(),
+                expected: entry.sha256.clone(),
+                actual,
+            });
+        }
+    }
+    Ok(entry)
+}
+
+/// Opens the output written at the given plain path, after verifying it.
+pub fn open(path: &Path) -> Result<Box<dyn BufRead + Send>, errors::ProcessingError> {
+    Ok(open_resolved(&resolve(path))?.0)
+}
+
+/// Opens the resolved output after verifying it and returns its expected number of records.
+fn open_resolved(
+    path: &Path,
+) -> Result<(Box<dyn BufRead + Send>, Option<usize>), errors::ProcessingError> {
+    let records = verify(path)?.map(|entry| entry.records);
+    let file = std::io::BufReader::new(std::fs::File::open(path)?);
+    let reader: Box<dyn BufRead + Send> = match Compression::from_path(path) {
+        Compression::None => Box::new(file),
+        Compression::Gzip => {
+            Box::new(std::io::BufReader::new(flate2::bufread::MultiGzDecoder::new(file)))
+        }
+        Compression::Zstd => Box::new(std::io::BufReader::new(zstd::Decoder::with_buffer(file)?)),
+    };
+    Ok((reader, records))
+}
+
+/// Reads the JSON Lines output written at the given plain path, after verifying it.
+///
+/// If the number of read records differs from the manifest, the last item is an error.
+pub fn read_json_lines<T: DeserializeOwned>(
+    path: &Path,
+) -> Result<impl Iterator<Item = std::io::Result<T>>, errors::ProcessingError> {
+    let path = resolve(path);
+    let (reader, expected) = open_resolved(&path)?;
+    let mut entries = serde_jsonlines::JsonLinesReader::new(reader).read_all::<T>();
+    let mut records = 0;
+    let mut finished = false;
+    Ok(std::iter::from_fn(move || {
+        if finished {
+            return None;
+        }
+        if let Some(entry) = entries.next() {
+            records += 1;
+            return Some(entry);
+        }
+        finished = true;
+        match expected {
+            Some(expected) if expected != records => Some(Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Expected {expected} records in `{}`, read {records}", path.display()),
+            ))),
+            _ => None,
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("numbers.jsonl");
+        for compression in [Compression::None, Compression::Gzip, Compression::Zstd] {
+            write_json_lines(&path, [1, 2, 3], compression).unwrap();
+            let written = compressed_path(&path, compression);
+            assert_eq!(resolve(&path), written);
+
+            let numbers: Vec<u32> =
+                read_json_lines(&path).unwrap().collect::<Result<_, _>>().unwrap();
+            assert_eq!(numbers, vec![1, 2, 3]);
+
+            let manifest = Manifest::load(dir.path()).unwrap();
+            let name = written.file_name().unwrap().to_str().unwrap();
+            assert_eq!(manifest.files[name].records, 3);
+            assert_eq!(manifest.files[name].sha256, checksum(&written).unwrap());
+
+            std::fs::remove_file(&written).unwrap();
+        }
+    }
+
+    /// Writing a file removes its stale variants; otherwise the newest variant is read.
+    #[test]
+    fn variants() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("numbers.jsonl");
+        write_json_lines(&path, [1], Compression::None).unwrap();
+        write_json_lines(&path, [1, 2], Compression::Zstd).unwrap();
+        assert!(!path.exists());
+        assert_eq!(resolve(&path), compressed_path(&path, Compression::Zstd));
+        let manifest = Manifest::load(dir.path()).unwrap();
+        assert_eq!(manifest.files.keys().collect::<Vec<_>>(), vec!["numbers.jsonl.zst"]);
+
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        std::fs::write(&path, "3\n").unwrap();
+        assert_eq!(resolve(&path), path);
+        let numbers: Vec<u32> = read_json_lines(&path).unwrap().collect::<Result<_, _>>().unwrap();
+        assert_eq!(numbers, vec![3]);
+    }
+
+    #[test]
+    fn record_count() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("numbers.jsonl");
+        write_json_lines(&path, [1, 2, 3], Compression::None).unwrap();
+
+        let mut manifest = Manifest::load(dir.path()).unwrap();
+        manifest.files.get_mut("numbers.jsonl").unwrap().records = 4;
+        manifest.save(dir.path()).unwrap();
+
+        let numbers: Vec<std::io::Result<u32>> = read_json_lines(&path).unwrap().collect();
+        assert_eq!(numbers.len(), 4);
+        assert!(numbers[..3].iter().all(Result::is_ok));
+        assert!(numbers[3].is_err());
+    }
+
+    #[test]
+    fn corruption() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("numbers.jsonl");
+        write_json_lines(&path, [1, 2, 3], Compression::None).unwrap();
+        std::fs::write(&path, "1\n2\n4\n").unwrap();
+        assert!(matches!(
+            read_json_lines::<u32>(&path),
+            Err(errors::ProcessingError::Checksum { .. })
+        ));
+    }
+}
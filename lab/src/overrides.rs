@@ -0,0 +1,221 @@
+//! Manual corrections of the data.
+//!
+//! Some errors in the source data take long to be fixed upstream (if ever). The maintainers can
+//! correct them in an overrides file, which is applied to the organisations and products as the
+//! last step of the crystalization, e.g.:
+//!
+//! ```yaml
+//! organisations:
+//!   - wiki: Q123
+//!     remove_certifications: [bcorp]
+//!     comment: Lost the certification in 2023
+//! products:
+//!   - wiki: Q456
+//!     categories: [laptop]
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use serde::Deserialize;
+
+use sustainity_models::gather;
+
+use crate::{errors, wikidata::WikiId};
+
+/// Certification which can be removed by an override.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Certification {
+    Bcorp,
+    EuEcolabel,
+    Fti,
+    Tco,
+}
+
+impl Certification {
+    fn remove_from(self, certifications: &mut gather::Certifications) {
+        match self {
+            Self::Bcorp => certifications.bcorp = None,
+            Self::EuEcolabel => certifications.eu_ecolabel = None,
+            Self::Fti => certifications.fti = None,
+            Self::Tco => certifications.tco = None,
+        }
+    }
+}
+
+/// Correction of an organisation.
+#[derive(Deserialize, Debug, Clone)]
+pub struct OrganisationOverride {
+    /// Wikidata ID of the organisation.
+    #[serde(deserialize_with = "WikiId::deserialize_from_string")]
+    pub wiki: WikiId,
+
+    /// Certifications the organisation does not hold.
+    #[serde(default)]
+    pub remove_certifications: Vec<Certification>,
+
+    /// Names replacing all the names found in the sources.
+    #[serde(default)]
+    pub names: Option<Vec<String>>,
+
+    /// Websites replacing all the websites found in the sources.
+    #[serde(default)]
+    pub websites: Option<Vec<String>>,
+
+    /// Explanation of the correction (not used in processing).
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+/// Correction of a product.
+#[derive(Deserialize, Debug, Clone)]
+pub struct ProductOverride {
+    /// Wikidata ID of the product.
+    #[serde(deserialize_with = "WikiId::deserialize_from_string")]
+    pub wiki: WikiId,
+
+    /// Certifications the product does not hold.
+    #[serde(default)]
+    pub remove_certifications: Vec<Certification>,
+
+    /// Names replacing all the names found in the sources.
+    #[serde(default)]
+    pub names: Option<Vec<String>>,
+
+    /// Categories replacing all the categories found in the sources.
+    #[serde(default)]
+    pub categories: Option<Vec<String>>,
+
+    /// Explanation of the correction (not used in processing).
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+/// All the corrections.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct Overrides {
+    #[serde(default)]
+    pub organisations: Vec<OrganisationOverride>,
+
+    #[serde(default)]
+    pub products: Vec<ProductOverride>,
+}
+
+/// Replaces the texts with the given ones.
+fn replace_texts(texts: &mut BTreeSet<gather::Text>, replacements: &[String]) {
+    *texts = replacements
+        .iter()
+        .map(|text| gather::Text { text: text.clone(), source: gather::Source::Other })
+        .collect();
+}
+
+/// Finds the override for an entity with any of the given Wikidata IDs.
+fn find<'a, T>(
+    overrides: &HashMap<gather::WikiId, &'a T>,
+    ids: &BTreeSet<gather::WikiId>,
+    used: &mut HashSet<gather::WikiId>,
+) -> Option<&'a T> {
+    ids.iter().find_map(|id| {
+        let entry = overrides.get(id)?;
+        used.insert(*id);
+        Some(*entry)
+    })
+}
+
+/// Warns about overrides which didn't match any entity (probably a typo in the ID).
+fn report_unused(
+    kind: &str,
+    ids: impl Iterator<Item = gather::WikiId>,
+    used: &HashSet<gather::WikiId>,
+) {
+    for id in ids {
+        if !used.contains(&id) {
+            log::warn!("Override for {kind} Q{} did not match anything", id.get_value());
+        }
+    }
+}
+
+impl Overrides {
+    /// Loads the overrides from a YAML file or returns empty overrides if no path is given.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if fails to read from `path` or parse the contents.
+    pub fn load(path: Option<&std::path::Path>) -> Result<Self, errors::ProcessingError> {
+        match path {
+            Some(path) => {
+                let contents = std::fs::read_to_string(path)?;
+                serde_yaml::from_str(&contents)
+                    .map_err(|e| errors::ProcessingError::ReadYaml(e, path.to_owned()))
+            }
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Applies the corrections to the organisations.
+    pub fn apply_to_organisations<K>(&self, organisations: &mut BTreeMap<K, gather::Organisation>) {
+        let overrides: HashMap<gather::WikiId, &OrganisationOverride> =
+            self.organisations.iter().map(|o| (gather::WikiId::from(o.wiki), o)).collect();
+        let mut used = HashSet::new();
+        for organisation in organisations.values_mut() {
+            let Some(entry) = find(&overrides, &organisation.ids.wiki, &mut used) else {
+                continue;
+            };
+            for certification in &entry.remove_certifications {
+                certification.remove_from(&mut organisation.certifications);
+            }
+            if let Some(names) = &entry.names {
+                replace_texts(&mut organisation.names, names);
+            }
+            if let Some(websites) = &entry.websites {
+                organisation.websites = websites.iter().cloned().collect();
+            }
+        }
+        log::info!(" -> applied {} organisation overrides", used.len());
+        report_unused("organisation", overrides.into_keys(), &used);
+    }
+
+    /// Applies the corrections to the products.
+    pub fn apply_to_products<K>(&self, products: &mut BTreeMap<K, gather::Product>) {
+        let overrides: HashMap<gather::WikiId, &ProductOverride> =
+            self.products.iter().map(|o| (gather::WikiId::from(o.wiki), o)).collect();
+        let mut used = HashSet::new();
+        for product in products.values_mut() {
+            let Some(entry) = find(&overrides, &product.ids.wiki, &mut used) else {
+                continue;
+            };
+            for certification in &entry.remove_certifications {
+                certification.remove_from(&mut product.certifications);
+            }
+            if let Some(names) = &entry.names {
+                replace_texts(&mut product.names, names);
+            }
+            if let Some(categories) = &entry.categories {
+                product.categories = categories.iter().cloned().collect();
+            }
+        }
+        log::info!(" -> applied {} product overrides", used.len());
+        report_unused("product", overrides.into_keys(), &used);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing() {
+        let overrides: Overrides = serde_yaml::from_str(
+            "organisations:\n  - wiki: Q123\n    remove_certifications: [bcorp, eu_ecolabel]\n\
+             products:\n  - wiki: Q456\n    categories: [laptop]\n    comment: Not a phone\n",
+        )
+        .unwrap();
+        assert_eq!(overrides.organisations[0].wiki, WikiId::new(123));
+        assert_eq!(
+            overrides.organisations[0].remove_certifications,
+            vec![Certification::Bcorp, Certification::EuEcolabel]
+        );
+        assert_eq!(overrides.products[0].categories, Some(vec!["laptop".to_owned()]));
+        assert_eq!(overrides.products[0].names, None);
+    }
+}
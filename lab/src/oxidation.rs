@@ -1,6 +1,6 @@
 use sustainity_models::gather as models;
 
-use crate::{advisors, config, errors};
+use crate::{advisors, config, errors, library, outputs, presentations};
 
 pub struct Oxidizer;
 
@@ -19,23 +19,64 @@ impl Oxidizer {
     fn transcribe_library(config: &config::OxidationConfig) -> Result<(), errors::ProcessingError> {
         let sustainity = advisors::SustainityLibraryAdvisor::load(&config.library_file_path)?;
         let mut library = Vec::<models::LibraryItem>::new();
+        let mut slugs = library::Slugs::default();
         for info in sustainity.get_info() {
             let id: &str = serde_variant::to_variant_name(&info.id)?;
-            let article_path = config.library_dir_path.join(id).with_extension("md");
+            let slug = info.slug.clone();
+            slugs.register(id, &slug)?;
+            for former_slug in &info.former_slugs {
+                slugs.register(id, former_slug)?;
+            }
+
+            let article_path = library::find_article(&config.library_dir_path, id);
             crate::utils::path_exists(&article_path)?;
+            let (article, rendered) = Self::read_article(&article_path, config)?;
+
+            let mut translations = Vec::new();
+            for (language, path) in library::find_translations(&config.library_dir_path, id)? {
+                let (translation, rendered) = Self::read_article(&path, config)?;
+                let front_matter = translation.front_matter;
+                translations.push(models::LibraryTranslation {
+                    language,
+                    title: front_matter.title.unwrap_or_else(|| info.title.clone()),
+                    summary: front_matter.summary.unwrap_or_else(|| info.summary.clone()),
+                    article: translation.body,
+                    html: rendered.html,
+                    toc: rendered.toc,
+                });
+            }
 
-            let article = std::fs::read_to_string(&article_path)?;
+            let front_matter = article.front_matter;
             library.push(models::LibraryItem {
                 id: info.id.to_str().into(),
-                title: info.title.clone(),
-                summary: info.summary.clone(),
-                article,
+                slug,
+                former_slugs: info.former_slugs.clone(),
+                title: front_matter.title.unwrap_or_else(|| info.title.clone()),
+                summary: front_matter.summary.unwrap_or_else(|| info.summary.clone()),
+                tags: front_matter.tags,
+                last_reviewed: front_matter.last_reviewed,
+                article: article.body,
+                html: rendered.html,
+                toc: rendered.toc,
+                translations,
             });
         }
-        serde_jsonlines::write_json_lines(&config.library_target_path, library)?;
+        outputs::write_json_lines(&config.library_target_path, library, config.compression)?;
         Ok(())
     }
 
+    /// Reads, validates and renders an article.
+    fn read_article(
+        path: &std::path::Path,
+        config: &config::OxidationConfig,
+    ) -> Result<(library::Article, library::Rendered), errors::ProcessingError> {
+        let contents = std::fs::read_to_string(path)?;
+        let article = library::Article::parse(path, &contents)?;
+        article.check_references(path, &config.library_dir_path)?;
+        let rendered = library::render(&article.body);
+        Ok((article, rendered))
+    }
+
     fn create_presentations(
         config: &config::OxidationConfig,
     ) -> Result<(), errors::ProcessingError> {
@@ -43,11 +84,17 @@ impl Oxidizer {
             &config.fashion_transparency_index_path,
         )?;
 
-        let mut presentations = vec![fti.prepare_presentation()];
+        let organisations =
+            presentations::PresentationBuilder::load_organisations(&config.organisations_path)?;
+        let builder = presentations::PresentationBuilder::new(fti, organisations);
+
+        let topics = presentations::TopicConfig::load(&config.presentations_config_path)?;
+        let mut presentations: Vec<models::Presentation> =
+            topics.iter().filter_map(|topic| builder.build(topic)).collect();
 
         presentations.sort_by(|a, b| a.id.cmp(&b.id));
 
-        serde_jsonlines::write_json_lines(&config.presentations_path, &presentations)?;
+        outputs::write_json_lines(&config.presentations_path, &presentations, config.compression)?;
         Ok(())
     }
 }
@@ -76,7 +76,16 @@ pub fn bounded<T>() -> (Sender<T>, Receiver<T>)
 where
     T: Clone + Send,
 {
-    let (sender, receiver) = async_channel::bounded(CHANNEL_CAP);
+    bounded_with_capacity(CHANNEL_CAP)
+}
+
+/// Creates a channel holding at most `capacity` messages.
+#[must_use]
+pub fn bounded_with_capacity<T>(capacity: usize) -> (Sender<T>, Receiver<T>)
+where
+    T: Clone + Send,
+{
+    let (sender, receiver) = async_channel::bounded(capacity);
     (Sender { sender }, Receiver { receiver })
 }
 
@@ -162,6 +171,7 @@ pub trait Consumer: Send {
 #[derive(Debug, Default)]
 pub struct Flow {
     name: Option<String>,
+    workers: Option<usize>,
     handlers: Vec<std::thread::JoinHandle<()>>,
 }
 
@@ -177,6 +187,13 @@ impl Flow {
         self
     }
 
+    /// Sets the number of processors spawned by `spawn_processors` (by default one per CPU).
+    #[must_use]
+    pub fn workers(mut self, workers: usize) -> Self {
+        self.workers = Some(workers);
+        self
+    }
+
     pub fn spawn_producer<P>(
         mut self,
         producer: P,
@@ -244,7 +261,7 @@ impl Flow {
     where
         P: Processor + 'static,
     {
-        for i in 0..num_cpus::get() {
+        for i in 0..self.workers.unwrap_or_else(num_cpus::get) {
             self.inner_spawn_processor(processor.clone(), rx.clone(), tx.clone(), i)?;
         }
         Ok(self)
@@ -0,0 +1,406 @@
+//! Resolution of conflicts between values of the same field coming from different sources.
+//!
+//! When records from several sources are merged into one organisation or product, all their names,
+//! descriptions, images and websites are kept. Which of them is shown first (and whether the others
+//! are kept at all) is decided by the `ConflictPolicy`: each field has a list of sources ordered by
+//! priority. Websites and countries are not stored with their sources, so these are passed to the
+//! policy separately as `Provenance`. The countries of products are only checked for conflicts, the
+//! regions themselves are kept merged. Categories are not covered by the policy.
+//!
+//! Values are compared in a normalised form (e.g. names without legal form suffixes, websites by
+//! their domains). If two sources provide values with nothing in common (e.g. completely different
+//! names), the disagreement is recorded as a `Conflict` so the maintainers can review it.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use sustainity_models::store;
+
+use crate::{errors, matching, utils};
+
+/// Fields the policy applies to.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Field {
+    Names,
+    Descriptions,
+    Images,
+    Websites,
+    Countries,
+}
+
+/// What to do with values from sources with lower priority.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Keep {
+    /// Keep all the values, ordered by the priority of their sources.
+    #[default]
+    All,
+
+    /// Keep only the values from the source with the highest priority.
+    Preferred,
+}
+
+/// Policy for a single field.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct FieldPolicy {
+    /// Sources ordered from the most to the least trusted. Unlisted sources go last.
+    pub priority: Vec<store::Source>,
+
+    /// What to do with values from less trusted sources.
+    #[serde(default)]
+    pub keep: Keep,
+}
+
+impl FieldPolicy {
+    fn rank(&self, source: &store::Source) -> usize {
+        self.priority.iter().position(|s| s == source).unwrap_or(self.priority.len())
+    }
+}
+
+impl Default for FieldPolicy {
+    fn default() -> Self {
+        use store::Source;
+
+        Self {
+            priority: vec![
+                Source::Wikidata,
+                Source::BCorp,
+                Source::EuEcolabel,
+                Source::Tco,
+                Source::Fti,
+                Source::OpenFoodFacts,
+                Source::Other,
+            ],
+            keep: Keep::All,
+        }
+    }
+}
+
+/// Policy for all the fields.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConflictPolicy {
+    #[serde(default)]
+    pub names: FieldPolicy,
+
+    #[serde(default)]
+    pub descriptions: FieldPolicy,
+
+    #[serde(default)]
+    pub images: FieldPolicy,
+
+    #[serde(default)]
+    pub websites: FieldPolicy,
+
+    #[serde(default)]
+    pub countries: FieldPolicy,
+}
+
+/// Values of fields stored without their sources, together with the sources.
+#[derive(Debug, Clone, Default)]
+pub struct Provenance {
+    /// Websites of an organisation.
+    pub websites: Vec<SourcedValue>,
+
+    /// Alpha-3 codes of the countries a product is available in.
+    pub countries: Vec<SourcedValue>,
+}
+
+/// Value of a field together with its source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourcedValue {
+    pub value: String,
+    pub source: store::Source,
+}
+
+impl ConflictPolicy {
+    /// Loads the policy from a YAML file or returns the default policy if no path is given.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if fails to read from `path` or parse the contents.
+    pub fn load(path: Option<&std::path::Path>) -> Result<Self, errors::ProcessingError> {
+        match path {
+            Some(path) => {
+                let contents = std::fs::read_to_string(path)?;
+                serde_yaml::from_str(&contents)
+                    .map_err(|e| errors::ProcessingError::ReadYaml(e, path.to_owned()))
+            }
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Orders the values of the organisation's fields, recording conflicts.
+    pub fn apply_to_organisation(
+        &self,
+        organisation: &mut store::Organisation,
+        provenance: Option<&Provenance>,
+        conflicts: &mut Vec<Conflict>,
+    ) {
+        let key = &organisation.db_key;
+        conflicts.extend(apply(&self.names, Field::Names, key, &mut organisation.names));
+        conflicts.extend(apply(
+            &self.descriptions,
+            Field::Descriptions,
+            key,
+            &mut organisation.descriptions,
+        ));
+        conflicts.extend(apply(&self.images, Field::Images, key, &mut organisation.images));
+
+        if let Some(provenance) = provenance {
+            let mut websites = provenance.websites.clone();
+            conflicts.extend(apply(&self.websites, Field::Websites, key, &mut websites));
+            reorder(&mut organisation.websites, &websites, self.websites.keep);
+        }
+    }
+
+    /// Orders the values of the product's fields, recording conflicts.
+    pub fn apply_to_product(
+        &self,
+        product: &mut store::Product,
+        provenance: Option<&Provenance>,
+        conflicts: &mut Vec<Conflict>,
+    ) {
+        let key = &product.db_key;
+        conflicts.extend(apply(&self.names, Field::Names, key, &mut product.names));
+        conflicts.extend(apply(
+            &self.descriptions,
+            Field::Descriptions,
+            key,
+            &mut product.descriptions,
+        ));
+        conflicts.extend(apply(&self.images, Field::Images, key, &mut product.images));
+
+        if let Some(provenance) = provenance {
+            let mut countries = provenance.countries.clone();
+            let policy = FieldPolicy { keep: Keep::All, ..self.countries.clone() };
+            conflicts.extend(apply(&policy, Field::Countries, key, &mut countries));
+        }
+    }
+}
+
+/// Orders the stored values like their sourced counterparts.
+///
+/// Values without a known source go last, or are removed if only the preferred ones are kept.
+fn reorder(values: &mut Vec<String>, sourced: &[SourcedValue], keep: Keep) {
+    let mut ordered: Vec<String> = Vec::with_capacity(values.len());
+    for entry in sourced {
+        if values.contains(&entry.value) && !ordered.contains(&entry.value) {
+            ordered.push(entry.value.clone());
+        }
+    }
+    if keep == Keep::All {
+        for value in values.iter() {
+            if !ordered.contains(value) {
+                ordered.push(value.clone());
+            }
+        }
+    }
+    *values = ordered;
+}
+
+/// Disagreement between sources about a value of a field.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Conflict {
+    /// DB key of the organisation or product.
+    pub key: String,
+
+    /// The field.
+    pub field: Field,
+
+    /// Source whose values were preferred.
+    pub chosen: store::Source,
+
+    /// Values by their sources.
+    pub values: BTreeMap<store::Source, BTreeSet<String>>,
+}
+
+/// Value with a known source.
+pub trait Sourced {
+    fn source(&self) -> &store::Source;
+    fn value(&self) -> &str;
+}
+
+impl Sourced for store::Text {
+    fn source(&self) -> &store::Source {
+        &self.source
+    }
+
+    fn value(&self) -> &str {
+        &self.text
+    }
+}
+
+impl Sourced for store::Image {
+    fn source(&self) -> &store::Source {
+        &self.source
+    }
+
+    fn value(&self) -> &str {
+        &self.image
+    }
+}
+
+impl Sourced for SourcedValue {
+    fn source(&self) -> &store::Source {
+        &self.source
+    }
+
+    fn value(&self) -> &str {
+        &self.value
+    }
+}
+
+/// Brings the value to the form used to decide if two sources agree.
+fn comparable(field: Field, value: &str) -> String {
+    match field {
+        Field::Names => matching::normalize(value),
+        Field::Descriptions => {
+            value.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+        }
+        Field::Images => {
+            let value = value.trim();
+            let value = value.split_once("://").map_or(value, |(_, rest)| rest);
+            value.trim_end_matches('/').to_lowercase()
+        }
+        Field::Websites => utils::extract_domain_from_url(value.trim()).to_lowercase(),
+        Field::Countries => value.trim().to_uppercase(),
+    }
+}
+
+/// Orders (and possibly filters) the values according to the policy.
+///
+/// Returns a conflict if the preferred source shares no value with some other source.
+fn apply<T: Sourced>(
+    policy: &FieldPolicy,
+    field: Field,
+    key: &str,
+    values: &mut Vec<T>,
+) -> Option<Conflict> {
+    values.sort_by_key(|value| policy.rank(value.source()));
+    let chosen = values.first()?.source().clone();
+
+    let mut comparables = BTreeMap::<&store::Source, BTreeSet<String>>::new();
+    for value in values.iter() {
+        comparables.entry(value.source()).or_default().insert(comparable(field, value.value()));
+    }
+    let preferred = &comparables[&chosen];
+    let conflict = comparables.values().any(|other| other.is_disjoint(preferred)).then(|| {
+        let mut originals = BTreeMap::<store::Source, BTreeSet<String>>::new();
+        for value in values.iter() {
+            originals.entry(value.source().clone()).or_default().insert(value.value().to_owned());
+        }
+        Conflict { key: key.to_owned(), field, chosen: chosen.clone(), values: originals }
+    });
+
+    if policy.keep == Keep::Preferred {
+        values.retain(|value| *value.source() == chosen);
+    }
+    conflict
+}
+
+#[cfg(test)]
+mod tests {
+    use store::{Source, Text};
+
+    use super::*;
+
+    fn text(text: &str, source: Source) -> Text {
+        Text { text: text.to_owned(), source }
+    }
+
+    #[test]
+    fn ordering() {
+        let policy =
+            FieldPolicy { priority: vec![Source::BCorp, Source::Wikidata], keep: Keep::All };
+        let mut names = vec![
+            text("ACME", Source::OpenFoodFacts),
+            text("Acme Inc.", Source::Wikidata),
+            text("ACME", Source::BCorp),
+        ];
+        assert_eq!(apply(&policy, Field::Names, "o1", &mut names), None);
+        let sources: Vec<Source> = names.iter().map(|n| n.source.clone()).collect();
+        assert_eq!(sources, vec![Source::BCorp, Source::Wikidata, Source::OpenFoodFacts]);
+    }
+
+    #[test]
+    fn conflicts() {
+        let policy = FieldPolicy { priority: vec![Source::Wikidata], keep: Keep::Preferred };
+        let mut names = vec![text("Globex", Source::BCorp), text("Acme", Source::Wikidata)];
+        let conflict = apply(&policy, Field::Names, "o1", &mut names).unwrap();
+        assert_eq!(conflict.chosen, Source::Wikidata);
+        assert_eq!(conflict.values.len(), 2);
+        assert_eq!(names, vec![text("Acme", Source::Wikidata)]);
+    }
+
+    /// Values differing only in formatting are not conflicts.
+    #[test]
+    fn normalised_comparison() {
+        let policy = FieldPolicy::default();
+        let mut names = vec![text("ACME Inc.", Source::Wikidata), text("Acme", Source::BCorp)];
+        assert_eq!(apply(&policy, Field::Names, "o1", &mut names), None);
+
+        let mut descriptions =
+            vec![text("Makes  phones.", Source::Wikidata), text("makes phones.\n", Source::BCorp)];
+        assert_eq!(apply(&policy, Field::Descriptions, "o1", &mut descriptions), None);
+    }
+
+    #[test]
+    fn websites() {
+        let sourced = |value: &str, source| SourcedValue { value: value.to_owned(), source };
+        let policy = ConflictPolicy::default();
+        let mut conflicts = Vec::new();
+        let provenance = Provenance {
+            websites: vec![
+                sourced("https://shop.acme.com", Source::BCorp),
+                sourced("http://www.acme.com/", Source::Wikidata),
+            ],
+            countries: Vec::new(),
+        };
+        let mut websites = vec![
+            "http://www.acme.com/".to_owned(),
+            "https://other.org".to_owned(),
+            "https://shop.acme.com".to_owned(),
+        ];
+        let mut sourced_websites = provenance.websites.clone();
+        conflicts.extend(apply(&policy.websites, Field::Websites, "o1", &mut sourced_websites));
+        reorder(&mut websites, &sourced_websites, Keep::All);
+        assert!(conflicts.is_empty());
+        assert_eq!(
+            websites,
+            vec!["http://www.acme.com/", "https://shop.acme.com", "https://other.org"]
+        );
+
+        let mut sourced_websites = vec![
+            sourced("https://acme.com", Source::Wikidata),
+            sourced("https://globex.com", Source::BCorp),
+        ];
+        let conflict =
+            apply(&policy.websites, Field::Websites, "o1", &mut sourced_websites).unwrap();
+        assert_eq!(conflict.chosen, Source::Wikidata);
+        reorder(&mut websites, &sourced_websites, Keep::Preferred);
+        assert!(websites.is_empty());
+    }
+
+    #[test]
+    fn countries() {
+        let sourced = |value: &str, source| SourcedValue { value: value.to_owned(), source };
+        let policy = FieldPolicy::default();
+        let mut countries = vec![sourced("NLD", Source::Wikidata), sourced("nld", Source::BCorp)];
+        assert_eq!(apply(&policy, Field::Countries, "p1", &mut countries), None);
+
+        let mut countries = vec![sourced("NLD", Source::Wikidata), sourced("DEU", Source::BCorp)];
+        let conflict = apply(&policy, Field::Countries, "p1", &mut countries).unwrap();
+        assert_eq!(conflict.field, Field::Countries);
+    }
+
+    #[test]
+    fn parsing() {
+        let policy: ConflictPolicy =
+            serde_yaml::from_str("names:\n  priority: [bcorp, wiki]\n  keep: preferred\n").unwrap();
+        assert_eq!(policy.names.priority, vec![Source::BCorp, Source::Wikidata]);
+        assert_eq!(policy.names.keep, Keep::Preferred);
+        assert_eq!(policy.images, FieldPolicy::default());
+    }
+}
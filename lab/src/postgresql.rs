@@ -0,0 +1,312 @@
+//! Storing condensed substrates in a PostgreSQL database.
+//!
+//! This is an alternative to writing the substrate files, for deployments which keep their data in
+//! PostgreSQL. Each substrate is stored as a row in `substrates` and its producers and products as
+//! JSON documents in `substrate_producers` and `substrate_products`, keyed by their IDs.
+//!
+//! Saving a substrate is an upsert: entries present in the new version are inserted or updated and
+//! entries missing from it are removed. Each save increments the generation of the substrate and
+//! tags its entries with it, so the stale entries are the ones with an older generation. Stored
+//! substrates can be read back with `SubstrateStore::load`.
+//!
+//! The database schema is created and updated by the migrations listed in `MIGRATIONS`. The applied
+//! ones are recorded in `schema_migrations`, so new migrations can be appended at any time.
+
+use serde_json::Value;
+
+use sustainity_schema as schema;
+
+use crate::{config, errors};
+
+/// Schema migrations, applied in order. Never modify the already published ones.
+const MIGRATIONS: &[&str] = &[
+    "
+    CREATE TABLE substrates (
+        name TEXT PRIMARY KEY,
+        variant TEXT NOT NULL,
+        meta JSONB NOT NULL,
+        about JSONB NOT NULL,
+        generation BIGINT NOT NULL,
+        updated TIMESTAMPTZ NOT NULL DEFAULT now()
+    );
+    CREATE TABLE substrate_producers (
+        substrate TEXT NOT NULL REFERENCES substrates(name) ON DELETE CASCADE,
+        id TEXT NOT NULL,
+        data JSONB NOT NULL,
+        generation BIGINT NOT NULL,
+        PRIMARY KEY (substrate, id)
+    );
+    CREATE TABLE substrate_products (
+        substrate TEXT NOT NULL REFERENCES substrates(name) ON DELETE CASCADE,
+        id TEXT NOT NULL,
+        data JSONB NOT NULL,
+        generation BIGINT NOT NULL,
+        PRIMARY KEY (substrate, id)
+    );
+",
+    "
+    ALTER TABLE substrates ADD COLUMN about_field TEXT;
+    ALTER TABLE substrate_producers ADD COLUMN position BIGINT NOT NULL DEFAULT 0;
+    ALTER TABLE substrate_products ADD COLUMN position BIGINT NOT NULL DEFAULT 0;
+",
+];
+
+/// Substrate split into the parts stored in separate tables.
+#[derive(Debug, Default, PartialEq)]
+struct SubstrateParts {
+    meta: Value,
+    about_field: String,
+    about: Value,
+    producers: Vec<(String, Value)>,
+    products: Vec<(String, Value)>,
+}
+
+impl SubstrateParts {
+    /// Splits the serialised substrate.
+    ///
+    /// The substrate is expected to consist of `meta`, `producers`, `products` and a single other
+    /// field describing the data provider. Entries without an ID are skipped.
+    fn split(substrate: Value) -> Self {
+        let mut parts = Self::default();
+        let Value::Object(fields) = substrate else {
+            return parts;
+        };
+        for (name, value) in fields {
+            match name.as_str() {
+                "meta" => parts.meta = value,
+                "producers" => parts.producers = Self::entries(value),
+                "products" => parts.products = Self::entries(value),
+                _ => {
+                    parts.about_field = name;
+                    parts.about = value;
+                }
+            }
+        }
+        parts
+    }
+
+    /// Joins the parts back into the serialised substrate.
+    fn assemble(self) -> Value {
+        let entries = |entries: Vec<(String, Value)>| {
+            Value::Array(entries.into_iter().map(|(_, entry)| entry).collect())
+        };
+        let mut fields = serde_json::Map::new();
+        fields.insert("meta".to_owned(), self.meta);
+        fields.insert(self.about_field, self.about);
+        fields.insert("producers".to_owned(), entries(self.producers));
+        fields.insert("products".to_owned(), entries(self.products));
+        Value::Object(fields)
+    }
+
+    fn entries(value: Value) -> Vec<(String, Value)> {
+        let Value::Array(entries) = value else {
+            return Vec::new();
+        };
+        entries
+            .into_iter()
+            .filter_map(|entry| {
+                let id = entry.get("id")?.as_str()?.to_owned();
+                Some((id, entry))
+            })
+            .collect()
+    }
+}
+
+/// Connection to the database keeping the substrates.
+pub struct SubstrateStore {
+    client: postgres::Client,
+}
+
+impl SubstrateStore {
+    /// Connects to the database and applies pending migrations.
+    pub fn connect(config: &config::PostgresConfig) -> Result<Self, errors::ProcessingError> {
+        let client = postgres::Client::connect(&config.url, postgres::NoTls)?;
+        let mut store = Self { client };
+        store.migrate()?;
+        Ok(store)
+    }
+
+    fn migrate(&mut self) -> Result<(), errors::ProcessingError> {
+        self.client.batch_execute(
+            "CREATE TABLE IF NOT EXISTS schema_migrations (
+                version BIGINT PRIMARY KEY,
+                applied TIMESTAMPTZ NOT NULL DEFAULT now()
+            )",
+        )?;
+        let row = self
+            .client
+            .query_one("SELECT COALESCE(MAX(version), 0) FROM schema_migrations", &[])?;
+        let current: i64 = row.get(0);
+
+        for (version, migration) in (1..).zip(MIGRATIONS) {
+            if version > current {
+                log::info!("Applying database migration {version}");
+                let mut tx = self.client.transaction()?;
+                tx.batch_execute(migration)?;
+                tx.execute("INSERT INTO schema_migrations (version) VALUES ($1)", &[&version])?;
+                tx.commit()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Inserts or replaces the substrate.
+    pub fn save(
+        &mut self,
+        name: &str,
+        variant: &str,
+        substrate: &schema::Root,
+    ) -> Result<(), errors::ProcessingError> {
+        let substrate =
+            serde_json::to_value(substrate).map_err(errors::ProcessingError::WriteJson)?;
+        let parts = SubstrateParts::split(substrate);
+
+        let mut tx = self.client.transaction()?;
+        let row = tx.query_one(
+            "INSERT INTO substrates (name, variant, meta, about_field, about, generation)
+             VALUES ($1, $2, $3, $4, $5, 1)
+             ON CONFLICT (name) DO UPDATE SET
+                variant = EXCLUDED.variant,
+                meta = EXCLUDED.meta,
+                about_field = EXCLUDED.about_field,
+                about = EXCLUDED.about,
+                generation = substrates.generation + 1,
+                updated = now()
+             RETURNING generation",
+            &[&name, &variant, &parts.meta, &parts.about_field, &parts.about],
+        )?;
+        let generation: i64 = row.get(0);
+
+        for (table, entries) in
+            [("substrate_producers", &parts.producers), ("substrate_products", &parts.products)]
+        {
+            let upsert = tx.prepare(&format!(
+                "INSERT INTO {table} (substrate, id, data, generation, position)
+                 VALUES ($1, $2, $3, $4, $5)
+                 ON CONFLICT (substrate, id) DO UPDATE SET
+                    data = EXCLUDED.data,
+                    generation = EXCLUDED.generation,
+                    position = EXCLUDED.position"
+            ))?;
+            for (position, (id, data)) in (0_i64..).zip(entries) {
+                tx.execute(&upsert, &[&name, id, data, &generation, &position])?;
+            }
+            let removed = tx.execute(
+                &format!("DELETE FROM {table} WHERE substrate = $1 AND generation < $2"),
+                &[&name, &generation],
+            )?;
+            log::info!(" - {table}: {} upserted, {removed} removed", entries.len());
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Reads the substrate back. Returns `None` if no substrate of that name is stored.
+    pub fn load(&mut self, name: &str) -> Result<Option<schema::Root>, errors::ProcessingError> {
+        let Some(row) = self.client.query_opt(
+            "SELECT meta, about_field, about FROM substrates WHERE name = $1",
+            &[&name],
+        )?
+        else {
+            return Ok(None);
+        };
+        let meta: Value = row.get(0);
+        let about_field: Option<String> = row.get(1);
+        // Substrates saved before the field name was recorded are named after their provider.
+        let about_field = about_field
+            .or_else(|| meta.get("variant").and_then(Value::as_str).map(str::to_owned))
+            .unwrap_or_default();
+
+        let mut parts =
+            SubstrateParts { meta, about_field, about: row.get(2), ..Default::default() };
+        for (table, entries) in [
+            ("substrate_producers", &mut parts.producers),
+            ("substrate_products", &mut parts.products),
+        ] {
+            let rows = self.client.query(
+                &format!("SELECT id, data FROM {table} WHERE substrate = $1 ORDER BY position, id"),
+                &[&name],
+            )?;
+            entries.extend(rows.into_iter().map(|row| (row.get(0), row.get(1))));
+        }
+
+        let substrate = serde_json::from_value(parts.assemble())
+            .map_err(|err| errors::ProcessingError::ReadStoredSubstrate(err, name.to_owned()))?;
+        Ok(Some(substrate))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn splitting() {
+        let substrate = json!({
+            "meta": {"version": "1"},
+            "reviewer": {"id": "bcorp"},
+            "producers": [{"id": "a", "names": []}, {"names": []}],
+            "products": [],
+        });
+        let parts = SubstrateParts::split(substrate);
+        assert_eq!(parts.meta, json!({"version": "1"}));
+        assert_eq!(parts.about_field, "reviewer");
+        assert_eq!(parts.about, json!({"id": "bcorp"}));
+        assert_eq!(parts.producers, vec![("a".to_owned(), json!({"id": "a", "names": []}))]);
+        assert!(parts.products.is_empty());
+    }
+
+    #[test]
+    fn assembling() {
+        let substrate = json!({
+            "meta": {"version": "1"},
+            "cataloger": {"id": "off"},
+            "producers": [{"id": "b"}, {"id": "a"}],
+            "products": [{"id": "p"}],
+        });
+        assert_eq!(SubstrateParts::split(substrate.clone()).assemble(), substrate);
+    }
+
+    /// Run with `SUSTAINITY_TEST_POSTGRES_URL=postgres://... cargo test -- --ignored`.
+    #[test]
+    #[ignore = "requires PostgreSQL"]
+    fn round_trip() {
+        let url = std::env::var("SUSTAINITY_TEST_POSTGRES_URL")
+            .unwrap_or_else(|_| "postgres://postgres@localhost/sustainity_test".to_owned());
+        let mut store = SubstrateStore::connect(&config::PostgresConfig { url }).unwrap();
+
+        let substrate = schema::Root::ReviewerRoot(schema::ReviewerRoot {
+            meta: schema::Meta {
+                version: "0.0.0".to_owned(),
+                variant: schema::ProviderVariant::Reviewer,
+                authors: vec!["Test".to_owned()],
+                title: String::new(),
+                description: None,
+                creation_timestamp: None,
+                valid_from: None,
+                valid_to: None,
+            },
+            reviewer: schema::AboutReviewer {
+                id: "test".to_owned(),
+                name: "Test".to_owned(),
+                description: "Test reviewer".to_owned(),
+                website: "https://example.com".to_owned(),
+                reviews: None,
+            },
+            producers: Vec::new(),
+            products: Vec::new(),
+        });
+        let name = format!("round_trip_{}", std::process::id());
+        store.save(&name, "jsonl", &substrate).unwrap();
+        store.save(&name, "jsonl", &substrate).unwrap();
+
+        let loaded = store.load(&name).unwrap().unwrap();
+        assert_eq!(serde_json::to_value(loaded).unwrap(), serde_json::to_value(substrate).unwrap());
+        assert!(store.load("missing").unwrap().is_none());
+
+        store.client.execute("DELETE FROM substrates WHERE name = $1", &[&name]).unwrap();
+    }
+}
@@ -0,0 +1,240 @@
+//! Presentations of the certifications on the Library page.
+//!
+//! Which data is presented for which topic is configured per topic, e.g.:
+//!
+//! ```yaml
+//! - topic: cert:fti
+//!   kind: scored
+//! - topic: cert:bcorp
+//!   kind: certified
+//! ```
+//!
+//! Scored lists are available only for certifiers publishing scores (currently the Fashion
+//! Transparency Index). Lists of certified companies are prepared from the crystalized
+//! organisations and contain their scores and countries (when known), so they can be also shown on
+//! a map.
+
+use serde::Deserialize;
+
+use sustainity_collecting::sustainity::data::LibraryTopic;
+use sustainity_models::{gather as models, store, vat};
+
+use crate::{advisors, errors, outputs, utils};
+
+/// Kind of the presented data.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Kind {
+    /// Companies ordered by their score.
+    Scored,
+
+    /// Certified companies with their countries.
+    Certified,
+}
+
+/// Configuration of a presentation of a single topic.
+#[derive(Deserialize, Debug, Clone)]
+pub struct TopicConfig {
+    /// Library topic the presentation belongs to.
+    pub topic: LibraryTopic,
+
+    /// Kind of the presented data.
+    pub kind: Kind,
+}
+
+impl TopicConfig {
+    /// Configuration used if no configuration file is available.
+    #[must_use]
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Self { topic: LibraryTopic::CertBcorp, kind: Kind::Certified },
+            Self { topic: LibraryTopic::CertEuEcolabel, kind: Kind::Certified },
+            Self { topic: LibraryTopic::CertFti, kind: Kind::Scored },
+            Self { topic: LibraryTopic::CertTco, kind: Kind::Certified },
+        ]
+    }
+
+    /// Loads the configuration from a file or returns the default one if the file is missing.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if fails to read from `path` or parse the contents.
+    pub fn load(path: &std::path::Path) -> Result<Vec<Self>, errors::ProcessingError> {
+        if utils::is_path_ok(path) {
+            let contents = std::fs::read_to_string(path)?;
+            serde_yaml::from_str(&contents)
+                .map_err(|e| errors::ProcessingError::ReadYaml(e, path.to_owned()))
+        } else {
+            log::warn!("Could not access {path:?}. Default presentations will be prepared!");
+            Ok(Self::defaults())
+        }
+    }
+}
+
+/// Checks if the organisation holds the certification the topic is about.
+///
+/// Returns `None` if the topic is not about a certification.
+fn is_certified(topic: &LibraryTopic, certifications: &models::Certifications) -> Option<bool> {
+    match topic {
+        LibraryTopic::CertBcorp => Some(certifications.bcorp.is_some()),
+        LibraryTopic::CertEuEcolabel => Some(certifications.eu_ecolabel.is_some()),
+        LibraryTopic::CertFti => Some(certifications.fti.is_some()),
+        LibraryTopic::CertTco => Some(certifications.tco.is_some()),
+        _ => None,
+    }
+}
+
+/// Returns the score the organisation got from the certifier the topic is about, if any.
+fn score(topic: &LibraryTopic, certifications: &models::Certifications) -> Option<i64> {
+    match topic {
+        LibraryTopic::CertFti => certifications.fti.as_ref().map(|cert| cert.score),
+        _ => None,
+    }
+}
+
+/// Guesses the country of the organisation from the prefix of its VAT number.
+fn guess_country(organisation: &store::Organisation) -> Option<String> {
+    organisation.ids.vat_ids.iter().find_map(|vat_id| vat::country(vat_id))
+}
+
+/// Prepares the presentations for the Library page.
+pub struct PresentationBuilder {
+    /// Fashion Transparency Index data (the source of the scores).
+    fti: advisors::FashionTransparencyIndexAdvisor,
+
+    /// Crystalized organisations (the source of the certified companies).
+    organisations: Vec<store::Organisation>,
+}
+
+impl PresentationBuilder {
+    #[must_use]
+    pub fn new(
+        fti: advisors::FashionTransparencyIndexAdvisor,
+        organisations: Vec<store::Organisation>,
+    ) -> Self {
+        Self { fti, organisations }
+    }
+
+    /// Loads the crystalized organisations, if they exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the organisations file exists but cannot be read.
+    pub fn load_organisations(
+        path: &std::path::Path,
+    ) -> Result<Vec<store::Organisation>, errors::ProcessingError> {
+        if outputs::resolve(path).exists() {
+            Ok(outputs::read_json_lines(path)?.collect::<Result<_, _>>()?)
+        } else {
+            log::warn!("Could not access {path:?}. Lists of certified companies will be empty!");
+            Ok(Vec::new())
+        }
+    }
+
+    /// Prepares the presentation for the topic.
+    ///
+    /// Returns `None` if the data of the requested kind are not available for the topic.
+    #[must_use]
+    pub fn build(&self, config: &TopicConfig) -> Option<models::Presentation> {
+        match (config.kind, &config.topic) {
+            (Kind::Scored, LibraryTopic::CertFti) => Some(self.fti.prepare_presentation()),
+            (Kind::Certified, topic) => self.prepare_certified(topic),
+            (Kind::Scored, topic) => {
+                log::warn!("Scores are not available for topic {}", topic.to_str());
+                None
+            }
+        }
+    }
+
+    /// Prepares the list of companies holding the certification the topic is about.
+    fn prepare_certified(&self, topic: &LibraryTopic) -> Option<models::Presentation> {
+        if is_certified(topic, &models::Certifications::default()).is_none() {
+            log::warn!("Topic {} is not about a certification", topic.to_str());
+            return None;
+        }
+
+        let mut entries = Vec::new();
+        for organisation in &self.organisations {
+            if is_certified(topic, &organisation.certifications) != Some(true) {
+                continue;
+            }
+            let Some(wiki_id) =
+                organisation.ids.wiki.first().and_then(|id| models::WikiId::try_from(id).ok())
+            else {
+                continue;
+            };
+            entries.push(models::CertifiedPresentationEntry {
+                wiki_id,
+                name: organisation.names.first().map(|name| name.text.clone()).unwrap_or_default(),
+                country: guess_country(organisation),
+                score: score(topic, &organisation.certifications),
+            });
+        }
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Some(models::Presentation {
+            id: topic.to_str().to_owned(),
+            data: models::PresentationData::Certified(entries),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing() {
+        let topics: Vec<TopicConfig> =
+            serde_yaml::from_str("- topic: cert:tco\n  kind: certified\n").unwrap();
+        assert_eq!(topics[0].topic, LibraryTopic::CertTco);
+        assert_eq!(topics[0].kind, Kind::Certified);
+    }
+
+    #[test]
+    fn certified() {
+        let organisation: store::Organisation = serde_json::from_str(
+            r#"{
+                "_key": "1",
+                "ids": {"wiki": ["123"], "vat_ids": ["DE123456789"], "domains": []},
+                "names": [{"text": "ACME", "source": "wiki"}],
+                "descriptions": [],
+                "images": [],
+                "websites": [],
+                "certifications": {"bcorp": {"id": "acme"}, "fti": {"score": 60}}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(guess_country(&organisation), Some("DE".to_owned()));
+
+        let mut greek = organisation.clone();
+        greek.ids.vat_ids = vec!["EL094014201".to_owned()];
+        assert_eq!(guess_country(&greek), Some("GR".to_owned()));
+
+        let builder = PresentationBuilder::new(
+            advisors::FashionTransparencyIndexAdvisor::new(&[]).unwrap(),
+            vec![organisation],
+        );
+        let config = TopicConfig { topic: LibraryTopic::CertBcorp, kind: Kind::Certified };
+        let Some(models::PresentationData::Certified(entries)) =
+            builder.build(&config).map(|presentation| presentation.data)
+        else {
+            panic!("expected a list of certified companies");
+        };
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].name, "ACME");
+        assert_eq!(entries[0].country, Some("DE".to_owned()));
+        assert_eq!(entries[0].score, None);
+
+        let config = TopicConfig { topic: LibraryTopic::CertFti, kind: Kind::Certified };
+        let Some(models::PresentationData::Certified(entries)) =
+            builder.build(&config).map(|presentation| presentation.data)
+        else {
+            panic!("expected a list of certified companies");
+        };
+        assert_eq!(entries[0].score, Some(60));
+
+        let config = TopicConfig { topic: LibraryTopic::CertTco, kind: Kind::Scored };
+        assert!(builder.build(&config).is_none());
+    }
+}
@@ -0,0 +1,340 @@
+//! Export of a crystalized data set as an RDF graph.
+//!
+//! The graph is serialised either as Turtle or as N-Triples. Wherever possible, it reuses the
+//! [schema.org](https://schema.org) vocabulary and links the entities to Wikidata items. Terms
+//! without a schema.org counterpart (certifications, scores) are defined in a small Sustainity
+//! ontology, which is emitted at the beginning of every export, so the output is self-describing.
+//!
+//! Every certification held by an entity is represented by a separate node pointing to the
+//! certification and to its source (using PROV-O), so that the provenance is kept in the graph.
+
+use std::io::Write;
+
+use sustainity_models::store as models;
+
+use crate::{commands::ExportFormat, config, errors, exporting, outputs};
+
+const RDF: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+const RDFS: &str = "http://www.w3.org/2000/01/rdf-schema#";
+const OWL: &str = "http://www.w3.org/2002/07/owl#";
+const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+const PROV: &str = "http://www.w3.org/ns/prov#";
+const SCHEMA: &str = "http://schema.org/";
+const WD: &str = "http://www.wikidata.org/entity/";
+const ONTOLOGY: &str = "https://sustainity.dev/ontology#";
+const ORGANISATION: &str = "https://sustainity.dev/data/organisation/";
+const PRODUCT: &str = "https://sustainity.dev/data/product/";
+
+/// Prefixes used to shorten the IRIs in Turtle.
+const PREFIXES: [(&str, &str); 10] = [
+    ("rdf", RDF),
+    ("rdfs", RDFS),
+    ("owl", OWL),
+    ("xsd", XSD),
+    ("prov", PROV),
+    ("schema", SCHEMA),
+    ("wd", WD),
+    ("sust", ONTOLOGY),
+    ("organisation", ORGANISATION),
+    ("product", PRODUCT),
+];
+
+/// Home pages of the certification sources, used as their IRIs.
+const CERTIFICATION_SOURCES: [(&str, &str); 4] = [
+    ("bcorp", "https://www.bcorporation.net/"),
+    ("eu_ecolabel", "https://environment.ec.europa.eu/topics/circular-economy/eu-ecolabel_en"),
+    ("fti", "https://www.fashionrevolution.org/about/transparency/"),
+    ("tco", "https://tcocertified.com/"),
+];
+
+/// Object of a triple.
+#[derive(Debug, Clone, PartialEq)]
+enum Term {
+    Iri(String),
+    Literal(String),
+    Typed(String, &'static str),
+}
+
+fn iri(namespace: &str, local: &str) -> String {
+    format!("{namespace}{local}")
+}
+
+fn term(namespace: &str, local: &str) -> Term {
+    Term::Iri(iri(namespace, local))
+}
+
+fn escape_literal(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => result.push_str("\\\\"),
+            '"' => result.push_str("\\\""),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+/// Percent-encodes characters which are not allowed in IRIs.
+fn escape_iri(iri: &str) -> String {
+    let mut result = String::with_capacity(iri.len());
+    for c in iri.chars() {
+        if c <= ' ' || "<>\"{}|^`\\".contains(c) {
+            let mut buffer = [0; 4];
+            for byte in c.encode_utf8(&mut buffer).bytes() {
+                result.push_str(&format!("%{byte:02X}"));
+            }
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+/// Writes triples in the selected serialisation.
+struct RdfWriter<W: Write> {
+    writer: W,
+    turtle: bool,
+}
+
+impl<W: Write> RdfWriter<W> {
+    fn new(mut writer: W, format: ExportFormat) -> std::io::Result<Self> {
+        let turtle = format == ExportFormat::Turtle;
+        if turtle {
+            for (prefix, namespace) in PREFIXES {
+                writeln!(writer, "@prefix {prefix}: <{namespace}> .")?;
+            }
+            writeln!(writer)?;
+        }
+        Ok(Self { writer, turtle })
+    }
+
+    /// Formats the IRI, shortening it with a prefix in Turtle if possible.
+    fn format_iri(&self, iri: &str) -> String {
+        if self.turtle {
+            for (prefix, namespace) in PREFIXES {
+                if let Some(local) = iri.strip_prefix(namespace) {
+                    if !local.is_empty()
+                        && local.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+                    {
+                        return format!("{prefix}:{local}");
+                    }
+                }
+            }
+        }
+        format!("<{}>", escape_iri(iri))
+    }
+
+    fn format_term(&self, term: &Term) -> String {
+        match term {
+            Term::Iri(iri) => self.format_iri(iri),
+            Term::Literal(text) => format!("\"{}\"", escape_literal(text)),
+            Term::Typed(text, datatype) => {
+                format!("\"{}\"^^{}", escape_literal(text), self.format_iri(&iri(XSD, datatype)))
+            }
+        }
+    }
+
+    fn write(&mut self, subject: &str, predicate: &str, object: &Term) -> std::io::Result<()> {
+        let subject = self.format_iri(subject);
+        let predicate = self.format_iri(predicate);
+        let object = self.format_term(object);
+        writeln!(self.writer, "{subject} {predicate} {object} .")
+    }
+
+    fn write_texts(
+        &mut self,
+        subject: &str,
+        predicate: &str,
+        texts: &[models::Text],
+    ) -> std::io::Result<()> {
+        for text in texts {
+            self.write(subject, predicate, &Term::Literal(text.text.clone()))?;
+        }
+        Ok(())
+    }
+
+    fn write_wiki_links(&mut self, subject: &str, ids: &[String]) -> std::io::Result<()> {
+        for id in ids {
+            self.write(subject, &iri(OWL, "sameAs"), &term(WD, &format!("Q{id}")))?;
+        }
+        Ok(())
+    }
+
+    /// Writes the Sustainity ontology.
+    fn write_ontology(&mut self) -> std::io::Result<()> {
+        let rdf_type = iri(RDF, "type");
+        let label = iri(RDFS, "label");
+
+        let class = iri(ONTOLOGY, "Certification");
+        self.write(&class, &rdf_type, &term(OWL, "Class"))?;
+        self.write(&class, &label, &Term::Literal("Certification".to_owned()))?;
+        let class = iri(ONTOLOGY, "CertificationAward");
+        self.write(&class, &rdf_type, &term(OWL, "Class"))?;
+        self.write(&class, &label, &Term::Literal("Certification award".to_owned()))?;
+        self.write(&class, &iri(RDFS, "subClassOf"), &term(PROV, "Entity"))?;
+
+        for (property, kind, text) in [
+            ("certified", "ObjectProperty", "holds certification award"),
+            ("certification", "ObjectProperty", "awarded certification"),
+            ("detail", "DatatypeProperty", "certification detail"),
+            ("sustainityScore", "DatatypeProperty", "Sustainity score"),
+            ("domain", "DatatypeProperty", "web domain"),
+        ] {
+            let property = iri(ONTOLOGY, property);
+            self.write(&property, &rdf_type, &term(OWL, kind))?;
+            self.write(&property, &label, &Term::Literal(text.to_owned()))?;
+        }
+
+        for ((id, name), (_, source)) in exporting::CERTIFICATIONS.iter().zip(CERTIFICATION_SOURCES)
+        {
+            let certification = iri(ONTOLOGY, id);
+            self.write(&certification, &rdf_type, &term(ONTOLOGY, "Certification"))?;
+            self.write(&certification, &label, &Term::Literal((*name).to_owned()))?;
+            self.write(&certification, &iri(RDFS, "seeAlso"), &Term::Iri(source.to_owned()))?;
+        }
+        Ok(())
+    }
+
+    fn write_certifications(
+        &mut self,
+        subject: &str,
+        certifications: &models::Certifications,
+    ) -> std::io::Result<()> {
+        for (certification, detail) in exporting::certification_entries(certifications) {
+            let award = format!("{subject}/certification/{certification}");
+            self.write(subject, &iri(ONTOLOGY, "certified"), &Term::Iri(award.clone()))?;
+            self.write(&award, &iri(RDF, "type"), &term(ONTOLOGY, "CertificationAward"))?;
+            self.write(&award, &iri(ONTOLOGY, "certification"), &term(ONTOLOGY, certification))?;
+            if let Some(detail) = detail {
+                self.write(&award, &iri(ONTOLOGY, "detail"), &Term::Literal(detail))?;
+            }
+            if let Some((_, source)) =
+                CERTIFICATION_SOURCES.iter().find(|(id, _)| *id == certification)
+            {
+                let predicate = iri(PROV, "wasDerivedFrom");
+                self.write(&award, &predicate, &Term::Iri((*source).to_owned()))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_organisation(&mut self, organisation: &models::Organisation) -> std::io::Result<()> {
+        let subject = iri(ORGANISATION, &organisation.db_key);
+        self.write(&subject, &iri(RDF, "type"), &term(SCHEMA, "Organization"))?;
+        self.write_texts(&subject, &iri(SCHEMA, "name"), &organisation.names)?;
+        self.write_texts(&subject, &iri(SCHEMA, "description"), &organisation.descriptions)?;
+        for website in &organisation.websites {
+            let website = Term::Typed(website.clone(), "anyURI");
+            self.write(&subject, &iri(SCHEMA, "url"), &website)?;
+        }
+        for vat_id in &organisation.ids.vat_ids {
+            self.write(&subject, &iri(SCHEMA, "vatID"), &Term::Literal(vat_id.clone()))?;
+        }
+        for lei in &organisation.ids.leis {
+            self.write(&subject, &iri(SCHEMA, "leiCode"), &Term::Literal(lei.clone()))?;
+        }
+        for domain in &organisation.ids.domains {
+            self.write(&subject, &iri(ONTOLOGY, "domain"), &Term::Literal(domain.clone()))?;
+        }
+        self.write_wiki_links(&subject, &organisation.ids.wiki)?;
+        self.write_certifications(&subject, &organisation.certifications)
+    }
+
+    fn write_product(&mut self, product: &models::Product) -> std::io::Result<()> {
+        let subject = iri(PRODUCT, &product.db_key);
+        self.write(&subject, &iri(RDF, "type"), &term(SCHEMA, "Product"))?;
+        self.write_texts(&subject, &iri(SCHEMA, "name"), &product.names)?;
+        self.write_texts(&subject, &iri(SCHEMA, "description"), &product.descriptions)?;
+        for gtin in product.ids.gtins.iter().chain(&product.ids.eans) {
+            self.write(&subject, &iri(SCHEMA, "gtin"), &Term::Literal(gtin.clone()))?;
+        }
+        for category in &product.categories {
+            self.write(&subject, &iri(SCHEMA, "category"), &Term::Literal(category.clone()))?;
+        }
+        for manufacturer in &product.manufacturer_ids {
+            let object = term(ORGANISATION, manufacturer);
+            self.write(&subject, &iri(SCHEMA, "manufacturer"), &object)?;
+        }
+        let score = Term::Typed(product.sustainity_score.total.to_string(), "double");
+        self.write(&subject, &iri(ONTOLOGY, "sustainityScore"), &score)?;
+        self.write_wiki_links(&subject, &product.ids.wiki)?;
+        self.write_certifications(&subject, &product.certifications)
+    }
+}
+
+pub struct RdfExporter;
+
+impl RdfExporter {
+    pub fn export(config: &config::ExportConfig) -> Result<(), errors::ProcessingError> {
+        let file = std::io::BufWriter::new(std::fs::File::create(&config.output_path)?);
+        let mut writer = RdfWriter::new(file, config.format)?;
+        writer.write_ontology()?;
+
+        log::info!("Exporting organisations");
+        for organisation in
+            outputs::read_json_lines::<models::Organisation>(&config.organisations_path)?
+        {
+            writer.write_organisation(&organisation?)?;
+        }
+
+        log::info!("Exporting products");
+        for product in outputs::read_json_lines::<models::Product>(&config.products_path)? {
+            writer.write_product(&product?)?;
+        }
+
+        writer.writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn serialise(format: ExportFormat) -> String {
+        let mut writer = RdfWriter::new(Vec::new(), format).unwrap();
+        let subject = iri(ORGANISATION, "abc");
+        writer
+            .write(&subject, &iri(SCHEMA, "name"), &Term::Literal("A \"B\"\nC".to_owned()))
+            .unwrap();
+        writer.write(&subject, &iri(OWL, "sameAs"), &term(WD, "Q42")).unwrap();
+        writer
+            .write(&iri(PRODUCT, "a b"), &iri(SCHEMA, "gtin"), &Term::Typed("1".to_owned(), "int"))
+            .unwrap();
+        String::from_utf8(writer.writer).unwrap()
+    }
+
+    #[test]
+    fn turtle() {
+        let output = serialise(ExportFormat::Turtle);
+        assert!(
+            output.starts_with("@prefix rdf: <http://www.w3.org/1999/02/22-rdf-syntax-ns#> .\n")
+        );
+        assert!(output.contains("\norganisation:abc schema:name \"A \\\"B\\\"\\nC\" .\n"));
+        assert!(output.contains("\norganisation:abc owl:sameAs wd:Q42 .\n"));
+        assert!(output.contains(
+            "\n<https://sustainity.dev/data/product/a%20b> schema:gtin \"1\"^^xsd:int .\n"
+        ));
+    }
+
+    #[test]
+    fn n_triples() {
+        let output = serialise(ExportFormat::NTriples);
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                "<https://sustainity.dev/data/organisation/abc> <http://schema.org/name> \
+                 \"A \\\"B\\\"\\nC\" .",
+                "<https://sustainity.dev/data/organisation/abc> \
+                 <http://www.w3.org/2002/07/owl#sameAs> <http://www.wikidata.org/entity/Q42> .",
+                "<https://sustainity.dev/data/product/a%20b> <http://schema.org/gtin> \
+                 \"1\"^^<http://www.w3.org/2001/XMLSchema#int> .",
+            ]
+        );
+    }
+}
@@ -0,0 +1,47 @@
+//! Configuration of the relevance of certifications for product categories.
+//!
+//! The relevance matrix lists the certifications relevant for product categories, e.g.:
+//!
+//! ```yaml
+//! food: [bcorp, eu_ecolabel]
+//! smartphone: [bcorp, tco]
+//! ```
+//!
+//! Certifications irrelevant for a product are not counted in its Sustainity score and are not
+//! shown as its badges. The matrix is stored in the dataset together with the categories.
+
+use sustainity_models::gather::RelevanceMatrix;
+
+use crate::errors;
+
+/// Loads the relevance matrix from a YAML file or returns an empty one if no path is given.
+///
+/// # Errors
+///
+/// Returns `Err` if fails to read from `path` or parse the contents.
+pub fn load(path: Option<&std::path::Path>) -> Result<RelevanceMatrix, errors::ProcessingError> {
+    match path {
+        Some(path) => {
+            let contents = std::fs::read_to_string(path)?;
+            serde_yaml::from_str(&contents)
+                .map_err(|e| errors::ProcessingError::ReadYaml(e, path.to_owned()))
+        }
+        None => Ok(RelevanceMatrix::default()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sustainity_models::gather::CertificationKind;
+
+    use super::*;
+
+    #[test]
+    fn parsing() {
+        let matrix: RelevanceMatrix =
+            serde_yaml::from_str("food: [bcorp, eu_ecolabel]\nsmartphone: [tco]\n").unwrap();
+        let relevant = matrix.lookup("food/dairy").unwrap();
+        assert!(relevant.contains(&CertificationKind::EuEcolabel));
+        assert!(!relevant.contains(&CertificationKind::Tco));
+    }
+}
@@ -0,0 +1,55 @@
+//! Exporting what the users looked for from the backend database.
+//!
+//! The users can request products missing in the data set, and the backend counts the search
+//! queries which returned no results per day (see its `analytics` module). This module exports all
+//! the product requests (to be fed to the matching workbench) and sums the counts of the queries
+//! over all the days still retained, so that the team can see which products and organisations are
+//! missing in the data set.
+
+use serde_json::json;
+
+use crate::{
+    commands::Compression,
+    config, errors,
+    importing::{Arango, FEEDBACK_DATABASE, PRODUCT_REQUESTS_COLLECTION, ZERO_RESULTS_COLLECTION},
+    outputs,
+};
+
+/// Lists the product requests, the most requested first.
+const PRODUCT_REQUESTS_QUERY: &str = "\
+FOR r IN @@collection
+    SORT r.count DESC, r.created
+    RETURN r";
+
+/// Sums the daily counts of the queries not expired yet, the most frequent first.
+const ZERO_RESULTS_QUERY: &str = "\
+FOR q IN @@collection
+    FILTER q.expires > DATE_NOW() / 1000
+    COLLECT query = q.query AGGREGATE count = SUM(q.count)
+    SORT count DESC, query
+    RETURN { query, count }";
+
+pub struct RequestsRunner;
+
+impl RequestsRunner {
+    /// Runs the `requests` command.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the database rejected any of the requests or writing the files failed.
+    pub async fn run(config: &config::RequestsConfig) -> Result<(), errors::ProcessingError> {
+        let arango = Arango::new(&config.url, &config.user, &config.password);
+
+        let bind_vars = json!({"@collection": PRODUCT_REQUESTS_COLLECTION});
+        let requests = arango.query(FEEDBACK_DATABASE, PRODUCT_REQUESTS_QUERY, bind_vars).await?;
+        log::info!(" - {} product requests", requests.len());
+        outputs::write_json_lines(&config.product_requests_path, requests, Compression::None)?;
+
+        let bind_vars = json!({"@collection": ZERO_RESULTS_COLLECTION});
+        let queries = arango.query(FEEDBACK_DATABASE, ZERO_RESULTS_QUERY, bind_vars).await?;
+        log::info!(" - {} search queries with no results", queries.len());
+        outputs::write_json_lines(&config.zero_results_path, queries, Compression::None)?;
+
+        Ok(())
+    }
+}
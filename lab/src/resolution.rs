@@ -0,0 +1,187 @@
+//! Resolution of records referring to the same real-world entity.
+//!
+//! Records are linked by the keys they share, but not all keys are equally trustworthy: sharing a
+//! Wikidata ID is a strong evidence that two records describe the same company, sharing a
+//! normalised name is a weak one. Keys are therefore processed as a cascade, from the strongest to
+//! the weakest. A key never links records which already have different keys of a stronger kind
+//! (e.g. two companies with the same name but different VAT numbers); such keys are reported as
+//! conflicts instead.
+
+use std::collections::{btree_map::Entry, BTreeMap, BTreeSet};
+
+/// Key identifying an entity.
+pub trait Key: Clone + Ord {
+    /// Priority of the kind of the key. Lower values denote stronger keys.
+    fn rank(&self) -> usize;
+}
+
+/// Records resolved to refer to the same entity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cluster<R, K> {
+    /// The records, in the order they were passed in.
+    pub records: Vec<R>,
+
+    /// Keys which linked the records together (empty for clusters with a single record).
+    pub links: BTreeSet<K>,
+}
+
+/// Result of the resolution.
+#[derive(Debug, Clone)]
+pub struct Resolution<R, K> {
+    /// Clusters ordered by their first record.
+    pub clusters: Vec<Cluster<R, K>>,
+
+    /// Keys not used for linking because they are shared by records with conflicting stronger keys.
+    pub conflicts: BTreeSet<K>,
+}
+
+/// Keys of a cluster grouped by their rank.
+type Profile<K> = BTreeMap<usize, BTreeSet<K>>;
+
+/// Finds the representative of the record's cluster, compressing the path on the way.
+fn find(parents: &mut [usize], mut index: usize) -> usize {
+    let mut root = index;
+    while parents[root] != root {
+        root = parents[root];
+    }
+    while parents[index] != root {
+        let next = parents[index];
+        parents[index] = root;
+        index = next;
+    }
+    root
+}
+
+/// Checks if the clusters have different keys of some kind stronger than `rank`.
+fn is_conflicting<K: Key>(roots: &[usize], profiles: &[Profile<K>], rank: usize) -> bool {
+    let mut seen = Profile::<K>::new();
+    for root in roots {
+        for (stronger, keys) in profiles[*root].range(..rank) {
+            match seen.entry(*stronger) {
+                Entry::Occupied(mut entry) => {
+                    if entry.get().is_disjoint(keys) {
+                        return true;
+                    }
+                    entry.get_mut().extend(keys.iter().cloned());
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(keys.clone());
+                }
+            }
+        }
+    }
+    false
+}
+
+/// Groups the records by the keys they share.
+#[must_use]
+pub fn resolve<R, K: Key>(records: Vec<(R, Vec<K>)>) -> Resolution<R, K> {
+    let mut parents: Vec<usize> = (0..records.len()).collect();
+    let mut profiles = Vec::<Profile<K>>::with_capacity(records.len());
+    let mut links = Vec::<BTreeSet<K>>::with_capacity(records.len());
+    let mut index = BTreeMap::<(usize, K), Vec<usize>>::new();
+    for (i, (_, keys)) in records.iter().enumerate() {
+        let mut profile = Profile::<K>::new();
+        for key in keys {
+            profile.entry(key.rank()).or_default().insert(key.clone());
+            index.entry((key.rank(), key.clone())).or_default().push(i);
+        }
+        profiles.push(profile);
+        links.push(BTreeSet::new());
+    }
+
+    let mut conflicts = BTreeSet::<K>::new();
+    for ((rank, key), members) in index {
+        let mut roots: Vec<usize> = members.iter().map(|m| find(&mut parents, *m)).collect();
+        roots.sort_unstable();
+        roots.dedup();
+        if roots.len() < 2 {
+            continue;
+        }
+        if is_conflicting(&roots, &profiles, rank) {
+            conflicts.insert(key);
+            continue;
+        }
+
+        let root = roots[0];
+        for other in &roots[1..] {
+            parents[*other] = root;
+            for (rank, keys) in std::mem::take(&mut profiles[*other]) {
+                profiles[root].entry(rank).or_default().extend(keys);
+            }
+            let other_links = std::mem::take(&mut links[*other]);
+            links[root].extend(other_links);
+        }
+        links[root].insert(key);
+    }
+
+    let mut clusters = Vec::<Cluster<R, K>>::new();
+    let mut root_to_cluster = BTreeMap::<usize, usize>::new();
+    for (i, (record, _)) in records.into_iter().enumerate() {
+        let root = find(&mut parents, i);
+        let cluster = *root_to_cluster.entry(root).or_insert_with(|| {
+            let links = std::mem::take(&mut links[root]);
+            clusters.push(Cluster { records: Vec::new(), links });
+            clusters.len() - 1
+        });
+        clusters[cluster].records.push(record);
+    }
+
+    Resolution { clusters, conflicts }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    enum TestKey {
+        Strong(u32),
+        Weak(&'static str),
+    }
+
+    impl Key for TestKey {
+        fn rank(&self) -> usize {
+            match self {
+                Self::Strong(_) => 0,
+                Self::Weak(_) => 1,
+            }
+        }
+    }
+
+    fn records_of(resolution: &Resolution<u32, TestKey>) -> Vec<Vec<u32>> {
+        resolution.clusters.iter().map(|c| c.records.clone()).collect()
+    }
+
+    #[test]
+    fn cascade() {
+        use TestKey::{Strong, Weak};
+
+        let resolution = resolve(vec![
+            (1, vec![Strong(1), Weak("acme")]),
+            (2, vec![Weak("acme")]),
+            (3, vec![Strong(1)]),
+            (4, vec![]),
+            (5, vec![Strong(2), Weak("initech")]),
+        ]);
+        assert_eq!(records_of(&resolution), vec![vec![1, 2, 3], vec![4], vec![5]]);
+        assert_eq!(resolution.clusters[0].links, BTreeSet::from([Strong(1), Weak("acme")]));
+        assert!(resolution.clusters[1].links.is_empty());
+        assert!(resolution.conflicts.is_empty());
+    }
+
+    #[test]
+    fn conflicts() {
+        use TestKey::{Strong, Weak};
+
+        let resolution = resolve(vec![
+            (1, vec![Strong(1), Weak("acme")]),
+            (2, vec![Strong(2), Weak("acme")]),
+            (3, vec![Weak("acme")]),
+            (4, vec![Strong(2), Strong(3)]),
+            (5, vec![Strong(3)]),
+        ]);
+        assert_eq!(records_of(&resolution), vec![vec![1], vec![2, 4, 5], vec![3]]);
+        assert_eq!(resolution.conflicts, BTreeSet::from([Weak("acme")]));
+    }
+}
@@ -0,0 +1,64 @@
+//! Reports of malformed rows skipped when reading CSV sources leniently.
+
+use sustainity_collecting::rows::RowError;
+
+use crate::{config, errors};
+
+/// Maximal number of skipped rows listed in the log.
+const MAX_LOGGED_ROWS: usize = 10;
+
+/// Reports the malformed rows skipped when reading the source.
+///
+/// The rows are summarised in the log and, if configured, written into a CSV report.
+///
+/// # Errors
+///
+/// Returns `Err` if writing the report fails.
+pub fn report(
+    source: &str,
+    rows: &[RowError],
+    config: &config::RowsConfig,
+) -> Result<(), errors::ProcessingError> {
+    if rows.is_empty() {
+        return Ok(());
+    }
+
+    log::warn!("Skipped {} malformed rows of {source}", rows.len());
+    for row in rows.iter().take(MAX_LOGGED_ROWS) {
+        match row.line {
+            Some(line) => log::warn!(" - line {line}: {}", row.reason),
+            None => log::warn!(" - unknown line: {}", row.reason),
+        }
+    }
+
+    if let Some(path) = &config.report_path {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut writer = csv::Writer::from_path(path).map_err(errors::ProcessingError::WriteCsv)?;
+        for row in rows {
+            writer.serialize(row).map_err(errors::ProcessingError::WriteCsv)?;
+        }
+        writer.flush()?;
+        log::info!("Malformed rows of {source} written to {path:?}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writing() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = config::RowsConfig {
+            mode: sustainity_collecting::rows::RowMode::Lenient,
+            report_path: Some(dir.path().join("bcorp.csv")),
+        };
+        let rows = vec![RowError { line: Some(3), reason: "bad".to_owned() }];
+        report("bcorp", &rows, &config).unwrap();
+        let contents = std::fs::read_to_string(dir.path().join("bcorp.csv")).unwrap();
+        assert_eq!(contents, "line,reason\n3,bad\n");
+    }
+}
@@ -1,10 +1,18 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
 use async_trait::async_trait;
 
 use sustainity_collecting::{eu_ecolabel, open_food_facts};
 
 use crate::{
-    config, errors,
+    advisors,
+    commands::CsvSource,
+    config, errors, limits,
     parallel::{self, Consumer, Flow, Processor, Producer, Sender},
+    rows,
 };
 
 pub trait Stash: Send {
@@ -54,12 +62,16 @@ where
 #[derive(Debug)]
 pub struct WikidataProducer {
     wiki: sustainity_wikidata::dump::Loader,
+    limits: limits::Limits,
 }
 
 impl WikidataProducer {
     /// Constructs a new `WikidataProducer`
     pub fn new(config: &config::WikidataProducerConfig) -> Result<Self, errors::ProcessingError> {
-        Ok(Self { wiki: sustainity_wikidata::dump::Loader::load(&config.wikidata_path)? })
+        let wiki = sustainity_wikidata::dump::Loader::load(&config.wikidata_path)?
+            .with_limit(config.limits.max_entities)
+            .with_buffer_size(config.io_buffer_size);
+        Ok(Self { wiki, limits: config.limits })
     }
 }
 
@@ -69,12 +81,15 @@ impl Producer for WikidataProducer {
     type Error = errors::ProcessingError;
 
     async fn produce(self, tx: Sender<Self::Output>) -> Result<(), errors::ProcessingError> {
+        let limits = self.limits;
         let num = self
             .wiki
             .run(move |s: String| {
                 let tx2 = tx.clone();
                 async move {
-                    tx2.send(s).await;
+                    if limits.accepts(limits::extract_wikidata_id(&s)) {
+                        tx2.send(s).await;
+                    }
                 }
             })
             .await?;
@@ -106,20 +121,129 @@ pub trait WikidataWorker: Clone + Send {
     ) -> Result<(), errors::ProcessingError>;
 }
 
+/// Maximal number of malformed Wikidata entities logged as samples.
+const MAX_LOGGED_SAMPLES: usize = 5;
+
+/// Maximal length of a logged sample of a malformed Wikidata entity.
+const MAX_SAMPLE_LEN: usize = 2000;
+
+/// Counts Wikidata entities skipped because they could not be parsed.
+///
+/// Shared by all the processors. The summary is logged when the last one of them is dropped.
+#[derive(Debug, Default)]
+struct ParsingStats {
+    malformed: AtomicUsize,
+}
+
+impl ParsingStats {
+    /// Records a malformed entity, logging the first few of them.
+    fn record(&self, error: &serde_json::Error, input: &str) {
+        let num = self.malformed.fetch_add(1, Ordering::Relaxed);
+        if num < MAX_LOGGED_SAMPLES {
+            let sample: String = input.chars().take(MAX_SAMPLE_LEN).collect();
+            log::warn!("Skipping a malformed Wikidata entity: {error}\nEntity:\n'{sample}'\n");
+        }
+    }
+}
+
+impl Drop for ParsingStats {
+    fn drop(&mut self) {
+        let num = *self.malformed.get_mut();
+        if num > 0 {
+            log::warn!("Skipped {num} malformed Wikidata entities");
+        }
+    }
+}
+
+/// Top-level fields added to entities by the `Special:EntityData` API but missing in the dump.
+const ENTITY_DATA_FIELDS: &[&str] = &[".pageid", ".ns", ".title", ".modified"];
+
+/// Checks if the field is added by the `Special:EntityData` API.
+///
+/// Entities fetched from the API (see `entities`) may be appended to the dump, so these fields are
+/// expected even though the parsing ignores them. Besides the top-level fields, the API adds URLs
+/// to the sitelinks.
+fn is_entity_data_field(path: &str) -> bool {
+    ENTITY_DATA_FIELDS.contains(&path)
+        || path
+            .strip_prefix(".sitelinks.")
+            .and_then(|rest| rest.strip_suffix(".url"))
+            .is_some_and(|site| !site.contains('.'))
+}
+
+/// Finds fields of a raw entity which were not recognised when parsing it.
+///
+/// `parsed` is the parsed entity serialized back, so it lacks the fields which were ignored.
+/// Fields added by the `Special:EntityData` API are not reported.
+fn find_unknown_fields(
+    raw: &serde_json::Value,
+    parsed: &serde_json::Value,
+    path: &str,
+    result: &mut Vec<String>,
+) {
+    match (raw, parsed) {
+        (serde_json::Value::Object(raw), serde_json::Value::Object(parsed)) => {
+            for (key, value) in raw {
+                let path = format!("{path}.{key}");
+                match parsed.get(key) {
+                    Some(parsed) => find_unknown_fields(value, parsed, &path, result),
+                    None if is_entity_data_field(&path) => {}
+                    None => result.push(path),
+                }
+            }
+        }
+        (serde_json::Value::Array(raw), serde_json::Value::Array(parsed)) => {
+            for (value, parsed) in raw.iter().zip(parsed) {
+                find_unknown_fields(value, parsed, &format!("{path}[]"), result);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Parses Wikidata entities and passes them to the worker.
+///
+/// Malformed entities are skipped and counted, unless the processing is strict. In the strict mode
+/// malformed entities and entities with unknown fields abort the processing.
 #[derive(Clone, Debug)]
 pub struct WikidataProcessor<W>
 where
     W: WikidataWorker,
 {
     worker: W,
+    strict: bool,
+    stats: Arc<ParsingStats>,
 }
 
 impl<W> WikidataProcessor<W>
 where
     W: WikidataWorker,
 {
-    pub fn new(worker: W) -> Self {
-        Self { worker }
+    pub fn new(worker: W, strict: bool) -> Self {
+        Self { worker, strict, stats: Arc::new(ParsingStats::default()) }
+    }
+
+    /// Checks that all the fields of the raw entity were recognised.
+    fn check_fields(
+        input: &str,
+        entity: &sustainity_wikidata::data::Entity,
+    ) -> Result<(), errors::ProcessingError> {
+        let malformed = |message: String| errors::ProcessingError::WikidataEntity {
+            id: limits::extract_wikidata_id(input).unwrap_or_default().to_owned(),
+            message,
+        };
+        let raw: serde_json::Value =
+            serde_json::from_str(input).map_err(|e| malformed(e.to_string()))?;
+        let parsed = serde_json::to_value(entity).map_err(errors::ProcessingError::WriteJson)?;
+        let mut unknown = Vec::new();
+        find_unknown_fields(&raw, &parsed, "", &mut unknown);
+        if unknown.is_empty() {
+            Ok(())
+        } else {
+            unknown.sort();
+            unknown.dedup();
+            Err(malformed(format!("unknown fields: {}", unknown.join(", "))))
+        }
     }
 }
 
@@ -141,15 +265,18 @@ where
             serde_json::from_str(&input);
         match result {
             Ok(entity) => {
+                if self.strict {
+                    Self::check_fields(&input, &entity)?;
+                }
                 self.worker.process(&input, entity, tx).await?;
             }
-            Err(err) => {
-                log::error!(
-                    "Failed to parse a Wikidata entity: {} \nMessage:\n'{}'\n\n",
-                    err,
-                    input
-                );
+            Err(err) if self.strict => {
+                return Err(errors::ProcessingError::WikidataEntity {
+                    id: limits::extract_wikidata_id(&input).unwrap_or_default().to_owned(),
+                    message: err.to_string(),
+                });
             }
+            Err(err) => self.stats.record(&err, &input),
         }
         Ok(())
     }
@@ -184,8 +311,9 @@ where
         let (tx1, rx1) = parallel::bounded::<String>();
         let (tx2, rx2) = parallel::bounded::<W::Output>();
 
-        let producer = WikidataProducer::new(&config.into())?;
-        let processor = WikidataProcessor::new(worker);
+        let config: config::WikidataProducerConfig = config.into();
+        let producer = WikidataProducer::new(&config)?;
+        let processor = WikidataProcessor::new(worker, config.strict);
         let consumer = RunnerConsumer::new(stash);
 
         let flow = flow
@@ -227,19 +355,26 @@ impl Producer for OpenFoodFactsProducer {
     type Error = errors::ProcessingError;
 
     async fn produce(self, tx: Sender<Self::Output>) -> Result<(), errors::ProcessingError> {
-        let num = open_food_facts::reader::load(
+        let limits = self.config.limits;
+        let (num, skipped) = open_food_facts::reader::load(
             self.config.open_food_facts_path,
+            limits.max_entities,
+            self.config.io_buffer_size,
+            self.config.rows.mode,
             move |headers: csv::StringRecord, record: csv::StringRecord| {
                 let tx2 = tx.clone();
                 async move {
-                    tx2.send(OpenFoodFactsRunnerMessage { record, headers }).await;
+                    let code = headers.iter().position(|h| h == "code").and_then(|i| record.get(i));
+                    if limits.accepts(code) {
+                        tx2.send(OpenFoodFactsRunnerMessage { record, headers }).await;
+                    }
                 }
             },
         )
         .await?;
 
         log::info!("Read {num} Open Food Facts records");
-        Ok(())
+        rows::report("Open Food Facts", &skipped, &self.config.rows)
     }
 }
 
@@ -384,8 +519,11 @@ impl Producer for EuEcolabelProducer {
     type Error = errors::ProcessingError;
 
     async fn produce(self, tx: Sender<Self::Output>) -> Result<(), errors::ProcessingError> {
-        let num = eu_ecolabel::reader::load(
+        let sheets = advisors::SpreadsheetsAdvisor::load(&self.config.spreadsheets_path)?;
+        let (num, skipped) = eu_ecolabel::reader::load(
             &self.config.eu_ecolabel_path,
+            sheets.get(CsvSource::EuEcolabel),
+            self.config.rows.mode,
             move |headers: csv::StringRecord, record: csv::StringRecord| {
                 let tx2 = tx.clone();
                 async move {
@@ -396,7 +534,7 @@ impl Producer for EuEcolabelProducer {
         .await?;
 
         log::info!("Read {num} EU Ecolabel records");
-        Ok(())
+        rows::report("EU Ecolabel", &skipped, &self.config.rows)
     }
 }
 
@@ -571,3 +709,46 @@ where
         Ok(flow)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_fields() {
+        let raw = serde_json::json!({"id": "Q1", "new": 1, "claims": [{"a": 1, "b": 2}]});
+        let parsed = serde_json::json!({"id": "Q1", "claims": [{"a": 1}], "extra": null});
+        let mut result = Vec::new();
+        find_unknown_fields(&raw, &parsed, "", &mut result);
+        result.sort();
+        assert_eq!(result, [".claims[].b", ".new"]);
+    }
+
+    #[test]
+    fn entity_data_fields() {
+        let raw = serde_json::json!({
+            "id": "Q1",
+            "pageid": 1,
+            "ns": 0,
+            "title": "Q1",
+            "modified": "2024-01-01T00:00:00Z",
+            "sitelinks": {
+                "enwiki": {"site": "enwiki", "title": "A", "url": "https://w.org/A"},
+                "url": {"site": "url", "title": "Url", "new": 1}
+            },
+            "claims": {"P1": [{"url": "x"}]}
+        });
+        let parsed = serde_json::json!({
+            "id": "Q1",
+            "sitelinks": {
+                "enwiki": {"site": "enwiki", "title": "A"},
+                "url": {"site": "url", "title": "Url"}
+            },
+            "claims": {"P1": [{}]}
+        });
+        let mut result = Vec::new();
+        find_unknown_fields(&raw, &parsed, "", &mut result);
+        result.sort();
+        assert_eq!(result, [".claims.P1[].url", ".sitelinks.url.new"]);
+    }
+}
@@ -7,7 +7,7 @@ use swagger::XSpanIdString;
 
 use api::Api;
 
-use crate::{config, errors};
+use crate::{config, errors, outputs};
 
 #[derive(Clone, Default)]
 struct Context {}
@@ -153,9 +153,7 @@ impl SamplingRunner {
         let fairphone_4_wiki_id = FAIRPHONE_4_WIKI_ID.to_string();
 
         let mut found = false;
-        for entry in
-            serde_jsonlines::json_lines::<models::IdEntry, _>(&config.product_wiki_ids_path)?
-        {
+        for entry in outputs::read_json_lines::<models::IdEntry>(&config.product_wiki_ids_path)? {
             let entry = entry?;
             if entry.db_key == fairphone_4_wiki_id {
                 found = true;
@@ -178,9 +176,7 @@ impl SamplingRunner {
         let fairphone_4_wiki_id = format!("product_wiki_ids/{FAIRPHONE_4_WIKI_ID}");
 
         let mut found_fairphone_4_uid = None;
-        for entry in
-            serde_jsonlines::json_lines::<models::Edge, _>(&config.product_wiki_id_edges_path)?
-        {
+        for entry in outputs::read_json_lines::<models::Edge>(&config.product_wiki_id_edges_path)? {
             let entry = entry?;
             if entry.from == fairphone_4_wiki_id {
                 found_fairphone_4_uid = entry.to.split('/').nth(1).map(str::to_owned);
@@ -205,7 +201,7 @@ impl SamplingRunner {
         let fairphone_4_uid = format!("products/{fairphone_4_uid}");
 
         let mut found: usize = 0;
-        for entry in serde_jsonlines::json_lines::<models::Edge, _>(&config.category_edges_path)? {
+        for entry in outputs::read_json_lines::<models::Edge>(&config.category_edges_path)? {
             let entry = entry?;
             if entry.to == fairphone_4_uid {
                 found += 1;
@@ -230,7 +226,7 @@ impl SamplingRunner {
         log::info!("Iterating products");
 
         let mut found_fairphone_org_uid = None;
-        for entry in serde_jsonlines::json_lines::<models::Product, _>(&config.products_path)? {
+        for entry in outputs::read_json_lines::<models::Product>(&config.products_path)? {
             let entry = entry?;
             if entry.db_key == fairphone_4_uid {
                 ensure_eq!(entry.ids.wiki, fairphone_4_ids, "wrong wiki IDs");
@@ -245,10 +241,26 @@ impl SamplingRunner {
                 ensure_eq!(
                     entry.certifications,
                     models::Certifications {
-                        bcorp: Some(models::BCorpCert { id: BCORP_FAIRPHONE_ID.to_owned() }),
+                        bcorp: Some(models::BCorpCert {
+                            id: BCORP_FAIRPHONE_ID.to_owned(),
+                            validity: entry
+                                .certifications
+                                .bcorp
+                                .as_ref()
+                                .map(|c| c.validity.clone())
+                                .unwrap_or_default(),
+                        }),
                         eu_ecolabel: None,
                         fti: None,
-                        tco: Some(models::TcoCert { brand_name: "FAIRPHONE".to_owned() }),
+                        tco: Some(models::TcoCert {
+                            brand_name: "FAIRPHONE".to_owned(),
+                            validity: entry
+                                .certifications
+                                .tco
+                                .as_ref()
+                                .map(|c| c.validity.clone())
+                                .unwrap_or_default(),
+                        }),
                     },
                     "wrong certifications"
                 );
@@ -271,7 +283,7 @@ impl SamplingRunner {
 
         let mut found = false;
         for entry in
-            serde_jsonlines::json_lines::<models::IdEntry, _>(&config.organisation_wiki_ids_path)?
+            outputs::read_json_lines::<models::IdEntry>(&config.organisation_wiki_ids_path)?
         {
             let entry = entry?;
             if entry.db_key == fairphone_org_wiki_id {
@@ -298,7 +310,7 @@ impl SamplingRunner {
 
         let mut found_fairphone_org_uid = None;
         for entry in
-            serde_jsonlines::json_lines::<models::Edge, _>(&config.organisation_wiki_id_edges_path)?
+            outputs::read_json_lines::<models::Edge>(&config.organisation_wiki_id_edges_path)?
         {
             let entry = entry?;
             if entry.from == fairphone_org_wiki_id {
@@ -330,9 +342,7 @@ impl SamplingRunner {
             "Fairphone organisation IDs were different"
         );
 
-        for entry in
-            serde_jsonlines::json_lines::<models::Organisation, _>(&config.organisations_path)?
-        {
+        for entry in outputs::read_json_lines::<models::Organisation>(&config.organisations_path)? {
             let entry = entry?;
             if entry.db_key == fairphone_org_uid_1 {
                 ensure_eq!(entry.ids.wiki, fairphone_org_ids, "wrong wiki IDs");
@@ -354,10 +364,26 @@ impl SamplingRunner {
                 ensure_eq!(
                     entry.certifications,
                     models::Certifications {
-                        bcorp: Some(models::BCorpCert { id: BCORP_FAIRPHONE_ID.to_owned() }),
+                        bcorp: Some(models::BCorpCert {
+                            id: BCORP_FAIRPHONE_ID.to_owned(),
+                            validity: entry
+                                .certifications
+                                .bcorp
+                                .as_ref()
+                                .map(|c| c.validity.clone())
+                                .unwrap_or_default(),
+                        }),
                         eu_ecolabel: None,
                         fti: None,
-                        tco: Some(models::TcoCert { brand_name: "FAIRPHONE".to_owned() }),
+                        tco: Some(models::TcoCert {
+                            brand_name: "FAIRPHONE".to_owned(),
+                            validity: entry
+                                .certifications
+                                .tco
+                                .as_ref()
+                                .map(|c| c.validity.clone())
+                                .unwrap_or_default(),
+                        }),
                     },
                     "wrong certifications"
                 );
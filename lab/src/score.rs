@@ -1,4 +1,4 @@
-use sustainity_models::gather as models;
+use sustainity_models::{attributes, gather as models};
 
 enum ScoreBranch {
     Leaf(models::SustainityScoreBranch),
@@ -35,11 +35,45 @@ impl SubscoreCalculator {
     }
 }
 
+/// Checks if the category belongs to the food category tree.
+fn is_food_category(category: &str) -> bool {
+    category == "food" || category.starts_with("food/")
+}
+
+/// Scores the packaging of a product.
+///
+/// Known recyclability decides the score. Otherwise packaging without plastic scores higher than
+/// packaging with plastic, and unknown packaging scores in the middle.
+fn packaging_score(attributes: &models::Attributes) -> f64 {
+    match attributes.get(attributes::RECYCLABLE_PACKAGING) {
+        Some(models::AttributeValue::Flag(true)) => 1.0,
+        Some(models::AttributeValue::Flag(false)) => 0.0,
+        _ => match attributes.get(attributes::PACKAGING_MATERIALS) {
+            Some(models::AttributeValue::Text(materials)) => {
+                if materials.split(',').any(|material| material == "plastic") {
+                    0.25
+                } else {
+                    0.75
+                }
+            }
+            _ => 0.5,
+        },
+    }
+}
+
+/// Calculates the Sustainity score of the product.
+///
+/// Only certifications relevant for the categories of the product are counted.
 #[must_use]
-pub fn calculate(product: &models::Product) -> models::SustainityScore {
+pub fn calculate(
+    product: &models::Product,
+    relevance: &models::RelevanceMatrix,
+) -> models::SustainityScore {
     let has_producer = !product.manufacturer_ids.is_empty();
     let has_categories = !product.categories.is_empty();
-    let num_certs = product.certifications.get_num();
+    let mut certifications = product.certifications.clone();
+    relevance.retain_relevant(&mut certifications, &product.categories);
+    let num_certs = certifications.get_num();
 
     let mut category_contributions = Vec::new();
     if product.categories.contains("smartphone") {
@@ -50,6 +84,14 @@ pub fn calculate(product: &models::Product) -> models::SustainityScore {
             branches: vec![],
         }));
     }
+    if product.categories.iter().any(|category| is_food_category(category)) {
+        category_contributions.push(ScoreBranch::Leaf(models::SustainityScoreBranch {
+            category: models::SustainityScoreCategory::Packaging,
+            weight: 1,
+            score: packaging_score(&product.attributes),
+            branches: vec![],
+        }));
+    }
 
     let tree = SubscoreCalculator {
         category: models::SustainityScoreCategory::Root,
@@ -114,3 +156,26 @@ pub fn calculate(product: &models::Product) -> models::SustainityScore {
 
     models::SustainityScore { tree: tree.branches, total: tree.score }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packaging() {
+        let text = |value: &str| models::AttributeValue::Text(value.to_owned());
+        assert!((packaging_score(&models::Attributes::new()) - 0.5).abs() < f64::EPSILON);
+        let attributes = models::Attributes::from([
+            (attributes::PACKAGING_MATERIALS.to_owned(), text("glass,plastic")),
+            (attributes::RECYCLABLE_PACKAGING.to_owned(), models::AttributeValue::Flag(true)),
+        ]);
+        assert!((packaging_score(&attributes) - 1.0).abs() < f64::EPSILON);
+        let attributes = models::Attributes::from([(
+            attributes::PACKAGING_MATERIALS.to_owned(),
+            text("plastic"),
+        )]);
+        assert!((packaging_score(&attributes) - 0.25).abs() < f64::EPSILON);
+        assert!(is_food_category("food/dairy"));
+        assert!(!is_food_category("foodstuff"));
+    }
+}
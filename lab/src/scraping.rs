@@ -0,0 +1,49 @@
+//! Refreshing of sources published only as web pages.
+//!
+//! The pages are saved to disk and scraped with table scrapers listed in a configuration file,
+//! e.g.:
+//!
+//! ```yaml
+//! tco:
+//!   page: pages/tco-certified-brands.html
+//!   rows: table.brands tbody tr
+//!   columns:
+//!     tco:
+//!       selector: td.brand
+//!     website:
+//!       selector: td.brand a
+//!       attribute: href
+//! ```
+//!
+//! Rows of each table are written to `<name>.yaml` in the output directory, so the result can be
+//! reviewed and merged into the source files.
+
+use sustainity_collecting::scraping;
+
+use crate::{config, errors};
+
+pub struct ScrapingRunner;
+
+impl ScrapingRunner {
+    /// Scrapes all the configured tables and writes their rows.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the configuration or a page cannot be read or the output cannot be written.
+    pub fn run(config: &config::ScrapingConfig) -> Result<(), errors::ProcessingError> {
+        let scrapers = scraping::reader::parse(&config.config_path)?;
+        for (name, scraper) in &scrapers {
+            let rows = scraping::reader::scrape(scraper)?;
+            if rows.is_empty() {
+                log::warn!("No rows scraped for {name} from {:?}", scraper.page);
+            } else {
+                log::info!("Scraped {} rows for {name}", rows.len());
+            }
+            let path = config.output_path.join(name).with_extension("yaml");
+            let contents =
+                serde_yaml::to_string(&rows).map_err(errors::ProcessingError::WriteYaml)?;
+            std::fs::write(path, contents)?;
+        }
+        Ok(())
+    }
+}
@@ -1,4 +1,17 @@
-use crate::{advisors, config, errors, utils, wikidata::ItemExt};
+use std::path::{Path, PathBuf};
+
+use sustainity_collecting::entity_lists;
+
+use crate::{
+    advisors,
+    commands::{CsvSource, SupplementarySource},
+    config::{self, SourcePolicy},
+    errors, utils,
+    wikidata::ItemExt,
+};
+
+/// Name of the report of the statuses of the sources.
+pub const REPORT_FILE_NAME: &str = "sources.csv";
 
 /// Trait for structures holding all the supplementary source data required by a `Processor`.
 pub trait Sourceable: Sized + Sync + Send {
@@ -12,6 +25,172 @@ pub trait Sourceable: Sized + Sync + Send {
     fn load(config: &Self::Config) -> Result<Self, errors::ProcessingError>;
 }
 
+/// Outcome of loading a supplementary source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceStatus {
+    /// Loaded from the configured file.
+    Loaded,
+
+    /// Failed to load (with the given error) and was left out.
+    Skipped(String),
+
+    /// Failed to load (with the given error) and was loaded from the previous copy.
+    Previous(String),
+}
+
+/// Loads supplementary sources applying the policies for the ones failing to load.
+struct SourceLoader<'a> {
+    config: &'a config::SourcesConfig,
+    statuses: Vec<(SupplementarySource, SourceStatus)>,
+}
+
+impl<'a> SourceLoader<'a> {
+    fn new(config: &'a config::SourcesConfig) -> Self {
+        Self { config, statuses: Vec::new() }
+    }
+
+    /// Returns the paths to the previous copies of the inputs of the source.
+    fn previous_paths(&self, source: SupplementarySource, inputs: &[&Path]) -> Vec<PathBuf> {
+        let previous = self.config.previous_path.join(source.name());
+        inputs
+            .iter()
+            .map(|input| match input.file_name() {
+                Some(name) => previous.join(name),
+                None => previous.join(input),
+            })
+            .collect()
+    }
+
+    /// Loads the source from the file at `path` with `load`.
+    ///
+    /// See [`SourceLoader::load_all`].
+    fn load<T>(
+        &mut self,
+        source: SupplementarySource,
+        path: &Path,
+        load: impl Fn(&Path) -> Result<T, errors::ProcessingError>,
+        empty: impl FnOnce() -> Result<T, errors::ProcessingError>,
+    ) -> Result<T, errors::ProcessingError> {
+        self.load_all(source, &[path], |paths| load(paths[0]), empty)
+    }
+
+    /// Loads the source from the files at `inputs` (the main file and the side files, e.g.
+    /// mappings, in the order `load` expects them) with `load`.
+    ///
+    /// If loading fails, the source is either left out (constructed with `empty`), loaded from the
+    /// previous copies of all its inputs or the error is returned, depending on the configured
+    /// policy.
+    fn load_all<T>(
+        &mut self,
+        source: SupplementarySource,
+        inputs: &[&Path],
+        load: impl Fn(&[&Path]) -> Result<T, errors::ProcessingError>,
+        empty: impl FnOnce() -> Result<T, errors::ProcessingError>,
+    ) -> Result<T, errors::ProcessingError> {
+        let policy = self.config.policy(source);
+        let previous = self.previous_paths(source, inputs);
+        let error = match load(inputs) {
+            Ok(data) => {
+                if policy == SourcePolicy::UsePrevious && utils::is_path_ok(inputs[0]) {
+                    save_previous(inputs, &previous);
+                }
+                self.statuses.push((source, SourceStatus::Loaded));
+                return Ok(data);
+            }
+            Err(error) => error,
+        };
+
+        let name = source.name();
+        match policy {
+            SourcePolicy::Fail => Err(error),
+            SourcePolicy::Skip => {
+                log::warn!("Failed to load {name}: {error}. Continuing without it!");
+                self.statuses.push((source, SourceStatus::Skipped(error.to_string())));
+                empty()
+            }
+            SourcePolicy::UsePrevious => {
+                if !utils::is_path_ok(&previous[0]) {
+                    log::error!("Failed to load {name} and there's no previous copy of it");
+                    return Err(error);
+                }
+                log::warn!("Failed to load {name}: {error}. Using the previous copy {previous:?}");
+                let previous: Vec<&Path> = previous.iter().map(PathBuf::as_path).collect();
+                let data = load(&previous)?;
+                self.statuses.push((source, SourceStatus::Previous(error.to_string())));
+                Ok(data)
+            }
+        }
+    }
+}
+
+/// Keeps copies of the inputs of a successfully loaded source for the future runs.
+///
+/// Previous copies of optional inputs which are missing now are removed, so that the copies stay
+/// consistent with each other.
+fn save_previous(inputs: &[&Path], previous: &[PathBuf]) {
+    for (path, previous) in inputs.iter().zip(previous) {
+        let result = if utils::is_path_ok(path) {
+            previous
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|()| std::fs::copy(path, previous).map(|_| ()))
+        } else if previous.exists() {
+            std::fs::remove_file(previous)
+        } else {
+            Ok(())
+        };
+        if let Err(err) = result {
+            log::warn!("Failed to save a copy of {path:?} to {previous:?}: {err}");
+        }
+    }
+}
+
+/// Logs the summary of the statuses of the sources.
+fn report_statuses(statuses: &[(SupplementarySource, SourceStatus)]) {
+    let loaded = statuses.iter().filter(|(_, status)| *status == SourceStatus::Loaded).count();
+    log::info!("Sources: {loaded} of {} loaded", statuses.len());
+    for (source, status) in statuses {
+        match status {
+            SourceStatus::Loaded => {}
+            SourceStatus::Skipped(error) => log::warn!(" - {} skipped: {error}", source.name()),
+            SourceStatus::Previous(error) => {
+                log::warn!(" - {} loaded from the previous copy: {error}", source.name());
+            }
+        }
+    }
+}
+
+/// Entry of the report of the statuses of the sources.
+#[derive(serde::Serialize)]
+struct StatusRecord<'a> {
+    source: &'static str,
+    status: &'static str,
+    error: &'a str,
+}
+
+/// Writes the statuses of the sources into a CSV report.
+///
+/// # Errors
+///
+/// Returns `Err` if writing to the file fails.
+pub fn write_report(
+    path: &Path,
+    statuses: &[(SupplementarySource, SourceStatus)],
+) -> Result<(), errors::ProcessingError> {
+    let mut writer = csv::Writer::from_path(path).map_err(errors::ProcessingError::WriteCsv)?;
+    for (source, status) in statuses {
+        let (status, error) = match status {
+            SourceStatus::Loaded => ("loaded", ""),
+            SourceStatus::Skipped(error) => ("skipped", error.as_str()),
+            SourceStatus::Previous(error) => ("previous", error.as_str()),
+        };
+        let record = StatusRecord { source: source.name(), status, error };
+        writer.serialize(record).map_err(errors::ProcessingError::WriteCsv)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
 /// Holds all the supplementary source data.
 pub struct FullSources {
     /// Wikidata data.
@@ -34,17 +213,64 @@ pub struct FullSources {
 
     /// Open Food Facts advisor.
     pub off: advisors::OpenFoodFactsAdvisor,
+
+    /// GS1 company prefix licence data.
+    pub gs1: advisors::Gs1Advisor,
+
+    /// Repairability scores of products.
+    pub repairability: advisors::RepairabilityAdvisor,
+
+    /// Curated controversies of companies.
+    pub controversies: advisors::ControversiesAdvisor,
+
+    /// Textile Exchange material standards of fashion brands.
+    pub textile_exchange: advisors::TextileExchangeAdvisor,
+
+    /// LCA coefficients for estimating carbon footprints.
+    pub lca: advisors::LcaAdvisor,
+
+    /// Index of CSRD sustainability reports.
+    pub csrd: advisors::CsrdAdvisor,
+
+    /// Sustainability-related awards.
+    pub awards: advisors::AwardsAdvisor,
+
+    /// Blocked and allowed entities.
+    pub lists: advisors::EntityListsAdvisor,
+
+    /// Owners of EUIPO trade marks.
+    pub euipo: advisors::EuipoAdvisor,
+
+    /// Pre-crawled sustainability and report pages of companies.
+    pub policy_pages: advisors::PolicyPagesAdvisor,
+
+    /// Registration numbers of legal entities from the GLEIF golden copy.
+    pub gleif: advisors::GleifAdvisor,
+
+    /// Outcomes of loading the sources.
+    pub statuses: Vec<(SupplementarySource, SourceStatus)>,
 }
 
 impl FullSources {
-    #[allow(clippy::unused_self)]
+    #[must_use]
+    pub fn is_blocked(&self, item: &sustainity_wikidata::data::Item) -> bool {
+        self.lists.is_blocked(&item.id, &item.get_labels())
+    }
+
     #[must_use]
     pub fn is_product(&self, item: &sustainity_wikidata::data::Item) -> bool {
-        item.has_manufacturer() || item.has_gtin()
+        if self.lists.is_allowed_organisation(&item.id) {
+            return false;
+        }
+        self.lists.is_allowed_product(&item.id) || item.has_manufacturer() || item.has_gtin()
     }
 
     #[must_use]
     pub fn is_organisation(&self, item: &sustainity_wikidata::data::Item) -> bool {
+        if self.lists.is_allowed_organisation(&item.id) {
+            return true;
+        }
+
         if self.is_product(item) {
             return false;
         }
@@ -76,20 +302,228 @@ impl Sourceable for FullSources {
     type Config = config::SourcesConfig;
 
     /// Constructs a new `FullSources`.
+    ///
+    /// Sources failing to load are handled according to the configured policies.
     fn load(config: &Self::Config) -> Result<Self, errors::ProcessingError> {
-        let wikidata = advisors::WikidataAdvisor::load(&config.wikidata_cache_path)?;
-        let matches = advisors::SustainityMatchesAdvisor::load(&config.match_path)?;
-        let bcorp = advisors::BCorpAdvisor::load(&config.bcorp_path)?;
-        let eu_ecolabel = advisors::EuEcolabelAdvisor::load(
-            &config.eu_ecolabel_original_path,
+        let mut loader = SourceLoader::new(config);
+        let wikidata = loader.load(
+            SupplementarySource::WikidataCache,
+            &config.wikidata_cache_path,
+            |path| advisors::WikidataAdvisor::load(path),
+            || Ok(advisors::WikidataAdvisor::new_empty()),
+        )?;
+        let matches = loader.load(
+            SupplementarySource::Matches,
             &config.match_path,
+            advisors::SustainityMatchesAdvisor::load,
+            || advisors::SustainityMatchesAdvisor::new(&[]),
+        )?;
+        let sheets = loader.load(
+            SupplementarySource::Spreadsheets,
+            &config.spreadsheets_path,
+            advisors::SpreadsheetsAdvisor::load,
+            || Ok(advisors::SpreadsheetsAdvisor::default()),
+        )?;
+
+        // The sheets apply only to the configured files, not to their previous copies.
+        let bcorp = loader.load(
+            SupplementarySource::Bcorp,
+            &config.bcorp_path,
+            |path| {
+                let sheet = sheets.get(CsvSource::Bcorp).filter(|_| path == config.bcorp_path);
+                advisors::BCorpAdvisor::load(path, sheet, config.bcorp_rows.mode)
+            },
+            || Ok(advisors::BCorpAdvisor::new(&[])),
+        )?;
+        let eu_ecolabel = loader.load_all(
+            SupplementarySource::EuEcolabel,
+            &[&config.eu_ecolabel_original_path, &config.match_path],
+            |paths| {
+                let sheet = sheets
+                    .get(CsvSource::EuEcolabel)
+                    .filter(|_| paths[0] == config.eu_ecolabel_original_path);
+                advisors::EuEcolabelAdvisor::load(
+                    paths[0],
+                    sheet,
+                    paths[1],
+                    config.eu_ecolabel_rows.mode,
+                )
+            },
+            || Ok(advisors::EuEcolabelAdvisor::new(&[], &[])),
         )?;
-        let tco = advisors::TcoAdvisor::load(&config.tco_path)?;
-        let fti = advisors::FashionTransparencyIndexAdvisor::load(
+        let tco = loader.load(
+            SupplementarySource::Tco,
+            &config.tco_path,
+            advisors::TcoAdvisor::load,
+            || Ok(advisors::TcoAdvisor::new(&[])),
+        )?;
+        let fti = loader.load(
+            SupplementarySource::FashionTransparencyIndex,
             &config.fashion_transparency_index_path,
+            advisors::FashionTransparencyIndexAdvisor::load,
+            || Ok(advisors::FashionTransparencyIndexAdvisor::new(&[])?),
+        )?;
+        let off = loader.load_all(
+            SupplementarySource::OpenFoodFacts,
+            &[&config.open_food_facts_countries_path, &config.open_food_facts_categories_path],
+            |paths| advisors::OpenFoodFactsAdvisor::load(paths[0], paths[1]),
+            || Ok(advisors::OpenFoodFactsAdvisor::new_empty()),
+        )?;
+        let gs1 = loader.load(
+            SupplementarySource::Gs1Prefixes,
+            &config.gs1_prefixes_path,
+            advisors::Gs1Advisor::load,
+            || Ok(advisors::Gs1Advisor::new(&[])),
+        )?;
+        let repairability = loader.load_all(
+            SupplementarySource::Repairability,
+            &[&config.repairability_path, &config.battery_characteristics_path],
+            |paths| advisors::RepairabilityAdvisor::load(paths[0], paths[1]),
+            || Ok(advisors::RepairabilityAdvisor::new(&[], &[])),
+        )?;
+        let controversies = loader.load(
+            SupplementarySource::Controversies,
+            &config.controversies_path,
+            advisors::ControversiesAdvisor::load,
+            || Ok(advisors::ControversiesAdvisor::new(&[])),
         )?;
-        let off = advisors::OpenFoodFactsAdvisor::load(&config.open_food_facts_countries_path)?;
+        let textile_exchange = loader.load(
+            SupplementarySource::TextileExchange,
+            &config.textile_exchange_path,
+            advisors::TextileExchangeAdvisor::load,
+            || Ok(advisors::TextileExchangeAdvisor::new(&[])),
+        )?;
+        let lca = loader.load(
+            SupplementarySource::LcaCoefficients,
+            &config.lca_coefficients_path,
+            advisors::LcaAdvisor::load,
+            || Ok(advisors::LcaAdvisor::new(&[])),
+        )?;
+        let csrd = loader.load(
+            SupplementarySource::CsrdReports,
+            &config.csrd_reports_path,
+            advisors::CsrdAdvisor::load,
+            || Ok(advisors::CsrdAdvisor::new(&[])),
+        )?;
+        let awards = loader.load(
+            SupplementarySource::Awards,
+            &config.awards_path,
+            advisors::AwardsAdvisor::load,
+            || Ok(advisors::AwardsAdvisor::new(&[])),
+        )?;
+        let lists = loader.load(
+            SupplementarySource::EntityLists,
+            &config.entity_lists_path,
+            advisors::EntityListsAdvisor::load,
+            || Ok(advisors::EntityListsAdvisor::new(&entity_lists::data::Lists::default())),
+        )?;
+        let policy_pages = loader.load(
+            SupplementarySource::PolicyPages,
+            &config.policy_pages_path,
+            advisors::PolicyPagesAdvisor::load,
+            || Ok(advisors::PolicyPagesAdvisor::new(&[])),
+        )?;
+        let euipo = loader.load_all(
+            SupplementarySource::Euipo,
+            &[&config.euipo_path, &config.match_path],
+            |paths| advisors::EuipoAdvisor::load(paths[0], paths[1], &config.euipo_rows),
+            || Ok(advisors::EuipoAdvisor::new(&[], &[], &[])),
+        )?;
+        let gleif = loader.load(
+            SupplementarySource::Gleif,
+            &config.gleif_path,
+            |path| advisors::GleifAdvisor::load(path, &config.gleif_rows),
+            || Ok(advisors::GleifAdvisor::new(&[])),
+        )?;
+
+        report_statuses(&loader.statuses);
+
+        Ok(Self {
+            wikidata,
+            matches,
+            bcorp,
+            eu_ecolabel,
+            tco,
+            fti,
+            off,
+            gs1,
+            repairability,
+            controversies,
+            textile_exchange,
+            lca,
+            csrd,
+            awards,
+            lists,
+            policy_pages,
+            euipo,
+            gleif,
+            statuses: loader.statuses,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(path: &Path) -> Result<u32, errors::ProcessingError> {
+        let contents = std::fs::read_to_string(path)?;
+        serde_yaml::from_str(&contents)
+            .map_err(|err| errors::ProcessingError::ReadYaml(err, path.to_owned()))
+    }
+
+    #[test]
+    fn policies() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tco.yaml");
+        let dir = dir.path().to_str().unwrap();
+        let config = config::SourcesConfig::new(dir, dir, dir)
+            .with_policies(&[SupplementarySource::Awards], &[SupplementarySource::Tco]);
+        let mut loader = SourceLoader::new(&config);
+
+        // A copy of the source is saved when it loads.
+        std::fs::write(&path, "1").unwrap();
+        assert_eq!(loader.load(SupplementarySource::Tco, &path, parse, || Ok(0)).unwrap(), 1);
+        assert!(config.previous_path.join("tco").join("tco.yaml").exists());
+
+        std::fs::write(&path, "invalid").unwrap();
+        assert_eq!(loader.load(SupplementarySource::Tco, &path, parse, || Ok(0)).unwrap(), 1);
+        assert_eq!(loader.load(SupplementarySource::Awards, &path, parse, || Ok(0)).unwrap(), 0);
+        assert!(loader.load(SupplementarySource::LcaCoefficients, &path, parse, || Ok(0)).is_err());
+
+        let statuses: Vec<_> = loader.statuses.iter().map(|(_, status)| status).collect();
+        assert!(matches!(
+            statuses.as_slice(),
+            [SourceStatus::Loaded, SourceStatus::Previous(_), SourceStatus::Skipped(_)]
+        ));
+
+        let report = Path::new(dir).join(REPORT_FILE_NAME);
+        write_report(&report, &loader.statuses).unwrap();
+        let report = std::fs::read_to_string(report).unwrap();
+        assert!(report.starts_with("source,status,error\ntco,loaded,\ntco,previous,"));
+    }
+
+    #[test]
+    fn previous_side_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let main = dir.path().join("open_food_facts_countries.yaml");
+        let side = dir.path().join("open_food_facts_categories.yaml");
+        let dir = dir.path().to_str().unwrap();
+        let config = config::SourcesConfig::new(dir, dir, dir)
+            .with_policies(&[], &[SupplementarySource::OpenFoodFacts]);
+        let mut loader = SourceLoader::new(&config);
+        let sum = |paths: &[&Path]| -> Result<u32, errors::ProcessingError> {
+            Ok(parse(paths[0])? + parse(paths[1])?)
+        };
+
+        std::fs::write(&main, "1").unwrap();
+        std::fs::write(&side, "2").unwrap();
+        let source = SupplementarySource::OpenFoodFacts;
+        assert_eq!(loader.load_all(source, &[&main, &side], sum, || Ok(0)).unwrap(), 3);
 
-        Ok(Self { wikidata, matches, bcorp, eu_ecolabel, tco, fti, off })
+        // Both the main and the side file come from the previous copies.
+        std::fs::write(&main, "10").unwrap();
+        std::fs::write(&side, "invalid").unwrap();
+        assert_eq!(loader.load_all(source, &[&main, &side], sum, || Ok(0)).unwrap(), 3);
     }
 }
@@ -0,0 +1,426 @@
+//! Populating the Wikidata cache from the Wikidata Query Service.
+//!
+//! Processing the whole Wikidata dump takes hours. When only the cache needs a refresh, the sets
+//! it consists of (manufacturers and classes referred to by any item) can be fetched with SPARQL
+//! queries instead. The queries select the same statements the dump processing reads (of all
+//! ranks, on items only), so both paths produce the same cache.
+//!
+//! The results are fetched in pages ordered by the item IDs, each page continuing after the last
+//! ID of the previous one. Each page is cached to `<cache>/sparql/<date>/` so that an interrupted
+//! run can be resumed the same day without repeating the queries. Failed requests (rate
+//! limiting, timeouts, server errors) are retried with an exponential backoff.
+
+use std::collections::BTreeSet;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use sustainity_collecting::errors::MapSerde;
+use sustainity_models::dates;
+use sustainity_wikidata::data::Id as WikiId;
+
+use crate::{cache, config, errors, filtering1::FilteringCollector};
+
+/// Prefix of IRIs of Wikidata items.
+const ITEM_PREFIX: &str = "http://www.wikidata.org/entity/Q";
+
+/// User agent identifying the requests as required by the Wikidata Query Service policy.
+const USER_AGENT: &str = "sustainity-lab (https://sustainity.dev)";
+
+/// Maximal number of attempts to fetch a single page.
+const MAX_ATTEMPTS: u32 = 5;
+
+/// Delay before the first retry (doubled with each next retry).
+const INITIAL_BACKOFF: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Query returning a set of IDs.
+#[derive(Debug, Clone, Copy)]
+pub struct Query {
+    /// Name of the query (used in the names of the cached pages).
+    pub name: &'static str,
+
+    /// Graph pattern binding the `?item` the statements are about and the `?id` they refer to.
+    pub pattern: &'static str,
+}
+
+impl Query {
+    /// Builds the query for the page of at most `limit` IDs following the item `Q<after>`.
+    ///
+    /// Only statements on items are selected (like when processing the dump) and the results are
+    /// ordered by the numeric IDs, so the pages can be fetched without an `OFFSET`.
+    #[must_use]
+    pub fn page(&self, after: u64, limit: usize) -> String {
+        format!(
+            "SELECT DISTINCT ?id ?num WHERE {{ {pattern} \
+             FILTER(STRSTARTS(STR(?item), \"{ITEM_PREFIX}\")) \
+             FILTER(STRSTARTS(STR(?id), \"{ITEM_PREFIX}\")) \
+             BIND(xsd:integer(STRAFTER(STR(?id), \"{ITEM_PREFIX}\")) AS ?num) \
+             FILTER(?num > {after}) }} ORDER BY ?num LIMIT {limit}",
+            pattern = self.pattern,
+        )
+    }
+}
+
+/// Values of the "manufacturer" property.
+pub const MANUFACTURERS: Query =
+    Query { name: "manufacturers", pattern: "?item p:P176/ps:P176 ?id ." };
+
+/// Values of the "instance of" and "subclass of" properties.
+pub const CLASSES: Query = Query {
+    name: "classes",
+    pattern: "{ ?item p:P31/ps:P31 ?id . } UNION { ?item p:P279/ps:P279 ?id . }",
+};
+
+/// Binding of a single variable in the query results.
+#[derive(Deserialize, Debug)]
+struct Binding {
+    value: String,
+}
+
+/// Single row of the query results.
+#[derive(Deserialize, Debug)]
+struct Row {
+    id: Option<Binding>,
+}
+
+/// Rows of the query results.
+#[derive(Deserialize, Debug)]
+struct Results {
+    bindings: Vec<Row>,
+}
+
+/// Query results in the SPARQL JSON format.
+#[derive(Deserialize, Debug)]
+struct Response {
+    results: Results,
+}
+
+/// Cached page of the results.
+#[derive(Serialize, Deserialize, Debug)]
+struct Page {
+    /// IDs of the items on the page.
+    ids: Vec<String>,
+
+    /// Whether this is the last page.
+    last: bool,
+}
+
+/// Extracts IDs of Wikidata items from the query results.
+///
+/// Bindings which are not items (e.g. literals or lexemes) are skipped.
+fn parse_ids(bindings: Vec<Row>) -> Vec<WikiId> {
+    bindings
+        .into_iter()
+        .filter_map(|row| row.id)
+        .filter_map(|binding| {
+            let id = binding.value.strip_prefix(ITEM_PREFIX)?;
+            id.parse().ok().map(WikiId::new)
+        })
+        .collect()
+}
+
+/// Source of the query results.
+#[async_trait]
+trait Endpoint: Sync {
+    /// Returns the bindings of the page of at most `limit` results following the item `Q<after>`.
+    async fn select(
+        &self,
+        query: &Query,
+        after: u64,
+        limit: usize,
+    ) -> Result<Vec<Row>, errors::ProcessingError>;
+}
+
+/// The Wikidata Query Service (or another SPARQL endpoint).
+struct QueryService {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl QueryService {
+    fn new(url: &str) -> Self {
+        Self { client: reqwest::Client::new(), url: url.to_owned() }
+    }
+}
+
+#[async_trait]
+impl Endpoint for QueryService {
+    /// Sends the query, retrying failed requests.
+    async fn select(
+        &self,
+        query: &Query,
+        after: u64,
+        limit: usize,
+    ) -> Result<Vec<Row>, errors::ProcessingError> {
+        let text = query.page(after, limit);
+        let mut backoff = INITIAL_BACKOFF;
+        let mut attempt = 1;
+        loop {
+            let result = self
+                .client
+                .get(&self.url)
+                .query(&[("query", text.as_str()), ("format", "json")])
+                .header(reqwest::header::ACCEPT, "application/sparql-results+json")
+                .header(reqwest::header::USER_AGENT, USER_AGENT)
+                .send()
+                .await;
+
+            let retry_after = match result {
+                Ok(response) if response.status().is_success() => {
+                    let response: Response = response.json().await?;
+                    return Ok(response.results.bindings);
+                }
+                Ok(response) => {
+                    let status = response.status();
+                    let retriable = status == reqwest::StatusCode::TOO_MANY_REQUESTS
+                        || status.is_server_error();
+                    if !retriable || attempt == MAX_ATTEMPTS {
+                        return Err(errors::ProcessingError::Download {
+                            url: self.url.clone(),
+                            status: status.as_u16(),
+                        });
+                    }
+                    log::warn!("SPARQL query failed with status {status} (attempt {attempt})");
+                    response
+                        .headers()
+                        .get(reqwest::header::RETRY_AFTER)
+                        .and_then(|value| value.to_str().ok())
+                        .and_then(|value| value.parse().ok())
+                        .map(std::time::Duration::from_secs)
+                }
+                Err(err) => {
+                    if attempt == MAX_ATTEMPTS {
+                        return Err(err.into());
+                    }
+                    log::warn!("SPARQL query failed: {err} (attempt {attempt})");
+                    None
+                }
+            };
+
+            tokio::time::sleep(retry_after.unwrap_or(backoff).max(backoff)).await;
+            backoff *= 2;
+            attempt += 1;
+        }
+    }
+}
+
+/// Client of a SPARQL endpoint caching the fetched pages.
+struct SparqlClient<E> {
+    endpoint: E,
+    cache_dir: std::path::PathBuf,
+    page_size: usize,
+}
+
+impl<E: Endpoint> SparqlClient<E> {
+    /// Fetches a single page of the results, reusing the cached page if it exists.
+    ///
+    /// Returns the IDs and whether this is the last page.
+    async fn fetch_page(
+        &self,
+        query: &Query,
+        after: u64,
+    ) -> Result<(Vec<WikiId>, bool), errors::ProcessingError> {
+        let path = self.cache_dir.join(format!("{}-{after}.json", query.name));
+        if path.exists() {
+            let contents = std::fs::read_to_string(&path)?;
+            let page: Page = serde_json::from_str(&contents).map_with_path(&path)?;
+            let ids = page.ids.iter().map(WikiId::try_from).collect::<Result<_, _>>()?;
+            return Ok((ids, page.last));
+        }
+
+        let bindings = self.endpoint.select(query, after, self.page_size).await?;
+        let last = bindings.len() < self.page_size;
+        let ids = parse_ids(bindings);
+
+        let page =
+            Page { ids: ids.iter().map(|id| format!("Q{}", id.get_value())).collect(), last };
+        std::fs::write(&path, serde_json::to_string(&page).map_serde()?)?;
+        Ok((ids, last))
+    }
+
+    /// Fetches all the results of the query page by page.
+    async fn fetch_all(&self, query: &Query) -> Result<BTreeSet<WikiId>, errors::ProcessingError> {
+        let mut result = BTreeSet::new();
+        let mut after = 0;
+        loop {
+            let (ids, last) = self.fetch_page(query, after).await?;
+            let Some(last_id) = ids.iter().map(|id| id.get_value()).max() else {
+                break;
+            };
+            result.extend(ids);
+            log::info!("Fetched {} IDs for {}", result.len(), query.name);
+            if last {
+                break;
+            }
+            after = last_id;
+        }
+        Ok(result)
+    }
+
+    /// Fetches the manufacturers and classes and converts them to the cache data.
+    async fn fetch_cache(&self) -> Result<cache::Wikidata, errors::ProcessingError> {
+        let manufacturer_ids = self.fetch_all(&MANUFACTURERS).await?;
+        let classes = self.fetch_all(&CLASSES).await?;
+
+        log::info!("Found {} manufacturers", manufacturer_ids.len());
+        log::info!("Found {} products or classes", classes.len());
+
+        let mut collector = FilteringCollector::default();
+        collector.add_manufacturer_ids(&manufacturer_ids.into_iter().collect::<Vec<_>>());
+        collector.add_classes(&classes.into_iter().collect::<Vec<_>>());
+        Ok(collector.into_cache())
+    }
+}
+
+pub struct SparqlRunner;
+
+impl SparqlRunner {
+    /// Populates the Wikidata cache using SPARQL queries instead of the dump.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if a query keeps failing or the cache cannot be written.
+    pub async fn run(
+        config: &config::Filtering1Config,
+        sparql: &config::SparqlConfig,
+    ) -> Result<(), errors::ProcessingError> {
+        let client = SparqlClient {
+            endpoint: QueryService::new(&sparql.endpoint),
+            cache_dir: sparql.cache_path.join(dates::today()),
+            page_size: sparql.page_size,
+        };
+        std::fs::create_dir_all(&client.cache_dir)?;
+
+        let cache = client.fetch_cache().await?;
+        log::info!("Writing to {:?}", config.wikidata_cache_path);
+        cache::save(&config.wikidata_cache_path, &cache)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    #[test]
+    fn parsing() {
+        let response: Response = serde_json::from_str(
+            r#"{
+                "head": {"vars": ["id"]},
+                "results": {"bindings": [
+                    {"id": {"type": "uri", "value": "http://www.wikidata.org/entity/Q42"}},
+                    {"id": {"type": "uri", "value": "http://www.wikidata.org/entity/L7"}},
+                    {"id": {"type": "literal", "value": "text"}},
+                    {}
+                ]}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(parse_ids(response.results.bindings), vec![WikiId::new(42)]);
+    }
+
+    #[test]
+    fn paging() {
+        let text = MANUFACTURERS.page(1234, 10);
+        assert!(text.contains("FILTER(?num > 1234)"));
+        assert!(text.ends_with("ORDER BY ?num LIMIT 10"));
+        assert!(!text.contains("OFFSET"));
+    }
+
+    /// Builds a statement referring to the item `id`.
+    fn statement(property: &str, id: &str, rank: &str) -> serde_json::Value {
+        serde_json::json!({
+            "type": "statement",
+            "id": "x",
+            "rank": rank,
+            "mainsnak": {
+                "snaktype": "value",
+                "property": property,
+                "datavalue": {
+                    "type": "wikibase-entityid",
+                    "value": {"entity-type": "item", "id": id, "numeric-id": 0}
+                }
+            }
+        })
+    }
+
+    /// Builds an item with the given statements.
+    fn item(id: &str, claims: &[(&str, &str, &str)]) -> sustainity_wikidata::data::Item {
+        let mut grouped = BTreeMap::<&str, Vec<serde_json::Value>>::new();
+        for (property, value, rank) in claims {
+            grouped.entry(*property).or_default().push(statement(property, value, rank));
+        }
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "lastrevid": 1,
+            "labels": {},
+            "descriptions": {},
+            "aliases": {},
+            "claims": grouped,
+            "sitelinks": {}
+        }))
+        .unwrap()
+    }
+
+    /// Endpoint answering with results the query service returns for the items from `items()`.
+    #[derive(Clone)]
+    struct FixedEndpoint(BTreeMap<&'static str, Vec<u64>>);
+
+    #[async_trait]
+    impl Endpoint for FixedEndpoint {
+        async fn select(
+            &self,
+            query: &Query,
+            after: u64,
+            limit: usize,
+        ) -> Result<Vec<Row>, errors::ProcessingError> {
+            Ok(self.0[query.name]
+                .iter()
+                .filter(|id| **id > after)
+                .take(limit)
+                .map(|id| Row { id: Some(Binding { value: format!("{ITEM_PREFIX}{id}") }) })
+                .collect())
+        }
+    }
+
+    fn items() -> Vec<sustainity_wikidata::data::Item> {
+        vec![
+            item("Q1", &[("P176", "Q10", "normal"), ("P31", "Q20", "preferred")]),
+            item("Q2", &[("P176", "Q11", "deprecated"), ("P176", "Q10", "normal")]),
+            item("Q3", &[("P31", "Q21", "normal"), ("P279", "Q22", "normal")]),
+            item("Q4", &[("P279", "Q20", "normal"), ("P176", "Q12", "preferred")]),
+        ]
+    }
+
+    #[test]
+    fn same_cache_as_dump() {
+        let mut collector = FilteringCollector::default();
+        for item in items() {
+            collector.add_item(&item).unwrap();
+        }
+        let from_dump = collector.into_cache();
+
+        let endpoint = FixedEndpoint(BTreeMap::from([
+            (MANUFACTURERS.name, vec![10, 11, 12]),
+            (CLASSES.name, vec![20, 21, 22]),
+        ]));
+        let dir = tempfile::tempdir().unwrap();
+        for page_size in [1, 2, 3, 100] {
+            let cache_dir = dir.path().join(page_size.to_string());
+            std::fs::create_dir_all(&cache_dir).unwrap();
+            let client = SparqlClient { endpoint: endpoint.clone(), cache_dir, page_size };
+
+            // Fetching the second time reads the cached pages.
+            for _ in 0..2 {
+                let from_sparql = futures::executor::block_on(client.fetch_cache()).unwrap();
+                assert_eq!(
+                    serde_json::to_string(&from_sparql).unwrap(),
+                    serde_json::to_string(&from_dump).unwrap(),
+                    "page size {page_size}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,259 @@
+//! Export of a crystalized data set into a single SQLite file.
+//!
+//! The file contains organisations, products, their certifications and the category tree, linked
+//! with foreign keys. The names and descriptions are additionally indexed in FTS5 tables, so the
+//! file can be searched offline, e.g. by mobile apps.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use rusqlite::params;
+
+use sustainity_models::store as models;
+
+use crate::{config, errors, exporting, outputs};
+
+const SCHEMA: &str = "
+    CREATE TABLE organisations (
+        key TEXT PRIMARY KEY,
+        name TEXT,
+        description TEXT,
+        website TEXT
+    );
+    CREATE TABLE organisation_ids (
+        organisation_key TEXT NOT NULL REFERENCES organisations(key),
+        variant TEXT NOT NULL,
+        id TEXT NOT NULL,
+        PRIMARY KEY (organisation_key, variant, id)
+    );
+    CREATE TABLE products (
+        key TEXT PRIMARY KEY,
+        name TEXT,
+        description TEXT,
+        score REAL NOT NULL
+    );
+    CREATE TABLE product_ids (
+        product_key TEXT NOT NULL REFERENCES products(key),
+        variant TEXT NOT NULL,
+        id TEXT NOT NULL,
+        PRIMARY KEY (product_key, variant, id)
+    );
+    CREATE TABLE manufacturers (
+        product_key TEXT NOT NULL REFERENCES products(key),
+        organisation_key TEXT NOT NULL REFERENCES organisations(key),
+        PRIMARY KEY (product_key, organisation_key)
+    );
+    CREATE TABLE certifications (
+        id TEXT PRIMARY KEY,
+        name TEXT NOT NULL
+    );
+    CREATE TABLE organisation_certifications (
+        organisation_key TEXT NOT NULL REFERENCES organisations(key),
+        certification_id TEXT NOT NULL REFERENCES certifications(id),
+        detail TEXT,
+        PRIMARY KEY (organisation_key, certification_id)
+    );
+    CREATE TABLE product_certifications (
+        product_key TEXT NOT NULL REFERENCES products(key),
+        certification_id TEXT NOT NULL REFERENCES certifications(id),
+        detail TEXT,
+        PRIMARY KEY (product_key, certification_id)
+    );
+    CREATE TABLE categories (
+        path TEXT PRIMARY KEY,
+        parent TEXT REFERENCES categories(path),
+        num_products INTEGER NOT NULL
+    );
+    CREATE TABLE product_categories (
+        product_key TEXT NOT NULL REFERENCES products(key),
+        category_path TEXT NOT NULL REFERENCES categories(path),
+        PRIMARY KEY (product_key, category_path)
+    );
+
+    CREATE INDEX organisation_ids_id ON organisation_ids(variant, id);
+    CREATE INDEX product_ids_id ON product_ids(variant, id);
+    CREATE INDEX manufacturers_organisation ON manufacturers(organisation_key);
+    CREATE INDEX product_categories_category ON product_categories(category_path);
+
+    CREATE VIRTUAL TABLE organisations_fts USING fts5(key UNINDEXED, names, descriptions);
+    CREATE VIRTUAL TABLE products_fts USING fts5(key UNINDEXED, names, descriptions);
+";
+
+fn join_texts(texts: &[models::Text]) -> String {
+    texts.iter().map(|text| text.text.as_str()).collect::<Vec<_>>().join("\n")
+}
+
+pub struct SqliteExporter;
+
+impl SqliteExporter {
+    fn insert_organisations(
+        tx: &rusqlite::Transaction,
+        path: &std::path::Path,
+    ) -> Result<HashSet<String>, errors::ProcessingError> {
+        let mut insert = tx.prepare(
+            "INSERT INTO organisations (key, name, description, website) VALUES (?1, ?2, ?3, ?4)",
+        )?;
+        let mut insert_id = tx.prepare(
+            "INSERT OR IGNORE INTO organisation_ids (organisation_key, variant, id)
+             VALUES (?1, ?2, ?3)",
+        )?;
+        let mut insert_cert = tx.prepare(
+            "INSERT INTO organisation_certifications (organisation_key, certification_id, detail)
+             VALUES (?1, ?2, ?3)",
+        )?;
+        let mut insert_fts = tx.prepare(
+            "INSERT INTO organisations_fts (key, names, descriptions) VALUES (?1, ?2, ?3)",
+        )?;
+
+        let mut keys = HashSet::new();
+        for organisation in outputs::read_json_lines::<models::Organisation>(path)? {
+            let organisation = organisation?;
+            let key = &organisation.db_key;
+            insert.execute(params![
+                key,
+                organisation.names.first().map(|name| &name.text),
+                organisation.descriptions.first().map(|description| &description.text),
+                organisation.websites.first(),
+            ])?;
+
+            let ids = &organisation.ids;
+            for (variant, ids) in [
+                ("wiki", &ids.wiki),
+                ("vat", &ids.vat_ids),
+                ("www", &ids.domains),
+                ("lei", &ids.leis),
+                ("registration_number", &ids.registration_numbers),
+            ] {
+                for id in ids {
+                    insert_id.execute(params![key, variant, id])?;
+                }
+            }
+            for (certification, detail) in
+                exporting::certification_entries(&organisation.certifications)
+            {
+                insert_cert.execute(params![key, certification, detail])?;
+            }
+            insert_fts.execute(params![
+                key,
+                join_texts(&organisation.names),
+                join_texts(&organisation.descriptions),
+            ])?;
+
+            keys.insert(organisation.db_key);
+        }
+        Ok(keys)
+    }
+
+    /// Inserts the products and returns the number of products in each category.
+    fn insert_products(
+        tx: &rusqlite::Transaction,
+        path: &std::path::Path,
+        organisations: &HashSet<String>,
+    ) -> Result<BTreeMap<String, usize>, errors::ProcessingError> {
+        let mut insert = tx.prepare(
+            "INSERT INTO products (key, name, description, score) VALUES (?1, ?2, ?3, ?4)",
+        )?;
+        let mut insert_id = tx.prepare(
+            "INSERT OR IGNORE INTO product_ids (product_key, variant, id) VALUES (?1, ?2, ?3)",
+        )?;
+        let mut insert_cert = tx.prepare(
+            "INSERT INTO product_certifications (product_key, certification_id, detail)
+             VALUES (?1, ?2, ?3)",
+        )?;
+        let mut insert_manufacturer = tx.prepare(
+            "INSERT OR IGNORE INTO manufacturers (product_key, organisation_key) VALUES (?1, ?2)",
+        )?;
+        let mut insert_category = tx.prepare(
+            "INSERT OR IGNORE INTO product_categories (product_key, category_path) VALUES (?1, ?2)",
+        )?;
+        let mut insert_fts =
+            tx.prepare("INSERT INTO products_fts (key, names, descriptions) VALUES (?1, ?2, ?3)")?;
+
+        let mut categories = BTreeMap::<String, usize>::new();
+        let mut num_dangling = 0;
+        for product in outputs::read_json_lines::<models::Product>(path)? {
+            let product = product?;
+            let key = &product.db_key;
+            insert.execute(params![
+                key,
+                product.names.first().map(|name| &name.text),
+                product.descriptions.first().map(|description| &description.text),
+                product.sustainity_score.total,
+            ])?;
+
+            let ids = &product.ids;
+            for (variant, ids) in [("ean", &ids.eans), ("gtin", &ids.gtins), ("wiki", &ids.wiki)] {
+                for id in ids {
+                    insert_id.execute(params![key, variant, id])?;
+                }
+            }
+            for (certification, detail) in exporting::certification_entries(&product.certifications)
+            {
+                insert_cert.execute(params![key, certification, detail])?;
+            }
+            for manufacturer in &product.manufacturer_ids {
+                if organisations.contains(manufacturer) {
+                    insert_manufacturer.execute(params![key, manufacturer])?;
+                } else {
+                    num_dangling += 1;
+                }
+            }
+            // The same category may be reached from several sources; count the product only once.
+            let product_categories: BTreeSet<&String> = product.categories.iter().collect();
+            for category in product_categories {
+                insert_category.execute(params![key, category])?;
+                *categories.entry(category.clone()).or_default() += 1;
+            }
+            insert_fts.execute(params![
+                key,
+                join_texts(&product.names),
+                join_texts(&product.descriptions),
+            ])?;
+        }
+
+        if num_dangling > 0 {
+            log::warn!(" - skipped {num_dangling} links to unknown manufacturers");
+        }
+        Ok(categories)
+    }
+
+    fn insert_categories(
+        tx: &rusqlite::Transaction,
+        categories: &BTreeMap<String, usize>,
+    ) -> Result<(), errors::ProcessingError> {
+        let mut insert =
+            tx.prepare("INSERT INTO categories (path, parent, num_products) VALUES (?1, ?2, ?3)")?;
+        for path in exporting::category_tree(categories.keys()) {
+            let num_products = categories.get(&path).copied().unwrap_or(0);
+            insert.execute(params![path, exporting::category_parent(&path), num_products])?;
+        }
+        Ok(())
+    }
+
+    pub fn export(config: &config::ExportConfig) -> Result<(), errors::ProcessingError> {
+        let mut connection = rusqlite::Connection::open(&config.output_path)?;
+        connection.pragma_update(None, "foreign_keys", true)?;
+        connection.execute_batch(SCHEMA)?;
+
+        let tx = connection.transaction()?;
+        // Products refer to categories which are inserted only after all the products were read.
+        tx.pragma_update(None, "defer_foreign_keys", true)?;
+
+        for (id, name) in exporting::CERTIFICATIONS {
+            tx.execute("INSERT INTO certifications (id, name) VALUES (?1, ?2)", params![id, name])?;
+        }
+
+        log::info!("Exporting organisations");
+        let organisations = Self::insert_organisations(&tx, &config.organisations_path)?;
+        log::info!(" - {} organisations", organisations.len());
+
+        log::info!("Exporting products");
+        let categories = Self::insert_products(&tx, &config.products_path, &organisations)?;
+
+        log::info!("Exporting categories");
+        Self::insert_categories(&tx, &categories)?;
+        log::info!(" - {} categories", categories.len());
+
+        tx.commit()?;
+        Ok(())
+    }
+}
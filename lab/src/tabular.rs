@@ -0,0 +1,176 @@
+//! Export of crystalized products into a flat CSV file.
+//!
+//! Each row describes one product. Lists are joined with semicolons, manufacturers are listed by
+//! their names and each certification has its own boolean column. The columns (and their order)
+//! can be selected, by default all of them are written.
+
+use std::collections::HashMap;
+
+use sustainity_models::store as models;
+
+use crate::{commands::CsvColumn, config, errors, outputs};
+
+/// Separator of values in list columns.
+const LIST_SEPARATOR: &str = "; ";
+
+/// All columns in the default order.
+const ALL_COLUMNS: [CsvColumn; 14] = [
+    CsvColumn::Key,
+    CsvColumn::Name,
+    CsvColumn::Description,
+    CsvColumn::Gtins,
+    CsvColumn::Eans,
+    CsvColumn::Wiki,
+    CsvColumn::Categories,
+    CsvColumn::Regions,
+    CsvColumn::Manufacturers,
+    CsvColumn::Score,
+    CsvColumn::Bcorp,
+    CsvColumn::EuEcolabel,
+    CsvColumn::Fti,
+    CsvColumn::Tco,
+];
+
+fn header(column: CsvColumn) -> &'static str {
+    match column {
+        CsvColumn::Key => "key",
+        CsvColumn::Name => "name",
+        CsvColumn::Description => "description",
+        CsvColumn::Gtins => "gtins",
+        CsvColumn::Eans => "eans",
+        CsvColumn::Wiki => "wiki",
+        CsvColumn::Categories => "categories",
+        CsvColumn::Regions => "regions",
+        CsvColumn::Manufacturers => "manufacturers",
+        CsvColumn::Score => "score",
+        CsvColumn::Bcorp => "bcorp",
+        CsvColumn::EuEcolabel => "eu_ecolabel",
+        CsvColumn::Fti => "fti",
+        CsvColumn::Tco => "tco",
+    }
+}
+
+fn cell(column: CsvColumn, product: &models::Product, names: &HashMap<String, String>) -> String {
+    let certifications = &product.certifications;
+    match column {
+        CsvColumn::Key => product.db_key.clone(),
+        CsvColumn::Name => product.names.first().map(|n| n.text.clone()).unwrap_or_default(),
+        CsvColumn::Description => {
+            product.descriptions.first().map(|d| d.text.clone()).unwrap_or_default()
+        }
+        CsvColumn::Gtins => product.ids.gtins.join(LIST_SEPARATOR),
+        CsvColumn::Eans => product.ids.eans.join(LIST_SEPARATOR),
+        CsvColumn::Wiki => product.ids.wiki.join(LIST_SEPARATOR),
+        CsvColumn::Categories => product.categories.join(LIST_SEPARATOR),
+        CsvColumn::Regions => match &product.regions {
+            models::Regions::World => "all".to_owned(),
+            models::Regions::Unknown => String::new(),
+            models::Regions::List(list) => {
+                list.iter().map(|code| code.alpha3()).collect::<Vec<_>>().join(LIST_SEPARATOR)
+            }
+        },
+        CsvColumn::Manufacturers => product
+            .manufacturer_ids
+            .iter()
+            .filter_map(|id| names.get(id).map(String::as_str))
+            .collect::<Vec<_>>()
+            .join(LIST_SEPARATOR),
+        CsvColumn::Score => product.sustainity_score.total.to_string(),
+        CsvColumn::Bcorp => certifications.bcorp.is_some().to_string(),
+        CsvColumn::EuEcolabel => certifications.eu_ecolabel.is_some().to_string(),
+        CsvColumn::Fti => certifications.fti.is_some().to_string(),
+        CsvColumn::Tco => certifications.tco.is_some().to_string(),
+    }
+}
+
+/// Reads the names of the organisations.
+fn read_names(path: &std::path::Path) -> Result<HashMap<String, String>, errors::ProcessingError> {
+    let mut names = HashMap::new();
+    for organisation in outputs::read_json_lines::<models::Organisation>(path)? {
+        let organisation = organisation?;
+        if let Some(name) = organisation.names.into_iter().next() {
+            names.insert(organisation.db_key, name.text);
+        }
+    }
+    Ok(names)
+}
+
+pub struct CsvExporter;
+
+impl CsvExporter {
+    pub fn export(config: &config::ExportConfig) -> Result<(), errors::ProcessingError> {
+        let columns: &[CsvColumn] =
+            if config.columns.is_empty() { &ALL_COLUMNS } else { &config.columns };
+
+        log::info!("Reading organisation names");
+        let names = read_names(&config.organisations_path)?;
+
+        log::info!("Exporting products");
+        let mut writer = csv::Writer::from_path(&config.output_path)
+            .map_err(errors::ProcessingError::WriteCsv)?;
+        writer
+            .write_record(columns.iter().map(|column| header(*column)))
+            .map_err(errors::ProcessingError::WriteCsv)?;
+        let mut count = 0;
+        for product in outputs::read_json_lines::<models::Product>(&config.products_path)? {
+            let product = product?;
+            writer
+                .write_record(columns.iter().map(|column| cell(*column, &product, &names)))
+                .map_err(errors::ProcessingError::WriteCsv)?;
+            count += 1;
+        }
+        writer.flush()?;
+        log::info!(" - {count} products");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn cells() {
+        let product: models::Product = serde_json::from_value(json!({
+            "_key": "p1",
+            "ids": {"eans": [], "gtins": ["1", "2"], "wiki": []},
+            "names": [{"text": "Phone", "source": "wiki"}],
+            "descriptions": [],
+            "images": [],
+            "categories": ["electronics/phones"],
+            "regions": {"variant": "all"},
+            "certifications": {
+                "bcorp": null,
+                "eu_ecolabel": null,
+                "fti": null,
+                "tco": {"brand_name": "A"},
+            },
+            "manufacturer_ids": ["o1", "o2", "o3"],
+            "follows": [],
+            "followed_by": [],
+            "sustainity_score": {"tree": [], "total": 0.5},
+        }))
+        .unwrap();
+        let names = HashMap::from([
+            ("o1".to_owned(), "Acme".to_owned()),
+            ("o3".to_owned(), "Initech".to_owned()),
+        ]);
+
+        let row: Vec<String> = [
+            CsvColumn::Name,
+            CsvColumn::Gtins,
+            CsvColumn::Eans,
+            CsvColumn::Regions,
+            CsvColumn::Manufacturers,
+            CsvColumn::Score,
+            CsvColumn::Bcorp,
+            CsvColumn::Tco,
+        ]
+        .into_iter()
+        .map(|column| cell(column, &product, &names))
+        .collect();
+        assert_eq!(row, vec!["Phone", "1; 2", "", "all", "Acme; Initech", "0.5", "false", "true"]);
+    }
+}
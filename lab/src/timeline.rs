@@ -0,0 +1,204 @@
+//! Sustainability timelines of organisations.
+//!
+//! The timeline lists dated events known from the sources: when certifications were granted and
+//! when they expire, when scores and sustainability reports were published, when awards were
+//! received and when controversies came up. Records without known dates don't contribute any
+//! events.
+
+use sustainity_models::{attributes, gather};
+
+/// Constructs an event without the optional details.
+fn event(date: &str, kind: gather::TimelineEventKind) -> gather::TimelineEvent {
+    gather::TimelineEvent {
+        date: date.to_owned(),
+        kind,
+        certification: None,
+        score: None,
+        title: None,
+        url: None,
+    }
+}
+
+/// Adds the granting and expiry events of a certification.
+fn add_validity(
+    events: &mut Vec<gather::TimelineEvent>,
+    certification: gather::CertificationKind,
+    validity: &gather::Validity,
+) {
+    let dates = [
+        (&validity.valid_from, gather::TimelineEventKind::CertificationGranted),
+        (&validity.valid_until, gather::TimelineEventKind::CertificationExpiry),
+    ];
+    for (date, kind) in dates {
+        if let Some(date) = date {
+            events.push(gather::TimelineEvent {
+                certification: Some(certification),
+                ..event(date, kind)
+            });
+        }
+    }
+}
+
+/// Builds the timeline of the organisation, sorted by date.
+#[must_use]
+pub fn build(organisation: &gather::Organisation) -> Vec<gather::TimelineEvent> {
+    let mut events = Vec::new();
+
+    let certifications = &organisation.certifications;
+    if let Some(bcorp) = &certifications.bcorp {
+        add_validity(&mut events, gather::CertificationKind::BCorp, &bcorp.validity);
+    }
+    if let Some(eu_ecolabel) = &certifications.eu_ecolabel {
+        add_validity(&mut events, gather::CertificationKind::EuEcolabel, &eu_ecolabel.validity);
+    }
+    if let Some(tco) = &certifications.tco {
+        add_validity(&mut events, gather::CertificationKind::Tco, &tco.validity);
+    }
+    if let Some(fti) = &certifications.fti {
+        // The index is published yearly, the snapshot tells which edition the score comes from.
+        let validity = &fti.validity;
+        if let Some(date) = validity.valid_from.as_ref().or(validity.source_snapshot_date.as_ref())
+        {
+            events.push(gather::TimelineEvent {
+                certification: Some(gather::CertificationKind::Fti),
+                score: Some(fti.score),
+                ..event(date, gather::TimelineEventKind::ScorePublished)
+            });
+        }
+    }
+
+    let attributes = &organisation.attributes;
+    if let Some(gather::AttributeValue::Number(year)) = attributes.get(attributes::CSRD_REPORT_YEAR)
+    {
+        let url = match attributes.get(attributes::CSRD_REPORT_URL) {
+            Some(gather::AttributeValue::Text(url)) => Some(url.clone()),
+            _ => None,
+        };
+        let date = format!("{year:.0}");
+        events.push(gather::TimelineEvent {
+            url,
+            ..event(&date, gather::TimelineEventKind::ReportPublished)
+        });
+    }
+
+    for recognition in &organisation.recognitions {
+        if let Some(date) = &recognition.date {
+            events.push(gather::TimelineEvent {
+                title: Some(recognition.name.clone()),
+                ..event(date, gather::TimelineEventKind::AwardReceived)
+            });
+        }
+    }
+
+    for controversy in &organisation.controversies {
+        events.push(gather::TimelineEvent {
+            title: Some(controversy.issue.clone()),
+            url: Some(controversy.url.clone()),
+            ..event(&controversy.date, gather::TimelineEventKind::Controversy)
+        });
+    }
+
+    events.sort();
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+
+    fn organisation() -> gather::Organisation {
+        gather::Organisation {
+            db_key: gather::OrganisationId::from_value(1),
+            ids: gather::OrganisationIds {
+                vat_ids: BTreeSet::new(),
+                wiki: BTreeSet::new(),
+                domains: BTreeSet::new(),
+                leis: BTreeSet::new(),
+                registration_numbers: BTreeSet::new(),
+            },
+            names: BTreeSet::new(),
+            keywords: BTreeSet::new(),
+            descriptions: BTreeSet::new(),
+            images: BTreeSet::new(),
+            websites: BTreeSet::new(),
+            social: BTreeSet::new(),
+            attributes: gather::Attributes::default(),
+            recognitions: BTreeSet::new(),
+            controversies: BTreeSet::new(),
+            policy_pages: BTreeSet::new(),
+            certifications: gather::Certifications::default(),
+            timeline: Vec::new(),
+            parent_ids: BTreeSet::new(),
+        }
+    }
+
+    #[test]
+    fn building() {
+        assert!(build(&organisation()).is_empty());
+
+        let mut organisation = organisation();
+        organisation.certifications.bcorp = Some(gather::BCorpCert {
+            id: "company".to_owned(),
+            validity: gather::Validity {
+                valid_from: Some("2021-03-01".to_owned()),
+                valid_until: Some("2024-03-01".to_owned()),
+                source_snapshot_date: Some("2023-05-01".to_owned()),
+            },
+        });
+        organisation.certifications.fti = Some(gather::FtiCert {
+            score: 42,
+            validity: gather::Validity::from_snapshot(Some("2023-05-01".to_owned())),
+        });
+        organisation.certifications.tco = Some(gather::TcoCert {
+            brand_name: "Company".to_owned(),
+            validity: gather::Validity::default(),
+        });
+        organisation.attributes = gather::Attributes::from([
+            (attributes::CSRD_REPORT_YEAR.to_owned(), gather::AttributeValue::Number(2022.0)),
+            (
+                attributes::CSRD_REPORT_URL.to_owned(),
+                gather::AttributeValue::Text("https://company.com/report.pdf".to_owned()),
+            ),
+        ]);
+        organisation.recognitions = BTreeSet::from([
+            gather::Recognition {
+                wiki_id: "1".to_owned(),
+                name: "Sustainability Award".to_owned(),
+                date: Some("2020-11-05".to_owned()),
+            },
+            gather::Recognition {
+                wiki_id: "2".to_owned(),
+                name: "Undated Award".to_owned(),
+                date: None,
+            },
+        ]);
+        organisation.controversies = BTreeSet::from([gather::Controversy {
+            date: "2023".to_owned(),
+            issue: "Misleading claims".to_owned(),
+            severity: gather::ControversySeverity::Medium,
+            url: "https://news.example.org/company".to_owned(),
+        }]);
+
+        let events = build(&organisation);
+        let summary: Vec<_> =
+            events.iter().map(|event| (event.date.as_str(), event.kind)).collect();
+        assert_eq!(
+            summary,
+            [
+                ("2020-11-05", gather::TimelineEventKind::AwardReceived),
+                ("2021-03-01", gather::TimelineEventKind::CertificationGranted),
+                ("2022", gather::TimelineEventKind::ReportPublished),
+                ("2023", gather::TimelineEventKind::Controversy),
+                ("2023-05-01", gather::TimelineEventKind::ScorePublished),
+                ("2024-03-01", gather::TimelineEventKind::CertificationExpiry),
+            ]
+        );
+        assert_eq!(events[0].title.as_deref(), Some("Sustainability Award"));
+        assert_eq!(events[1].certification, Some(gather::CertificationKind::BCorp));
+        assert_eq!(events[2].url.as_deref(), Some("https://company.com/report.pdf"));
+        assert_eq!(events[3].title.as_deref(), Some("Misleading claims"));
+        assert_eq!(events[4].score, Some(42));
+    }
+}
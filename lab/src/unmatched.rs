@@ -0,0 +1,146 @@
+//! Records which could not be matched to Wikidata during condensation.
+//!
+//! Such records are not dropped from the substrates, but without a Wikidata ID they are unlikely
+//! to be merged with data from other sources. Exporting them lets the maintainers improve the
+//! matching files iteratively.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors;
+
+/// Reason why a record was not matched.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Reason {
+    /// The record has no VAT number.
+    NoVat,
+
+    /// The VAT number was not found in the matching file.
+    NoVatMatch,
+
+    /// The name was not found in the matching file.
+    NoNameMatch,
+
+    /// The record has no website from which a domain to match by could be extracted.
+    NoDomain,
+
+    /// The Wikidata ID is not one of the known manufacturers.
+    UnknownWikiId,
+
+    /// The ID of the record is not valid (e.g. a VAT number or GTIN with wrong format).
+    InvalidId,
+
+    /// The record lacks data needed to present it (e.g. the name or the brand of a product).
+    ///
+    /// These are reported in aggregate (see `InsufficientData`).
+    InsufficientData,
+}
+
+/// An unmatched record.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Record {
+    /// Why the record was not matched.
+    pub reason: Reason,
+
+    /// ID of the record in the source (or its best substitute, e.g. the company name).
+    pub id: String,
+
+    /// Name of the company or product.
+    pub name: String,
+
+    /// The value that failed to match (e.g. the VAT number).
+    pub value: String,
+}
+
+impl Record {
+    #[must_use]
+    pub fn new(reason: Reason, id: String, name: String, value: String) -> Self {
+        Self { reason, id, name, value }
+    }
+}
+
+/// Numbers of records lacking data, by the data they lack.
+///
+/// Missing data cannot be fixed in the matching files, so listing every such record would only
+/// bury the ones which can. Instead, a single record with the count is reported for each kind of
+/// missing data.
+#[derive(Debug, Clone, Default)]
+pub struct InsufficientData(BTreeMap<String, usize>);
+
+impl InsufficientData {
+    /// Counts a record lacking the given data (e.g. `brand`).
+    pub fn add(&mut self, missing: &str) {
+        *self.0.entry(missing.to_owned()).or_default() += 1;
+    }
+
+    /// Converts the counts to records with the missing data as the ID and the count as the value.
+    #[must_use]
+    pub fn into_records(self) -> Vec<Record> {
+        self.0
+            .into_iter()
+            .map(|(missing, count)| {
+                Record::new(Reason::InsufficientData, missing, String::new(), count.to_string())
+            })
+            .collect()
+    }
+}
+
+impl merge::Merge for InsufficientData {
+    fn merge(&mut self, other: Self) {
+        for (missing, count) in other.0 {
+            *self.0.entry(missing).or_default() += count;
+        }
+    }
+}
+
+/// Writes the records into a CSV file, sorted and without duplicates.
+///
+/// # Errors
+///
+/// Returns `Err` if writing to the file fails.
+pub fn write(
+    path: &std::path::Path,
+    mut records: Vec<Record>,
+) -> Result<(), errors::ProcessingError> {
+    records.sort();
+    records.dedup();
+
+    let mut writer = csv::Writer::from_path(path).map_err(errors::ProcessingError::WriteCsv)?;
+    for record in &records {
+        writer.serialize(record).map_err(errors::ProcessingError::WriteCsv)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use merge::Merge;
+
+    use super::*;
+
+    #[test]
+    fn insufficient_data() {
+        let mut first = InsufficientData::default();
+        first.add("brand");
+        first.add("product_name");
+        let mut second = InsufficientData::default();
+        second.add("brand");
+        first.merge(second);
+
+        assert_eq!(
+            first.into_records(),
+            vec![
+                Record::new(Reason::InsufficientData, "brand".into(), String::new(), "2".into()),
+                Record::new(
+                    Reason::InsufficientData,
+                    "product_name".into(),
+                    String::new(),
+                    "1".into()
+                ),
+            ]
+        );
+    }
+}
@@ -7,6 +7,7 @@ use sustainity_collecting::{errors::MapSerde, open_food_facts};
 
 use crate::{config, convert, errors, parallel, runners, sources, sources::Sourceable, utils};
 
+/// Orders tags by their count (descending) and then by the tag.
 fn compare_countries(c1: &(String, usize), c2: &(String, usize)) -> Ordering {
     let cmp = c2.1.cmp(&c1.1);
     match cmp {
@@ -25,6 +26,9 @@ pub struct UpdateCollector {
 
     /// Counts how many empty countries were present.
     empty_count: usize,
+
+    /// Gathers all found category tags. The count is used for sorting.
+    categories: HashMap<String, usize>,
 }
 
 impl UpdateCollector {}
@@ -33,6 +37,7 @@ impl merge::Merge for UpdateCollector {
     fn merge(&mut self, other: Self) {
         utils::merge_hashmaps_with(&mut self.countries, other.countries, |a, b| *a += b);
         self.empty_count += other.empty_count;
+        utils::merge_hashmaps_with(&mut self.categories, other.categories, |a, b| *a += b);
     }
 }
 
@@ -59,6 +64,9 @@ impl runners::OpenFoodFactsWorker for UpdateWorker {
                 self.collector.countries.entry(tag).and_modify(|n| *n += 1).or_insert(1);
             }
         }
+        for tag in record.extract_category_tags() {
+            self.collector.categories.entry(tag).and_modify(|n| *n += 1).or_insert(1);
+        }
         Ok(())
     }
 
@@ -121,6 +129,30 @@ impl runners::Stash for UpdateStash {
         let contents = serde_yaml::to_string(&countries).map_serde()?;
         std::fs::write(&self.config.sources.open_food_facts_countries_path, contents)?;
 
+        // Unmapped categories are kept in the file without a category to guide the mapping.
+        let mut counted_categories: Vec<(String, usize)> =
+            self.collector.categories.drain().collect();
+        counted_categories.sort_by(compare_countries);
+
+        let mut categories = Vec::<open_food_facts::data::CategoryEntry>::new();
+        let mut assigned: usize = 0;
+        let mut all: usize = 0;
+        for (category_tag, count) in counted_categories {
+            let category = self.sources.off.get_category(&category_tag).map(|c| c.join("/"));
+            if category.is_some() {
+                assigned += count;
+            }
+            all += count;
+            categories.push(open_food_facts::data::CategoryEntry { category_tag, category, count });
+        }
+
+        let unmapped = categories.iter().filter(|entry| entry.category.is_none()).count();
+        println!(" - found {} categories ({unmapped} unmapped)", categories.len());
+        println!(" - {}% of category use-cases assigned", 100 * assigned / all.max(1));
+
+        let contents = serde_yaml::to_string(&categories).map_serde()?;
+        std::fs::write(&self.config.sources.open_food_facts_categories_path, contents)?;
+
         Ok(())
     }
 }
@@ -4,23 +4,54 @@ use std::collections::{BTreeMap, HashMap};
 
 use crate::errors;
 
-/// Extracts domain from a URL.
+/// Extracts the host name from a URL.
+///
+/// The scheme, user info, port, path, query and fragment are removed. The host is lower-cased and
+/// internationalised names are converted to punycode.
 #[must_use]
-pub fn extract_domain_from_url(url: &str) -> String {
-    let mut domain = url;
-    if domain.starts_with("http://") {
-        domain = &domain[7..];
+pub fn extract_host_from_url(url: &str) -> String {
+    let mut host = url.trim();
+    if let Some((_scheme, rest)) = host.split_once("://") {
+        host = rest;
+    }
+    if let Some(end) = host.find(['/', '?', '#']) {
+        host = &host[..end];
     }
-    if domain.starts_with("https://") {
-        domain = &domain[8..];
+    if let Some((_user_info, rest)) = host.rsplit_once('@') {
+        host = rest;
+    }
+    if let Some((name, port)) = host.rsplit_once(':') {
+        if port.chars().all(|c| c.is_ascii_digit()) {
+            host = name;
+        }
     }
-    if domain.starts_with("www.") {
-        domain = &domain[4..];
+    let host = host.trim_end_matches('.');
+    idna::domain_to_ascii(host).unwrap_or_else(|_| host.to_lowercase())
+}
+
+/// Extracts the registrable domain from a URL.
+///
+/// The registrable domain is the public suffix (e.g. `com` or `co.uk`) and one more label, so
+/// `https://shop.example.co.uk/about` gives `example.co.uk`. Hosts without a known public suffix
+/// (e.g. IP addresses) are returned whole, with the `www.` prefix removed.
+#[must_use]
+pub fn extract_domain_from_url(url: &str) -> String {
+    let host = extract_host_from_url(url);
+    if host.parse::<std::net::IpAddr>().is_ok() {
+        return host;
     }
-    if let Some((host, _path)) = domain.split_once('/') {
-        domain = host;
+    match psl::domain_str(&host) {
+        Some(domain) => domain.to_owned(),
+        None => host.strip_prefix("www.").map(str::to_owned).unwrap_or(host),
     }
-    domain.to_lowercase()
+}
+
+/// Iterates over the domain and all its parent domains with at least two labels.
+///
+/// For `a.b.example.com` yields `a.b.example.com`, `b.example.com` and `example.com`.
+pub fn domain_with_parents(domain: &str) -> impl Iterator<Item = &str> {
+    std::iter::successors(Some(domain), |d| d.split_once('.').map(|(_, parent)| parent))
+        .take_while(|d| d.contains('.'))
 }
 
 /// Extracts domains from multiple URLs.
@@ -57,6 +88,15 @@ pub fn path_exists(path: &std::path::Path) -> Result<(), errors::ConfigCheckErro
     Ok(())
 }
 
+/// Verifies that the pipeline output written at the path exists (possibly compressed).
+///
+/// # Errors
+///
+/// Returns an error if the output does not exist in any of the compressions.
+pub fn output_exists(path: &std::path::Path) -> Result<(), errors::ConfigCheckError> {
+    path_exists(&crate::outputs::resolve(path))
+}
+
 /// Verifies that the path exists and is a directory.
 ///
 /// # Errors
@@ -181,6 +221,8 @@ where
 mod tests {
     use std::collections::{HashMap, HashSet};
 
+    use proptest::prelude::*;
+
     use super::*;
 
     #[test]
@@ -194,6 +236,63 @@ mod tests {
         assert_eq!(extract_domain_from_url("https://www.ExamPle.com/a/"), "example.com");
     }
 
+    #[test]
+    fn test_extract_domain_from_url_public_suffixes() {
+        assert_eq!(extract_domain_from_url("shop.example.co.uk"), "example.co.uk");
+        assert_eq!(extract_domain_from_url("https://www.example.co.uk/en/"), "example.co.uk");
+        assert_eq!(extract_domain_from_url("https://a.b.example.com.au"), "example.com.au");
+        assert_eq!(extract_domain_from_url("http://eu.shop.example.de"), "example.de");
+        assert_eq!(extract_domain_from_url("company.blogspot.com"), "company.blogspot.com");
+    }
+
+    #[test]
+    fn test_extract_domain_from_url_edge_cases() {
+        assert_eq!(extract_domain_from_url(" HTTPS://WWW.EXAMPLE.COM "), "example.com");
+        assert_eq!(extract_domain_from_url("https://example.com:8080/path"), "example.com");
+        assert_eq!(extract_domain_from_url("https://user:pw@example.com"), "example.com");
+        assert_eq!(extract_domain_from_url("example.com?ref=a#top"), "example.com");
+        assert_eq!(extract_domain_from_url("example.com."), "example.com");
+        assert_eq!(extract_domain_from_url("https://www.bücher.de/"), "xn--bcher-kva.de");
+        assert_eq!(extract_domain_from_url("xn--bcher-kva.de"), "xn--bcher-kva.de");
+        assert_eq!(extract_domain_from_url("http://192.168.0.1/a"), "192.168.0.1");
+        assert_eq!(extract_domain_from_url("localhost:3000"), "localhost");
+    }
+
+    proptest! {
+        #[test]
+        fn test_extract_domain_from_url_never_panics(url in "\\PC*") {
+            let domain = extract_domain_from_url(&url);
+            let _ = domain_with_parents(&domain).count();
+        }
+
+        #[test]
+        fn test_extract_domain_from_url_generated(
+            scheme in "(https?://)?",
+            subdomains in "([a-z0-9]{1,10}\\.){0,3}",
+            name in "[a-z]{6}[a-z0-9]{0,10}",
+            suffix in "com|org|de|co\\.uk",
+            path in "(/[a-zA-Z0-9]{0,8}){0,3}",
+        ) {
+            let url = format!("{scheme}{subdomains}{name}.{suffix}{path}");
+            let domain = extract_domain_from_url(&url);
+            prop_assert_eq!(&domain, &format!("{name}.{suffix}"));
+            prop_assert_eq!(&extract_domain_from_url(&domain), &domain);
+        }
+    }
+
+    #[test]
+    fn test_extract_host_from_url() {
+        assert_eq!(extract_host_from_url("https://Shop.Example.co.uk/a"), "shop.example.co.uk");
+        assert_eq!(extract_host_from_url("ftp://files.example.com:21"), "files.example.com");
+    }
+
+    #[test]
+    fn test_domain_with_parents() {
+        let domains: Vec<&str> = domain_with_parents("a.b.example.com").collect();
+        assert_eq!(domains, vec!["a.b.example.com", "b.example.com", "example.com"]);
+        assert_eq!(domain_with_parents("localhost").count(), 0);
+    }
+
     #[test]
     fn test_extract_domains_from_urls_vec() {
         let input = vec!["www.example.com", "http://www.example.com", "example2.com"];
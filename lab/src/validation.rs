@@ -0,0 +1,247 @@
+//! JSON Schemas of the intermediate files and validation against them.
+//!
+//! The schemas are generated from the same types the files are (de)serialised with, so they always
+//! describe what the lab writes and the backend importer reads. JSON Lines files are validated
+//! record by record; each violation is reported with its line number and the JSON Pointer to the
+//! offending value inside the record.
+
+use std::io::{BufRead, Read};
+
+use serde_json::Value;
+
+use sustainity_models::store as models;
+
+use crate::{cache, commands::FileKind, config, errors, outputs};
+
+/// Maximal number of violations reported in the log (all of them are counted).
+const MAX_REPORTED_VIOLATIONS: usize = 100;
+
+/// All kinds of the intermediate files.
+const ALL_KINDS: [FileKind; 5] = [
+    FileKind::Products,
+    FileKind::Organisations,
+    FileKind::Library,
+    FileKind::Presentations,
+    FileKind::Cache,
+];
+
+impl FileKind {
+    /// Name of the file of this kind as written by the pipeline.
+    #[must_use]
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Self::Products => "products.jsonl",
+            Self::Organisations => "organisations.jsonl",
+            Self::Library => "library.jsonl",
+            Self::Presentations => "presentations.jsonl",
+            Self::Cache => "wikidata_cache.json",
+        }
+    }
+
+    /// Name of the schema file of this kind.
+    #[must_use]
+    pub fn schema_name(self) -> String {
+        let stem = self.file_name().split('.').next().unwrap_or_default();
+        format!("{stem}.schema.json")
+    }
+
+    /// Tells if the file contains one record per line.
+    #[must_use]
+    pub fn is_json_lines(self) -> bool {
+        !matches!(self, Self::Cache)
+    }
+
+    /// Guesses the kind from the file name, ignoring the compression extension.
+    #[must_use]
+    pub fn infer(path: &std::path::Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?;
+        let name = name.strip_suffix(".gz").or_else(|| name.strip_suffix(".zst")).unwrap_or(name);
+        ALL_KINDS.into_iter().find(|kind| kind.file_name() == name)
+    }
+
+    /// Generates the JSON Schema of this kind.
+    #[must_use]
+    pub fn schema(self) -> schemars::schema::RootSchema {
+        match self {
+            Self::Products => schemars::schema_for!(models::Product),
+            Self::Organisations => schemars::schema_for!(models::Organisation),
+            Self::Library => schemars::schema_for!(models::LibraryItem),
+            Self::Presentations => schemars::schema_for!(models::Presentation),
+            Self::Cache => schemars::schema_for!(cache::Wikidata),
+        }
+    }
+}
+
+/// Single schema violation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// Line in the file (starting from 1).
+    pub line: usize,
+
+    /// JSON Pointer to the offending value (empty if the record could not be parsed).
+    pub pointer: String,
+
+    /// Description of the problem.
+    pub message: String,
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.pointer.is_empty() {
+            write!(f, "line {}: {}", self.line, self.message)
+        } else {
+            write!(f, "line {}, at `{}`: {}", self.line, self.pointer, self.message)
+        }
+    }
+}
+
+/// Validates JSON values against the schema of one kind of files.
+pub struct Validator {
+    schema: jsonschema::JSONSchema,
+}
+
+impl Validator {
+    /// Compiles the schema of the given kind.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the generated schema is not a valid JSON Schema.
+    pub fn new(kind: FileKind) -> Result<Self, errors::ProcessingError> {
+        let schema =
+            serde_json::to_value(kind.schema()).map_err(errors::ProcessingError::WriteJson)?;
+        let schema = jsonschema::JSONSchema::compile(&schema)
+            .map_err(|e| errors::ProcessingError::Schema(e.to_string()))?;
+        Ok(Self { schema })
+    }
+
+    /// Validates the text of a single record found at the given line.
+    #[must_use]
+    pub fn validate(&self, line: usize, text: &str) -> Vec<Violation> {
+        let value: Value = match serde_json::from_str(text) {
+            Ok(value) => value,
+            Err(err) => {
+                let line = line + err.line().saturating_sub(1);
+                return vec![Violation { line, pointer: String::new(), message: err.to_string() }];
+            }
+        };
+        match self.schema.validate(&value) {
+            Ok(()) => Vec::new(),
+            Err(errors) => errors
+                .map(|error| Violation {
+                    line,
+                    pointer: error.instance_path.to_string(),
+                    message: error.to_string(),
+                })
+                .collect(),
+        }
+    }
+}
+
+pub struct SchemasRunner;
+
+impl SchemasRunner {
+    /// Runs the `schemas` command.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if writing the schemas failed.
+    pub fn run(config: &config::SchemasConfig) -> Result<(), errors::ProcessingError> {
+        std::fs::create_dir_all(&config.output_path)?;
+        for kind in ALL_KINDS {
+            let path = config.output_path.join(kind.schema_name());
+            let contents = serde_json::to_string_pretty(&kind.schema())
+                .map_err(errors::ProcessingError::WriteJson)?;
+            std::fs::write(&path, contents)?;
+            log::info!(" - {}", path.display());
+        }
+        Ok(())
+    }
+}
+
+pub struct ValidationRunner;
+
+impl ValidationRunner {
+    /// Runs the `validate` command.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the file could not be read or does not match its schema.
+    pub fn run(config: &config::ValidationConfig) -> Result<(), errors::ProcessingError> {
+        let path = &config.input_path;
+        let kind = config
+            .kind
+            .or_else(|| FileKind::infer(path))
+            .ok_or_else(|| errors::ProcessingError::UnknownFileKind(path.clone()))?;
+        let validator = Validator::new(kind)?;
+
+        let mut reader = outputs::open(path)?;
+        let mut records = 0;
+        let mut violations = 0;
+        let mut report = |found: Vec<Violation>| {
+            for violation in found {
+                if violations < MAX_REPORTED_VIOLATIONS {
+                    log::error!("{violation}");
+                }
+                violations += 1;
+            }
+        };
+        if kind.is_json_lines() {
+            for (index, line) in reader.lines().enumerate() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                report(validator.validate(index + 1, &line));
+                records += 1;
+            }
+        } else {
+            let mut contents = String::new();
+            reader.read_to_string(&mut contents)?;
+            report(validator.validate(1, &contents));
+            records += 1;
+        }
+
+        if violations > MAX_REPORTED_VIOLATIONS {
+            log::error!("... and {} more", violations - MAX_REPORTED_VIOLATIONS);
+        }
+        if violations == 0 {
+            log::info!(" - {records} records match the {kind:?} schema");
+            Ok(())
+        } else {
+            Err(errors::ProcessingError::Validation { path: path.clone(), errors: violations })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inference() {
+        let path = std::path::Path::new("target/products.jsonl.zst");
+        assert_eq!(FileKind::infer(path), Some(FileKind::Products));
+        let path = std::path::Path::new("cache/wikidata_cache.json");
+        assert_eq!(FileKind::infer(path), Some(FileKind::Cache));
+        assert_eq!(FileKind::infer(std::path::Path::new("other.jsonl")), None);
+        assert_eq!(FileKind::Library.schema_name(), "library.schema.json");
+    }
+
+    #[test]
+    fn violations() {
+        let validator = Validator::new(FileKind::Library).unwrap();
+        let valid = r#"{"id": "cert:bcorp", "title": "B", "summary": "S", "article": "A"}"#;
+        assert!(validator.validate(1, valid).is_empty());
+
+        let invalid = r#"{"id": "cert:bcorp", "title": 5, "summary": "S", "article": "A"}"#;
+        let violations = validator.validate(7, invalid);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].line, 7);
+        assert_eq!(violations[0].pointer, "/title");
+
+        let violations = validator.validate(3, "{\"id\": ");
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].line, 3);
+        assert!(violations[0].pointer.is_empty());
+    }
+}
@@ -0,0 +1,229 @@
+//! Grouping of product variants under their parent products.
+//!
+//! Products often come in variants differing only in colour or storage size. Variants are linked
+//! to their parent either explicitly (Wikidata "edition or translation of" claims loaded with the
+//! attributes) or by their names: electronics of the same manufacturer whose names differ only in
+//! colours or storage sizes are grouped under the one with the plainest name. Names of other
+//! products (e.g. "Green Tea" and "Tea" of the same brand) are not grouped.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use sustainity_models::gather;
+
+use crate::dedup;
+
+/// Categories of electronics, the only products grouped by their names.
+const ELECTRONICS_CATEGORIES: &[&str] = &[
+    "calculator",
+    "camera",
+    "computer",
+    "drone",
+    "game_console",
+    "game_controller",
+    "laptop",
+    "smartphone",
+    "smartwatch",
+    "tablet",
+];
+
+/// Prefix of the category paths of electronics from other sources.
+const ELECTRONICS_PREFIX: &str = "electronics";
+
+/// Words naming colours which distinguish product variants.
+const COLOURS: &[&str] = &[
+    "black",
+    "blue",
+    "gold",
+    "graphite",
+    "gray",
+    "green",
+    "grey",
+    "midnight",
+    "orange",
+    "pink",
+    "purple",
+    "red",
+    "silver",
+    "starlight",
+    "white",
+    "yellow",
+];
+
+/// Units of storage sizes which distinguish product variants.
+const STORAGE_UNITS: &[&str] = &["gb", "tb"];
+
+/// Checks if the word is a storage size with the unit, e.g. `128gb`.
+fn is_storage_size(word: &str) -> bool {
+    STORAGE_UNITS.iter().any(|unit| {
+        word.strip_suffix(unit)
+            .is_some_and(|number| !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()))
+    })
+}
+
+/// Returns the name of the product with the colours and storage sizes removed.
+#[must_use]
+pub fn base_name(name: &str) -> String {
+    let normalised = dedup::normalise_name(name);
+    let words: Vec<&str> = normalised.split(' ').collect();
+    let mut result = Vec::with_capacity(words.len());
+    let mut i = 0;
+    while i < words.len() {
+        let word = words[i];
+        let is_number = !word.is_empty() && word.chars().all(|c| c.is_ascii_digit());
+        if is_number && words.get(i + 1).is_some_and(|unit| STORAGE_UNITS.contains(unit)) {
+            i += 2;
+            continue;
+        }
+        if !COLOURS.contains(&word) && !is_storage_size(word) {
+            result.push(word);
+        }
+        i += 1;
+    }
+    result.join(" ")
+}
+
+/// Checks if the product belongs to one of the electronics categories.
+fn is_electronics(product: &gather::Product) -> bool {
+    product.categories.iter().any(|category| {
+        ELECTRONICS_CATEGORIES.contains(&category.as_str())
+            || category.split('/').next() == Some(ELECTRONICS_PREFIX)
+    })
+}
+
+/// Finds groups of electronics with the same manufacturer and base name.
+///
+/// Products which are already variants, which have no name or which are not electronics are
+/// skipped.
+fn find_name_groups(
+    products: &BTreeMap<gather::ProductId, gather::Product>,
+) -> Vec<BTreeSet<gather::ProductId>> {
+    let mut groups =
+        BTreeMap::<(gather::OrganisationId, String), BTreeSet<gather::ProductId>>::new();
+    for (id, product) in products {
+        if product.variant_of.is_some() || !is_electronics(product) {
+            continue;
+        }
+        let Some(name) = product.names.first() else { continue };
+        let base = base_name(&name.text);
+        if base.is_empty() {
+            continue;
+        }
+        for manufacturer in &product.manufacturer_ids {
+            groups.entry((manufacturer.clone(), base.clone())).or_default().insert(id.clone());
+        }
+    }
+    groups.into_values().filter(|group| group.len() > 1).collect()
+}
+
+/// Chooses the parent of a group: the product with the plainest name, or the first one.
+fn choose_parent(
+    group: &BTreeSet<gather::ProductId>,
+    products: &BTreeMap<gather::ProductId, gather::Product>,
+) -> Option<gather::ProductId> {
+    let is_plain = |id: &&gather::ProductId| {
+        products[*id]
+            .names
+            .iter()
+            .any(|name| base_name(&name.text) == dedup::normalise_name(&name.text))
+    };
+    group.iter().find(is_plain).or_else(|| group.first()).cloned()
+}
+
+/// Groups the product variants under their parents and fills in the lists of variants.
+///
+/// Parents of parents are followed, so that all variants hang directly under the topmost product.
+/// Returns the number of variants.
+pub fn group(products: &mut BTreeMap<gather::ProductId, gather::Product>) -> usize {
+    for group in find_name_groups(products) {
+        let Some(parent) = choose_parent(&group, products) else { continue };
+        for id in group {
+            if id != parent {
+                if let Some(product) = products.get_mut(&id) {
+                    product.variant_of = Some(parent.clone());
+                }
+            }
+        }
+    }
+
+    // Resolve the topmost parents, dropping links to unknown products and cycles.
+    let mut parents = BTreeMap::new();
+    for (id, product) in products.iter() {
+        let mut visited = BTreeSet::from([id.clone()]);
+        let mut current = product.variant_of.clone();
+        let mut topmost = None;
+        while let Some(parent) = current {
+            if !products.contains_key(&parent) {
+                break;
+            }
+            if !visited.insert(parent.clone()) {
+                topmost = None;
+                break;
+            }
+            current = products[&parent].variant_of.clone();
+            topmost = Some(parent);
+        }
+        if let Some(topmost) = topmost {
+            parents.insert(id.clone(), topmost);
+        }
+    }
+
+    for product in products.values_mut() {
+        product.variant_of = None;
+        product.variants.clear();
+    }
+    for (id, parent) in &parents {
+        if let Some(product) = products.get_mut(id) {
+            product.variant_of = Some(parent.clone());
+        }
+        if let Some(product) = products.get_mut(parent) {
+            product.variants.insert(id.clone());
+        }
+    }
+
+    parents.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base_names() {
+        assert_eq!(base_name("iPhone 15 128 GB Black"), "iphone 15");
+        assert_eq!(base_name("Fairphone 5 (256GB, Black)"), "fairphone 5");
+        assert_eq!(base_name("Galaxy S24"), "galaxy s24");
+        assert_eq!(base_name("Red"), "");
+    }
+
+    fn product(id: usize, name: &str, category: &str) -> gather::Product {
+        gather::Product {
+            db_key: gather::ProductId::from_value(id),
+            names: BTreeSet::from([gather::Text {
+                text: name.to_owned(),
+                source: gather::Source::Wikidata,
+            }]),
+            categories: BTreeSet::from([category.to_owned()]),
+            manufacturer_ids: BTreeSet::from([gather::OrganisationId::from_value(1)]),
+            ..gather::Product::default()
+        }
+    }
+
+    #[test]
+    fn grouping_only_electronics() {
+        let mut products: BTreeMap<_, _> = [
+            product(1, "Fairphone 5", "smartphone"),
+            product(2, "Fairphone 5 256GB Black", "smartphone"),
+            product(3, "Tea", "food"),
+            product(4, "Green Tea", "food"),
+        ]
+        .into_iter()
+        .map(|product| (product.db_key.clone(), product))
+        .collect();
+
+        assert_eq!(group(&mut products), 1);
+        let parent = gather::ProductId::from_value(1);
+        assert_eq!(products[&gather::ProductId::from_value(2)].variant_of, Some(parent.clone()));
+        assert_eq!(products[&parent].variants.len(), 1);
+        assert_eq!(products[&gather::ProductId::from_value(4)].variant_of, None);
+    }
+}
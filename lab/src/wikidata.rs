@@ -1,7 +1,11 @@
 //! Extensions for types from `sustainity_wikidata` crate.
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 
+use sustainity_models::{
+    attributes,
+    gather::{AttributeValue, Attributes, ExternalIdKind, SocialLink, SocialPlatform},
+};
 use sustainity_wikidata::{data, errors, properties};
 
 pub use sustainity_wikidata::data::Id as WikiId;
@@ -644,9 +648,21 @@ pub trait ItemExt {
         property_id: &str,
     ) -> Result<Option<Vec<data::Id>>, errors::ParseIdError>;
 
+    /// Returns IDs of entities associated with the passed property.
+    ///
+    /// Unlike `get_entity_ids`, malformed IDs are logged and skipped instead of failing.
+    #[must_use]
+    fn get_valid_entity_ids(&self, property_id: &str) -> Vec<data::Id>;
+
     /// Returns strings associated with the passed property.
     fn get_strings(&self, property_id: &str) -> Option<Vec<String>>;
 
+    /// Returns quantities associated with the passed property.
+    ///
+    /// Each quantity is returned with the Wikidata ID of its unit (empty if it has no unit).
+    #[must_use]
+    fn get_quantities(&self, property_id: &str) -> Vec<(f64, String)>;
+
     /// Checks if has at least one passed property.
     fn has_property(&self, property_id: &str) -> bool;
 
@@ -659,9 +675,28 @@ pub trait ItemExt {
     /// Returns IDs of entities linked with "followed by" property.
     fn get_followed_by(&self) -> Result<Option<Vec<data::Id>>, errors::ParseIdError>;
 
+    /// Returns IDs of entities linked with "award received" property together with the dates the
+    /// awards were received (if known).
+    ///
+    /// Malformed IDs are logged and skipped.
+    #[must_use]
+    fn get_awards(&self) -> Vec<(data::Id, Option<String>)>;
+
+    /// Returns IDs of entities linked with "edition or translation of" property.
+    fn get_edition_of(&self) -> Result<Option<Vec<data::Id>>, errors::ParseIdError>;
+
+    /// Returns IDs of entities linked with "has characteristic" or "has part(s)" properties.
+    ///
+    /// Malformed IDs are logged and skipped.
+    #[must_use]
+    fn get_characteristics(&self) -> Vec<data::Id>;
+
     /// Returns IDs of entities linked with "manufacturer" property.
     fn get_manufacturer_ids(&self) -> Result<Option<Vec<data::Id>>, errors::ParseIdError>;
 
+    /// Returns IDs of entities linked with "parent organization" property.
+    fn get_parent_organisation_ids(&self) -> Result<Option<Vec<data::Id>>, errors::ParseIdError>;
+
     /// Checks if has any entities linked with "manufacturer" property.
     #[must_use]
     fn has_manufacturer(&self) -> bool;
@@ -690,6 +725,20 @@ pub trait ItemExt {
     #[must_use]
     fn has_logo_image(&self) -> bool;
 
+    /// Returns links to the profiles on social media (X, Facebook and LinkedIn).
+    #[must_use]
+    fn get_social_links(&self) -> Vec<SocialLink>;
+
+    /// Returns product attributes (mass and battery capacity).
+    #[must_use]
+    fn get_attributes(&self) -> Attributes;
+
+    /// Returns identifiers of the product in online shops (e.g. ASINs).
+    ///
+    /// Invalid identifiers are skipped.
+    #[must_use]
+    fn get_external_ids(&self) -> BTreeMap<ExternalIdKind, Vec<String>>;
+
     /// Checks if this items is linked to the passed entity with `instalce of` property.
     ///
     /// In simpler words: chacks if this item is an instance of the passed class.
@@ -722,6 +771,12 @@ pub trait ItemExt {
     #[must_use]
     fn has_eu_vat_number(&self) -> bool;
 
+    /// Returns the Legal Entity Identifiers (in the canonical upper-case form).
+    ///
+    /// Invalid identifiers are skipped.
+    #[must_use]
+    fn get_leis(&self) -> Vec<String>;
+
     /// Checks if this item can be clasified as an organisation.
     #[must_use]
     fn is_organisation(&self) -> bool;
@@ -729,6 +784,77 @@ pub trait ItemExt {
     /// Extracts internet domains from website addresses.
     #[must_use]
     fn extract_domains(&self) -> Option<HashSet<String>>;
+
+    /// Returns IDs of entities linked with "country" property.
+    fn get_country_ids(&self) -> Result<Option<Vec<data::Id>>, errors::ParseIdError>;
+
+    /// Returns strings associated with the "ISO 3166-1 alpha-2 code" property.
+    #[must_use]
+    fn get_iso_country_codes(&self) -> Option<Vec<String>>;
+}
+
+/// Wikidata precision of time values known to a day.
+const PRECISION_DAY: i64 = 11;
+
+/// Wikidata precision of time values known to a year.
+const PRECISION_YEAR: i64 = 9;
+
+/// Returns statements of the passed property linking to entities, with the IDs of the entities.
+///
+/// Malformed IDs are logged and skipped.
+fn valid_entity_statements<'a>(
+    item: &'a data::Item,
+    property_id: &str,
+) -> Vec<(data::Id, &'a data::Statement)> {
+    let mut result = Vec::new();
+    for claim in item.claims.get(property_id).into_iter().flatten() {
+        let data::Claim::Statement(statement) = claim;
+        if let data::Snak::Value(value) = &statement.mainsnak {
+            if let data::DataValue::WikibaseEntityId(data::EntityIdDataValue::Item(entity_info)) =
+                &value.datavalue
+            {
+                match entity_info.id.to_num_id() {
+                    Ok(id) => result.push((id, statement)),
+                    Err(err) => log::warn!(
+                        "Item {:?} has invalid {property_id} value {:?}: {err}",
+                        item.id,
+                        entity_info.id,
+                    ),
+                }
+            }
+        }
+    }
+    result
+}
+
+/// Converts a Wikidata time value to a date.
+///
+/// The date is in the `YYYY-MM-DD` form if the day is known, otherwise just the year is returned.
+/// Values less precise than a year and years before the common era give `None`.
+fn time_to_date(time: &data::TimeDataValue) -> Option<String> {
+    let (date, _time) = time.time.strip_prefix('+')?.split_once('T')?;
+    if time.precision >= PRECISION_DAY {
+        Some(date.to_owned())
+    } else if time.precision >= PRECISION_YEAR {
+        date.split('-').next().map(str::to_owned)
+    } else {
+        None
+    }
+}
+
+/// Returns the date from the "point in time" qualifier of the statement.
+fn point_in_time(statement: &data::Statement) -> Option<String> {
+    let (_, snaks) = statement
+        .qualifiers
+        .as_ref()?
+        .iter()
+        .find(|(id, _)| id.as_str() == properties::POINT_IN_TIME)?;
+    snaks.iter().find_map(|snak| match snak {
+        data::Snak::Value(data::Value { datavalue: data::DataValue::Time(time), .. }) => {
+            time_to_date(time)
+        }
+        _ => None,
+    })
 }
 
 impl ItemExt for data::Item {
@@ -786,6 +912,10 @@ impl ItemExt for data::Item {
         }
     }
 
+    fn get_valid_entity_ids(&self, property_id: &str) -> Vec<data::Id> {
+        valid_entity_statements(self, property_id).into_iter().map(|(id, _)| id).collect()
+    }
+
     fn relates(&self, property: &str, class: &str) -> bool {
         if let Some(claims) = self.claims.get(property) {
             for claim in claims {
@@ -829,6 +959,32 @@ impl ItemExt for data::Item {
         }
     }
 
+    #[must_use]
+    fn get_quantities(&self, property_id: &str) -> Vec<(f64, String)> {
+        let mut result = Vec::new();
+        for claim in self.claims.get(property_id).map(Vec::as_slice).unwrap_or_default() {
+            let data::Claim::Statement(statement) = claim;
+            if let data::Snak::Value(value) = &statement.mainsnak {
+                if let data::DataValue::Quantity(quantity) = &value.datavalue {
+                    if let Ok(amount) = quantity.amount.trim_start_matches('+').parse::<f64>() {
+                        let unit = quantity.unit.rsplit('/').next().unwrap_or_default();
+                        let unit =
+                            if unit.starts_with('Q') { unit.to_owned() } else { String::new() };
+                        result.push((amount, unit));
+                    }
+                } else {
+                    log::warn!(
+                        "Item {:?} has properties {} which are not quantities: {:?}",
+                        self.id,
+                        property_id,
+                        value
+                    );
+                }
+            }
+        }
+        result
+    }
+
     fn has_property(&self, property_id: &str) -> bool {
         if let Some(claims) = self.claims.get(property_id) {
             !claims.is_empty()
@@ -845,10 +1001,31 @@ impl ItemExt for data::Item {
         self.get_entity_ids(properties::FOLLOWED_BY)
     }
 
+    fn get_awards(&self) -> Vec<(data::Id, Option<String>)> {
+        valid_entity_statements(self, properties::AWARD_RECEIVED)
+            .into_iter()
+            .map(|(id, statement)| (id, point_in_time(statement)))
+            .collect()
+    }
+
+    fn get_edition_of(&self) -> Result<Option<Vec<data::Id>>, errors::ParseIdError> {
+        self.get_entity_ids(properties::EDITION_OR_TRANSLATION_OF)
+    }
+
+    fn get_characteristics(&self) -> Vec<data::Id> {
+        let mut result = self.get_valid_entity_ids(properties::HAS_CHARACTERISTIC);
+        result.extend(self.get_valid_entity_ids(properties::HAS_PARTS));
+        result
+    }
+
     fn get_manufacturer_ids(&self) -> Result<Option<Vec<data::Id>>, errors::ParseIdError> {
         self.get_entity_ids(properties::MANUFACTURER)
     }
 
+    fn get_parent_organisation_ids(&self) -> Result<Option<Vec<data::Id>>, errors::ParseIdError> {
+        self.get_entity_ids(properties::PARENT_ORGANIZATION)
+    }
+
     #[must_use]
     fn has_manufacturer(&self) -> bool {
         self.has_property(properties::MANUFACTURER)
@@ -884,6 +1061,59 @@ impl ItemExt for data::Item {
         self.has_property(properties::LOGO_IMAGE)
     }
 
+    #[must_use]
+    fn get_social_links(&self) -> Vec<SocialLink> {
+        let mut result = Vec::new();
+        for (property, platform) in [
+            (properties::X_USERNAME, SocialPlatform::X),
+            (properties::FACEBOOK_USERNAME, SocialPlatform::Facebook),
+            (properties::LINKEDIN_COMPANY_ID, SocialPlatform::LinkedIn),
+        ] {
+            for handle in self.get_strings(property).unwrap_or_default() {
+                result.push(SocialLink::new(platform, &handle));
+            }
+        }
+        result
+    }
+
+    #[must_use]
+    fn get_external_ids(&self) -> BTreeMap<ExternalIdKind, Vec<String>> {
+        let mut result = BTreeMap::new();
+        for (kind, property_id) in [(ExternalIdKind::Asin, properties::ASIN)] {
+            let mut ids = Vec::new();
+            for id in self.get_strings(property_id).unwrap_or_default() {
+                match kind.normalize(&id) {
+                    Some(id) => ids.push(id),
+                    None => log::warn!("Item {:?} has invalid {} {id:?}", self.id, kind.as_str()),
+                }
+            }
+            if !ids.is_empty() {
+                result.insert(kind, ids);
+            }
+        }
+        result
+    }
+
+    #[must_use]
+    fn get_attributes(&self) -> Attributes {
+        let mut result = Attributes::new();
+        if let Some(mass) = self
+            .get_quantities(properties::MASS)
+            .into_iter()
+            .find_map(|(amount, unit)| attributes::mass_in_kg(amount, &unit))
+        {
+            result.insert(attributes::MASS_KG.to_owned(), AttributeValue::Number(mass));
+        }
+        if let Some((capacity, unit)) =
+            self.get_quantities(properties::ENERGY_STORAGE_CAPACITY).into_iter().next()
+        {
+            result
+                .insert(attributes::BATTERY_CAPACITY.to_owned(), AttributeValue::Number(capacity));
+            result.insert(attributes::BATTERY_CAPACITY_UNIT.to_owned(), AttributeValue::Text(unit));
+        }
+        result
+    }
+
     #[must_use]
     fn is_instance_of(&self, class: &str) -> bool {
         self.relates(properties::INSTANCE_OF, class)
@@ -922,6 +1152,20 @@ impl ItemExt for data::Item {
         self.has_property(properties::EU_VAT_NUMBER)
     }
 
+    #[must_use]
+    fn get_leis(&self) -> Vec<String> {
+        let mut result = Vec::new();
+        for lei in self.get_strings(properties::LEI).unwrap_or_default() {
+            let lei = lei.trim().to_uppercase();
+            if lei.len() == 20 && lei.chars().all(|c| c.is_ascii_alphanumeric()) {
+                result.push(lei);
+            } else {
+                log::warn!("Item {:?} has invalid LEI {lei:?}", self.id);
+            }
+        }
+        result
+    }
+
     #[must_use]
     fn is_organisation(&self) -> bool {
         if self.has_eu_vat_number() {
@@ -947,4 +1191,91 @@ impl ItemExt for data::Item {
     fn extract_domains(&self) -> Option<HashSet<String>> {
         self.get_official_websites().map(|u| utils::extract_domains_from_urls(&u))
     }
+
+    fn get_country_ids(&self) -> Result<Option<Vec<data::Id>>, errors::ParseIdError> {
+        self.get_entity_ids(properties::COUNTRY)
+    }
+
+    #[must_use]
+    fn get_iso_country_codes(&self) -> Option<Vec<String>> {
+        self.get_strings(properties::ISO_3166_1_ALPHA_2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds an item with the given "award received" values and their "point in time" qualifiers
+    /// (time and precision).
+    fn item_with_awards(awards: &[(&str, Option<(&str, i64)>)]) -> data::Item {
+        let claims: Vec<serde_json::Value> = awards
+            .iter()
+            .map(|(id, time)| {
+                let mut claim = serde_json::json!({
+                    "type": "statement",
+                    "id": "x",
+                    "rank": "normal",
+                    "mainsnak": {
+                        "snaktype": "value",
+                        "property": "P166",
+                        "datavalue": {
+                            "type": "wikibase-entityid",
+                            "value": {"entity-type": "item", "id": id, "numeric-id": 0}
+                        }
+                    }
+                });
+                if let Some((time, precision)) = time {
+                    claim["qualifiers"] = serde_json::json!({"P585": [{
+                        "snaktype": "value",
+                        "property": "P585",
+                        "datavalue": {
+                            "type": "time",
+                            "value": {
+                                "time": time,
+                                "timezone": 0,
+                                "before": 0,
+                                "after": 0,
+                                "precision": precision,
+                                "calendarmodel": "http://www.wikidata.org/entity/Q1985727"
+                            }
+                        }
+                    }]});
+                }
+                claim
+            })
+            .collect();
+        serde_json::from_value(serde_json::json!({
+            "id": "Q1",
+            "lastrevid": 1,
+            "labels": {},
+            "descriptions": {},
+            "aliases": {},
+            "claims": {"P166": claims},
+            "sitelinks": {}
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn awards() {
+        assert!(item_with_awards(&[]).get_awards().is_empty());
+        assert_eq!(
+            item_with_awards(&[("Q123", None), ("Qx", None), ("Q456", None)]).get_awards(),
+            vec![(data::Id::new(123), None), (data::Id::new(456), None)]
+        );
+        assert_eq!(
+            item_with_awards(&[
+                ("Q1", Some(("+2021-03-15T00:00:00Z", 11))),
+                ("Q2", Some(("+2019-00-00T00:00:00Z", 9))),
+                ("Q3", Some(("+2000-00-00T00:00:00Z", 7))),
+            ])
+            .get_awards(),
+            vec![
+                (data::Id::new(1), Some("2021-03-15".to_owned())),
+                (data::Id::new(2), Some("2019".to_owned())),
+                (data::Id::new(3), None),
+            ]
+        );
+    }
 }
@@ -0,0 +1,573 @@
+//! Matching workbench: proposing Wikidata candidates for companies known only by name.
+//!
+//! The name-to-Wikidata matches (`matches.yaml`) are curated by hand. This module helps with the
+//! curation: it collects the company names from BCorp, EU Ecolabel and Fashion Transparency Index
+//! data (and the companies of products requested by the users) which are not matched yet and ranks
+//! organisations from Wikidata by how well they fit.
+//!
+//! The rank is based on the similarity of the name to the labels and aliases of the item, with
+//! bonuses for a shared website domain and for the same country. As in `matching::NameIndex`, an
+//! item is compared only with the companies whose names start like one of its labels (or which
+//! share a domain with it), so that not all pairs need to be compared.
+//!
+//! The resulting candidates file has the same layout as the matches file (with extra details for
+//! the reviewer), so once the wrong candidates are removed its entries can be copied to the matches
+//! file directly.
+
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use merge::Merge;
+use serde::Serialize;
+
+use sustainity_collecting::{
+    bcorp, errors::MapSerde, eu_ecolabel, fashion_transparency_index, sustainity,
+};
+use sustainity_models::product_requests::ProductRequest;
+use sustainity_wikidata::data::{Entity, Id, Item};
+
+use crate::{
+    config, entities, errors, matching, outputs, parallel, runners, sources::Sourceable, utils,
+    wikidata::ItemExt,
+};
+
+/// Minimal name similarity for an item to be considered a candidate (unless the domains match).
+const MIN_NAME_SIMILARITY: f64 = 0.75;
+
+/// Bonus for a website domain shared by the company and the item.
+const DOMAIN_BONUS: f64 = 0.5;
+
+/// Bonus for the company and the item being registered in the same country.
+const COUNTRY_BONUS: f64 = 0.1;
+
+/// Company to be matched.
+#[derive(Clone, Debug, Default)]
+struct Subject {
+    /// Name of the company (as first found in the data).
+    name: String,
+
+    /// Normalised name of the company.
+    key: String,
+
+    /// Data sources listing the company.
+    sources: BTreeSet<&'static str>,
+
+    /// Domains of the company websites.
+    domains: HashSet<String>,
+
+    /// ISO 3166-1 alpha-2 codes of the countries of the company.
+    countries: HashSet<String>,
+
+    /// GTINs of the products of the company requested by the users.
+    gtins: BTreeSet<String>,
+}
+
+/// Proposed Wikidata item for a subject.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Candidate {
+    /// Wikidata ID of the item.
+    #[serde(serialize_with = "serialize_id")]
+    pub id: Id,
+
+    /// Label of the item.
+    pub label: String,
+
+    /// Highest similarity of the subject name to the labels and aliases of the item.
+    pub name_similarity: f64,
+
+    /// Whether the company and the item share a website domain.
+    pub domain_match: bool,
+
+    /// Whether the company and the item are registered in the same country.
+    pub country_match: bool,
+
+    /// Final score used for ranking.
+    pub score: f64,
+
+    /// IDs of the countries of the item (resolved to codes when saving).
+    #[serde(skip)]
+    country_ids: Vec<Id>,
+}
+
+fn serialize_id<S: serde::Serializer>(id: &Id, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(id.to_str_id().as_str())
+}
+
+impl Candidate {
+    fn base_score(&self) -> f64 {
+        self.name_similarity + if self.domain_match { DOMAIN_BONUS } else { 0.0 }
+    }
+
+    fn rescore(&mut self, subject: &Subject, country_codes: &HashMap<Id, String>) {
+        self.country_match = self
+            .country_ids
+            .iter()
+            .filter_map(|id| country_codes.get(id))
+            .any(|code| subject.countries.contains(code));
+        self.score = self.base_score() + if self.country_match { COUNTRY_BONUS } else { 0.0 };
+    }
+}
+
+/// Entry of the candidates file.
+///
+/// The `name`, `ids` and `similarity` fields mirror `sustainity::data::NameMatching`.
+#[derive(Serialize, Clone, Debug)]
+pub struct CandidateEntry {
+    /// Company name.
+    pub name: String,
+
+    /// Wikidata IDs of the candidates, best first.
+    pub ids: Vec<String>,
+
+    /// Score of the best candidate (capped at 1).
+    pub similarity: f64,
+
+    /// Data sources listing the company.
+    pub sources: Vec<&'static str>,
+
+    /// GTINs of the products of the company requested by the users, so that the GS1 prefix of the
+    /// company can be added once it's matched.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub gtins: Vec<String>,
+
+    /// Details of the candidates.
+    pub candidates: Vec<Candidate>,
+}
+
+/// Ranks the candidates and keeps the best ones.
+fn rank(candidates: &mut Vec<Candidate>, limit: usize) {
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.id.cmp(&b.id)));
+    candidates.dedup_by(|a, b| a.id == b.id);
+    candidates.truncate(limit);
+}
+
+/// Evaluates how well the item fits the subject.
+///
+/// `labels` are the normalised labels and aliases of the item.
+fn evaluate(
+    subject: &Subject,
+    item: &Item,
+    labels: &[String],
+    item_domains: &HashSet<String>,
+) -> Option<Candidate> {
+    let name_similarity =
+        labels.iter().map(|l| matching::normalized_similarity(&subject.key, l)).fold(0.0, f64::max);
+    let domain_match = !subject.domains.is_disjoint(item_domains);
+    if name_similarity < MIN_NAME_SIMILARITY && !domain_match {
+        return None;
+    }
+
+    let mut candidate = Candidate {
+        id: item.id,
+        label: item.get_labels().first().map(|label| (*label).to_owned()).unwrap_or_default(),
+        name_similarity,
+        domain_match,
+        country_match: false,
+        score: 0.0,
+        country_ids: item.get_country_ids().ok().flatten().unwrap_or_default(),
+    };
+    candidate.score = candidate.base_score();
+    Some(candidate)
+}
+
+/// Companies to be matched, indexed for limiting which of them are compared with an item.
+#[derive(Debug, Default)]
+struct SubjectIndex {
+    list: Vec<Subject>,
+
+    /// Indices of the subjects by the blocks of their names (see `matching::block_of`).
+    by_block: HashMap<String, Vec<usize>>,
+
+    /// Indices of the subjects by their website domains.
+    by_domain: HashMap<String, Vec<usize>>,
+}
+
+impl SubjectIndex {
+    fn new(list: Vec<Subject>) -> Self {
+        let mut by_block = HashMap::<String, Vec<usize>>::new();
+        let mut by_domain = HashMap::<String, Vec<usize>>::new();
+        for (index, subject) in list.iter().enumerate() {
+            by_block.entry(matching::block_of(&subject.key)).or_default().push(index);
+            for domain in &subject.domains {
+                by_domain.entry(domain.clone()).or_default().push(index);
+            }
+        }
+        Self { list, by_block, by_domain }
+    }
+
+    /// Returns indices of the subjects which may fit an item with the given labels and domains.
+    fn candidates(&self, labels: &[String], domains: &HashSet<String>) -> BTreeSet<usize> {
+        labels
+            .iter()
+            .filter_map(|label| self.by_block.get(&matching::block_of(label)))
+            .chain(domains.iter().filter_map(|domain| self.by_domain.get(domain)))
+            .flatten()
+            .copied()
+            .collect()
+    }
+}
+
+/// Holds the companies to be matched.
+#[derive(Debug)]
+pub struct MatchSources {
+    subjects: Vec<Subject>,
+}
+
+impl MatchSources {
+    fn subject<'a>(subjects: &'a mut HashMap<String, Subject>, name: &str) -> &'a mut Subject {
+        let key = matching::normalize(name);
+        subjects.entry(key.clone()).or_insert_with(|| Subject {
+            name: name.trim().to_owned(),
+            key,
+            ..Subject::default()
+        })
+    }
+
+    /// Adds the companies of the products requested by the users.
+    ///
+    /// The company is known by the brand or, if it wasn't given, by the product name, which usually
+    /// starts with the brand. Returns the number of requests with neither of them (only a GTIN),
+    /// which can't be matched by name.
+    fn add_requests(
+        subjects: &mut HashMap<String, Subject>,
+        requests: impl IntoIterator<Item = ProductRequest>,
+    ) -> usize {
+        let mut unnamed = 0;
+        for request in requests {
+            let submission = request.submission;
+            let Some(name) = submission.brand.as_ref().or(submission.name.as_ref()) else {
+                unnamed += 1;
+                continue;
+            };
+            let subject = Self::subject(subjects, name);
+            subject.sources.insert("requests");
+            subject.gtins.extend(submission.gtin);
+        }
+        unnamed
+    }
+}
+
+impl Sourceable for MatchSources {
+    type Config = config::MatchConfig;
+
+    fn load(config: &Self::Config) -> Result<Self, errors::ProcessingError> {
+        let matched: HashSet<String> = sustainity::reader::parse_id_map(&config.match_path)?
+            .into_iter()
+            .filter(|entry| entry.matched().is_some())
+            .map(|entry| matching::normalize(&entry.name))
+            .collect();
+
+        let mut subjects = HashMap::<String, Subject>::new();
+        for record in bcorp::reader::parse(&config.bcorp_path)? {
+            let subject = Self::subject(&mut subjects, &record.company_name);
+            subject.sources.insert("bcorp");
+            if !record.website.is_empty() {
+                subject.domains.insert(utils::extract_domain_from_url(&record.website));
+            }
+        }
+        for record in eu_ecolabel::reader::parse(&config.eu_ecolabel_path)? {
+            let subject = Self::subject(&mut subjects, &record.company_name);
+            subject.sources.insert("eu_ecolabel");
+            subject.countries.insert(record.company_country.to_uppercase());
+        }
+        for entry in fashion_transparency_index::reader::parse(&config.fti_path)? {
+            if entry.wikidata_id.is_none() {
+                Self::subject(&mut subjects, &entry.name).sources.insert("fti");
+            }
+        }
+        if let Some(requests_path) = &config.requests_path {
+            let requests = outputs::read_json_lines::<ProductRequest>(requests_path)?
+                .collect::<Result<Vec<_>, _>>()?;
+            let unnamed = Self::add_requests(&mut subjects, requests);
+            if unnamed > 0 {
+                log::info!("Skipped {unnamed} product requests with only a GTIN");
+            }
+        }
+
+        let all = subjects.len();
+        subjects.retain(|key, _| !key.is_empty() && !matched.contains(key));
+        log::info!("Found {all} companies, {} of them not matched yet", subjects.len());
+
+        let mut subjects: Vec<Subject> = subjects.into_values().collect();
+        subjects.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(Self { subjects })
+    }
+}
+
+/// Data storage for gathered data.
+///
+/// Allows merging different instances.
+#[derive(Debug, Clone, Default)]
+pub struct MatchCollector {
+    /// Candidates by the index of the subject.
+    candidates: HashMap<usize, Vec<Candidate>>,
+
+    /// ISO country codes by Wikidata IDs of the countries.
+    country_codes: HashMap<Id, String>,
+}
+
+impl merge::Merge for MatchCollector {
+    fn merge(&mut self, other: Self) {
+        for (index, candidates) in other.candidates {
+            self.candidates.entry(index).or_default().extend(candidates);
+        }
+        self.country_codes.extend(other.country_codes);
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MatchWorker {
+    subjects: Arc<SubjectIndex>,
+    limit: usize,
+    collector: MatchCollector,
+}
+
+impl MatchWorker {
+    #[must_use]
+    pub fn new(sources: MatchSources, limit: usize) -> Self {
+        let subjects = Arc::new(SubjectIndex::new(sources.subjects));
+        Self { subjects, limit, collector: MatchCollector::default() }
+    }
+
+    /// Collects the country code of the item or evaluates it as a candidate for all subjects.
+    fn inspect(&mut self, item: &Item) {
+        if let Some(codes) = item.get_iso_country_codes() {
+            if let Some(code) = codes.into_iter().next() {
+                self.collector.country_codes.insert(item.id, code.to_uppercase());
+            }
+        }
+        if item.is_organisation() {
+            let domains = item.extract_domains().unwrap_or_default();
+            let labels: Vec<String> = item
+                .get_all_labels_and_aliases()
+                .iter()
+                .map(|label| matching::normalize(label))
+                .collect();
+            for index in self.subjects.candidates(&labels, &domains) {
+                let subject = &self.subjects.list[index];
+                if let Some(candidate) = evaluate(subject, item, &labels, &domains) {
+                    let candidates = self.collector.candidates.entry(index).or_default();
+                    candidates.push(candidate);
+                    if candidates.len() > 2 * self.limit {
+                        rank(candidates, self.limit);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl runners::WikidataWorker for MatchWorker {
+    type Output = MatchCollector;
+
+    async fn process(
+        &mut self,
+        _msg: &str,
+        entity: Entity,
+        _tx: parallel::Sender<Self::Output>,
+    ) -> Result<(), errors::ProcessingError> {
+        if let Entity::Item(item) = entity {
+            self.inspect(&item);
+        }
+        Ok(())
+    }
+
+    async fn finish(
+        self,
+        tx: parallel::Sender<Self::Output>,
+    ) -> Result<(), errors::ProcessingError> {
+        tx.send(self.collector).await;
+        Ok(())
+    }
+}
+
+pub struct MatchStash {
+    subjects: Arc<SubjectIndex>,
+    collector: MatchCollector,
+    config: config::MatchConfig,
+}
+
+impl MatchStash {
+    #[must_use]
+    pub fn new(worker: &MatchWorker, config: config::MatchConfig) -> Self {
+        Self { subjects: worker.subjects.clone(), collector: MatchCollector::default(), config }
+    }
+}
+
+#[async_trait]
+impl runners::Stash for MatchStash {
+    type Input = MatchCollector;
+
+    fn stash(&mut self, input: Self::Input) -> Result<(), errors::ProcessingError> {
+        self.collector.merge(input);
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<(), errors::ProcessingError> {
+        log::info!("Saving match candidates");
+
+        let mut entries = Vec::new();
+        for (index, subject) in self.subjects.list.iter().enumerate() {
+            let Some(mut candidates) = self.collector.candidates.remove(&index) else {
+                continue;
+            };
+            for candidate in &mut candidates {
+                candidate.rescore(subject, &self.collector.country_codes);
+            }
+            rank(&mut candidates, self.config.limit);
+            entries.push(CandidateEntry {
+                name: subject.name.clone(),
+                ids: candidates.iter().map(|c| c.id.to_str_id().as_str().to_owned()).collect(),
+                similarity: candidates.first().map_or(0.0, |c| c.score.min(1.0)),
+                sources: subject.sources.iter().copied().collect(),
+                gtins: subject.gtins.iter().cloned().collect(),
+                candidates,
+            });
+        }
+        let num_subjects = self.subjects.list.len();
+        log::info!(" - found candidates for {} / {num_subjects} names", entries.len());
+
+        let contents = serde_yaml::to_string(&entries).map_serde()?;
+        std::fs::write(&self.config.output_path, contents)?;
+
+        Ok(())
+    }
+}
+
+pub struct MatchRunner;
+
+impl MatchRunner {
+    pub async fn run(config: &config::MatchConfig) -> Result<(), errors::ProcessingError> {
+        let sources = MatchSources::load(config)?;
+        let worker = MatchWorker::new(sources, config.limit);
+        let mut stash = MatchStash::new(&worker, config.clone());
+
+        // Items missing in the dump are fetched from the API and evaluated up front.
+        if !config.fetch_ids.is_empty() {
+            let fetcher = entities::EntityFetcher::new(config.cache_path.as_deref());
+            let mut extra = worker.clone();
+            for id in entities::parse_ids(&config.fetch_ids)? {
+                if let Entity::Item(item) = fetcher.fetch(id).await? {
+                    extra.inspect(&item);
+                }
+            }
+            log::info!("Evaluated {} items fetched from the API", config.fetch_ids.len());
+            stash.collector.merge(extra.collector);
+        }
+
+        let flow = parallel::Flow::new();
+        runners::WikidataRunner::flow(flow, config, worker, stash)?.join();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(id: u64, name_similarity: f64, domain_match: bool) -> Candidate {
+        let mut candidate = Candidate {
+            id: Id::from(id),
+            label: String::new(),
+            name_similarity,
+            domain_match,
+            country_match: false,
+            score: 0.0,
+            country_ids: vec![Id::new(183)],
+        };
+        candidate.score = candidate.base_score();
+        candidate
+    }
+
+    #[test]
+    fn ranking() {
+        let subject = Subject {
+            name: "ACME".to_owned(),
+            key: "acme".to_owned(),
+            countries: HashSet::from(["DE".to_owned()]),
+            ..Subject::default()
+        };
+        let country_codes = HashMap::from([(Id::new(183), "DE".to_owned())]);
+
+        let mut candidates =
+            vec![candidate(1, 0.9, false), candidate(2, 0.8, true), candidate(1, 0.9, false)];
+        for candidate in &mut candidates {
+            candidate.rescore(&subject, &country_codes);
+        }
+        rank(&mut candidates, 5);
+
+        let ids: Vec<Id> = candidates.iter().map(|c| c.id).collect();
+        assert_eq!(ids, vec![Id::new(2), Id::new(1)]);
+        assert!(candidates.iter().all(|c| c.country_match));
+        assert!((candidates[0].score - 1.4).abs() < 1e-9);
+    }
+
+    #[test]
+    fn blocking() {
+        let subject = |name: &str, domain: Option<&str>| Subject {
+            name: name.to_owned(),
+            key: matching::normalize(name),
+            domains: domain.into_iter().map(str::to_owned).collect(),
+            ..Subject::default()
+        };
+        let index = SubjectIndex::new(vec![
+            subject("Acme Foods", None),
+            subject("Globex", Some("globex.com")),
+            subject("Acmex", None),
+        ]);
+
+        let labels = vec!["acme".to_owned()];
+        assert_eq!(index.candidates(&labels, &HashSet::new()), BTreeSet::from([0, 2]));
+
+        let domains = HashSet::from(["globex.com".to_owned()]);
+        assert_eq!(index.candidates(&labels, &domains), BTreeSet::from([0, 1, 2]));
+        assert!(index.candidates(&["initech".to_owned()], &HashSet::new()).is_empty());
+    }
+
+    #[test]
+    fn requests() {
+        use sustainity_models::product_requests::ProductRequestSubmission;
+
+        let request =
+            |gtin: Option<&str>, name: Option<&str>, brand: Option<&str>| ProductRequest {
+                db_key: gtin.map(str::to_owned),
+                submission: ProductRequestSubmission {
+                    gtin: gtin.map(str::to_owned),
+                    name: name.map(str::to_owned),
+                    brand: brand.map(str::to_owned),
+                    photo_url: None,
+                },
+                count: 1,
+                created: String::new(),
+                updated: String::new(),
+            };
+
+        let mut subjects = HashMap::new();
+        let unnamed = MatchSources::add_requests(
+            &mut subjects,
+            [
+                request(Some("08712345000011"), Some("Phone 5"), Some("Fairphone")),
+                request(Some("08712345000028"), None, Some("fairphone")),
+                request(None, Some("Teracube 2e"), None),
+                request(Some("04006381333931"), None, None),
+            ],
+        );
+        assert_eq!(unnamed, 1);
+
+        let mut keys: Vec<&String> = subjects.keys().collect();
+        keys.sort();
+        assert_eq!(keys, [&matching::normalize("Fairphone"), &matching::normalize("Teracube 2e")]);
+        let fairphone = &subjects[&matching::normalize("Fairphone")];
+        assert_eq!(fairphone.name, "Fairphone");
+        assert_eq!(
+            fairphone.gtins,
+            BTreeSet::from(["08712345000011".to_owned(), "08712345000028".to_owned()])
+        );
+        assert!(subjects.values().all(|s| s.sources.contains("requests")));
+    }
+}
@@ -0,0 +1,139 @@
+//! Generic attributes of products and organisations.
+//!
+//! Attributes hold structured facts which are relevant only for some kinds of products (e.g. mass
+//! or battery capacity of electronic devices) or organisations (e.g. companies obliged to publish
+//! sustainability reports) and so don't deserve a dedicated field. The known keys are listed in
+//! this module.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Mass of the product in kilograms.
+pub const MASS_KG: &str = "mass_kg";
+
+/// Energy storage capacity of the battery (in the unit given by `BATTERY_CAPACITY_UNIT`).
+pub const BATTERY_CAPACITY: &str = "battery_capacity";
+
+/// Unit of the battery capacity (Wikidata ID of the unit, e.g. `Q794261` for watt-hour).
+pub const BATTERY_CAPACITY_UNIT: &str = "battery_capacity_unit";
+
+/// Whether the battery can be replaced by the user.
+pub const REPLACEABLE_BATTERY: &str = "replaceable_battery";
+
+/// Repairability score (from 0 to 10, as published by iFixit).
+pub const REPAIRABILITY_SCORE: &str = "repairability_score";
+
+/// Materials of the packaging, comma-separated (e.g. `glass,paper`).
+pub const PACKAGING_MATERIALS: &str = "packaging_materials";
+
+/// Whether the packaging is recyclable.
+pub const RECYCLABLE_PACKAGING: &str = "recyclable_packaging";
+
+/// Carbon footprint in kilograms of CO2 equivalent per 100 g of the product.
+pub const CARBON_FOOTPRINT: &str = "carbon_footprint_kg_co2e_per_100g";
+
+/// Provenance of the carbon footprint: `estimate` if computed from average coefficients rather
+/// than measured for the specific product.
+pub const CARBON_FOOTPRINT_PROVENANCE: &str = "carbon_footprint_provenance";
+
+/// What the carbon footprint estimate is based on (a category tag or `ingredients`).
+pub const CARBON_FOOTPRINT_BASIS: &str = "carbon_footprint_basis";
+
+/// Provenance of values estimated from average coefficients.
+pub const PROVENANCE_ESTIMATE: &str = "estimate";
+
+/// URL of the latest sustainability report published under the CSRD (ESRS).
+pub const CSRD_REPORT_URL: &str = "csrd_report_url";
+
+/// Reporting year of the latest sustainability report published under the CSRD (ESRS).
+pub const CSRD_REPORT_YEAR: &str = "csrd_report_year";
+
+/// Prefix of flags marking organisations using materials certified to a Textile Exchange
+/// standard, followed by the code of the standard (e.g. `material_standard_grs`).
+pub const MATERIAL_STANDARD_PREFIX: &str = "material_standard_";
+
+/// Value of an attribute.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[serde(untagged)]
+pub enum AttributeValue {
+    /// Yes/no attribute.
+    Flag(bool),
+
+    /// Numeric attribute.
+    Number(f64),
+
+    /// Textual attribute.
+    Text(String),
+}
+
+/// Attributes of a product, mapping keys to values.
+pub type Attributes = BTreeMap<String, AttributeValue>;
+
+/// Converts a mass to kilograms.
+///
+/// `unit` is the Wikidata ID of the unit. Returns `None` for unknown units.
+#[must_use]
+pub fn mass_in_kg(amount: f64, unit: &str) -> Option<f64> {
+    match unit {
+        "Q11570" => Some(amount),
+        "Q41803" => Some(amount / 1000.0),
+        _ => None,
+    }
+}
+
+/// Returns the key of the flag for the material standard with the given code.
+#[must_use]
+pub fn material_standard(code: &str) -> String {
+    format!("{MATERIAL_STANDARD_PREFIX}{code}")
+}
+
+/// Returns the codes of the material standards flagged in the attributes.
+#[must_use]
+pub fn material_standards(attributes: &Attributes) -> Vec<String> {
+    attributes
+        .iter()
+        .filter_map(|(key, value)| {
+            let code = key.strip_prefix(MATERIAL_STANDARD_PREFIX)?;
+            (*value == AttributeValue::Flag(true)).then(|| code.to_owned())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serde() {
+        let attributes = Attributes::from([
+            (MASS_KG.to_owned(), AttributeValue::Number(0.2)),
+            (BATTERY_CAPACITY_UNIT.to_owned(), AttributeValue::Text("Q794261".to_owned())),
+            (REPLACEABLE_BATTERY.to_owned(), AttributeValue::Flag(true)),
+        ]);
+        let json = serde_json::to_string(&attributes).unwrap();
+        assert_eq!(
+            json,
+            r#"{"battery_capacity_unit":"Q794261","mass_kg":0.2,"replaceable_battery":true}"#
+        );
+        assert_eq!(serde_json::from_str::<Attributes>(&json).unwrap(), attributes);
+    }
+
+    #[test]
+    fn mass() {
+        assert_eq!(mass_in_kg(150.0, "Q41803"), Some(0.15));
+        assert_eq!(mass_in_kg(1.5, "Q11570"), Some(1.5));
+        assert_eq!(mass_in_kg(1.5, "Q100995"), None);
+    }
+
+    #[test]
+    fn material_standards_from_flags() {
+        let attributes = Attributes::from([
+            (material_standard("grs"), AttributeValue::Flag(true)),
+            (material_standard("rws"), AttributeValue::Flag(false)),
+            (CSRD_REPORT_YEAR.to_owned(), AttributeValue::Number(2024.0)),
+        ]);
+        assert_eq!(material_standards(&attributes), vec!["grs".to_owned()]);
+    }
+}
@@ -0,0 +1,98 @@
+//! Changes between dataset versions.
+//!
+//! The lab compares two crystallized datasets and lists the entities which were added, removed or
+//! re-scored. The changes are kept by the backend across dataset reloads, so that clients can sync
+//! their local caches incrementally.
+
+use serde::{Deserialize, Serialize};
+
+/// Kind of the changed entity.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChangedEntity {
+    #[serde(rename = "organisation")]
+    Organisation,
+
+    #[serde(rename = "product")]
+    Product,
+}
+
+impl ChangedEntity {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Organisation => "organisation",
+            Self::Product => "product",
+        }
+    }
+}
+
+/// Kind of the change.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChangeKind {
+    /// The entity is new in this version.
+    #[serde(rename = "added")]
+    Added,
+
+    /// The entity is not present in this version anymore.
+    #[serde(rename = "removed")]
+    Removed,
+
+    /// The score (or certifications in case of organisations) of the entity changed.
+    #[serde(rename = "rescored")]
+    Rescored,
+}
+
+/// A single change of an entity between two dataset versions.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Change {
+    /// DB entry key.
+    #[serde(rename = "_key")]
+    pub db_key: String,
+
+    /// Version of the dataset in which the change appeared.
+    #[serde(rename = "version")]
+    pub version: String,
+
+    /// Version of the dataset the new one was compared to.
+    #[serde(rename = "previous_version")]
+    pub previous_version: String,
+
+    /// Time of the comparison (RFC 3339), used to order the changes.
+    #[serde(rename = "created")]
+    pub created: String,
+
+    /// Kind of the changed entity.
+    #[serde(rename = "entity")]
+    pub entity: ChangedEntity,
+
+    /// Variant of the entity ID, as used in the API paths.
+    #[serde(rename = "id_variant")]
+    pub id_variant: String,
+
+    /// ID of the entity.
+    #[serde(rename = "id")]
+    pub id: String,
+
+    /// Kind of the change.
+    #[serde(rename = "kind")]
+    pub kind: ChangeKind,
+
+    /// Score in the previous version (products only).
+    #[serde(rename = "previous_score", default, skip_serializing_if = "Option::is_none")]
+    pub previous_score: Option<f64>,
+
+    /// Score in the new version (products only).
+    #[serde(rename = "score", default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
+}
+
+impl Change {
+    /// Constructs a DB key unique for the entity in the given version.
+    ///
+    /// The IDs may contain characters which are not allowed in DB keys, so a digest is used.
+    #[must_use]
+    pub fn make_key(version: &str, entity: ChangedEntity, id_variant: &str, id: &str) -> String {
+        let input = format!("{version}\n{}\n{id_variant}\n{id}", entity.as_str());
+        format!("{:x}", md5::compute(input.as_bytes()))
+    }
+}
@@ -11,6 +11,7 @@ const PRODUCT_EANS_COLLECTION: &str = "product_eans";
 const PRODUCT_GTINS_COLLECTION: &str = "product_gtins";
 const PRODUCT_WIKI_IDS_COLLECTION: &str = "product_wiki_ids";
 const CATEGORIES_COLLECTION: &str = "categories";
+const PRODUCT_ALTERNATIVES_COLLECTION: &str = "product_alternatives";
 
 pub struct KeyId {
     pub key: String,
@@ -84,3 +85,11 @@ pub fn category(category: &str) -> KeyId {
     let id = format!("{}/{}", CATEGORIES_COLLECTION, key);
     KeyId { key, id }
 }
+
+/// Key of the precomputed alternatives of the product in the category and region.
+pub fn product_alternatives(product: &str, category: &str, region: Option<&str>) -> KeyId {
+    let digest = md5::compute(format!("{product}/{category}/{}", region.unwrap_or_default()));
+    let key = format!("{:x}", digest);
+    let id = format!("{}/{}", PRODUCT_ALTERNATIVES_COLLECTION, key);
+    KeyId { key, id }
+}
@@ -0,0 +1,86 @@
+//! Handling of calendar dates.
+//!
+//! Dates are stored as ISO 8601 strings (`YYYY-MM-DD`), which can be compared lexicographically.
+
+/// Number of seconds in a day.
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Converts number of days since the Unix epoch to a (year, month, day) triple.
+///
+/// See <http://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, u32::try_from(month).unwrap_or_default(), u32::try_from(day).unwrap_or_default())
+}
+
+/// Converts a (year, month, day) triple to number of days since the Unix epoch.
+///
+/// See <http://howardhinnant.github.io/date_algorithms.html#days_from_civil>.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = i64::from((month + 9) % 12);
+    let doy = (153 * mp + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Formats the time as a date.
+#[must_use]
+pub fn format(time: std::time::SystemTime) -> String {
+    let seconds =
+        time.duration_since(std::time::UNIX_EPOCH).map(|since| since.as_secs()).unwrap_or_default();
+    let days = i64::try_from(seconds / SECONDS_PER_DAY).unwrap_or_default();
+    let (year, month, day) = civil_from_days(days);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// Returns the current date.
+#[must_use]
+pub fn today() -> String {
+    format(std::time::SystemTime::now())
+}
+
+/// Parses a date in the `YYYY-MM-DD` form into number of days since the Unix epoch.
+#[must_use]
+pub fn parse(date: &str) -> Option<i64> {
+    let mut parts = date.splitn(3, '-');
+    let year = parts.next()?.parse::<i64>().ok()?;
+    let month = parts.next()?.parse::<u32>().ok()?;
+    let day = parts.next()?.parse::<u32>().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    Some(days_from_civil(year, month, day))
+}
+
+/// Returns number of days from `earlier` to `later` or `None` if any of them is not a valid date.
+#[must_use]
+pub fn days_between(earlier: &str, later: &str) -> Option<i64> {
+    Some(parse(later)? - parse(earlier)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversions() {
+        assert_eq!(format(std::time::UNIX_EPOCH), "1970-01-01");
+        let time = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_709_251_200);
+        assert_eq!(format(time), "2024-03-01");
+        assert_eq!(parse("2024-03-01"), Some(19_783));
+        assert_eq!(parse("2024-13-01"), None);
+        assert_eq!(parse("yesterday"), None);
+        assert_eq!(days_between("2023-12-31", "2024-03-01"), Some(61));
+    }
+}
@@ -0,0 +1,159 @@
+//! Corrections submitted by the users.
+//!
+//! Feedback is stored by the backend in a moderation queue. Accepted feedback can be exported and
+//! turned into matching overrides by the lab.
+
+use serde::{Deserialize, Serialize};
+
+/// Kind of the reported problem.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FeedbackKind {
+    /// The subject (a name or an ID) is matched to a wrong entity.
+    ///
+    /// The correction is the Wikidata ID of the correct entity.
+    #[serde(rename = "wrong_match")]
+    WrongMatch,
+
+    /// Some data about the subject (e.g. a certification) is wrong or outdated.
+    #[serde(rename = "wrong_data")]
+    WrongData,
+
+    /// Anything else.
+    #[serde(rename = "other")]
+    Other,
+}
+
+/// State of the feedback in the moderation workflow.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FeedbackStatus {
+    /// Waiting for moderation.
+    #[serde(rename = "pending")]
+    Pending,
+
+    /// Accepted by a moderator, waiting to be applied.
+    #[serde(rename = "accepted")]
+    Accepted,
+
+    /// Rejected by a moderator.
+    #[serde(rename = "rejected")]
+    Rejected,
+
+    /// Applied to the data.
+    #[serde(rename = "applied")]
+    Applied,
+}
+
+impl FeedbackStatus {
+    /// Returns the serialized name of the status.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Accepted => "accepted",
+            Self::Rejected => "rejected",
+            Self::Applied => "applied",
+        }
+    }
+
+    /// Checks if the feedback can be moved from this status to the other one.
+    #[must_use]
+    pub fn can_change_to(self, other: Self) -> bool {
+        matches!(
+            (self, other),
+            (Self::Pending, Self::Accepted | Self::Rejected)
+                | (Self::Accepted, Self::Rejected | Self::Applied)
+                | (Self::Rejected, Self::Pending)
+        )
+    }
+}
+
+/// Feedback as submitted by a user.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct FeedbackSubmission {
+    /// Kind of the reported problem.
+    #[serde(rename = "kind")]
+    pub kind: FeedbackKind,
+
+    /// Name or ID of the product or organisation the feedback is about.
+    #[serde(rename = "subject")]
+    pub subject: String,
+
+    /// Proposed correct value, if any.
+    #[serde(rename = "correction", default)]
+    pub correction: Option<String>,
+
+    /// Free-form description.
+    #[serde(rename = "message", default)]
+    pub message: String,
+}
+
+/// Feedback stored in the moderation queue.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Feedback {
+    /// DB entry key.
+    #[serde(rename = "_key", default, skip_serializing_if = "Option::is_none")]
+    pub db_key: Option<String>,
+
+    /// The submitted feedback.
+    #[serde(flatten)]
+    pub submission: FeedbackSubmission,
+
+    /// Moderation status.
+    #[serde(rename = "status")]
+    pub status: FeedbackStatus,
+
+    /// Note left by the moderator.
+    #[serde(rename = "note", default)]
+    pub note: Option<String>,
+
+    /// Time of submission (RFC 3339).
+    #[serde(rename = "created")]
+    pub created: String,
+
+    /// Time of the last status change (RFC 3339).
+    #[serde(rename = "updated")]
+    pub updated: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_transitions() {
+        use FeedbackStatus::*;
+
+        assert!(Pending.can_change_to(Accepted));
+        assert!(Pending.can_change_to(Rejected));
+        assert!(Accepted.can_change_to(Applied));
+        assert!(Rejected.can_change_to(Pending));
+
+        assert!(!Pending.can_change_to(Applied));
+        assert!(!Pending.can_change_to(Pending));
+        assert!(!Applied.can_change_to(Pending));
+        assert!(!Rejected.can_change_to(Applied));
+    }
+
+    #[test]
+    fn serialization() {
+        let feedback = Feedback {
+            db_key: None,
+            submission: FeedbackSubmission {
+                kind: FeedbackKind::WrongMatch,
+                subject: "Fairphone".to_owned(),
+                correction: Some("Q5431244".to_owned()),
+                message: String::new(),
+            },
+            status: FeedbackStatus::Pending,
+            note: None,
+            created: "2024-01-01T00:00:00Z".to_owned(),
+            updated: "2024-01-01T00:00:00Z".to_owned(),
+        };
+
+        let json = serde_json::to_value(&feedback).unwrap();
+        assert_eq!(json["kind"], "wrong_match");
+        assert_eq!(json["status"], "pending");
+        assert!(json.get("_key").is_none());
+        assert_eq!(serde_json::from_value::<Feedback>(json).unwrap(), feedback);
+    }
+}
@@ -1,13 +1,20 @@
 pub use crate::{
-    ids::{Ean, Gtin, ParseIdError, VatId, WikiId},
+    attributes::{AttributeValue, Attributes},
+    ids::{Ean, EntityId, Gtin, ParseIdError, SusId, VatId, WikiId},
     models::{
-        BCorpCert, Certifications, Edge, EuEcolabelCert, FtiCert, GatherDomain as Domain,
+        BCorpCert, Category, CategoryStats, CertificationInfo, Certifications, Controversy,
+        ControversySeverity, Edge, EuEcolabelCert, ExternalIdKind, FtiCert,
+        GatherCertifiedPresentationEntry as CertifiedPresentationEntry, GatherDomain as Domain,
         GatherOrganisation as Organisation, GatherOrganisationId as OrganisationId,
         GatherOrganisationIds as OrganisationIds, GatherPresentation as Presentation,
         GatherPresentationData as PresentationData, GatherProduct as Product,
         GatherProductId as ProductId, GatherProductIds as ProductIds,
-        GatherScoredPresentationEntry as ScoredPresentationEntry, IdEntry, Image, Keyword,
-        LibraryItem, LibraryTopic, Regions, Source, SustainityScore, SustainityScoreBranch,
-        SustainityScoreCategory, TcoCert, Text,
+        GatherScoredPresentationEntry as ScoredPresentationEntry, GraphEdge, IdEntry, Identifier,
+        IdentifierKind, Image, Keyword, LibraryItem, LibraryTocEntry, LibraryTopic,
+        LibraryTranslation, PolicyPage, PolicyPageKind, ProductAlternatives, Recognition, Regions,
+        Relation, Source, SustainityScore, SustainityScoreBranch, SustainityScoreCategory, TcoCert,
+        Text, TimelineEvent, TimelineEventKind, Validity,
     },
+    relevance::{Certification as CertificationKind, RelevanceMatrix},
+    social::{Platform as SocialPlatform, SocialLink},
 };
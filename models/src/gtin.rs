@@ -0,0 +1,147 @@
+//! Validation and normalisation of Global Trade Item Numbers.
+//!
+//! GTINs come in four lengths: GTIN-8, GTIN-12 (UPC-A), GTIN-13 (EAN-13) and GTIN-14. All of them
+//! can be represented as GTIN-14 by padding them with leading zeros, which is the canonical form
+//! used through the whole project. The last digit of every GTIN is a check digit.
+//!
+//! The first digits of a GTIN-13 form a GS1 company prefix identifying the company which licensed
+//! the number. The length of the prefix is not fixed (it depends on the licence), but it always
+//! starts with a three-digit prefix of the GS1 member organisation which issued it.
+
+use crate::ids::ParseIdError;
+
+/// Length of the canonical form.
+pub const CANONICAL_LENGTH: usize = 14;
+
+/// Allowed lengths of GTINs (before padding).
+const LENGTHS: [usize; 4] = [8, 12, 13, 14];
+
+/// Minimal length of a GS1 company prefix.
+pub const MIN_COMPANY_PREFIX_LENGTH: usize = 4;
+
+/// Maximal length of a GS1 company prefix.
+pub const MAX_COMPANY_PREFIX_LENGTH: usize = 12;
+
+/// Removes separators often used when writing GTINs down.
+fn strip(code: &str) -> String {
+    code.chars().filter(|c| !matches!(c, ' ' | '-' | '.')).collect()
+}
+
+/// Calculates the check digit for the given digits (without the check digit).
+///
+/// Returns `None` if the string contains other characters than digits.
+#[must_use]
+pub fn check_digit(payload: &str) -> Option<u32> {
+    let mut sum = 0;
+    for (i, c) in payload.chars().rev().enumerate() {
+        let digit = c.to_digit(10)?;
+        sum += if i % 2 == 0 { 3 * digit } else { digit };
+    }
+    Some((10 - sum % 10) % 10)
+}
+
+/// Brings the GTIN to its canonical (GTIN-14) form, verifying its length and check digit.
+///
+/// # Errors
+///
+/// Returns an error if the code has invalid length, contains other characters than digits or its
+/// check digit does not match.
+pub fn normalize(code: &str) -> Result<String, ParseIdError> {
+    let stripped = strip(code);
+    if !LENGTHS.contains(&stripped.len()) {
+        return Err(ParseIdError::length(code.to_owned()));
+    }
+    if let Err(err) = stripped.parse::<u64>() {
+        return Err(ParseIdError::num(stripped, err));
+    }
+
+    let (payload, check) = stripped.split_at(stripped.len() - 1);
+    if check_digit(payload).map(|d| d.to_string()).as_deref() != Some(check) {
+        return Err(ParseIdError::checksum(stripped));
+    }
+    Ok(format!("{stripped:0>CANONICAL_LENGTH$}"))
+}
+
+/// Checks if the code is a GTIN with a valid check digit.
+#[must_use]
+pub fn is_valid(code: &str) -> bool {
+    normalize(code).is_ok()
+}
+
+/// Returns the GTIN-13 part of a canonical GTIN or `None` if it was padded from GTIN-8.
+///
+/// GTIN-8 numbers are assigned individually and don't contain a company prefix. The indicator
+/// digit of GTIN-14 (packaging level) is dropped.
+fn gtin13_part(canonical: &str) -> Option<&str> {
+    if canonical.len() != CANONICAL_LENGTH || canonical.starts_with("000000") {
+        None
+    } else {
+        Some(&canonical[1..])
+    }
+}
+
+/// Returns the three-digit prefix of the GS1 member organisation that issued the GTIN.
+#[must_use]
+pub fn gs1_prefix(canonical: &str) -> Option<&str> {
+    gtin13_part(canonical).map(|part| &part[..3])
+}
+
+/// Returns the GS1 company prefix of the given length.
+///
+/// The length of the company prefix can't be inferred from the GTIN itself; callers are expected
+/// to try all lengths from `MIN_COMPANY_PREFIX_LENGTH` to `MAX_COMPANY_PREFIX_LENGTH` against the
+/// licence data they have.
+#[must_use]
+pub fn company_prefix(canonical: &str, length: usize) -> Option<&str> {
+    if !(MIN_COMPANY_PREFIX_LENGTH..=MAX_COMPANY_PREFIX_LENGTH).contains(&length) {
+        return None;
+    }
+    gtin13_part(canonical).map(|part| &part[..length])
+}
+
+/// Checks if the GTIN belongs to a range reserved for restricted circulation.
+///
+/// Such numbers (e.g. in-store codes for weighted goods or coupons) are not globally unique and
+/// should not be used to identify products across data sources.
+#[must_use]
+pub fn is_restricted(canonical: &str) -> bool {
+    gs1_prefix(canonical)
+        .and_then(|prefix| prefix.parse::<u16>().ok())
+        .is_some_and(|prefix| matches!(prefix, 20..=29 | 40..=49 | 200..=299 | 980..=999))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_digits() {
+        assert_eq!(check_digit("871767733955"), Some(6));
+        assert_eq!(check_digit("9638507"), Some(4));
+        assert_eq!(check_digit("03600029145"), Some(2));
+        assert_eq!(check_digit("12a"), None);
+    }
+
+    #[test]
+    fn normalization() {
+        assert_eq!(normalize("8717677339556").unwrap(), "08717677339556");
+        assert_eq!(normalize("8717-6773 3955.6").unwrap(), "08717677339556");
+        assert_eq!(normalize("96385074").unwrap(), "00000096385074");
+        assert_eq!(normalize("036000291452").unwrap(), "00036000291452");
+        assert_eq!(normalize("18717677339553").unwrap(), "18717677339553");
+        assert!(matches!(normalize("8717677339557"), Err(ParseIdError::Checksum { .. })));
+        assert!(matches!(normalize("87176773395"), Err(ParseIdError::Length { .. })));
+        assert!(matches!(normalize("871767733955a"), Err(ParseIdError::Num { .. })));
+    }
+
+    #[test]
+    fn prefixes() {
+        let gtin = normalize("8717677339556").unwrap();
+        assert_eq!(gs1_prefix(&gtin), Some("871"));
+        assert_eq!(company_prefix(&gtin, 7), Some("8717677"));
+        assert_eq!(company_prefix(&gtin, 3), None);
+        assert_eq!(gs1_prefix("00000096385074"), None);
+        assert!(!is_restricted(&gtin));
+        assert!(is_restricted(&normalize("2001234567893").unwrap()));
+    }
+}
@@ -5,6 +5,8 @@ use std::collections::HashSet;
 use serde::{de::Deserializer, ser::Serializer, Deserialize, Serialize};
 use snafu::prelude::*;
 
+use crate::{gtin, vat};
+
 /// Maximal EAN (highest number with 13 digits).
 const MAX_EAN: usize = 9_999_999_999_999;
 
@@ -25,6 +27,14 @@ pub enum ParseIdError {
     /// The ID didn't contain the expected prefix.
     #[snafu(display("The ID `{string}` has unexpected prefix"))]
     Prefix { string: String },
+
+    /// The ID doesn't follow the format expected for its country.
+    #[snafu(display("The ID `{string}` has invalid format"))]
+    Format { string: String },
+
+    /// The check digits of the ID don't match.
+    #[snafu(display("The ID `{string}` has invalid check digits"))]
+    Checksum { string: String },
 }
 
 impl ParseIdError {
@@ -39,6 +49,14 @@ impl ParseIdError {
     pub fn prefix(string: String) -> Self {
         Self::Prefix { string }
     }
+
+    pub fn format(string: String) -> Self {
+        Self::Format { string }
+    }
+
+    pub fn checksum(string: String) -> Self {
+        Self::Checksum { string }
+    }
 }
 
 impl From<sustainity_wikidata::errors::ParseIdError> for ParseIdError {
@@ -214,6 +232,21 @@ impl Gtin {
         format!("{:0>14}", self.0)
     }
 
+    /// Verifies the check digit.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the check digit does not match.
+    pub fn validate(&self) -> Result<(), ParseIdError> {
+        gtin::normalize(&self.to_canonical_string()).map(|_| ())
+    }
+
+    /// Checks if the GTIN belongs to a range reserved for restricted circulation.
+    #[must_use]
+    pub fn is_restricted(&self) -> bool {
+        gtin::is_restricted(&self.to_canonical_string())
+    }
+
     /// Converts optional vector of strings to a vector of VAT IDs.
     ///
     /// # Errors
@@ -297,11 +330,37 @@ pub struct VatId(String);
 
 impl VatId {
     /// Constructs a new `VatId`.
+    ///
+    /// The number is brought to its canonical form (see `vat::normalize`).
     #[must_use]
     pub fn new(id: &str) -> Self {
-        let mut reduced = id.to_owned();
-        reduced.retain(|c| c.is_ascii_alphanumeric());
-        Self(reduced)
+        Self(vat::normalize(id))
+    }
+
+    /// Constructs a new `VatId` attaching the prefix of the given country if it is missing.
+    ///
+    /// `country` is the ISO 3166-1 alpha-2 code of the country the company is registered in.
+    #[must_use]
+    pub fn with_country(id: &str, country: &str) -> Self {
+        Self(vat::normalize_with_country(id, country))
+    }
+
+    /// Checks if the number follows the format of its country.
+    ///
+    /// Numbers from unknown countries are accepted. Check digits are verified only if
+    /// `verify_checksum` is set and the country defines them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the format or check digits are invalid.
+    pub fn validate(&self, verify_checksum: bool) -> Result<(), ParseIdError> {
+        if vat::is_valid_format(&self.0) == Some(false) {
+            return Err(ParseIdError::format(self.0.clone()));
+        }
+        if verify_checksum && vat::is_valid_checksum(&self.0) == Some(false) {
+            return Err(ParseIdError::checksum(self.0.clone()));
+        }
+        Ok(())
     }
 
     /// Returns reference to the inner string.
@@ -410,7 +469,7 @@ impl Serialize for OrganisationId {
 }
 
 /// Represents in ID of a product.
-#[derive(Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+#[derive(Debug, Clone, Default, Hash, Eq, PartialEq, Ord, PartialOrd)]
 pub struct ProductId(usize);
 
 impl ProductId {
@@ -444,3 +503,258 @@ impl Serialize for ProductId {
         serializer.serialize_str(self.to_canonical_string().as_ref())
     }
 }
+
+/// Namespace of an entity ID.
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Namespace {
+    /// Wikidata items.
+    Wiki,
+
+    /// Open Food Facts products (identified by their GTINs).
+    Off,
+
+    /// Entities known only to Sustainity (identified by their IDs in the source data).
+    Sus,
+}
+
+impl Namespace {
+    /// Returns the prefix of the IDs in this namespace.
+    #[must_use]
+    pub fn prefix(self) -> &'static str {
+        match self {
+            Self::Wiki => "wiki",
+            Self::Off => "off",
+            Self::Sus => "sus",
+        }
+    }
+
+    /// Parses an ID without the prefix as an ID in this namespace.
+    ///
+    /// Useful for converting IDs stored before namespaces were introduced.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the ID is not valid in this namespace.
+    pub fn parse(self, id: &str) -> Result<EntityId, ParseIdError> {
+        match self {
+            Self::Wiki => {
+                let number = id.strip_prefix('Q').unwrap_or(id);
+                Ok(EntityId::Wiki(WikiId::try_from(number)?))
+            }
+            Self::Off => Ok(EntityId::Off(Gtin::try_from(id)?)),
+            Self::Sus => Ok(EntityId::Sus(SusId::try_from(id)?)),
+        }
+    }
+}
+
+/// Represents an ID of an organisation known only to Sustainity.
+///
+/// The ID is derived from a stable ID the organisation has in the source data, so that it stays
+/// the same when the data is crystalized again (unlike the database keys). The kind of the source
+/// ID is the prefix of the ID (`vat-NL123456789B01`, `www-example.com`).
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub enum SusId {
+    /// Organisation identified by its VAT number.
+    Vat(VatId),
+
+    /// Organisation identified by the domain of its website.
+    Domain(String),
+}
+
+impl SusId {
+    /// Returns the prefix of the IDs of this kind.
+    #[must_use]
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            Self::Vat(_) => "vat",
+            Self::Domain(_) => "www",
+        }
+    }
+
+    /// Returns the source ID without the prefix.
+    #[must_use]
+    pub fn as_source_str(&self) -> &str {
+        match self {
+            Self::Vat(vat) => vat.as_str(),
+            Self::Domain(domain) => domain,
+        }
+    }
+}
+
+impl std::fmt::Display for SusId {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}-{}", self.prefix(), self.as_source_str())
+    }
+}
+
+impl TryFrom<&str> for SusId {
+    type Error = ParseIdError;
+
+    fn try_from(string: &str) -> Result<Self, Self::Error> {
+        let Some((kind, id)) = string.split_once('-') else {
+            return Err(ParseIdError::prefix(string.to_owned()));
+        };
+        match kind {
+            "vat" => {
+                let vat = VatId::try_from(id)?;
+                if vat.as_str() != id {
+                    return Err(ParseIdError::format(id.to_owned()));
+                }
+                Ok(Self::Vat(vat))
+            }
+            "www" => {
+                let valid = id.contains('.')
+                    && id.chars().all(|c| {
+                        c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '.'
+                    });
+                if !valid {
+                    return Err(ParseIdError::format(id.to_owned()));
+                }
+                Ok(Self::Domain(id.to_owned()))
+            }
+            _ => Err(ParseIdError::prefix(kind.to_owned())),
+        }
+    }
+}
+
+impl std::str::FromStr for Namespace {
+    type Err = ParseIdError;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string {
+            "wiki" => Ok(Self::Wiki),
+            "off" => Ok(Self::Off),
+            "sus" => Ok(Self::Sus),
+            _ => Err(ParseIdError::prefix(string.to_owned())),
+        }
+    }
+}
+
+/// Represents an ID of a product or an organisation together with the namespace it comes from.
+///
+/// Most entities are known from Wikidata, but some exist only in Open Food Facts or only in
+/// Sustainity. The namespace prefix (`wiki:Q123`, `off:00123456789012`, `sus:www-example.com`)
+/// allows all of them to be referred to in the same way without their IDs colliding.
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub enum EntityId {
+    /// Wikidata item.
+    Wiki(WikiId),
+
+    /// Open Food Facts product.
+    Off(Gtin),
+
+    /// Organisation known only to Sustainity.
+    Sus(SusId),
+}
+
+impl EntityId {
+    /// Returns the namespace of the ID.
+    #[must_use]
+    pub fn namespace(&self) -> Namespace {
+        match self {
+            Self::Wiki(_) => Namespace::Wiki,
+            Self::Off(_) => Namespace::Off,
+            Self::Sus(_) => Namespace::Sus,
+        }
+    }
+
+    /// Returns the Wikidata ID if the entity is a Wikidata item.
+    #[must_use]
+    pub fn as_wiki_id(&self) -> Option<WikiId> {
+        match self {
+            Self::Wiki(id) => Some(*id),
+            Self::Off(_) | Self::Sus(_) => None,
+        }
+    }
+
+    /// Returns the GTIN if the entity is an Open Food Facts product.
+    #[must_use]
+    pub fn as_gtin(&self) -> Option<&Gtin> {
+        match self {
+            Self::Off(gtin) => Some(gtin),
+            Self::Wiki(_) | Self::Sus(_) => None,
+        }
+    }
+
+    /// Returns the ID without the namespace prefix.
+    #[must_use]
+    pub fn to_local_string(&self) -> String {
+        match self {
+            Self::Wiki(id) => format!("Q{}", id.get_value()),
+            Self::Off(gtin) => gtin.to_canonical_string(),
+            Self::Sus(id) => id.to_string(),
+        }
+    }
+
+    #[must_use]
+    pub fn to_canonical_string(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl From<WikiId> for EntityId {
+    fn from(id: WikiId) -> Self {
+        Self::Wiki(id)
+    }
+}
+
+impl From<SusId> for EntityId {
+    fn from(id: SusId) -> Self {
+        Self::Sus(id)
+    }
+}
+
+impl From<Gtin> for EntityId {
+    fn from(gtin: Gtin) -> Self {
+        Self::Off(gtin)
+    }
+}
+
+impl std::fmt::Display for EntityId {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}:{}", self.namespace().prefix(), self.to_local_string())
+    }
+}
+
+impl TryFrom<&str> for EntityId {
+    type Error = ParseIdError;
+
+    fn try_from(string: &str) -> Result<Self, Self::Error> {
+        let Some((namespace, id)) = string.split_once(':') else {
+            return Err(ParseIdError::prefix(string.to_owned()));
+        };
+        namespace.parse::<Namespace>()?.parse(id)
+    }
+}
+
+impl TryFrom<&String> for EntityId {
+    type Error = ParseIdError;
+
+    fn try_from(string: &String) -> Result<Self, Self::Error> {
+        Self::try_from(string.as_str())
+    }
+}
+
+impl std::str::FromStr for EntityId {
+    type Err = ParseIdError;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        Self::try_from(string)
+    }
+}
+
+impl Serialize for EntityId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for EntityId {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(d)?;
+        Self::try_from(s.as_str()).map_err(serde::de::Error::custom)
+    }
+}
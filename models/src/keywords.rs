@@ -0,0 +1,265 @@
+//! Extraction of keywords used in the text search.
+//!
+//! Keywords are folded words of names: lowercased, stripped of diacritics and with compatibility
+//! characters (e.g. fullwidth letters) replaced by their plain forms, so that "Müller" is found by
+//! "muller" and "ＳＯＮＹ" by "sony". The same folding must be applied to the queries.
+//! Product names often contain model numbers, which people
+//! write in different ways ("XPS 13" vs "XPS13", "WH-1000XM4" vs "WH1000XM4"), so their variants
+//! with and without separators are added as well.
+//!
+//! Stop words (e.g. "the" in "The Body Shop") and legal forms of organisations (e.g. "GmbH") are
+//! left out, as they would match a large part of the index. The same [`StopWords`] must be used for
+//! extracting the keywords and for tokenizing the queries.
+
+use std::collections::BTreeSet;
+
+use snafu::prelude::*;
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+/// Characters separating parts of a single word, e.g. a model number.
+const WORD_SEPARATORS: &[char] = &['-', '/', '.', '_'];
+
+/// Characters trimmed from the ends of the words.
+const PUNCTUATION: &[char] = &[',', ';', ':', '!', '?', '(', ')', '[', ']', '"', '\'', '.'];
+
+/// Letters which do not decompose into a base letter and a diacritic, with their transliterations.
+const TRANSLITERATIONS: &[(char, &str)] = &[
+    ('ß', "ss"),
+    ('æ', "ae"),
+    ('œ', "oe"),
+    ('ø', "o"),
+    ('ł', "l"),
+    ('đ', "d"),
+    ('ð', "d"),
+    ('þ', "th"),
+    ('ı', "i"),
+    ('ς', "σ"),
+];
+
+/// Folds the text for case- and accent-insensitive comparison.
+///
+/// Compatibility characters are replaced by their plain forms, letters are lowercased and latin
+/// letters are stripped of diacritics. Letters of other scripts keep theirs (e.g. "が" is not
+/// folded to "か"), as there they often change the meaning.
+#[must_use]
+pub fn fold(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut is_latin = false;
+    for c in text.nfkd().flat_map(char::to_lowercase) {
+        if is_combining_mark(c) {
+            if !is_latin {
+                result.push(c);
+            }
+        } else if let Some((_, transliteration)) = TRANSLITERATIONS.iter().find(|(t, _)| *t == c) {
+            result.push_str(transliteration);
+            is_latin = transliteration.is_ascii();
+        } else {
+            result.push(c);
+            is_latin = c.is_ascii();
+        }
+    }
+    result.nfc().collect()
+}
+
+/// Stop words per language (ISO 639-1 code), folded.
+const STOP_WORDS: &[(&str, &[&str])] = &[
+    ("de", &["das", "dem", "den", "der", "des", "die", "ein", "eine", "fur", "im", "mit", "und"]),
+    ("en", &["&", "a", "an", "and", "by", "for", "in", "of", "on", "the", "with"]),
+    ("es", &["con", "de", "del", "el", "en", "la", "las", "los", "para", "un", "una", "y"]),
+    ("fr", &["au", "aux", "avec", "de", "des", "du", "et", "la", "le", "les", "pour", "un", "une"]),
+    ("it", &["con", "del", "della", "di", "e", "gli", "il", "la", "le", "lo", "per", "un", "una"]),
+    ("nl", &["de", "een", "en", "het", "met", "op", "van", "voor"]),
+    ("pl", &["dla", "do", "i", "na", "w", "z"]),
+];
+
+/// Generic suffixes of organisation names (legal forms), folded and stripped of punctuation.
+const BRAND_SUFFIXES: &[&str] = &[
+    "a/s", "ab", "ag", "b.v", "bv", "co", "corp", "gmbh", "inc", "kg", "llc", "ltd", "n.v", "nv",
+    "oy", "plc", "s.a", "s.p.a", "s.r.l", "s.r.o", "sa", "sarl", "sas", "srl",
+];
+
+/// Error returned when parsing stop words of an unsupported language.
+#[derive(Debug, Eq, PartialEq, Snafu)]
+#[snafu(display("Stop words of language `{language}` are not available"))]
+pub struct UnknownLanguageError {
+    language: String,
+}
+
+/// Words ignored by the search: stop words of the selected languages and generic suffixes of
+/// organisation names.
+///
+/// Parsed from a comma-separated list of language codes (e.g. "en,de"). All the supported
+/// languages are selected by default.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StopWords {
+    languages: Vec<&'static [&'static str]>,
+}
+
+impl StopWords {
+    /// Lists codes of the supported languages.
+    pub fn supported_languages() -> impl Iterator<Item = &'static str> {
+        STOP_WORDS.iter().map(|(language, _)| *language)
+    }
+
+    /// Checks if the (folded) word is ignored.
+    #[must_use]
+    pub fn contains(&self, word: &str) -> bool {
+        BRAND_SUFFIXES.contains(&word) || self.languages.iter().any(|words| words.contains(&word))
+    }
+
+    /// Removes the ignored words, unless all of the words are ignored (e.g. the query "the").
+    #[must_use]
+    pub fn filter(&self, words: Vec<String>) -> Vec<String> {
+        if words.iter().all(|word| self.contains(word)) {
+            words
+        } else {
+            words.into_iter().filter(|word| !self.contains(word)).collect()
+        }
+    }
+}
+
+impl Default for StopWords {
+    fn default() -> Self {
+        Self { languages: STOP_WORDS.iter().map(|(_, words)| *words).collect() }
+    }
+}
+
+impl std::str::FromStr for StopWords {
+    type Err = UnknownLanguageError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut languages = Vec::new();
+        for language in s.split(',').map(str::trim).filter(|language| !language.is_empty()) {
+            match STOP_WORDS.iter().find(|(code, _)| code.eq_ignore_ascii_case(language)) {
+                Some((_, words)) => languages.push(*words),
+                None => return UnknownLanguageSnafu { language }.fail(),
+            }
+        }
+        Ok(Self { languages })
+    }
+}
+
+/// Splits the text into folded words, stripping punctuation.
+#[must_use]
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split_whitespace()
+        .map(|word| fold(word.trim_matches(PUNCTUATION)))
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// Checks if the word looks like (a part of) a model number.
+fn has_digit(word: &str) -> bool {
+    word.chars().any(|c| c.is_ascii_digit())
+}
+
+/// Extracts keywords from the text.
+fn extract_from(text: &str, stop_words: &StopWords, keywords: &mut BTreeSet<String>) {
+    let words = stop_words.filter(tokenize(text));
+    for word in &words {
+        keywords.insert(word.clone());
+        if word.contains(WORD_SEPARATORS) {
+            keywords.extend(
+                word.split(WORD_SEPARATORS).filter(|part| !part.is_empty()).map(String::from),
+            );
+            keywords.insert(word.replace(WORD_SEPARATORS, ""));
+        }
+    }
+    for pair in words.windows(2) {
+        if has_digit(&pair[1]) {
+            keywords.insert(format!("{}{}", pair[0], pair[1]).replace(WORD_SEPARATORS, ""));
+        }
+    }
+}
+
+/// Extracts keywords from all the texts (e.g. names, brand names).
+#[must_use]
+pub fn extract<'a>(
+    texts: impl IntoIterator<Item = &'a str>,
+    stop_words: &StopWords,
+) -> BTreeSet<String> {
+    let mut keywords = BTreeSet::new();
+    for text in texts {
+        extract_from(text, stop_words, &mut keywords);
+    }
+    keywords.remove("");
+    keywords
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(words: &[&str]) -> BTreeSet<String> {
+        words.iter().map(|w| (*w).to_owned()).collect()
+    }
+
+    #[test]
+    fn tokenization() {
+        assert_eq!(tokenize("Fairphone 4 (5G)"), vec!["fairphone", "4", "5g"]);
+        assert_eq!(tokenize("  Ben & Jerry's!  "), vec!["ben", "&", "jerry's"]);
+        assert_eq!(tokenize("Müller Brot"), vec!["muller", "brot"]);
+    }
+
+    #[test]
+    fn folding() {
+        assert_eq!(fold("Crème Brûlée"), "creme brulee");
+        assert_eq!(fold("Straße"), "strasse");
+        assert_eq!(fold("ŁÓDŹ"), "lodz");
+        assert_eq!(fold("Ｆａｉｒｐｈｏｎｅ"), "fairphone");
+        assert_eq!(fold("ｶﾞ"), "ガ");
+        assert_eq!(fold("ガラス"), "ガラス");
+        assert_eq!(fold("Ελληνικός"), "ελληνικόσ");
+        assert_eq!(fold("हिन्दी"), "हिन्दी");
+        assert_eq!(fold("e\u{301}"), "e");
+        assert_eq!(fold(&fold("Søstrene Grene")), fold("Søstrene Grene"));
+    }
+
+    #[test]
+    fn extraction() {
+        let stop_words = StopWords::default();
+        let extract = |texts: &[&str]| extract(texts.iter().copied(), &stop_words);
+        assert_eq!(extract(&["Dell XPS 13"]), set(&["dell", "xps", "13", "xps13"]));
+        assert_eq!(
+            extract(&["Sony WH-1000XM4", "Sony"]),
+            set(&["sony", "wh-1000xm4", "wh", "1000xm4", "wh1000xm4", "sonywh1000xm4"])
+        );
+        assert_eq!(extract(&["Fairphone"]), set(&["fairphone"]));
+        assert!(extract(&["", " . "]).is_empty());
+        assert_eq!(
+            extract(&["The Body Shop", "The Body Shop International Ltd."]),
+            set(&["body", "shop", "international"])
+        );
+        assert_eq!(extract(&["Ben & Jerry's"]), set(&["ben", "jerry's"]));
+        assert_eq!(extract(&["The The"]), set(&["the"]));
+    }
+
+    #[test]
+    fn stop_words() {
+        let all = StopWords::default();
+        assert!(all.contains("the"));
+        assert!(all.contains("der"));
+        assert!(all.contains("gmbh"));
+        assert!(!all.contains("shop"));
+
+        let english: StopWords = "en".parse().unwrap();
+        assert!(english.contains("the"));
+        assert!(!english.contains("der"));
+        assert!(english.contains("gmbh"));
+
+        let none: StopWords = "".parse().unwrap();
+        assert!(!none.contains("the"));
+        assert!(none.contains("inc"));
+
+        assert_eq!(" EN , de".parse::<StopWords>().unwrap(), "en,de".parse().unwrap());
+        assert!("en,xx".parse::<StopWords>().is_err());
+        assert_eq!(StopWords::supported_languages().count(), STOP_WORDS.len());
+
+        let words = |words: &[&str]| words.iter().map(|w| (*w).to_owned()).collect::<Vec<_>>();
+        assert_eq!(
+            all.filter(words(&["the", "body", "shop", "soap"])),
+            words(&["body", "shop", "soap"])
+        );
+        assert_eq!(all.filter(words(&["the", "inc"])), words(&["the", "inc"]));
+    }
+}
@@ -1,5 +1,15 @@
+pub mod attributes;
+pub mod changes;
 pub mod collections;
+pub mod dates;
+pub mod feedback;
 pub mod gather;
+pub mod gtin;
 pub mod ids;
+pub mod keywords;
 pub mod models;
+pub mod product_requests;
+pub mod relevance;
+pub mod social;
 pub mod store;
+pub mod vat;
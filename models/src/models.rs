@@ -1,6 +1,9 @@
 //! This modules contains definitions of data stored in the internal database.
 
-use std::{collections::BTreeSet, str::FromStr};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    str::FromStr,
+};
 
 use merge::Merge;
 use serde::{Deserialize, Serialize};
@@ -12,7 +15,7 @@ use sustainity_api::models as api;
 #[cfg(feature = "from-substrate")]
 use sustainity_schema as schema;
 
-use crate::ids;
+use crate::{attributes::Attributes, dates, ids, relevance, social::SocialLink};
 
 pub type LibraryTopic = String;
 pub type StoreGtin = usize;
@@ -42,6 +45,7 @@ impl IntoApiError {
 
 /// Points to a source of some data.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub enum Source {
     /// Wikidata.
     #[serde(rename = "wiki")]
@@ -118,6 +122,7 @@ impl Source {
 
 /// Text together with it's source.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct Text {
     /// Text.
     #[serde(rename = "text")]
@@ -157,6 +162,7 @@ impl Text {
 
 /// Image together with it's source.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct Image {
     /// Name of the images.
     ///
@@ -177,6 +183,7 @@ impl Image {
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 #[serde(tag = "variant", content = "content")]
 pub enum Regions {
     /// Available world-wide
@@ -190,7 +197,10 @@ pub enum Regions {
 
     /// List of regions
     #[serde(rename = "list")]
-    List(Vec<isocountry::CountryCode>),
+    List(
+        #[cfg_attr(feature = "json-schema", schemars(with = "Vec<String>"))]
+        Vec<isocountry::CountryCode>,
+    ),
 }
 
 impl merge::Merge for Regions {
@@ -215,11 +225,67 @@ impl merge::Merge for Regions {
     }
 }
 
+/// Number of days after which a certification from a source snapshot is considered stale.
+pub const MAX_CERTIFICATION_AGE_DAYS: i64 = 2 * 365;
+
+/// Period in which a certification is valid and the date the information comes from.
+///
+/// Dates are in the `YYYY-MM-DD` form. Missing dates mean the source does not provide them.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct Validity {
+    /// Date since which the certification is valid.
+    #[serde(default)]
+    pub valid_from: Option<String>,
+
+    /// Date until which the certification is valid.
+    #[serde(default)]
+    pub valid_until: Option<String>,
+
+    /// Date of the snapshot of the source data.
+    #[serde(default)]
+    pub source_snapshot_date: Option<String>,
+}
+
+impl Validity {
+    /// Constructs a new `Validity` knowing only the date of the source snapshot.
+    #[must_use]
+    pub fn from_snapshot(source_snapshot_date: Option<String>) -> Self {
+        Self { valid_from: None, valid_until: None, source_snapshot_date }
+    }
+
+    /// Checks if the certification expired before `today`.
+    #[must_use]
+    pub fn is_expired(&self, today: &str) -> bool {
+        self.valid_until.as_deref().is_some_and(|until| until < today)
+    }
+
+    /// Checks if the source snapshot is older than `MAX_CERTIFICATION_AGE_DAYS`.
+    #[must_use]
+    pub fn is_stale(&self, today: &str) -> bool {
+        self.source_snapshot_date
+            .as_deref()
+            .and_then(|date| dates::days_between(date, today))
+            .is_some_and(|age| age > MAX_CERTIFICATION_AGE_DAYS)
+    }
+
+    /// Checks if the certification can be trusted at `today`.
+    #[must_use]
+    pub fn is_current(&self, today: &str) -> bool {
+        !self.is_expired(today) && !self.is_stale(today)
+    }
+}
+
 /// Data about a `BCorp` company.
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct BCorpCert {
     /// Name identifying the company.
     pub id: String,
+
+    /// Validity of the certification.
+    #[serde(default)]
+    pub validity: Validity,
 }
 
 #[cfg(feature = "into-api")]
@@ -245,8 +311,13 @@ impl BCorpCert {
 }
 
 /// Data about a company certified by EU Ecolabel.
-#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
-pub struct EuEcolabelCert;
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct EuEcolabelCert {
+    /// Validity of the certification.
+    #[serde(default)]
+    pub validity: Validity,
+}
 
 #[cfg(feature = "into-api")]
 impl EuEcolabelCert {
@@ -264,9 +335,14 @@ impl EuEcolabelCert {
 
 /// Data about a company scored by Fashion Transparency Index.
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct FtiCert {
     /// Score (from 0% to 100%).
     pub score: i64,
+
+    /// Validity of the score.
+    #[serde(default)]
+    pub validity: Validity,
 }
 
 #[cfg(feature = "into-api")]
@@ -285,9 +361,14 @@ impl FtiCert {
 
 /// Data about a company which products were certified by TCO.
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct TcoCert {
     /// Name identifying the company.
     pub brand_name: String,
+
+    /// Validity of the certification.
+    #[serde(default)]
+    pub validity: Validity,
 }
 
 #[cfg(feature = "into-api")]
@@ -312,8 +393,155 @@ impl TcoCert {
     }
 }
 
+/// Award or prize received by an organisation (e.g. for its sustainability efforts).
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct Recognition {
+    /// Wikidata ID of the award.
+    #[serde(rename = "wiki_id")]
+    pub wiki_id: String,
+
+    /// Name of the award.
+    #[serde(rename = "name")]
+    pub name: String,
+
+    /// Date the award was received (`YYYY-MM-DD`, or just `YYYY` if only the year is known).
+    #[serde(rename = "date", default, skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+}
+
+/// Severity of a controversy.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub enum ControversySeverity {
+    #[serde(rename = "low")]
+    Low,
+
+    #[serde(rename = "medium")]
+    Medium,
+
+    #[serde(rename = "high")]
+    High,
+}
+
+/// Controversy (e.g. greenwashing) an organisation was involved in.
+///
+/// Presented as a counterweight to certifications.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct Controversy {
+    /// Date of the issue (`YYYY-MM-DD`, or just `YYYY` if only the year is known).
+    #[serde(rename = "date")]
+    pub date: String,
+
+    /// Short description of the issue.
+    #[serde(rename = "issue")]
+    pub issue: String,
+
+    /// Severity of the issue.
+    #[serde(rename = "severity")]
+    pub severity: ControversySeverity,
+
+    /// URL of the source documenting the issue.
+    #[serde(rename = "url")]
+    pub url: String,
+}
+
+/// Kind of an event in the sustainability timeline of an organisation.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub enum TimelineEventKind {
+    /// A certification was granted.
+    #[serde(rename = "certification_granted")]
+    CertificationGranted,
+
+    /// A certification expires (or expired).
+    #[serde(rename = "certification_expiry")]
+    CertificationExpiry,
+
+    /// A score (e.g. the Fashion Transparency Index) was published.
+    #[serde(rename = "score_published")]
+    ScorePublished,
+
+    /// A sustainability report was published.
+    #[serde(rename = "report_published")]
+    ReportPublished,
+
+    /// An award was received.
+    #[serde(rename = "award_received")]
+    AwardReceived,
+
+    /// A controversy came up.
+    #[serde(rename = "controversy")]
+    Controversy,
+}
+
+/// Dated event in the sustainability timeline of an organisation.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct TimelineEvent {
+    /// Date of the event (`YYYY-MM-DD`, or just `YYYY` if only the year is known).
+    #[serde(rename = "date")]
+    pub date: String,
+
+    /// Kind of the event.
+    #[serde(rename = "kind")]
+    pub kind: TimelineEventKind,
+
+    /// Certification or score the event relates to.
+    #[serde(rename = "certification", default, skip_serializing_if = "Option::is_none")]
+    pub certification: Option<relevance::Certification>,
+
+    /// Published score.
+    #[serde(rename = "score", default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<i64>,
+
+    /// Name of the award or description of the controversy.
+    #[serde(rename = "title", default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+
+    /// Link to the source (e.g. the report).
+    #[serde(rename = "url", default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+/// Kind of a policy page.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub enum PolicyPageKind {
+    /// General page about the sustainability efforts.
+    #[serde(rename = "sustainability")]
+    Sustainability,
+
+    /// Sustainability, impact or annual report.
+    #[serde(rename = "report")]
+    Report,
+
+    /// Policy document (e.g. code of conduct, supplier policy).
+    #[serde(rename = "policy")]
+    Policy,
+}
+
+/// Sustainability-related page on one of the organisation's own websites.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct PolicyPage {
+    /// Kind of the page.
+    #[serde(rename = "kind")]
+    pub kind: PolicyPageKind,
+
+    /// Title of the page.
+    #[serde(rename = "title")]
+    pub title: String,
+
+    /// URL of the page.
+    #[serde(rename = "url")]
+    pub url: String,
+}
+
 /// Lists known certifications.
 #[derive(Serialize, Deserialize, Debug, Clone, Default, Eq, PartialEq, Merge)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct Certifications {
     /// Manufacturer certifiad by BCorp.
     pub bcorp: Option<BCorpCert>,
@@ -340,6 +568,25 @@ impl Certifications {
             + usize::from(self.tco.is_some())
     }
 
+    /// Lists the given certifications.
+    #[must_use]
+    pub fn kinds(&self) -> Vec<relevance::Certification> {
+        let mut kinds = Vec::new();
+        if self.bcorp.is_some() {
+            kinds.push(relevance::Certification::BCorp);
+        }
+        if self.eu_ecolabel.is_some() {
+            kinds.push(relevance::Certification::EuEcolabel);
+        }
+        if self.fti.is_some() {
+            kinds.push(relevance::Certification::Fti);
+        }
+        if self.tco.is_some() {
+            kinds.push(relevance::Certification::Tco);
+        }
+        kinds
+    }
+
     /// Copies certifications.
     ///
     /// EU Ecolabel is not inherited - this certification is assigned directly to products, not companies.
@@ -354,44 +601,75 @@ impl Certifications {
             self.tco.clone_from(&other.tco);
         }
     }
+
+    /// Removes certifications which are not listed as relevant.
+    pub fn retain(&mut self, relevant: &BTreeSet<relevance::Certification>) {
+        if !relevant.contains(&relevance::Certification::BCorp) {
+            self.bcorp = None;
+        }
+        if !relevant.contains(&relevance::Certification::EuEcolabel) {
+            self.eu_ecolabel = None;
+        }
+        if !relevant.contains(&relevance::Certification::Fti) {
+            self.fti = None;
+        }
+        if !relevant.contains(&relevance::Certification::Tco) {
+            self.tco = None;
+        }
+    }
+
+    /// Removes certifications which expired or come from stale source snapshots.
+    #[must_use]
+    pub fn current(self, today: &str) -> Self {
+        Self {
+            bcorp: self.bcorp.filter(|c| c.validity.is_current(today)),
+            eu_ecolabel: self.eu_ecolabel.filter(|c| c.validity.is_current(today)),
+            fti: self.fti.filter(|c| c.validity.is_current(today)),
+            tco: self.tco.filter(|c| c.validity.is_current(today)),
+        }
+    }
 }
 
+/// Certifications are presented in the API only if they are current.
 #[cfg(feature = "into-api")]
 impl Certifications {
     pub fn into_api_medallions(self) -> Vec<api::Medallion> {
+        let current = self.current(&dates::today());
         let mut medallions = Vec::new();
-        if let Some(bcorp) = self.bcorp {
+        if let Some(bcorp) = current.bcorp {
             medallions.push(bcorp.into_api());
         }
-        if let Some(eu_ecolabel) = self.eu_ecolabel {
+        if let Some(eu_ecolabel) = current.eu_ecolabel {
             medallions.push(eu_ecolabel.into_api());
         }
-        if let Some(fti) = self.fti {
+        if let Some(fti) = current.fti {
             medallions.push(fti.into_api());
         }
-        if let Some(tco) = self.tco {
+        if let Some(tco) = current.tco {
             medallions.push(tco.into_api());
         }
         medallions
     }
 
     pub fn to_api_badges(&self) -> Vec<api::BadgeName> {
+        let current = self.clone().current(&dates::today());
         let mut badges = Vec::new();
-        if self.bcorp.is_some() {
+        if current.bcorp.is_some() {
             badges.push(api::BadgeName::Bcorp);
         }
-        if self.eu_ecolabel.is_some() {
+        if current.eu_ecolabel.is_some() {
             badges.push(api::BadgeName::Eu);
         }
-        if self.tco.is_some() {
+        if current.tco.is_some() {
             badges.push(api::BadgeName::Tco);
         }
         badges
     }
 
     pub fn to_api_scores(&self) -> Vec<api::Score> {
+        let current = self.clone().current(&dates::today());
         let mut scores = Vec::with_capacity(1);
-        if let Some(fti) = &self.fti {
+        if let Some(fti) = &current.fti {
             scores.push(api::Score { scorer_name: api::ScorerName::Fti, score: fti.score });
         }
         scores
@@ -405,6 +683,110 @@ pub struct IdEntry {
     pub db_key: String,
 }
 
+/// Represents a product category.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub struct Category {
+    /// DB entry key.
+    ///
+    /// Categories are hierarchical, the key is a path with the levels separated by `/`.
+    #[serde(rename = "_key")]
+    pub db_key: String,
+
+    /// Number of products in the category.
+    #[serde(rename = "num_products")]
+    pub num_products: usize,
+
+    /// Certifications relevant for the category (all are relevant if not set).
+    #[serde(rename = "relevant_certifications", default, skip_serializing_if = "Option::is_none")]
+    pub relevant_certifications: Option<Vec<relevance::Certification>>,
+}
+
+/// Statistics of a product category shown on its overview page.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub struct CategoryStats {
+    /// DB entry key (same as the key of the category).
+    #[serde(rename = "_key")]
+    pub db_key: String,
+
+    /// Number of products in the category.
+    #[serde(rename = "num_products")]
+    pub num_products: usize,
+
+    /// Number of products with at least one certification.
+    #[serde(rename = "num_certified")]
+    pub num_certified: usize,
+
+    /// Number of products with each of the certifications.
+    #[serde(rename = "certifications")]
+    pub certifications: BTreeMap<relevance::Certification, usize>,
+
+    /// DB keys of the products with the highest Sustainity score, the best first.
+    #[serde(rename = "best_products")]
+    pub best_products: Vec<String>,
+}
+
+/// Precomputed best alternatives of a product in one of its categories.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub struct ProductAlternatives {
+    /// DB entry key.
+    #[serde(rename = "_key")]
+    pub db_key: String,
+
+    /// DB key of the product.
+    #[serde(rename = "product")]
+    pub product: String,
+
+    /// Category the alternatives belong to.
+    #[serde(rename = "category")]
+    pub category: String,
+
+    /// ISO 3166-1 alpha-3 code of the region the alternatives are available in (any region if not
+    /// set).
+    #[serde(rename = "region", default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+
+    /// DB keys of the alternatives, the best first.
+    #[serde(rename = "alternatives")]
+    pub alternatives: Vec<String>,
+
+    /// All the alternatives are listed, as there are fewer of them than the precomputation limit.
+    #[serde(rename = "exhaustive", default)]
+    pub exhaustive: bool,
+}
+
+/// Describes a certification supported by Sustainity.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct CertificationInfo {
+    /// DB entry key (same as the name of the substrate).
+    #[serde(rename = "_key")]
+    pub db_key: String,
+
+    /// Name of the certification.
+    #[serde(rename = "name")]
+    pub name: String,
+
+    /// Organisation issuing the certification.
+    #[serde(rename = "issuer")]
+    pub issuer: String,
+
+    /// What the certification assesses.
+    #[serde(rename = "covers")]
+    pub covers: String,
+
+    /// Website of the certification.
+    #[serde(rename = "website")]
+    pub website: String,
+
+    /// How the certification data gets into the dataset.
+    #[serde(rename = "ingestion")]
+    pub ingestion: String,
+
+    /// Date of the snapshot of the certification data used in the dataset.
+    #[serde(rename = "snapshot_date", default, skip_serializing_if = "Option::is_none")]
+    pub snapshot_date: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
 pub struct Keyword {
     /// DB entry ID.
@@ -417,6 +799,7 @@ pub struct Keyword {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub enum SustainityScoreCategory {
     #[serde(rename = "root")]
     Root,
@@ -442,6 +825,9 @@ pub enum SustainityScoreCategory {
     #[serde(rename = "warranty_length")]
     WarrantyLength,
 
+    #[serde(rename = "packaging")]
+    Packaging,
+
     #[serde(rename = "num_certs")]
     NumCerts,
 
@@ -464,6 +850,9 @@ impl SustainityScoreCategory {
             Self::CategoryAssigned => api::SustainityScoreCategory::CategoryAssigned,
             Self::Category => api::SustainityScoreCategory::Category,
             Self::WarrantyLength => api::SustainityScoreCategory::WarrantyLength,
+            // The API has no dedicated category yet, so the packaging signal is presented as one
+            // of the category-specific contributions.
+            Self::Packaging => api::SustainityScoreCategory::Category,
             Self::NumCerts => api::SustainityScoreCategory::NumCerts,
             Self::AtLeastOneCert => api::SustainityScoreCategory::AtLeastOneCert,
             Self::AtLeastTwoCerts => api::SustainityScoreCategory::AtLeastTwoCerts,
@@ -472,6 +861,7 @@ impl SustainityScoreCategory {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct SustainityScoreBranch {
     /// Subbranches of the tree.
     #[serde(rename = "branches")]
@@ -503,6 +893,7 @@ impl SustainityScoreBranch {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct SustainityScore {
     /// Score tree.
     #[serde(rename = "tree")]
@@ -552,6 +943,130 @@ pub struct Edge {
     pub to: String,
 }
 
+/// Kind of a relationship between two entities.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+#[serde(rename_all = "snake_case")]
+pub enum Relation {
+    /// A product is manufactured by an organisation.
+    ManufacturedBy,
+
+    /// An organisation is owned by its parent organisation.
+    OwnedBy,
+
+    /// A product is a variant of another product.
+    VariantOf,
+
+    /// A product belongs to a category.
+    InCategory,
+}
+
+/// Represents a typed edge of the graph of relationships between entities.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub struct GraphEdge {
+    /// The "from" vertex ID.
+    #[serde(rename = "_from")]
+    pub from: String,
+
+    /// The "to" vertex ID.
+    #[serde(rename = "_to")]
+    pub to: String,
+
+    /// Kind of the relationship.
+    #[serde(rename = "relation")]
+    pub relation: Relation,
+}
+
+/// Kind of an organisation identifier.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+#[serde(rename_all = "snake_case")]
+pub enum IdentifierKind {
+    /// VAT number.
+    Vat,
+
+    /// Legal Entity Identifier.
+    Lei,
+
+    /// National company registration number.
+    RegistrationNumber,
+
+    /// Web domain.
+    Domain,
+}
+
+impl IdentifierKind {
+    /// Returns the name of the kind as stored in the database.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Vat => "vat",
+            Self::Lei => "lei",
+            Self::RegistrationNumber => "registration_number",
+            Self::Domain => "domain",
+        }
+    }
+}
+
+/// Kind of an external product identifier (e.g. used by an online shop).
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ExternalIdKind {
+    /// Amazon Standard Identification Number.
+    Asin,
+}
+
+impl ExternalIdKind {
+    /// All the kinds.
+    pub const ALL: [Self; 1] = [Self::Asin];
+
+    /// Returns the name of the kind as stored in the database.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Asin => "asin",
+        }
+    }
+
+    /// Returns the kind with the given name.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.as_str() == name)
+    }
+
+    /// Brings the value to its canonical form.
+    ///
+    /// Returns `None` if the value is not a valid identifier of this kind.
+    #[must_use]
+    pub fn normalize(self, value: &str) -> Option<String> {
+        match self {
+            Self::Asin => {
+                let value = value.trim().to_ascii_uppercase();
+                (value.len() == 10 && value.chars().all(|c| c.is_ascii_alphanumeric()))
+                    .then_some(value)
+            }
+        }
+    }
+}
+
+/// Entry of the index of organisation identifiers.
+///
+/// Allows finding organisations by the exact values of their identifiers without scanning the
+/// whole organisation collection.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub struct Identifier {
+    /// Kind of the identifier.
+    #[serde(rename = "kind")]
+    pub kind: IdentifierKind,
+
+    /// Value of the identifier in its canonical form.
+    #[serde(rename = "value")]
+    pub value: String,
+
+    /// DB key of the organisation.
+    #[serde(rename = "organisation")]
+    pub organisation: String,
+}
+
 /// Represents a set of IDs of an organisation.
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
 pub struct GatherOrganisationIds {
@@ -563,21 +1078,52 @@ pub struct GatherOrganisationIds {
 
     /// Web domains.
     pub domains: BTreeSet<GatherDomain>,
+
+    /// Legal Entity Identifiers.
+    pub leis: BTreeSet<String>,
+
+    /// National company registration numbers.
+    pub registration_numbers: BTreeSet<String>,
 }
 
 impl GatherOrganisationIds {
+    /// Lists the identifiers to be indexed for the organisation with the given DB key.
+    pub fn identifiers(&self, organisation: &str) -> Vec<Identifier> {
+        let vat_ids = self.vat_ids.iter().map(|id| (IdentifierKind::Vat, id.as_str()));
+        let leis = self.leis.iter().map(|id| (IdentifierKind::Lei, id.as_str()));
+        let registration_numbers = self
+            .registration_numbers
+            .iter()
+            .map(|id| (IdentifierKind::RegistrationNumber, id.as_str()));
+        let domains = self.domains.iter().map(|id| (IdentifierKind::Domain, id.as_str()));
+        vat_ids
+            .chain(leis)
+            .chain(registration_numbers)
+            .chain(domains)
+            .map(|(kind, value)| Identifier {
+                kind,
+                value: value.to_owned(),
+                organisation: organisation.to_owned(),
+            })
+            .collect()
+    }
+
     pub fn store(self) -> StoreOrganisationIds {
         let mut vat_ids: Vec<String> =
             self.vat_ids.into_iter().map(|id| id.as_str().to_owned()).collect();
         let mut wiki: Vec<String> =
             self.wiki.into_iter().map(|id| id.get_value().to_string()).collect();
         let mut domains: Vec<String> = self.domains.into_iter().collect();
+        let mut leis: Vec<String> = self.leis.into_iter().collect();
+        let mut registration_numbers: Vec<String> = self.registration_numbers.into_iter().collect();
 
         vat_ids.sort();
         wiki.sort();
         domains.sort();
+        leis.sort();
+        registration_numbers.sort();
 
-        StoreOrganisationIds { vat_ids, wiki, domains }
+        StoreOrganisationIds { vat_ids, wiki, domains, leis, registration_numbers }
     }
 }
 
@@ -586,6 +1132,8 @@ impl merge::Merge for GatherOrganisationIds {
         self.wiki.extend(other.wiki);
         self.vat_ids.extend(other.vat_ids);
         self.domains.extend(other.domains);
+        self.leis.extend(other.leis);
+        self.registration_numbers.extend(other.registration_numbers);
     }
 }
 
@@ -615,12 +1163,19 @@ impl TryFrom<schema::ProducerIds> for GatherOrganisationIds {
             }
         }
 
-        Ok(Self { vat_ids, wiki, domains })
+        Ok(Self {
+            vat_ids,
+            wiki,
+            domains,
+            leis: BTreeSet::new(),
+            registration_numbers: BTreeSet::new(),
+        })
     }
 }
 
 /// Represents a set of IDs of an organisation.
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct StoreOrganisationIds {
     /// Organisation ID.
     #[serde(rename = "wiki")]
@@ -633,6 +1188,14 @@ pub struct StoreOrganisationIds {
     /// Web domains.
     #[serde(rename = "domains")]
     pub domains: Vec<StoreDomain>,
+
+    /// Legal Entity Identifiers.
+    #[serde(rename = "leis", default)]
+    pub leis: Vec<String>,
+
+    /// National company registration numbers.
+    #[serde(rename = "registration_numbers", default)]
+    pub registration_numbers: Vec<String>,
 }
 
 #[allow(clippy::ptr_arg)]
@@ -663,6 +1226,9 @@ pub struct GatherOrganisation {
     /// Names of the organisation.
     pub names: BTreeSet<Text>,
 
+    /// Lowercase keywords for the text search.
+    pub keywords: BTreeSet<String>,
+
     /// Descriptions of the organisation.
     pub descriptions: BTreeSet<Text>,
 
@@ -672,8 +1238,29 @@ pub struct GatherOrganisation {
     /// Websites.
     pub websites: BTreeSet<String>,
 
+    /// Profiles on social media.
+    pub social: BTreeSet<SocialLink>,
+
+    /// Generic attributes (e.g. links to sustainability reports).
+    pub attributes: Attributes,
+
+    /// Notable awards.
+    pub recognitions: BTreeSet<Recognition>,
+
+    /// Known controversies.
+    pub controversies: BTreeSet<Controversy>,
+
+    /// Sustainability-related pages on the organisation's websites.
+    pub policy_pages: BTreeSet<PolicyPage>,
+
     /// Known certifications.
     pub certifications: Certifications,
+
+    /// Dated events from the sources, sorted by date.
+    pub timeline: Vec<TimelineEvent>,
+
+    /// DB IDs of parent organisations.
+    pub parent_ids: BTreeSet<GatherOrganisationId>,
 }
 
 impl GatherOrganisation {
@@ -681,17 +1268,39 @@ impl GatherOrganisation {
         let db_key = self.db_key.to_string();
         let ids = self.ids.store();
         let mut names: Vec<_> = self.names.into_iter().collect();
+        let keywords = self.keywords.into_iter().collect();
         let mut descriptions: Vec<_> = self.descriptions.into_iter().collect();
         let mut images: Vec<_> = self.images.into_iter().collect();
         let mut websites: Vec<_> = self.websites.into_iter().collect();
+        let social = self.social.into_iter().collect();
+        let attributes = self.attributes;
+        let recognitions = self.recognitions.into_iter().collect();
+        let controversies = self.controversies.into_iter().rev().collect();
+        let policy_pages = self.policy_pages.into_iter().collect();
         let certifications = self.certifications;
+        let timeline = self.timeline;
 
         names.sort();
         descriptions.sort();
         images.sort();
         websites.sort();
 
-        StoreOrganisation { db_key, ids, names, descriptions, images, websites, certifications }
+        StoreOrganisation {
+            db_key,
+            ids,
+            names,
+            keywords,
+            descriptions,
+            images,
+            websites,
+            social,
+            attributes,
+            recognitions,
+            controversies,
+            policy_pages,
+            certifications,
+            timeline,
+        }
     }
 }
 
@@ -699,15 +1308,24 @@ impl merge::Merge for GatherOrganisation {
     fn merge(&mut self, other: Self) {
         self.ids.merge(other.ids);
         self.names.extend(other.names);
+        self.keywords.extend(other.keywords);
         self.descriptions.extend(other.descriptions);
         self.images.extend(other.images);
         self.websites.extend(other.websites);
+        self.social.extend(other.social);
+        self.attributes.extend(other.attributes);
+        self.recognitions.extend(other.recognitions);
+        self.controversies.extend(other.controversies);
+        self.policy_pages.extend(other.policy_pages);
         self.certifications.merge(other.certifications);
+        self.timeline.extend(other.timeline);
+        self.parent_ids.extend(other.parent_ids);
     }
 }
 
 /// Represents an organisation (e.g. manufacturer, shop).
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct StoreOrganisation {
     /// DB entry ID.
     #[serde(rename = "_key")]
@@ -721,6 +1339,10 @@ pub struct StoreOrganisation {
     #[serde(rename = "names")]
     pub names: Vec<Text>,
 
+    /// Lowercase keywords for the text search.
+    #[serde(rename = "keywords", default)]
+    pub keywords: Vec<String>,
+
     /// Descriptions of the organisation.
     #[serde(rename = "descriptions")]
     pub descriptions: Vec<Text>,
@@ -733,9 +1355,65 @@ pub struct StoreOrganisation {
     #[serde(rename = "websites")]
     pub websites: Vec<String>,
 
+    /// Profiles on social media.
+    #[serde(rename = "social", default)]
+    pub social: Vec<SocialLink>,
+
+    /// Generic attributes (e.g. links to sustainability reports).
+    #[serde(rename = "attributes", default)]
+    pub attributes: Attributes,
+
+    /// Notable awards.
+    #[serde(rename = "recognitions", default)]
+    pub recognitions: Vec<Recognition>,
+
+    /// Known controversies, the latest first.
+    #[serde(rename = "controversies", default)]
+    pub controversies: Vec<Controversy>,
+
+    /// Sustainability-related pages on the organisation's websites, grouped by kind.
+    #[serde(rename = "policy_pages", default)]
+    pub policy_pages: Vec<PolicyPage>,
+
     /// Known certifications.
     #[serde(rename = "certifications")]
     pub certifications: Certifications,
+
+    /// Dated events from the sources, sorted by date.
+    #[serde(rename = "timeline", default)]
+    pub timeline: Vec<TimelineEvent>,
+}
+
+impl StoreOrganisation {
+    /// Returns the entry of the lookup of organisation names, if the organisation has a name.
+    #[must_use]
+    pub fn label(&self) -> Option<StoreOrganisationLabel> {
+        self.names.first().map(|name| StoreOrganisationLabel {
+            db_key: self.db_key.clone(),
+            name: name.text.clone(),
+            wiki: self.ids.wiki.first().cloned(),
+        })
+    }
+}
+
+/// Entry of the lookup of organisation names.
+///
+/// Allows rendering names of organisations referenced from other entries (e.g. manufacturers in
+/// lists of products) without joining the whole organisations.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct StoreOrganisationLabel {
+    /// DB key of the organisation.
+    #[serde(rename = "_key")]
+    pub db_key: StoreOrganisationId,
+
+    /// Preferred name of the organisation.
+    #[serde(rename = "name")]
+    pub name: String,
+
+    /// Wikidata ID of the organisation, if it has one.
+    #[serde(rename = "wiki", default, skip_serializing_if = "Option::is_none")]
+    pub wiki: Option<String>,
 }
 
 fn default_short_string() -> api::ShortString {
@@ -788,7 +1466,7 @@ impl StoreOrganisation {
 }
 
 /// Represents a set of product IDs.
-#[derive(Serialize, Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
+#[derive(Serialize, Debug, Clone, Default, Eq, PartialEq, Ord, PartialOrd)]
 pub struct GatherProductIds {
     /// GTIN of the product.
     #[serde(rename = "eans")]
@@ -861,6 +1539,7 @@ impl TryFrom<schema::ProductIds> for GatherProductIds {
 
 /// Represents a set of product IDs.
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct StoreProductIds {
     /// GTIN of the product.
     #[serde(rename = "eans")]
@@ -887,7 +1566,7 @@ impl StoreProductIds {
 }
 
 /// Represents a product.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct GatherProduct {
     /// DB entry ID.
     pub db_key: GatherProductId,
@@ -898,6 +1577,9 @@ pub struct GatherProduct {
     /// Names of the product.
     pub names: BTreeSet<Text>,
 
+    /// Lowercase keywords for the text search.
+    pub keywords: BTreeSet<String>,
+
     /// Descriptions of the product.
     pub descriptions: BTreeSet<Text>,
 
@@ -922,6 +1604,21 @@ pub struct GatherProduct {
     /// Wikidata IDs older version products.
     pub followed_by: BTreeSet<GatherProductId>,
 
+    /// Public IDs (e.g. `wiki:Q123`) of near-duplicate products merged into this one.
+    pub also_known_as: BTreeSet<String>,
+
+    /// Identifiers of the product in external systems (e.g. online shops).
+    pub external_ids: BTreeMap<ExternalIdKind, BTreeSet<String>>,
+
+    /// ID of the parent product if this product is its variant (e.g. a storage size of a phone).
+    pub variant_of: Option<GatherProductId>,
+
+    /// IDs of the variants of this product.
+    pub variants: BTreeSet<GatherProductId>,
+
+    /// Generic attributes (e.g. mass or repairability).
+    pub attributes: Attributes,
+
     /// The Sustainity score.
     pub sustainity_score: SustainityScore,
 }
@@ -931,6 +1628,7 @@ impl GatherProduct {
         let db_key = self.db_key.to_string();
         let ids = self.ids.store();
         let mut names: Vec<_> = self.names.into_iter().collect();
+        let keywords = self.keywords.into_iter().collect();
         let descriptions = self.descriptions.into_iter().collect();
         let mut images: Vec<_> = self.images.into_iter().collect();
         let mut categories: Vec<_> = self.categories.into_iter().collect();
@@ -941,6 +1639,15 @@ impl GatherProduct {
         let mut follows: Vec<_> = self.follows.into_iter().map(|id| id.to_string()).collect();
         let mut followed_by: Vec<_> =
             self.followed_by.into_iter().map(|id| id.to_string()).collect();
+        let mut also_known_as: Vec<_> = self.also_known_as.into_iter().collect();
+        let external_ids = self
+            .external_ids
+            .into_iter()
+            .map(|(kind, ids)| (kind, ids.into_iter().collect()))
+            .collect();
+        let variant_of = self.variant_of.map(|id| id.to_string());
+        let variants = self.variants.into_iter().map(|id| id.to_string()).collect();
+        let attributes = self.attributes;
         let sustainity_score = self.sustainity_score;
 
         names.sort();
@@ -949,11 +1656,13 @@ impl GatherProduct {
         manufacturer_ids.sort();
         follows.sort();
         followed_by.sort();
+        also_known_as.sort();
 
         StoreProduct {
             db_key,
             ids,
             names,
+            keywords,
             descriptions,
             images,
             categories,
@@ -962,6 +1671,11 @@ impl GatherProduct {
             manufacturer_ids,
             follows,
             followed_by,
+            also_known_as,
+            external_ids,
+            variant_of,
+            variants,
+            attributes,
             sustainity_score,
         }
     }
@@ -971,6 +1685,7 @@ impl merge::Merge for GatherProduct {
     fn merge(&mut self, other: Self) {
         self.ids.merge(other.ids);
         self.names.extend(other.names);
+        self.keywords.extend(other.keywords);
         self.descriptions.extend(other.descriptions);
         self.images.extend(other.images);
         self.categories.extend(other.categories);
@@ -979,11 +1694,21 @@ impl merge::Merge for GatherProduct {
         self.manufacturer_ids.extend(other.manufacturer_ids);
         self.follows.extend(other.follows);
         self.followed_by.extend(other.followed_by);
+        self.also_known_as.extend(other.also_known_as);
+        for (kind, ids) in other.external_ids {
+            self.external_ids.entry(kind).or_default().extend(ids);
+        }
+        if self.variant_of.is_none() {
+            self.variant_of = other.variant_of;
+        }
+        self.variants.extend(other.variants);
+        self.attributes.extend(other.attributes);
     }
 }
 
 /// Represents a product.
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct StoreProduct {
     /// DB entry ID.
     #[serde(rename = "_key")]
@@ -997,6 +1722,10 @@ pub struct StoreProduct {
     #[serde(rename = "names")]
     pub names: Vec<Text>,
 
+    /// Lowercase keywords for the text search.
+    #[serde(rename = "keywords", default)]
+    pub keywords: Vec<String>,
+
     /// Descriptions of the product.
     #[serde(rename = "descriptions")]
     pub descriptions: Vec<Text>,
@@ -1029,6 +1758,27 @@ pub struct StoreProduct {
     #[serde(rename = "followed_by")]
     pub followed_by: Vec<StoreProductId>,
 
+    /// Public IDs (e.g. `wiki:Q123` or `gtin:05900000000001`) of near-duplicate products merged
+    /// into this one.
+    #[serde(rename = "also_known_as", default, skip_serializing_if = "Vec::is_empty")]
+    pub also_known_as: Vec<String>,
+
+    /// Identifiers of the product in external systems (e.g. online shops).
+    #[serde(rename = "external_ids", default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub external_ids: BTreeMap<ExternalIdKind, Vec<String>>,
+
+    /// ID of the parent product if this product is its variant (e.g. a storage size of a phone).
+    #[serde(rename = "variant_of", default, skip_serializing_if = "Option::is_none")]
+    pub variant_of: Option<StoreProductId>,
+
+    /// IDs of the variants of this product.
+    #[serde(rename = "variants", default, skip_serializing_if = "Vec::is_empty")]
+    pub variants: Vec<StoreProductId>,
+
+    /// Generic attributes (e.g. mass or repairability).
+    #[serde(rename = "attributes", default)]
+    pub attributes: Attributes,
+
     /// The Sustainity score.
     #[serde(rename = "sustainity_score")]
     pub sustainity_score: SustainityScore,
@@ -1067,7 +1817,12 @@ impl StoreProduct {
 }
 
 /// One enttry in `PresentationData::Scored`.
+///
+/// Unknown fields are rejected so that entries of `PresentationData::Certified` (which also may
+/// have a score) are not mistaken for scored entries.
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[serde(deny_unknown_fields)]
 pub struct ScoredPresentationEntry<W> {
     /// Organisation ID.
     #[serde(rename = "wiki_id")]
@@ -1096,10 +1851,49 @@ impl StoreScoredPresentationEntry {
     }
 }
 
+/// One entry in `PresentationData::Certified`.
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct CertifiedPresentationEntry<W> {
+    /// Organisation ID.
+    #[serde(rename = "wiki_id")]
+    pub wiki_id: W,
+
+    /// Name of the organisation.
+    #[serde(rename = "name")]
+    pub name: String,
+
+    /// Country of the organisation (two-letter code), if known.
+    #[serde(rename = "country", default)]
+    pub country: Option<String>,
+
+    /// Score from the certifier, if it publishes scores.
+    #[serde(rename = "score", default)]
+    pub score: Option<i64>,
+}
+
+pub type GatherCertifiedPresentationEntry = CertifiedPresentationEntry<ids::WikiId>;
+pub type StoreCertifiedPresentationEntry = CertifiedPresentationEntry<String>;
+
+#[cfg(feature = "into-api")]
+impl StoreCertifiedPresentationEntry {
+    /// The API has no dedicated shape for lists of certified companies yet, so they are
+    /// presented as scored entries (with zero score if the certifier publishes no scores).
+    pub fn into_api(self) -> api::PresentationEntry {
+        api::PresentationEntry {
+            wiki_id: api::Id::from_str(&self.wiki_id).expect("Converting to Wikidata ID"),
+            name: str_to_short_string(self.name),
+            score: self.score.unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 #[serde(untagged)]
 pub enum PresentationData<O> {
     Scored(Vec<ScoredPresentationEntry<O>>),
+    Certified(Vec<CertifiedPresentationEntry<O>>),
 }
 
 pub type GatherPresentationData = PresentationData<ids::WikiId>;
@@ -1112,11 +1906,15 @@ impl StorePresentationData {
             PresentationData::Scored(entries) => {
                 entries.into_iter().map(|e| e.into_api()).collect()
             }
+            PresentationData::Certified(entries) => {
+                entries.into_iter().map(|e| e.into_api()).collect()
+            }
         }
     }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct Presentation<O> {
     /// Topic ID.
     pub id: LibraryTopic,
@@ -1137,10 +1935,104 @@ impl StorePresentation {
 
 /// Represents a topic info.
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct LibraryItem {
     /// Topic ID.
     pub id: LibraryTopic,
 
+    /// Stable, human-readable name of the article used in its URL (e.g. `bcorp`).
+    #[serde(default)]
+    pub slug: String,
+
+    /// Slugs the article was available under before it was renamed.
+    ///
+    /// Requests for these are redirected to the current slug.
+    #[serde(default)]
+    pub former_slugs: Vec<String>,
+
+    /// Article title.
+    pub title: String,
+
+    /// Short one line summary of the article.
+    pub summary: String,
+
+    /// Tags of the article.
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    /// Date of the last review of the article (`YYYY-MM-DD`).
+    #[serde(default)]
+    pub last_reviewed: Option<String>,
+
+    /// Contents of the article in markdown format.
+    pub article: String,
+
+    /// Contents of the article rendered to sanitised HTML.
+    #[serde(default)]
+    pub html: String,
+
+    /// Table of contents of the article.
+    #[serde(default)]
+    pub toc: Vec<LibraryTocEntry>,
+
+    /// Variants of the article in other languages.
+    #[serde(default)]
+    pub translations: Vec<LibraryTranslation>,
+}
+
+impl LibraryItem {
+    /// Language of the base article (the translations are in other languages).
+    pub const BASE_LANGUAGE: &'static str = "en";
+
+    /// Checks if the slug consists only of lowercase ASCII letters, digits and single hyphens.
+    #[must_use]
+    pub fn is_valid_slug(slug: &str) -> bool {
+        !slug.is_empty()
+            && !slug.starts_with('-')
+            && !slug.ends_with('-')
+            && !slug.contains("--")
+            && slug.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    }
+
+    /// Replaces the contents with the translation best matching the preferred languages.
+    ///
+    /// The languages are expected in order of preference as lowercase tags (e.g. `nl-be`). A
+    /// translation to `nl` matches `nl-be` if there is no exact match. The base article matches
+    /// `BASE_LANGUAGE` (also with any region), so e.g. `en` preferred over `nl` keeps it. If no
+    /// translation matches, the original contents are kept.
+    #[must_use]
+    pub fn localize(mut self, languages: &[String]) -> Self {
+        let found = languages.iter().find_map(|language| {
+            let primary = language.split('-').next().unwrap_or(language);
+            if primary == Self::BASE_LANGUAGE {
+                return Some(None);
+            }
+            self.translations
+                .iter()
+                .position(|t| t.language == *language)
+                .or_else(|| self.translations.iter().position(|t| t.language == primary))
+                .map(Some)
+        });
+        if let Some(Some(index)) = found {
+            let translation = self.translations.swap_remove(index);
+            self.title = translation.title;
+            self.summary = translation.summary;
+            self.article = translation.article;
+            self.html = translation.html;
+            self.toc = translation.toc;
+        }
+        self.translations.clear();
+        self
+    }
+}
+
+/// Variant of a library article in a specific language.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct LibraryTranslation {
+    /// Language tag (e.g. `nl` or `pt-br`).
+    pub language: String,
+
     /// Article title.
     pub title: String,
 
@@ -1149,6 +2041,26 @@ pub struct LibraryItem {
 
     /// Contents of the article in markdown format.
     pub article: String,
+
+    /// Contents of the article rendered to sanitised HTML.
+    pub html: String,
+
+    /// Table of contents of the article.
+    pub toc: Vec<LibraryTocEntry>,
+}
+
+/// Entry in a table of contents of a library article.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct LibraryTocEntry {
+    /// Level of the heading (1 to 6).
+    pub level: u8,
+
+    /// Text of the heading.
+    pub title: String,
+
+    /// ID of the heading in the HTML.
+    pub anchor: String,
 }
 
 #[cfg(feature = "into-api")]
@@ -0,0 +1,81 @@
+//! Requests for products missing in the dataset.
+//!
+//! Users who don't find a product can request it to be added. The backend stores the requests,
+//! merging repeated requests for the same GTIN into one entry which counts them. The requests can
+//! be exported with the lab's `requests` command and fed to its matching workbench.
+
+use serde::{Deserialize, Serialize};
+
+/// Request as submitted by a user.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProductRequestSubmission {
+    /// GTIN of the product.
+    #[serde(rename = "gtin", default)]
+    pub gtin: Option<String>,
+
+    /// Name of the product.
+    #[serde(rename = "name", default)]
+    pub name: Option<String>,
+
+    /// Name of the brand or producer.
+    #[serde(rename = "brand", default)]
+    pub brand: Option<String>,
+
+    /// URL of a photo of the product.
+    #[serde(rename = "photo_url", default)]
+    pub photo_url: Option<String>,
+}
+
+/// Request stored by the backend.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ProductRequest {
+    /// DB entry key (the canonical GTIN if the GTIN is known).
+    #[serde(rename = "_key", default, skip_serializing_if = "Option::is_none")]
+    pub db_key: Option<String>,
+
+    /// The submitted request (merged from all submissions for the same GTIN).
+    #[serde(flatten)]
+    pub submission: ProductRequestSubmission,
+
+    /// Number of times the product was requested.
+    #[serde(rename = "count")]
+    pub count: usize,
+
+    /// Time of the first submission (RFC 3339).
+    #[serde(rename = "created")]
+    pub created: String,
+
+    /// Time of the last submission (RFC 3339).
+    #[serde(rename = "updated")]
+    pub updated: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serialization() {
+        let request = ProductRequest {
+            db_key: Some("08712345000011".to_owned()),
+            submission: ProductRequestSubmission {
+                gtin: Some("8712345000011".to_owned()),
+                brand: Some("Fairphone".to_owned()),
+                ..ProductRequestSubmission::default()
+            },
+            count: 2,
+            created: "2024-01-01T00:00:00Z".to_owned(),
+            updated: "2024-01-02T00:00:00Z".to_owned(),
+        };
+
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["_key"], "08712345000011");
+        assert_eq!(json["brand"], "Fairphone");
+        assert_eq!(serde_json::from_value::<ProductRequest>(json).unwrap(), request);
+
+        let json = serde_json::json!({"name": "Fairphone 6"});
+        let submission: ProductRequestSubmission = serde_json::from_value(json).unwrap();
+        assert_eq!(submission.name.as_deref(), Some("Fairphone 6"));
+        assert_eq!(submission.gtin, None);
+    }
+}
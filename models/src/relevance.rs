@@ -0,0 +1,128 @@
+//! Relevance of certifications for product categories.
+//!
+//! Not every certification makes sense for every product, e.g. TCO certifies IT equipment, so it's
+//! meaningless for a food brand. The relevance matrix lists the relevant certifications for
+//! categories. Categories are hierarchical, so the closest configured ancestor of a category is
+//! used, and categories without any configured ancestor accept all certifications.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::{Category, Certifications};
+
+/// Certifications the relevance can be configured for.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub enum Certification {
+    #[serde(rename = "bcorp")]
+    BCorp,
+
+    #[serde(rename = "eu_ecolabel")]
+    EuEcolabel,
+
+    #[serde(rename = "fti")]
+    Fti,
+
+    #[serde(rename = "tco")]
+    Tco,
+}
+
+/// Maps categories to the certifications relevant for them.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct RelevanceMatrix {
+    categories: BTreeMap<String, BTreeSet<Certification>>,
+}
+
+impl RelevanceMatrix {
+    #[must_use]
+    pub fn new(categories: BTreeMap<String, BTreeSet<Certification>>) -> Self {
+        Self { categories }
+    }
+
+    /// Reconstructs the matrix from the categories stored in the dataset.
+    #[must_use]
+    pub fn from_categories(categories: &[Category]) -> Self {
+        Self::new(
+            categories
+                .iter()
+                .filter_map(|category| {
+                    let relevant = category.relevant_certifications.as_ref()?;
+                    Some((category.db_key.clone(), relevant.iter().copied().collect()))
+                })
+                .collect(),
+        )
+    }
+
+    /// Returns the certifications relevant for the category.
+    ///
+    /// Returns `None` if all certifications are relevant.
+    #[must_use]
+    pub fn lookup(&self, category: &str) -> Option<&BTreeSet<Certification>> {
+        let mut path = category;
+        loop {
+            if let Some(relevant) = self.categories.get(path) {
+                return Some(relevant);
+            }
+            path = path.rsplit_once('/')?.0;
+        }
+    }
+
+    /// Returns the certifications relevant for a product in all the passed categories.
+    ///
+    /// A certification is relevant if it's relevant for at least one of the categories. Returns
+    /// `None` if all certifications are relevant.
+    #[must_use]
+    pub fn relevant_for<'a, I>(&self, categories: I) -> Option<BTreeSet<Certification>>
+    where
+        I: IntoIterator<Item = &'a String>,
+    {
+        let mut result = BTreeSet::new();
+        let mut any = false;
+        for category in categories {
+            match self.lookup(category) {
+                Some(relevant) => result.extend(relevant.iter().copied()),
+                None => return None,
+            }
+            any = true;
+        }
+        any.then_some(result)
+    }
+
+    /// Removes certifications irrelevant for a product in the passed categories.
+    pub fn retain_relevant<'a, I>(&self, certifications: &mut Certifications, categories: I)
+    where
+        I: IntoIterator<Item = &'a String>,
+    {
+        if let Some(relevant) = self.relevant_for(categories) {
+            certifications.retain(&relevant);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup() {
+        let matrix = RelevanceMatrix::new(BTreeMap::from([
+            ("food".to_owned(), BTreeSet::from([Certification::BCorp])),
+            ("electronics".to_owned(), BTreeSet::from([Certification::Tco])),
+        ]));
+        let relevant = matrix.lookup("food/dairy").unwrap();
+        assert!(relevant.contains(&Certification::BCorp));
+        assert!(!relevant.contains(&Certification::Tco));
+        assert_eq!(matrix.lookup("toy"), None);
+
+        let categories = vec!["food/dairy".to_owned(), "electronics".to_owned()];
+        assert_eq!(
+            matrix.relevant_for(&categories),
+            Some(BTreeSet::from([Certification::BCorp, Certification::Tco]))
+        );
+        let categories = vec!["food".to_owned(), "toy".to_owned()];
+        assert_eq!(matrix.relevant_for(&categories), None);
+        assert_eq!(matrix.relevant_for(&Vec::new()), None);
+    }
+}
@@ -0,0 +1,113 @@
+//! Links to social media profiles.
+//!
+//! The substrate files have no dedicated field for social media profiles, so they are passed
+//! between the processing steps as URLs among the websites and recognised again by `from_url`.
+
+use serde::{Deserialize, Serialize};
+
+/// Supported social media platforms.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub enum Platform {
+    /// X (formerly Twitter).
+    #[serde(rename = "x")]
+    X,
+
+    /// Facebook.
+    #[serde(rename = "facebook")]
+    Facebook,
+
+    /// LinkedIn.
+    #[serde(rename = "linkedin")]
+    LinkedIn,
+}
+
+impl Platform {
+    /// Prefix of the profile URLs (followed by the handle).
+    fn url_prefix(self) -> &'static str {
+        match self {
+            Self::X => "https://x.com/",
+            Self::Facebook => "https://www.facebook.com/",
+            Self::LinkedIn => "https://www.linkedin.com/company/",
+        }
+    }
+
+    /// Recognises the platform from the host and path of an URL.
+    ///
+    /// Returns the platform and the part of the path holding the handle.
+    fn recognise<'a>(host: &str, path: &'a str) -> Option<(Self, &'a str)> {
+        match host {
+            "x.com" | "twitter.com" => Some((Self::X, path)),
+            "facebook.com" => Some((Self::Facebook, path)),
+            "linkedin.com" => path.strip_prefix("company/").map(|path| (Self::LinkedIn, path)),
+            _ => None,
+        }
+    }
+}
+
+/// Link to a profile on a social media platform.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct SocialLink {
+    /// The platform.
+    #[serde(rename = "platform")]
+    pub platform: Platform,
+
+    /// Username or ID of the profile on the platform.
+    #[serde(rename = "handle")]
+    pub handle: String,
+}
+
+impl SocialLink {
+    #[must_use]
+    pub fn new(platform: Platform, handle: &str) -> Self {
+        Self { platform, handle: handle.trim().trim_start_matches('@').to_owned() }
+    }
+
+    /// Returns URL of the profile.
+    #[must_use]
+    pub fn url(&self) -> String {
+        format!("{}{}", self.platform.url_prefix(), self.handle)
+    }
+
+    /// Recognises a link to a social media profile.
+    ///
+    /// Returns `None` if the URL doesn't point to a profile on a supported platform.
+    #[must_use]
+    pub fn from_url(url: &str) -> Option<Self> {
+        let rest = url.strip_prefix("https://").or_else(|| url.strip_prefix("http://"))?;
+        let (host, path) = rest.split_once('/')?;
+        let host = host.strip_prefix("www.").unwrap_or(host);
+        let (platform, path) = Platform::recognise(host, path)?;
+        let handle = path.split(['/', '?', '#']).next().unwrap_or_default();
+        if handle.is_empty() {
+            None
+        } else {
+            Some(Self::new(platform, handle))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn urls() {
+        let link = SocialLink::new(Platform::X, "@sustainity");
+        assert_eq!(link.handle, "sustainity");
+        assert_eq!(link.url(), "https://x.com/sustainity");
+        assert_eq!(SocialLink::from_url(&link.url()), Some(link.clone()));
+        assert_eq!(SocialLink::from_url("http://twitter.com/sustainity/"), Some(link));
+
+        let link = SocialLink::new(Platform::LinkedIn, "sustainity");
+        assert_eq!(SocialLink::from_url(&link.url()), Some(link));
+        assert_eq!(SocialLink::from_url("https://www.linkedin.com/in/someone"), None);
+
+        let link = SocialLink::new(Platform::Facebook, "sustainity");
+        assert_eq!(SocialLink::from_url("https://facebook.com/sustainity?ref=x"), Some(link));
+
+        assert_eq!(SocialLink::from_url("https://sustainity.org/about"), None);
+        assert_eq!(SocialLink::from_url("https://x.com/"), None);
+    }
+}
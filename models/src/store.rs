@@ -1,9 +1,17 @@
+pub use crate::attributes::{AttributeValue, Attributes};
+pub use crate::ids::{EntityId, SusId};
 pub use crate::models::{
-    BCorpCert, Certifications, Edge, EuEcolabelCert, FtiCert, IdEntry, Image, LibraryItem,
-    LibraryTopic, Regions, Source, StoreGtin as Gtin, StoreOrganisation as Organisation,
-    StoreOrganisationId as OrganisationId, StoreOrganisationIds as OrganisationIds,
+    BCorpCert, Category, CategoryStats, CertificationInfo, Certifications, Controversy,
+    ControversySeverity, Edge, EuEcolabelCert, ExternalIdKind, FtiCert, GraphEdge, IdEntry,
+    Identifier, IdentifierKind, Image, LibraryItem, LibraryTocEntry, LibraryTopic,
+    LibraryTranslation, PolicyPage, PolicyPageKind, ProductAlternatives, Recognition, Regions,
+    Relation, Source, StoreCertifiedPresentationEntry as CertifiedPresentationEntry,
+    StoreGtin as Gtin, StoreOrganisation as Organisation, StoreOrganisationId as OrganisationId,
+    StoreOrganisationIds as OrganisationIds, StoreOrganisationLabel as OrganisationLabel,
     StorePresentation as Presentation, StorePresentationData as PresentationData,
     StoreProduct as Product, StoreProductId as ProductId, StoreProductIds as ProductIds,
     StoreScoredPresentationEntry as ScoredPresentationEntry, StoreVatId as VatId, SustainityScore,
-    SustainityScoreBranch, TcoCert, Text,
+    SustainityScoreBranch, TcoCert, Text, TimelineEvent, TimelineEventKind, Validity,
 };
+pub use crate::relevance::{Certification as CertificationKind, RelevanceMatrix};
+pub use crate::social::{Platform as SocialPlatform, SocialLink};
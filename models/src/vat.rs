@@ -0,0 +1,301 @@
+//! Normalisation and validation of VAT numbers.
+//!
+//! VAT numbers are written in many ways: with or without the country prefix, with spaces, dots or
+//! dashes separating groups of characters, in lower or upper case. The canonical form used through
+//! the whole project is upper-case, alphanumeric only and prefixed with the country code (`EL` for
+//! Greece, as used by the VIES system).
+//!
+//! The formats follow the VIES specification for the EU member states, plus the United Kingdom,
+//! Northern Ireland, Switzerland and Norway. Check digits are verified only for the countries with
+//! a publicly documented algorithm.
+
+/// Brings the VAT number to its canonical form without guessing the country.
+#[must_use]
+pub fn normalize(id: &str) -> String {
+    let id: String =
+        id.chars().filter(char::is_ascii_alphanumeric).map(|c| c.to_ascii_uppercase()).collect();
+    match id.strip_prefix("GR") {
+        Some(rest) => format!("EL{rest}"),
+        None => id,
+    }
+}
+
+/// Brings the VAT number to its canonical form, attaching the country prefix if it is missing.
+///
+/// `country` is the ISO 3166-1 alpha-2 code of the country the company is registered in.
+#[must_use]
+pub fn normalize_with_country(id: &str, country: &str) -> String {
+    let id = normalize(id);
+    let prefix = country_prefix(country);
+    if prefix.is_empty() || id.starts_with(&prefix) || has_known_prefix(&id) {
+        id
+    } else {
+        format!("{prefix}{id}")
+    }
+}
+
+/// Returns the VAT prefix of the country.
+fn country_prefix(country: &str) -> String {
+    let country = country.trim().to_ascii_uppercase();
+    if country == "GR" {
+        "EL".to_owned()
+    } else {
+        country
+    }
+}
+
+/// Returns the ISO 3166-1 alpha-2 code of the country the canonical VAT number was issued in.
+///
+/// Returns `None` if the prefix is not a known country.
+#[must_use]
+pub fn country(id: &str) -> Option<String> {
+    if !has_known_prefix(id) {
+        return None;
+    }
+    let country = match id.get(..2)? {
+        "EL" => "GR",
+        "XI" => "GB",
+        prefix => prefix,
+    };
+    Some(country.to_owned())
+}
+
+/// Checks if the number starts with a prefix of a country with a known format.
+fn has_known_prefix(id: &str) -> bool {
+    id.get(..2).is_some_and(|prefix| is_valid_body(prefix, "").is_some())
+}
+
+/// Splits the canonical number into the prefix and the rest.
+fn split(id: &str) -> Option<(&str, &str)> {
+    if id.len() > 2 && id.is_ascii() {
+        Some(id.split_at(2))
+    } else {
+        None
+    }
+}
+
+fn is_digits(s: &str, lengths: std::ops::RangeInclusive<usize>) -> bool {
+    lengths.contains(&s.len()) && s.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn digits(s: &str) -> Vec<u32> {
+    s.chars().filter_map(|c| c.to_digit(10)).collect()
+}
+
+/// Checks the part after the prefix against the format of the country.
+///
+/// Returns `None` if the format of the country is not known.
+#[allow(clippy::too_many_lines)]
+fn is_valid_body(prefix: &str, body: &str) -> Option<bool> {
+    let bytes = body.as_bytes();
+    let valid = match prefix {
+        "AT" => body.len() == 9 && bytes[0] == b'U' && is_digits(&body[1..], 8..=8),
+        "BE" => is_digits(body, 10..=10) && matches!(bytes[0], b'0' | b'1'),
+        "BG" => is_digits(body, 9..=10),
+        "CY" => body.len() == 9 && is_digits(&body[..8], 8..=8) && bytes[8].is_ascii_alphabetic(),
+        "CZ" => is_digits(body, 8..=10),
+        "DE" | "EE" | "EL" | "PT" => is_digits(body, 9..=9),
+        "DK" | "FI" | "HU" | "LU" | "MT" | "SI" => is_digits(body, 8..=8),
+        "ES" => {
+            body.len() == 9
+                && bytes[0].is_ascii_alphanumeric()
+                && is_digits(&body[1..8], 7..=7)
+                && bytes[8].is_ascii_alphanumeric()
+        }
+        "FR" => {
+            body.len() == 11
+                && bytes[..2].iter().all(u8::is_ascii_alphanumeric)
+                && is_digits(&body[2..], 9..=9)
+        }
+        "HR" | "IT" | "LV" => is_digits(body, 11..=11),
+        "IE" => {
+            (8..=9).contains(&body.len())
+                && bytes[0].is_ascii_digit()
+                && is_digits(&body[2..7], 5..=5)
+                && bytes[7].is_ascii_alphabetic()
+        }
+        "LT" => is_digits(body, 9..=9) || is_digits(body, 12..=12),
+        "NL" => {
+            body.len() == 12
+                && is_digits(&body[..9], 9..=9)
+                && bytes[9] == b'B'
+                && is_digits(&body[10..], 2..=2)
+        }
+        "PL" | "SK" => is_digits(body, 10..=10),
+        "RO" => is_digits(body, 2..=10),
+        "SE" => is_digits(body, 12..=12),
+        "GB" | "XI" => {
+            is_digits(body, 9..=9)
+                || is_digits(body, 12..=12)
+                || ((body.starts_with("GD") || body.starts_with("HA"))
+                    && is_digits(&body[2..], 3..=3))
+        }
+        "CH" => {
+            let body = ["MWST", "TVA", "IVA"]
+                .iter()
+                .find_map(|suffix| body.strip_suffix(suffix))
+                .unwrap_or(body);
+            body.strip_prefix('E').is_some_and(|rest| is_digits(rest, 9..=9))
+        }
+        "NO" => body.strip_suffix("MVA").is_some_and(|rest| is_digits(rest, 9..=9)),
+        _ => return None,
+    };
+    Some(valid)
+}
+
+/// Sum of the digits multiplied by the weights.
+fn weighted_sum(digits: &[u32], weights: &[u32]) -> u32 {
+    digits.iter().zip(weights).map(|(d, w)| d * w).sum()
+}
+
+/// Luhn algorithm (used by Italy and Sweden).
+fn luhn(digits: &[u32]) -> bool {
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| {
+            if i % 2 == 1 {
+                if d * 2 > 9 {
+                    d * 2 - 9
+                } else {
+                    d * 2
+                }
+            } else {
+                d
+            }
+        })
+        .sum();
+    sum % 10 == 0
+}
+
+/// Verifies the check digits of the part after the prefix, assuming it has a valid format.
+///
+/// Returns `None` if the check digit algorithm of the country is not known.
+fn is_valid_checksum_of(prefix: &str, body: &str) -> Option<bool> {
+    let d = digits(body);
+    let valid = match prefix {
+        "BE" => {
+            let number: u32 = body[..8].parse().ok()?;
+            let check: u32 = body[8..].parse().ok()?;
+            97 - number % 97 == check
+        }
+        "DE" => {
+            // ISO 7064, MOD 11,10
+            let mut product = 10;
+            for digit in &d[..8] {
+                let mut sum = (digit + product) % 10;
+                if sum == 0 {
+                    sum = 10;
+                }
+                product = (2 * sum) % 11;
+            }
+            let check = (11 - product) % 10;
+            check == d[8]
+        }
+        "DK" => weighted_sum(&d, &[2, 7, 6, 5, 4, 3, 2, 1]) % 11 == 0,
+        "EL" => {
+            let sum = weighted_sum(&d[..8], &[256, 128, 64, 32, 16, 8, 4, 2]);
+            sum % 11 % 10 == d[8]
+        }
+        "FI" => {
+            let remainder = weighted_sum(&d[..7], &[7, 9, 10, 5, 8, 4, 2]) % 11;
+            match remainder {
+                0 => d[7] == 0,
+                1 => false,
+                _ => 11 - remainder == d[7],
+            }
+        }
+        "FR" => {
+            let key: u64 = body[..2].parse().ok()?;
+            let siren: u64 = body[2..].parse().ok()?;
+            (12 + 3 * (siren % 97)) % 97 == key
+        }
+        "IT" => luhn(&d),
+        "LU" => {
+            let number: u32 = body[..6].parse().ok()?;
+            let check: u32 = body[6..].parse().ok()?;
+            number % 89 == check
+        }
+        "PL" => {
+            let sum = weighted_sum(&d[..9], &[6, 5, 7, 2, 3, 4, 5, 6, 7]);
+            sum % 11 == d[9]
+        }
+        "PT" => {
+            let check = 11 - weighted_sum(&d[..8], &[9, 8, 7, 6, 5, 4, 3, 2]) % 11;
+            (if check >= 10 { 0 } else { check }) == d[8]
+        }
+        "SE" => luhn(&d[..10]) && body.ends_with("01"),
+        _ => return None,
+    };
+    Some(valid)
+}
+
+/// Checks if the canonical VAT number has a valid format.
+///
+/// Returns `None` if the country is not known.
+#[must_use]
+pub fn is_valid_format(id: &str) -> Option<bool> {
+    let (prefix, body) = split(id)?;
+    is_valid_body(prefix, body)
+}
+
+/// Verifies the check digits of the canonical VAT number.
+///
+/// Returns `None` if the country is not known or does not define a check digit algorithm. Numbers
+/// with an invalid format are reported as invalid.
+#[must_use]
+pub fn is_valid_checksum(id: &str) -> Option<bool> {
+    let (prefix, body) = split(id)?;
+    if !is_valid_body(prefix, body)? {
+        return Some(false);
+    }
+    is_valid_checksum_of(prefix, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalization() {
+        assert_eq!(normalize("de 123.456.789"), "DE123456789");
+        assert_eq!(normalize("GR-094014201"), "EL094014201");
+        assert_eq!(normalize_with_country("123 456 789", "de"), "DE123456789");
+        assert_eq!(normalize_with_country("094014201", "GR"), "EL094014201");
+        assert_eq!(normalize_with_country("DE123456789", "AT"), "DE123456789");
+        assert_eq!(normalize_with_country("NL123456789B01", "NL"), "NL123456789B01");
+    }
+
+    #[test]
+    fn countries() {
+        assert_eq!(country("DE123456789"), Some("DE".to_owned()));
+        assert_eq!(country("EL094014201"), Some("GR".to_owned()));
+        assert_eq!(country("XI123456789"), Some("GB".to_owned()));
+        assert_eq!(country("CHE123456789MWST"), Some("CH".to_owned()));
+        assert_eq!(country("ZZ123"), None);
+    }
+
+    #[test]
+    fn formats() {
+        assert_eq!(is_valid_format("ATU12345678"), Some(true));
+        assert_eq!(is_valid_format("AT12345678"), Some(false));
+        assert_eq!(is_valid_format("NL123456789B01"), Some(true));
+        assert_eq!(is_valid_format("CHE123456789MWST"), Some(true));
+        assert_eq!(is_valid_format("ZZ123"), None);
+        assert_eq!(is_valid_format("D"), None);
+    }
+
+    #[test]
+    fn checksums() {
+        assert_eq!(is_valid_checksum("DE136695976"), Some(true));
+        assert_eq!(is_valid_checksum("DE136695977"), Some(false));
+        assert_eq!(is_valid_checksum("BE0403170701"), Some(true));
+        assert_eq!(is_valid_checksum("IT00743110157"), Some(true));
+        assert_eq!(is_valid_checksum("FR40303265045"), Some(true));
+        assert_eq!(is_valid_checksum("PL5260250274"), Some(true));
+        assert_eq!(is_valid_checksum("DK13585628"), Some(true));
+        assert_eq!(is_valid_checksum("FI20774740"), Some(true));
+        assert_eq!(is_valid_checksum("ATU12345678"), None);
+    }
+}
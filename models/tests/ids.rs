@@ -46,3 +46,44 @@ fn product_id_to_string() {
 
     assert_eq!(&ProductId::from_value(1234).to_string(), "1234");
 }
+
+#[test]
+fn entity_id_to_string() {
+    use sustainity_models::ids::{EntityId, Gtin, SusId, VatId, WikiId};
+
+    assert_eq!(EntityId::from(WikiId::new(123)).to_string(), "wiki:Q123");
+    assert_eq!(EntityId::from(Gtin::new(123456789)).to_string(), "off:00000123456789");
+    assert_eq!(EntityId::from(SusId::Vat(VatId::new("NL123"))).to_string(), "sus:vat-NL123");
+    assert_eq!(EntityId::from(SusId::Domain("a.com".into())).to_string(), "sus:www-a.com");
+}
+
+#[test]
+fn entity_id_from_string() {
+    use sustainity_models::ids::{EntityId, Gtin, Namespace, ParseIdError, SusId, VatId, WikiId};
+
+    assert_eq!(EntityId::try_from("wiki:Q123"), Ok(EntityId::Wiki(WikiId::new(123))));
+    assert_eq!(EntityId::try_from("wiki:123"), Ok(EntityId::Wiki(WikiId::new(123))));
+    assert_eq!(EntityId::try_from("off:00123456789"), Ok(EntityId::Off(Gtin::new(123456789))));
+    assert_eq!(EntityId::try_from("sus:vat-NL123"), Ok(SusId::Vat(VatId::new("NL123")).into()));
+    assert_eq!(EntityId::try_from("sus:www-a-b.com"), Ok(SusId::Domain("a-b.com".into()).into()));
+    assert_eq!(Namespace::Wiki.parse("Q5"), Ok(EntityId::Wiki(WikiId::new(5))));
+
+    assert_eq!(EntityId::try_from("Q123"), Err(ParseIdError::prefix("Q123".to_string())));
+    assert_eq!(EntityId::try_from("ean:123"), Err(ParseIdError::prefix("ean".to_string())));
+    assert_eq!(EntityId::try_from("sus:"), Err(ParseIdError::prefix(String::new())));
+    assert_eq!(EntityId::try_from("sus:123"), Err(ParseIdError::prefix("123".to_string())));
+    assert_eq!(EntityId::try_from("sus:key-1"), Err(ParseIdError::prefix("key".to_string())));
+    assert_eq!(EntityId::try_from("sus:vat-nl 123"), Err(ParseIdError::format("nl 123".into())));
+    assert_eq!(EntityId::try_from("sus:www-A.com"), Err(ParseIdError::format("A.com".into())));
+}
+
+#[test]
+fn entity_id_serde() {
+    use sustainity_models::ids::{EntityId, WikiId};
+
+    let id = EntityId::Wiki(WikiId::new(42));
+    let json = serde_json::to_string(&id).unwrap();
+    assert_eq!(json, "\"wiki:Q42\"");
+    assert_eq!(serde_json::from_str::<EntityId>(&json).unwrap(), id);
+    assert!(serde_json::from_str::<EntityId>("\"Q42\"").is_err());
+}
@@ -62,12 +62,17 @@ fn regions_merge() {
 
 #[test]
 fn serde_product_defaults() {
-    use sustainity_models::store::{Certifications, Product, ProductIds, Regions, SustainityScore};
+    use std::collections::BTreeMap;
+
+    use sustainity_models::store::{
+        Attributes, Certifications, Product, ProductIds, Regions, SustainityScore,
+    };
 
     let original_product = Product {
         db_key: "12".to_string(),
         ids: ProductIds { eans: vec![], gtins: vec![], wiki: vec![] },
         names: Vec::default(),
+        keywords: Vec::default(),
         descriptions: Vec::default(),
         images: Vec::default(),
         categories: Vec::default(),
@@ -76,6 +81,11 @@ fn serde_product_defaults() {
         manufacturer_ids: Vec::new(),
         follows: Vec::default(),
         followed_by: Vec::default(),
+        also_known_as: Vec::default(),
+        external_ids: BTreeMap::default(),
+        variant_of: None,
+        variants: Vec::default(),
+        attributes: Attributes::default(),
         sustainity_score: SustainityScore::default(),
     };
 
@@ -88,6 +98,7 @@ fn serde_product_defaults() {
             "wiki": []
           },
           "names": [],
+          "keywords": [],
           "descriptions": [],
           "images": [],
           "categories": [],
@@ -103,6 +114,7 @@ fn serde_product_defaults() {
           "manufacturer_ids": [],
           "follows": [],
           "followed_by": [],
+          "attributes": {},
           "sustainity_score": {
             "tree": [],
             "total": 0.0
@@ -117,7 +129,11 @@ fn serde_product_defaults() {
 
 #[test]
 fn serde_product_filled() {
-    use sustainity_models::store::{Certifications, Product, ProductIds, Regions, SustainityScore};
+    use std::collections::BTreeMap;
+
+    use sustainity_models::store::{
+        Attributes, Certifications, ExternalIdKind, Product, ProductIds, Regions, SustainityScore,
+    };
 
     let original_product = Product {
         db_key: "12".to_string(),
@@ -127,6 +143,7 @@ fn serde_product_filled() {
             wiki: vec!["78".to_string()],
         },
         names: Vec::default(),
+        keywords: Vec::default(),
         descriptions: Vec::default(),
         images: Vec::default(),
         categories: Vec::default(),
@@ -135,6 +152,11 @@ fn serde_product_filled() {
         manufacturer_ids: Vec::new(),
         follows: Vec::default(),
         followed_by: Vec::default(),
+        also_known_as: Vec::default(),
+        external_ids: BTreeMap::from([(ExternalIdKind::Asin, vec!["B0C1234567".to_string()])]),
+        variant_of: None,
+        variants: Vec::default(),
+        attributes: Attributes::default(),
         sustainity_score: SustainityScore::default(),
     };
 
@@ -153,6 +175,7 @@ fn serde_product_filled() {
             ]
           },
           "names": [],
+          "keywords": [],
           "descriptions": [],
           "images": [],
           "categories": [],
@@ -172,6 +195,12 @@ fn serde_product_filled() {
           "manufacturer_ids": [],
           "follows": [],
           "followed_by": [],
+          "external_ids": {
+            "asin": [
+              "B0C1234567"
+            ]
+          },
+          "attributes": {},
           "sustainity_score": {
             "tree": [],
             "total": 0.0
@@ -183,3 +212,88 @@ fn serde_product_filled() {
 
     pretty_assertions::assert_eq!(expected_string, received_string);
 }
+
+#[test]
+fn library_item_localization() {
+    use sustainity_models::store::{LibraryItem, LibraryTranslation};
+
+    let translation = |language: &str| LibraryTranslation {
+        language: language.to_owned(),
+        title: format!("title-{language}"),
+        summary: String::new(),
+        article: String::new(),
+        html: String::new(),
+        toc: Vec::new(),
+    };
+    let item = LibraryItem {
+        id: "cert:bcorp".to_owned(),
+        slug: "bcorp".to_owned(),
+        former_slugs: Vec::new(),
+        title: "title".to_owned(),
+        summary: String::new(),
+        tags: Vec::new(),
+        last_reviewed: None,
+        article: String::new(),
+        html: String::new(),
+        toc: Vec::new(),
+        translations: vec![translation("nl"), translation("pt-br")],
+    };
+    let languages = |languages: &[&str]| -> Vec<String> {
+        languages.iter().map(|language| (*language).to_owned()).collect()
+    };
+
+    assert_eq!(item.clone().localize(&languages(&["pt-br", "nl"])).title, "title-pt-br");
+    assert_eq!(item.clone().localize(&languages(&["de", "nl-be"])).title, "title-nl");
+    assert_eq!(item.clone().localize(&languages(&["de"])).title, "title");
+    assert_eq!(item.clone().localize(&languages(&["en", "nl"])).title, "title");
+    assert_eq!(item.clone().localize(&languages(&["en-gb", "nl"])).title, "title");
+    assert_eq!(item.clone().localize(&languages(&["de", "en", "nl"])).title, "title");
+    assert!(item.localize(&[]).translations.is_empty());
+}
+
+#[test]
+fn library_slugs() {
+    use sustainity_models::store::LibraryItem;
+
+    assert!(LibraryItem::is_valid_slug("cert-bcorp"));
+    assert!(!LibraryItem::is_valid_slug("cert:bcorp"));
+    assert!(!LibraryItem::is_valid_slug("cert--bcorp"));
+    assert!(!LibraryItem::is_valid_slug("-bcorp"));
+    assert!(!LibraryItem::is_valid_slug(""));
+}
+
+#[test]
+fn organisation_identifiers() {
+    use std::collections::BTreeSet;
+
+    use sustainity_models::{
+        gather::{Identifier, IdentifierKind, OrganisationIds},
+        ids::VatId,
+    };
+
+    let ids = OrganisationIds {
+        vat_ids: BTreeSet::from([VatId::new("GR 094014201")]),
+        wiki: BTreeSet::new(),
+        domains: BTreeSet::from(["fairphone.com".to_owned()]),
+        leis: BTreeSet::from(["5493001KJTIIGC8Y1R12".to_owned()]),
+        registration_numbers: BTreeSet::new(),
+    };
+    let identifier = |kind, value: &str| Identifier {
+        kind,
+        value: value.to_owned(),
+        organisation: "7".to_owned(),
+    };
+
+    assert_eq!(
+        ids.identifiers("7"),
+        vec![
+            identifier(IdentifierKind::Vat, "EL094014201"),
+            identifier(IdentifierKind::Lei, "5493001KJTIIGC8Y1R12"),
+            identifier(IdentifierKind::Domain, "fairphone.com"),
+        ]
+    );
+    assert_eq!(
+        serde_json::to_string(&identifier(IdentifierKind::RegistrationNumber, "123")).unwrap(),
+        r#"{"kind":"registration_number","value":"123","organisation":"7"}"#
+    );
+}
@@ -0,0 +1,112 @@
+//! Property-based tests of parsing and normalisation of IDs.
+//!
+//! The IDs come from many loosely curated sources, so the parsers are expected to reject any
+//! malformed input with an error instead of panicking, and to accept their own outputs.
+
+use proptest::prelude::*;
+
+use sustainity_models::{
+    gtin,
+    ids::{EntityId, Gtin, ParseIdError, SusId, VatId, WikiId},
+    vat,
+};
+
+/// Appends the check digit to the payload.
+fn with_check_digit(payload: &str) -> String {
+    format!("{payload}{}", gtin::check_digit(payload).unwrap())
+}
+
+proptest! {
+    #[test]
+    fn gtin_never_panics(code in "\\PC*") {
+        let _ = gtin::normalize(&code);
+        let _ = Gtin::try_from(code.as_str());
+    }
+
+    #[test]
+    fn gtin_normalization(payload in "[0-9]{7}|[0-9]{11}|[0-9]{12}|[0-9]{13}") {
+        let code = with_check_digit(&payload);
+        let canonical = gtin::normalize(&code).unwrap();
+        prop_assert_eq!(canonical.len(), gtin::CANONICAL_LENGTH);
+        prop_assert!(canonical.ends_with(&code));
+        prop_assert_eq!(gtin::normalize(&canonical).unwrap(), canonical.clone());
+        prop_assert_eq!(Gtin::try_from(canonical.as_str()).unwrap().to_string(), canonical);
+    }
+
+    #[test]
+    fn gtin_wrong_check_digit(payload in "[0-9]{12}", shift in 1u32..10) {
+        let check = (gtin::check_digit(&payload).unwrap() + shift) % 10;
+        let code = format!("{payload}{check}");
+        prop_assert!(matches!(gtin::normalize(&code), Err(ParseIdError::Checksum { .. })));
+    }
+
+    #[test]
+    fn gtin_canonical_is_numeric(code in "[0-9 .-]{0,20}") {
+        if let Ok(canonical) = gtin::normalize(&code) {
+            prop_assert!(canonical.bytes().all(|b| b.is_ascii_digit()));
+        }
+    }
+
+    #[test]
+    fn vat_never_panics(id in "\\PC*", country in "\\PC{0,3}") {
+        let vat_id = VatId::with_country(&id, &country);
+        let _ = vat_id.validate(true);
+        let _ = vat::is_valid_format(vat_id.as_str());
+        let _ = vat::is_valid_checksum(vat_id.as_str());
+        let _ = VatId::try_from(id.as_str());
+    }
+
+    #[test]
+    fn vat_well_formed_never_panics(prefix in "[A-Z]{2}", body in "[0-9A-Z]{0,16}") {
+        let id = format!("{prefix}{body}");
+        let _ = vat::is_valid_format(&id);
+        let _ = vat::is_valid_checksum(&id);
+    }
+
+    #[test]
+    fn vat_normalization(id in "\\PC*", country in "[A-Za-z]{2}") {
+        let canonical = vat::normalize(&id);
+        prop_assert!(canonical.bytes().all(|b| b.is_ascii_digit() || b.is_ascii_uppercase()));
+        prop_assert_eq!(vat::normalize(&canonical), canonical.clone());
+
+        let with_country = vat::normalize_with_country(&id, &country);
+        prop_assert_eq!(vat::normalize_with_country(&with_country, &country), with_country);
+    }
+
+    #[test]
+    fn wiki_id_round_trip(number in any::<u64>()) {
+        let id = WikiId::new(number);
+        prop_assert_eq!(WikiId::try_from(id.to_canonical_string().as_str()), Ok(id));
+
+        let wikidata_id = sustainity_wikidata::data::Id::try_from(format!("Q{number}").as_str());
+        prop_assert_eq!(wikidata_id.map(WikiId::from).ok(), Some(id));
+    }
+
+    #[test]
+    fn wiki_id_never_panics(id in "\\PC*") {
+        let _ = WikiId::try_from(id.as_str());
+        let _ = sustainity_wikidata::data::Id::try_from(id.as_str());
+    }
+
+    #[test]
+    fn entity_id_never_panics(id in "\\PC*") {
+        let _ = EntityId::try_from(id.as_str());
+    }
+
+    #[test]
+    fn entity_id_round_trip(
+        wiki in any::<u64>(),
+        gtin in 0usize..=99_999_999_999_999,
+        vat in "[A-Z]{2}[0-9A-Z]{2,12}",
+        domain in "[a-z0-9-]{1,20}\\.[a-z]{2,6}",
+    ) {
+        for id in [
+            EntityId::Wiki(WikiId::new(wiki)),
+            EntityId::Off(Gtin::new(gtin)),
+            EntityId::Sus(SusId::Vat(VatId::new(&vat))),
+            EntityId::Sus(SusId::Domain(domain.clone())),
+        ] {
+            prop_assert_eq!(EntityId::try_from(id.to_string().as_str()), Ok(id));
+        }
+    }
+}
@@ -1,6 +1,8 @@
 //! Structures and enums found in this module represents data found in the Wikidata dump file.
 //!
 //! Many of the stuctures and enums are not finished!
+//!
+//! Unknown fields are ignored, so that new fields added upstream don't break parsing of the dumps.
 
 use std::collections::HashMap;
 
@@ -239,7 +241,6 @@ pub struct Redirection {
 
 /// Represents a Wikidata label.
 #[derive(Serialize, Deserialize, Debug, Clone)]
-#[serde(deny_unknown_fields)]
 pub struct Label {
     /// Language of the text.
     pub language: String,
@@ -252,7 +253,6 @@ pub struct Label {
 ///
 /// The ID is a number with "Q" or "P" prefix.
 #[derive(Serialize, Deserialize, Debug, Clone)]
-#[serde(deny_unknown_fields)]
 pub struct EntityIdInfo {
     /// Full ID.
     pub id: StrId,
@@ -264,7 +264,6 @@ pub struct EntityIdInfo {
 
 /// Represents a Wikidata entity ID.
 #[derive(Serialize, Deserialize, Debug, Clone)]
-#[serde(deny_unknown_fields)]
 pub struct EntityIdOptionInfo {
     /// Full ID.
     pub id: Option<String>,
@@ -275,7 +274,7 @@ pub struct EntityIdOptionInfo {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
-#[serde(tag = "entity-type", deny_unknown_fields)]
+#[serde(tag = "entity-type")]
 pub enum EntityIdDataValue {
     #[serde(rename = "item")]
     Item(EntityIdInfo),
@@ -294,7 +293,6 @@ pub enum EntityIdDataValue {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
-#[serde(deny_unknown_fields)]
 pub struct TimeDataValue {
     #[serde(rename = "time")]
     pub time: String,
@@ -316,7 +314,6 @@ pub struct TimeDataValue {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
-#[serde(deny_unknown_fields)]
 pub struct MonolingualTextDataValue {
     #[serde(rename = "text")]
     pub text: String,
@@ -326,7 +323,6 @@ pub struct MonolingualTextDataValue {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
-#[serde(deny_unknown_fields)]
 pub struct GlobeCoordinateDataValue {
     #[serde(rename = "latitude")]
     pub latitude: f64,
@@ -345,7 +341,6 @@ pub struct GlobeCoordinateDataValue {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
-#[serde(deny_unknown_fields)]
 pub struct QuantityDataValue {
     #[serde(rename = "amount")]
     pub amount: String,
@@ -363,7 +358,7 @@ pub struct QuantityDataValue {
 /// `DataValue` holds value and type of the data.
 #[allow(clippy::module_name_repetitions)]
 #[derive(Serialize, Deserialize, Debug, Clone)]
-#[serde(tag = "type", content = "value", deny_unknown_fields)]
+#[serde(tag = "type", content = "value")]
 pub enum DataValue {
     /// String.
     #[serde(rename = "string")]
@@ -387,7 +382,6 @@ pub enum DataValue {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
-#[serde(deny_unknown_fields)]
 pub struct Value {
     #[serde(rename = "hash")]
     pub hash: Option<String>,
@@ -403,7 +397,6 @@ pub struct Value {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
-#[serde(deny_unknown_fields)]
 pub struct SomeValue {
     #[serde(rename = "hash")]
     pub hash: Option<String>,
@@ -416,7 +409,6 @@ pub struct SomeValue {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
-#[serde(deny_unknown_fields)]
 pub struct NoValue {
     #[serde(rename = "hash")]
     pub hash: Option<String>,
@@ -429,7 +421,7 @@ pub struct NoValue {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
-#[serde(tag = "snaktype", deny_unknown_fields)]
+#[serde(tag = "snaktype")]
 pub enum Snak {
     #[serde(rename = "value")]
     Value(Value),
@@ -442,7 +434,6 @@ pub enum Snak {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
-#[serde(deny_unknown_fields)]
 pub enum Rank {
     #[serde(rename = "preferred")]
     Preferred,
@@ -455,7 +446,6 @@ pub enum Rank {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
-#[serde(deny_unknown_fields)]
 pub struct Reference {
     #[serde(rename = "hash")]
     hash: String,
@@ -468,7 +458,6 @@ pub struct Reference {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
-#[serde(deny_unknown_fields)]
 pub struct Statement {
     #[serde(rename = "id")]
     pub id: String,
@@ -490,14 +479,13 @@ pub struct Statement {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
-#[serde(tag = "type", deny_unknown_fields)]
+#[serde(tag = "type")]
 pub enum Claim {
     #[serde(rename = "statement")]
     Statement(Statement),
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
-#[serde(deny_unknown_fields)]
 pub struct Sitelink {
     pub site: String,
     pub title: String,
@@ -506,7 +494,6 @@ pub struct Sitelink {
 
 /// Represents an item ("Q") entry.
 #[derive(Serialize, Deserialize, Debug, Clone)]
-#[serde(deny_unknown_fields)]
 pub struct Item {
     /// Item ID.
     #[serde(deserialize_with = "Id::deserialize_from_string")]
@@ -539,7 +526,6 @@ pub struct Item {
 
 /// Represents a property ("P") entry.
 #[derive(Serialize, Deserialize, Debug, Clone)]
-#[serde(deny_unknown_fields)]
 pub struct Property {
     /// Property ID.
     pub id: String,
@@ -566,7 +552,7 @@ pub struct Property {
 
 /// Represents one entry in Wikidata.
 #[derive(Serialize, Deserialize, Debug, Clone)]
-#[serde(tag = "type", deny_unknown_fields)]
+#[serde(tag = "type")]
 pub enum Entity {
     /// Item ("Q") entry.
     #[serde(rename = "item")]
@@ -52,6 +52,9 @@ pub struct Loader {
 
     /// Compression method to use.
     compression_method: CompressionMethod,
+
+    /// Maximal number of entries to read.
+    limit: Option<usize>,
 }
 
 impl Loader {
@@ -71,7 +74,26 @@ impl Loader {
         let file = std::fs::File::open(path)?;
         let reader = std::io::BufReader::new(file);
 
-        Ok(Self { reader, compression_method })
+        Ok(Self { reader, compression_method, limit: None })
+    }
+
+    /// Limits the number of entries read from the dump file.
+    #[must_use]
+    pub fn with_limit(mut self, limit: Option<usize>) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Sets the size of the buffer for reading the dump file.
+    #[must_use]
+    pub fn with_buffer_size(self, size: usize) -> Self {
+        let reader = std::io::BufReader::with_capacity(size, self.reader.into_inner());
+        Self { reader, ..self }
+    }
+
+    /// Checks if the maximal number of entries was already read.
+    fn is_limit_reached(limit: Option<usize>, entries: usize) -> bool {
+        limit.is_some_and(|limit| entries >= limit)
     }
 
     /// Parses the Wikidata dump file while unzipping it and sends the parsed out entries to the
@@ -102,15 +124,19 @@ impl Loader {
         F: Future<Output = ()>,
     {
         let mut entries: usize = 0;
+        let limit = self.limit;
 
         self.reader.seek(std::io::SeekFrom::End(0))?;
         let file_size = self.reader.stream_position()?;
         self.reader.seek(std::io::SeekFrom::Start(0))?;
 
-        loop {
+        'members: loop {
             let decoder = flate2::bufread::GzDecoder::new(&mut self.reader);
             for line in std::io::BufReader::new(decoder).lines() {
                 entries += Self::handle_line(&mut callback, &line?).await?;
+                if Self::is_limit_reached(limit, entries) {
+                    break 'members;
+                }
             }
 
             if self.reader.stream_position()? == file_size {
@@ -126,10 +152,14 @@ impl Loader {
         F: Future<Output = ()>,
     {
         let mut entries: usize = 0;
+        let limit = self.limit;
 
         let decoder = bzip2::bufread::MultiBzDecoder::new(&mut self.reader);
         for line in std::io::BufReader::new(decoder).lines() {
             entries += Self::handle_line(&mut callback, &line?).await?;
+            if Self::is_limit_reached(limit, entries) {
+                break;
+            }
         }
 
         Ok(entries)
@@ -141,9 +171,13 @@ impl Loader {
         F: Future<Output = ()>,
     {
         let mut entries: usize = 0;
+        let limit = self.limit;
 
         for line in std::io::BufReader::new(&mut self.reader).lines() {
             entries += Self::handle_line(&mut callback, &line?).await?;
+            if Self::is_limit_reached(limit, entries) {
+                break;
+            }
         }
 
         Ok(entries)
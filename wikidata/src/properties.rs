@@ -1,3 +1,6 @@
+/// "Country" property.
+pub const COUNTRY: &str = "P17";
+
 /// "Image" property.
 pub const IMAGE: &str = "P18";
 
@@ -16,9 +19,15 @@ pub const FOLLOWED_BY: &str = "P156";
 /// "Manufacturer" property.
 pub const MANUFACTURER: &str = "P176";
 
+/// "Parent organization" property.
+pub const PARENT_ORGANIZATION: &str = "P749";
+
 /// "Subclass of" property.
 pub const SUBCLASS_OF: &str = "P279";
 
+/// "ISO 3166-1 alpha-2 code" property.
+pub const ISO_3166_1_ALPHA_2: &str = "P297";
+
 /// "Official website" property.
 pub const OFFICIAL_WEBSITE: &str = "P856";
 
@@ -27,3 +36,39 @@ pub const GTIN: &str = "P3962";
 
 /// "EU VAT number" property.
 pub const EU_VAT_NUMBER: &str = "P3608";
+
+/// "X (formerly Twitter) username" property.
+pub const X_USERNAME: &str = "P2002";
+
+/// "Facebook username" property.
+pub const FACEBOOK_USERNAME: &str = "P2013";
+
+/// "LinkedIn company or organization ID" property.
+pub const LINKEDIN_COMPANY_ID: &str = "P4264";
+
+/// "Mass" property.
+pub const MASS: &str = "P2067";
+
+/// "Energy storage capacity" property.
+pub const ENERGY_STORAGE_CAPACITY: &str = "P4140";
+
+/// "Award received" property.
+pub const AWARD_RECEIVED: &str = "P166";
+
+/// "Point in time" property (used as a qualifier, e.g. of received awards).
+pub const POINT_IN_TIME: &str = "P585";
+
+/// "Edition or translation of" property (used also for variants of products).
+pub const EDITION_OR_TRANSLATION_OF: &str = "P629";
+
+/// "Amazon Standard Identification Number" property.
+pub const ASIN: &str = "P5749";
+
+/// "Legal Entity Identifier" property.
+pub const LEI: &str = "P1278";
+
+/// "Has characteristic" property.
+pub const HAS_CHARACTERISTIC: &str = "P1552";
+
+/// "Has part(s)" property.
+pub const HAS_PARTS: &str = "P527";